@@ -1,13 +1,13 @@
+#[cfg(feature = "share")]
+extern crate cc;
+
 use std::env;
 use std::path::Path;
 
 fn main() {
 	let target = env::var_os("TARGET").expect("TARGET is not defined");
-	if target
-		.to_str()
-		.expect("Invalid TARGET value")
-		.ends_with("x86_64-pc-windows-msvc")
-	{
+	let target = target.to_str().expect("Invalid TARGET value");
+	if target.ends_with("x86_64-pc-windows-msvc") {
 		let current_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
 		// Library paths can be set as linking is a downstream op
 		println!(
@@ -18,4 +18,37 @@ fn main() {
 				.expect("Invalid library path")
 		)
 	}
+	#[cfg(feature = "share")]
+	build_share(target);
+}
+
+// The Spout or Syphon shim of src/app/share, against the SDK in SPOUT_DIR or SYPHON_DIR
+#[cfg(feature = "share")]
+fn build_share(target: &str) {
+	println!("cargo:rerun-if-changed=src/app/share");
+	println!("cargo:rerun-if-env-changed=SPOUT_DIR");
+	println!("cargo:rerun-if-env-changed=SYPHON_DIR");
+	if target.contains("windows") {
+		// SpoutLibrary.h, SpoutLibrary.lib and SpoutLibrary.dll from the Spout SDK
+		let spout_dir = env::var("SPOUT_DIR").unwrap_or_else(|_| "lib/spout".to_owned());
+		cc::Build::new()
+			.cpp(true)
+			.include(&spout_dir)
+			.file("src/app/share/spout.cpp")
+			.compile("rust_oids_share");
+		println!("cargo:rustc-link-search=native={}", spout_dir);
+		println!("cargo:rustc-link-lib=dylib=SpoutLibrary");
+	} else if target.contains("apple-darwin") {
+		// where Syphon.framework is
+		let syphon_dir = env::var("SYPHON_DIR").unwrap_or_else(|_| "/Library/Frameworks".to_owned());
+		cc::Build::new()
+			.flag("-fobjc-arc")
+			.flag(&format!("-F{}", syphon_dir))
+			.file("src/app/share/syphon.m")
+			.compile("rust_oids_share");
+		println!("cargo:rustc-link-search=framework={}", syphon_dir);
+		for framework in &["Syphon", "OpenGL", "Foundation"] {
+			println!("cargo:rustc-link-lib=framework={}", framework);
+		}
+	}
 }
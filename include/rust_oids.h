@@ -0,0 +1,65 @@
+/* The C interface to the rust-oids simulation, see src/ffi.rs */
+#ifndef RUST_OIDS_H
+#define RUST_OIDS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define RUST_OIDS_OK 0
+#define RUST_OIDS_INVALID -1
+#define RUST_OIDS_IO -2
+#define RUST_OIDS_PANIC -3
+
+/* the agent types, in the order of AgentType */
+#define RUST_OIDS_MINION 0
+#define RUST_OIDS_SPORE 1
+#define RUST_OIDS_PLAYER 2
+#define RUST_OIDS_FRIENDLY_BULLET 3
+#define RUST_OIDS_ENEMY 4
+#define RUST_OIDS_ENEMY_BULLET 5
+#define RUST_OIDS_RESOURCE 6
+#define RUST_OIDS_PROP 7
+#define RUST_OIDS_PLANT 8
+
+#define RUST_OIDS_EVENT_NEW_MINION 0
+#define RUST_OIDS_EVENT_RANDOM_MINION 1
+#define RUST_OIDS_EVENT_NEW_RESOURCE 2
+#define RUST_OIDS_EVENT_PICK_MINION 3
+
+typedef struct rust_oids_simulation rust_oids_simulation;
+
+typedef struct {
+	uint64_t id;
+	uint32_t agent_type;
+	uint32_t segments;
+	float x;
+	float y;
+	float angle;
+	float vx;
+	float vy;
+	float energy;
+	double age;
+} rust_oids_agent;
+
+rust_oids_simulation *rust_oids_new(const char *resources, const char *snapshot);
+void rust_oids_free(rust_oids_simulation *simulation);
+int rust_oids_step(rust_oids_simulation *simulation, double dt, uint32_t steps);
+uint64_t rust_oids_ticks(const rust_oids_simulation *simulation);
+double rust_oids_seconds(const rust_oids_simulation *simulation);
+size_t rust_oids_agents(const rust_oids_simulation *simulation, uint32_t agent_type, rust_oids_agent *out,
+	size_t capacity);
+int rust_oids_inject(rust_oids_simulation *simulation, uint32_t event, float x, float y);
+int rust_oids_save(const rust_oids_simulation *simulation, const char *path);
+/* width * height RGB pixels, top row first; span <= 0 draws the whole world */
+int rust_oids_render(const rust_oids_simulation *simulation, float x, float y, float span, uint32_t width,
+	uint32_t height, uint8_t *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
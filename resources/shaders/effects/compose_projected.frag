@@ -0,0 +1,46 @@
+#version 150 core
+
+// the tone mapped frame plus the bloom, as seen by each of the outputs side by side
+
+uniform sampler2D t_Source1;
+uniform sampler2D t_Source2;
+
+layout (std140) uniform cb_FragmentArgs {
+	vec4 u_Warp;
+	float u_Outputs;
+	float u_Overlap;
+	float u_BlendGamma;
+	float u_Padding;
+};
+
+in vec2 v_TexCoord;
+out vec4 o_Color;
+
+// the widest angle of the equirectangular warp from the center to an edge, in radians
+const float MAX_ANGLE = 1.2;
+
+void main() {
+	float n = max(u_Outputs, 1.);
+	float i = min(floor(v_TexCoord.x * n), n - 1.);
+	vec2 local = vec2(v_TexCoord.x * n - i, v_TexCoord.y);
+
+	// columns are longitudes and rows latitudes of a view of the plane from above its center
+	vec2 warped = 0.5 + 0.5 * tan((local - 0.5) * 2. * MAX_ANGLE) / tan(MAX_ANGLE);
+	local = mix(local, warped, clamp(u_Warp[int(i)], 0., 1.));
+
+	// neighbouring outputs share a strip of the frame, and fade across it
+	float slice = 1. / (n - (n - 1.) * u_Overlap);
+	vec2 source = vec2((i * (1. - u_Overlap) + local.x) * slice, local.y);
+	float ramp = 1.;
+	if (u_Overlap > 0.) {
+		if (i > 0.) {
+			ramp *= clamp(local.x / u_Overlap, 0., 1.);
+		}
+		if (i < n - 1.) {
+			ramp *= clamp((1. - local.x) / u_Overlap, 0., 1.);
+		}
+	}
+
+	vec4 color = texture(t_Source1, source, 0) + texture(t_Source2, source, 0);
+	o_Color = vec4(color.rgb * pow(ramp, 1. / u_BlendGamma), color.a);
+}
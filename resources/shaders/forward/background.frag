@@ -0,0 +1,119 @@
+// background.frag
+#version 150 core
+
+#define MAX_NUM_TOTAL_LIGHTS 16
+#define MAX_NUM_SHAPES 256
+
+const float PI = 3.1415926535897932384626433832795;
+
+struct Material {
+    vec4 u_Emissive;
+    vec4 u_Effect;
+};
+
+struct Light {
+    vec4 propagation;
+    vec4 center;
+    vec4 color;
+};
+
+layout (std140) uniform cb_FragmentArgs {
+	int u_LightCount;
+};
+
+layout (std140) uniform cb_MaterialArgs {
+    Material material[MAX_NUM_SHAPES];
+};
+
+layout (std140) uniform u_Lights {
+    Light light[MAX_NUM_TOTAL_LIGHTS];
+};
+
+in VertexData {
+	vec4 Position;
+	vec3 Normal; // unused
+	mat3 TBN; // unused
+	vec2 TexCoord;
+	vec3 BaryCoord;
+	flat int PrimIndex;
+}v_In;
+
+out vec4 o_Color;
+
+const int PATTERN_STARFIELD = 0;
+const int PATTERN_GRID = 1;
+const int PATTERN_NEBULA = 2;
+
+const float STAR_THRESHOLD = 0.7;
+const float STAR_SIZE = 0.08;
+const float GRID_WIDTH = 1.5;
+const int NEBULA_OCTAVES = 5;
+const float NEBULA_DRIFT = 0.01;
+
+float hash(vec2 p) {
+	return fract(sin(dot(p, vec2(127.1, 311.7))) * 43758.5453);
+}
+
+float noise(vec2 p) {
+	vec2 i = floor(p);
+	vec2 f = fract(p);
+	vec2 u = f * f * (3 - 2 * f);
+	return mix(mix(hash(i), hash(i + vec2(1, 0)), u.x),
+			mix(hash(i + vec2(0, 1)), hash(i + vec2(1, 1)), u.x), u.y);
+}
+
+float starfield(vec2 p, float phase) {
+	vec2 cell = floor(p);
+	vec2 f = fract(p) - 0.5;
+	float h = hash(cell);
+	vec2 offset = vec2(hash(cell + 17.0), hash(cell + 31.0)) - 0.5;
+	float d = length(f - offset * 0.8);
+	float size = STAR_SIZE * (0.5 + h);
+	float twinkle = 0.75 + 0.25 * sin(phase * (1 + 4 * h) + h * 2 * PI);
+	return step(STAR_THRESHOLD, h) * smoothstep(size, 0, d) * twinkle;
+}
+
+float grid(vec2 p) {
+	// distance to the nearest line, in cell units
+	vec2 f = abs(fract(p + 0.5) - 0.5);
+	vec2 w = fwidth(p) * GRID_WIDTH;
+	vec2 l = vec2(1) - smoothstep(vec2(0), w, f);
+	return max(l.x, l.y);
+}
+
+float nebula(vec2 p, float phase) {
+	float n = 0;
+	float a = 0.5;
+	vec2 q = p + vec2(phase * NEBULA_DRIFT);
+	for (int i = 0; i < NEBULA_OCTAVES; i++) {
+		n += a * noise(q);
+		q *= 2.0;
+		a *= 0.5;
+	}
+	return smoothstep(0.4, 1.0, n);
+}
+
+void main() {
+	vec4 u_Emissive = material[v_In.PrimIndex].u_Emissive;
+	// u_Effect.x: pattern
+	// u_Effect.y: density, cells per world unit
+	// u_Effect.z: layer half extent, world units
+	// u_Effect.w: animation phase
+	vec4 u_Effect = material[v_In.PrimIndex].u_Effect;
+
+	int pattern = int(u_Effect.x + 0.5);
+	vec2 p = (2 * v_In.TexCoord - 1) * u_Effect.z * u_Effect.y;
+
+	float v;
+	if (pattern == PATTERN_STARFIELD) {
+		v = starfield(p, u_Effect.w);
+	} else if (pattern == PATTERN_GRID) {
+		v = grid(p);
+	} else {
+		v = nebula(p, u_Effect.w);
+	}
+
+	vec4 color = u_Emissive * v;
+	o_Color.rgb = color.rgb * color.a;
+	o_Color.a = 0;
+}
@@ -0,0 +1,62 @@
+// shadow.frag
+#version 150 core
+
+#define MAX_NUM_TOTAL_LIGHTS 16
+#define MAX_NUM_SHAPES 256
+
+struct Material {
+    vec4 u_Emissive;
+    vec4 u_Effect;
+};
+
+struct Light {
+    vec4 propagation;
+    vec4 center;
+    vec4 color;
+};
+
+layout (std140) uniform cb_FragmentArgs {
+	int u_LightCount;
+};
+
+layout (std140) uniform cb_MaterialArgs {
+    Material material[MAX_NUM_SHAPES];
+};
+
+layout (std140) uniform u_Lights {
+    Light light[MAX_NUM_TOTAL_LIGHTS];
+};
+
+in VertexData {
+	vec4 Position;
+	vec3 Normal; // unused
+	mat3 TBN; // unused
+	vec2 TexCoord;
+	vec3 BaryCoord;
+	flat int PrimIndex;
+}v_In;
+
+out vec4 o_Color;
+
+// fraction of the half width which is penumbra, near and far end of the shadow
+const float PENUMBRA_NEAR = 0.1;
+const float PENUMBRA_FAR = 1.0;
+
+void main() {
+	vec4 u_Emissive = material[v_In.PrimIndex].u_Emissive;
+	// u_Effect.x: shadow intensity
+	vec4 u_Effect = material[v_In.PrimIndex].u_Effect;
+
+	// x across the shadow, y along the shadow (0 at the caster)
+	float dx = abs(2 * clamp(v_In.TexCoord.x, 0, 1) - 1);
+	float dy = clamp(v_In.TexCoord.y, 0, 1);
+
+	// the penumbra widens with the distance from the caster
+	float penumbra = mix(PENUMBRA_NEAR, PENUMBRA_FAR, dy);
+	float edge = smoothstep(1, 1 - penumbra, dx);
+	float fade = (1 - dy) * (1 - dy);
+	float a = clamp(u_Effect.x * u_Emissive.a * edge * fade, 0, 1);
+
+	// premultiplied: no color, alpha darkens whatever is already there
+	o_Color = vec4(0, 0, 0, a);
+}
@@ -0,0 +1,151 @@
+use app::constants::*;
+use backend::world::gen;
+use core::color;
+use num;
+
+// What the minions are colored by
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+	// the colors their genes give them
+	Livery,
+	// the same color for the same body plan
+	Species,
+	// from empty to full
+	Energy,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+	// any hue for the species, red to green for the energy
+	Standard,
+	// Okabe-Ito for the species and a viridis ramp for the energy, told apart with any color vision
+	Safe,
+	// shades of gray only, for monochrome vision and projectors
+	Mono,
+}
+
+impl ColorMode {
+	pub fn parse(name: &str) -> Option<ColorMode> {
+		match name {
+			"livery" => Some(ColorMode::Livery),
+			"species" => Some(ColorMode::Species),
+			"energy" => Some(ColorMode::Energy),
+			_ => None,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			ColorMode::Livery => "livery",
+			ColorMode::Species => "species",
+			ColorMode::Energy => "energy",
+		}
+	}
+}
+
+const OKABE_ITO: &[[f32; 3]] = &[
+	[0.90, 0.62, 0.00],
+	[0.34, 0.71, 0.91],
+	[0.00, 0.62, 0.45],
+	[0.94, 0.89, 0.26],
+	[0.00, 0.45, 0.70],
+	[0.84, 0.37, 0.00],
+	[0.80, 0.47, 0.65],
+];
+
+const VIRIDIS: &[[f32; 3]] = &[
+	[0.27, 0.00, 0.33],
+	[0.23, 0.32, 0.55],
+	[0.13, 0.57, 0.55],
+	[0.37, 0.79, 0.38],
+	[0.99, 0.91, 0.14],
+];
+
+const MONO_SPECIES: &[f32] = &[0.25, 0.45, 0.65, 0.85, 1.0];
+
+fn ramp(stops: &[[f32; 3]], t: f32) -> [f32; 3] {
+	let x = t.max(0.).min(1.) * (stops.len() - 1) as f32;
+	let i = (x as usize).min(stops.len() - 2);
+	let f = x - i as f32;
+	let (a, b) = (stops[i], stops[i + 1]);
+	[a[0] + (b[0] - a[0]) * f, a[1] + (b[1] - a[1]) * f, a[2] + (b[2] - a[2]) * f]
+}
+
+impl Palette {
+	pub fn parse(name: &str) -> Option<Palette> {
+		match name {
+			"standard" => Some(Palette::Standard),
+			"safe" => Some(Palette::Safe),
+			"mono" => Some(Palette::Mono),
+			_ => None,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Palette::Standard => "standard",
+			Palette::Safe => "safe",
+			Palette::Mono => "mono",
+		}
+	}
+
+	// the hue of the species itself, without the shade of each minion
+	pub fn species(self, dna: &[u8]) -> [f32; 3] {
+		let hash = gen::species_key(dna);
+		match self {
+			Palette::Standard => {
+				let hue = ((hash % SPECIES_HUES) as f32 + 0.5) / SPECIES_HUES as f32;
+				color::equiluminant(hue, SPECIES_LUMA, SPECIES_CHROMA)
+			}
+			Palette::Safe => OKABE_ITO[hash as usize % OKABE_ITO.len()],
+			Palette::Mono => {
+				let l = MONO_SPECIES[hash as usize % MONO_SPECIES.len()];
+				[l, l, l]
+			}
+		}
+	}
+
+	pub fn energy(self, ratio: f32) -> [f32; 3] {
+		match self {
+			Palette::Standard => ramp(&[[1., 0., 0.], [1., 1., 0.], [0., 1., 0.]], ratio),
+			Palette::Safe => ramp(VIRIDIS, ratio),
+			Palette::Mono => ramp(&[[0.1, 0.1, 0.1], [1., 1., 1.]], ratio),
+		}
+	}
+}
+
+// How things look, for more eyes and for projectors
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Accessibility {
+	pub color_mode: ColorMode,
+	pub palette: Palette,
+	// of the HUD, the inspector, the console and the labels
+	pub ui_scale: f32,
+}
+
+impl Default for Accessibility {
+	fn default() -> Self {
+		Accessibility {
+			color_mode: ColorMode::Livery,
+			palette: Palette::Standard,
+			ui_scale: 1.,
+		}
+	}
+}
+
+impl Accessibility {
+	// clamped, the config can be edited by hand
+	pub fn ui_scale(&self) -> f32 { num::clamp(self.ui_scale, UI_SCALE_MIN, UI_SCALE_MAX) }
+
+	// none for the genes' own colors
+	pub fn tint(&self, dna: &[u8], energy_ratio: f32) -> Option<[f32; 3]> {
+		match self.color_mode {
+			ColorMode::Livery => None,
+			ColorMode::Species => Some(self.palette.species(dna)),
+			ColorMode::Energy => Some(self.palette.energy(energy_ratio)),
+		}
+	}
+}
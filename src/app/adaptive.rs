@@ -0,0 +1,134 @@
+use app::constants::*;
+use core::clock::SecondsValue;
+
+// What is given up to keep the frame rate, in this order: the looks first, the brains out of view last
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+	Trails,
+	Particles,
+	Background,
+	Bloom,
+	Shadows,
+	Brains,
+}
+
+const STEPS: &[Step] = &[
+	Step::Trails,
+	Step::Particles,
+	Step::Background,
+	Step::Bloom,
+	Step::Shadows,
+	Step::Brains,
+];
+
+impl Step {
+	pub fn name(self) -> &'static str {
+		match self {
+			Step::Trails => "particle trails",
+			Step::Particles => "half the particles",
+			Step::Background => "background layers",
+			Step::Bloom => "bloom",
+			Step::Shadows => "shadows",
+			Step::Brains => "full rate brains out of view",
+		}
+	}
+}
+
+// The frame rate to hold by lowering the quality, saved in the profile
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AdaptiveQuality {
+	pub enabled: bool,
+	pub target_fps: f32,
+}
+
+impl Default for AdaptiveQuality {
+	fn default() -> Self {
+		AdaptiveQuality {
+			enabled: false,
+			target_fps: ADAPTIVE_TARGET_FPS,
+		}
+	}
+}
+
+// How many steps are given up now, one more after the frame rate stayed under the target for a while,
+// one back after it stayed well over it for longer
+#[derive(Default)]
+pub struct Governor {
+	level: usize,
+	is_low: bool,
+	since: Option<SecondsValue>,
+}
+
+impl Governor {
+	// what was given up or brought back, if anything
+	pub fn update(&mut self, settings: &AdaptiveQuality, fps: f32, now: SecondsValue) -> Option<String> {
+		if !settings.enabled {
+			self.since = None;
+			if self.level > 0 {
+				self.level = 0;
+				return Some("Quality restored".to_owned());
+			}
+			return None;
+		}
+		let is_low = if fps < settings.target_fps {
+			true
+		} else if fps > settings.target_fps * ADAPTIVE_HEADROOM {
+			false
+		} else {
+			self.since = None;
+			return None;
+		};
+		if self.since.is_none() || self.is_low != is_low {
+			self.is_low = is_low;
+			self.since = Some(now);
+		}
+		let held = now - self.since.unwrap_or(now);
+		if is_low && held >= ADAPTIVE_LOWER_DELAY && self.level < STEPS.len() {
+			self.level += 1;
+			self.since = Some(now);
+			Some(format!(
+				"Frame rate {:.0} under {:.0} fps, dropped {}",
+				fps,
+				settings.target_fps,
+				STEPS[self.level - 1].name()
+			))
+		} else if !is_low && held >= ADAPTIVE_RAISE_DELAY && self.level > 0 {
+			self.level -= 1;
+			self.since = Some(now);
+			Some(format!("Frame rate back to {:.0} fps, restored {}", fps, STEPS[self.level].name()))
+		} else {
+			None
+		}
+	}
+
+	pub fn dropped(&self) -> &[Step] { &STEPS[..self.level] }
+
+	fn keeps(&self, step: Step) -> bool { !self.dropped().contains(&step) }
+
+	pub fn has_trails(&self) -> bool { self.keeps(Step::Trails) }
+
+	// every how many particles one is drawn
+	pub fn particle_stride(&self) -> usize {
+		if self.keeps(Step::Particles) {
+			1
+		} else {
+			2
+		}
+	}
+
+	pub fn has_background_layers(&self) -> bool { self.keeps(Step::Background) }
+
+	pub fn has_bloom(&self) -> bool { self.keeps(Step::Bloom) }
+
+	pub fn has_shadows(&self) -> bool { self.keeps(Step::Shadows) }
+
+	// how much of the range where the brains run at the full rate is left
+	pub fn focus_scale(&self) -> f32 {
+		if self.keeps(Step::Brains) {
+			1.
+		} else {
+			ADAPTIVE_FOCUS_SCALE
+		}
+	}
+}
@@ -0,0 +1,170 @@
+use app::constants::*;
+use core::clock::SecondsValue;
+use serde_json;
+use std::fs;
+use std::io;
+use std::path;
+
+// A world saved under a name, to come back to
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Checkpoint {
+	pub name: String,
+	// in the checkpoints folder of its branch
+	pub file: String,
+	pub seconds: SecondsValue,
+}
+
+// Where a branch came from and the checkpoints along it, in its folder
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct BranchInfo {
+	pub parent: Option<String>,
+	// the checkpoint of the parent it continues from
+	pub forked_from: Option<String>,
+	pub checkpoints: Vec<Checkpoint>,
+}
+
+// lowercase letters, digits and dashes, as in before-predator-introduction
+fn slug(name: &str) -> String {
+	let dashed = name
+		.chars()
+		.map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+		.collect::<String>();
+	dashed.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+// The branch that the world saves go to. Continuing from an old checkpoint starts a new branch next to it,
+// so that the timeline it came from stays as it was
+pub struct Timeline {
+	root: path::PathBuf,
+	branch: String,
+}
+
+impl Timeline {
+	// on the branch of the world loaded, if any
+	pub fn new(saved_state_dir: &path::Path, world_file: Option<&path::Path>) -> Self {
+		let mut timeline = Timeline {
+			root: saved_state_dir.to_owned(),
+			branch: MAIN_BRANCH.to_owned(),
+		};
+		if let Some(world_file) = world_file {
+			timeline.follow(world_file);
+		}
+		timeline
+	}
+
+	pub fn branch(&self) -> &str { &self.branch }
+
+	fn dir_of(&self, branch: &str) -> path::PathBuf {
+		if branch == MAIN_BRANCH {
+			self.root.clone()
+		} else {
+			self.root.join(BRANCHES_DIR).join(branch)
+		}
+	}
+
+	pub fn dir(&self) -> path::PathBuf { self.dir_of(&self.branch) }
+
+	// switches to the branch a save or a checkpoint belongs to, the main one if it is elsewhere
+	pub fn follow(&mut self, world_file: &path::Path) {
+		let branches = self.root.join(BRANCHES_DIR);
+		self.branch = world_file
+			.strip_prefix(&branches)
+			.ok()
+			.and_then(|relative| relative.components().next())
+			.and_then(|branch| branch.as_os_str().to_str())
+			.map_or_else(|| MAIN_BRANCH.to_owned(), str::to_owned);
+	}
+
+	pub fn info(&self, branch: &str) -> BranchInfo {
+		fs::File::open(self.dir_of(branch).join(BRANCH_FILE))
+			.ok()
+			.and_then(|in_file| serde_json::from_reader(in_file).ok())
+			.unwrap_or_default()
+	}
+
+	fn save_info(&self, branch: &str, info: &BranchInfo) -> io::Result<()> {
+		let dir = self.dir_of(branch);
+		fs::create_dir_all(&dir)?;
+		let out_file = fs::File::create(dir.join(BRANCH_FILE))?;
+		serde_json::to_writer_pretty(out_file, info)?;
+		Ok(())
+	}
+
+	// the main one first, then the others by name
+	pub fn branches(&self) -> Vec<String> {
+		let mut branches = fs::read_dir(self.root.join(BRANCHES_DIR))
+			.map(|dir| {
+				dir.filter_map(|entry| entry.ok())
+					.filter(|entry| entry.path().is_dir())
+					.filter_map(|entry| entry.file_name().into_string().ok())
+					.collect::<Vec<_>>()
+			}).unwrap_or_default();
+		branches.sort();
+		branches.insert(0, MAIN_BRANCH.to_owned());
+		branches
+	}
+
+	pub fn checkpoint_path(&self, branch: &str, checkpoint: &Checkpoint) -> path::PathBuf {
+		self.dir_of(branch).join(CHECKPOINTS_DIR).join(&checkpoint.file)
+	}
+
+	// where to save a new checkpoint on this branch, replacing one with the same name
+	pub fn new_checkpoint(&self, name: &str, seconds: SecondsValue) -> Result<(Checkpoint, path::PathBuf), String> {
+		let file_stem = slug(name);
+		if file_stem.is_empty() {
+			return Err(format!("Invalid checkpoint name {}", name));
+		}
+		let checkpoint = Checkpoint {
+			name: name.to_owned(),
+			file: format!("{}.json", file_stem),
+			seconds,
+		};
+		let file_name = self.checkpoint_path(&self.branch, &checkpoint);
+		if let Some(dir) = file_name.parent() {
+			fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+		}
+		Ok((checkpoint, file_name))
+	}
+
+	// once the world is saved in it
+	pub fn record_checkpoint(&self, checkpoint: Checkpoint) -> io::Result<()> {
+		let mut info = self.info(&self.branch);
+		info.checkpoints.retain(|existing| existing.file != checkpoint.file);
+		info.checkpoints.push(checkpoint);
+		self.save_info(&self.branch, &info)
+	}
+
+	// on this branch first, then on the others
+	pub fn find_checkpoint(&self, name: &str) -> Option<(String, Checkpoint)> {
+		let mut branches = self.branches();
+		branches.retain(|branch| *branch != self.branch);
+		branches.insert(0, self.branch.clone());
+		let file_stem = slug(name);
+		branches.into_iter().filter_map(|branch| {
+			let info = self.info(&branch);
+			info.checkpoints
+				.into_iter()
+				.find(|checkpoint| checkpoint.name == name || checkpoint.file == format!("{}.json", file_stem))
+				.map(|checkpoint| (branch, checkpoint))
+		}).next()
+	}
+
+	// a new branch continuing from the checkpoint, named after it, and switches to it
+	pub fn fork(&mut self, parent: &str, checkpoint: &Checkpoint) -> io::Result<String> {
+		let base = slug(&checkpoint.name);
+		let existing = self.branches();
+		let branch = (1..)
+			.map(|n| if n == 1 { base.clone() } else { format!("{}-{}", base, n) })
+			.find(|branch| !existing.contains(branch))
+			.unwrap_or(base);
+		let info = BranchInfo {
+			parent: Some(parent.to_owned()),
+			forked_from: Some(checkpoint.name.clone()),
+			checkpoints: Vec::new(),
+		};
+		self.save_info(&branch, &info)?;
+		self.branch = branch.clone();
+		Ok(branch)
+	}
+}
@@ -0,0 +1,62 @@
+use super::preview::Preview;
+use app::constants::*;
+use backend::obj::Id;
+use backend::world;
+use backend::world::gen::{Dna, Genome};
+use rand;
+
+#[derive(Clone, Debug)]
+pub struct Parent {
+	pub id: Id,
+	pub dna: Dna,
+}
+
+// Two parents and a few of the children they could have, to pick from by hand. The child picked is spawned and
+// takes the place of the first parent, for the next round
+#[derive(Clone, Debug)]
+pub struct Workbench {
+	pub parents: [Parent; 2],
+	pub round: usize,
+	pub candidates: Vec<Preview>,
+}
+
+// crossed over and mutated as the spores are, at the mutation rate of the mating rules
+fn candidates(world: &world::World, parents: &[Parent; 2], round: usize) -> Vec<Preview> {
+	let mut rng = rand::thread_rng();
+	let mutation = world.mating().mutation;
+	(0..BREEDING_CANDIDATES)
+		.map(|i| {
+			let child = Genome::copy_from(&parents[0].dna)
+				.crossover(&mut rng, &parents[1].dna)
+				.mutate_by(&mut rng, mutation);
+			Preview::new(world, &format!("{}.{}", round, i + 1), child.dna_cloned())
+		}).collect()
+}
+
+impl Workbench {
+	pub fn new(world: &world::World, parents: [Parent; 2]) -> Workbench {
+		Workbench {
+			candidates: candidates(world, &parents, 1),
+			parents,
+			round: 1,
+		}
+	}
+
+	// other children of the same parents
+	pub fn reroll(&self, world: &world::World) -> Workbench {
+		Workbench {
+			candidates: candidates(world, &self.parents, self.round),
+			..self.clone()
+		}
+	}
+
+	// the child spawned as `id` is the first parent of the next round
+	pub fn next_round(&self, world: &world::World, id: Id, dna: Dna) -> Workbench {
+		let parents = [Parent { id, dna }, self.parents[1].clone()];
+		Workbench {
+			candidates: candidates(world, &parents, self.round + 1),
+			parents,
+			round: self.round + 1,
+		}
+	}
+}
@@ -0,0 +1,101 @@
+use app::capture::PixelRect;
+use conrod::text;
+use conrod::text::rt;
+use image;
+use image::ImageBuffer;
+use image::Pixel;
+
+// What is written into the recorded frames, unlike the HUD it ends up in the video
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CaptionRules {
+	pub enabled: bool,
+	// simulated time, frame, population and speed factor
+	pub stats: bool,
+	pub annotations: bool,
+	// an extra line on top, e.g. the name of the experiment
+	pub title: String,
+	pub font_size: f32,
+}
+
+impl Default for CaptionRules {
+	fn default() -> Self {
+		CaptionRules {
+			enabled: false,
+			stats: true,
+			annotations: true,
+			title: String::new(),
+			font_size: 16.,
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Caption {
+	pub lines: Vec<String>,
+	// in window pixels from the bottom left corner
+	pub labels: Vec<(f32, f32, String)>,
+	pub font_size: f32,
+}
+
+impl Caption {
+	// moves the labels into the frame, with the origin at its top left corner like the image
+	pub fn within(mut self, rect: &PixelRect) -> Caption {
+		for label in &mut self.labels {
+			label.0 -= rect.x as f32;
+			label.1 = (rect.y + rect.h) as f32 - label.1;
+		}
+		self
+	}
+
+	pub fn burn_in(&self, img: &mut ImageBuffer<image::Rgb<u8>, Vec<u8>>, font: &text::Font) {
+		let scale = text::Scale::uniform(self.font_size);
+		let margin = self.font_size * 0.5;
+		for (i, line) in self.lines.iter().enumerate() {
+			draw_text(img, font, line, scale, margin, margin + self.font_size * (i + 1) as f32);
+		}
+		for &(x, y, ref label) in &self.labels {
+			draw_text(img, font, label, scale, x, y);
+		}
+	}
+}
+
+// white on a shadow, so that it reads on any background
+fn draw_text(
+	img: &mut ImageBuffer<image::Rgb<u8>, Vec<u8>>,
+	font: &text::Font,
+	text: &str,
+	scale: text::Scale,
+	x: f32,
+	y: f32,
+)
+{
+	draw_glyphs(img, font, text, scale, x + 1., y + 1., 0);
+	draw_glyphs(img, font, text, scale, x, y, 255);
+}
+
+fn draw_glyphs(
+	img: &mut ImageBuffer<image::Rgb<u8>, Vec<u8>>,
+	font: &text::Font,
+	text: &str,
+	scale: text::Scale,
+	x: f32,
+	y: f32,
+	color: u8,
+)
+{
+	let (w, h) = img.dimensions();
+	for glyph in font.layout(text, scale, rt::point(x, y)) {
+		if let Some(bounds) = glyph.pixel_bounding_box() {
+			glyph.draw(|gx, gy, coverage| {
+				let px = bounds.min.x + gx as i32;
+				let py = bounds.min.y + gy as i32;
+				if px >= 0 && py >= 0 && (px as u32) < w && (py as u32) < h {
+					for channel in img.get_pixel_mut(px as u32, py as u32).channels_mut() {
+						*channel = (f32::from(*channel) * (1. - coverage) + f32::from(color) * coverage) as u8;
+					}
+				}
+			});
+		}
+	}
+}
@@ -1,15 +1,216 @@
+use app::caption::Caption;
 use app::constants::*;
+use app::stream::Stream;
+use app::stream::StreamSettings;
 use chrono::DateTime;
 use chrono::Utc;
+use conrod::text;
 use gl;
 use glutin;
 use glutin::GlContext;
 use image;
+use core::clock::SecondsValue;
+use core::geometry::Rect;
 use image::ImageBuffer;
 use num::Integer;
-use rayon;
+use std::fs;
 use std::fs::create_dir_all;
+use std::io;
+use std::io::Seek;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::mem;
+use std::path::Path;
 use std::path::PathBuf;
+use std::process;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::TryLockError;
+use std::thread;
+use std::time::Duration;
+
+// A rectangle of the window in pixels, from the bottom left corner
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelRect {
+	pub x: u32,
+	pub y: u32,
+	pub w: u32,
+	pub h: u32,
+}
+
+// The part of the window that gets recorded
+#[derive(Clone, Copy, Debug)]
+pub enum CaptureRegion {
+	Window,
+	// a fixed rectangle of the window
+	Screen(PixelRect),
+	// a rectangle of the world, where it is or centered on the camera
+	World { extent: Rect, follow: bool },
+}
+
+impl Default for CaptureRegion {
+	fn default() -> Self { CaptureRegion::Window }
+}
+
+// The mix coming out of the sound system, shared with the audio callback and buffered while recording
+#[derive(Clone)]
+pub struct AudioTrack {
+	sample_hz: u32,
+	channels: u16,
+	samples: Arc<Mutex<Option<Vec<i16>>>>,
+	// dropped by the callback while the buffer was being taken, made up with silence to keep the track in time
+	missed: Arc<AtomicUsize>,
+}
+
+impl AudioTrack {
+	pub fn new(sample_hz: u32, channels: u16) -> Self {
+		AudioTrack {
+			sample_hz,
+			channels,
+			samples: Arc::new(Mutex::new(None)),
+			missed: Arc::new(AtomicUsize::new(0)),
+		}
+	}
+
+	// Called from the audio callback, only buffers in memory so that it never waits on the disk, and never waits on
+	// the lock either: the frames are dropped if the buffer is being taken
+	pub fn record<F: AsRef<[f32]>>(&self, frames: &[F]) {
+		match self.samples.try_lock() {
+			Ok(mut samples) => if let Some(ref mut samples) = *samples {
+				for frame in frames {
+					samples.extend(
+						frame.as_ref().iter().map(|s| (s.max(-1.).min(1.) * f32::from(i16::max_value())) as i16),
+					);
+				}
+			},
+			Err(TryLockError::WouldBlock) => {
+				let len = frames.iter().map(|frame| frame.as_ref().len()).sum();
+				self.missed.fetch_add(len, Ordering::SeqCst);
+			}
+			Err(TryLockError::Poisoned(_)) => {}
+		}
+	}
+
+	fn arm(&self, enabled: bool) {
+		if let Ok(mut samples) = self.samples.lock() {
+			*samples = if enabled { Some(Vec::new()) } else { None };
+			self.missed.store(0, Ordering::SeqCst);
+		}
+	}
+
+	// swaps the buffer for an empty one of the same size rather than copying it, so that the lock is held only briefly
+	// and the callback seldom has to grow the buffer
+	fn take(&self) -> Vec<i16> {
+		let mut taken = match self.samples.lock() {
+			Ok(mut samples) => match *samples {
+				Some(ref mut samples) => mem::replace(samples, Vec::with_capacity(samples.capacity())),
+				None => return Vec::new(),
+			},
+			Err(_) => return Vec::new(),
+		};
+		let missed = self.missed.swap(0, Ordering::SeqCst);
+		if missed > 0 {
+			warn!("The audio track missed {} samples, made up with silence", missed);
+			taken.resize(taken.len() + missed, 0);
+		}
+		taken
+	}
+}
+
+// 16 bit PCM, the sizes in the header are filled in when done
+struct WavWriter {
+	path: PathBuf,
+	file: io::BufWriter<fs::File>,
+	data_len: u32,
+}
+
+fn write_u16<W: Write>(w: &mut W, value: u16) -> io::Result<()> { w.write_all(&[value as u8, (value >> 8) as u8]) }
+
+fn write_u32<W: Write>(w: &mut W, value: u32) -> io::Result<()> {
+	w.write_all(&[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8])
+}
+
+impl WavWriter {
+	fn create(path: PathBuf, sample_hz: u32, channels: u16) -> io::Result<WavWriter> {
+		let mut file = io::BufWriter::new(fs::File::create(&path)?);
+		let block_align = channels * 2;
+		file.write_all(b"RIFF")?;
+		write_u32(&mut file, 36)?;
+		file.write_all(b"WAVEfmt ")?;
+		write_u32(&mut file, 16)?;
+		write_u16(&mut file, 1)?;
+		write_u16(&mut file, channels)?;
+		write_u32(&mut file, sample_hz)?;
+		write_u32(&mut file, sample_hz * u32::from(block_align))?;
+		write_u16(&mut file, block_align)?;
+		write_u16(&mut file, 16)?;
+		file.write_all(b"data")?;
+		write_u32(&mut file, 0)?;
+		Ok(WavWriter { path, file, data_len: 0 })
+	}
+
+	fn write(&mut self, samples: &[i16]) -> io::Result<()> {
+		for &sample in samples {
+			write_u16(&mut self.file, sample as u16)?;
+		}
+		self.data_len += samples.len() as u32 * 2;
+		Ok(())
+	}
+
+	fn finish(self) -> io::Result<PathBuf> {
+		let mut file = self.file.into_inner()?;
+		file.seek(io::SeekFrom::Start(4))?;
+		write_u32(&mut file, 36 + self.data_len)?;
+		file.seek(io::SeekFrom::Start(40))?;
+		write_u32(&mut file, self.data_len)?;
+		Ok(self.path)
+	}
+}
+
+// What goes into the video of the current recording
+struct Recording {
+	first_seq: usize,
+	// file names, with the number of frames each stays on, so that dropped frames don't speed up the video
+	frames: Vec<(String, u32)>,
+	audio: Option<WavWriter>,
+}
+
+// The last frames, in a folder of their own, the oldest deleted as new ones come. Those that made it into a clip are
+// handed over to its muxer, which deletes them once the video is written
+struct Clip {
+	frames: VecDeque<String>,
+	length: usize,
+}
+
+impl Clip {
+	// deletes the frames kept so far
+	fn discard(&mut self, dir: &Path) {
+		for file_name in self.frames.drain(..) {
+			fs::remove_file(dir.join(file_name)).is_ok();
+		}
+	}
+
+	fn push(&mut self, dir: &Path, file_name: String) {
+		self.frames.push_back(file_name);
+		while self.frames.len() > self.length {
+			if let Some(oldest) = self.frames.pop_front() {
+				fs::remove_file(dir.join(oldest)).is_ok();
+			}
+		}
+	}
+}
+
+struct Frame {
+	w: u32,
+	h: u32,
+	pixels: Vec<[u8; 3]>,
+	path: PathBuf,
+	caption: Option<Caption>,
+}
 
 pub struct Capture {
 	seq: usize,
@@ -18,15 +219,54 @@ pub struct Capture {
 	enabled: bool,
 	w: u32,
 	h: u32,
+	// bounded, so that frames are dropped rather than piling up in memory when encoding can't keep up
+	frames: Option<mpsc::SyncSender<Frame>>,
+	// pixel buffers taking turns, one being filled by the GPU while the other is copied out
+	pbos: [gl::types::GLuint; 2],
+	pbo_index: usize,
+	// the frame still being read back, sent to the encoders on the next grab
+	in_flight: Option<(PathBuf, PixelRect, Option<Caption>)>,
+	// frames of a recording all have the size of the first one, even if the region is zoomed
+	frame_size: Option<(u32, u32)>,
+	encoders: Vec<thread::JoinHandle<()>>,
+	// frames sent to the encoders and not yet saved, the video is muxed once they are all written
+	pending: Arc<AtomicUsize>,
+	// in bytes, past it the frames are dropped as when the queue is full, zero for no limit
+	memory_budget: usize,
+	dropped: usize,
+	audio_track: Option<AudioTrack>,
+	recording: Option<Recording>,
+	muxers: Vec<thread::JoinHandle<()>>,
+	caption_font: Option<Arc<text::Font>>,
+	// the live stream gets the same frames as the recording, whether recording or not
+	stream: Option<Stream>,
+	clip: Option<Clip>,
 }
 
 impl Capture {
-	// Initializes capture system
-	pub fn init(window: &glutin::GlWindow) -> Capture {
-		//use gl;
-		gl::ReadPixels::load_with(|s| window.get_proc_address(s) as *const _);
+	// Initializes capture system, the font is used for the captions burnt into the frames
+	pub fn init(window: &glutin::GlWindow, caption_font: Option<text::Font>) -> Capture {
+		gl::load_with(|s| window.get_proc_address(s) as *const _);
 		let (w, h) = window.get_inner_size().unwrap();
+		let mut pbos = [0; 2];
+		unsafe {
+			// rows of RGB pixels are not padded to 4 bytes
+			gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+			gl::GenBuffers(2, pbos.as_mut_ptr());
+		}
+		Self::allocate_pixel_buffers(&pbos, w, h);
 		let now: DateTime<Utc> = Utc::now();
+		let (frames, queue) = mpsc::sync_channel(CAPTURE_QUEUE_LENGTH);
+		let queue = Arc::new(Mutex::new(queue));
+		let pending = Arc::new(AtomicUsize::new(0));
+		let caption_font = caption_font.map(Arc::new);
+		let encoders = (0..CAPTURE_ENCODERS)
+			.map(|_| {
+				let queue = queue.clone();
+				let pending = pending.clone();
+				let caption_font = caption_font.clone();
+				thread::spawn(move || Self::encode(&queue, &pending, caption_font.as_ref().map(|f| &**f)))
+			}).collect();
 		Capture {
 			seq: 0,
 			capture_path: PathBuf::from(CAPTURE_FOLDER).join(now.format(CAPTURE_FOLDER_TIMESTAMP_PATTERN).to_string()),
@@ -34,44 +274,319 @@ impl Capture {
 			enabled: false,
 			w,
 			h,
+			frames: Some(frames),
+			pbos,
+			pbo_index: 0,
+			in_flight: None,
+			frame_size: None,
+			encoders,
+			pending,
+			memory_budget: 0,
+			dropped: 0,
+			audio_track: None,
+			recording: None,
+			muxers: Vec::new(),
+			caption_font,
+			stream: None,
+			clip: None,
 		}
 	}
 
-	// Capture current framebuffer if recording is enabled
-	pub fn screen_grab(&mut self) {
-		if self.enabled {
-			let w = self.w;
-			let h = self.h;
-			let mut buf: Vec<[u8; 3]> = vec![[0u8; 3]; (w * h) as usize];
-			unsafe {
-				gl::ReadPixels(
-					0,
-					0,
-					w as i32,
-					h as i32,
-					gl::RGB,
-					gl::UNSIGNED_BYTE,
-					buf.as_mut_ptr() as *mut _,
+	// Room for a whole window of RGB pixels in each buffer
+	fn allocate_pixel_buffers(pbos: &[gl::types::GLuint], w: u32, h: u32) {
+		unsafe {
+			for &pbo in pbos {
+				gl::BindBuffer(gl::PIXEL_PACK_BUFFER, pbo);
+				gl::BufferData(
+					gl::PIXEL_PACK_BUFFER,
+					(w * h * 3) as gl::types::GLsizeiptr,
+					ptr::null(),
+					gl::STREAM_READ,
 				);
 			}
+			gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+		}
+	}
+
+	// The window changed size: the pixel buffers follow it. Frames that no longer fit can't go on in the same video,
+	// so a recording goes on in a new one and the clip kept so far is let go
+	pub fn resize(&mut self, w: u32, h: u32) {
+		if (w, h) == (self.w, self.h) {
+			return;
+		}
+		// the frame in flight is read back from the buffers as they were
+		self.flush();
+		self.w = w;
+		self.h = h;
+		Self::allocate_pixel_buffers(&self.pbos, w, h);
+		if self.frame_size.map_or(true, |(frame_w, frame_h)| frame_w <= w && frame_h <= h) {
+			return;
+		}
+		self.frame_size = None;
+		let dir = self.clip_dir();
+		if let Some(ref mut clip) = self.clip {
+			clip.discard(&dir);
+		}
+		if self.enabled {
+			self.stop();
+			self.start();
+		}
+	}
+
+	// Remote control, restarts the stream when the url changes
+	pub fn stream_to(&mut self, settings: Option<&StreamSettings>) {
+		let url = settings.map(|settings| settings.url.as_str());
+		if self.stream.as_ref().map(|stream| stream.url()) == url {
+			return;
+		}
+		// joins the worker, which hangs up ffmpeg
+		self.stream = None;
+		if !self.enabled {
+			self.in_flight = None;
+			self.frame_size = None;
+		}
+		self.stream = settings.map(|settings| Stream::start(settings.clone(), self.caption_font.clone()));
+	}
+
+	pub fn streaming(&self) -> bool { self.stream.is_some() }
+
+	// Records the sound along with the frames
+	pub fn record_audio(&mut self, track: AudioTrack) { self.audio_track = Some(track); }
+
+	// Encoder worker, runs until the capture is dropped
+	fn encode(queue: &Mutex<mpsc::Receiver<Frame>>, pending: &AtomicUsize, caption_font: Option<&text::Font>) {
+		loop {
+			// the lock is only held while waiting, so that the other workers can encode meanwhile
+			let frame = match queue.lock() {
+				Ok(receiver) => receiver.recv(),
+				Err(_) => return,
+			};
+			match frame {
+				Ok(Frame {
+					w,
+					h,
+					pixels,
+					path,
+					caption,
+				}) => {
+					let mut img = Self::to_image(w, h, pixels);
+					if let (Some(caption), Some(font)) = (caption, caption_font) {
+						caption.burn_in(&mut img, font);
+					}
+					match img.save(&path) {
+						Ok(_) => info!("Saved image {}", path.to_string_lossy()),
+						Err(_) => error!("Could not save image {}", path.to_string_lossy()),
+					}
+					pending.fetch_sub(1, Ordering::SeqCst);
+				}
+				Err(_) => return,
+			}
+		}
+	}
+
+	// Capture current framebuffer, or the given part of it, if recording is enabled.
+	// A frame late, so that readback overlaps with rendering
+	pub fn screen_grab(&mut self, region: Option<PixelRect>, caption: Option<Caption>) {
+		if self.enabled || self.stream.is_some() || self.clip.is_some() {
 			self.seq += 1;
 			let filename = self.capture_prefix.clone() + &format!("{:08}.png", self.seq);
-			let full_path = self.capture_path.join(filename);
-			rayon::spawn(move || {
-				// throws it into the background
-				let mut img = ImageBuffer::new(w, h);
-				for (idx, rgb) in (0u32..).zip(buf) {
-					let (i, j) = idx.div_mod_floor(&w);
-					img.put_pixel(j, h - i - 1, image::Rgb(rgb));
+			let dir = if self.enabled { self.capture_path.clone() } else { self.clip_dir() };
+			let rect = self.clip(region);
+			let caption = caption.map(|caption| caption.within(&rect));
+			let previous = self.in_flight.replace((dir.join(filename), rect, caption));
+			let pixels = self.read_pixels_async(rect, previous.as_ref().map(|&(_, rect, _)| rect));
+			if let Some((path, rect, caption)) = previous {
+				self.send(pixels, path, rect, caption);
+			}
+			self.write_audio();
+		}
+	}
+
+	fn write_audio(&mut self) {
+		let samples = match self.audio_track {
+			Some(ref track) => track.take(),
+			None => return,
+		};
+		if let Some(ref mut recording) = self.recording {
+			let failed = match recording.audio {
+				Some(ref mut wav) => wav.write(&samples).is_err(),
+				None => false,
+			};
+			if failed {
+				error!("Could not write the audio track, recording video only");
+				recording.audio = None;
+			}
+		}
+	}
+
+	// Fits the region in the window, keeping the size of the first frame
+	fn clip(&mut self, region: Option<PixelRect>) -> PixelRect {
+		let (max_w, max_h) = (self.w, self.h);
+		let region = region.unwrap_or(PixelRect {
+			x: 0,
+			y: 0,
+			w: max_w,
+			h: max_h,
+		});
+		let (w, h) = *self
+			.frame_size
+			.get_or_insert((region.w.min(max_w).max(1), region.h.min(max_h).max(1)));
+		let x = (region.x + region.w / 2).saturating_sub(w / 2).min(max_w - w);
+		let y = (region.y + region.h / 2).saturating_sub(h / 2).min(max_h - h);
+		PixelRect { x, y, w, h }
+	}
+
+	// Collects the frame still in flight
+	fn flush(&mut self) {
+		if let Some((path, rect, caption)) = self.in_flight.take() {
+			let pbo = self.pbos[1 - self.pbo_index];
+			let pixels = self.map_pixels(pbo, rect);
+			self.send(pixels, path, rect, caption);
+		}
+	}
+
+	fn send(&mut self, pixels: Vec<[u8; 3]>, path: PathBuf, rect: PixelRect, caption: Option<Caption>) {
+		if pixels.is_empty() {
+			error!("Could not read back image {}", path.to_string_lossy());
+			return;
+		}
+		if let Some(ref mut stream) = self.stream {
+			stream.push(rect.w, rect.h, pixels.clone(), caption.clone());
+		}
+		if !self.enabled && self.clip.is_none() {
+			return;
+		}
+		let clip_dir = self.clip_dir();
+		let is_clip_frame = path.parent() == Some(clip_dir.as_path());
+		let frame = Frame {
+			w: rect.w,
+			h: rect.h,
+			pixels,
+			path,
+			caption,
+		};
+		let file_name = frame.path.file_name().map(|name| name.to_string_lossy().into_owned());
+		let frame_bytes = frame.pixels.len() * 3;
+		let over_budget =
+			self.memory_budget > 0 && (self.pending.load(Ordering::SeqCst) + 1) * frame_bytes > self.memory_budget;
+		if let Some(ref frames) = self.frames {
+			self.pending.fetch_add(1, Ordering::SeqCst);
+			let sent = if over_budget {
+				Err(mpsc::TrySendError::Full(frame))
+			} else {
+				frames.try_send(frame)
+			};
+			match sent {
+				Ok(_) => if let Some(file_name) = file_name {
+					match (self.clip.as_mut(), self.recording.as_mut()) {
+						(Some(clip), _) if is_clip_frame => clip.push(&clip_dir, file_name),
+						(_, Some(recording)) => recording.frames.push((file_name, 1)),
+						_ => {}
+					}
+				},
+				Err(mpsc::TrySendError::Full(frame)) => {
+					self.pending.fetch_sub(1, Ordering::SeqCst);
+					self.dropped += 1;
+					warn!(
+						"Encoders falling behind, dropped frame {} ({} so far)",
+						frame.path.to_string_lossy(),
+						self.dropped
+					);
+					// the previous frame stays on in its place
+					if let Some(&mut (_, ref mut duration)) = self.recording.as_mut().and_then(|r| r.frames.last_mut()) {
+						*duration += 1;
+					}
 				}
-				match img.save(full_path.clone()) {
-					Ok(_) => println!("Saved image {}", full_path.to_str().unwrap()),
-					Err(_) => println!("Could not save image {}", full_path.to_str().unwrap()),
+				Err(mpsc::TrySendError::Disconnected(_)) => {
+					self.pending.fetch_sub(1, Ordering::SeqCst);
 				}
-			});
+			}
+		}
+	}
+
+	// Starts reading the rectangle into one pixel buffer, and returns what was read into the other one last time
+	fn read_pixels_async(&mut self, rect: PixelRect, previous: Option<PixelRect>) -> Vec<[u8; 3]> {
+		// the buffers are as large as the window, and no larger
+		assert!(rect.x + rect.w <= self.w && rect.y + rect.h <= self.h, "{:?} is out of the window", rect);
+		unsafe {
+			gl::BindBuffer(gl::PIXEL_PACK_BUFFER, self.pbos[self.pbo_index]);
+			gl::ReadPixels(
+				rect.x as i32,
+				rect.y as i32,
+				rect.w as i32,
+				rect.h as i32,
+				gl::RGB,
+				gl::UNSIGNED_BYTE,
+				ptr::null_mut(),
+			);
+			gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+		}
+		self.pbo_index = 1 - self.pbo_index;
+		match previous {
+			Some(previous) => {
+				let pbo = self.pbos[self.pbo_index];
+				self.map_pixels(pbo, previous)
+			}
+			None => Vec::new(),
 		}
 	}
 
+	fn map_pixels(&self, pbo: gl::types::GLuint, rect: PixelRect) -> Vec<[u8; 3]> {
+		let len = (rect.w * rect.h) as usize;
+		unsafe {
+			gl::BindBuffer(gl::PIXEL_PACK_BUFFER, pbo);
+			let src = gl::MapBuffer(gl::PIXEL_PACK_BUFFER, gl::READ_ONLY) as *const [u8; 3];
+			let pixels = if src.is_null() {
+				Vec::new()
+			} else {
+				let pixels = slice::from_raw_parts(src, len).to_vec();
+				gl::UnmapBuffer(gl::PIXEL_PACK_BUFFER);
+				pixels
+			};
+			gl::BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+			pixels
+		}
+	}
+
+	// Saves a small picture of the current framebuffer, waiting for it to be written
+	pub fn thumbnail(&self, path: &Path, width: u32) -> bool {
+		let img = Self::to_image(self.w, self.h, self.read_pixels());
+		let height = (u64::from(width) * u64::from(self.h) / u64::from(self.w.max(1))) as u32;
+		let thumbnail = image::imageops::resize(&img, width, height, image::FilterType::Triangle);
+		match thumbnail.save(path) {
+			Ok(_) => true,
+			Err(_) => {
+				error!("Could not save thumbnail {:?}", path);
+				false
+			}
+		}
+	}
+
+	fn read_pixels(&self) -> Vec<[u8; 3]> {
+		let mut buf: Vec<[u8; 3]> = vec![[0u8; 3]; (self.w * self.h) as usize];
+		unsafe {
+			gl::ReadPixels(
+				0,
+				0,
+				self.w as i32,
+				self.h as i32,
+				gl::RGB,
+				gl::UNSIGNED_BYTE,
+				buf.as_mut_ptr() as *mut _,
+			);
+		}
+		buf
+	}
+
+	pub fn to_image(w: u32, h: u32, buf: Vec<[u8; 3]>) -> ImageBuffer<image::Rgb<u8>, Vec<u8>> {
+		let mut img = ImageBuffer::new(w, h);
+		for (idx, rgb) in (0u32..).zip(buf) {
+			let (i, j) = idx.div_mod_floor(&w);
+			img.put_pixel(j, h - i - 1, image::Rgb(rgb));
+		}
+		img
+	}
+
 	// Remote control, detects state changes
 	pub fn enable(&mut self, enabled: bool) {
 		if enabled != self.enabled {
@@ -82,7 +597,11 @@ impl Capture {
 	// Starts/restarts recording
 	pub fn start(&mut self) {
 		match create_dir_all(self.capture_path.clone()) {
-			Ok(_) => self.enabled = true,
+			Ok(_) => {
+				self.frame_size = None;
+				self.enabled = true;
+				self.start_recording();
+			}
 			Err(msg) => error!(
 				"Could not create capture directory {}: {}",
 				self.capture_path.to_str().unwrap(),
@@ -91,11 +610,168 @@ impl Capture {
 		}
 	}
 
+	fn start_recording(&mut self) {
+		let first_seq = self.seq + 1;
+		let audio = self.audio_track.as_ref().and_then(|track| {
+			track.arm(true);
+			let path = self.capture_path.join(format!("{}{:08}.wav", self.capture_prefix, first_seq));
+			match WavWriter::create(path, track.sample_hz, track.channels) {
+				Ok(wav) => Some(wav),
+				Err(e) => {
+					error!("Could not create the audio track: {}", e);
+					None
+				}
+			}
+		});
+		self.recording = Some(Recording {
+			first_seq,
+			frames: Vec::new(),
+			audio,
+		});
+	}
+
 	// Stops recording and flushes
-	pub fn stop(&mut self) { self.enabled = false; }
+	pub fn stop(&mut self) {
+		if self.enabled {
+			self.flush();
+			self.write_audio();
+			if let Some(ref track) = self.audio_track {
+				track.arm(false);
+			}
+			if let Some(recording) = self.recording.take() {
+				self.finish_recording(recording);
+			}
+		}
+		self.enabled = false;
+	}
+
+	// Writes the frame list for ffmpeg and muxes it with the audio in the background
+	fn finish_recording(&mut self, recording: Recording) {
+		if recording.frames.is_empty() {
+			return;
+		}
+		let name = format!("{}{:08}", self.capture_prefix, recording.first_seq);
+		let list = self.capture_path.join(format!("{}.txt", name));
+		if let Err(e) = Self::write_frame_list(&list, &recording.frames) {
+			error!("Could not write the frame list {}: {}", list.to_string_lossy(), e);
+			return;
+		}
+		let audio = recording.audio.and_then(|wav| match wav.finish() {
+			Ok(path) => Some(path),
+			Err(e) => {
+				error!("Could not finish the audio track: {}", e);
+				None
+			}
+		});
+		let output = self.capture_path.join(format!("{}.mp4", name));
+		let pending = self.pending.clone();
+		self.muxers.push(thread::spawn(move || Self::mux(&list, audio.as_ref(), &output, &pending, &[])));
+	}
+
+	// For ffmpeg, the frames are in the same folder as the list
+	fn write_frame_list(list: &Path, frames: &[(String, u32)]) -> io::Result<()> {
+		let mut f = io::BufWriter::new(fs::File::create(list)?);
+		f.write_fmt(format_args!("ffconcat version 1.0\n"))?;
+		for &(ref file_name, duration) in frames {
+			f.write_fmt(format_args!(
+				"file '{}'\nduration {:.6}\n",
+				file_name,
+				FRAME_TIME_TARGET * f64::from(duration)
+			))?;
+		}
+		f.flush()
+	}
+
+	fn clip_dir(&self) -> PathBuf { self.capture_path.join(CAPTURE_CLIP_DIR) }
+
+	// Keeps the last seconds of frames while not recording, zero to stop and delete those not in a clip
+	pub fn set_clip_seconds(&mut self, seconds: SecondsValue) {
+		let length = (seconds / FRAME_TIME_TARGET).round() as usize;
+		if length == 0 {
+			if let Some(mut clip) = self.clip.take() {
+				clip.discard(&self.clip_dir());
+			}
+		} else if let Some(ref mut clip) = self.clip {
+			clip.length = length;
+		} else {
+			match create_dir_all(self.clip_dir()) {
+				Ok(_) => {
+					self.clip = Some(Clip {
+						frames: VecDeque::new(),
+						length,
+					})
+				}
+				Err(e) => error!("Could not create clip directory {}: {}", self.clip_dir().to_string_lossy(), e),
+			}
+		}
+	}
+
+	// Makes a video of the last seconds, in the background once all their frames are saved. The frames go with it, the
+	// next clip starts from the frames after them
+	pub fn save_clip(&mut self, name: &str) {
+		let dir = self.clip_dir();
+		let frames: Vec<(String, u32)> = match self.clip {
+			Some(ref mut clip) => clip.frames.drain(..).map(|file_name| (file_name, 1)).collect(),
+			None => return,
+		};
+		if frames.is_empty() {
+			return;
+		}
+		let stem: String = name.chars().map(|c| if c.is_alphanumeric() { c } else { '_' }).collect();
+		let list = dir.join(format!("{}.txt", stem));
+		if let Err(e) = Self::write_frame_list(&list, &frames) {
+			error!("Could not write the frame list {}: {}", list.to_string_lossy(), e);
+			return;
+		}
+		let output = self.capture_path.join(format!("{}.mp4", stem));
+		let pending = self.pending.clone();
+		let used: Vec<PathBuf> =
+			frames.iter().map(|&(ref file_name, _)| dir.join(file_name)).chain(Some(list.clone())).collect();
+		self.muxers.push(thread::spawn(move || Self::mux(&list, None, &output, &pending, &used)));
+	}
+
+	// Audio is recorded in real time and frames are simulation steps, they stay in sync while rendering keeps up.
+	// The files used up are deleted once the video is written, and left for another try if it fails
+	fn mux(list: &Path, audio: Option<&PathBuf>, output: &Path, pending: &AtomicUsize, used: &[PathBuf]) {
+		while pending.load(Ordering::SeqCst) > 0 {
+			thread::sleep(Duration::from_millis(CAPTURE_MUX_POLL_MILLIS));
+		}
+		let mut command = process::Command::new("ffmpeg");
+		command.arg("-y").arg("-loglevel").arg("error").arg("-f").arg("concat").arg("-i").arg(list);
+		if let Some(audio) = audio {
+			command.arg("-i").arg(audio).arg("-c:a").arg("aac").arg("-shortest");
+		}
+		command
+			.arg("-c:v")
+			.arg("libx264")
+			.arg("-pix_fmt")
+			.arg("yuv420p")
+			// the encoder wants even sizes
+			.arg("-vf")
+			.arg("scale=trunc(iw/2)*2:trunc(ih/2)*2")
+			.arg(output);
+		match command.status() {
+			Ok(ref status) if status.success() => {
+				info!("Saved video {}", output.to_string_lossy());
+				for path in used {
+					fs::remove_file(path).is_ok();
+				}
+			}
+			Ok(status) => error!("Could not mux video {}: {}", output.to_string_lossy(), status),
+			Err(e) => error!("Could not run ffmpeg, the frames are left in {}: {}", list.to_string_lossy(), e),
+		}
+	}
 
 	pub fn enabled(&self) -> bool { self.enabled }
 
+	pub fn set_memory_budget(&mut self, bytes: usize) { self.memory_budget = bytes; }
+
+	// the frames waiting for the encoders, in bytes
+	pub fn queued_bytes(&self) -> usize {
+		let (w, h) = self.frame_size.unwrap_or((self.w, self.h));
+		self.pending.load(Ordering::SeqCst) * (w * h) as usize * 3
+	}
+
 	pub fn toggle(&mut self) {
 		if self.enabled {
 			self.stop();
@@ -104,3 +780,18 @@ impl Capture {
 		}
 	}
 }
+
+impl Drop for Capture {
+	// flushes the frames still queued
+	fn drop(&mut self) {
+		self.stop();
+		self.set_clip_seconds(0.);
+		self.frames = None;
+		for encoder in self.encoders.drain(..) {
+			encoder.join().is_ok();
+		}
+		for muxer in self.muxers.drain(..) {
+			muxer.join().is_ok();
+		}
+	}
+}
@@ -0,0 +1,108 @@
+use app::constants::*;
+use backend::world::objective::Objective;
+use serde_json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path;
+
+// A game over a scenario, won by meeting all its objectives, in time if it has a limit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Challenge {
+	#[serde(default)]
+	pub title: String,
+	#[serde(default)]
+	pub description: String,
+	// the layout to play on, relative to the pack, an empty arena if none
+	#[serde(default)]
+	pub scenario: Option<path::PathBuf>,
+	pub objectives: Vec<Objective>,
+	#[serde(default)]
+	pub minutes: Option<f32>,
+}
+
+// A challenge of the pack, named after its file, with the best score it was won with
+#[derive(Clone, Debug)]
+pub struct Entry {
+	pub name: String,
+	pub file: path::PathBuf,
+	pub challenge: Challenge,
+	pub best: Option<f32>,
+}
+
+impl Entry {
+	pub fn label(&self) -> String {
+		match self.best {
+			Some(best) => format!("{} (best {:.0})", self.challenge.title, best),
+			None => self.challenge.title.clone(),
+		}
+	}
+
+	pub fn scenario(&self) -> Option<path::PathBuf> {
+		let dir = self.file.parent().unwrap_or_else(|| path::Path::new(""));
+		self.challenge.scenario.as_ref().map(|scenario| dir.join(scenario))
+	}
+}
+
+// The challenge being played, and when it started
+#[derive(Clone, Debug)]
+pub struct Attempt {
+	pub name: String,
+	pub title: String,
+	pub started: f64,
+	pub minutes: Option<f32>,
+}
+
+impl Attempt {
+	pub fn new(entry: &Entry, started: f64) -> Self {
+		Attempt {
+			name: entry.name.clone(),
+			title: entry.challenge.title.clone(),
+			started,
+			minutes: entry.challenge.minutes,
+		}
+	}
+
+	pub fn is_over(&self, now: f64) -> bool {
+		self.minutes.map_or(false, |minutes| now - self.started > f64::from(minutes) * 60.)
+	}
+}
+
+pub fn default_pack(saved_state_dir: &path::Path) -> path::PathBuf { saved_state_dir.join(CHALLENGES_DIR) }
+
+pub fn load(file: &path::Path) -> Result<Challenge, String> {
+	let text = fs::read_to_string(file).map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+	let mut challenge: Challenge = serde_json::from_str(&text).map_err(|e| format!("Invalid {:?}: {}", file, e))?;
+	if challenge.objectives.is_empty() {
+		return Err(format!("No objectives in {:?}", file));
+	}
+	if challenge.title.is_empty() {
+		challenge.title = file.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+	}
+	Ok(challenge)
+}
+
+// every challenge in the pack by name, and what is wrong with the files that are not
+pub fn entries(pack: &path::Path, best: &BTreeMap<String, f32>) -> (Vec<Entry>, Vec<String>) {
+	let mut files = fs::read_dir(pack)
+		.map(|dir| {
+			dir.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+				.filter(|file| file.extension().and_then(|ext| ext.to_str()) == Some("json"))
+				.collect::<Vec<_>>()
+		}).unwrap_or_default();
+	files.sort();
+	let mut entries = Vec::new();
+	let mut problems = Vec::new();
+	for file in files {
+		let name = file.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+		match load(&file) {
+			Ok(challenge) => entries.push(Entry {
+				best: best.get(&name).cloned(),
+				name,
+				file,
+				challenge,
+			}),
+			Err(e) => problems.push(e),
+		}
+	}
+	(entries, problems)
+}
@@ -0,0 +1,288 @@
+use app::config::Config;
+use app::constants::*;
+use backend::world::persist;
+use backend::world::persist::Scenario;
+use backend::world::persist::Serializer;
+use core::geometry::Rect;
+use frontend::render::MAX_OUTPUTS;
+use serde_json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path;
+
+// the line each value starts on, by JSON pointer, for the reports
+struct Lines<'a> {
+	text: &'a [u8],
+	at: usize,
+	line: usize,
+	lines: HashMap<String, usize>,
+}
+
+impl<'a> Lines<'a> {
+	// the text has to be valid JSON already, it isn't checked again
+	fn scan(text: &'a str) -> HashMap<String, usize> {
+		let mut lines = Lines {
+			text: text.as_bytes(),
+			at: 0,
+			line: 1,
+			lines: HashMap::new(),
+		};
+		lines.value(String::new());
+		lines.lines
+	}
+
+	fn peek(&self) -> u8 { self.text.get(self.at).cloned().unwrap_or(0) }
+
+	fn skip_whitespace(&mut self) {
+		while self.at < self.text.len() && (self.peek() as char).is_whitespace() {
+			if self.peek() == b'\n' {
+				self.line += 1;
+			}
+			self.at += 1;
+		}
+	}
+
+	fn string(&mut self) -> String {
+		let start = self.at + 1;
+		self.at = start;
+		while self.at < self.text.len() && self.peek() != b'"' {
+			self.at += if self.peek() == b'\\' { 2 } else { 1 };
+		}
+		self.at += 1;
+		String::from_utf8_lossy(&self.text[start..self.at - 1]).into_owned()
+	}
+
+	fn value(&mut self, pointer: String) {
+		self.skip_whitespace();
+		self.lines.insert(pointer.clone(), self.line);
+		match self.peek() {
+			b'{' => {
+				self.at += 1;
+				loop {
+					self.skip_whitespace();
+					match self.peek() {
+						b'}' | 0 => break,
+						b',' => self.at += 1,
+						_ => {
+							let key = self.string();
+							self.skip_whitespace();
+							self.at += 1;
+							self.value(format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1")));
+						}
+					}
+				}
+				self.at += 1;
+			}
+			b'[' => {
+				self.at += 1;
+				let mut index = 0;
+				loop {
+					self.skip_whitespace();
+					match self.peek() {
+						b']' | 0 => break,
+						b',' => self.at += 1,
+						_ => {
+							self.value(format!("{}/{}", pointer, index));
+							index += 1;
+						}
+					}
+				}
+				self.at += 1;
+			}
+			b'"' => {
+				self.string();
+			}
+			_ => {
+				while self.at < self.text.len() && !b",}] \t\r\n".contains(&self.peek()) {
+					self.at += 1;
+				}
+			}
+		}
+	}
+}
+
+// the keys of the file that don't come back when what was read is written again
+fn unknown_keys(source: &Value, known: &Value, pointer: &str, found: &mut Vec<String>) {
+	match (source, known) {
+		(&Value::Object(ref source), &Value::Object(ref known)) => for (key, value) in source {
+			let child = format!("{}/{}", pointer, key);
+			match known.get(key) {
+				Some(known) => unknown_keys(value, known, &child, found),
+				None => found.push(child),
+			}
+		},
+		(&Value::Array(ref source), &Value::Array(ref known)) => {
+			for (index, (value, known)) in source.iter().zip(known.iter()).enumerate() {
+				unknown_keys(value, known, &format!("{}/{}", pointer, index), found);
+			}
+		}
+		_ => {}
+	}
+}
+
+fn number(value: &Value, pointer: &str) -> Option<f64> { value.pointer(pointer).and_then(Value::as_f64) }
+
+// problems as the messages with the JSON pointer of the value to blame
+fn check_range(value: &Value, pointer: &str, min: f64, max: Option<f64>, problems: &mut Vec<(String, String)>) {
+	if let Some(n) = number(value, pointer) {
+		if n < min || max.map_or(false, |max| n > max) {
+			let range = match max {
+				Some(max) => format!("between {} and {}", min, max),
+				None => format!("at least {}", min),
+			};
+			problems.push((pointer.to_owned(), format!("{} should be {}", n, range)));
+		}
+	}
+}
+
+fn check_config(value: &Value, problems: &mut Vec<(String, String)>) {
+	check_range(value, "/mating/max_distance", 0., Some(1.), problems);
+	check_range(value, "/mating/range", 0., None, problems);
+	check_range(value, "/mating/mutation", 0., None, problems);
+	for term in &["transmission", "drain", "duration"] {
+		check_range(value, &format!("/disease/{}", term), 0., None, problems);
+	}
+	check_range(value, "/physics/regions", 1., None, problems);
+	check_range(value, "/physics/margin", 0., None, problems);
+	check_range(value, "/warnings/min_fps", 0., None, problems);
+	check_range(value, "/osc/rate", 0., None, problems);
+	check_range(value, "/accessibility/ui_scale", f64::from(UI_SCALE_MIN), Some(f64::from(UI_SCALE_MAX)), problems);
+	check_range(value, "/projection/overlap", 0., Some(0.5), problems);
+	check_range(value, "/projection/blend_gamma", 0.1, None, problems);
+	let outputs = value.pointer("/projection/outputs").and_then(Value::as_array).map_or(0, |outputs| outputs.len());
+	if outputs > MAX_OUTPUTS {
+		problems.push((
+			"/projection/outputs".to_owned(),
+			format!("{} outputs, only the first {} are shown", outputs, MAX_OUTPUTS),
+		));
+	}
+	for i in 0..outputs {
+		check_range(value, &format!("/projection/outputs/{}/warp", i), 0., Some(1.), problems);
+	}
+	check_range(value, "/terrain/scale", f64::from(TERRAIN_CELL), None, problems);
+}
+
+fn extent_of(value: &Value, pointer: &str) -> Option<Rect> {
+	let side = |name: &str| number(value, &format!("{}/{}", pointer, name)).map(|n| n as f32);
+	Some(Rect::new(side("left")?, side("bottom")?, side("right")?, side("top")?))
+}
+
+fn check_extent(value: &Value, pointer: &str, problems: &mut Vec<(String, String)>) {
+	if let Some(extent) = extent_of(value, pointer) {
+		if extent.min.x >= extent.max.x || extent.min.y >= extent.max.y {
+			problems.push((pointer.to_owned(), "left and bottom should be below right and top".to_owned()));
+		}
+	}
+}
+
+fn check_scenario(value: &Value, problems: &mut Vec<(String, String)>) {
+	let count = |list: &str| value.pointer(list).and_then(Value::as_array).map_or(0, |items| items.len());
+	let mut walls = Vec::new();
+	for i in 0..count("/obstacles") {
+		let pointer = format!("/obstacles/{}", i);
+		check_extent(value, &pointer, problems);
+		for property in &["friction", "restitution", "sticky", "drag"] {
+			check_range(value, &format!("{}/{}", pointer, property), 0., None, problems);
+		}
+		let is_solid = value.pointer(&format!("{}/solid", pointer)).and_then(Value::as_bool).unwrap_or(false);
+		if let (true, Some(extent)) = (is_solid, extent_of(value, &pointer)) {
+			for &(j, ref other) in &walls {
+				if extent.overlaps(other) {
+					problems.push((pointer.clone(), format!("overlaps obstacle {}", j + 1)));
+				}
+			}
+			walls.push((i, extent));
+		}
+	}
+	for i in 0..count("/portals") {
+		check_range(value, &format!("/portals/{}/radius", i), f64::from(PORTAL_MIN_RADIUS), None, problems);
+	}
+	for i in 0..count("/feeders") {
+		let pointer = format!("/feeders/{}/rate", i);
+		if number(value, &pointer).map_or(false, |rate| rate <= 0.) {
+			problems.push((pointer, "feeders need a rate above zero".to_owned()));
+		}
+	}
+	for i in 0..count("/probes") {
+		check_extent(value, &format!("/probes/{}", i), problems);
+	}
+}
+
+// as loading it would find it, what would be repaired and what would have it refused
+fn check_save(file_path: &path::Path) -> Vec<String> {
+	let name = file_path.display();
+	match Serializer::check(file_path) {
+		Ok(Ok(repairs)) => repairs.iter().map(|repair| format!("{}: repairable: {}", name, repair)).collect(),
+		Ok(Err(problems)) => problems.iter().map(|problem| format!("{}: {}", name, problem)).collect(),
+		Err(e) => vec![format!("{}: {}", name, e)],
+	}
+}
+
+// as for the JSON ones, but only the syntax errors have their line numbers
+fn check_toml_scenario(file_path: &path::Path, text: &str) -> Vec<String> {
+	let name = file_path.display();
+	let source = match persist::toml_to_json(text) {
+		Ok(source) => source,
+		Err(e) => {
+			let line = e.line_col().map_or(1, |(line, _)| line + 1);
+			return vec![format!("{}:{}: {}", name, line, e)];
+		}
+	};
+	let parsed = serde_json::from_value::<Scenario>(source.clone());
+	let known = match parsed.and_then(|scenario| serde_json::to_value(&scenario)) {
+		Ok(known) => known,
+		Err(e) => return vec![format!("{}: {}", name, e)],
+	};
+	let mut problems = Vec::new();
+	let mut unknown = Vec::new();
+	unknown_keys(&source, &known, "", &mut unknown);
+	problems.extend(unknown.into_iter().map(|pointer| (pointer, "unknown key".to_owned())));
+	check_scenario(&source, &mut problems);
+	problems.into_iter().map(|(pointer, message)| format!("{}: {}: {}", name, pointer, message)).collect()
+}
+
+// everything wrong with a config, scenario or save file, one line each, empty if it is fine
+pub fn check(file_path: &path::Path) -> Vec<String> {
+	let name = file_path.display();
+	let text = match fs::read_to_string(file_path) {
+		Ok(text) => text,
+		Err(e) => return vec![format!("{}: {}", name, e)],
+	};
+	if persist::is_toml(file_path) {
+		return check_toml_scenario(file_path, &text);
+	}
+	let is_save = serde_json::from_str::<Value>(&text).ok().map_or(false, |value| value.get("swarms").is_some());
+	if is_save {
+		return check_save(file_path);
+	}
+	let is_config = file_path.file_name().map_or(false, |file_name| file_name == CONFIG_FILE);
+	let parsed = if is_config {
+		serde_json::from_str::<Config>(&text).and_then(|config| serde_json::to_value(&config))
+	} else {
+		serde_json::from_str::<Scenario>(&text).and_then(|scenario| serde_json::to_value(&scenario))
+	};
+	let (source, known) = match (serde_json::from_str::<Value>(&text), parsed) {
+		(Ok(source), Ok(known)) => (source, known),
+		(Err(e), _) | (_, Err(e)) => return vec![format!("{}:{}: {}", name, e.line(), e)],
+	};
+
+	let mut problems = Vec::new();
+	let mut unknown = Vec::new();
+	unknown_keys(&source, &known, "", &mut unknown);
+	problems.extend(unknown.into_iter().map(|pointer| (pointer, "unknown key".to_owned())));
+	if is_config {
+		check_config(&source, &mut problems);
+	} else {
+		check_scenario(&source, &mut problems);
+	}
+
+	let lines = Lines::scan(&text);
+	problems
+		.into_iter()
+		.map(|(pointer, message)| {
+			let line = lines.get(&pointer).cloned().unwrap_or(1);
+			format!("{}:{}: {}: {}", name, line, pointer, message)
+		})
+		.collect()
+}
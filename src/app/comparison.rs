@@ -0,0 +1,95 @@
+use app::constants::*;
+use std::fs;
+use std::path;
+
+// One telemetry log, a line in each chart
+#[derive(Clone, Debug)]
+pub struct Run {
+	pub label: String,
+	// seconds and value, for each of the charts: the energy is what the fitness pays out
+	pub series: Vec<Vec<[f64; 2]>>,
+}
+
+// Telemetry of runs side by side, drawn over each other on the same axes
+#[derive(Clone, Debug)]
+pub struct Comparison {
+	pub runs: Vec<Run>,
+}
+
+// every other sample until they fit, long runs have many
+fn thin(points: Vec<[f64; 2]>) -> Vec<[f64; 2]> {
+	let stride = (points.len() + COMPARE_POINTS_MAX - 1) / COMPARE_POINTS_MAX;
+	if stride <= 1 {
+		points
+	} else {
+		points.into_iter().step_by(stride).collect()
+	}
+}
+
+fn read_run(file: &path::Path) -> Result<Run, String> {
+	let text = fs::read_to_string(file).map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+	let mut series = vec![Vec::new(); COMPARE_CHARTS.len()];
+	// long format, as the telemetry writes it: seconds,key,value
+	for line in text.lines().skip(1) {
+		let fields: Vec<&str> = line.split(',').collect();
+		if fields.len() != 3 {
+			continue;
+		}
+		if let Some(chart) = COMPARE_CHARTS.iter().position(|key| *key == fields[1]) {
+			if let (Ok(seconds), Ok(value)) = (fields[0].parse::<f64>(), fields[2].parse::<f64>()) {
+				series[chart].push([seconds, value]);
+			}
+		}
+	}
+	if series.iter().all(|points| points.is_empty()) {
+		return Err(format!("No population or energy in {:?}", file));
+	}
+	let label = file.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+	Ok(Run {
+		label,
+		series: series.into_iter().map(thin).collect(),
+	})
+}
+
+// the telemetry logs in the saved state directory, the latest first
+pub fn latest(saved_state_dir: &path::Path, count: usize) -> Vec<path::PathBuf> {
+	let mut files = fs::read_dir(saved_state_dir)
+		.map(|dir| {
+			dir.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+				.filter(|file| {
+					let name = file.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+					name.starts_with("telemetry_") && name.ends_with(".csv")
+				}).collect::<Vec<_>>()
+		}).unwrap_or_default();
+	files.sort();
+	files.into_iter().rev().take(count).collect()
+}
+
+impl Comparison {
+	// names without a folder are looked for in the saved state directory
+	pub fn load(files: &[path::PathBuf], saved_state_dir: &path::Path) -> Result<Comparison, String> {
+		if files.len() > COMPARE_RUNS_MAX {
+			return Err(format!("At most {} runs at once", COMPARE_RUNS_MAX));
+		}
+		let runs = files
+			.iter()
+			.map(|file| {
+				if file.is_file() {
+					read_run(file)
+				} else {
+					read_run(&saved_state_dir.join(file))
+				}
+			}).collect::<Result<Vec<_>, _>>()?;
+		if runs.is_empty() {
+			return Err("No telemetry to compare".to_owned());
+		}
+		Ok(Comparison { runs })
+	}
+
+	// the same for all the runs, so that the lines can be compared
+	pub fn extent(&self, chart: usize) -> [f64; 2] {
+		self.runs.iter().flat_map(|run| run.series[chart].iter()).fold([0., 0.], |extent, point| {
+			[extent[0].max(point[0]), extent[1].max(point[1])]
+		})
+	}
+}
@@ -0,0 +1,69 @@
+use app::accessibility::Accessibility;
+use app::caption::CaptionRules;
+use app::memory::MemoryBudget;
+use app::midi::MidiSettings;
+use app::moments::MomentRules;
+use app::mouse::MouseButtons;
+use app::osc::OscSettings;
+use app::projection::ProjectionSettings;
+use app::share::ShareSettings;
+use app::stream::StreamSettings;
+use app::warning::WarningRules;
+use app::webhook::Webhook;
+use backend::systems::PhysicsSettings;
+use backend::world::disease::Disease;
+use backend::world::fitness::Fitness;
+use backend::world::mating::Mating;
+use backend::world::objective::GameMode;
+use backend::world::terrain::Terrain;
+use serde_json;
+use std::fs;
+use std::io;
+use std::path;
+
+// User settings that survive restarts, as opposed to the world state in the saved games
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+	pub fitness: Fitness,
+	pub mating: Mating,
+	pub disease: Disease,
+	// the objectives and score over the sandbox
+	pub game: GameMode,
+	pub warnings: WarningRules,
+	// what is clipped and bookmarked when something unusual happens
+	pub moments: MomentRules,
+	pub webhook: Webhook,
+	pub caption: CaptionRules,
+	pub stream: StreamSettings,
+	pub share: ShareSettings,
+	pub osc: OscSettings,
+	pub mouse: MouseButtons,
+	pub midi: MidiSettings,
+	pub projection: ProjectionSettings,
+	pub accessibility: Accessibility,
+	// of the text, the system's if empty
+	pub locale: String,
+	pub physics: PhysicsSettings,
+	// what the records kept over a long run may take, in megabytes
+	pub memory: MemoryBudget,
+	// grown for every new world, instead of the empty one
+	pub terrain: Option<Terrain>,
+}
+
+impl Config {
+	pub fn load(file_path: &path::Path) -> io::Result<Config> {
+		let in_file = fs::File::open(file_path)?;
+		let config = serde_json::from_reader(in_file)?;
+		Ok(config)
+	}
+
+	pub fn save(&self, file_path: &path::Path) -> io::Result<()> {
+		if let Some(dir) = file_path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		let out_file = fs::File::create(file_path)?;
+		serde_json::to_writer_pretty(out_file, self)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,467 @@
+use app::accessibility::{ColorMode, Palette};
+use app::constants::*;
+use app::editor::Tool;
+use app::l10n::Strings;
+use app::mouse::MouseAction;
+use app::mouse::MouseButton;
+use backend::systems::CollisionCategory;
+use backend::systems::PhysicsEngine;
+use backend::world::fitness;
+use backend::world::obstacle;
+use backend::world::terrain;
+use backend::world::Topology;
+use backend::world::mating::Reproduction;
+use backend::world::objective::Objective;
+use frontend::render::Quality;
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug)]
+pub enum Command {
+	Help,
+	Fitness,
+	SetFitness(String, f32),
+	Mating,
+	SetReproduction(Reproduction),
+	SetAssortative(bool),
+	SetMatingDistance(f32),
+	SetMatingRange(f32),
+	SetMutation(f32),
+	Disease,
+	Energy,
+	Outbreak(usize),
+	SetTransmission(f32),
+	SetDrain(f32),
+	SetInfectionDuration(f32),
+	Game,
+	SetGame(bool),
+	AddObjective(Objective),
+	ClearObjectives,
+	Capture,
+	CaptureWindow,
+	CaptureScreen,
+	CaptureWorld(bool),
+	Mouse,
+	SetMouseButton(MouseAction, MouseButton),
+	SetLeftHanded(bool),
+	Physics,
+	SetPhysicsEngine(PhysicsEngine),
+	SetPhysicsRegions(usize),
+	SetPhysicsMargin(f32),
+	CheckPhysics,
+	// despawned rather than left frozen
+	SetDespawnRunaways(bool),
+	SetCollision(CollisionCategory, CollisionCategory, bool),
+	SetGhost(CollisionCategory, bool),
+	Obstacles,
+	NewObstacle(bool),
+	SetObstacle(usize, obstacle::Property, f32),
+	RemoveObstacle(usize),
+	SetDoor(usize, Option<obstacle::Trigger>),
+	OpenDoor(usize, bool),
+	SetGate(usize, Option<obstacle::Direction>),
+	SetFlow(usize, Option<obstacle::Flow>),
+	Topology,
+	SetTopology(Topology),
+	Portals,
+	NewPortal,
+	Feeders,
+	SetFeederRate(f32),
+	Plants,
+	SowPlants(usize),
+	SetPortalRadius(usize, f32),
+	RemovePortal(usize),
+	Editor,
+	SetEditor(bool),
+	SetEditorTool(Tool),
+	SaveScenario(String),
+	LoadScenario(String),
+	// a random seed if none is given
+	GenerateTerrain(terrain::Layout, Option<u32>),
+	Stream,
+	SetStreaming(bool),
+	SetStreamUrl(String),
+	Share,
+	SetSharing(bool),
+	SetShareName(String),
+	Caption,
+	SetCaption(bool),
+	SetCaptionStats(bool),
+	SetCaptionAnnotations(bool),
+	SetCaptionTitle(String),
+	Profile,
+	Recent,
+	Genealogy(Option<usize>),
+	DiffGenomes(Option<usize>, Option<usize>),
+	CloseGenomeDiff,
+	PreviewGenome(Option<usize>),
+	ImportGenome(String),
+	SpawnPreview,
+	ClosePreview,
+	EditGenome(Option<usize>),
+	CloseGenomeEditor,
+	SetGene(usize, u8),
+	Breed(Option<(usize, usize)>),
+	Rebreed,
+	PickCandidate(usize),
+	CloseBreeding,
+	Checkpoints,
+	SaveCheckpoint(String),
+	LoadCheckpoint(String),
+	Branches,
+	LoadRecent(usize),
+	Challenges,
+	StartChallenge(usize),
+	AbandonChallenge,
+	SetChallengePack(String),
+	Compare(Vec<String>),
+	CompareLatest(usize),
+	CloseCompare,
+	DiffWorlds(String, String),
+	SetQuality(Quality),
+	Quality,
+	SetAdaptive(bool),
+	SetAdaptiveFps(f32),
+	Memory,
+	SetMemoryBudget(String, usize),
+	Diagnostics,
+	SetDiagnostics(bool),
+	Locale,
+	Colors,
+	SetColorMode(ColorMode),
+	SetPalette(Palette),
+	SetUiScale(f32),
+	Warnings,
+	SetMinPopulation(usize),
+	SetMinFps(f32),
+	SetMinFreeDisk(usize),
+	SetAutosaveWarning(bool),
+	Moments,
+	SetMoments(bool),
+	SetMomentClip(f32),
+	SetMomentCheckpoint(bool),
+}
+
+#[derive(Clone, Debug)]
+pub struct ConsoleView {
+	pub lines: Vec<String>,
+	pub input: String,
+}
+
+#[derive(Default)]
+pub struct Console {
+	is_open: bool,
+	input: String,
+	log: VecDeque<String>,
+}
+
+impl Console {
+	pub fn toggle(&mut self) { self.is_open = !self.is_open; }
+
+	pub fn close(&mut self) { self.is_open = false; }
+
+	pub fn is_open(&self) -> bool { self.is_open }
+
+	// the key that opens the console ends up in the text box too
+	pub fn set_input(&mut self, text: String) { self.input = text.replace('`', ""); }
+
+	pub fn take_input(&mut self) -> String { self.input.split_off(0) }
+
+	pub fn print<S: Into<String>>(&mut self, line: S) {
+		if self.log.len() >= CONSOLE_MAX_LINES {
+			self.log.pop_front();
+		}
+		self.log.push_back(line.into());
+	}
+
+	pub fn view(&self) -> Option<ConsoleView> {
+		if self.is_open {
+			Some(ConsoleView {
+				lines: self.log.iter().cloned().collect(),
+				input: self.input.clone(),
+			})
+		} else {
+			None
+		}
+	}
+}
+
+pub fn help() -> String {
+	"help | fitness [<term> <weight>] | mating [mode mixed|budding|mating] [assortative on|off] \
+	 [distance <0..1>] [range <r>] [mutation <rate>] | \
+	 disease [outbreak <n>] [transmission <rate>] [drain <energy>] [duration <seconds>] | energy | \
+	 game [on|off] [population <n> <minutes>] [crossing] [clear] | \
+	 capture [window|screen|world|follow] | caption [on|off] [stats on|off] [annotations on|off] [title <text>] | \
+	 stream [on|off] [url <rtmp url>] | share [on|off] [name <name>] | mouse [shoot|drag|spawn left|right|middle] [lefthanded on|off] | \
+	 physics [engine box2d|rapier] [regions <n>] [margin <m>] [check] [runaways freeze|despawn] \
+	 [collide <category> <category> on|off] [ghost <category> on|off] | \
+	 obstacle [wall|zone] [<n> friction|restitution|sticky|drag <value>] [<n> remove] \
+	 [<n> door manual|off|timer <open> <closed>|population <count>] [<n> open|close] \
+	 [<n> gate left|right|up|down|off] [<n> flow left|right|up|down <speed>] [<n> flow off] | \
+	 portal [add] [<n> radius <r>] [<n> remove] | feeders [rate <seconds>] | plants [sow <n>] | \
+	 topology [bounded|toroidal|unbounded] | terrain caves|islands [<seed>] | \
+	 edit [on|off] [tool wall|zone|feeder|probe|move|resize|delete] [save|load <name>] | \
+	 profile | recent [<n>] | challenge [<n>|off] [pack <dir>|default] | checkpoints | checkpoint [load] <name> | \
+	 branches | quality [low|high] [auto on|off] [auto fps <f>] | \
+	 memory [total|lineage|probes|report|capture <megabytes>] | diagnostics [on|off] | locale | \
+	 compare <telemetry file>... | compare latest <n> | compare off | diff <save> <save> | genealogy [<agent>] | \
+	 genome diff [<agent> [<agent>]] | genome diff off | genome preview [<agent>] | genome preview off | \
+	 genome import <base64> | genome spawn | genome edit [<agent>] | genome edit off | genome set <gene> <value> | \
+	 breed [<agent> <agent>] | breed again | breed pick <n> | breed off | \
+	 colors [mode livery|species|energy] [palette standard|safe|mono] | ui scale <s> | \
+	 warnings [population <n>] [fps <f>] [disk <megabytes>] [autosave on|off] | \
+	 moments [on|off] [clip <seconds>] [checkpoint on|off]"
+		.to_owned()
+}
+
+fn parse_f32(strings: &Strings, value: &str) -> Result<f32, String> {
+	value.parse::<f32>().map_err(|_| strings.fill("console.invalid_number", &[value]))
+}
+
+fn parse_layout(strings: &Strings, name: &str) -> Result<terrain::Layout, String> {
+	terrain::Layout::parse(name).ok_or_else(|| strings.fill("console.unknown_layout", &[name]))
+}
+
+fn parse_usize(strings: &Strings, value: &str) -> Result<usize, String> {
+	value.parse::<usize>().map_err(|_| strings.fill("console.invalid_count", &[value]))
+}
+
+// counted from one, as listed
+fn parse_index(strings: &Strings, value: &str) -> Result<usize, String> {
+	match parse_usize(strings, value)? {
+		0 => Err(strings.get("console.counted_from_one").to_owned()),
+		n => Ok(n - 1),
+	}
+}
+
+fn parse_direction(strings: &Strings, value: &str) -> Result<obstacle::Direction, String> {
+	obstacle::Direction::parse(value).ok_or_else(|| strings.fill("console.unknown_direction", &[value]))
+}
+
+fn parse_category(strings: &Strings, value: &str) -> Result<CollisionCategory, String> {
+	CollisionCategory::parse(value).ok_or_else(|| strings.fill("console.unknown_category", &[value]))
+}
+
+// in decimal, or in hex as 0x3f
+fn parse_byte(strings: &Strings, value: &str) -> Result<u8, String> {
+	let parsed = if value.starts_with("0x") { u8::from_str_radix(&value[2..], 16) } else { value.parse::<u8>() };
+	parsed.map_err(|_| strings.fill("console.invalid_byte", &[value]))
+}
+
+fn parse_switch(strings: &Strings, value: &str) -> Result<bool, String> {
+	match value {
+		"on" => Ok(true),
+		"off" => Ok(false),
+		_ => Err(strings.fill("console.invalid_switch", &[value])),
+	}
+}
+
+pub fn parse(strings: &Strings, line: &str) -> Result<Command, String> {
+	let words: Vec<&str> = line.split_whitespace().collect();
+	match words.as_slice() {
+		["help"] => Ok(Command::Help),
+		["fitness"] => Ok(Command::Fitness),
+		["fitness", term, weight] => {
+			if !fitness::TERMS.contains(term) {
+				return Err(strings.fill("console.unknown_fitness_term", &[*term, &fitness::TERMS.join(", ")]));
+			}
+			parse_f32(strings, weight).map(|weight| Command::SetFitness(term.to_string(), weight))
+		}
+		["mating"] => Ok(Command::Mating),
+		["mating", "mode", mode] => Reproduction::parse(mode)
+			.map(Command::SetReproduction)
+			.ok_or_else(|| strings.fill("console.unknown_reproduction", &[*mode])),
+		["mating", "assortative", value] => parse_switch(strings, value).map(Command::SetAssortative),
+		["mating", "distance", value] => parse_f32(strings, value).map(Command::SetMatingDistance),
+		["mating", "range", value] => parse_f32(strings, value).map(Command::SetMatingRange),
+		["mating", "mutation", value] => parse_f32(strings, value).map(Command::SetMutation),
+		["disease"] => Ok(Command::Disease),
+		["energy"] => Ok(Command::Energy),
+		["disease", "outbreak", count] => parse_usize(strings, count).map(Command::Outbreak),
+		["disease", "transmission", value] => parse_f32(strings, value).map(Command::SetTransmission),
+		["disease", "drain", value] => parse_f32(strings, value).map(Command::SetDrain),
+		["disease", "duration", value] => parse_f32(strings, value).map(Command::SetInfectionDuration),
+		["game"] => Ok(Command::Game),
+		["game", "population", count, minutes] => {
+			let minions = parse_usize(strings, count)?;
+			parse_f32(strings, minutes).map(|minutes| Command::AddObjective(Objective::Population { minions, minutes }))
+		}
+		["game", "crossing"] => Ok(Command::AddObjective(Objective::Crossing)),
+		["game", "clear"] => Ok(Command::ClearObjectives),
+		["game", value] => parse_switch(strings, value).map(Command::SetGame),
+		["capture"] => Ok(Command::Capture),
+		["capture", "window"] => Ok(Command::CaptureWindow),
+		["capture", "screen"] => Ok(Command::CaptureScreen),
+		["capture", "world"] => Ok(Command::CaptureWorld(false)),
+		["capture", "follow"] => Ok(Command::CaptureWorld(true)),
+		["mouse"] => Ok(Command::Mouse),
+		["mouse", "lefthanded", value] => parse_switch(strings, value).map(Command::SetLeftHanded),
+		["mouse", action, button] => {
+			let action = MouseAction::parse(action)
+				.ok_or_else(|| strings.fill("console.unknown_mouse_action", &[*action]))?;
+			MouseButton::parse(button)
+				.map(|button| Command::SetMouseButton(action, button))
+				.ok_or_else(|| strings.fill("console.unknown_mouse_button", &[*button]))
+		}
+		["physics"] => Ok(Command::Physics),
+		["physics", "engine", engine] => PhysicsEngine::parse(engine)
+			.map(Command::SetPhysicsEngine)
+			.ok_or_else(|| strings.fill("console.unknown_engine", &[*engine])),
+		["physics", "regions", count] => parse_usize(strings, count).map(Command::SetPhysicsRegions),
+		["physics", "margin", value] => parse_f32(strings, value).map(Command::SetPhysicsMargin),
+		["physics", "check"] => Ok(Command::CheckPhysics),
+		["physics", "runaways", "freeze"] => Ok(Command::SetDespawnRunaways(false)),
+		["physics", "runaways", "despawn"] => Ok(Command::SetDespawnRunaways(true)),
+		["physics", "collide", a, b, value] => {
+			let (a, b) = (parse_category(strings, a)?, parse_category(strings, b)?);
+			parse_switch(strings, value).map(|collides| Command::SetCollision(a, b, collides))
+		}
+		["physics", "ghost", category, value] => {
+			let category = parse_category(strings, category)?;
+			parse_switch(strings, value).map(|is_ghost| Command::SetGhost(category, is_ghost))
+		}
+		["obstacle"] => Ok(Command::Obstacles),
+		["obstacle", "wall"] => Ok(Command::NewObstacle(true)),
+		["obstacle", "zone"] => Ok(Command::NewObstacle(false)),
+		["obstacle", index, "remove"] => parse_index(strings, index).map(Command::RemoveObstacle),
+		["obstacle", index, "open"] => parse_index(strings, index).map(|index| Command::OpenDoor(index, true)),
+		["obstacle", index, "close"] => parse_index(strings, index).map(|index| Command::OpenDoor(index, false)),
+		["obstacle", index, "door", "manual"] => {
+			parse_index(strings, index).map(|index| Command::SetDoor(index, Some(obstacle::Trigger::Manual)))
+		}
+		["obstacle", index, "door", "off"] => parse_index(strings, index).map(|index| Command::SetDoor(index, None)),
+		["obstacle", index, "door", "timer", open, closed] => {
+			let index = parse_index(strings, index)?;
+			let (open, closed) = (parse_f32(strings, open)?, parse_f32(strings, closed)?);
+			Ok(Command::SetDoor(index, Some(obstacle::Trigger::Timer { open, closed })))
+		}
+		["obstacle", index, "door", "population", count] => {
+			let index = parse_index(strings, index)?;
+			parse_usize(strings, count).map(|count| Command::SetDoor(index, Some(obstacle::Trigger::Population(count))))
+		}
+		["obstacle", index, "flow", "off"] => parse_index(strings, index).map(|index| Command::SetFlow(index, None)),
+		["obstacle", index, "flow", direction, speed] => {
+			let index = parse_index(strings, index)?;
+			let direction = parse_direction(strings, direction)?;
+			parse_f32(strings, speed).map(|speed| Command::SetFlow(index, Some(obstacle::Flow { direction, speed })))
+		}
+		["obstacle", index, "gate", "off"] => parse_index(strings, index).map(|index| Command::SetGate(index, None)),
+		["obstacle", index, "gate", direction] => {
+			let index = parse_index(strings, index)?;
+			parse_direction(strings, direction).map(|gate| Command::SetGate(index, Some(gate)))
+		}
+		["obstacle", index, property, value] => {
+			let index = parse_index(strings, index)?;
+			let property = obstacle::Property::parse(property)
+				.ok_or_else(|| strings.fill("console.unknown_obstacle_property", &[*property]))?;
+			parse_f32(strings, value).map(|value| Command::SetObstacle(index, property, value))
+		}
+		["topology"] => Ok(Command::Topology),
+		["topology", name] => Topology::parse(name)
+			.map(Command::SetTopology)
+			.ok_or_else(|| strings.fill("console.unknown_topology", &[*name])),
+		["feeders"] => Ok(Command::Feeders),
+		["feeders", "rate", value] => parse_f32(strings, value).map(Command::SetFeederRate),
+		["plants"] => Ok(Command::Plants),
+		["plants", "sow", count] => parse_usize(strings, count).map(Command::SowPlants),
+		["portal"] => Ok(Command::Portals),
+		["portal", "add"] => Ok(Command::NewPortal),
+		["portal", index, "remove"] => parse_index(strings, index).map(Command::RemovePortal),
+		["portal", index, "radius", value] => {
+			let index = parse_index(strings, index)?;
+			parse_f32(strings, value).map(|radius| Command::SetPortalRadius(index, radius))
+		}
+		["edit"] => Ok(Command::Editor),
+		["edit", "tool", name] => Tool::parse(name)
+			.map(Command::SetEditorTool)
+			.ok_or_else(|| strings.fill("console.unknown_tool", &[*name])),
+		["edit", "save", name] => Ok(Command::SaveScenario(name.to_string())),
+		["edit", "load", name] => Ok(Command::LoadScenario(name.to_string())),
+		["edit", value] => parse_switch(strings, value).map(Command::SetEditor),
+		["terrain", layout] => parse_layout(strings, layout).map(|layout| Command::GenerateTerrain(layout, None)),
+		["terrain", layout, seed] => {
+			let layout = parse_layout(strings, layout)?;
+			let seed = seed.parse::<u32>().map_err(|_| strings.fill("console.invalid_seed", &[*seed]))?;
+			Ok(Command::GenerateTerrain(layout, Some(seed)))
+		}
+		["stream"] => Ok(Command::Stream),
+		["stream", "url", url] => Ok(Command::SetStreamUrl(url.to_string())),
+		["stream", value] => parse_switch(strings, value).map(Command::SetStreaming),
+		["share"] => Ok(Command::Share),
+		["share", "name", name] => Ok(Command::SetShareName(name.to_string())),
+		["share", value] => parse_switch(strings, value).map(Command::SetSharing),
+		["caption"] => Ok(Command::Caption),
+		["caption", "stats", value] => parse_switch(strings, value).map(Command::SetCaptionStats),
+		["caption", "annotations", value] => parse_switch(strings, value).map(Command::SetCaptionAnnotations),
+		["caption", "title", ..] => Ok(Command::SetCaptionTitle(words[2..].join(" "))),
+		["caption", value] => parse_switch(strings, value).map(Command::SetCaption),
+		["profile"] => Ok(Command::Profile),
+		["recent"] => Ok(Command::Recent),
+		["genealogy"] => Ok(Command::Genealogy(None)),
+		["genealogy", id] => parse_usize(strings, id).map(|id| Command::Genealogy(Some(id))),
+		["genome", "diff"] => Ok(Command::DiffGenomes(None, None)),
+		["genome", "diff", "off"] => Ok(Command::CloseGenomeDiff),
+		["genome", "diff", id] => parse_usize(strings, id).map(|id| Command::DiffGenomes(Some(id), None)),
+		["genome", "preview"] => Ok(Command::PreviewGenome(None)),
+		["genome", "preview", "off"] => Ok(Command::ClosePreview),
+		["genome", "preview", id] => parse_usize(strings, id).map(|id| Command::PreviewGenome(Some(id))),
+		["genome", "import", text] => Ok(Command::ImportGenome(text.to_string())),
+		["genome", "spawn"] => Ok(Command::SpawnPreview),
+		["genome", "edit"] => Ok(Command::EditGenome(None)),
+		["genome", "edit", "off"] => Ok(Command::CloseGenomeEditor),
+		["genome", "edit", id] => parse_usize(strings, id).map(|id| Command::EditGenome(Some(id))),
+		["breed"] => Ok(Command::Breed(None)),
+		["breed", "off"] => Ok(Command::CloseBreeding),
+		["breed", "again"] => Ok(Command::Rebreed),
+		["breed", "pick", n] => parse_index(strings, n).map(Command::PickCandidate),
+		["breed", a, b] => Ok(Command::Breed(Some((parse_usize(strings, a)?, parse_usize(strings, b)?)))),
+		["genome", "set", gene, value] => Ok(Command::SetGene(parse_usize(strings, gene)?, parse_byte(strings, value)?)),
+		["genome", "diff", id, other] => {
+			let (id, other) = (parse_usize(strings, id)?, parse_usize(strings, other)?);
+			Ok(Command::DiffGenomes(Some(id), Some(other)))
+		}
+		["recent", n] => parse_usize(strings, n).map(Command::LoadRecent),
+		["challenge"] => Ok(Command::Challenges),
+		["challenge", "off"] => Ok(Command::AbandonChallenge),
+		["challenge", "pack", _, ..] => Ok(Command::SetChallengePack(words[2..].join(" "))),
+		["challenge", n] => parse_usize(strings, n).map(Command::StartChallenge),
+		["compare", "off"] => Ok(Command::CloseCompare),
+		["compare", "latest", n] => parse_usize(strings, n).map(Command::CompareLatest),
+		["compare", _, ..] => Ok(Command::Compare(words[1..].iter().map(|file| (*file).to_owned()).collect())),
+		["diff", before, after] => Ok(Command::DiffWorlds(before.to_string(), after.to_string())),
+		["checkpoints"] => Ok(Command::Checkpoints),
+		["checkpoint", "load", _, ..] => Ok(Command::LoadCheckpoint(words[2..].join(" "))),
+		["checkpoint", _, ..] => Ok(Command::SaveCheckpoint(words[1..].join(" "))),
+		["branches"] => Ok(Command::Branches),
+		["quality"] => Ok(Command::Quality),
+		["memory"] => Ok(Command::Memory),
+		["memory", part, megabytes] => {
+			parse_usize(strings, megabytes).map(|megabytes| Command::SetMemoryBudget(part.to_string(), megabytes))
+		}
+		["diagnostics"] => Ok(Command::Diagnostics),
+		["diagnostics", value] => parse_switch(strings, value).map(Command::SetDiagnostics),
+		["quality", "auto", "fps", fps] => parse_f32(strings, fps).map(Command::SetAdaptiveFps),
+		["quality", "auto", value] => parse_switch(strings, value).map(Command::SetAdaptive),
+		["quality", quality] => Quality::parse(quality)
+			.map(Command::SetQuality)
+			.ok_or_else(|| strings.fill("console.unknown_quality", &[*quality])),
+		["locale"] => Ok(Command::Locale),
+		["colors"] => Ok(Command::Colors),
+		["colors", "mode", mode] => ColorMode::parse(mode)
+			.map(Command::SetColorMode)
+			.ok_or_else(|| strings.fill("console.unknown_color_mode", &[*mode])),
+		["colors", "palette", palette] => Palette::parse(palette)
+			.map(Command::SetPalette)
+			.ok_or_else(|| strings.fill("console.unknown_palette", &[*palette])),
+		["ui", "scale", value] => parse_f32(strings, value).map(Command::SetUiScale),
+		["warnings"] => Ok(Command::Warnings),
+		["warnings", "population", value] => parse_usize(strings, value).map(Command::SetMinPopulation),
+		["warnings", "fps", value] => parse_f32(strings, value).map(Command::SetMinFps),
+		["warnings", "disk", value] => parse_usize(strings, value).map(Command::SetMinFreeDisk),
+		["warnings", "autosave", value] => parse_switch(strings, value).map(Command::SetAutosaveWarning),
+		["moments"] => Ok(Command::Moments),
+		["moments", "clip", seconds] => parse_f32(strings, seconds).map(Command::SetMomentClip),
+		["moments", "checkpoint", value] => parse_switch(strings, value).map(Command::SetMomentCheckpoint),
+		["moments", value] => parse_switch(strings, value).map(Command::SetMoments),
+		_ => Err(strings.fill("console.unknown_command", &[line])),
+	}
+}
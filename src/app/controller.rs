@@ -12,30 +12,62 @@ use super::events::Event::*;
 use frontend::input::Axis::*;
 use frontend::input::Key::*;
 
-const KEY_HELD_MAP: &[(input::Key, Event)] = &[(W, CamUp(1.)), (S, CamDown(1.)), (A, CamLeft(1.)), (D, CamRight(1.))];
+// zooming goes on for as long as the key is held
+const KEY_HELD_MAP: &[(input::Key, Event)] = &[
+	(W, CamUp(1.)),
+	(S, CamDown(1.)),
+	(A, CamLeft(1.)),
+	(D, CamRight(1.)),
+	(GamepadDPadUp, ZoomIn),
+	(GamepadDPadDown, ZoomOut),
+	(Plus, ZoomIn),
+	(Minus, ZoomOut),
+];
+
+// the buttons as the input state sees them, after the mapping set up in the mouse section of the configuration
+const SHOOT_BUTTON: input::Key = MouseLeft;
+const DRAG_BUTTON: input::Key = MouseRight;
+const SPAWN_BUTTON: input::Key = MouseMiddle;
+
+// modifiers match on either side
+const KEY_CHORD_MAP: &[(&[input::Key], Event)] = &[(&[LCtrl, LShift, S], SaveWorldToFile)];
+
+const KEY_DOUBLE_TAP_MAP: &[(input::Key, Event)] = &[
+	(W, CamDash(Position { x: 0., y: 1. })),
+	(S, CamDash(Position { x: 0., y: -1. })),
+	(A, CamDash(Position { x: -1., y: 0. })),
+	(D, CamDash(Position { x: 1., y: 0. })),
+];
 
 const KEY_PRESSED_ONCE_MAP: &[(input::Key, Event)] = &[
 	(F5, Reload),
 	(F1, ToggleGui),
+	(F2, ToggleShadows),
+	(F3, ToggleGrid),
+	(F4, ToggleAnnotations),
+	(E, ToggleEditor),
 	(GamepadL3, ToggleGui),
 	(N0, CamReset),
 	(Home, CamReset),
 	(GamepadSelect, ToggleCapture),
 	(GamepadStart, TogglePause),
 	(KpHome, CamReset),
-	(GamepadDPadUp, ZoomIn),
-	(GamepadDPadDown, ZoomOut),
 	(GamepadR3, ZoomReset),
-	(Plus, ZoomIn),
-	(Minus, ZoomOut),
 	(N1, ZoomReset),
 	(F6, SaveGenePoolToFile),
 	(F7, SaveWorldToFile),
 	(F8, RestartFromCheckpoint),
+	(F9, SaveProbesToFile),
 	(F10, ToggleDebug),
+	(F11, ToggleQuickLoad),
 	(F12, ToggleCapture),
 	(GamepadStart, ToggleDebug),
 	(Z, DeselectAll),
+	(X, ToggleFreezeSelected),
+	(C, TogglePossess),
+	(J, ToggleGenealogy),
+	(O, ToggleChallenges),
+	(Backtick, ToggleConsole),
 	(L, NextLight),
 	(B, NextBackground),
 	(K, PrevLight),
@@ -63,14 +95,35 @@ pub trait InputController {
 impl DefaultController {
 	fn interpret_key_press<I>(input_state: &I, events: &mut Vec<Event>)
 	where I: input::InputRead {
-		for (key_held, event) in KEY_HELD_MAP {
-			if input_state.key_pressed(*key_held) {
+		// the keys of a chord held down belong to it alone: the S of Ctrl+Shift+S neither pans nor dashes the camera
+		let chorded: Vec<input::Key> = KEY_CHORD_MAP
+			.iter()
+			.filter(|&&(chord, _)| input_state.chord_pressed(chord))
+			.flat_map(|&(chord, _)| chord.iter().cloned())
+			.collect();
+		let free = |key: &input::Key| !chorded.contains(key);
+
+		// a tap shorter than a frame still counts for one
+		for (key_held, event) in KEY_HELD_MAP.iter().filter(|&&(key, _)| free(&key)) {
+			if input_state.key_pressed(*key_held) || input_state.key_once(*key_held) {
 				events.push(*event);
 			}
 		}
 
-		for (key_pressed, event) in KEY_PRESSED_ONCE_MAP {
-			if input_state.key_pressed(*key_pressed) {
+		for (key_pressed, event) in KEY_PRESSED_ONCE_MAP.iter().filter(|&&(key, _)| free(&key)) {
+			if input_state.key_once(*key_pressed) {
+				events.push(*event);
+			}
+		}
+
+		for (chord, event) in KEY_CHORD_MAP {
+			if input_state.chord_once(chord) {
+				events.push(*event);
+			}
+		}
+
+		for (key_tapped, event) in KEY_DOUBLE_TAP_MAP.iter().filter(|&&(key, _)| free(&key)) {
+			if input_state.key_double_tap(*key_tapped) {
 				events.push(*event);
 			}
 		}
@@ -92,11 +145,11 @@ impl DefaultController {
 		let mouse_view_pos = view_transform.to_view(mouse_window_pos);
 		let mouse_world_pos = world_transform.to_world(mouse_view_pos);
 
-		if input_state.key_once(MouseLeft) && input_state.any_ctrl_pressed() {
+		if input_state.key_once(SHOOT_BUTTON) && input_state.any_ctrl_pressed() && !input_state.any_shift_pressed() {
 			events.push(Event::PickMinion(mouse_world_pos));
 		};
 
-		if input_state.key_once(MouseMiddle) {
+		if input_state.key_once(SPAWN_BUTTON) {
 			if input_state.any_ctrl_pressed() {
 				events.push(Event::RandomizeMinion(mouse_world_pos));
 			} else {
@@ -105,17 +158,17 @@ impl DefaultController {
 		}
 
 		match input_state.dragging() {
-			input::Dragging::Begin(_, from) => {
+			input::Dragging::Begin(DRAG_BUTTON, from) => {
 				let from = world_transform.to_world(from);
 				events.push(Event::BeginDrag(from, from));
 			}
-			input::Dragging::Dragging(_, from, to) => {
+			input::Dragging::Dragging(DRAG_BUTTON, from, to) => {
 				events.push(Event::Drag(
 					world_transform.to_world(from),
 					world_transform.to_world(to),
 				));
 			}
-			input::Dragging::End(_, from, to, prev) => {
+			input::Dragging::End(DRAG_BUTTON, from, to, prev) => {
 				let mouse_vel = (view_transform.to_view(prev) - to) / dt.into();
 				events.push(Event::EndDrag(
 					world_transform.to_world(from),
@@ -123,14 +176,70 @@ impl DefaultController {
 					mouse_vel,
 				));
 			}
+			input::Dragging::End(SHOOT_BUTTON, from, to, _)
+				if input_state.any_shift_pressed() && input_state.any_ctrl_pressed() =>
+			{
+				events.push(Event::NewProbe(
+					world_transform.to_world(from),
+					world_transform.to_world(to),
+				));
+			}
+			input::Dragging::Begin(SHOOT_BUTTON, from) if input_state.any_shift_pressed() => {
+				let from = world_transform.to_world(from);
+				events.push(Event::Measure(from, from));
+			}
+			input::Dragging::Dragging(SHOOT_BUTTON, from, to) if input_state.any_shift_pressed() => {
+				events.push(Event::Measure(
+					world_transform.to_world(from),
+					world_transform.to_world(to),
+				));
+			}
+			input::Dragging::End(SHOOT_BUTTON, from, to, _) if input_state.any_shift_pressed() => {
+				events.push(Event::EndMeasure(
+					world_transform.to_world(from),
+					world_transform.to_world(to),
+				));
+			}
+			input::Dragging::End(SHOOT_BUTTON, from, to, _) if input_state.any_alt_pressed() => {
+				events.push(Event::NewAnnotation(
+					world_transform.to_world(from),
+					world_transform.to_world(to),
+				));
+			}
+			// the editor's, ignored outside of it
+			input::Dragging::Begin(SHOOT_BUTTON, from) if !input_state.any_ctrl_pressed() => {
+				let from = world_transform.to_world(from);
+				events.push(Event::EditDrag(from, from));
+			}
+			input::Dragging::Dragging(SHOOT_BUTTON, from, to) if !input_state.any_ctrl_pressed() => {
+				events.push(Event::EditDrag(
+					world_transform.to_world(from),
+					world_transform.to_world(to),
+				));
+			}
+			input::Dragging::End(SHOOT_BUTTON, from, to, _) if !input_state.any_ctrl_pressed() => {
+				events.push(Event::EndEditDrag(
+					world_transform.to_world(from),
+					world_transform.to_world(to),
+				));
+			}
 			_ => {}
 		}
 		mouse_world_pos
 	}
 
+	// LMB with any modifier is a tool (pick, measure, probe, annotate) rather than aim and shoot
+	fn plain_mouse_left_pressed<I>(input_state: &I) -> bool
+	where I: input::InputRead {
+		input_state.key_pressed(SHOOT_BUTTON)
+			&& !input_state.any_ctrl_pressed()
+			&& !input_state.any_shift_pressed()
+			&& !input_state.any_alt_pressed()
+	}
+
 	fn interpret_trigger_fire<I>(input_state: &I, events: &mut Vec<Event>)
 	where I: input::InputRead {
-		let mouse_left_pressed = input_state.key_pressed(MouseLeft) && !input_state.any_ctrl_pressed();
+		let mouse_left_pressed = Self::plain_mouse_left_pressed(input_state);
 		let firerate = input_state.gamepad_axis(0, L2);
 		let firepower = input_state.gamepad_axis(0, R2);
 		if firepower >= DEAD_ZONE {
@@ -167,7 +276,7 @@ impl DefaultController {
 
 		use cgmath::InnerSpace;
 		let magnitude = thrust.magnitude2();
-		let mouse_left_pressed = input_state.key_pressed(MouseLeft) && !input_state.any_ctrl_pressed();
+		let mouse_left_pressed = Self::plain_mouse_left_pressed(input_state);
 		events.push(Event::VectorThrust(
 			if magnitude >= DEAD_ZONE {
 				Some(thrust / magnitude.max(1.))
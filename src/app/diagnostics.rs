@@ -0,0 +1,55 @@
+use app::constants::*;
+use core::clock::SecondsValue;
+use frontend::input::InputLag;
+use std::time::Duration;
+
+fn millis(duration: Duration) -> f64 {
+	duration.as_secs() as f64 * 1000. + f64::from(duration.subsec_nanos()) / 1_000_000.
+}
+
+// How responsive the main loop is: the input as of the last update, and the worst of it over the last few seconds
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+	// in milliseconds, of the last update that read any input
+	pub latency: f64,
+	pub events: usize,
+	pub dropped_taps: usize,
+	// frames that took a couple of frame times or more, out of all of them
+	pub missed_frames: usize,
+	pub frames: usize,
+	peak_latency: f64,
+	peak_events: usize,
+	window_latency: f64,
+	window_events: usize,
+	window_started: SecondsValue,
+}
+
+impl Diagnostics {
+	// the paused app waits for input, so its frames are not counted as missed
+	pub fn record(&mut self, lag: &InputLag, frame_time: SecondsValue, now: SecondsValue, is_running: bool) {
+		if lag.events > 0 {
+			self.latency = millis(lag.latency);
+		}
+		self.events = lag.events;
+		self.dropped_taps = lag.dropped_taps;
+		self.frames += 1;
+		if is_running && frame_time > FRAME_TIME_TARGET * DIAGNOSTICS_MISSED_FRAME {
+			self.missed_frames += 1;
+		}
+		if now < self.window_started || now - self.window_started >= DIAGNOSTICS_PEAK_WINDOW {
+			self.peak_latency = self.window_latency;
+			self.peak_events = self.window_events;
+			self.window_latency = 0.;
+			self.window_events = 0;
+			self.window_started = now;
+		}
+		if lag.events > 0 {
+			self.window_latency = self.window_latency.max(self.latency);
+		}
+		self.window_events = self.window_events.max(lag.events);
+	}
+
+	pub fn peak_latency(&self) -> f64 { self.peak_latency.max(self.window_latency) }
+
+	pub fn peak_events(&self) -> usize { self.peak_events.max(self.window_events) }
+}
@@ -0,0 +1,165 @@
+use app::constants::*;
+use backend::obj::Transformable;
+use backend::world;
+use backend::world::obstacle::Obstacle;
+use backend::world::probe::Probe;
+use cgmath::MetricSpace;
+use core::clock::Seconds;
+use core::geometry::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tool {
+	Wall,
+	Zone,
+	Feeder,
+	Probe,
+	Move,
+	Resize,
+	Delete,
+}
+
+impl Tool {
+	pub fn parse(name: &str) -> Option<Tool> {
+		match name {
+			"wall" => Some(Tool::Wall),
+			"zone" => Some(Tool::Zone),
+			"feeder" => Some(Tool::Feeder),
+			"probe" => Some(Tool::Probe),
+			"move" => Some(Tool::Move),
+			"resize" => Some(Tool::Resize),
+			"delete" => Some(Tool::Delete),
+			_ => None,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Tool::Wall => "wall",
+			Tool::Zone => "zone",
+			Tool::Feeder => "feeder",
+			Tool::Probe => "probe",
+			Tool::Move => "move",
+			Tool::Resize => "resize",
+			Tool::Delete => "delete",
+		}
+	}
+}
+
+// what the cursor is on, the latest added first
+#[derive(Clone, Copy, Debug)]
+enum Item {
+	Feeder(usize),
+	Probe(usize),
+	Obstacle(usize),
+}
+
+fn pick(world: &world::World, p: Position) -> Option<Item> {
+	let is_near = |feeder: &world::Feeder| feeder.transform().position.distance(p) < EDITOR_PICK_RADIUS;
+	let probe = || world.probes().iter().rposition(|probe| probe.contains(p));
+	let obstacle = || world.obstacles().iter().rposition(|obstacle| obstacle.contains(p));
+	world.feeders().iter().rposition(is_near)
+		.map(Item::Feeder)
+		.or_else(|| probe().map(Item::Probe))
+		.or_else(|| obstacle().map(Item::Obstacle))
+}
+
+fn translate(extent: &Rect, delta: Position) -> Rect { Rect::from_corners(extent.min + delta, extent.max + delta) }
+
+// the corner nearest to where the drag started goes where it ended
+fn resize(extent: &Rect, from: Position, to: Position) -> Rect {
+	let center = (extent.min + extent.max) * 0.5;
+	let x = if from.x < center.x { (to.x, extent.max.x) } else { (extent.min.x, to.x) };
+	let y = if from.y < center.y { (to.y, extent.max.y) } else { (extent.min.y, to.y) };
+	Rect::from_corners(Position::new(x.0, y.0), Position::new(x.1, y.1))
+}
+
+// while it is open the simulation stands still and the left button builds instead of shooting
+pub struct Editor {
+	pub tool: Tool,
+	// the drag in progress, for the preview
+	pub drag: Option<(Position, Position)>,
+	// how the simulation was before, to go back to it when leaving
+	was_paused: bool,
+}
+
+impl Editor {
+	pub fn new(was_paused: bool) -> Self {
+		Editor {
+			tool: Tool::Wall,
+			drag: None,
+			was_paused,
+		}
+	}
+
+	pub fn was_paused(&self) -> bool { self.was_paused }
+
+	// a click is a drag that didn't go anywhere
+	pub fn apply(&mut self, world: &mut world::World, from: Position, to: Position) -> Result<String, String> {
+		self.drag = None;
+		let is_click = from.distance(to) < MEASURE_MIN_LENGTH;
+		match self.tool {
+			Tool::Wall | Tool::Zone | Tool::Probe if is_click => Err(format!("Drag to size the {}", self.tool.name())),
+			Tool::Wall => {
+				world.obstacles_mut().push(Obstacle::wall(from, to));
+				Ok(format!("wall {} added", world.obstacles().len()))
+			}
+			Tool::Zone => {
+				world.obstacles_mut().push(Obstacle::zone(from, to));
+				Ok(format!("zone {} added", world.obstacles().len()))
+			}
+			Tool::Probe => {
+				world.probes_mut().push(Probe::new(from, to));
+				Ok(format!("probe {} added", world.probes().len()))
+			}
+			Tool::Feeder => {
+				world.feeders_mut().push(world::Feeder::new(to.x, to.y, Seconds::new(EMITTER_PERIOD)));
+				Ok(format!("feeder {} added", world.feeders().len()))
+			}
+			Tool::Move => match pick(world, from).ok_or("Nothing to move there")? {
+				Item::Feeder(index) => {
+					let feeder = &mut world.feeders_mut()[index];
+					let angle = feeder.transform().angle;
+					feeder.transform_to(Transform::new(to, angle));
+					Ok(format!("feeder {} moved", index + 1))
+				}
+				Item::Probe(index) => {
+					let probe = &mut world.probes_mut()[index];
+					probe.extent = translate(&probe.extent, to - from);
+					Ok(format!("probe {} moved", index + 1))
+				}
+				Item::Obstacle(index) => {
+					let obstacle = &mut world.obstacles_mut()[index];
+					obstacle.extent = translate(&obstacle.extent, to - from);
+					Ok(format!("obstacle {} moved", index + 1))
+				}
+			},
+			Tool::Resize => match pick(world, from).ok_or("Nothing to resize there")? {
+				Item::Feeder(_) => Err("Feeders have no size".to_owned()),
+				Item::Probe(index) => {
+					let probe = &mut world.probes_mut()[index];
+					probe.extent = resize(&probe.extent, from, to);
+					Ok(format!("probe {} resized", index + 1))
+				}
+				Item::Obstacle(index) => {
+					let obstacle = &mut world.obstacles_mut()[index];
+					obstacle.extent = resize(&obstacle.extent, from, to);
+					Ok(format!("obstacle {} resized", index + 1))
+				}
+			},
+			Tool::Delete => match pick(world, to).ok_or("Nothing to delete there")? {
+				Item::Feeder(index) => {
+					world.feeders_mut().remove(index);
+					Ok(format!("feeder {} deleted", index + 1))
+				}
+				Item::Probe(index) => {
+					world.probes_mut().remove(index);
+					Ok(format!("probe {} deleted", index + 1))
+				}
+				Item::Obstacle(index) => {
+					world.obstacles_mut().remove(index);
+					Ok(format!("obstacle {} deleted", index + 1))
+				}
+			},
+		}
+	}
+}
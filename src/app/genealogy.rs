@@ -0,0 +1,77 @@
+use backend::obj::Id;
+use backend::world;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kinship {
+	Mother,
+	Father,
+	Sibling,
+	Child,
+}
+
+impl Kinship {
+	// in the string table
+	pub fn key(self) -> &'static str {
+		match self {
+			Kinship::Mother => "genealogy.mother",
+			Kinship::Father => "genealogy.father",
+			Kinship::Sibling => "genealogy.sibling",
+			Kinship::Child => "genealogy.child",
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Relative {
+	pub id: Id,
+	pub kinship: Kinship,
+	pub alive: bool,
+}
+
+// The agent the genealogy browser is on, and the relatives it can go to next
+#[derive(Clone, Debug)]
+pub struct GenealogyView {
+	pub id: Id,
+	pub generation: usize,
+	pub alive: bool,
+	// the genes that differ from the mother's, and how many there are
+	pub changed: Option<(usize, usize)>,
+	pub relatives: Vec<Relative>,
+}
+
+// a gene is a byte of the genome, those of only one count as changed
+pub fn changed_genes(dna: &[u8], other: &[u8]) -> usize {
+	let common = dna.iter().zip(other.iter()).filter(|&(a, b)| a != b).count();
+	common + (dna.len() as isize - other.len() as isize).abs() as usize
+}
+
+// parents first, then siblings and children; a minion of the first population has none of the first two
+pub fn view(world: &world::World, id: Id) -> GenealogyView {
+	let lineage = world.lineage();
+	let relative = |id: Id, kinship: Kinship| Relative {
+		id,
+		kinship,
+		alive: world.agent(id).is_some(),
+	};
+	let mut relatives = Vec::new();
+	let mut changed = None;
+	let mut generation = 0;
+	if let Some(record) = lineage.get(id) {
+		generation = record.generation;
+		relatives.extend(record.mother.map(|mother| relative(mother, Kinship::Mother)));
+		relatives.extend(record.father.map(|father| relative(father, Kinship::Father)));
+		relatives.extend(lineage.siblings(id).into_iter().map(|sibling| relative(sibling, Kinship::Sibling)));
+		relatives.extend(record.children.iter().map(|child| relative(*child, Kinship::Child)));
+		changed = record
+			.mother
+			.and_then(|mother| lineage.get(mother))
+			.map(|mother| (changed_genes(&record.dna, &mother.dna), record.dna.len()));
+	}
+	GenealogyView {
+		id,
+		generation,
+		alive: world.agent(id).is_some(),
+		changed,
+		relatives,
+	}
+}
@@ -0,0 +1,130 @@
+use super::genealogy;
+use backend::obj::Id;
+use backend::world;
+use backend::world::agent::Agent;
+use backend::world::agent::AgentType;
+use backend::world::agent::TypedAgent;
+use backend::world::gen::Dna;
+use backend::world::segment::Flags;
+use cgmath::InnerSpace;
+use core::geometry::Position;
+
+// What a genome grows into, as far as can be told without letting it live
+#[derive(Clone, Debug)]
+pub struct BodyPlan {
+	// the outline of every segment, around the torso at the origin
+	pub outlines: Vec<Vec<Position>>,
+	pub color: [f32; 4],
+	// in the order they are shown, the body first and the temperament after
+	pub traits: Vec<(&'static str, f32)>,
+}
+
+fn count(agent: &Agent, flags: Flags) -> f32 {
+	agent.segments().iter().filter(|segment| segment.flags.contains(flags)).count() as f32
+}
+
+impl BodyPlan {
+	pub fn of(agent: &Agent) -> BodyPlan {
+		let outlines = agent.segments().iter().map(|segment| segment.outline()).collect();
+		let brain = agent.brain();
+		BodyPlan {
+			outlines,
+			color: agent.segments()[0].livery.albedo,
+			traits: vec![
+				("segments", agent.segments().len() as f32),
+				("arms", count(agent, Flags::ARM)),
+				("legs", count(agent, Flags::LEG)),
+				("rudders", count(agent, Flags::RUDDER)),
+				("thrusters", count(agent, Flags::THRUSTER)),
+				("storage", count(agent, Flags::STORAGE)),
+				("gender", f32::from(agent.gender())),
+				("resistance", agent.resistance()),
+				("hunger", brain.hunger),
+				("haste", brain.haste),
+				("prudence", brain.prudence),
+				("fear", brain.fear),
+				("rest", brain.rest),
+				("thrust", brain.thrust),
+			],
+		}
+	}
+
+	pub fn summary(&self) -> String {
+		let traits: Vec<_> = self.traits.iter().map(|&(name, value)| format!("{} {}", name, show(value))).collect();
+		traits.join(", ")
+	}
+
+	// the farthest point from the torso, to fit the outlines in the preview
+	pub fn radius(&self) -> f32 {
+		self.outlines
+			.iter()
+			.flat_map(|outline| outline.iter())
+			.fold(0., |radius, point| point.magnitude().max(radius))
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Side {
+	pub id: Id,
+	pub dna: Dna,
+	pub plan: BodyPlan,
+}
+
+// Two genomes side by side, the one being looked at on the left
+#[derive(Clone, Debug)]
+pub struct GenomeDiff {
+	pub left: Side,
+	pub right: Side,
+}
+
+fn show(value: f32) -> String {
+	if value.fract() == 0. {
+		format!("{:.0}", value)
+	} else {
+		format!("{:.2}", value)
+	}
+}
+
+// minions and spores while alive, and what the lineage remembers of them after
+pub fn dna_of(world: &world::World, id: Id) -> Option<Dna> {
+	match id.type_of() {
+		AgentType::Minion | AgentType::Spore => world
+			.agent(id)
+			.map(|agent| agent.dna().clone())
+			.or_else(|| world.lineage().get(id).map(|record| record.dna.clone())),
+		_ => None,
+	}
+}
+
+pub fn diff(world: &world::World, left: Id, right: Id) -> Result<GenomeDiff, String> {
+	let side = |id: Id| -> Result<Side, String> {
+		let dna = dna_of(world, id).ok_or_else(|| format!("No genome known for {}", id))?;
+		let plan = BodyPlan::of(&world.develop_minion(&dna));
+		Ok(Side { id, dna, plan })
+	};
+	Ok(GenomeDiff {
+		left: side(left)?,
+		right: side(right)?,
+	})
+}
+
+impl GenomeDiff {
+	pub fn len(&self) -> usize { self.left.dna.len().max(self.right.dna.len()) }
+
+	// a gene is a byte, as in the genealogy
+	pub fn is_changed(&self, gene: usize) -> bool { self.left.dna.get(gene) != self.right.dna.get(gene) }
+
+	pub fn changed(&self) -> usize { genealogy::changed_genes(&self.left.dna, &self.right.dna) }
+
+	// the traits that differ, as they go from the right to the left
+	pub fn consequences(&self) -> Vec<String> {
+		self.left
+			.plan
+			.traits
+			.iter()
+			.zip(self.right.plan.traits.iter())
+			.filter(|&(left, right)| (left.1 - right.1).abs() > 1e-3)
+			.map(|(left, right)| format!("{} {} \u{2192} {}", left.0, show(right.1), show(left.1)))
+			.collect()
+	}
+}
@@ -0,0 +1,173 @@
+use super::main::make_resource_loader;
+use super::*;
+use backend::harness::Stats;
+use frontend::input::Key::*;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::XorShiftRng;
+
+// as in the headless mode, the viewport only matters to the level of detail of the AI
+const WIDTH: u32 = 1024;
+const HEIGHT: u32 = 1024;
+
+// what a player could press, leaving out quitting and the keys that reload or write to disk
+const FUZZ_KEYS: &[input::Key] = &[
+	W, A, S, D, E, P, X, Z, C, G, H, K, L, B, V, N0, N1, Plus, Minus, F1, F2, F3, F4, F10, Backtick, LShift, LCtrl,
+	LAlt, MouseLeft, MouseRight, MouseMiddle, MouseScrollUp, MouseScrollDown,
+];
+
+// the app without a window, stepped by hand at the frame rate of the headless mode: what the sweeps and the fuzzer
+// drive, through the console and the input as a player would, where rust_oids::backend::harness only steps a world
+pub struct AppHarness {
+	app: App,
+	ticks: usize,
+}
+
+impl AppHarness {
+	// the config is read from the given folder and telemetry and saves go below it, a scratch folder keeps runs apart
+	pub fn new(config_home: &path::Path) -> Self {
+		let res = make_resource_loader(config_home);
+		let mut app = App::new(
+			WIDTH,
+			HEIGHT,
+			VIEW_SCALE_BASE,
+			config_home.to_owned(),
+			&res,
+			DEFAULT_MINION_GENE_POOL_FILE,
+			None,
+			SystemMode::Batch,
+		);
+		app.init();
+		AppHarness { app, ticks: 0 }
+	}
+
+	// the walls, zones, portals, feeders and probes of a scenario saved from the editor
+	pub fn with_scenario(config_home: &path::Path, scenario: &path::Path) -> Result<Self, String> {
+		let mut harness = Self::new(config_home);
+		harness.app.load_scenario(scenario)?;
+		Ok(harness)
+	}
+
+	pub fn step(&mut self, ticks: usize) -> Stats {
+		for _ in 0..ticks {
+			self.app.simulate(seconds(FRAME_TIME_TARGET));
+			self.ticks += 1;
+		}
+		Stats::of(self.app.simulation.world(), self.ticks)
+	}
+
+	// everything random in the simulation follows from the seed from here on
+	pub fn reseed(&mut self, seed: u32) { self.app.simulation.world_mut().reseed(seed); }
+
+	// a random but valid mix of keys, mouse moves and app events, the same for the same seed, with a step of the
+	// simulation after each: anything that panics on a stale id or an empty world shows here
+	pub fn fuzz(&mut self, seed: u32, steps: usize) {
+		self.reseed(seed);
+		let mut rng: XorShiftRng = SeedableRng::from_seed([seed, !seed, 0x5851_f42d, 1]);
+		let extent = self.app.simulation.world().extent;
+		for _ in 0..steps {
+			let mut position = || {
+				Position::new(
+					extent.min.x + rng.next_f32() * (extent.max.x - extent.min.x),
+					extent.min.y + rng.next_f32() * (extent.max.y - extent.min.y),
+				)
+			};
+			let (a, b) = (position(), position());
+			// ids of agents long gone are as likely as live ones
+			let id = rng.gen_range(0, self.app.simulation.world().agents(agent::AgentType::Minion).len() * 2 + 2) << 8;
+			let events = [
+				Event::NewMinion(a),
+				Event::RandomizeMinion(a),
+				Event::PickMinion(a),
+				Event::SelectMinion(id),
+				Event::Quarantine(id),
+				Event::ObjectiveMet(id),
+				Event::InterestingMoment(world::moment::Moment::Population(id % 100, (id >> 8) % 100)),
+				Event::DeselectAll,
+				Event::ToggleFreezeSelected,
+				Event::TogglePossess,
+				Event::ToggleGenealogy,
+				Event::StartChallenge(id % 4),
+				Event::FocusRelative(id),
+				Event::DiffGenomes(id, None),
+				Event::CloseGenomeDiff,
+				Event::SpawnPreview,
+				Event::ClosePreview,
+				Event::ToggleGenomeEditor,
+				Event::ShowGenes(id % 72),
+				Event::EditGene(id % 72, (id >> 8) as u8),
+				Event::PickCandidate(id % 4),
+				Event::Rebreed,
+				Event::CloseBreeding,
+				Event::NewProbe(a, b),
+				Event::Measure(a, b),
+				Event::EndMeasure(a, b),
+				Event::EditDrag(a, b),
+				Event::EndEditDrag(a, b),
+				Event::PrimaryTrigger(rng.next_f32(), 1.),
+				Event::VectorThrust(Some(b - a), VectorDirection::LookAt(b)),
+				Event::CamDash(b - a),
+				Event::NextSpeedFactor,
+				Event::PrevSpeedFactor,
+			];
+			let event = *rng.choose(&events).unwrap();
+			self.app.interact(event);
+
+			let key = *rng.choose(FUZZ_KEYS).unwrap();
+			let state = if rng.gen() { input::State::Down } else { input::State::Up };
+			self.app.on_input_event(&input::Event::Key(state, key));
+			let cursor = Position::new(rng.gen_range(0., WIDTH as f32), rng.gen_range(0., HEIGHT as f32));
+			self.app.on_input_event(&input::Event::Mouse(cursor));
+			self.app.update_input::<DefaultController>(seconds(FRAME_TIME_TARGET));
+			self.step(1);
+		}
+	}
+
+	pub fn console(&mut self, line: &str) -> Result<String, String> {
+		let command = console::parse(&self.app.strings, line)?;
+		self.app.execute(command)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SEEDS: &[u32] = &[1, 2, 3, 0xdead_beef];
+	// three seconds of simulation each, the debug build being slow: the --fuzz option runs for longer
+	const STEPS: usize = 180;
+
+	fn find_nan(world: &world::World) -> Option<String> {
+		for swarm in world.swarms().values() {
+			for agent in swarm.agents().values() {
+				if !agent.state.energy().is_finite() {
+					return Some(format!("the energy of {}", agent.id()));
+				}
+				for segment in agent.segments() {
+					let position = segment.transform.position;
+					let velocity = segment.motion.velocity;
+					if !(position.x.is_finite() && position.y.is_finite() && velocity.x.is_finite() && velocity.y.is_finite())
+					{
+						return Some(format!("the motion of {}", agent.id()));
+					}
+				}
+			}
+		}
+		None
+	}
+
+	#[test]
+	fn fuzzing_neither_panics_nor_breeds_nans() {
+		for seed in SEEDS {
+			let config_home = env::temp_dir().join(FUZZ_CONFIG_HOME).join(format!("test-{}", seed));
+			// what an earlier run left there would change how this one goes
+			let _ = fs::remove_dir_all(&config_home);
+			let mut harness = AppHarness::new(&config_home);
+			harness.fuzz(*seed, STEPS);
+			if let Some(nan) = find_nan(harness.app.simulation.world()) {
+				panic!("NaN in {} after fuzzing {} steps from seed {}", nan, STEPS, seed);
+			}
+			let _ = fs::remove_dir_all(&config_home);
+		}
+	}
+}
@@ -0,0 +1,99 @@
+use core::resource::ResourceLoader;
+use serde_json;
+use std::collections::HashMap;
+use std::env;
+
+// as it reads in English, and what a key missing from a translation falls back to
+const ENGLISH: &str = include_str!("../../resources/locale/en.json");
+
+// The text of the HUD, the inspector and the console, by key, from locale/<code>.json in the resources
+#[derive(Clone, Debug)]
+pub struct Strings {
+	locale: String,
+	table: HashMap<String, String>,
+}
+
+impl Default for Strings {
+	fn default() -> Self {
+		Strings {
+			locale: "en".to_owned(),
+			table: serde_json::from_str(ENGLISH).expect("Invalid built-in locale/en.json"),
+		}
+	}
+}
+
+// the language of LC_ALL, LC_MESSAGES or LANG, as in it_IT.UTF-8
+fn system_locale() -> String {
+	["LC_ALL", "LC_MESSAGES", "LANG"]
+		.iter()
+		.filter_map(|name| env::var(name).ok())
+		.find(|value| !value.is_empty())
+		.and_then(|value| value.split(|c| c == '_' || c == '.' || c == '@').next().map(str::to_lowercase))
+		.filter(|language| !language.is_empty() && language != "c" && language != "posix")
+		.unwrap_or_else(|| "en".to_owned())
+}
+
+impl Strings {
+	// an empty locale is the system's
+	pub fn load<R>(res: &R, locale: &str) -> Self
+	where R: ResourceLoader<u8> {
+		let locale = if locale.is_empty() { system_locale() } else { locale.to_owned() };
+		let mut strings = Strings::default();
+		if locale == strings.locale {
+			return strings;
+		}
+		let file = format!("locale/{}.json", locale);
+		let translation = res
+			.load(&file)
+			.map_err(|e| e.to_string())
+			.and_then(|bytes| serde_json::from_slice::<HashMap<String, String>>(&bytes).map_err(|e| e.to_string()));
+		match translation {
+			Ok(translation) => {
+				for key in translation.keys().filter(|key| !strings.table.contains_key(*key)) {
+					warn!("Unknown key {} in {}", key, file);
+				}
+				strings.table.extend(translation);
+				strings.locale = locale;
+			}
+			Err(e) => warn!("No translation for {} in {}, the text stays in English: {}", locale, file, e),
+		}
+		strings
+	}
+
+	pub fn locale(&self) -> &str { &self.locale }
+
+	// the key itself if nobody wrote the text
+	pub fn get<'a>(&'a self, key: &'a str) -> &'a str { self.table.get(key).map_or(key, |text| text.as_str()) }
+
+	// with each {} replaced by the next argument
+	pub fn fill(&self, key: &str, args: &[&str]) -> String {
+		let mut pieces = self.get(key).split("{}");
+		let mut text = pieces.next().unwrap_or_default().to_owned();
+		for (piece, arg) in pieces.zip(args.iter().chain(::std::iter::repeat(&""))) {
+			text.push_str(arg);
+			text.push_str(piece);
+		}
+		text
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TRANSLATIONS: &[(&str, &str)] = &[("it", include_str!("../../resources/locale/it.json"))];
+
+	#[test]
+	fn translations_have_the_keys_of_the_english_text_and_no_others() {
+		let english = Strings::default();
+		for &(locale, text) in TRANSLATIONS {
+			let translation: HashMap<String, String> = serde_json::from_str(text).unwrap();
+			for key in english.table.keys() {
+				assert!(translation.contains_key(key), "{} has no {}", locale, key);
+			}
+			for key in translation.keys() {
+				assert!(english.table.contains_key(key), "{} has an unknown {}", locale, key);
+			}
+		}
+	}
+}
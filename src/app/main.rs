@@ -1,7 +1,9 @@
 use frontend::audio::{self, SoundSystem};
 use frontend::gfx_window_glutin;
+use frontend::input;
 use frontend::input::EventMapper;
 use frontend::input::GamepadEventLoop;
+use frontend::input::MidiEventLoop;
 use frontend::render;
 use frontend::render::{formats, Overlay, Renderer};
 use frontend::ui;
@@ -11,14 +13,19 @@ use conrod;
 
 use core::clock::{seconds, Hourglass, SecondsValue, SystemTimer};
 use core::math::Directional;
+use core::resource;
 use core::resource::filesystem::ResourceLoader;
 use core::resource::filesystem::ResourceLoaderBuilder;
 use ctrlc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use app;
 use app::capture::Capture;
+use app::profile::Profile;
+use app::share::Share;
 use app::constants::*;
 use glutin;
 use glutin::GlContext;
@@ -40,6 +47,17 @@ pub fn make_resource_loader(config_home: &path::Path) -> ResourceLoader {
 		).build()
 }
 
+fn load_caption_font(res: &resource::ResourceLoader<u8>) -> Option<conrod::text::Font> {
+	let font = res
+		.load(CAPTION_FONT)
+		.ok()
+		.and_then(|bytes| conrod::text::FontCollection::from_bytes(bytes).into_font());
+	if font.is_none() {
+		warn!("Could not load {}, captures will have no captions", CAPTION_FONT);
+	}
+	font
+}
+
 pub fn main_loop(
 	minion_gene_pool: &str,
 	config_home: path::PathBuf,
@@ -76,11 +94,11 @@ pub fn main_loop(
 			&events_loop,
 		);
 	let (w, h, _, _) = frame_buffer.get_dimensions();
-	let mut capture = Capture::init(&window);
-
 	let mut encoder = factory.create_command_buffer().into();
 
 	let res = make_resource_loader(&config_home);
+	let mut capture = Capture::init(&window, load_caption_font(&res));
+	let mut share = Share::default();
 
 	let renderer = &mut render::ForwardRenderer::new(&mut factory, &mut encoder, &res, &frame_buffer).unwrap();
 	let mapper = app::WinitEventMapper::new();
@@ -94,23 +112,50 @@ pub fn main_loop(
 		&res,
 		minion_gene_pool,
 		world_file,
+		app::SystemMode::Interactive,
 	);
 
 	let mut ui = ui::conrod_ui::Ui::new(&res, &mut factory, &frame_buffer, f64::from(window.hidpi_factor()))
 		.expect("Unable to create UI");
 
 	let audio = audio::ThreadedSoundSystem::new(audio_device).expect("Failure in audio initialization");
+	capture.record_audio(audio.track());
 	let mut no_audio = ui::NullAlertPlayer::new();
 	let mut audio_alert_player = audio::ThreadedAlertPlayer::new(audio);
-	app.init(app::SystemMode::Interactive);
+	app.init();
+	if let Some(profile_file) = Profile::file() {
+		app.load_profile(profile_file);
+	}
+	let mut maybe_midi = app.midi_port().and_then(|port| MidiEventLoop::new(&port));
+
+	// wakes the event loop up now and then while it waits for input, so that the gamepad and the toasts still update
+	let wakeup = events_loop.create_proxy();
+	thread::spawn(move || {
+		while wakeup.wakeup().is_ok() {
+			thread::sleep(Duration::from_millis(IDLE_WAKEUP_MILLIS));
+		}
+	});
 
 	'main: loop {
         maybe_gamepad = maybe_gamepad.and_then(|mut gamepad| {
             gamepad.poll_events(|event| app.on_input_event(&event));
             Some(gamepad)
         });
+		if let Some(ref mut midi) = maybe_midi {
+			midi.poll_events(|event| app.on_input_event(&event));
+		}
 
-		events_loop.poll_events(|event| {
+		let mut events = Vec::new();
+		events_loop.poll_events(|event| events.push(event));
+		// nothing moves while paused and idle, so wait for input rather than spinning
+		if events.is_empty() && app.is_idle() && !capture.enabled() && !capture.streaming() {
+			events_loop.run_forever(|event| {
+				events.push(event);
+				winit::ControlFlow::Break
+			});
+		}
+
+		for event in events {
 			if app.has_ui_overlay() {
 				if let Some(event) = conrod::backend::winit::convert_event(event.clone(), window.window()) {
 					ui.push_event(event);
@@ -123,6 +168,7 @@ pub fn main_loop(
 						renderer.resize_to(&frame_buffer).expect("Unable to resize window");
 						ui.resize_to(&frame_buffer).expect("Unable to resize window");
 						app.on_resize(new_width, new_height);
+						capture.resize(new_width, new_height);
 					}
 					WindowEvent::Closed => app.quit(),
 					WindowEvent::KeyboardInput {
@@ -133,21 +179,32 @@ pub fn main_loop(
 						..
 					} => renderer.rebuild().unwrap(),
 					e => if let Some(i) = mapper.translate(&e) {
-						app.on_input_event(&i)
+						match i {
+							input::Event::Key(input::State::Down, _) if ui.is_capturing_keyboard() => {}
+							i => app.on_input_event(&i),
+						}
 					},
 				}
 			}
-		});
+		}
 
 		capture.enable(app.is_capturing());
+		capture.stream_to(app.stream());
+		capture.set_memory_budget(app.capture_memory_budget());
+		capture.set_clip_seconds(app.clip_seconds());
+		app.set_capture_memory(capture.queued_bytes());
 
 		if !app.is_running() {
 			capture.stop();
 			app.save_world_to_file();
+			if let Err(e) = app.save_profile() {
+				error!("{}", e);
+			}
+			app.write_report();
 			break 'main;
 		}
 
-		let speed_factor = app.speed_factors.get();
+		let speed_factor = app.speed_factor();
 		let frame_update = if capture.enabled() || speed_factor > 5.0 {
 			// forces 60Hz simulation for frame capture and fast forward
 			app.update_with_quantum(Some(FRAME_TIME_TARGET))
@@ -166,9 +223,10 @@ pub fn main_loop(
 		// draw the scene
 		app.paint(renderer);
 		// post-render effects and tone mapping
-		renderer.resolve_frame_buffer();
+		renderer.resolve_frame_buffer(&app.projection(), app.render_quality());
 
 		if app.has_ui_overlay() {
+			ui.set_scale(&frame_buffer, app.ui_scale()).expect("Unable to scale the UI");
 			let screen = ui::Screen::Main(frame_update);
 			renderer.overlay(|_, encoder| {
 				ui.update_and_draw_screen(&screen, encoder);
@@ -178,6 +236,9 @@ pub fn main_loop(
 			for app_event in ui.drain_app_events() {
 				app.interact(app_event)
 			}
+			for text_edit in ui.drain_text_edits() {
+				app.on_text_edit(text_edit)
+			}
 		}
 
 		if speed_factor < 10.0 {
@@ -188,7 +249,21 @@ pub fn main_loop(
 
 		// push the commands
 		renderer.end_frame(&mut device);
-		capture.screen_grab();
+		let (frame_width, frame_height, _, _) = frame_buffer.get_dimensions();
+		share.publish(app.share(), u32::from(frame_width), u32::from(frame_height));
+		capture.screen_grab(app.capture_rect(), app.capture_caption());
+		if let Some(name) = app.take_clip_request() {
+			capture.save_clip(&name);
+		}
+		if let Some(thumbnail) = app.next_report_thumbnail() {
+			capture.thumbnail(&thumbnail, REPORT_THUMBNAIL_WIDTH);
+		}
+		if app.is_summary_due() {
+			let thumbnail = app
+				.thumbnail_path()
+				.filter(|path| capture.thumbnail(path, WEBHOOK_THUMBNAIL_WIDTH));
+			app.post_summary(thumbnail.as_ref().map(|path| path.as_path()));
+		}
 
 		window.swap_buffers().expect("swap_buffers() failed");
 		renderer.cleanup(&mut device);
@@ -208,9 +283,10 @@ pub fn main_loop_headless(minion_gene_pool: &str, config_home: path::PathBuf, wo
 		&res,
 		minion_gene_pool,
 		world_file,
+		app::SystemMode::Batch,
 	);
 	let mut no_audio = ui::NullAlertPlayer::new();
-	app.init(app::SystemMode::Batch);
+	app.init();
 
 	let running = Arc::new(AtomicBool::new(true));
 	let r = running.clone();
@@ -232,6 +308,7 @@ pub fn main_loop_headless(minion_gene_pool: &str, config_home: path::PathBuf, wo
 		if !running.load(Ordering::SeqCst) {
 			eprintln!("Interrupted, exiting");
 			app.save_world_to_file();
+			app.write_report();
 			break 'main;
 		}
 		// update and measure
@@ -240,6 +317,10 @@ pub fn main_loop_headless(minion_gene_pool: &str, config_home: path::PathBuf, wo
 			app.save_world_to_file();
 		}
 
+		if app.is_summary_due() {
+			app.post_summary(None);
+		}
+
 		app.play_alerts(&mut no_audio);
 		if output_hourglass.flip_if_expired(&wall_clock) {
 			info!(
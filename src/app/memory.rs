@@ -0,0 +1,102 @@
+use app::constants::*;
+use backend::world;
+use core::geometry::Position;
+use std::mem;
+
+// Roughly what grows in memory over a long run, in bytes
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MemoryUsage {
+	// the agents and the particles
+	pub world: usize,
+	pub lineage: usize,
+	// the history of every probe
+	pub probes: usize,
+	// the samples and the events of the session report
+	pub report: usize,
+	// the frames waiting for the encoders
+	pub capture: usize,
+}
+
+impl MemoryUsage {
+	pub fn total(&self) -> usize { self.world + self.lineage + self.probes + self.report + self.capture }
+
+	pub fn parts(&self) -> [(&'static str, usize); 5] {
+		[
+			("world", self.world),
+			("lineage", self.lineage),
+			("probes", self.probes),
+			("report", self.report),
+			("capture", self.capture),
+		]
+	}
+}
+
+// In megabytes, zero for no limit. A part over its budget forgets its oldest records, and over the total
+// all of them do, the world aside; the capture drops frames instead
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct MemoryBudget {
+	pub total: usize,
+	pub lineage: usize,
+	pub probes: usize,
+	pub report: usize,
+	pub capture: usize,
+}
+
+impl Default for MemoryBudget {
+	fn default() -> Self {
+		MemoryBudget {
+			total: MEMORY_DEFAULT_BUDGET,
+			lineage: 0,
+			probes: 0,
+			report: 0,
+			capture: 0,
+		}
+	}
+}
+
+impl MemoryBudget {
+	pub const PARTS: &'static [&'static str] = &["total", "lineage", "probes", "report", "capture"];
+
+	pub fn part_mut(&mut self, name: &str) -> Option<&mut usize> {
+		match name {
+			"total" => Some(&mut self.total),
+			"lineage" => Some(&mut self.lineage),
+			"probes" => Some(&mut self.probes),
+			"report" => Some(&mut self.report),
+			"capture" => Some(&mut self.capture),
+			_ => None,
+		}
+	}
+
+	// how much of a part to keep to fit both its own budget and the total, none if it fits already
+	pub fn keep(&self, used: usize, megabytes: usize, total_used: usize) -> Option<f64> {
+		fn fit(used: usize, megabytes: usize) -> Option<f64> {
+			let bytes = megabytes * MEGABYTE;
+			if megabytes > 0 && used > bytes {
+				Some(bytes as f64 / used as f64)
+			} else {
+				None
+			}
+		}
+		if used == 0 {
+			return None;
+		}
+		match (fit(used, megabytes), fit(total_used, self.total)) {
+			(None, None) => None,
+			// with some slack, not to trim again at the next check
+			(own, total) => Some(own.unwrap_or(1.).min(total.unwrap_or(1.)) * MEMORY_TRIM_SLACK),
+		}
+	}
+}
+
+pub fn world_footprint(world: &world::World) -> usize {
+	let agents: usize = world
+		.swarms()
+		.values()
+		.flat_map(|swarm| swarm.agents().values())
+		.map(|agent| agent.footprint())
+		.sum();
+	let trails: usize = world.particles().iter().map(|particle| particle.trail().len()).sum();
+	agents + world.particles().len() * mem::size_of::<world::particle::Particle>() + trails * mem::size_of::<Position>()
+}
@@ -0,0 +1,43 @@
+use frontend::input::Key;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Parameter {
+	// the time scale, from a tenth to a hundred times
+	Speed,
+	// how many bits of the spores' genomes flip, the middle is the usual
+	Mutation,
+	// the ambient light, the middle is as bright as usual
+	Light,
+}
+
+// A MIDI controller played like an instrument: pads press keys, knobs turn parameters
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct MidiSettings {
+	pub enabled: bool,
+	// the first input with this in its name, any if empty, opened at start
+	pub port: String,
+	// note numbers to keys, as if typed
+	pub pads: BTreeMap<u8, Key>,
+	// controller numbers to what they turn
+	pub knobs: BTreeMap<u8, Parameter>,
+}
+
+impl Default for MidiSettings {
+	fn default() -> Self {
+		MidiSettings {
+			enabled: false,
+			port: String::new(),
+			pads: BTreeMap::new(),
+			knobs: BTreeMap::new(),
+		}
+	}
+}
+
+impl MidiSettings {
+	pub fn pad_map(&self) -> Vec<(u8, Key)> { self.pads.iter().map(|(note, key)| (*note, *key)).collect() }
+
+	pub fn knob(&self, control: u8) -> Option<Parameter> { self.knobs.get(&control).cloned() }
+}
@@ -1,8 +1,10 @@
 use app::constants::*;
-use backend::messagebus::{Inbox, Message, Outbox, PubSub, ReceiveDrain, Whiteboard};
+use backend::messagebus::{Inbox, Message, ReceiveDrain};
 use backend::obj;
 use backend::obj::*;
+use backend::raster;
 use backend::systems;
+use backend::simulation::Simulation;
 use backend::world;
 use backend::world::agent;
 use backend::world::segment;
@@ -17,40 +19,81 @@ use core::math::Relative;
 use core::math::Smooth;
 use core::resource::ResourceLoader;
 use core::util::Cycle;
+use core::view::ViewTransform;
 use core::view::Viewport;
 use core::view::WorldTransform;
 use dirs;
+use fs2;
 use frontend::input;
 use frontend::render;
 use frontend::ui;
 use getopts::Options;
 use num;
-use rayon::prelude::*;
+use rand;
+use std::env;
 use std::ffi::OsStr;
 use std::fs;
+use std::io;
 use std::path;
 
+pub use backend::systems::SystemMode;
+pub use self::capture::AudioTrack;
+pub use self::console::ConsoleView;
 pub use self::controller::DefaultController;
 pub use self::controller::InputController;
 pub use self::events::Event;
 use self::events::VectorDirection;
 pub use self::winit_event::WinitEventMapper;
 pub use self::winit_event::WinitEventMapper as EventMapper;
+use rust_oids::events;
 use std::ffi::OsString;
 use std::fmt::Debug;
 use std::iter::Iterator;
 use std::process;
-use std::sync::Arc;
-use std::sync::RwLock;
+use std::rc::Rc;
 
+mod accessibility;
+mod adaptive;
+mod branch;
+mod breeding;
+mod caption;
 //#[cfg(feature="capture")]
 mod capture;
+mod challenge;
+mod check;
+mod comparison;
 
+mod config;
+mod console;
 mod controller;
-mod events;
+mod diagnostics;
+mod editor;
+mod genealogy;
+mod genome_diff;
+mod harness;
+mod l10n;
+mod memory;
 mod main;
+mod midi;
+mod moments;
+mod mouse;
+mod osc;
 mod paint;
+mod preview;
+mod profile;
+mod projection;
+mod queue;
+mod quick_load;
+mod reload;
+mod report;
+mod share;
+mod stream;
+mod sweep;
+mod telemetry;
+mod warning;
+mod webhook;
 mod winit_event;
+mod world_diff;
 
 pub mod constants;
 
@@ -68,8 +111,111 @@ pub fn run(args: &[OsString]) {
 	opt.optopt("w", "width", "Window width", "1024");
 	opt.optopt("h", "height", "Window height", "1024");
 	opt.optopt("a", "audio_device", "Audio device index (portaudio)", "0");
+	opt.optmulti("c", "check", "Validate a config or scenario file and exit, can be repeated", "config.json");
+	opt.optopt("", "fuzz", "Feed random input to a headless world from the seed, and exit", "1");
+	opt.optopt(
+		"",
+		"sweep",
+		"Run every combination of a sweep file headless, write the results to CSV and exit",
+		"sweep.json",
+	);
+	opt.optopt("", "queue", "Spread the sweep as jobs in a folder shared with workers", "/mnt/shared/sweep");
+	opt.optopt(
+		"",
+		"worker",
+		"Run sweep jobs from a shared folder or a job server until none are left",
+		"/mnt/shared/sweep",
+	);
+	opt.optmulti("", "diff", "Report how the world changed between two saves, given twice, and exit", "save.json");
+	opt.optflag("", "diff-json", "Write the report of --diff as JSON");
 	match opt.parse(args) {
 		Ok(options) => {
+			let checked = options.opt_strs("c");
+			if !checked.is_empty() {
+				let mut failures = 0;
+				for file_name in &checked {
+					let problems = check::check(path::Path::new(file_name));
+					for problem in &problems {
+						eprintln!("{}", problem);
+					}
+					if problems.is_empty() {
+						println!("{}: ok", file_name);
+					} else {
+						failures += 1;
+					}
+				}
+				process::exit(if failures > 0 { 1 } else { 0 });
+			}
+			if let Some(seed) = options.opt_str("fuzz") {
+				let seed = seed.parse::<u32>().unwrap_or_else(|_| {
+					eprintln!("Invalid seed: {}", seed);
+					process::exit(1)
+				});
+				// a scratch folder, so that the user's config and saves stay out of it
+				let config_home = env::temp_dir().join(FUZZ_CONFIG_HOME);
+				info!("Fuzzing {} steps from seed {} in {:?}", FUZZ_STEPS, seed, config_home);
+				harness::AppHarness::new(&config_home).fuzz(seed, FUZZ_STEPS);
+				process::exit(0);
+			}
+			if let Some(file_name) = options.opt_str("sweep") {
+				let config_home = env::temp_dir().join(SWEEP_CONFIG_HOME);
+				let result = sweep::Sweep::load(path::Path::new(&file_name))
+					.map_err(|e| format!("Invalid {}: {}", file_name, e))
+					.and_then(|sweep| match options.opt_str("queue") {
+						// this machine works on it too, then waits for the others
+						Some(dir) => {
+							let dir = path::Path::new(&dir);
+							let jobs = queue::submit(dir, &sweep)?;
+							info!("{} jobs queued in {:?}", jobs, dir);
+							queue::Queue::Dir(dir.to_owned()).work(&config_home)?;
+							queue::gather(dir, &sweep).map(|cells| (cells, sweep.output))
+						}
+						None => sweep.run(&config_home).map(|cells| (cells, sweep.output)),
+					});
+				match result {
+					Ok((cells, output)) => println!("{} combinations written to {:?}", cells, output),
+					Err(e) => {
+						eprintln!("{}", e);
+						process::exit(1)
+					}
+				}
+				process::exit(0);
+			}
+			let diffed = options.opt_strs("diff");
+			if !diffed.is_empty() {
+				let result = if diffed.len() == 2 {
+					world_diff::WorldDiff::load(path::Path::new(&diffed[0]), path::Path::new(&diffed[1]))
+				} else {
+					Err("Expected --diff twice, the earlier save first".to_owned())
+				};
+				let report = result.and_then(|diff| {
+					if options.opt_present("diff-json") {
+						diff.to_json()
+					} else {
+						Ok(diff.to_text())
+					}
+				});
+				match report {
+					Ok(report) => println!("{}", report),
+					Err(e) => {
+						eprintln!("{}", e);
+						process::exit(1)
+					}
+				}
+				process::exit(0);
+			}
+			if let Some(location) = options.opt_str("worker") {
+				// one scratch folder for each, so that workers on the same machine stay apart
+				let config_home = env::temp_dir().join(format!("{}-{}", SWEEP_CONFIG_HOME, process::id()));
+				match queue::Queue::parse(&location).work(&config_home) {
+					Ok(jobs) => println!("{} jobs run from {}", jobs, location),
+					Err(e) => {
+						eprintln!("{}", e);
+						process::exit(1)
+					}
+				}
+				process::exit(0);
+			}
 			let pool_file_name = options
 				.free
 				.get(1)
@@ -83,16 +229,21 @@ pub fn run(args: &[OsString]) {
 			let user_home = dirs::home_dir().unwrap_or_else(|| path::PathBuf::from("."));
 			let config_home = user_home.join(CONFIG_DIR_HOME);
 			if !options.opt_present("n") && world_file.is_none() {
-				let mut max_path = None;
-				if let Ok(dir) = fs::read_dir(config_home.join(CONFIG_DIR_SAVED_STATE)) {
-					// get the highest file in lexicographical order
-					// or the first one, if any
-					for entry in dir {
-						let path_name = entry.unwrap().path().to_owned();
-						if path_name.extension().to_owned().and_then(OsStr::to_str) == Some("json")
-							&& max_path.as_ref().map(|m| path_name > *m).unwrap_or(true)
-						{
-							max_path = Some(path_name.clone());
+				// the world last played comes first, it may be on a branch
+				let mut max_path = profile::Profile::file()
+					.and_then(|file| profile::Profile::load(&file).ok())
+					.and_then(|profile| profile.recent.into_iter().find(|recent| recent.is_file()));
+				if max_path.is_none() {
+					if let Ok(dir) = fs::read_dir(config_home.join(CONFIG_DIR_SAVED_STATE)) {
+						// get the highest file in lexicographical order
+						// or the first one, if any
+						for entry in dir {
+							let path_name = entry.unwrap().path().to_owned();
+							if path_name.extension().to_owned().and_then(OsStr::to_str) == Some("json")
+								&& max_path.as_ref().map(|m| path_name > *m).unwrap_or(true)
+							{
+								max_path = Some(path_name.clone());
+							}
 						}
 					}
 				}
@@ -129,133 +280,10 @@ pub fn run(args: &[OsString]) {
 	}
 }
 
-#[derive(Default)]
-struct SendSystem<T>
-where T: systems::System {
-	ptr: Arc<RwLock<T>>,
-}
-
-impl<T> SendSystem<T>
-where T: systems::System
-{
-	fn boxed(ptr: Arc<RwLock<T>>) -> Box<Self> { Box::new(SendSystem { ptr }) }
-}
-
-impl<T> systems::System for SendSystem<T>
-where T: systems::System
-{
-	fn attach(&mut self, bus: &mut PubSub) { self.ptr.write().unwrap().attach(bus) }
-	fn init(&mut self, world: &world::World) { self.ptr.write().unwrap().init(world) }
-	fn clear(&mut self) { self.ptr.write().unwrap().clear() }
-	fn register(&mut self, agent: &world::agent::Agent) { self.ptr.write().unwrap().register(agent) }
-	fn unregister(&mut self, agent: &world::agent::Agent) { self.ptr.write().unwrap().unregister(agent) }
-
-	fn step(&mut self, world: &world::World, dt: Seconds) { self.ptr.write().unwrap().step(world, dt) }
-	fn apply(&self, world: &mut world::World, outbox: &Outbox) { self.ptr.read().unwrap().apply(world, outbox) }
-}
-
-// unsafe?
-unsafe impl<T> Send for SendSystem<T> where T: systems::System {}
-
-#[derive(Copy, Clone)]
-pub enum SystemMode {
-	Interactive,
-	Batch,
-}
-
-impl Default for SystemMode {
-	fn default() -> Self { SystemMode::Interactive }
-}
-
-#[derive(Default)]
-pub struct Systems {
-	mode: SystemMode,
-	physics: Arc<RwLock<systems::PhysicsSystem>>,
-	animation: Arc<RwLock<systems::AnimationSystem>>,
-	game: Arc<RwLock<systems::GameSystem>>,
-	ai: Arc<RwLock<systems::AiSystem>>,
-	alife: Arc<RwLock<systems::AlifeSystem>>,
-	particle: Arc<RwLock<systems::ParticleSystem>>,
-}
-
-impl Systems {
-	fn set_mode(&mut self, mode: SystemMode) { self.mode = mode; }
-
-	fn systems(&mut self) -> Vec<Box<(systems::System + Send)>> {
-		match self.mode {
-			SystemMode::Interactive => vec![
-				SendSystem::boxed(self.physics.clone()),
-				SendSystem::boxed(self.animation.clone()),
-				SendSystem::boxed(self.particle.clone()),
-				SendSystem::boxed(self.game.clone()),
-				SendSystem::boxed(self.ai.clone()),
-				SendSystem::boxed(self.alife.clone()),
-			],
-			SystemMode::Batch => vec![
-				SendSystem::boxed(self.physics.clone()),
-				SendSystem::boxed(self.game.clone()),
-				SendSystem::boxed(self.ai.clone()),
-				SendSystem::boxed(self.alife.clone()),
-			],
-		}
-	}
-
-	pub fn unregister(&mut self, agents: &[world::agent::Agent]) {
-		if !agents.is_empty() {
-			self.systems().par_iter_mut().for_each(|system| {
-				for agent in agents {
-					system.unregister(agent)
-				}
-			})
-		}
-	}
-
-	pub fn register(&mut self, agents: &[world::agent::Agent]) {
-		if !agents.is_empty() {
-			self.systems().par_iter_mut().for_each(|system| {
-				for agent in agents {
-					system.register(&agent)
-				}
-			})
-		}
-	}
-
-	fn init(&mut self, world: &world::World) {
-		for system in &mut self.systems() {
-			system.init(world);
-		}
-	}
-
-	fn clear(&mut self) {
-		for system in &mut self.systems() {
-			system.clear();
-		}
-	}
-
-	fn attach(&mut self, bus: &mut PubSub) {
-		for system in &mut self.systems() {
-			system.attach(bus);
-		}
-	}
-
-	fn for_each_read(
-		&mut self,
-		world: &mut world::World,
-		outbox: &Outbox,
-		apply: &(Fn(&mut systems::System, &mut world::World, &Outbox) + Sync),
-	)
-	{
-		self.systems().iter_mut().for_each(|r| apply(&mut (**r), world, outbox))
-	}
-
-	fn for_each_par_write(&mut self, world: &world::World, apply: &(Fn(&mut systems::System, &world::World) + Sync)) {
-		self.systems().par_iter_mut().for_each(|r| apply(&mut (**r), world))
-	}
-}
-
 bitflags! {
 	pub struct DebugFlags: u32 {
 		const DEBUG_TARGETS = 0x1;
+		const DEBUG_DIAGNOSTICS = 0x2;
 	}
 }
 
@@ -274,27 +302,72 @@ pub struct App {
 	is_running: bool,
 	is_paused: bool,
 	is_capturing: bool,
+	is_streaming: bool,
+	capture_region: capture::CaptureRegion,
 	// interactions: Vec<Event>,
 	//
 	camera: math::Inertial<f32>,
 	is_camera_tracking: bool,
 	lights: Cycle<Rgba>,
-	backgrounds: Cycle<Rgba>,
+	backgrounds: Cycle<render::Background>,
 	speed_factors: Cycle<SpeedFactor>,
+	// turned by a MIDI knob, until the speed keys are pressed again
+	speed_knob: Option<SpeedFactor>,
+	light_gain: f32,
 	//
-	world: world::World,
-	bus: PubSub,
+	simulation: Simulation,
 	reply_inbox: Inbox,
 	alert_inbox: Inbox,
-	systems: Systems,
 	//
-	#[allow(unused)]
 	config_home: path::PathBuf,
 	saved_state_dir: path::PathBuf,
 	last_saved: Option<path::PathBuf>,
+	timeline: branch::Timeline,
+	config: config::Config,
+	profile: profile::Profile,
+	// none for the headless runs and the harness, which keep to the defaults
+	profile_file: Option<path::PathBuf>,
+	telemetry: telemetry::Telemetry,
+	osc: osc::Osc,
+	warnings: warning::Warnings,
+	next_disk_check: SecondsValue,
+	next_summary: SecondsValue,
+	report: report::Report,
+	next_report_thumbnail: SecondsValue,
+	memory: memory::MemoryUsage,
+	next_memory_check: SecondsValue,
+	diagnostics: diagnostics::Diagnostics,
+	moments: world::moment::Detector,
+	// of the wall clock, no clip or checkpoint for another moment before then
+	next_moment_action: SecondsValue,
+	clip_request: Option<String>,
 	//
 	debug_flags: DebugFlags,
 	has_ui_overlay: bool,
+	has_shadows: bool,
+	has_grid: bool,
+	measurement: Option<(Position, Position)>,
+	has_annotations: bool,
+	editing_annotation: Option<usize>,
+	possessed: Option<Id>,
+	console: console::Console,
+	adaptive: adaptive::Governor,
+	quick_load: Option<Vec<quick_load::Entry>>,
+	challenges: Option<Vec<challenge::Entry>>,
+	challenge: Option<challenge::Attempt>,
+	comparison: Option<Rc<comparison::Comparison>>,
+	// the agent the genealogy browser is on
+	genealogy: Option<Id>,
+	genome_diff: Option<Rc<genome_diff::GenomeDiff>>,
+	preview: Option<Rc<preview::Preview>>,
+	// the first gene of the page the genome editor is on, over the preview
+	genome_editor: Option<usize>,
+	breeding: Option<Rc<breeding::Workbench>>,
+	strings: Rc<l10n::Strings>,
+	editor: Option<editor::Editor>,
+	config_watch: reload::Watch,
+	// the scenario last loaded or saved
+	scenario_watch: Option<reload::Watch>,
 }
 
 pub struct Environment {
@@ -310,6 +383,31 @@ pub struct SimulationUpdate {
 	pub elapsed: Seconds,
 	pub population: usize,
 	pub extinctions: usize,
+	// per second, over the last period
+	pub energy: world::energy::EnergyFlow,
+	// with the game mode on
+	pub score: Option<world::objective::Score>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AgentInspector {
+	pub id: Id,
+	pub energy: f32,
+	pub energy_ratio: f32,
+	pub age: Seconds,
+	pub stage: agent::LifeStage,
+	pub segments: usize,
+	pub resistance: f32,
+	pub is_infected: bool,
+	pub is_frozen: bool,
+	pub is_possessed: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct WorldLabel {
+	// in pixels from the center of the window
+	pub position: Position,
+	pub text: String,
 }
 
 #[derive(Clone, Debug)]
@@ -321,7 +419,24 @@ pub struct FrameUpdate {
 	pub elapsed: Seconds,
 	pub duration_smooth: Seconds,
 	pub fps: f32,
+	pub memory: memory::MemoryUsage,
+	pub diagnostics: Option<diagnostics::Diagnostics>,
 	pub simulation: SimulationUpdate,
+	pub cursor: Option<Position>,
+	pub labels: Vec<WorldLabel>,
+	pub editing: Option<String>,
+	pub inspector: Option<AgentInspector>,
+	pub console: Option<ConsoleView>,
+	pub quick_load: Option<Vec<quick_load::Entry>>,
+	pub challenges: Option<Vec<challenge::Entry>>,
+	pub comparison: Option<Rc<comparison::Comparison>>,
+	pub genealogy: Option<genealogy::GenealogyView>,
+	pub genome_diff: Option<Rc<genome_diff::GenomeDiff>>,
+	pub preview: Option<Rc<preview::Preview>>,
+	pub genome_editor: Option<usize>,
+	pub breeding: Option<Rc<breeding::Workbench>>,
+	pub toasts: Vec<String>,
+	pub strings: Rc<l10n::Strings>,
 }
 
 impl App {
@@ -333,47 +448,72 @@ impl App {
 		resource_loader: &R,
 		minion_gene_pool: &str,
 		world_file: Option<path::PathBuf>,
+		mode: SystemMode,
 	) -> Self
 	where
 		R: ResourceLoader<u8>,
 	{
 		let system_timer = SystemTimer::new();
-		let mut bus = PubSub::new();
-		let alert_inbox = bus.subscribe(Box::new(|e| match *e {
-			Message::Alert(_) => true,
-			Message::Event(_) => true,
-			_ => false,
-		}));
-		let reply_inbox = bus.subscribe(Box::new(|e| match *e {
-			Message::Event(Event::SelectMinion(_)) => true,
-			_ => false,
-		}));
 
+		let config = match config::Config::load(&config_home.join(CONFIG_FILE)) {
+			Ok(config) => config,
+			Err(_) => {
+				info!("No valid {} found, using defaults", CONFIG_FILE);
+				config::Config::default()
+			}
+		};
+		let next_summary = config.webhook.summary_interval;
 		let mut new_world = world::World::new(resource_loader, minion_gene_pool);
+		new_world.set_fitness(config.fitness.clone());
+		new_world.set_mating(config.mating.clone());
+		new_world.set_disease(config.disease.clone());
+		new_world.set_game_mode(config.game.clone());
 		let last_saved = world_file.map(|world_file| {
-			if world::persist::Serializer::load(&world_file, &mut new_world).is_err() {
-				panic!(format!("Could not load {:?}", &world_file));
+			if let Err(e) = world::persist::Serializer::load(&world_file, &mut new_world) {
+				panic!(format!("Could not load {:?}: {}", &world_file, e));
 			}
 			world_file
 		});
+		if let (None, Some(terrain)) = (&last_saved, &config.terrain) {
+			let (walls, resources) = new_world.generate_terrain(terrain);
+			info!("Generated terrain from seed {}: {} walls, {} resources", terrain.seed, walls, resources);
+		}
+
+		let config_watch = reload::Watch::new(&config_home.join(CONFIG_FILE));
+		let saved_state_dir = config_home.join(CONFIG_DIR_SAVED_STATE);
+		let mut simulation = Simulation::with_mode(new_world, config.physics.clone(), mode);
+		simulation.set_chunks_dir(Some(saved_state_dir.join(CHUNKS_DIR)));
+		let alert_inbox = simulation.subscribe(Box::new(|e| match *e {
+			Message::Alert(_) => true,
+			Message::Event(_) => true,
+			_ => false,
+		}));
+		let reply_inbox = simulation.subscribe(Box::new(|e| match *e {
+			Message::Event(Event::SelectMinion(_))
+			| Message::Event(Event::Quarantine(_))
+			| Message::Event(Event::ObjectiveMet(_))
+			| Message::Event(Event::InterestingMoment(_)) => true,
+			_ => false,
+		}));
+		let timeline = branch::Timeline::new(&saved_state_dir, last_saved.as_ref().map(|file| file.as_path()));
+		let strings = Rc::new(l10n::Strings::load(resource_loader, &config.locale));
 
 		App {
 			viewport: Viewport::rect(w, h, scale),
 			zoom: math::exponential_filter(1., 1., VIEW_ZOOM_DURATION),
-			input_state: input::InputState::default(),
+			input_state: Self::init_input_state(&config),
 
 			camera: Self::init_camera(),
 			is_camera_tracking: true,
 			lights: Self::init_lights(),
 			backgrounds: Self::init_backgrounds(),
 			speed_factors: Self::init_speed_factors(),
+			speed_knob: None,
+			light_gain: 1.,
 
-			world: new_world,
-			bus,
+			simulation,
 			alert_inbox,
 			reply_inbox,
-			// subsystems
-			systems: Systems::default(),
 			// runtime and timing
 			simulations_count: 0usize,
 			frame_count: 0usize,
@@ -384,13 +524,53 @@ impl App {
 			is_running: true,
 			is_paused: false,
 			is_capturing: false,
+			is_streaming: false,
+			capture_region: capture::CaptureRegion::default(),
 			// savegame
-			saved_state_dir: config_home.join(CONFIG_DIR_SAVED_STATE),
+			saved_state_dir,
 			config_home,
 			last_saved,
+			timeline,
+			config,
+			profile: profile::Profile::default(),
+			profile_file: None,
+			telemetry: telemetry::Telemetry::default(),
+			osc: osc::Osc::default(),
+			warnings: warning::Warnings::default(),
+			next_disk_check: 0.,
+			next_summary,
+			report: report::Report::default(),
+			next_report_thumbnail: 0.,
+			memory: memory::MemoryUsage::default(),
+			next_memory_check: 0.,
+			diagnostics: diagnostics::Diagnostics::default(),
+			moments: world::moment::Detector::default(),
+			next_moment_action: 0.,
+			clip_request: None,
 			// debug
 			debug_flags: DebugFlags::empty(),
 			has_ui_overlay: true,
+			has_shadows: true,
+			has_grid: false,
+			measurement: None,
+			has_annotations: true,
+			editing_annotation: None,
+			possessed: None,
+			console: console::Console::default(),
+			adaptive: adaptive::Governor::default(),
+			quick_load: None,
+			challenges: None,
+			challenge: None,
+			comparison: None,
+			genealogy: None,
+			genome_diff: None,
+			preview: None,
+			genome_editor: None,
+			breeding: None,
+			strings,
+			editor: None,
+			config_watch,
+			scenario_watch: None,
 		}
 	}
 
@@ -400,6 +580,7 @@ impl App {
 			Event::CamDown(w) => self.camera.push(math::Direction::Down, w),
 			Event::CamLeft(w) => self.camera.push(math::Direction::Left, w),
 			Event::CamRight(w) => self.camera.push(math::Direction::Right, w),
+			Event::CamDash(direction) => self.camera.velocity(direction * CAMERA_DASH_SPEED),
 			Event::CamReset => self.camera.reset(),
 
 			Event::ZoomIn => {
@@ -412,6 +593,8 @@ impl App {
 			}
 			Event::ZoomReset => self.zoom.input(1.),
 
+			Event::VectorThrust(thrust, _) if self.possessed.is_some() => self.steer_possessed(thrust),
+
 			Event::VectorThrust(None, VectorDirection::None) => self.set_player_intent(segment::Intent::Idle),
 
 			Event::VectorThrust(thrust, rotation) => {
@@ -428,6 +611,8 @@ impl App {
 					pilot_rotation,
 				));
 			}
+			// the left button builds in the editor
+			Event::PrimaryTrigger(_, _) if self.editor.is_some() => {}
 			Event::PrimaryTrigger(speed, rate) => self.primary_fire(
 				BULLET_SPEED_SCALE * speed,
 				BULLET_FIRE_RATE_SCALE * rate + (1. - BULLET_FIRE_RATE_SCALE),
@@ -446,9 +631,11 @@ impl App {
 				self.backgrounds.prev();
 			}
 			Event::NextSpeedFactor => {
+				self.speed_knob = None;
 				self.speed_factors.next();
 			}
 			Event::PrevSpeedFactor => {
+				self.speed_knob = None;
 				self.speed_factors.prev();
 			}
 			Event::ToggleDebug => self.debug_flags.toggle(DebugFlags::DEBUG_TARGETS),
@@ -457,8 +644,12 @@ impl App {
 			Event::AppQuit => self.quit(),
 			Event::TogglePause => self.is_paused = !self.is_paused,
 			Event::ToggleGui => self.has_ui_overlay = !self.has_ui_overlay,
+			Event::ToggleShadows => self.has_shadows = !self.has_shadows,
+			Event::ToggleGrid => self.has_grid = !self.has_grid,
+			Event::ToggleAnnotations => self.has_annotations = !self.has_annotations,
 			Event::ToggleCapture => self.is_capturing = !self.is_capturing,
 			Event::SaveGenePoolToFile => self.save_gene_pool_to_file(),
+			Event::SaveProbesToFile => self.save_probes_to_file(),
 			Event::SaveWorldToFile => self.save_world_to_file(),
 			Event::BeginDrag(_, _) => {
 				self.camera.zero();
@@ -471,8 +662,103 @@ impl App {
 				self.camera.set_relative(start - end);
 				self.camera.velocity(vel);
 			}
+			Event::Measure(start, end) => self.measurement = Some((start, end)),
+			Event::EndMeasure(start, end) => {
+				use cgmath::MetricSpace;
+				// a click without dragging clears the measurement
+				self.measurement = if start.distance(end) < MEASURE_MIN_LENGTH {
+					None
+				} else {
+					Some((start, end))
+				}
+			}
+			Event::NewAnnotation(start, end) => self.new_annotation(start, end),
+			Event::NewProbe(start, end) => self.new_probe(start, end),
+			Event::ToggleEditor => {
+				let is_editing = self.editor.is_none();
+				self.set_editor(is_editing);
+			}
+			Event::EditDrag(start, end) => {
+				if let Some(ref mut editor) = self.editor {
+					editor.drag = Some((start, end));
+				}
+			}
+			Event::EndEditDrag(start, end) => {
+				let result = match self.editor {
+					Some(ref mut editor) => editor.apply(self.simulation.world_mut(), start, end),
+					None => return,
+				};
+				match result {
+					Ok(output) => self.console.print(output),
+					Err(error) => self.console.print(error),
+				}
+			}
 			Event::SelectMinion(id) => self.select_minion(id),
+			Event::Quarantine(id) => self.quarantine(id),
+			Event::ObjectiveMet(index) => self.objective_met(index),
+			Event::InterestingMoment(moment) => self.interesting_moment(moment),
 			Event::DeselectAll => self.deselect_all_minions(),
+			Event::ToggleFreezeSelected => self.toggle_freeze_selected(),
+			Event::TogglePossess => self.toggle_possess(),
+			Event::ToggleConsole => self.console.toggle(),
+			Event::ToggleQuickLoad => self.toggle_quick_load(),
+			Event::ToggleChallenges => self.toggle_challenges(),
+			Event::ToggleGenealogy => {
+				self.genealogy = match self.genealogy {
+					Some(_) => None,
+					None => self.inspector().map(|inspector| inspector.id),
+				}
+			}
+			Event::FocusRelative(id) => self.focus_relative(id),
+			Event::DiffGenomes(id, other) => {
+				if let Err(error) = self.diff_genomes(id, other) {
+					self.console.print(error);
+				}
+			}
+			Event::CloseGenomeDiff => self.genome_diff = None,
+			Event::SpawnPreview => {
+				if let Err(error) = self.spawn_preview() {
+					self.console.print(error);
+				}
+			}
+			Event::ClosePreview => self.preview = None,
+			Event::ToggleGenomeEditor => {
+				self.genome_editor = match self.genome_editor {
+					Some(_) => None,
+					None => Some(0),
+				}
+			}
+			Event::ShowGenes(first) => self.genome_editor = Some(first),
+			Event::EditGene(gene, value) => {
+				if let Err(error) = self.edit_gene(gene, value) {
+					self.console.print(error);
+				}
+			}
+			Event::PickCandidate(index) => {
+				if let Err(error) = self.pick_candidate(index) {
+					self.console.print(error);
+				}
+			}
+			Event::Rebreed => self.rebreed(),
+			Event::CloseBreeding => self.breeding = None,
+			Event::QuickLoad(index) => {
+				let entry = self.quick_load.as_ref().and_then(|entries| entries.get(index)).cloned();
+				if let Some(entry) = entry {
+					match self.quick_load_entry(&entry) {
+						Ok(output) => self.console.print(output),
+						Err(error) => self.console.print(error),
+					}
+				}
+			}
+			Event::StartChallenge(index) => {
+				let entry = self.challenges.as_ref().and_then(|entries| entries.get(index)).cloned();
+				if let Some(entry) = entry {
+					match self.start_challenge(&entry) {
+						Ok(output) => self.console.print(output),
+						Err(error) => self.console.print(error),
+					}
+				}
+			}
 			Event::NewMinion(pos) => self.new_minion(pos),
 			Event::RandomizeMinion(pos) => self.randomize_minion(pos),
 			Event::PrimaryFire(_, _) => { /* Handled by the gameplay system */ }
@@ -481,147 +767,1972 @@ impl App {
 		}
 	}
 
+	fn init_input_state(config: &config::Config) -> input::InputState {
+		let mut input_state = input::InputState::default();
+		input_state.set_button_map(&Self::button_map(&config.mouse, &profile::Profile::default()));
+		input_state.set_midi_map(&config.midi.pad_map());
+		input_state
+	}
+
+	// the keys remapped in the profile, and the mouse buttons
+	fn button_map(mouse: &mouse::MouseButtons, profile: &profile::Profile) -> Vec<(input::Key, input::Key)> {
+		let mut map = profile.key_map();
+		map.extend(mouse.button_map());
+		map
+	}
+
 	fn init_camera() -> math::Inertial<f32> { math::Inertial::new(CAMERA_IMPULSE, CAMERA_INERTIA, CAMERA_LIMIT) }
 
 	fn init_lights() -> Cycle<[f32; 4]> { Cycle::new(constants::AMBIENT_LIGHTS) }
 
 	fn init_speed_factors() -> Cycle<SpeedFactor> { Cycle::new(constants::SPEED_FACTORS) }
 
-	fn init_backgrounds() -> Cycle<[f32; 4]> { Cycle::new(constants::BACKGROUNDS) }
+	fn init_backgrounds() -> Cycle<render::Background> { Cycle::new(constants::BACKGROUNDS) }
 
-	fn randomize_minion(&mut self, pos: Position) { self.world.randomize_minion(pos, Motion::default()); }
+	fn randomize_minion(&mut self, pos: Position) { self.simulation.world_mut().randomize_minion(pos, Motion::default()); }
 
-	fn new_minion(&mut self, pos: Position) { self.world.new_minion(pos, Motion::default()); }
+	fn new_minion(&mut self, pos: Position) { self.simulation.world_mut().new_minion(pos, Motion::default()); }
 
 	fn primary_fire(&mut self, bullet_speed: f32, rate: SecondsValue) {
 		// forwards the message to the bus
-		self.bus.post(Event::PrimaryFire(bullet_speed, rate).into());
+		self.simulation.post(Event::PrimaryFire(bullet_speed, rate));
 	}
 
-	fn set_player_intent(&mut self, intent: segment::Intent) { self.world.set_player_intent(intent) }
-
-	fn deselect_all_minions(&mut self) { self.world.for_all_agents(&mut |agent| agent.state.deselect()); }
-
-	fn select_minion(&mut self, id: Id) {
-		self.debug_flags |= DebugFlags::DEBUG_TARGETS;
-		self.world
-			.agent_mut(id)
-			.iter_mut()
-			.for_each(|a| a.state.toggle_selection());
+	fn new_annotation(&mut self, start: Position, end: Position) {
+		use cgmath::MetricSpace;
+		let target = if start.distance(end) < MEASURE_MIN_LENGTH { None } else { Some(end) };
+		let text = format!("Note {}", self.simulation.world().annotations().len() + 1);
+		self.simulation.world_mut().annotations_mut().push(world::annotation::Annotation::new(start, target, &text));
+		self.editing_annotation = Some(self.simulation.world().annotations().len() - 1);
+		self.has_annotations = true;
 	}
 
-	pub fn save_gene_pool_to_file(&self) {
-		match self.world.dump(&self.saved_state_dir) {
-			Err(_) => error!("Failed to save gene pool"),
-			Ok(path) => info!("Saved {:?}", path),
+	// the simulation stands still while editing, and goes back to how it was after
+	fn set_editor(&mut self, is_editing: bool) {
+		match (self.editor.take(), is_editing) {
+			(None, true) => {
+				self.editor = Some(editor::Editor::new(self.is_paused));
+				self.is_paused = true;
+			}
+			(Some(editor), false) => self.is_paused = editor.was_paused(),
+			(editor, _) => self.editor = editor,
 		}
 	}
 
-	pub fn save_world_to_file(&mut self) {
-		let result = self.world.serialize(&self.saved_state_dir);
-		match result {
-			Err(_) => error!("Failed to save world state"),
-			Ok(path) => {
-				info!("Saved {:?}", path);
-				self.set_last_saved(path);
-			}
+	fn editor_summary(&self) -> String {
+		match self.editor {
+			Some(ref editor) => format!("edit: on, tool {}", editor.tool.name()),
+			None => "edit: off".to_owned(),
 		}
 	}
 
-	fn set_last_saved(&mut self, name: path::PathBuf) { self.last_saved = Some(name) }
-
-	pub fn interact(&mut self, e: Event) {
-		self.bus.post(e.into());
-		self.on_app_event(e)
+	// JSON unless the name says otherwise, or only a TOML one by that name is there
+	fn scenario_path(&self, name: &str) -> Result<path::PathBuf, String> {
+		let dir = self.saved_state_dir.join(SCENARIOS_DIR);
+		fs::create_dir_all(&dir).map_err(|e| format!("Could not create {:?}: {}", dir, e))?;
+		if path::Path::new(name).extension().map_or(false, |ext| ext == "json" || ext == "toml") {
+			return Ok(dir.join(name));
+		}
+		let (json, toml) = (dir.join(format!("{}.json", name)), dir.join(format!("{}.toml", name)));
+		Ok(if toml.exists() && !json.exists() { toml } else { json })
 	}
 
-	pub fn has_ui_overlay(&self) -> bool { self.has_ui_overlay }
-
-	pub fn quit(&mut self) { self.is_running = false; }
-
-	fn restart_from_checkpoint(&mut self) {
-		self.systems.clear();
-		self.world.clear();
-		if let Some(ref world_file) = self.last_saved {
-			world::persist::Serializer::load(&world_file, &mut self.world).is_ok();
-		};
-		self.bus.post(world::alert::Alert::RestartFromCheckpoint.into())
+	fn new_probe(&mut self, start: Position, end: Position) {
+		use cgmath::MetricSpace;
+		self.measurement = None;
+		if start.distance(end) < MEASURE_MIN_LENGTH {
+			// a click without dragging removes the probes under the cursor
+			self.simulation.world_mut().probes_mut().retain(|probe| !probe.contains(start));
+		} else {
+			self.simulation.world_mut().probes_mut().push(world::probe::Probe::new(start, end));
+		}
 	}
 
-	pub fn is_running(&self) -> bool { self.is_running }
-
-	pub fn is_capturing(&self) -> bool { self.is_capturing }
-
-	pub fn on_input_event(&mut self, e: &input::Event) { self.input_state.event(e); }
-
-	fn update_input<C>(&mut self, dt: Seconds)
-	where C: InputController {
-		self.input_state.pre_update(&self.viewport);
-
-		for e in C::update(&self.input_state, &self.viewport, &self.camera, dt) {
-			self.interact(e)
+	pub fn on_text_edit(&mut self, edit: ui::TextEdit) {
+		if self.console.is_open() {
+			match edit {
+				ui::TextEdit::Update(text) => self.console.set_input(text),
+				ui::TextEdit::Commit => self.on_console_line(),
+			}
+		} else if let Some(index) = self.editing_annotation {
+			match edit {
+				ui::TextEdit::Update(text) => if let Some(annotation) = self.simulation.world_mut().annotations_mut().get_mut(index) {
+					annotation.text = text;
+				},
+				ui::TextEdit::Commit => {
+					// committing an empty text deletes the annotation
+					if self.simulation.world().annotations().get(index).map(|a| a.text.trim().is_empty()).unwrap_or(false) {
+						self.simulation.world_mut().annotations_mut().remove(index);
+					}
+					self.editing_annotation = None;
+				}
+			}
 		}
-		self.input_state.post_update();
 	}
 
-	pub fn on_resize(&mut self, width: u32, height: u32) {
-		self.viewport = Viewport::rect(width, height, self.viewport.scale);
+	fn on_console_line(&mut self) {
+		let line = self.console.take_input();
+		// an empty line closes the console
+		if line.trim().is_empty() {
+			self.console.close();
+			return;
+		}
+		self.console.print(format!("> {}", line));
+		let result = console::parse(&self.strings, &line).and_then(|command| self.execute(command));
+		match result {
+			Ok(output) => self.console.print(output),
+			Err(error) => self.console.print(error),
+		}
 	}
 
-	fn from_transform(transform: &Transform) -> Matrix4<f32> {
-		use cgmath::Rotation3;
-		let position = transform.position;
-		let angle = transform.angle;
-		let rot = Matrix4::from(cgmath::Quaternion::from_axis_angle(
-			cgmath::Vector3::unit_z(),
-			cgmath::Rad(angle),
-		));
-		let trans = Matrix4::from_translation(cgmath::Vector3::new(position.x, position.y, 0.0));
-
-		trans * rot
+	fn execute(&mut self, command: console::Command) -> Result<String, String> {
+		match command {
+			console::Command::Help => Ok(self.strings.fill("console.help", &[&console::help()])),
+			console::Command::Profile => Ok(self.profile_summary()),
+			console::Command::Recent => Ok(self.recent_summary()),
+			console::Command::Genealogy(id) => {
+				if let Some(id) = id {
+					self.focus_relative(id);
+				}
+				let id = self
+					.genealogy
+					.or_else(|| self.inspector().map(|inspector| inspector.id))
+					.ok_or_else(|| self.strings.get("console.select_minion").to_owned())?;
+				Ok(self.genealogy_summary(id))
+			}
+			console::Command::DiffGenomes(id, other) => {
+				let id = id
+					.or_else(|| self.inspector().map(|inspector| inspector.id))
+					.ok_or_else(|| self.strings.get("console.select_minion").to_owned())?;
+				self.diff_genomes(id, other)
+			}
+			console::Command::CloseGenomeDiff => {
+				self.genome_diff = None;
+				Ok(self.strings.get("console.genome_diff_closed").to_owned())
+			}
+			console::Command::PreviewGenome(id) => {
+				let id = id
+					.or_else(|| self.inspector().map(|inspector| inspector.id))
+					.ok_or_else(|| self.strings.get("console.select_minion").to_owned())?;
+				self.preview_agent(id)
+			}
+			console::Command::ImportGenome(text) => {
+				let dna = preview::parse(&text)?;
+				let source = self.strings.get("preview.imported").to_owned();
+				Ok(self.preview_genome(&source, dna))
+			}
+			console::Command::SpawnPreview => self.spawn_preview(),
+			console::Command::EditGenome(id) => {
+				// the genome already in the preview goes first, imported or edited, then the selected minion
+				let selected = self.inspector().map(|inspector| inspector.id);
+				let summary = match (id, selected) {
+					(Some(id), _) => self.preview_agent(id)?,
+					(None, _) if self.preview.is_some() => String::new(),
+					(None, Some(id)) => self.preview_agent(id)?,
+					(None, None) => return Err(self.strings.get("console.select_or_import").to_owned()),
+				};
+				self.genome_editor = Some(0);
+				Ok(summary)
+			}
+			console::Command::CloseGenomeEditor => {
+				self.genome_editor = None;
+				Ok(self.strings.get("console.genome_editor_closed").to_owned())
+			}
+			console::Command::SetGene(gene, value) => self.edit_gene(gene, value),
+			console::Command::Breed(parents) => self.breed(parents),
+			console::Command::Rebreed => {
+				self.rebreed();
+				self.breeding_summary()
+			}
+			console::Command::PickCandidate(index) => self.pick_candidate(index),
+			console::Command::CloseBreeding => {
+				self.breeding = None;
+				Ok(self.strings.get("console.breeding_closed").to_owned())
+			}
+			console::Command::ClosePreview => {
+				self.preview = None;
+				Ok(self.strings.get("console.preview_closed").to_owned())
+			}
+			console::Command::Checkpoints => Ok(self.checkpoints_summary()),
+			console::Command::SaveCheckpoint(name) => self.save_checkpoint(&name),
+			console::Command::LoadCheckpoint(name) => self.load_checkpoint(&name),
+			console::Command::Branches => Ok(self.branches_summary()),
+			console::Command::Compare(files) => {
+				let files: Vec<path::PathBuf> = files.iter().map(path::PathBuf::from).collect();
+				self.compare(&files)
+			}
+			console::Command::CompareLatest(n) => {
+				let files = comparison::latest(&self.saved_state_dir, n);
+				self.compare(&files)
+			}
+			console::Command::CloseCompare => {
+				self.comparison = None;
+				Ok(self.strings.get("console.comparison_closed").to_owned())
+			}
+			console::Command::DiffWorlds(before, after) => self.diff_worlds(&before, &after),
+			console::Command::Challenges => Ok(self.challenges_summary()),
+			console::Command::StartChallenge(n) => {
+				let (entries, _) = challenge::entries(&self.challenge_pack(), &self.profile.best);
+				let entry = entries.get(n.wrapping_sub(1)).cloned();
+				let entry = entry.ok_or_else(|| self.strings.fill("console.no_challenge", &[&n.to_string()]))?;
+				self.start_challenge(&entry)
+			}
+			console::Command::AbandonChallenge => match self.abandon_challenge() {
+				Some(attempt) => Ok(self.strings.fill("console.challenge_abandoned", &[&attempt.title])),
+				None => Err(self.strings.get("console.no_challenge_played").to_owned()),
+			},
+			console::Command::SetChallengePack(dir) => {
+				self.profile.challenges = if dir == "default" { None } else { Some(path::PathBuf::from(dir)) };
+				self.save_profile()?;
+				Ok(self.challenges_summary())
+			}
+			console::Command::LoadRecent(n) => {
+				let entry = self.quick_load_entries().get(n.wrapping_sub(1)).cloned();
+				let entry = entry.ok_or_else(|| self.strings.fill("console.no_recent", &[&n.to_string()]))?;
+				self.quick_load_entry(&entry)
+			}
+			console::Command::SetQuality(quality) => {
+				self.profile.quality = quality;
+				self.save_profile()?;
+				Ok(self.profile_summary())
+			}
+			console::Command::Quality => Ok(self.quality_summary()),
+			console::Command::Memory => Ok(self.memory_summary()),
+			console::Command::SetMemoryBudget(part, megabytes) => self.update_memory_budget(&part, megabytes),
+			console::Command::Diagnostics => Ok(self.diagnostics_summary()),
+			console::Command::SetDiagnostics(enabled) => {
+				self.debug_flags.set(DebugFlags::DEBUG_DIAGNOSTICS, enabled);
+				Ok(self.diagnostics_summary())
+			}
+			console::Command::SetAdaptive(enabled) => {
+				self.profile.adaptive.enabled = enabled;
+				self.save_profile()?;
+				Ok(self.quality_summary())
+			}
+			console::Command::SetAdaptiveFps(fps) => {
+				self.profile.adaptive.target_fps = fps.max(1.);
+				self.save_profile()?;
+				Ok(self.quality_summary())
+			}
+			console::Command::Locale => Ok(self.strings.fill("console.locale", &[self.strings.locale()])),
+			console::Command::Fitness => Ok(self.fitness_summary()),
+			console::Command::SetFitness(term, weight) => {
+				let mut fitness = self.simulation.world().fitness().clone();
+				fitness.set_weight(&term, weight);
+				self.simulation.world_mut().set_fitness(fitness.clone());
+				self.config.fitness = fitness;
+				self.save_config()?;
+				Ok(self.fitness_summary())
+			}
+			console::Command::Mating => Ok(self.mating_summary()),
+			console::Command::SetReproduction(mode) => self.update_mating(|mating| mating.mode = mode),
+			console::Command::SetAssortative(assortative) => self.update_mating(|mating| mating.assortative = assortative),
+			console::Command::SetMatingDistance(distance) => {
+				self.update_mating(|mating| mating.max_distance = num::clamp(distance, 0., 1.))
+			}
+			console::Command::SetMatingRange(range) => self.update_mating(|mating| mating.range = range.max(0.)),
+			console::Command::SetMutation(rate) => self.update_mating(|mating| mating.mutation = rate.max(0.)),
+			console::Command::Disease => Ok(self.disease_summary()),
+			console::Command::Energy => Ok(self.energy_summary()),
+			console::Command::Outbreak(count) => {
+				let infected = self.simulation.world_mut().outbreak(count);
+				let seconds = self.simulation.world().seconds().get();
+				let infected = infected.to_string();
+				self.report.note(seconds, self.strings.fill("report.outbreak", &[&infected]));
+				Ok(self.strings.fill("console.outbreak", &[&infected]))
+			}
+			console::Command::SetTransmission(rate) => self.update_disease(|disease| disease.transmission = rate.max(0.)),
+			console::Command::SetDrain(drain) => self.update_disease(|disease| disease.drain = drain.max(0.)),
+			console::Command::SetInfectionDuration(duration) => {
+				self.update_disease(|disease| disease.duration = duration.max(0.))
+			}
+			console::Command::Game => Ok(self.game_summary()),
+			console::Command::SetGame(enabled) => self.update_game(|game| game.enabled = enabled),
+			console::Command::AddObjective(objective) => self.update_game(|game| {
+				game.enabled = true;
+				game.objectives.push(objective);
+			}),
+			console::Command::ClearObjectives => {
+				let summary = self.update_game(|game| game.objectives.clear())?;
+				self.simulation.world_mut().set_score(world::objective::Score::default());
+				Ok(summary)
+			}
+			console::Command::Capture => Ok(self.capture_summary()),
+			console::Command::CaptureWindow => self.set_capture_region(capture::CaptureRegion::Window),
+			console::Command::CaptureScreen => {
+				let rect = self.to_pixel_rect(&self.measured_extent()?);
+				self.set_capture_region(capture::CaptureRegion::Screen(rect))
+			}
+			console::Command::CaptureWorld(follow) => {
+				let extent = self.measured_extent()?;
+				self.set_capture_region(capture::CaptureRegion::World { extent, follow })
+			}
+			console::Command::Mouse => Ok(self.mouse_summary()),
+			console::Command::SetMouseButton(action, button) => self.update_mouse(|mouse| mouse.bind(action, button)),
+			console::Command::SetLeftHanded(left_handed) => self.update_mouse(|mouse| mouse.left_handed = left_handed),
+			console::Command::Physics => Ok(self.physics_summary()),
+			console::Command::SetDespawnRunaways(despawn) => {
+				self.config.physics.despawn_runaways = despawn;
+				Ok(self.physics_summary())
+			}
+			console::Command::CheckPhysics => {
+				let found = self.check_physics();
+				if found.is_empty() {
+					return Ok(self.strings.get("console.physics_agree").to_owned());
+				}
+				let mut lines = vec![self.strings.fill("console.physics_repaired", &[&found.len().to_string()])];
+				lines.extend(found.iter().take(PHYSICS_CHECK_SHOWN).map(|discrepancy| discrepancy.to_string()));
+				Ok(lines.join("\n"))
+			}
+			console::Command::SetPhysicsEngine(engine) => {
+				if !engine.is_available() {
+					return Err(self.strings.fill("console.no_engine", &[&format!("{:?}", engine)]));
+				}
+				self.update_physics(|physics| physics.engine = engine)
+			}
+			console::Command::SetPhysicsRegions(regions) => {
+				self.update_physics(|physics| physics.regions = num::clamp(regions, 1, PHYSICS_MAX_REGIONS))
+			}
+			console::Command::SetPhysicsMargin(margin) => {
+				self.update_physics(|physics| physics.margin = margin.max(0.))
+			}
+			console::Command::SetCollision(a, b, collides) => {
+				self.update_physics(|physics| physics.collisions.set_collides(a, b, collides))
+			}
+			console::Command::SetGhost(category, is_ghost) => {
+				self.update_physics(|physics| physics.collisions.set_ghost(category, is_ghost))
+			}
+			console::Command::Obstacles => Ok(self.obstacles_summary()),
+			console::Command::NewObstacle(solid) => {
+				let extent = self.measured_extent()?;
+				let obstacle = if solid {
+					world::obstacle::Obstacle::wall(extent.min, extent.max)
+				} else {
+					world::obstacle::Obstacle::zone(extent.min, extent.max)
+				};
+				self.simulation.world_mut().obstacles_mut().push(obstacle);
+				Ok(self.obstacles_summary())
+			}
+			console::Command::SetObstacle(index, property, value) => {
+				self.update_obstacle(index, |obstacle| {
+					obstacle.set(property, value);
+					Ok(())
+				})
+			}
+			console::Command::SetDoor(index, trigger) => {
+				let error = self.strings.get("console.wall_door").to_owned();
+				self.update_obstacle(index, move |obstacle| {
+					if !obstacle.solid {
+						return Err(error);
+					}
+					obstacle.door = trigger.map(world::obstacle::Door::new);
+					Ok(())
+				})
+			}
+			console::Command::OpenDoor(index, is_open) => {
+				let error = self.strings.fill("console.no_door", &[&(index + 1).to_string()]);
+				self.update_obstacle(index, move |obstacle| {
+					let door = obstacle.door.as_mut().ok_or(error)?;
+					door.set_open(is_open);
+					Ok(())
+				})
+			}
+			console::Command::SetGate(index, gate) => {
+				let error = self.strings.get("console.zone_gate").to_owned();
+				self.update_obstacle(index, move |obstacle| {
+					if obstacle.solid {
+						return Err(error);
+					}
+					obstacle.gate = gate;
+					Ok(())
+				})
+			}
+			console::Command::SetFlow(index, flow) => {
+				let error = self.strings.get("console.zone_flow").to_owned();
+				self.update_obstacle(index, move |obstacle| {
+					if obstacle.solid {
+						return Err(error);
+					}
+					obstacle.flow = flow;
+					Ok(())
+				})
+			}
+			console::Command::RemoveObstacle(index) => {
+				if index >= self.simulation.world().obstacles().len() {
+					return Err(self.strings.fill("console.no_obstacle", &[&(index + 1).to_string()]));
+				}
+				self.simulation.world_mut().obstacles_mut().remove(index);
+				Ok(self.obstacles_summary())
+			}
+			console::Command::Topology => Ok(self.topology_summary()),
+			console::Command::SetTopology(topology) => {
+				self.simulation
+					.set_topology(topology)
+					.map_err(|e| self.strings.fill("console.thaw_failed", &[&e.to_string()]))?;
+				Ok(self.topology_summary())
+			}
+			console::Command::Feeders => Ok(self.feeders_summary()),
+			console::Command::Plants => Ok(self.plants_summary()),
+			console::Command::SowPlants(count) => {
+				self.sow_plants(count);
+				Ok(self.plants_summary())
+			}
+			console::Command::SetFeederRate(rate) => {
+				if rate <= 0. {
+					return Err(self.strings.fill("console.invalid_rate", &[&rate.to_string()]));
+				}
+				for feeder in self.simulation.world_mut().feeders_mut().iter_mut() {
+					feeder.set_rate(seconds(rate));
+				}
+				Ok(self.feeders_summary())
+			}
+			console::Command::Portals => Ok(self.portals_summary()),
+			console::Command::NewPortal => {
+				let (start, end) = self
+					.measurement
+					.ok_or_else(|| self.strings.get("console.measure_portal").to_owned())?;
+				self.simulation.world_mut().portals_mut().push(world::portal::Portal::new(start, end));
+				Ok(self.portals_summary())
+			}
+			console::Command::SetPortalRadius(index, radius) => {
+				let error = self.strings.fill("console.no_portal", &[&(index + 1).to_string()]);
+				self.simulation.world_mut()
+					.portals_mut()
+					.get_mut(index)
+					.ok_or(error)?
+					.radius = radius.max(PORTAL_MIN_RADIUS);
+				Ok(self.portals_summary())
+			}
+			console::Command::RemovePortal(index) => {
+				if index >= self.simulation.world().portals().len() {
+					return Err(self.strings.fill("console.no_portal", &[&(index + 1).to_string()]));
+				}
+				self.simulation.world_mut().portals_mut().remove(index);
+				Ok(self.portals_summary())
+			}
+			console::Command::GenerateTerrain(layout, seed) => {
+				let terrain = world::terrain::Terrain {
+					layout,
+					seed: seed.unwrap_or_else(rand::random),
+					..self.config.terrain.clone().unwrap_or_default()
+				};
+				let (walls, resources) = self.simulation.world_mut().generate_terrain(&terrain);
+				Ok(self.strings.fill(
+					"console.terrain",
+					&[&format!("{:?}", layout), &terrain.seed.to_string(), &walls.to_string(), &resources.to_string()],
+				))
+			}
+			console::Command::Editor => Ok(self.editor_summary()),
+			console::Command::SetEditor(is_editing) => {
+				self.set_editor(is_editing);
+				Ok(self.editor_summary())
+			}
+			console::Command::SetEditorTool(tool) => {
+				if self.editor.is_none() {
+					self.set_editor(true);
+				}
+				if let Some(ref mut editor) = self.editor {
+					editor.tool = tool;
+				}
+				Ok(self.editor_summary())
+			}
+			console::Command::SaveScenario(name) => {
+				let file_path = self.scenario_path(&name)?;
+				let file_name = format!("{:?}", file_path);
+				world::persist::Serializer::save_scenario(
+					&file_path,
+					self.simulation.world(),
+					&self.config.physics.collisions,
+				).map_err(|e| self.strings.fill("console.save_failed", &[&file_name, &e.to_string()]))?;
+				self.scenario_watch = Some(reload::Watch::new(&file_path));
+				Ok(self.strings.fill("console.scenario_saved", &[&file_name]))
+			}
+			console::Command::LoadScenario(name) => {
+				let file_path = self.scenario_path(&name)?;
+				self.load_scenario(&file_path)?;
+				self.scenario_watch = Some(reload::Watch::new(&file_path));
+				Ok(self.strings.fill("console.scenario_loaded", &[&format!("{:?}", file_path)]))
+			}
+			console::Command::Stream => Ok(self.stream_summary()),
+			console::Command::Share => Ok(self.share_summary()),
+			console::Command::SetSharing(enabled) => self.set_sharing(enabled),
+			console::Command::SetShareName(name) => {
+				self.config.share.name = name;
+				self.save_config()?;
+				Ok(self.share_summary())
+			}
+			console::Command::SetStreaming(enabled) => self.set_streaming(enabled),
+			console::Command::SetStreamUrl(url) => {
+				self.config.stream.url = url;
+				self.save_config()?;
+				Ok(self.stream_summary())
+			}
+			console::Command::Caption => Ok(self.caption_summary()),
+			console::Command::SetCaption(enabled) => self.update_caption(|rules| rules.enabled = enabled),
+			console::Command::SetCaptionStats(enabled) => self.update_caption(|rules| rules.stats = enabled),
+			console::Command::SetCaptionAnnotations(enabled) => {
+				self.update_caption(|rules| rules.annotations = enabled)
+			}
+			console::Command::SetCaptionTitle(title) => self.update_caption(|rules| rules.title = title),
+			console::Command::Colors => Ok(self.colors_summary()),
+			console::Command::SetColorMode(mode) => self.update_accessibility(|settings| settings.color_mode = mode),
+			console::Command::SetPalette(palette) => self.update_accessibility(|settings| settings.palette = palette),
+			console::Command::SetUiScale(scale) => {
+				self.update_accessibility(|settings| settings.ui_scale = num::clamp(scale, UI_SCALE_MIN, UI_SCALE_MAX))
+			}
+			console::Command::Warnings => Ok(self.warnings_summary()),
+			console::Command::SetMinPopulation(population) => {
+				self.update_warnings(|rules| rules.min_population = population)
+			}
+			console::Command::SetMinFps(fps) => self.update_warnings(|rules| rules.min_fps = fps.max(0.)),
+			console::Command::SetMinFreeDisk(megabytes) => {
+				self.update_warnings(|rules| rules.min_free_disk = megabytes as u64)
+			}
+			console::Command::SetAutosaveWarning(autosave) => self.update_warnings(|rules| rules.autosave = autosave),
+			console::Command::Moments => Ok(self.moments_summary()),
+			console::Command::SetMoments(enabled) => self.update_moments(|rules| rules.enabled = enabled),
+			console::Command::SetMomentClip(seconds) => {
+				self.update_moments(|rules| rules.clip_seconds = f64::from(seconds.max(0.)))
+			}
+			console::Command::SetMomentCheckpoint(checkpoint) => {
+				self.update_moments(|rules| rules.checkpoint = checkpoint)
+			}
+		}
 	}
 
-	pub fn init(&mut self, mode: SystemMode) {
-		self.init_systems(mode);
-		self.register_all();
-		self.bus.post(world::alert::Alert::BeginSimulation.into());
+	fn update_accessibility<F>(&mut self, f: F) -> Result<String, String>
+	where F: FnOnce(&mut accessibility::Accessibility) {
+		f(&mut self.config.accessibility);
+		self.save_config()?;
+		Ok(self.colors_summary())
 	}
 
-	fn register_all(&mut self) {
-		// registered() drains the list, so this can be called only once per frame
-		let found: Vec<agent::Agent> = self
-			.world
-			.registered()
-			.into_iter()
-			.filter_map(|id| self.world.agent(*id))
-			.cloned()
-			.collect();
-		self.systems.register(&found[..]);
+	fn colors_summary(&self) -> String {
+		let settings = &self.config.accessibility;
+		format!(
+			"mode: {} palette: {} ui scale: {:.2}",
+			settings.color_mode.name(),
+			settings.palette.name(),
+			settings.ui_scale
+		)
 	}
 
-	fn cleanup_before(&mut self) {
-		self.world.cleanup_before();
-		self.systems.unregister(&self.world.sweep());
+	fn update_mouse<F>(&mut self, f: F) -> Result<String, String>
+	where F: FnOnce(&mut mouse::MouseButtons) {
+		f(&mut self.config.mouse);
+		self.input_state.set_button_map(&Self::button_map(&self.config.mouse, &self.profile));
+		self.save_config()?;
+		Ok(self.mouse_summary())
 	}
 
-	fn init_systems(&mut self, mode: SystemMode) {
-		self.systems.set_mode(mode);
-		self.systems.attach(&mut self.bus);
-		self.systems.init(&self.world);
+	fn mouse_summary(&self) -> String {
+		let mouse = &self.config.mouse;
+		format!(
+			"shoot: {:?} drag: {:?} spawn: {:?} left handed: {}",
+			mouse.shoot,
+			mouse.drag,
+			mouse.spawn,
+			if mouse.left_handed { "on" } else { "off" }
+		)
 	}
 
-	fn update_systems(&mut self, dt: Seconds) {
-		self.systems
-			.for_each_par_write(&self.world, &|s, world| s.step(&world, dt));
-		self.systems
-			.for_each_read(&mut self.world, &self.bus, &|s, mut world, outbox| {
-				s.apply(&mut world, outbox)
+	// the layout of a scenario, and its collision rules if it has any
+	fn load_scenario(&mut self, file_path: &path::Path) -> Result<(), String> {
+		let mut collisions = self.config.physics.collisions.clone();
+		world::persist::Serializer::load_scenario(file_path, self.simulation.world_mut(), &mut collisions)
+			.map_err(|e| self.strings.fill("console.load_failed", &[&format!("{:?}", file_path), &e.to_string()]))?;
+		if reload::differs(&collisions, &self.config.physics.collisions) {
+			self.update_physics(|physics| physics.collisions = collisions)?;
+		}
+		Ok(())
+	}
+
+	fn update_physics<F>(&mut self, f: F) -> Result<String, String>
+	where F: FnOnce(&mut systems::PhysicsSettings) {
+		f(&mut self.config.physics);
+		self.simulation.set_physics(self.config.physics.clone());
+		self.save_config()?;
+		Ok(self.physics_summary())
+	}
+
+	// bodies left behind by an agent that died or went away, or agents without theirs, are bugs: they are logged
+	fn check_physics(&mut self) -> Vec<systems::PhysicsDiscrepancy> {
+		let found = self.simulation.check_physics(true);
+		for discrepancy in &found {
+			warn!("Physics out of step with the world, repaired: {}", discrepancy);
+		}
+		found
+	}
+
+	// frozen by the physics already, the rest is telling the user, and removing it if asked to
+	fn quarantine(&mut self, id: Id) {
+		let despawn = self.config.physics.despawn_runaways;
+		match self.simulation.world_mut().agent_mut(id) {
+			Some(agent) => if despawn {
+				agent.state.die();
+			},
+			None => return,
+		}
+		let now = self.wall_clock.seconds().get();
+		let text = self.warnings.raise(&self.config.warnings, &self.strings, &warning::Warning::Runaway(id), now);
+		self.report.note(self.simulation.world().seconds().get(), text);
+	}
+
+	fn physics_summary(&self) -> String {
+		let physics = &self.config.physics;
+		let ignored: Vec<String> = physics
+			.collisions
+			.ignored_pairs()
+			.into_iter()
+			.map(|(a, b)| format!("{:?}/{:?}", a, b))
+			.collect();
+		format!(
+			"physics engine: {:?} regions: {} margin: {:.1}{} not colliding: {} ghosts: {:?} runaways: {}",
+			physics.engine,
+			physics.regions,
+			physics.margin,
+			if physics.regions > 1 { ", bodies in different regions don't collide" } else { "" },
+			if ignored.is_empty() { "none".to_owned() } else { ignored.join(", ") },
+			physics.collisions.ghosts,
+			if physics.despawn_runaways { "despawn" } else { "freeze" }
+		)
+	}
+
+	fn update_obstacle<F>(&mut self, index: usize, f: F) -> Result<String, String>
+	where F: FnOnce(&mut world::obstacle::Obstacle) -> Result<(), String> {
+		let error = self.strings.fill("console.no_obstacle", &[&(index + 1).to_string()]);
+		f(self.simulation.world_mut().obstacles_mut().get_mut(index).ok_or(error)?)?;
+		Ok(self.obstacles_summary())
+	}
+
+	fn obstacles_summary(&self) -> String {
+		let obstacles: Vec<String> = self
+			.simulation
+			.world()
+			.obstacles()
+			.iter()
+			.enumerate()
+			.map(|(i, o)| {
+				let flow = o.flow.map(|flow| format!(" flow: {:?} at {:.1}", flow.direction, flow.speed));
+				let kind = match (&o.door, o.gate) {
+					(Some(door), _) => format!(
+						"door ({}, {:?})",
+						if door.is_open { "open" } else { "closed" },
+						door.trigger
+					),
+					(None, Some(gate)) => format!("gate ({:?})", gate),
+					_ => (if o.solid { "wall" } else { "zone" }).to_owned(),
+				};
+				format!(
+					"{}: {} {:.1},{:.1} to {:.1},{:.1} friction: {:.2} restitution: {:.2} sticky: {:.1}s drag: {:.2}{}",
+					i + 1,
+					kind,
+					o.extent.min.x,
+					o.extent.min.y,
+					o.extent.max.x,
+					o.extent.max.y,
+					o.friction,
+					o.restitution,
+					o.sticky,
+					o.drag,
+					flow.unwrap_or_default()
+				)
+			}).collect();
+		if obstacles.is_empty() {
+			"no obstacles, measure a rectangle and add a wall or a zone".to_owned()
+		} else {
+			obstacles.join(" | ")
+		}
+	}
+
+	fn topology_summary(&self) -> String {
+		match self.simulation.world().topology() {
+			world::Topology::Bounded => "topology: bounded, the world is walled in".to_owned(),
+			world::Topology::Toroidal => {
+				"topology: toroidal, what leaves the world on one edge comes back on the other".to_owned()
+			}
+			world::Topology::Unbounded => {
+				let far = self.simulation.world().chunks.far_field();
+				format!(
+					"topology: unbounded, {} chunks generated, {} frozen on disk ({:.0} minions, {:.0} resources)",
+					self.simulation.world().chunks.generated.len(),
+					self.simulation.world().chunks.frozen.len(),
+					far.minions,
+					far.resources
+				)
+			}
+		}
+	}
+
+	fn feeders_summary(&self) -> String {
+		let feeders = self.simulation.world().feeders();
+		match feeders.first() {
+			Some(feeder) if feeders.iter().all(|f| f.rate() == feeder.rate()) => {
+				format!("feeders: {}, a resource every {:.2}s", feeders.len(), feeder.rate().get())
+			}
+			Some(_) => {
+				let rates: Vec<String> = feeders.iter().map(|f| format!("{:.2}s", f.rate().get())).collect();
+				format!("feeders: {}, a resource every {}", feeders.len(), rates.join(", "))
+			}
+			None => "no feeders".to_owned(),
+		}
+	}
+
+	fn plants_summary(&self) -> String {
+		let plants = self.simulation.world().agents(agent::AgentType::Plant);
+		let energy: f32 = plants.values().map(|plant| plant.state.energy()).sum();
+		format!(
+			"plants: {} of at most {}, {:.0} energy, rooted in the light of the feeders and the player",
+			plants.len(),
+			PLANT_MAX_POPULATION,
+			energy
+		)
+	}
+
+	// in the light, around the feeders in turn, or the camera if there are none
+	fn sow_plants(&mut self, count: usize) {
+		let mut centers: Vec<Position> = self.simulation.world().feeders().iter().map(|f| f.transform().position).collect();
+		if centers.is_empty() {
+			centers.push(self.camera.position());
+		}
+		for i in 0..count {
+			let angle = rand::random::<f32>() * 2. * ::std::f32::consts::PI;
+			let distance = PLANT_SEED_RANGE * rand::random::<f32>();
+			let position = centers[i % centers.len()] + Position::new(angle.cos(), angle.sin()) * distance;
+			self.simulation.world_mut().new_plant(Transform::new(position, angle), None);
+		}
+		let seconds = self.simulation.world().seconds().get();
+		self.report.note(seconds, format!("{} plants sown", count));
+	}
+
+	fn portals_summary(&self) -> String {
+		let portals: Vec<String> = self
+			.simulation
+			.world()
+			.portals()
+			.iter()
+			.enumerate()
+			.map(|(i, p)| {
+				format!(
+					"{}: {:.1},{:.1} and {:.1},{:.1} radius: {:.1}",
+					i + 1,
+					p.ends[0].x,
+					p.ends[0].y,
+					p.ends[1].x,
+					p.ends[1].y,
+					p.radius
+				)
+			}).collect();
+		if portals.is_empty() {
+			"no portals, measure from one end to the other and add one".to_owned()
+		} else {
+			portals.join(" | ")
+		}
+	}
+
+	fn update_caption<F>(&mut self, f: F) -> Result<String, String>
+	where F: FnOnce(&mut caption::CaptionRules) {
+		f(&mut self.config.caption);
+		self.save_config()?;
+		Ok(self.caption_summary())
+	}
+
+	fn caption_summary(&self) -> String {
+		let rules = &self.config.caption;
+		let switch = |enabled| if enabled { "on" } else { "off" };
+		format!(
+			"caption: {} stats: {} annotations: {} title: {}",
+			switch(rules.enabled),
+			switch(rules.stats),
+			switch(rules.annotations),
+			if rules.title.is_empty() { "none" } else { rules.title.as_str() }
+		)
+	}
+
+	fn update_warnings<F>(&mut self, f: F) -> Result<String, String>
+	where F: FnOnce(&mut warning::WarningRules) {
+		f(&mut self.config.warnings);
+		self.save_config()?;
+		Ok(self.warnings_summary())
+	}
+
+	fn warnings_summary(&self) -> String {
+		let rules = &self.config.warnings;
+		format!(
+			"population: {} fps: {:.0} disk: {} MB autosave: {}",
+			rules.min_population,
+			rules.min_fps,
+			rules.min_free_disk,
+			if rules.autosave { "on" } else { "off" }
+		)
+	}
+
+	fn update_disease<F>(&mut self, f: F) -> Result<String, String>
+	where F: FnOnce(&mut world::disease::Disease) {
+		let mut disease = self.simulation.world().disease().clone();
+		f(&mut disease);
+		self.simulation.world_mut().set_disease(disease.clone());
+		self.config.disease = disease;
+		self.save_config()?;
+		Ok(self.disease_summary())
+	}
+
+	fn disease_summary(&self) -> String {
+		let disease = self.simulation.world().disease();
+		let infected = self
+			.simulation
+			.world()
+			.agents(agent::AgentType::Minion)
+			.values()
+			.filter(|a| a.state.is_infected())
+			.count();
+		format!(
+			"infected: {} transmission: {:.2} drain: {:.2} duration: {:.1}",
+			infected, disease.transmission, disease.drain, disease.duration
+		)
+	}
+
+	fn update_game<F>(&mut self, f: F) -> Result<String, String>
+	where F: FnOnce(&mut world::objective::GameMode) {
+		if let Some(ref attempt) = self.challenge {
+			return Err(self.strings.fill("console.playing_challenge", &[&attempt.title]));
+		}
+		let mut game = self.simulation.world().game_mode().clone();
+		f(&mut game);
+		self.simulation.world_mut().set_game_mode(game.clone());
+		self.config.game = game;
+		self.save_config()?;
+		Ok(self.game_summary())
+	}
+
+	fn game_summary(&self) -> String {
+		let game = self.simulation.world().game_mode();
+		let score = self.simulation.world().score();
+		let mut lines = vec![format!(
+			"game: {} score: {:.0}",
+			if game.enabled { "on" } else { "off" },
+			score.points
+		)];
+		for (i, objective) in game.objectives.iter().enumerate() {
+			let held = score.held.get(i).cloned().unwrap_or_default();
+			let progress = match *objective {
+				world::objective::Objective::Population { .. } => format!("{:.1} minutes", held / 60.),
+				world::objective::Objective::Crossing => format!("{:.0}% crossed", held * 100.),
+			};
+			let met = score.met.get(i).cloned().unwrap_or_default();
+			lines.push(format!("{}: {}, {}{}", i + 1, objective, progress, if met { ", met" } else { "" }));
+		}
+		lines.join("\n")
+	}
+
+	fn objective_met(&mut self, index: usize) {
+		let text = match self.simulation.world().game_mode().objectives.get(index) {
+			Some(objective) => self.strings.fill(
+				"toast.objective_met",
+				&[&objective.to_string(), &format!("{:.0}", self.simulation.world().score().points)],
+			),
+			None => return,
+		};
+		self.console.print(text.clone());
+		self.report.note(self.simulation.world().seconds().get(), text);
+		if self.challenge.is_some() && self.simulation.world().score().met_count() == self.simulation.world().game_mode().objectives.len() {
+			self.finish_challenge(true);
+		}
+	}
+
+	fn detect_moments(&mut self) {
+		if !self.config.moments.enabled {
+			return;
+		}
+		for moment in self.moments.detect(self.simulation.world(), self.simulation.world().seconds().get()) {
+			self.simulation.post(Event::InterestingMoment(moment));
+		}
+	}
+
+	// always noted, clipped and bookmarked unless another moment just was
+	fn interesting_moment(&mut self, moment: world::moment::Moment) {
+		let seconds = self.simulation.world().seconds().get();
+		let text = self.strings.fill("toast.moment", &[&moment.to_string()]);
+		info!("{}", text);
+		self.console.print(text.clone());
+		self.report.note(seconds, text);
+		let now = self.wall_clock.seconds().get();
+		if now < self.next_moment_action {
+			return;
+		}
+		self.next_moment_action = now + MOMENT_COOLDOWN;
+		let name = moment.name(seconds);
+		if self.clip_seconds() > 0. {
+			self.clip_request = Some(name.clone());
+		}
+		if self.config.moments.checkpoint {
+			match self.save_checkpoint(&name) {
+				Ok(text) => info!("{}", text),
+				Err(e) => error!("{}", e),
+			}
+		}
+	}
+
+	// how far back the capture keeps frames for the clips
+	pub fn clip_seconds(&self) -> SecondsValue {
+		if self.config.moments.enabled {
+			self.config.moments.clip_seconds
+		} else {
+			0.
+		}
+	}
+
+	// the name of the clip the capture should save now, if any
+	pub fn take_clip_request(&mut self) -> Option<String> { self.clip_request.take() }
+
+	fn update_moments<F>(&mut self, f: F) -> Result<String, String>
+	where F: FnOnce(&mut moments::MomentRules) {
+		f(&mut self.config.moments);
+		self.save_config()?;
+		Ok(self.moments_summary())
+	}
+
+	fn moments_summary(&self) -> String {
+		let rules = &self.config.moments;
+		format!(
+			"moments: {} clip: {:.0}s checkpoint: {}",
+			if rules.enabled { "on" } else { "off" },
+			rules.clip_seconds,
+			if rules.checkpoint { "on" } else { "off" }
+		)
+	}
+
+	fn energy_summary(&self) -> String {
+		let rates = self.simulation.world().energy().rates();
+		let flows: Vec<String> = rates.entries().iter().map(|&(name, rate)| format!("{}: {:.2}", name, rate)).collect();
+		format!(
+			"energy per second over {}s, {} | resources: {:+.2} minions: {:+.2}",
+			ENERGY_FLOW_PERIOD,
+			flows.join(" "),
+			rates.resource_balance(),
+			rates.minion_balance()
+		)
+	}
+
+	fn update_mating<F>(&mut self, f: F) -> Result<String, String>
+	where F: FnOnce(&mut world::mating::Mating) {
+		let mut mating = self.simulation.world().mating().clone();
+		f(&mut mating);
+		self.simulation.world_mut().set_mating(mating.clone());
+		self.config.mating = mating;
+		self.save_config()?;
+		Ok(self.mating_summary())
+	}
+
+	fn mating_summary(&self) -> String {
+		let mating = self.simulation.world().mating();
+		format!(
+			"mode: {} assortative: {} distance: {:.2} range: {:.1} mutation: {:.2}",
+			mating.mode.name(),
+			if mating.assortative { "on" } else { "off" },
+			mating.max_distance,
+			mating.range,
+			mating.mutation
+		)
+	}
+
+	fn fitness_summary(&self) -> String {
+		let fitness = self.simulation.world().fitness();
+		world::fitness::TERMS
+			.iter()
+			.map(|term| format!("{}: {:.2}", term, fitness.weight(term).unwrap_or(0.)))
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+
+	// what can change without a restart is applied as soon as the files are saved
+	fn hot_reload(&mut self) {
+		let now = self.wall_clock.seconds().get();
+		if self.config_watch.is_changed(now) {
+			let file_path = self.config_watch.file_path().to_owned();
+			match config::Config::load(&file_path) {
+				Ok(config) => self.reload_config(config),
+				Err(e) => warn!("Not reloading {:?}: {}", file_path, e),
+			}
+		}
+		let scenario = match self.scenario_watch {
+			Some(ref mut watch) => if watch.is_changed(now) { Some(watch.file_path().to_owned()) } else { None },
+			None => None,
+		};
+		if let Some(file_path) = scenario {
+			let mut collisions = self.config.physics.collisions.clone();
+			match world::persist::Serializer::reload_scenario(&file_path, self.simulation.world_mut(), &mut collisions) {
+				Ok(ref changed) if changed.is_empty() => {}
+				Ok(changed) => {
+					if changed.contains(&"collisions") {
+						if let Err(e) = self.update_physics(|physics| physics.collisions = collisions) {
+							warn!("{}", e);
+						}
+					}
+					let text = self.strings.fill("toast.reloaded", &[&changed.join(", "), &format!("{:?}", file_path)]);
+					info!("{}", text);
+					self.console.print(text);
+				}
+				Err(e) => warn!("Not reloading {:?}: {}", file_path, e),
+			}
+		}
+	}
+
+	fn reload_config(&mut self, config: config::Config) {
+		let mut changed = Vec::new();
+		if reload::differs(&config.fitness, &self.config.fitness) {
+			self.simulation.world_mut().set_fitness(config.fitness.clone());
+			changed.push("fitness");
+		}
+		if reload::differs(&config.mating, &self.config.mating) {
+			self.simulation.world_mut().set_mating(config.mating.clone());
+			changed.push("mating");
+		}
+		if reload::differs(&config.disease, &self.config.disease) {
+			self.simulation.world_mut().set_disease(config.disease.clone());
+			changed.push("disease");
+		}
+		if reload::differs(&config.game, &self.config.game) && self.challenge.is_none() {
+			self.simulation.world_mut().set_game_mode(config.game.clone());
+			changed.push("game");
+		}
+		if reload::differs(&config.mouse, &self.config.mouse) {
+			self.input_state.set_button_map(&Self::button_map(&config.mouse, &self.profile));
+			changed.push("mouse");
+		}
+		if reload::differs(&config.midi, &self.config.midi) {
+			self.input_state.set_midi_map(&config.midi.pad_map());
+			if config.midi.enabled != self.config.midi.enabled || config.midi.port != self.config.midi.port {
+				warn!("The MIDI port in {} is only opened at start, restart to use it", CONFIG_FILE);
+			}
+			changed.push("midi");
+		}
+		if reload::differs(&config.physics, &self.config.physics) {
+			self.simulation.set_physics(config.physics.clone());
+			changed.push("physics");
+		}
+		// read when needed, taking the new values is enough
+		if reload::differs(&config.warnings, &self.config.warnings) {
+			changed.push("warnings");
+		}
+		if reload::differs(&config.moments, &self.config.moments) {
+			changed.push("moments");
+		}
+		if reload::differs(&config.memory, &self.config.memory) {
+			self.next_memory_check = 0.;
+			changed.push("memory");
+		}
+		if reload::differs(&config.webhook, &self.config.webhook) {
+			changed.push("webhook");
+		}
+		if reload::differs(&config.caption, &self.config.caption) {
+			changed.push("caption");
+		}
+		if reload::differs(&config.stream, &self.config.stream) {
+			changed.push("stream");
+		}
+		if reload::differs(&config.share, &self.config.share) {
+			changed.push("share");
+		}
+		if reload::differs(&config.accessibility, &self.config.accessibility) {
+			changed.push("accessibility");
+		}
+		if reload::differs(&config.projection, &self.config.projection) {
+			changed.push("projection");
+		}
+		let reopen_osc = reload::differs(&config.osc, &self.config.osc);
+		if reopen_osc {
+			changed.push("osc");
+		}
+		if config.locale != self.config.locale {
+			warn!("The locale in {} is only read at start, restart to see it", CONFIG_FILE);
+		}
+		if reload::differs(&config.terrain, &self.config.terrain) {
+			warn!("The terrain in {} only grows with a new world, restart with -n to see it", CONFIG_FILE);
+		}
+		self.config = config;
+		if reopen_osc {
+			self.open_osc();
+		}
+		if !changed.is_empty() {
+			let text = self.strings.fill("toast.reloaded", &[&changed.join(", "), CONFIG_FILE]);
+			info!("{}", text);
+			self.console.print(text);
+		}
+	}
+
+	fn save_config(&self) -> Result<(), String> {
+		let file_name = self.config_home.join(CONFIG_FILE);
+		self.config
+			.save(&file_name)
+			.map_err(|e| format!("Failed to save {:?}: {}", file_name, e))
+	}
+
+	fn set_player_intent(&mut self, intent: segment::Intent) { self.simulation.world_mut().set_player_intent(intent) }
+
+	fn deselect_all_minions(&mut self) { self.simulation.world_mut().for_all_agents(&mut |agent| agent.state.deselect()); }
+
+	// freezes the whole selection, unless all of it is frozen already
+	fn toggle_freeze_selected(&mut self) {
+		let minions = self.simulation.world_mut().agents_mut(agent::AgentType::Minion);
+		let freeze = minions.values().any(|a| a.state.selected() && !a.state.is_frozen());
+		for agent in minions.values_mut().filter(|a| a.state.selected()) {
+			agent.state.freeze(freeze);
+		}
+	}
+
+	// takes over the first selected minion, or releases the one currently possessed
+	fn toggle_possess(&mut self) {
+		let target = match self.possessed.take() {
+			Some(id) => {
+				if let Some(agent) = self.simulation.world_mut().agent_mut(id) {
+					agent.state.possess(false);
+				}
+				None
+			}
+			None => self
+				.simulation
+				.world()
+				.agents(agent::AgentType::Minion)
+				.values()
+				.find(|a| a.state.selected())
+				.map(|a| a.id()),
+		};
+		if let Some(id) = target {
+			if let Some(agent) = self.simulation.world_mut().agent_mut(id) {
+				agent.state.possess(true);
+				self.possessed = Some(id);
+				self.is_camera_tracking = true;
+			}
+		}
+	}
+
+	fn steer_possessed(&mut self, thrust: Option<Position>) {
+		let steering = thrust.unwrap_or_else(origin);
+		let id = match self.possessed {
+			Some(id) => id,
+			None => return,
+		};
+		match self.simulation.world_mut().agent_mut(id) {
+			Some(agent) => agent.state.steer_puppet(steering),
+			// the puppet died
+			None => self.possessed = None,
+		}
+	}
+
+	fn inspector(&self) -> Option<AgentInspector> {
+		self.simulation.world()
+			.agents(agent::AgentType::Minion)
+			.values()
+			.find(|a| a.state.selected())
+			.map(|agent| AgentInspector {
+				id: agent.id(),
+				energy: agent.state.energy(),
+				energy_ratio: agent.state.energy_ratio(),
+				age: agent.age(),
+				stage: agent.life_stage(),
+				segments: agent.segments().len(),
+				resistance: agent.resistance(),
+				is_infected: agent.state.is_infected(),
+				is_frozen: agent.state.is_frozen(),
+				is_possessed: agent.state.is_possessed(),
+			})
+	}
+
+	fn select_minion(&mut self, id: Id) {
+		self.debug_flags |= DebugFlags::DEBUG_TARGETS;
+		self.simulation.world_mut()
+			.agent_mut(id)
+			.iter_mut()
+			.for_each(|a| a.state.toggle_selection());
+	}
+
+	pub fn save_gene_pool_to_file(&mut self) {
+		match self.simulation.world().dump(&self.saved_state_dir) {
+			Err(_) => error!("Failed to save gene pool"),
+			Ok(path) => {
+				info!("Saved {:?}", path);
+				self.picture(&path);
+			}
+		}
+	}
+
+	pub fn save_probes_to_file(&self) {
+		match self.simulation.world().dump_probes(&self.saved_state_dir) {
+			Err(_) => error!("Failed to save probe history"),
+			Ok(path) => info!("Saved {:?}", path),
+		}
+	}
+
+	pub fn save_world_to_file(&mut self) {
+		let result = self.simulation.world().serialize(&self.timeline.dir());
+		match result {
+			Err(_) => {
+				error!("Failed to save world state");
+				if self.config.warnings.autosave {
+					self.warn(warning::Warning::AutosaveFailed, true);
+				}
+			}
+			Ok(path) => {
+				info!("Saved {:?}", path);
+				if let Some(thumbnail) = self.picture(&path) {
+					self.report.add_save(self.simulation.world().seconds().get(), thumbnail);
+				}
+				self.set_last_saved(path);
+			}
+		}
+	}
+
+	fn set_last_saved(&mut self, name: path::PathBuf) {
+		self.profile.add_recent(&name);
+		self.last_saved = Some(name);
+		if let Err(e) = self.save_profile() {
+			error!("{}", e);
+		}
+	}
+
+	// restores the camera, the keys and the quality the window was left with
+	pub fn load_profile(&mut self, file_name: path::PathBuf) {
+		match profile::Profile::load(&file_name) {
+			Ok(profile) => {
+				info!("Loaded profile {:?}", file_name);
+				self.profile = profile;
+			}
+			Err(e) => info!("No profile in {:?} yet: {}", file_name, e),
+		}
+		if let Some(view) = self.profile.camera {
+			self.camera.set(Position::new(view.x, view.y));
+			self.zoom.input(num::clamp(view.zoom, VIEW_ZOOM_MIN, VIEW_ZOOM_MAX));
+			self.is_camera_tracking = view.tracking;
+		}
+		self.input_state.set_button_map(&Self::button_map(&self.config.mouse, &self.profile));
+		if let Some(world_file) = self.last_saved.clone() {
+			self.profile.add_recent(&world_file);
+		}
+		self.profile_file = Some(file_name);
+	}
+
+	// with the camera as it is now
+	pub fn save_profile(&mut self) -> Result<(), String> {
+		let file_name = match self.profile_file {
+			Some(ref file_name) => file_name.clone(),
+			None => return Ok(()),
+		};
+		let position = self.camera.position();
+		self.profile.camera = Some(profile::CameraView {
+			x: position.x,
+			y: position.y,
+			zoom: self.zoom.last_input(),
+			tracking: self.is_camera_tracking,
+		});
+		self.profile
+			.save(&file_name)
+			.map_err(|e| format!("Failed to save {:?}: {}", file_name, e))
+	}
+
+	fn profile_summary(&self) -> String {
+		let profile = &self.profile;
+		format!(
+			"file: {} quality: {} keys: {} recent: {}",
+			self.profile_file.as_ref().map_or("none".to_owned(), |file| file.display().to_string()),
+			profile.quality.name(),
+			profile.keys.len(),
+			profile.recent.len()
+		)
+	}
+
+	fn quality_summary(&self) -> String {
+		let adaptive = &self.profile.adaptive;
+		let dropped: Vec<&str> = self.adaptive.dropped().iter().map(|step| step.name()).collect();
+		format!(
+			"quality: {} auto: {} target: {:.0} fps dropped: {}",
+			self.profile.quality.name(),
+			if adaptive.enabled { "on" } else { "off" },
+			adaptive.target_fps,
+			if dropped.is_empty() { "none".to_owned() } else { dropped.join(", ") }
+		)
+	}
+
+	pub fn interact(&mut self, e: Event) {
+		self.simulation.post(e);
+		self.on_app_event(e)
+	}
+
+	pub fn has_ui_overlay(&self) -> bool { self.has_ui_overlay }
+
+	pub fn quit(&mut self) { self.is_running = false; }
+
+	fn restart_from_checkpoint(&mut self) {
+		let world_file = self.last_saved.clone();
+		self.restart_from(world_file.as_ref().map(|file| file.as_path())).is_ok();
+	}
+
+	// a new world if none is given
+	fn restart_from(&mut self, world_file: Option<&path::Path>) -> io::Result<()> {
+		self.abandon_challenge();
+		self.editing_annotation = None;
+		self.possessed = None;
+		self.simulation.clear_systems();
+		self.simulation.world_mut().clear();
+		let result = match world_file {
+			Some(world_file) => world::persist::Serializer::load(world_file, self.simulation.world_mut()),
+			None => Ok(()),
+		};
+		self.simulation.post(world::alert::Alert::RestartFromCheckpoint);
+		result
+	}
+
+	pub fn is_running(&self) -> bool { self.is_running }
+
+	fn quick_load_entries(&self) -> Vec<quick_load::Entry> {
+		quick_load::entries(&self.profile.recent, &self.saved_state_dir)
+	}
+
+	// the browser moves on to it, and the camera too if it is still alive
+	fn focus_relative(&mut self, id: Id) {
+		self.genealogy = Some(id);
+		let position = match self.simulation.world().agent(id) {
+			Some(agent) => agent.transform().position,
+			None => return,
+		};
+		self.deselect_all_minions();
+		self.select_minion(id);
+		self.is_camera_tracking = false;
+		self.camera.set(position);
+	}
+
+	fn genealogy_summary(&self, id: Id) -> String {
+		let view = genealogy::view(self.simulation.world(), id);
+		let dead = |alive: bool| if alive { "" } else { " \u{2020}" };
+		let generation = self.strings.fill("genealogy.generation", &[&view.generation.to_string()]);
+		let mut lines = vec![format!(
+			"{} {}{}, {}",
+			self.strings.get("inspector.agent"),
+			id,
+			dead(view.alive),
+			generation
+		)];
+		if let Some((changed, total)) = view.changed {
+			lines.push(self.strings.fill("genealogy.changed", &[&changed.to_string(), &total.to_string()]));
+		}
+		for relative in &view.relatives {
+			lines.push(format!("{} {}{}", self.strings.get(relative.kinship.key()), relative.id, dead(relative.alive)));
+		}
+		lines.join("\n")
+	}
+
+	// the genomes of two agents side by side, also of the dead the lineage still remembers
+	fn diff_genomes(&mut self, id: Id, other: Option<Id>) -> Result<String, String> {
+		let other = other
+			.or_else(|| self.simulation.world().lineage().get(id).and_then(|record| record.mother))
+			.ok_or_else(|| self.strings.fill("console.no_mother", &[&id.to_string()]))?;
+		let diff = genome_diff::diff(self.simulation.world(), id, other)?;
+		let mut lines = vec![self.strings.fill(
+			"genome.title",
+			&[&id.to_string(), &other.to_string(), &diff.changed().to_string(), &diff.len().to_string()],
+		)];
+		lines.extend(diff.consequences());
+		self.genome_diff = Some(Rc::new(diff));
+		Ok(lines.join("\n"))
+	}
+
+	// also of the dead the lineage still remembers
+	fn preview_agent(&mut self, id: Id) -> Result<String, String> {
+		let dna = genome_diff::dna_of(self.simulation.world(), id)
+			.ok_or_else(|| self.strings.fill("console.no_genome", &[&id.to_string()]))?;
+		let source = format!("{} {}", self.strings.get("inspector.agent"), id);
+		Ok(self.preview_genome(&source, dna))
+	}
+
+	fn preview_genome(&mut self, source: &str, dna: world::gen::Dna) -> String {
+		let preview = preview::Preview::new(self.simulation.world(), source, dna);
+		let summary = format!("{}: {}\n{}", source, preview.plan.summary(), preview.to_base64());
+		self.preview = Some(Rc::new(preview));
+		summary
+	}
+
+	// the preview grows again from the edited genome
+	fn edit_gene(&mut self, gene: usize, value: u8) -> Result<String, String> {
+		let (source, mut dna) = self
+			.preview
+			.as_ref()
+			.map(|preview| (preview.source.clone(), preview.dna.clone()))
+			.ok_or_else(|| self.strings.get("console.nothing_to_edit").to_owned())?;
+		if gene >= dna.len() {
+			return Err(self.strings.fill("console.no_gene", &[&dna.len().to_string()]));
+		}
+		dna[gene] = value;
+		Ok(self.preview_genome(&source, dna))
+	}
+
+	// the two selected minions if none are given, and the dead the lineage still remembers
+	fn breed(&mut self, parents: Option<(Id, Id)>) -> Result<String, String> {
+		let (mother, father) = match parents {
+			Some(parents) => parents,
+			None => {
+				let mut selected = self
+					.simulation
+					.world()
+					.agents(agent::AgentType::Minion)
+					.values()
+					.filter(|agent| agent.state.selected())
+					.map(|agent| agent.id())
+					.collect::<Vec<_>>();
+				if selected.len() != 2 {
+					return Err(self.strings.get("console.select_two").to_owned());
+				}
+				selected.sort();
+				(selected[0], selected[1])
+			}
+		};
+		if mother == father {
+			return Err(self.strings.get("console.self_breeding").to_owned());
+		}
+		let parent = |id: Id| {
+			genome_diff::dna_of(self.simulation.world(), id)
+				.map(|dna| breeding::Parent { id, dna })
+				.ok_or_else(|| self.strings.fill("console.no_genome", &[&id.to_string()]))
+		};
+		let parents = [parent(mother)?, parent(father)?];
+		self.breeding = Some(Rc::new(breeding::Workbench::new(self.simulation.world(), parents)));
+		self.breeding_summary()
+	}
+
+	fn rebreed(&mut self) {
+		let workbench = self.breeding.as_ref().map(|workbench| workbench.reroll(self.simulation.world()));
+		if let Some(workbench) = workbench {
+			self.breeding = Some(Rc::new(workbench));
+		}
+	}
+
+	// spawned at the camera as the child of both, then the first parent of the next round
+	fn pick_candidate(&mut self, index: usize) -> Result<String, String> {
+		let workbench = self.breeding.clone().ok_or_else(|| self.strings.get("console.nothing_to_pick").to_owned())?;
+		let dna = workbench
+			.candidates
+			.get(index)
+			.map(|candidate| candidate.dna.clone())
+			.ok_or_else(|| self.strings.fill("console.no_candidate", &[&(index + 1).to_string()]))?;
+		let mut genome = world::gen::Genome::copy_from(&dna);
+		let id = self.simulation.world_mut().new_minion_with(self.camera.position(), Motion::default(), &mut genome);
+		let now = self.simulation.world().seconds().get();
+		let (mother, father) = (&workbench.parents[0], &workbench.parents[1]);
+		self.simulation.world_mut().lineage_mut().bred(id, (mother.id, &mother.dna), (father.id, &father.dna), &dna, now);
+		self.breeding = Some(Rc::new(workbench.next_round(self.simulation.world(), id, dna)));
+		Ok(self.strings.fill("console.picked", &[&id.to_string(), &(workbench.round + 1).to_string()]))
+	}
+
+	fn breeding_summary(&self) -> Result<String, String> {
+		let workbench = self.breeding.as_ref().ok_or_else(|| self.strings.get("console.nothing_to_breed").to_owned())?;
+		let mut lines = vec![self.strings.fill(
+			"breeding.title",
+			&[
+				&workbench.round.to_string(),
+				&workbench.parents[0].id.to_string(),
+				&workbench.parents[1].id.to_string(),
+			],
+		)];
+		for (i, candidate) in workbench.candidates.iter().enumerate() {
+			lines.push(format!("{}: {}", i + 1, candidate.plan.summary()));
+		}
+		Ok(lines.join("\n"))
+	}
+
+	// at the camera, and the preview stays open to spawn more
+	fn spawn_preview(&mut self) -> Result<String, String> {
+		let dna = self
+			.preview
+			.as_ref()
+			.map(|preview| preview.dna.clone())
+			.ok_or_else(|| self.strings.get("console.nothing_to_spawn").to_owned())?;
+		let mut genome = world::gen::Genome::copy_from(&dna);
+		let id = self.simulation.world_mut().new_minion_with(self.camera.position(), Motion::default(), &mut genome);
+		Ok(self.strings.fill("console.spawned", &[&id.to_string()]))
+	}
+
+	fn toggle_quick_load(&mut self) {
+		self.quick_load = match self.quick_load {
+			Some(_) => None,
+			None => Some(self.quick_load_entries()),
+		}
+	}
+
+	// a world replaces this one, a gene pool breeds the next minions
+	fn quick_load_entry(&mut self, entry: &quick_load::Entry) -> Result<String, String> {
+		match entry.kind {
+			quick_load::Kind::World => {
+				let file_name = format!("{:?}", entry.file);
+				self.restart_from(Some(&entry.file))
+					.map_err(|e| self.strings.fill("console.load_failed", &[&file_name, &e.to_string()]))?;
+				self.timeline.follow(&entry.file);
+				self.set_last_saved(entry.file.clone());
+			}
+			quick_load::Kind::GenePool => {
+				let genomes = quick_load::read_gene_pool(&entry.file)?;
+				self.simulation.world_mut().replace_minion_gene_pool(&genomes);
+				self.simulation.world_mut().init_minions();
+			}
+		}
+		self.quick_load = None;
+		Ok(self.strings.fill("console.loaded", &[&entry.label]))
+	}
+
+	fn challenge_pack(&self) -> path::PathBuf {
+		self.profile.challenges.clone().unwrap_or_else(|| challenge::default_pack(&self.saved_state_dir))
+	}
+
+	fn toggle_challenges(&mut self) {
+		self.challenges = match self.challenges {
+			Some(_) => None,
+			None => {
+				let (entries, problems) = challenge::entries(&self.challenge_pack(), &self.profile.best);
+				for problem in problems {
+					warn!("{}", problem);
+				}
+				Some(entries)
+			}
+		}
+	}
+
+	// a new world on the layout of the challenge, with its objectives in place of those of the config
+	fn start_challenge(&mut self, entry: &challenge::Entry) -> Result<String, String> {
+		self.restart_from(None).map_err(|e| self.strings.fill("console.restart_failed", &[&e.to_string()]))?;
+		if let Some(scenario) = entry.scenario() {
+			self.load_scenario(&scenario)?;
+		}
+		self.simulation.world_mut().set_game_mode(world::objective::GameMode {
+			enabled: true,
+			objectives: entry.challenge.objectives.clone(),
+		});
+		let now = self.simulation.world().seconds().get();
+		self.challenge = Some(challenge::Attempt::new(entry, now));
+		self.challenges = None;
+		let text = self.strings.fill("toast.challenge_started", &[&entry.challenge.title]);
+		self.report.note(now, text.clone());
+		if entry.challenge.description.is_empty() {
+			Ok(text)
+		} else {
+			Ok(format!("{}: {}", text, entry.challenge.description))
+		}
+	}
+
+	// back to the game of the config, if any
+	fn abandon_challenge(&mut self) -> Option<challenge::Attempt> {
+		let attempt = self.challenge.take()?;
+		self.simulation.world_mut().set_game_mode(self.config.game.clone());
+		self.simulation.world_mut().set_score(world::objective::Score::default());
+		Some(attempt)
+	}
+
+	// won when all the objectives are met, lost when the time runs out
+	fn finish_challenge(&mut self, won: bool) {
+		let score = self.simulation.world().score().points;
+		let attempt = match self.abandon_challenge() {
+			Some(attempt) => attempt,
+			None => return,
+		};
+		let text = if won {
+			let is_best = self.profile.add_score(&attempt.name, score);
+			if is_best {
+				if let Err(e) = self.save_profile() {
+					error!("{}", e);
+				}
+			}
+			let key = if is_best { "toast.challenge_best" } else { "toast.challenge_won" };
+			self.strings.fill(key, &[&attempt.title, &format!("{:.0}", score)])
+		} else {
+			self.strings.fill("toast.challenge_lost", &[&attempt.title, &format!("{:.0}", score)])
+		};
+		self.console.print(text.clone());
+		self.report.note(self.simulation.world().seconds().get(), text);
+	}
+
+	fn challenges_summary(&self) -> String {
+		let pack = self.challenge_pack();
+		let (entries, problems) = challenge::entries(&pack, &self.profile.best);
+		let mut lines = vec![format!("pack: {}", pack.display())];
+		if let Some(ref attempt) = self.challenge {
+			let minutes = (self.simulation.world().seconds().get() - attempt.started) / 60.;
+			lines.push(match attempt.minutes {
+				Some(limit) => format!("playing: {}, {:.1} of {:.1} minutes", attempt.title, minutes, limit),
+				None => format!("playing: {}, {:.1} minutes", attempt.title, minutes),
 			});
+		}
+		if entries.is_empty() {
+			lines.push(self.strings.get("challenges.empty").to_owned());
+		}
+		for (i, entry) in entries.iter().enumerate() {
+			let limit = entry.challenge.minutes.map_or_else(String::new, |minutes| format!(" in {:.1} minutes", minutes));
+			lines.push(format!("{}: {}, {} objectives{}", i + 1, entry.label(), entry.challenge.objectives.len(), limit));
+		}
+		lines.extend(problems);
+		lines.join("\n")
+	}
+
+	fn save_checkpoint(&mut self, name: &str) -> Result<String, String> {
+		let (checkpoint, file_name) = self.timeline.new_checkpoint(name, self.simulation.world().seconds().get())?;
+		world::persist::Serializer::save(&file_name, self.simulation.world())
+			.map_err(|e| self.strings.fill("console.save_failed", &[&format!("{:?}", file_name), &e.to_string()]))?;
+		self.picture(&file_name);
+		self.timeline
+			.record_checkpoint(checkpoint)
+			.map_err(|e| self.strings.fill("console.record_checkpoint_failed", &[name, &e.to_string()]))?;
+		self.profile.add_recent(&file_name);
+		Ok(self.strings.fill("console.checkpoint_saved", &[name, self.timeline.branch()]))
+	}
+
+	// the saves from then on go to a new branch, the old timeline stays as it was
+	fn load_checkpoint(&mut self, name: &str) -> Result<String, String> {
+		let (parent, checkpoint) = self
+			.timeline
+			.find_checkpoint(name)
+			.ok_or_else(|| self.strings.fill("console.no_checkpoint", &[name]))?;
+		let file_name = self.timeline.checkpoint_path(&parent, &checkpoint);
+		self.restart_from(Some(&file_name))
+			.map_err(|e| self.strings.fill("console.load_failed", &[&format!("{:?}", file_name), &e.to_string()]))?;
+		let branch = self
+			.timeline
+			.fork(&parent, &checkpoint)
+			.map_err(|e| self.strings.fill("console.branch_failed", &[&e.to_string()]))?;
+		self.save_world_to_file();
+		Ok(self.strings.fill("console.checkpoint_loaded", &[&checkpoint.name, &parent, &branch]))
+	}
+
+	fn checkpoints_summary(&self) -> String {
+		let lines = self
+			.timeline
+			.branches()
+			.iter()
+			.flat_map(|branch| {
+				self.timeline
+					.info(branch)
+					.checkpoints
+					.into_iter()
+					.map(move |checkpoint| format!("{}: {} at {:.0}s", branch, checkpoint.name, checkpoint.seconds))
+			}).collect::<Vec<_>>();
+		if lines.is_empty() {
+			self.strings.get("console.no_checkpoints").to_owned()
+		} else {
+			lines.join("\n")
+		}
+	}
+
+	fn branches_summary(&self) -> String {
+		self.timeline
+			.branches()
+			.iter()
+			.map(|branch| {
+				let info = self.timeline.info(branch);
+				let current = if branch == self.timeline.branch() { "* " } else { "  " };
+				let origin = match (info.parent, info.forked_from) {
+					(Some(parent), Some(checkpoint)) => format!(", from {} of {}", checkpoint, parent),
+					_ => String::new(),
+				};
+				format!("{}{}: {} checkpoints{}", current, branch, info.checkpoints.len(), origin)
+			}).collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	fn compare(&mut self, files: &[path::PathBuf]) -> Result<String, String> {
+		let comparison = comparison::Comparison::load(files, &self.saved_state_dir)?;
+		let labels: Vec<&str> = comparison.runs.iter().map(|run| run.label.as_str()).collect();
+		let summary = format!("comparing {}", labels.join(", "));
+		self.comparison = Some(Rc::new(comparison));
+		Ok(summary)
+	}
+
+	// names without a folder are looked for in the saved state directory, as with compare
+	fn diff_worlds(&self, before: &str, after: &str) -> Result<String, String> {
+		let resolve = |file: &str| {
+			let file = path::PathBuf::from(file);
+			if file.is_file() {
+				file
+			} else {
+				self.saved_state_dir.join(file)
+			}
+		};
+		let diff = world_diff::WorldDiff::load(&resolve(before), &resolve(after))?;
+		let report_file = self.saved_state_dir.join(WORLD_DIFF_FILE);
+		diff.save(&report_file)?;
+		Ok(format!("{}\nwritten to {:?}", diff.to_text(), report_file))
+	}
+
+	fn recent_summary(&self) -> String {
+		let entries = self.quick_load_entries();
+		if entries.is_empty() {
+			return self.strings.get("quick_load.empty").to_owned();
+		}
+		entries
+			.iter()
+			.enumerate()
+			.map(|(i, entry)| format!("{}: {}", i + 1, entry.label))
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	// next to a file just saved, drawn offscreen so that it needs no window
+	fn picture(&self, file_name: &path::Path) -> Option<path::PathBuf> {
+		let thumbnail = quick_load::thumbnail_of(file_name);
+		let frame = raster::thumbnail(self.simulation.world(), SAVE_THUMBNAIL_WIDTH);
+		match quick_load::write_thumbnail(&frame, &thumbnail) {
+			Ok(()) => Some(thumbnail),
+			Err(e) => {
+				error!("Could not save thumbnail {:?}: {}", thumbnail, e);
+				None
+			}
+		}
+	}
+
+	// paused with nothing held, the next frame can wait for input
+	pub fn is_idle(&self) -> bool { self.is_paused && self.input_state.is_idle() }
+
+	pub fn is_capturing(&self) -> bool { self.is_capturing }
+
+	// where to stream the frames to, if streaming
+	pub fn stream(&self) -> Option<&stream::StreamSettings> {
+		Some(&self.config.stream).filter(|stream| self.is_streaming && !stream.url.is_empty())
+	}
+
+	fn set_streaming(&mut self, enabled: bool) -> Result<String, String> {
+		if enabled && self.config.stream.url.is_empty() {
+			return Err(self.strings.get("console.no_stream_url").to_owned());
+		}
+		self.is_streaming = enabled;
+		Ok(self.stream_summary())
+	}
+
+	fn stream_summary(&self) -> String {
+		let url = &self.config.stream.url;
+		match (self.is_streaming, url.is_empty()) {
+			(_, true) => "not streaming, no url".to_owned(),
+			(true, false) => format!("streaming to {}", url),
+			(false, false) => format!("not streaming, ready for {}", url),
+		}
+	}
+
+	// the name to share the frames under, none if not sharing
+	pub fn share(&self) -> Option<&str> {
+		Some(&self.config.share).filter(|share| share.enabled).map(|share| share.name.as_str())
 	}
 
-	fn cleanup_after(&mut self) { self.register_all(); }
+	fn set_sharing(&mut self, enabled: bool) -> Result<String, String> {
+		if enabled && !share::Share::is_supported() {
+			return Err(self.strings.get("console.no_share").to_owned());
+		}
+		self.config.share.enabled = enabled;
+		self.save_config()?;
+		Ok(self.share_summary())
+	}
+
+	fn share_summary(&self) -> String {
+		let share = &self.config.share;
+		format!("sharing: {} as {}", if share.enabled { "on" } else { "off" }, share.name)
+	}
 
-	fn tick(&mut self, dt: Seconds) { self.world.tick(dt); }
+	// the recorded part of the world, None when recording the whole window
+	fn capture_extent(&self) -> Option<Rect> {
+		let camera = self.camera.position();
+		match self.capture_region {
+			capture::CaptureRegion::Window => None,
+			capture::CaptureRegion::Screen(rect) => {
+				let half = Position::new(self.viewport.width() as f32, self.viewport.height() as f32) * 0.5;
+				let pixel_size = self.viewport.pixel_size();
+				let to_world = |x: u32, y: u32| camera + (Position::new(x as f32, y as f32) - half) * pixel_size;
+				Some(Rect::from_corners(
+					to_world(rect.x, rect.y),
+					to_world(rect.x + rect.w, rect.y + rect.h),
+				))
+			}
+			capture::CaptureRegion::World { extent, follow: true } => {
+				let half = (extent.max - extent.min) * 0.5;
+				Some(Rect::from_corners(camera - half, camera + half))
+			}
+			capture::CaptureRegion::World { extent, .. } => Some(extent),
+		}
+	}
+
+	pub fn capture_rect(&self) -> Option<capture::PixelRect> {
+		self.capture_extent().map(|extent| self.to_pixel_rect(&extent))
+	}
+
+	fn to_pixel_rect(&self, extent: &Rect) -> capture::PixelRect {
+		let camera = self.camera.position();
+		let half = Position::new(self.viewport.width() as f32, self.viewport.height() as f32) * 0.5;
+		let min = self.viewport.to_pixels(extent.min - camera) + half;
+		let max = self.viewport.to_pixels(extent.max - camera) + half;
+		capture::PixelRect {
+			x: min.x.max(0.) as u32,
+			y: min.y.max(0.) as u32,
+			w: (max.x - min.x).max(1.) as u32,
+			h: (max.y - min.y).max(1.) as u32,
+		}
+	}
+
+	// what gets burnt into the recorded frames, if enabled
+	pub fn capture_caption(&self) -> Option<caption::Caption> {
+		let rules = &self.config.caption;
+		if !rules.enabled {
+			return None;
+		}
+		let mut lines = Vec::new();
+		if !rules.title.is_empty() {
+			lines.push(rules.title.clone());
+		}
+		if rules.stats {
+			lines.push(format!(
+				"{:.1}s frame {} population {} speed x{}",
+				self.simulation.world().seconds().get(),
+				self.frame_count,
+				self.simulation.world().agents(world::agent::AgentType::Minion).len(),
+				self.speed_factor()
+			));
+		}
+		let mut labels = Vec::new();
+		if rules.annotations {
+			let camera = self.camera.position();
+			let half = Position::new(self.viewport.width() as f32, self.viewport.height() as f32) * 0.5;
+			for annotation in self.simulation.world().annotations() {
+				let p = self.viewport.to_pixels(annotation.position - camera) + half;
+				labels.push((p.x, p.y, annotation.text.clone()));
+			}
+		}
+		Some(caption::Caption {
+			lines,
+			labels,
+			font_size: rules.font_size,
+		})
+	}
+
+	fn set_capture_region(&mut self, region: capture::CaptureRegion) -> Result<String, String> {
+		self.capture_region = region;
+		Ok(self.capture_summary())
+	}
+
+	// the measured rectangle, as the extent of a new capture region
+	fn measured_extent(&self) -> Result<Rect, String> {
+		self.measurement
+			.map(|(start, end)| Rect::from_corners(start, end))
+			.ok_or_else(|| self.strings.get("console.measure_region").to_owned())
+	}
+
+	fn capture_summary(&self) -> String {
+		match self.capture_region {
+			capture::CaptureRegion::Window => "capturing the whole window".to_owned(),
+			capture::CaptureRegion::Screen(rect) => {
+				format!("capturing {}x{} pixels at {}, {}", rect.w, rect.h, rect.x, rect.y)
+			}
+			capture::CaptureRegion::World { extent, follow } => format!(
+				"capturing {:.1}x{:.1} world units {}",
+				extent.max.x - extent.min.x,
+				extent.max.y - extent.min.y,
+				if follow { "around the camera" } else { "pinned" }
+			),
+		}
+	}
+
+	pub fn on_input_event(&mut self, e: &input::Event) {
+		if let input::Event::MidiControl(control, value) = *e {
+			self.turn_knob(control, value);
+		}
+		self.input_state.event(e);
+	}
+
+	fn turn_knob(&mut self, control: u8, value: input::AxisValue) {
+		match self.config.midi.knob(control) {
+			Some(midi::Parameter::Speed) => {
+				let range = MIDI_SPEED_MAX / MIDI_SPEED_MIN;
+				self.speed_knob = Some(MIDI_SPEED_MIN * range.powf(SpeedFactor::from(value)));
+			}
+			// the world only, it would be saved at every turn otherwise
+			Some(midi::Parameter::Mutation) => {
+				let mut mating = self.simulation.world().mating().clone();
+				mating.mutation = value * MIDI_MUTATION_MAX;
+				self.simulation.world_mut().set_mating(mating);
+			}
+			Some(midi::Parameter::Light) => self.light_gain = value * MIDI_LIGHT_MAX,
+			None => {}
+		}
+	}
+
+	pub fn ui_scale(&self) -> f64 { f64::from(self.config.accessibility.ui_scale()) }
+
+	pub fn projection(&self) -> render::Projection { self.config.projection.projection() }
+
+	pub fn render_quality(&self) -> render::Quality {
+		if self.adaptive.has_bloom() {
+			self.profile.quality
+		} else {
+			render::Quality::Low
+		}
+	}
+
+	pub fn speed_factor(&self) -> SpeedFactor { self.speed_knob.unwrap_or_else(|| self.speed_factors.get()) }
+
+	// the MIDI input to open, if any
+	pub fn midi_port(&self) -> Option<String> {
+		if self.config.midi.enabled {
+			Some(self.config.midi.port.clone())
+		} else {
+			None
+		}
+	}
+
+	fn update_input<C>(&mut self, dt: Seconds)
+	where C: InputController {
+		self.input_state.pre_update(&self.viewport);
+
+		for e in C::update(&self.input_state, &self.viewport, &self.camera, dt) {
+			self.interact(e)
+		}
+		self.input_state.post_update();
+	}
+
+	fn cursor_position(&self) -> Position {
+		use frontend::input::InputRead;
+		self.camera.to_world(self.viewport.to_view(self.input_state.mouse_position()))
+	}
+
+	pub fn on_resize(&mut self, width: u32, height: u32) {
+		self.viewport = Viewport::rect(width, height, self.viewport.scale);
+	}
+
+	fn from_transform(transform: &Transform) -> Matrix4<f32> {
+		use cgmath::Rotation3;
+		let position = transform.position;
+		let angle = transform.angle;
+		let rot = Matrix4::from(cgmath::Quaternion::from_axis_angle(
+			cgmath::Vector3::unit_z(),
+			cgmath::Rad(angle),
+		));
+		let trans = Matrix4::from_translation(cgmath::Vector3::new(position.x, position.y, 0.0));
+
+		trans * rot
+	}
+
+	pub fn init(&mut self) {
+		match self.telemetry.open(&self.saved_state_dir) {
+			Err(_) => error!("Failed to open telemetry log"),
+			Ok(path) => info!("Logging telemetry to {:?}", path),
+		}
+		self.open_osc();
+		self.simulation.post(world::alert::Alert::BeginSimulation);
+	}
+
+	fn open_osc(&mut self) {
+		match self.osc.open(&self.config.osc) {
+			Err(e) => error!("Failed to open OSC to {}: {}", self.config.osc.address, e),
+			Ok(Some(target)) => info!("Sending OSC to {}", target),
+			Ok(None) => {}
+		}
+	}
+
+	// the AI looks closer at what is in view, and the chunks of an unbounded world stream around the camera
+	fn update_focus(&mut self) {
+		let half = Position::new(self.viewport.width() as f32, self.viewport.height() as f32)
+			* (0.5 * self.viewport.pixel_size());
+		let radius = half.x.hypot(half.y) * AI_LOD_VIEW_MARGIN * self.adaptive.focus_scale();
+		self.simulation.set_focus(Some((self.camera.position(), radius)));
+	}
 
 	pub fn receive(&mut self) {
 		for event in self.reply_inbox.drain() {
@@ -636,6 +2747,12 @@ impl App {
 		P: ui::AlertPlayer<world::alert::Alert, E> + ui::AlertPlayer<Event, E>,
 		E: Debug, {
 		for alert in self.alert_inbox.drain() {
+			if let Message::Alert(world::alert::Alert::NewMinion) = alert {
+				self.report.hatch();
+			}
+			if let Message::Alert(ref alert) = alert {
+				self.osc.alert(&self.config.osc, alert);
+			}
 			match alert {
 				Message::Event(ref alert) => if let Err(e) = alert_player.play(alert) {
 					error!("Unable to play alert {:?}", e)
@@ -657,12 +2774,16 @@ impl App {
 		let frame_time_smooth = self.frame_smooth.smooth(frame_time);
 
 		let player_follow = if self.is_camera_tracking {
-			self.world.get_player_segment().map(|s| s.transform.position)
+			match self.possessed.and_then(|id| self.simulation.world().agent(id)) {
+				Some(puppet) => Some(puppet.transform().position),
+				None => self.simulation.world().get_player_segment().map(|s| s.transform.position),
+			}
 		} else {
 			None
 		};
+		let widening = self.config.projection.widening(self.viewport.ratio);
 		self.viewport
-			.scale(VIEW_SCALE_BASE / self.zoom.update(frame_time_smooth.get() as f32));
+			.scale(VIEW_SCALE_BASE * widening / self.zoom.update(frame_time_smooth.get() as f32));
 		self.camera.set_inertia(CAMERA_INERTIA * self.zoom.get());
 		self.camera.follow(player_follow);
 		self.camera.update(frame_time_smooth);
@@ -674,7 +2795,7 @@ impl App {
 		let speed_factor = if self.is_paused {
 			0.0 as SpeedFactor
 		} else {
-			self.speed_factors.get()
+			self.speed_factor()
 		};
 		let quantum = quantum_target.unwrap_or_else(|| num::clamp(target_duration, MIN_FRAME_LENGTH, MAX_FRAME_LENGTH));
 		let (dt, rounds) = if speed_factor <= 1.0 {
@@ -690,6 +2811,12 @@ impl App {
 
 		let simulation_update = self.simulate(dt);
 		self.frame_count += 1;
+		self.check_frame_warnings(1. / target_duration as f32);
+		self.adapt_quality(1. / target_duration as f32);
+		self.check_memory();
+		self.detect_moments();
+		let now = self.wall_clock.seconds().get();
+		self.diagnostics.record(&self.input_state.lag(), frame_time.get(), now, !self.is_paused);
 
 		FrameUpdate {
 			timestamp: self.wall_clock.seconds(),
@@ -699,15 +2826,240 @@ impl App {
 			elapsed: self.frame_elapsed.seconds(),
 			duration_smooth: frame_time_smooth,
 			fps: 1. / target_duration as f32,
+			memory: self.memory,
+			diagnostics: if self.debug_flags.contains(DebugFlags::DEBUG_DIAGNOSTICS) {
+				Some(self.diagnostics.clone())
+			} else {
+				None
+			},
 			simulation: simulation_update,
+			cursor: if self.has_grid { Some(self.cursor_position()) } else { None },
+			labels: self.world_labels(),
+			editing: self
+				.editing_annotation
+				.filter(|_| !self.console.is_open())
+				.and_then(|index| self.simulation.world().annotations().get(index))
+				.map(|annotation| annotation.text.clone()),
+			inspector: self.inspector(),
+			console: self.console.view(),
+			quick_load: self.quick_load.clone(),
+			challenges: self.challenges.clone(),
+			comparison: self.comparison.clone(),
+			genealogy: self.genealogy.map(|id| genealogy::view(self.simulation.world(), id)),
+			genome_diff: self.genome_diff.clone(),
+			preview: self.preview.clone(),
+			genome_editor: self.genome_editor.filter(|_| self.preview.is_some()),
+			breeding: self.breeding.clone(),
+			toasts: self.warnings.toasts(self.wall_clock.seconds().get()),
+			strings: self.strings.clone(),
+		}
+	}
+
+	// fires a warning when its condition starts holding, and forwards it to the webhook
+	fn warn(&mut self, warning: warning::Warning, holds: bool) {
+		let now = self.wall_clock.seconds().get();
+		if let Some(text) = self.warnings.check(&self.config.warnings, &self.strings, warning, holds, now) {
+			self.config.webhook.post(&text, None);
+			self.report.note(self.simulation.world().seconds().get(), text);
+		}
+	}
+
+	fn check_frame_warnings(&mut self, fps: f32) {
+		let now = self.wall_clock.seconds().get();
+		let min_fps = self.config.warnings.min_fps;
+		let min_free_disk = self.config.warnings.min_free_disk;
+		// the first frames are slow anyway, and the paused app waits for input
+		let low_fps =
+			min_fps > 0. && self.frame_count > WARNING_FPS_WARMUP_FRAMES && !self.is_paused && fps < min_fps;
+		self.warn(warning::Warning::LowFps(fps), low_fps);
+		if self.is_capturing && min_free_disk > 0 && now >= self.next_disk_check {
+			self.next_disk_check = now + WARNING_DISK_CHECK_INTERVAL;
+			let folder = path::Path::new(CAPTURE_FOLDER);
+			let folder = if folder.exists() { folder } else { path::Path::new(".") };
+			if let Ok(bytes) = fs2::available_space(folder) {
+				let megabytes = bytes / (1024 * 1024);
+				self.warn(warning::Warning::LowDiskSpace(megabytes), megabytes < min_free_disk);
+			}
+		}
+	}
+
+	fn adapt_quality(&mut self, fps: f32) {
+		// the first frames are slow anyway, and the paused app waits for input
+		let is_measurable = self.frame_count > WARNING_FPS_WARMUP_FRAMES && !self.is_paused;
+		if !is_measurable && self.profile.adaptive.enabled {
+			return;
+		}
+		let now = self.wall_clock.seconds().get();
+		if let Some(text) = self.adaptive.update(&self.profile.adaptive, fps, now) {
+			info!("{}", text);
+			self.console.print(text);
+		}
+	}
+
+	// the parts over their budget forget their oldest records
+	fn check_memory(&mut self) {
+		let now = self.wall_clock.seconds().get();
+		if now < self.next_memory_check {
+			return;
+		}
+		self.next_memory_check = now + MEMORY_CHECK_INTERVAL;
+		self.memory = memory::MemoryUsage {
+			world: memory::world_footprint(self.simulation.world()),
+			lineage: self.simulation.world().lineage().footprint(),
+			probes: self.simulation.world().probes().iter().map(|probe| probe.footprint()).sum(),
+			report: self.report.footprint(),
+			capture: self.memory.capture,
+		};
+		let budget = self.config.memory.clone();
+		let usage = self.memory;
+		let total = usage.total();
+		let mut trimmed = Vec::new();
+		if let Some(keep) = budget.keep(usage.lineage, budget.lineage, total) {
+			let records = (self.simulation.world().lineage().len() as f64 * keep) as usize;
+			self.simulation.world_mut().lineage_mut().trim(records);
+			trimmed.push(format!("the lineage to {} records", records));
+		}
+		if let Some(keep) = budget.keep(usage.probes, budget.probes, total) {
+			for probe in self.simulation.world_mut().probes_mut() {
+				let records = (probe.history().len() as f64 * keep) as usize;
+				probe.trim_history(records);
+			}
+			trimmed.push(format!("the probe history to {:.0}%", keep * 100.));
+		}
+		if let Some(keep) = budget.keep(usage.report, budget.report, total) {
+			self.report.trim(keep);
+			trimmed.push(format!("the report to {:.0}%", keep * 100.));
+		}
+		if !trimmed.is_empty() {
+			let text = format!("Over the memory budget, trimmed {}", trimmed.join(", "));
+			warn!("{}", text);
+			self.console.print(text.clone());
+			self.report.note(self.simulation.world().seconds().get(), text);
+		}
+	}
+
+	pub fn set_capture_memory(&mut self, bytes: usize) { self.memory.capture = bytes; }
+
+	pub fn capture_memory_budget(&self) -> usize { self.config.memory.capture * MEGABYTE }
+
+	fn update_memory_budget(&mut self, part: &str, megabytes: usize) -> Result<String, String> {
+		match self.config.memory.part_mut(part) {
+			Some(budget) => *budget = megabytes,
+			None => {
+				return Err(self.strings.fill("console.unknown_part", &[part, &memory::MemoryBudget::PARTS.join(", ")]))
+			}
 		}
+		self.next_memory_check = 0.;
+		self.save_config()?;
+		Ok(self.memory_summary())
+	}
+
+	fn memory_summary(&self) -> String {
+		let budget = &self.config.memory;
+		let megabytes = |bytes: usize| bytes as f64 / MEGABYTE as f64;
+		let mut lines = vec![format!("total: {:.1} MB of {} MB", megabytes(self.memory.total()), budget.total)];
+		for &(name, bytes) in self.memory.parts().iter() {
+			let limit = match name {
+				"lineage" => budget.lineage,
+				"probes" => budget.probes,
+				"report" => budget.report,
+				"capture" => budget.capture,
+				_ => 0,
+			};
+			if limit > 0 {
+				lines.push(format!("{}: {:.1} MB of {} MB", name, megabytes(bytes), limit));
+			} else {
+				lines.push(format!("{}: {:.1} MB", name, megabytes(bytes)));
+			}
+		}
+		lines.join("\n")
+	}
+
+	fn diagnostics_summary(&self) -> String {
+		let diagnostics = &self.diagnostics;
+		let shown = self.debug_flags.contains(DebugFlags::DEBUG_DIAGNOSTICS);
+		vec![
+			format!("overlay: {}", if shown { "on" } else { "off" }),
+			format!("input latency: {:.1} ms, peak {:.1} ms", diagnostics.latency, diagnostics.peak_latency()),
+			format!("events per update: {}, peak {}", diagnostics.events, diagnostics.peak_events()),
+			format!("dropped taps: {}", diagnostics.dropped_taps),
+			format!("missed frames: {} of {}", diagnostics.missed_frames, diagnostics.frames),
+		].join("\n")
+	}
+
+	// where the frontend should save the next picture for the session report, if one is due
+	pub fn next_report_thumbnail(&mut self) -> Option<path::PathBuf> {
+		let now = self.wall_clock.seconds().get();
+		if now < self.next_report_thumbnail {
+			return None;
+		}
+		self.next_report_thumbnail = now + REPORT_THUMBNAIL_INTERVAL;
+		let seconds = self.simulation.world().seconds().get();
+		let file_name = self
+			.saved_state_dir
+			.join(format!("{}{:08}.png", REPORT_THUMBNAIL_PREFIX, seconds as u64));
+		fs::create_dir_all(&self.saved_state_dir).is_ok();
+		self.report.add_thumbnail(seconds, file_name.clone());
+		Some(file_name)
+	}
+
+	pub fn write_report(&self) {
+		match self.report.write(self.simulation.world(), &self.saved_state_dir) {
+			Err(_) => error!("Failed to write session report"),
+			Ok(path) => info!("Session report written to {:?}", path),
+		}
+	}
+
+	pub fn is_summary_due(&self) -> bool {
+		let webhook = &self.config.webhook;
+		webhook.is_enabled() && webhook.summary_interval > 0. && self.wall_clock.seconds().get() >= self.next_summary
+	}
+
+	// where the frontend should save a picture to attach to the next summary
+	pub fn thumbnail_path(&self) -> Option<path::PathBuf> {
+		if self.config.webhook.thumbnail {
+			Some(self.saved_state_dir.join(WEBHOOK_THUMBNAIL_FILE))
+		} else {
+			None
+		}
+	}
+
+	pub fn post_summary(&mut self, thumbnail: Option<&path::Path>) {
+		self.next_summary = self.wall_clock.seconds().get() + self.config.webhook.summary_interval;
+		let minions = self.simulation.world().agents(agent::AgentType::Minion);
+		let text = format!(
+			"Simulated {:.0} minutes, population {}, {} infected, {} extinctions",
+			self.simulation.world().seconds().get() / 60.,
+			minions.len(),
+			minions.values().filter(|a| a.state.is_infected()).count(),
+			self.simulation.world().extinctions()
+		);
+		self.config.webhook.post(&text, thumbnail);
 	}
 
 	pub fn simulate(&mut self, dt: Seconds) -> SimulationUpdate {
-		self.cleanup_before();
-		self.update_systems(dt);
-		self.cleanup_after();
-		self.tick(dt);
+		self.update_focus();
+		let dead = self.simulation.step(dt);
+		if self.config.moments.enabled {
+			self.moments.died(&dead);
+		}
+		if cfg!(debug_assertions) && self.simulations_count % PHYSICS_CHECK_STEPS == 0 {
+			self.check_physics();
+		}
+		self.telemetry.sample(self.simulation.world());
+		self.report.sample(self.simulation.world());
+		self.osc.sample(&self.config.osc, self.wall_clock.seconds().get(), self.simulation.world());
+		self.hot_reload();
+		if self.challenge.as_ref().map_or(false, |attempt| attempt.is_over(self.simulation.world().seconds().get())) {
+			self.finish_challenge(false);
+		}
+
+		let population = self.simulation.world().agents(agent::AgentType::Minion).len();
+		let min_population = self.config.warnings.min_population;
+		self.warn(
+			warning::Warning::LowPopulation(population),
+			min_population > 0 && population < min_population,
+		);
 
 		self.simulations_count += 1;
 
@@ -715,13 +3067,11 @@ impl App {
 			timestamp: self.wall_clock.seconds(),
 			dt,
 			count: self.simulations_count,
-			elapsed: self.world.seconds(),
-			population: self.world.agents(agent::AgentType::Minion).len(),
-			extinctions: self.world.extinctions(),
+			elapsed: self.simulation.world().seconds(),
+			population,
+			extinctions: self.simulation.world().extinctions(),
+			energy: *self.simulation.world().energy().rates(),
+			score: if self.simulation.world().game_mode().enabled { Some(self.simulation.world().score().clone()) } else { None },
 		}
 	}
 }
-
-impl WorldTransform for math::Inertial<f32> {
-	fn to_world(&self, view_position: Position) -> Position { view_position + self.position() }
-}
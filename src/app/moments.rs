@@ -0,0 +1,23 @@
+use app::constants::*;
+use core::clock::SecondsValue;
+
+// What the app does when something unusual happens in the world, off unless asked for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct MomentRules {
+	pub enabled: bool,
+	// the seconds before the moment saved as a video, none if zero
+	pub clip_seconds: SecondsValue,
+	// a checkpoint on the timeline at the moment
+	pub checkpoint: bool,
+}
+
+impl Default for MomentRules {
+	fn default() -> Self {
+		MomentRules {
+			enabled: false,
+			clip_seconds: MOMENT_CLIP_SECONDS,
+			checkpoint: true,
+		}
+	}
+}
@@ -0,0 +1,110 @@
+use frontend::input::Key;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MouseButton {
+	Left,
+	Right,
+	Middle,
+}
+
+impl MouseButton {
+	pub fn parse(name: &str) -> Option<MouseButton> {
+		match name {
+			"left" => Some(MouseButton::Left),
+			"right" => Some(MouseButton::Right),
+			"middle" => Some(MouseButton::Middle),
+			_ => None,
+		}
+	}
+
+	fn key(self) -> Key {
+		match self {
+			MouseButton::Left => Key::MouseLeft,
+			MouseButton::Right => Key::MouseRight,
+			MouseButton::Middle => Key::MouseMiddle,
+		}
+	}
+
+	fn mirror(self) -> MouseButton {
+		match self {
+			MouseButton::Left => MouseButton::Right,
+			MouseButton::Right => MouseButton::Left,
+			MouseButton::Middle => MouseButton::Middle,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum MouseAction {
+	Shoot,
+	Drag,
+	Spawn,
+}
+
+impl MouseAction {
+	pub fn parse(name: &str) -> Option<MouseAction> {
+		match name {
+			"shoot" => Some(MouseAction::Shoot),
+			"drag" => Some(MouseAction::Drag),
+			"spawn" => Some(MouseAction::Spawn),
+			_ => None,
+		}
+	}
+}
+
+// Which button does what, the controller sees the buttons by what they do
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MouseButtons {
+	// aim and shoot, and the tools with modifiers
+	pub shoot: MouseButton,
+	// pan the camera
+	pub drag: MouseButton,
+	pub spawn: MouseButton,
+	// mirrors left and right, after the above
+	pub left_handed: bool,
+}
+
+impl Default for MouseButtons {
+	fn default() -> Self {
+		MouseButtons {
+			shoot: MouseButton::Left,
+			drag: MouseButton::Right,
+			spawn: MouseButton::Middle,
+			left_handed: false,
+		}
+	}
+}
+
+impl MouseButtons {
+	// pairs of the physical button and the one the controller binds to the action
+	pub fn button_map(&self) -> Vec<(Key, Key)> {
+		let physical = |button: MouseButton| (if self.left_handed { button.mirror() } else { button }).key();
+		vec![
+			(physical(self.shoot), MouseButton::Left.key()),
+			(physical(self.drag), MouseButton::Right.key()),
+			(physical(self.spawn), MouseButton::Middle.key()),
+		]
+	}
+
+	fn button_mut(&mut self, action: MouseAction) -> &mut MouseButton {
+		match action {
+			MouseAction::Shoot => &mut self.shoot,
+			MouseAction::Drag => &mut self.drag,
+			MouseAction::Spawn => &mut self.spawn,
+		}
+	}
+
+	// the action the button was bound to gets the one it replaces
+	pub fn bind(&mut self, action: MouseAction, button: MouseButton) {
+		let previous = *self.button_mut(action);
+		for other in &[MouseAction::Shoot, MouseAction::Drag, MouseAction::Spawn] {
+			let other = self.button_mut(*other);
+			if *other == button {
+				*other = previous;
+			}
+		}
+		*self.button_mut(action) = button;
+	}
+}
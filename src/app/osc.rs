@@ -0,0 +1,183 @@
+use app::constants::*;
+use backend::obj::Transformable;
+use backend::world;
+use backend::world::agent::AgentType;
+use backend::world::alert::Alert;
+use core::clock::SecondsValue;
+use std::io;
+use std::net;
+
+// Where the simulation is broadcast as Open Sound Control, for TouchDesigner, Max/MSP and the like
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct OscSettings {
+	// host:port of the receiver, empty disables it
+	pub address: String,
+	// in front of all the addresses sent
+	pub prefix: String,
+	// agent position updates per second, zero sends the alerts only
+	pub rate: f64,
+	// the most agents of each type in an update, the oldest first
+	pub max_agents: usize,
+}
+
+impl Default for OscSettings {
+	fn default() -> Self {
+		OscSettings {
+			address: String::new(),
+			prefix: "/rust-oids".to_owned(),
+			rate: 10.,
+			max_agents: 256,
+		}
+	}
+}
+
+enum Arg<'a> {
+	Int(i32),
+	Float(f32),
+	Str(&'a str),
+}
+
+// strings and blocks are null terminated and padded to four bytes
+fn push_padded(buf: &mut Vec<u8>, bytes: &[u8]) {
+	buf.extend_from_slice(bytes);
+	buf.push(0);
+	while buf.len() % 4 != 0 {
+		buf.push(0);
+	}
+}
+
+fn message(address: &str, args: &[Arg]) -> Vec<u8> {
+	let mut buf = Vec::new();
+	push_padded(&mut buf, address.as_bytes());
+	let tags: String = ",".chars()
+		.chain(args.iter().map(|arg| match *arg {
+			Arg::Int(_) => 'i',
+			Arg::Float(_) => 'f',
+			Arg::Str(_) => 's',
+		})).collect();
+	push_padded(&mut buf, tags.as_bytes());
+	for arg in args {
+		match *arg {
+			Arg::Int(i) => buf.extend_from_slice(&i.to_be_bytes()),
+			Arg::Float(f) => buf.extend_from_slice(&f.to_bits().to_be_bytes()),
+			Arg::Str(s) => push_padded(&mut buf, s.as_bytes()),
+		}
+	}
+	buf
+}
+
+// the time tag 1 means right away
+fn bundle(messages: &[Vec<u8>]) -> Vec<u8> {
+	let mut buf = Vec::new();
+	push_padded(&mut buf, b"#bundle");
+	buf.extend_from_slice(&1u64.to_be_bytes());
+	for message in messages {
+		buf.extend_from_slice(&(message.len() as i32).to_be_bytes());
+		buf.extend_from_slice(message);
+	}
+	buf
+}
+
+fn alert_name(alert: &Alert) -> &'static str {
+	match *alert {
+		Alert::BeginSimulation => "begin_simulation",
+		Alert::RestartFromCheckpoint => "restart",
+		Alert::NewMinion => "new_minion",
+		Alert::NewSpore => "new_spore",
+		Alert::NewResource => "new_resource",
+		Alert::NewBullet(_) => "new_bullet",
+		Alert::DieMinion => "die_minion",
+		Alert::DieResource => "die_resource",
+		Alert::Fertilised => "fertilised",
+		Alert::GrowMinion => "grow_minion",
+	}
+}
+
+// The alerts go out as they come, the agents as bundles of /<prefix>/agent id type x y angle energy,
+// after a /<prefix>/frame seconds population
+#[derive(Default)]
+pub struct Osc {
+	socket: Option<(net::UdpSocket, net::SocketAddr)>,
+	next_sample: SecondsValue,
+}
+
+impl Osc {
+	// closes the socket if the address is empty
+	pub fn open(&mut self, settings: &OscSettings) -> io::Result<Option<net::SocketAddr>> {
+		self.socket = None;
+		if settings.address.is_empty() {
+			return Ok(None);
+		}
+		let target = net::ToSocketAddrs::to_socket_addrs(settings.address.as_str())?
+			.next()
+			.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, settings.address.clone()))?;
+		let local = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+		self.socket = Some((net::UdpSocket::bind(local)?, target));
+		self.next_sample = 0.;
+		Ok(Some(target))
+	}
+
+	fn send(&mut self, packet: &[u8]) {
+		let failed = match self.socket {
+			Some((ref socket, target)) => socket.send_to(packet, target).is_err(),
+			None => false,
+		};
+		// a receiver that is not there yet is fine for UDP, a local failure is not going away
+		if failed {
+			error!("Failed to send OSC, disabling it");
+			self.socket = None;
+		}
+	}
+
+	pub fn alert(&mut self, settings: &OscSettings, alert: &Alert) {
+		if self.socket.is_none() {
+			return;
+		}
+		let address = format!("{}/alert/{}", settings.prefix, alert_name(alert));
+		let packet = match *alert {
+			Alert::NewBullet(count) => message(&address, &[Arg::Int(count as i32)]),
+			_ => message(&address, &[]),
+		};
+		self.send(&packet);
+	}
+
+	pub fn sample(&mut self, settings: &OscSettings, now: SecondsValue, world: &world::World) {
+		if self.socket.is_none() || settings.rate <= 0. || now < self.next_sample {
+			return;
+		}
+		self.next_sample = now + 1. / settings.rate;
+		let population = world.agents(AgentType::Minion).len() as i32;
+		let frame = message(
+			&format!("{}/frame", settings.prefix),
+			&[Arg::Float(world.seconds().get() as f32), Arg::Int(population)],
+		);
+		self.send(&frame);
+		let address = format!("{}/agent", settings.prefix);
+		for agent_type in &[AgentType::Minion, AgentType::Spore, AgentType::Resource, AgentType::Player] {
+			let agents = world.agents(*agent_type);
+			let mut ids = agents.keys().cloned().collect::<Vec<_>>();
+			ids.sort();
+			ids.truncate(settings.max_agents);
+			let type_name = agent_type.to_string();
+			let messages = ids
+				.iter()
+				.map(|id| {
+					let agent = &agents[id];
+					let transform = agent.transform();
+					message(&address, &[
+						Arg::Int(*id as i32),
+						Arg::Str(&type_name),
+						Arg::Float(transform.position.x),
+						Arg::Float(transform.position.y),
+						Arg::Float(transform.angle),
+						Arg::Float(agent.state.energy()),
+					])
+				}).collect::<Vec<_>>();
+			// small enough for a datagram each
+			for chunk in messages.chunks(OSC_BUNDLE_SIZE) {
+				self.send(&bundle(chunk));
+			}
+		}
+	}
+}
@@ -5,9 +5,14 @@ use frontend::render::Draw;
 
 impl App {
 	pub fn environment(&self) -> Environment {
-		let light_color = self.lights.get();
+		let ambient = self.lights.get();
+		let light_color = [
+			ambient[0] * self.light_gain,
+			ambient[1] * self.light_gain,
+			ambient[2] * self.light_gain,
+			ambient[3]];
 
-		let mut emitter_lights = self.world
+		let mut emitter_lights = self.simulation.world()
 			.feeders()
 			.iter()
 			.map(|e| {
@@ -23,7 +28,7 @@ impl App {
 				}
 			})
 			.collect::<Vec<_>>();
-		if let Some(ref segment) = self.world.get_player_segment() {
+		if let Some(ref segment) = self.simulation.world().get_player_segment() {
 			let position = segment.transform.position;
 			let intensity = segment.state.charge();
 			emitter_lights.push(render::Light::PointLight {
@@ -38,14 +43,14 @@ impl App {
 		}
 
 		Environment {
-			background_color: self.backgrounds.get(),
+			background_color: self.backgrounds.get().color,
 			lights: emitter_lights.into_boxed_slice(),
 		}
 	}
 
 	fn paint_particles<R>(&self, renderer: &mut R) where R: render::DrawBuffer {
 		let mut batch = render::PrimitiveBuffer::new();
-		for particle in self.world.particles() {
+		for particle in self.simulation.world().particles().iter().step_by(self.adaptive.particle_stride()) {
 			let appearance = render::Appearance::new(particle.color(), particle.effect());
 			let transform = Self::from_transform(&particle.transform()) * Matrix4::from_scale(particle.scale());
 			batch.draw_quad(Some(Style::Particle), transform, 1.0, appearance);
@@ -55,7 +60,7 @@ impl App {
 
 	fn paint_particles_trails<R>(&self, renderer: &mut R) where R: render::DrawBuffer {
 		let mut batch = render::PrimitiveBuffer::new();
-		for particle in self.world.particles() {
+		for particle in self.simulation.world().particles() {
 			use cgmath::SquareMatrix;
 			let appearance = render::Appearance::new(particle.color(), particle.effect());
 			batch.draw_lines(None, Matrix4::identity(), particle.trail(), appearance);
@@ -64,19 +69,38 @@ impl App {
 	}
 
 	fn paint_minions<R>(&self, renderer: &mut R) where R: render::DrawBuffer {
-		for (_, swarm) in self.world.swarms().iter() {
+		for (_, swarm) in self.simulation.world().swarms().iter() {
 			let mut batch_buffer = render::PrimitiveBuffer::new();
 			for (_, agent) in swarm.agents().iter() {
 				let energy_left = agent.state.energy_ratio();
 				let phase = agent.state.phase();
-				for segment in agent.segments() {
-					let body_transform = Self::from_transform(&segment.transform());
+				let dim = if agent.state.is_dormant() { DORMANT_DIM } else { 1. };
+				let [tr, tg, tb] = if agent.state.is_infected() { INFECTED_TINT } else { [1., 1., 1.] };
+				let recolor = self.config.accessibility.tint(agent.dna(), energy_left);
+				// agents straddling the seam of a toroidal world show on both sides
+				let offsets = match self.simulation.world().wrap() {
+					Some(extent) => extent.wrap_offsets(agent.transform().position, WRAP_SEAM_MARGIN),
+					None => vec![Position::new(0., 0.)],
+				};
+				for (segment, offset) in agent.segments().iter().flat_map(|s| offsets.iter().map(move |o| (s, o))) {
+					let body_transform = Matrix4::from_translation(cgmath::Vector3::new(offset.x, offset.y, 0.0))
+						* Self::from_transform(&segment.transform());
 
 					let mesh = &segment.mesh();
 					let fixture_scale = Matrix4::from_scale(segment.growing_radius());
 					let transform = body_transform * fixture_scale;
 
-					let appearance = render::Appearance::new(segment.color(), [energy_left, phase, 0., 0.]);
+					let [r, g, b, a] = match recolor {
+						Some([r, g, b]) => {
+							let c = segment.brightness();
+							[r * c, g * c, b * c, segment.color()[3]]
+						}
+						None => segment.color(),
+					};
+					let appearance = render::Appearance::new(
+						[r * tr * dim, g * tg * dim, b * tb * dim, a],
+						[energy_left, phase, 0., 0.],
+					);
 
 					match mesh.shape {
 						obj::Shape::Ball { .. } => {
@@ -101,10 +125,86 @@ impl App {
 		}
 	}
 
+	fn paint_shadows<R>(&self, renderer: &mut R) where R: render::DrawBuffer {
+		use cgmath::InnerSpace;
+		use cgmath::SquareMatrix;
+		let light_sources: Vec<(Position, f32)> = self
+			.environment()
+			.lights
+			.iter()
+			.map(|light| match *light {
+				render::Light::PointLight { position, color, .. } => (position, color[0].max(color[1]).max(color[2])),
+			})
+			.collect();
+
+		let mut batch_buffer = render::PrimitiveBuffer::new();
+		for (_, swarm) in self.simulation.world().swarms().iter() {
+			for (_, agent) in swarm.agents().iter() {
+				for segment in agent.segments() {
+					let center = segment.transform.position;
+					let radius = segment.growing_radius();
+					for &(light_position, intensity) in &light_sources {
+						let delta = center - light_position;
+						let distance = delta.magnitude();
+						// casters too close to (or containing) the light, or too far away, don't cast
+						if distance <= radius * SHADOW_MIN_DISTANCE_RATIO || distance >= SHADOW_LIGHT_RANGE {
+							continue;
+						}
+						let u = delta / distance;
+						let n = Position::new(-u.y, u.x);
+						let length = (radius * SHADOW_LENGTH_RATIO).min(SHADOW_LENGTH_MAX);
+						let mut left = Vec::with_capacity(SHADOW_SECTIONS + 1);
+						let mut right = Vec::with_capacity(SHADOW_SECTIONS + 1);
+						for k in 0..=SHADOW_SECTIONS {
+							let along = length * k as f32 / SHADOW_SECTIONS as f32;
+							// similar triangles: the shadow widens moving away from the light
+							let half_width = radius * (1. + along / distance);
+							let p = center + u * along;
+							left.push(p + n * half_width);
+							right.push(p - n * half_width);
+						}
+						let strength = SHADOW_INTENSITY * intensity.min(1.) * (1. - distance / SHADOW_LIGHT_RANGE);
+						batch_buffer.draw_ribbon(
+							Some(Style::Shadow),
+							Matrix4::identity(),
+							&left,
+							&right,
+							render::Appearance::new(COLOR_BLACK, [strength, 0., 0., 0.]),
+						);
+					}
+				}
+			}
+		}
+		renderer.draw_buffer(batch_buffer);
+	}
+
+	fn paint_background<R>(&self, renderer: &mut R)
+		where R: render::Draw {
+		let camera = self.camera.position();
+		let phase = self.simulation.world().phase()[1];
+		let layers = self.backgrounds.get().layers;
+		let layers = if self.adaptive.has_background_layers() { layers } else { &layers[..layers.len().min(1)] };
+		let view = self.viewport.half_extent();
+		for layer in layers {
+			// the layer follows the camera by its parallax factor, so it appears to scroll slower
+			let offset = camera * layer.parallax;
+			// just big enough to cover the view wherever the camera is, at any zoom and in a world of any size
+			let half_extent = (view.x + (camera.x - offset.x).abs()).max(view.y + (camera.y - offset.y).abs());
+			let transform = Matrix4::from_translation(cgmath::Vector3::new(offset.x, offset.y, 0.0))
+				* Matrix4::from_scale(half_extent);
+			renderer.draw_quad(
+				Some(Style::Background),
+				transform,
+				1.,
+				render::Appearance::new(layer.color, [layer.pattern as u32 as f32, layer.density, half_extent, phase]),
+			);
+		}
+	}
+
 	fn paint_extent<R>(&self, renderer: &mut R)
 		where R: render::Draw {
 		use cgmath::SquareMatrix;
-		let extent = &self.world.extent;
+		let extent = &self.simulation.world().extent;
 		let points = &[
 			extent.min,
 			Position::new(extent.min.x, extent.max.y),
@@ -122,25 +222,357 @@ impl App {
 			Some(Style::Stage),
 			Matrix4::from_scale(extent.max.x - extent.min.x),
 			1.,
-			render::Appearance::new(self.backgrounds.get(), self.world.phase()),
+			render::Appearance::new(self.backgrounds.get().color, self.simulation.world().phase()),
 		);
 	}
 
 	fn paint_feeders<R>(&self, renderer: &mut R) where R: render::DrawBuffer {
 		let mut batch_buffer = render::PrimitiveBuffer::new();
-		for e in self.world.feeders() {
+		for e in self.simulation.world().feeders() {
 			let transform = Self::from_transform(&e.transform());
 			batch_buffer.draw_ball(None, transform, render::Appearance::rgba(self.lights.get()));
 		}
 		renderer.draw_buffer(batch_buffer)
 	}
 
+	// walls and closed doors are filled, zones and open doors only outlined since bodies go through them
+	fn paint_obstacles<R>(&self, renderer: &mut R)
+		where R: render::DrawBuffer {
+		use cgmath::SquareMatrix;
+		let mut batch_buffer = render::PrimitiveBuffer::new();
+		for obstacle in self.simulation.world().obstacles() {
+			let rect = &obstacle.extent;
+			let corners = [rect.bottom_left(), rect.bottom_right(), rect.top_right(), rect.top_left()];
+			let outline = [corners[0], corners[1], corners[2], corners[3], corners[0]];
+			let center = (rect.min + rect.max) * 0.5;
+			let half_extent = (rect.max - rect.min) * 0.5;
+			if obstacle.is_blocking() {
+				let transform = Matrix4::from_translation(cgmath::Vector3::new(center.x, center.y, 0.0))
+					* Matrix4::from_nonuniform_scale(half_extent.x, half_extent.y, 1.);
+				let color = if obstacle.door.is_some() {
+					OBSTACLE_DOOR_COLOR
+				} else if obstacle.sticky > 0. {
+					OBSTACLE_STICKY_COLOR
+				} else {
+					OBSTACLE_COLOR
+				};
+				batch_buffer.draw_quad(Some(Style::Flat), transform, 1., render::Appearance::rgba(color));
+			} else {
+				let color = if obstacle.door.is_some() { OBSTACLE_DOOR_COLOR } else { OBSTACLE_ZONE_COLOR };
+				let appearance = render::Appearance::rgba(color);
+				batch_buffer.draw_lines(Some(Style::Lines), Matrix4::identity(), &outline, appearance);
+			}
+			// an arrow the way bodies may cross
+			if let Some(gate) = obstacle.gate {
+				let direction = gate.unit();
+				let length = 0.5 * (direction.x.abs() * half_extent.x + direction.y.abs() * half_extent.y);
+				let (tip, tail) = (center + direction * length, center - direction * length);
+				let side = Position::new(-direction.y, direction.x) * (length * 0.5);
+				let back = tip - direction * (length * 0.5);
+				batch_buffer.draw_lines(
+					Some(Style::Lines),
+					Matrix4::identity(),
+					&[tail, tip, back + side, back - side, tip],
+					render::Appearance::rgba(OBSTACLE_GATE_COLOR),
+				);
+			}
+			// chevrons moving along with the strip
+			if let Some(flow) = obstacle.flow {
+				let direction = flow.direction.unit();
+				let across = Position::new(-direction.y, direction.x);
+				let length = 2. * (direction.x * half_extent.x + direction.y * half_extent.y).abs();
+				let side = across * (0.8 * (across.x * half_extent.x + across.y * half_extent.y).abs());
+				let depth = (OBSTACLE_FLOW_SPACING * 0.4).min(length);
+				let start = center - direction * (length * 0.5);
+				let travelled = self.simulation.world().seconds().get() as f32 * flow.speed;
+				let mut t = depth + (travelled % OBSTACLE_FLOW_SPACING + OBSTACLE_FLOW_SPACING) % OBSTACLE_FLOW_SPACING;
+				while t <= length {
+					let tip = start + direction * t;
+					let back = tip - direction * depth;
+					batch_buffer.draw_lines(
+						Some(Style::Lines),
+						Matrix4::identity(),
+						&[back + side, tip, back - side],
+						render::Appearance::rgba(OBSTACLE_FLOW_COLOR),
+					);
+					t += OBSTACLE_FLOW_SPACING;
+				}
+			}
+		}
+		renderer.draw_buffer(batch_buffer);
+	}
+
+	fn paint_portals<R>(&self, renderer: &mut R)
+		where R: render::DrawBuffer {
+		use cgmath::SquareMatrix;
+		let mut batch_buffer = render::PrimitiveBuffer::new();
+		for portal in self.simulation.world().portals() {
+			for end in &portal.ends {
+				let outline: Vec<_> = (0..=PORTAL_EDGES)
+					.map(|i| {
+						let alpha = 2. * ::std::f32::consts::PI * i as f32 / PORTAL_EDGES as f32;
+						*end + Position::new(alpha.cos(), alpha.sin()) * portal.radius
+					}).collect();
+				batch_buffer.draw_lines(
+					Some(Style::Lines),
+					Matrix4::identity(),
+					&outline,
+					render::Appearance::rgba(PORTAL_COLOR),
+				);
+			}
+		}
+		renderer.draw_buffer(batch_buffer);
+	}
+
+	fn visible_rect(&self) -> Rect {
+		let center = self.camera.position();
+		let extent = self.viewport.half_extent();
+		Rect::new(center.x - extent.x, center.y - extent.y, center.x + extent.x, center.y + extent.y)
+	}
+
+	// a 1, 2, 5 sequence of steps so the number of lines on screen stays roughly constant with the zoom
+	fn grid_spacing(&self) -> f32 {
+		let extent = self.viewport.half_extent();
+		let raw = 2. * extent.x / GRID_TARGET_LINES;
+		let magnitude = 10f32.powf(raw.log10().floor());
+		let residual = raw / magnitude;
+		magnitude * if residual < 2. {
+			1.
+		} else if residual < 5. {
+			2.
+		} else {
+			5.
+		}
+	}
+
+	fn paint_grid<R>(&self, renderer: &mut R)
+		where R: render::DrawBuffer {
+		use cgmath::SquareMatrix;
+		let step = self.grid_spacing();
+		let rect = self.visible_rect();
+		let mut batch_buffer = render::PrimitiveBuffer::new();
+		let grid_color = |i: i32| if i == 0 { GRID_AXIS_COLOR } else { GRID_COLOR };
+		for i in (rect.min.x / step).floor() as i32..=(rect.max.x / step).ceil() as i32 {
+			let x = i as f32 * step;
+			batch_buffer.draw_lines(
+				Some(Style::DebugLines),
+				Matrix4::identity(),
+				&[Position::new(x, rect.min.y), Position::new(x, rect.max.y)],
+				render::Appearance::rgba(grid_color(i)),
+			);
+		}
+		for j in (rect.min.y / step).floor() as i32..=(rect.max.y / step).ceil() as i32 {
+			let y = j as f32 * step;
+			batch_buffer.draw_lines(
+				Some(Style::DebugLines),
+				Matrix4::identity(),
+				&[Position::new(rect.min.x, y), Position::new(rect.max.x, y)],
+				render::Appearance::rgba(grid_color(j)),
+			);
+		}
+		renderer.draw_buffer(batch_buffer);
+	}
+
+	fn grid_labels(&self) -> Vec<WorldLabel> {
+		let step = self.grid_spacing();
+		let rect = self.visible_rect();
+		let precision = if step >= 1. { 0 } else { (-step.log10()).ceil() as usize };
+		let mut labels = Vec::new();
+		for i in (rect.min.x / step).ceil() as i32..=(rect.max.x / step).floor() as i32 {
+			let x = i as f32 * step;
+			labels.push(WorldLabel {
+				position: self.label_position(Position::new(x, rect.min.y)) + GRID_LABEL_OFFSET_X,
+				text: format!("{:.*}", precision, x),
+			});
+		}
+		for j in (rect.min.y / step).ceil() as i32..=(rect.max.y / step).floor() as i32 {
+			let y = j as f32 * step;
+			labels.push(WorldLabel {
+				position: self.label_position(Position::new(rect.min.x, y)) + GRID_LABEL_OFFSET_Y,
+				text: format!("{:.*}", precision, y),
+			});
+		}
+		labels
+	}
+
+	fn paint_measurement<R>(&self, renderer: &mut R)
+		where R: render::DrawBuffer {
+		use cgmath::InnerSpace;
+		use cgmath::SquareMatrix;
+		if let Some((start, end)) = self.measurement {
+			let mut batch_buffer = render::PrimitiveBuffer::new();
+			let appearance = render::Appearance::rgba(MEASURE_COLOR);
+			batch_buffer.draw_lines(Some(Style::Lines), Matrix4::identity(), &[start, end], appearance.clone());
+			let delta = end - start;
+			if delta.magnitude2() > 0. {
+				// end ticks are a fixed size on screen
+				let n = Position::new(-delta.y, delta.x).normalize_to(MEASURE_TICK_PIXELS * self.viewport.pixel_size());
+				for p in &[start, end] {
+					batch_buffer.draw_lines(Some(Style::Lines), Matrix4::identity(), &[*p - n, *p + n], appearance.clone());
+				}
+			}
+			renderer.draw_buffer(batch_buffer);
+		}
+	}
+
+	fn measurement_label(&self) -> Option<WorldLabel> {
+		use cgmath::InnerSpace;
+		self.measurement.map(|(start, end)| {
+			let delta = end - start;
+			let angle = delta.y.atan2(delta.x).to_degrees();
+			let midpoint = (start + end) * 0.5;
+			WorldLabel {
+				position: self.label_position(midpoint) + MEASURE_LABEL_OFFSET,
+				text: format!("{:.2} @ {:.1}°", delta.magnitude(), angle),
+			}
+		})
+	}
+
+	fn paint_annotations<R>(&self, renderer: &mut R)
+		where R: render::DrawBuffer {
+		use cgmath::InnerSpace;
+		use cgmath::SquareMatrix;
+		let mut batch_buffer = render::PrimitiveBuffer::new();
+		let appearance = render::Appearance::rgba(ANNOTATION_COLOR);
+		for annotation in self.simulation.world().annotations() {
+			if let Some(target) = annotation.target {
+				let delta = target - annotation.position;
+				if delta.magnitude2() > 0. {
+					let head = ANNOTATION_ARROW_PIXELS * self.viewport.pixel_size();
+					let back = delta.normalize_to(head);
+					let side = Position::new(-back.y, back.x) * 0.5;
+					batch_buffer.draw_lines(
+						Some(Style::Lines),
+						Matrix4::identity(),
+						&[annotation.position, target],
+						appearance.clone(),
+					);
+					batch_buffer.draw_lines(
+						Some(Style::Lines),
+						Matrix4::identity(),
+						&[target - back + side, target, target - back - side],
+						appearance.clone(),
+					);
+				}
+			}
+		}
+		renderer.draw_buffer(batch_buffer);
+	}
+
+	fn annotation_labels(&self) -> Vec<WorldLabel> {
+		self.simulation.world()
+			.annotations()
+			.iter()
+			.map(|annotation| WorldLabel {
+				position: self.label_position(annotation.position),
+				text: annotation.text.clone(),
+			})
+			.collect()
+	}
+
+	fn paint_probes<R>(&self, renderer: &mut R)
+		where R: render::DrawBuffer {
+		use cgmath::SquareMatrix;
+		let mut batch_buffer = render::PrimitiveBuffer::new();
+		for probe in self.simulation.world().probes() {
+			let rect = &probe.extent;
+			batch_buffer.draw_lines(
+				Some(Style::Lines),
+				Matrix4::identity(),
+				&[rect.bottom_left(), rect.bottom_right(), rect.top_right(), rect.top_left(), rect.bottom_left()],
+				render::Appearance::rgba(PROBE_COLOR),
+			);
+		}
+		renderer.draw_buffer(batch_buffer);
+	}
+
+	// the outline of what the drag would build, or the way it would move or stretch things
+	fn paint_editor<R>(&self, renderer: &mut R)
+		where R: render::DrawBuffer {
+		use cgmath::SquareMatrix;
+		let (tool, (start, end)) = match self.editor {
+			Some(ref editor) => match editor.drag {
+				Some(drag) => (editor.tool, drag),
+				None => return,
+			},
+			None => return,
+		};
+		let mut batch_buffer = render::PrimitiveBuffer::new();
+		let appearance = render::Appearance::rgba(EDITOR_COLOR);
+		match tool {
+			editor::Tool::Wall | editor::Tool::Zone | editor::Tool::Probe => {
+				let rect = Rect::from_corners(start, end);
+				batch_buffer.draw_lines(
+					Some(Style::Lines),
+					Matrix4::identity(),
+					&[rect.bottom_left(), rect.bottom_right(), rect.top_right(), rect.top_left(), rect.bottom_left()],
+					appearance,
+				);
+			}
+			_ => batch_buffer.draw_lines(Some(Style::Lines), Matrix4::identity(), &[start, end], appearance),
+		}
+		renderer.draw_buffer(batch_buffer);
+	}
+
+	// only while not recording, so that the outline doesn't end up in the frames
+	fn paint_capture_region<R>(&self, renderer: &mut R)
+		where R: render::DrawBuffer {
+		use cgmath::SquareMatrix;
+		if let Some(rect) = self.capture_extent().filter(|_| !self.is_capturing) {
+			let mut batch_buffer = render::PrimitiveBuffer::new();
+			batch_buffer.draw_lines(
+				Some(Style::Lines),
+				Matrix4::identity(),
+				&[rect.bottom_left(), rect.bottom_right(), rect.top_right(), rect.top_left(), rect.bottom_left()],
+				render::Appearance::rgba(CAPTURE_REGION_COLOR),
+			);
+			renderer.draw_buffer(batch_buffer);
+		}
+	}
+
+	fn probe_labels(&self) -> Vec<WorldLabel> {
+		self.simulation.world()
+			.probes()
+			.iter()
+			.enumerate()
+			.map(|(i, probe)| WorldLabel {
+				position: self.label_position(probe.extent.top_left()) + PROBE_LABEL_OFFSET,
+				text: format!(
+					"#{} n:{} e:{:.1} r:{:.2}/s",
+					i + 1,
+					probe.stats.agent_count,
+					probe.stats.mean_energy,
+					probe.stats.throughput
+				),
+			})
+			.collect()
+	}
+
+	// where a label goes in the UI, which is scaled while the world isn't
+	fn label_position(&self, position: Position) -> Position {
+		self.viewport.to_pixels(position - self.camera.position()) / self.config.accessibility.ui_scale()
+	}
+
+	pub fn world_labels(&self) -> Vec<WorldLabel> {
+		let mut labels = Vec::new();
+		labels.extend(self.measurement_label());
+		labels.extend(self.probe_labels());
+		if self.has_annotations {
+			labels.extend(self.annotation_labels());
+		}
+		if self.has_grid {
+			labels.extend(self.grid_labels());
+		}
+		labels.truncate(MAX_WORLD_LABELS);
+		labels
+	}
+
 	fn paint_hud<R>(&self, renderer: &mut R)
 		where R: render::DrawBuffer {
 		if self.debug_flags.contains(DebugFlags::DEBUG_TARGETS) {
 			let mut batch_buffer = render::PrimitiveBuffer::new();
 			use cgmath::*;
-			for (_, agent) in self.world.agents(world::agent::AgentType::Minion).iter() {
+			for (_, agent) in self.simulation.world().agents(world::agent::AgentType::Minion).iter() {
 				if agent.state.selected() {
 					let sensor = agent.first_segment(segment::Flags::HEAD).unwrap();
 					let p0 = sensor.transform.position;
@@ -213,11 +645,30 @@ impl App {
 
 	pub fn paint<R>(&self, renderer: &mut R)
 		where R: render::Draw + render::DrawBatch + render::DrawBuffer {
+		// shadows darken what's already drawn, so the background and stage go first
+		self.paint_background(renderer);
+		self.paint_extent(renderer);
+		if self.has_shadows && self.adaptive.has_shadows() {
+			self.paint_shadows(renderer);
+		}
 		self.paint_feeders(renderer);
+		self.paint_obstacles(renderer);
+		self.paint_portals(renderer);
 		self.paint_minions(renderer);
 		self.paint_particles(renderer);
-		self.paint_particles_trails(renderer);
-		self.paint_extent(renderer);
+		if self.adaptive.has_trails() {
+			self.paint_particles_trails(renderer);
+		}
+		if self.has_grid {
+			self.paint_grid(renderer);
+		}
+		self.paint_probes(renderer);
+		self.paint_capture_region(renderer);
+		self.paint_measurement(renderer);
+		self.paint_editor(renderer);
+		if self.has_annotations {
+			self.paint_annotations(renderer);
+		}
 		self.paint_hud(renderer);
 	}
 }
\ No newline at end of file
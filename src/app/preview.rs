@@ -0,0 +1,39 @@
+use super::genome_diff::BodyPlan;
+use app::constants::*;
+use backend::raster;
+use backend::world;
+use backend::world::gen::Dna;
+use serialize::base64::{self, FromBase64, ToBase64};
+
+// A genome as it would grow, built and drawn by the software renderer without spawning it
+#[derive(Clone, Debug)]
+pub struct Preview {
+	// where the genome came from, for the title
+	pub source: String,
+	pub dna: Dna,
+	pub image: raster::Frame,
+	pub plan: BodyPlan,
+}
+
+impl Preview {
+	pub fn new(world: &world::World, source: &str, dna: Dna) -> Preview {
+		let agent = world.develop_minion(&dna);
+		Preview {
+			source: source.to_owned(),
+			image: raster::body(&agent, PREVIEW_SIZE, PREVIEW_SIZE),
+			plan: BodyPlan::of(&agent),
+			dna,
+		}
+	}
+
+	pub fn to_base64(&self) -> String { self.dna.to_base64(base64::STANDARD) }
+}
+
+// as written in the gene pools and the saves
+pub fn parse(text: &str) -> Result<Dna, String> {
+	let dna = text.trim().from_base64().map_err(|e| format!("Not a genome: {}", e))?;
+	if dna.is_empty() {
+		return Err("An empty genome grows nothing".to_owned());
+	}
+	Ok(dna.into_boxed_slice())
+}
@@ -0,0 +1,93 @@
+use app::adaptive::AdaptiveQuality;
+use app::constants::*;
+use dirs;
+use frontend::input::Key;
+use frontend::render::Quality;
+use serde_json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path;
+
+// Where the camera was left
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct CameraView {
+	pub x: f32,
+	pub y: f32,
+	pub zoom: f32,
+	// following the player rather than left where it was
+	pub tracking: bool,
+}
+
+// The user's own preferences, whatever the world, the scenario or the config
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Profile {
+	// keys to the keys they press instead, e.g. "Z": "W" on an AZERTY keyboard
+	pub keys: BTreeMap<Key, Key>,
+	pub camera: Option<CameraView>,
+	pub quality: Quality,
+	pub adaptive: AdaptiveQuality,
+	// the worlds last saved or loaded, the latest first
+	pub recent: Vec<path::PathBuf>,
+	// the folder the challenges are picked from, the one in the saved state directory if none
+	pub challenges: Option<path::PathBuf>,
+	// the highest score each challenge was won with, by name
+	pub best: BTreeMap<String, f32>,
+}
+
+impl Default for Profile {
+	fn default() -> Self {
+		Profile {
+			keys: BTreeMap::new(),
+			camera: None,
+			quality: Quality::High,
+			adaptive: AdaptiveQuality::default(),
+			recent: Vec::new(),
+			challenges: None,
+			best: BTreeMap::new(),
+		}
+	}
+}
+
+impl Profile {
+	// in the platform's configuration directory, none if it has none
+	pub fn file() -> Option<path::PathBuf> {
+		dirs::config_dir().map(|dir| dir.join(PROFILE_DIR).join(PROFILE_FILE))
+	}
+
+	pub fn load(file_path: &path::Path) -> io::Result<Profile> {
+		let in_file = fs::File::open(file_path)?;
+		let profile = serde_json::from_reader(in_file)?;
+		Ok(profile)
+	}
+
+	pub fn save(&self, file_path: &path::Path) -> io::Result<()> {
+		if let Some(dir) = file_path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		let out_file = fs::File::create(file_path)?;
+		serde_json::to_writer_pretty(out_file, self)?;
+		Ok(())
+	}
+
+	pub fn key_map(&self) -> Vec<(Key, Key)> { self.keys.iter().map(|(from, to)| (*from, *to)).collect() }
+
+	// moved to the front if already there
+	pub fn add_recent(&mut self, file_path: &path::Path) {
+		self.recent.retain(|recent| recent != file_path);
+		self.recent.insert(0, file_path.to_owned());
+		self.recent.truncate(PROFILE_RECENT_MAX);
+	}
+
+	// true if it beats the best so far
+	pub fn add_score(&mut self, name: &str, score: f32) -> bool {
+		match self.best.get(name) {
+			Some(best) if *best >= score => false,
+			_ => {
+				self.best.insert(name.to_owned(), score);
+				true
+			}
+		}
+	}
+}
@@ -0,0 +1,60 @@
+use app::constants::*;
+use frontend::render;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct OutputSettings {
+	// 0 is flat, 1 is an equirectangular view of the world plane, for domes and curved screens
+	pub warp: f32,
+}
+
+impl Default for OutputSettings {
+	fn default() -> Self { OutputSettings { warp: 0. } }
+}
+
+// For installations: a window spanning several projectors, one output each from left to right
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ProjectionSettings {
+	// the view keeps the height of a 16:9 window however wide it gets, instead of its width
+	pub wide: bool,
+	pub outputs: Vec<OutputSettings>,
+	// the fraction of an output its neighbour shows too, for edge blending
+	pub overlap: f32,
+	pub blend_gamma: f32,
+}
+
+impl Default for ProjectionSettings {
+	fn default() -> Self {
+		ProjectionSettings {
+			wide: false,
+			outputs: Vec::new(),
+			overlap: 0.,
+			blend_gamma: 2.2,
+		}
+	}
+}
+
+impl ProjectionSettings {
+	pub fn projection(&self) -> render::Projection {
+		let mut warp = [0.; render::MAX_OUTPUTS];
+		for (warp, output) in warp.iter_mut().zip(&self.outputs) {
+			*warp = output.warp;
+		}
+		render::Projection {
+			outputs: self.outputs.len().max(1),
+			overlap: self.overlap,
+			blend_gamma: self.blend_gamma,
+			warp,
+		}
+	}
+
+	// how much more of the world the view spans across than a window of the default aspect would
+	pub fn widening(&self, ratio: f32) -> f32 {
+		if self.wide {
+			ratio * DEFAULT_WINDOW_HEIGHT as f32 / DEFAULT_WINDOW_WIDTH as f32
+		} else {
+			1.
+		}
+	}
+}
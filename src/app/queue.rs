@@ -0,0 +1,194 @@
+use super::sweep::{Cell, Sweep};
+use app::constants::*;
+use serde_json;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path;
+use std::process;
+use std::thread;
+use std::time;
+
+// One combination of a sweep, run by whichever worker takes it first
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Job {
+	pub id: usize,
+	pub sweep: Sweep,
+	pub values: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Outcome {
+	pub id: usize,
+	pub worker: String,
+	pub cell: Cell,
+}
+
+// Where the workers take jobs from and leave their results: a folder that every machine sees, or a server that
+// hands out a job on GET <url>/job, nothing once they are all given out, and takes the outcome on POST <url>/result
+pub enum Queue {
+	Dir(path::PathBuf),
+	Http(String),
+}
+
+// as in the outcomes, to tell which machine ran what
+fn worker_name() -> String {
+	let host = env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_owned());
+	format!("{}-{}", host, process::id())
+}
+
+fn job_file(id: usize) -> String { format!("{:06}.json", id) }
+
+fn read_json<T>(file: &path::Path) -> Result<T, String>
+where T: ::serde::de::DeserializeOwned {
+	let in_file = fs::File::open(file).map_err(|e| format!("Could not read {:?}: {}", file, e))?;
+	serde_json::from_reader(in_file).map_err(|e| format!("Invalid {:?}: {}", file, e))
+}
+
+// written aside and renamed, so that nobody reads half of it
+fn write_json<T>(file: &path::Path, value: &T) -> Result<(), String>
+where T: ::serde::Serialize {
+	let partial = file.with_extension("part");
+	let failed = |e: String| format!("Could not write {:?}: {}", file, e);
+	let out_file = fs::File::create(&partial).map_err(|e| failed(e.to_string()))?;
+	serde_json::to_writer(out_file, value).map_err(|e| failed(e.to_string()))?;
+	fs::rename(&partial, file).map_err(|e| failed(e.to_string()))
+}
+
+fn json_files(dir: &path::Path) -> Vec<path::PathBuf> {
+	let mut files = fs::read_dir(dir)
+		.map(|dir| {
+			dir.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+				.filter(|file| file.extension().and_then(|ext| ext.to_str()) == Some("json"))
+				.collect::<Vec<_>>()
+		}).unwrap_or_default();
+	files.sort();
+	files
+}
+
+fn curl(args: &[&str], input: Option<&str>) -> Result<String, String> {
+	let mut command = process::Command::new("curl");
+	command.arg("-s").arg("-f").args(args);
+	command.stdin(process::Stdio::piped()).stdout(process::Stdio::piped());
+	let mut child = command.spawn().map_err(|e| format!("Could not run curl: {}", e))?;
+	if let (Some(input), Some(stdin)) = (input, child.stdin.as_mut()) {
+		stdin.write_all(input.as_bytes()).map_err(|e| format!("Could not send to curl: {}", e))?;
+	}
+	let output = child.wait_with_output().map_err(|e| format!("curl failed: {}", e))?;
+	if !output.status.success() {
+		return Err(format!("curl {} failed: {}", args.last().unwrap_or(&""), output.status));
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+impl Queue {
+	pub fn parse(location: &str) -> Queue {
+		if location.starts_with("http://") || location.starts_with("https://") {
+			Queue::Http(location.trim_right_matches('/').to_owned())
+		} else {
+			Queue::Dir(path::PathBuf::from(location))
+		}
+	}
+
+	// none once there is nothing left to run
+	fn take(&self) -> Result<Option<Job>, String> {
+		match *self {
+			Queue::Dir(ref dir) => {
+				let running = dir.join(SWEEP_RUNNING_DIR);
+				fs::create_dir_all(&running).map_err(|e| format!("Failed to create {:?}: {}", running, e))?;
+				for file in json_files(&dir.join(SWEEP_PENDING_DIR)) {
+					let taken = running.join(file.file_name().unwrap_or_default());
+					// only one of the workers renaming it at once gets it
+					if fs::rename(&file, &taken).is_ok() {
+						return read_json(&taken).map(Some);
+					}
+				}
+				Ok(None)
+			}
+			Queue::Http(ref url) => {
+				let body = curl(&[format!("{}/job", url).as_str()], None)?;
+				if body.trim().is_empty() {
+					Ok(None)
+				} else {
+					serde_json::from_str(&body).map(Some).map_err(|e| format!("Invalid job from {}: {}", url, e))
+				}
+			}
+		}
+	}
+
+	fn give_back(&self, outcome: &Outcome) -> Result<(), String> {
+		match *self {
+			Queue::Dir(ref dir) => {
+				let done = dir.join(SWEEP_DONE_DIR);
+				fs::create_dir_all(&done).map_err(|e| format!("Failed to create {:?}: {}", done, e))?;
+				write_json(&done.join(job_file(outcome.id)), outcome)?;
+				fs::remove_file(dir.join(SWEEP_RUNNING_DIR).join(job_file(outcome.id))).is_ok();
+				Ok(())
+			}
+			Queue::Http(ref url) => {
+				let body = serde_json::to_string(outcome).map_err(|e| e.to_string())?;
+				let target = format!("{}/result", url);
+				let json = "Content-Type: application/json";
+				curl(&["-X", "POST", "-H", json, "--data-binary", "@-", target.as_str()], Some(&body)).map(|_| ())
+			}
+		}
+	}
+
+	// runs jobs until there are none left, the count of those it ran
+	pub fn work(&self, config_home: &path::Path) -> Result<usize, String> {
+		let worker = worker_name();
+		let mut count = 0;
+		while let Some(job) = self.take()? {
+			info!("Job {} by {}: {:?}", job.id, worker, job.values);
+			job.sweep.prepare(config_home)?;
+			let cell = job.sweep.run_cell(config_home, &job.values)?;
+			self.give_back(&Outcome {
+				id: job.id,
+				worker: worker.clone(),
+				cell,
+			})?;
+			count += 1;
+		}
+		Ok(count)
+	}
+}
+
+// a job for each combination in the pending folder of an empty queue
+pub fn submit(dir: &path::Path, sweep: &Sweep) -> Result<usize, String> {
+	sweep.check()?;
+	let queued = [SWEEP_PENDING_DIR, SWEEP_RUNNING_DIR, SWEEP_DONE_DIR]
+		.iter()
+		.any(|sub| !json_files(&dir.join(sub)).is_empty());
+	if queued {
+		return Err(format!("{:?} already holds a sweep, empty it first", dir));
+	}
+	let pending = dir.join(SWEEP_PENDING_DIR);
+	fs::create_dir_all(&pending).map_err(|e| format!("Failed to create {:?}: {}", pending, e))?;
+	let combinations = sweep.combinations();
+	for (id, values) in combinations.iter().enumerate() {
+		let job = Job {
+			id,
+			sweep: sweep.clone(),
+			values: values.clone(),
+		};
+		write_json(&pending.join(job_file(id)), &job)?;
+	}
+	Ok(combinations.len())
+}
+
+// waits for the outcome of every job, then writes them in the order of the grid
+pub fn gather(dir: &path::Path, sweep: &Sweep) -> Result<usize, String> {
+	let total = sweep.combinations().len();
+	let done = dir.join(SWEEP_DONE_DIR);
+	loop {
+		let finished = json_files(&done).len();
+		if finished >= total {
+			break;
+		}
+		// the jobs of a worker that stopped halfway stay in the running folder, to be moved back by hand
+		info!("Waiting for {} of {} jobs", total - finished, total);
+		thread::sleep(time::Duration::from_secs(SWEEP_POLL_INTERVAL));
+	}
+	let cells = (0..total).map(|id| read_json::<Outcome>(&done.join(job_file(id))).map(|outcome| outcome.cell));
+	sweep.write_results(cells)
+}
@@ -0,0 +1,108 @@
+use app::constants::*;
+use backend::raster;
+use backend::world::persist::Serializer;
+use chrono::NaiveDateTime;
+use image;
+use serialize::base64::FromBase64;
+use std::fs;
+use std::path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+	World,
+	GenePool,
+}
+
+// A save or a gene pool to resume from, with the picture taken when it was written
+#[derive(Clone, Debug)]
+pub struct Entry {
+	pub kind: Kind,
+	pub file: path::PathBuf,
+	pub label: String,
+	pub thumbnail: Option<path::PathBuf>,
+}
+
+pub fn thumbnail_of(file: &path::Path) -> path::PathBuf { file.with_extension("png") }
+
+pub fn write_thumbnail(frame: &raster::Frame, file: &path::Path) -> Result<(), String> {
+	let picture = image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(
+		frame.width as u32,
+		frame.height as u32,
+		frame.pixels.clone(),
+	).ok_or_else(|| "thumbnail size mismatch".to_owned())?;
+	picture.save(file).map_err(|e| e.to_string())
+}
+
+// a save copied without its picture still has the one inside it
+fn extract_thumbnail(file: &path::Path, thumbnail: &path::Path) -> bool {
+	match Serializer::load_thumbnail(file) {
+		Ok(Some(frame)) => write_thumbnail(&frame, thumbnail).is_ok(),
+		_ => false,
+	}
+}
+
+// the saves and the gene pool dumps are named after when they were written
+fn written(file: &path::Path) -> Option<NaiveDateTime> {
+	let stem = file.file_stem().and_then(|stem| stem.to_str())?;
+	NaiveDateTime::parse_from_str(stem, "%Y%m%d_%H%M%S").ok()
+}
+
+fn describe(file: &path::Path) -> String {
+	match written(file) {
+		Some(time) => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+		None => file.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+	}
+}
+
+fn entry(kind: Kind, file: &path::Path, label: String) -> Entry {
+	let thumbnail = thumbnail_of(file);
+	let pictured = thumbnail.is_file() || (kind == Kind::World && extract_thumbnail(file, &thumbnail));
+	Entry {
+		kind,
+		file: file.to_owned(),
+		label,
+		thumbnail: if pictured { Some(thumbnail) } else { None },
+	}
+}
+
+// one genome in base64 per line, as the gene pool dumps are written
+pub fn read_gene_pool(file: &path::Path) -> Result<Vec<String>, String> {
+	let text = fs::read_to_string(file).map_err(|e| format!("Failed to read {:?}: {}", file, e))?;
+	let genomes = text
+		.lines()
+		.map(|line| line.split(',').next().unwrap_or_default().trim())
+		.filter(|line| !line.is_empty())
+		.map(|line| match line.from_base64() {
+			Ok(_) => Ok(line.to_owned()),
+			Err(e) => Err(format!("Invalid genome {} in {:?}: {}", line, file, e)),
+		}).collect::<Result<Vec<_>, _>>()?;
+	if genomes.is_empty() {
+		return Err(format!("No genomes in {:?}", file));
+	}
+	Ok(genomes)
+}
+
+// the recent worlds first, then the latest gene pools in the saved state directory
+pub fn entries(recent: &[path::PathBuf], saved_state_dir: &path::Path) -> Vec<Entry> {
+	let mut entries = recent
+		.iter()
+		.filter(|file| file.is_file())
+		.map(|file| entry(Kind::World, file, format!("world {}", describe(file))))
+		.collect::<Vec<_>>();
+	let mut pools = fs::read_dir(saved_state_dir)
+		.map(|dir| {
+			dir.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+				.filter(|file| file.extension().and_then(|ext| ext.to_str()) == Some("csv") && written(file).is_some())
+				.collect::<Vec<_>>()
+		}).unwrap_or_default();
+	pools.sort();
+	for file in pools.iter().rev().take(QUICK_LOAD_GENE_POOLS) {
+		let count = fs::read_to_string(file).map(|text| text.lines().filter(|l| !l.trim().is_empty()).count());
+		let label = match count {
+			Ok(count) => format!("{} genomes {}", count, describe(file)),
+			Err(_) => format!("genomes {}", describe(file)),
+		};
+		entries.push(entry(Kind::GenePool, file, label));
+	}
+	entries
+}
@@ -0,0 +1,47 @@
+use app::constants::*;
+use core::clock::SecondsValue;
+use std::fs;
+use std::path;
+use std::time::SystemTime;
+
+pub use backend::world::persist::differs;
+
+// notices a file changing on disk by its modification time, looked at every now and then: one stat a second for a
+// file or two costs nothing next to a frame, needs no thread, and still sees editors that save by renaming a new file
+// over the old one, which a watch on the file itself loses track of
+pub struct Watch {
+	file_path: path::PathBuf,
+	modified: Option<SystemTime>,
+	next_check: SecondsValue,
+}
+
+fn modified_time(file_path: &path::Path) -> Option<SystemTime> {
+	fs::metadata(file_path).and_then(|metadata| metadata.modified()).ok()
+}
+
+impl Watch {
+	pub fn new(file_path: &path::Path) -> Self {
+		Watch {
+			file_path: file_path.to_owned(),
+			modified: modified_time(file_path),
+			next_check: 0.,
+		}
+	}
+
+	pub fn file_path(&self) -> &path::Path { &self.file_path }
+
+	// true once after each change, a file that disappears isn't one
+	pub fn is_changed(&mut self, now: SecondsValue) -> bool {
+		if now < self.next_check {
+			return false;
+		}
+		self.next_check = now + RELOAD_CHECK_INTERVAL;
+		match modified_time(&self.file_path) {
+			Some(modified) if self.modified != Some(modified) => {
+				self.modified = Some(modified);
+				true
+			}
+			_ => false,
+		}
+	}
+}
@@ -0,0 +1,221 @@
+use app::constants::*;
+use backend::world;
+use backend::world::agent;
+use chrono::DateTime;
+use chrono::Utc;
+use core::clock::*;
+use serialize::base64::{self, ToBase64};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::mem;
+use std::path;
+
+// What happened during a session, written out as a single HTML page when the app shuts down
+pub struct Report {
+	started: DateTime<Utc>,
+	sample_interval: SecondsValue,
+	next_sample: SecondsValue,
+	population: Vec<(SecondsValue, usize)>,
+	hatched: usize,
+	extinctions: Option<usize>,
+	events: Vec<(SecondsValue, String)>,
+	thumbnails: Vec<(SecondsValue, path::PathBuf)>,
+	// the pictures of the saves, kept next to them
+	saves: Vec<(SecondsValue, path::PathBuf)>,
+}
+
+impl Default for Report {
+	fn default() -> Self {
+		Report {
+			started: Utc::now(),
+			sample_interval: REPORT_SAMPLE_INTERVAL,
+			next_sample: 0.,
+			population: Vec::new(),
+			hatched: 0,
+			extinctions: None,
+			events: Vec::new(),
+			thumbnails: Vec::new(),
+			saves: Vec::new(),
+		}
+	}
+}
+
+impl Report {
+	pub fn sample(&mut self, world: &world::World) {
+		let seconds = world.seconds().get();
+		let extinctions = world.extinctions();
+		// the first sample only catches up with the world that was loaded
+		if self.extinctions.map(|previous| extinctions > previous).unwrap_or(false) {
+			self.note(seconds, format!("Extinction #{}, the population was regenerated", extinctions));
+		}
+		self.extinctions = Some(extinctions);
+		if seconds < self.next_sample {
+			return;
+		}
+		self.next_sample = seconds + self.sample_interval;
+		self.population.push((seconds, world.agents(agent::AgentType::Minion).len()));
+		// long runs keep the chart detail bounded by halving the resolution
+		if self.population.len() >= REPORT_MAX_SAMPLES {
+			self.population = self.population.iter().cloned().step_by(2).collect();
+			self.sample_interval *= 2.;
+		}
+	}
+
+	pub fn hatch(&mut self) { self.hatched += 1; }
+
+	// roughly what the samples and the events take in memory, in bytes
+	pub fn footprint(&self) -> usize {
+		self.population.len() * mem::size_of::<(SecondsValue, usize)>()
+			+ self.events.iter().map(|(_, text)| mem::size_of::<(SecondsValue, String)>() + text.len()).sum::<usize>()
+			+ (self.thumbnails.len() + self.saves.len()) * mem::size_of::<(SecondsValue, path::PathBuf)>()
+	}
+
+	// down to about the given share, a coarser chart and the latest events only
+	pub fn trim(&mut self, keep: f64) {
+		let stride = (1. / keep.max(0.01)).ceil() as usize;
+		if stride > 1 {
+			self.population = self.population.iter().cloned().step_by(stride).collect();
+			self.sample_interval *= stride as SecondsValue;
+		}
+		let dropped = self.events.len() - (self.events.len() as f64 * keep) as usize;
+		self.events.drain(..dropped);
+	}
+
+	pub fn note<S: Into<String>>(&mut self, seconds: SecondsValue, text: S) { self.events.push((seconds, text.into())) }
+
+	pub fn add_thumbnail(&mut self, seconds: SecondsValue, file_name: path::PathBuf) {
+		self.thumbnails.push((seconds, file_name));
+	}
+
+	pub fn add_save(&mut self, seconds: SecondsValue, thumbnail: path::PathBuf) {
+		self.saves.push((seconds, thumbnail));
+	}
+
+	pub fn write(&self, world: &world::World, containing_dir: &path::Path) -> io::Result<path::PathBuf> {
+		let now: DateTime<Utc> = Utc::now();
+		fs::create_dir_all(containing_dir).is_ok();
+		let file_name = containing_dir.join(now.format(REPORT_FILE_PATTERN_HTML).to_string());
+		let mut f = io::BufWriter::new(fs::File::create(&file_name)?);
+		let seconds = world.seconds().get();
+		let population = world.agents(agent::AgentType::Minion);
+		let mean_population = if self.population.is_empty() {
+			0.
+		} else {
+			self.population.iter().map(|&(_, n)| n as f64).sum::<f64>() / self.population.len() as f64
+		};
+
+		f.write_fmt(format_args!(
+			"<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>rust-oids session {}</title></head><body>\n",
+			self.started.format("%Y-%m-%d %H:%M")
+		))?;
+		f.write_fmt(format_args!("<h1>rust-oids session</h1>\n<ul>\n"))?;
+		f.write_fmt(format_args!(
+			"<li>From {} to {} ({} minutes)</li>\n",
+			self.started.format("%Y-%m-%d %H:%M:%S"),
+			now.format("%Y-%m-%d %H:%M:%S"),
+			now.signed_duration_since(self.started).num_minutes()
+		))?;
+		f.write_fmt(format_args!("<li>Simulated {:.1} minutes</li>\n", seconds / 60.))?;
+		f.write_fmt(format_args!("<li>Final population {}</li>\n", population.len()))?;
+		f.write_fmt(format_args!("<li>{} minions hatched", self.hatched))?;
+		if mean_population > 0. {
+			f.write_fmt(format_args!(", about {:.0} generations", self.hatched as f64 / mean_population))?;
+		}
+		f.write_fmt(format_args!("</li>\n<li>{} extinctions</li>\n</ul>\n", world.extinctions()))?;
+
+		f.write_fmt(format_args!("<h2>Population</h2>\n"))?;
+		self.write_chart(&mut f)?;
+
+		f.write_fmt(format_args!("<h2>Top genomes</h2>\n<table>\n"))?;
+		f.write_fmt(format_args!("<tr><th>Minions</th><th>Mean energy</th><th>Genome</th></tr>\n"))?;
+		let mut lineages: HashMap<String, (usize, f32)> = HashMap::new();
+		for agent in population.values() {
+			let entry = lineages
+				.entry(agent.dna().to_base64(base64::STANDARD))
+				.or_insert((0, 0.));
+			entry.0 += 1;
+			entry.1 += agent.state.energy();
+		}
+		let mut lineages = lineages.into_iter().collect::<Vec<_>>();
+		lineages.sort_by(|a, b| (b.1).0.cmp(&(a.1).0));
+		for (dna, (count, energy)) in lineages.into_iter().take(REPORT_TOP_GENOMES) {
+			f.write_fmt(format_args!(
+				"<tr><td>{}</td><td>{:.1}</td><td><code>{}</code></td></tr>\n",
+				count,
+				energy / count as f32,
+				dna
+			))?;
+		}
+		f.write_fmt(format_args!("</table>\n"))?;
+		if let Some(agent) = population.values().max_by_key(|a| a.age().get() as u64) {
+			f.write_fmt(format_args!(
+				"<p>Oldest minion: {} at {:.0} seconds</p>\n",
+				agent.id(),
+				agent.age().get()
+			))?;
+		}
+
+		f.write_fmt(format_args!("<h2>Events</h2>\n<ul>\n"))?;
+		for &(seconds, ref text) in &self.events {
+			f.write_fmt(format_args!("<li>{:.0}s: {}</li>\n", seconds, escape(text)))?;
+		}
+		f.write_fmt(format_args!("</ul>\n"))?;
+
+		if !self.thumbnails.is_empty() {
+			f.write_fmt(format_args!("<h2>Snapshots</h2>\n"))?;
+		}
+		for &(seconds, ref thumbnail) in &self.thumbnails {
+			// embedded, so that the report is a single file
+			let mut png = Vec::new();
+			if fs::File::open(thumbnail).and_then(|mut t| t.read_to_end(&mut png)).is_ok() {
+				f.write_fmt(format_args!(
+					"<figure><img src=\"data:image/png;base64,{}\"><figcaption>{:.0}s</figcaption></figure>\n",
+					png.to_base64(base64::STANDARD),
+					seconds
+				))?;
+				fs::remove_file(thumbnail).is_ok();
+			}
+		}
+		if !self.saves.is_empty() {
+			f.write_fmt(format_args!("<h2>Saves</h2>\n"))?;
+		}
+		for &(seconds, ref thumbnail) in &self.saves {
+			let mut png = Vec::new();
+			if fs::File::open(thumbnail).and_then(|mut t| t.read_to_end(&mut png)).is_ok() {
+				let name = thumbnail.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+				f.write_fmt(format_args!(
+					"<figure><img src=\"data:image/png;base64,{}\"><figcaption>{:.0}s, {}</figcaption></figure>\n",
+					png.to_base64(base64::STANDARD),
+					seconds,
+					escape(&name)
+				))?;
+			}
+		}
+		f.write_fmt(format_args!("</body></html>\n"))?;
+		f.flush()?;
+		Ok(file_name)
+	}
+
+	fn write_chart<W: Write>(&self, f: &mut W) -> io::Result<()> {
+		const WIDTH: f64 = 800.;
+		const HEIGHT: f64 = 200.;
+		let t_max = self.population.last().map(|&(t, _)| t).unwrap_or(0.).max(1.);
+		let n_max = self.population.iter().map(|&(_, n)| n).max().unwrap_or(0).max(1) as f64;
+		let points = self
+			.population
+			.iter()
+			.map(|&(t, n)| format!("{:.1},{:.1}", t / t_max * WIDTH, HEIGHT - n as f64 / n_max * HEIGHT))
+			.collect::<Vec<_>>()
+			.join(" ");
+		f.write_fmt(format_args!(
+			"<svg width=\"{}\" height=\"{}\" style=\"background:#222\"><polyline fill=\"none\" stroke=\"#8c4\" \
+			 points=\"{}\"/></svg>\n<p>Peak {} minions</p>\n",
+			WIDTH, HEIGHT, points, n_max
+		))
+	}
+}
+
+fn escape(text: &str) -> String { text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;") }
@@ -0,0 +1,116 @@
+use std::os::raw::c_void;
+
+// The window shared as a GPU texture with VJ software, through Spout on Windows and Syphon on macOS, off unless asked for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ShareSettings {
+	pub enabled: bool,
+	// as the receivers list it
+	pub name: String,
+}
+
+impl Default for ShareSettings {
+	fn default() -> Self {
+		ShareSettings {
+			enabled: false,
+			name: "rust-oids".to_owned(),
+		}
+	}
+}
+
+// the shims in spout.cpp and syphon.m, built by build.rs with the share feature
+#[cfg(all(feature = "share", any(windows, target_os = "macos")))]
+mod native {
+	use std::os::raw::{c_char, c_int, c_uint, c_void};
+
+	extern "C" {
+		pub fn rust_oids_share_new(name: *const c_char) -> *mut c_void;
+		pub fn rust_oids_share_publish(sender: *mut c_void, width: c_uint, height: c_uint) -> c_int;
+		pub fn rust_oids_share_free(sender: *mut c_void);
+	}
+}
+
+struct Sender {
+	name: String,
+	#[cfg_attr(not(all(feature = "share", any(windows, target_os = "macos"))), allow(dead_code))]
+	handle: *mut c_void,
+}
+
+impl Sender {
+	#[cfg(all(feature = "share", any(windows, target_os = "macos")))]
+	fn open(name: &str) -> Option<Sender> {
+		use std::ffi::CString;
+		let c_name = CString::new(name).ok()?;
+		let handle = unsafe { native::rust_oids_share_new(c_name.as_ptr()) };
+		if handle.is_null() {
+			None
+		} else {
+			Some(Sender {
+				name: name.to_owned(),
+				handle,
+			})
+		}
+	}
+
+	#[cfg(not(all(feature = "share", any(windows, target_os = "macos"))))]
+	fn open(_name: &str) -> Option<Sender> { None }
+
+	#[cfg(all(feature = "share", any(windows, target_os = "macos")))]
+	fn publish(&mut self, width: u32, height: u32) -> bool {
+		unsafe { native::rust_oids_share_publish(self.handle, width, height) != 0 }
+	}
+
+	#[cfg(not(all(feature = "share", any(windows, target_os = "macos"))))]
+	fn publish(&mut self, _width: u32, _height: u32) -> bool { false }
+}
+
+impl Drop for Sender {
+	fn drop(&mut self) {
+		#[cfg(all(feature = "share", any(windows, target_os = "macos")))]
+		unsafe {
+			native::rust_oids_share_free(self.handle)
+		}
+	}
+}
+
+// Hands the window over to the sender once the frame is drawn, on the GPU: unlike the capture there is no readback
+#[derive(Default)]
+pub struct Share {
+	sender: Option<Sender>,
+	// the name a sender could not be opened for, not tried again until it changes
+	failed: Option<String>,
+}
+
+impl Share {
+	pub fn is_supported() -> bool { cfg!(all(feature = "share", any(windows, target_os = "macos"))) }
+
+	// the name to share under, none to stop: to be called after the frame is flushed, with its GL context current
+	pub fn publish(&mut self, name: Option<&str>, width: u32, height: u32) {
+		if self.sender.as_ref().map(|sender| sender.name.as_str()) != name {
+			self.sender = None;
+		}
+		let name = match name {
+			Some(name) => name,
+			None => {
+				self.failed = None;
+				return;
+			}
+		};
+		if self.sender.is_none() && self.failed.as_ref().map_or(true, |failed| failed != name) {
+			self.sender = Sender::open(name);
+			match self.sender {
+				Some(_) => info!("Sharing the frames as {}", name),
+				None => {
+					warn!("Could not share the frames as {}", name);
+					self.failed = Some(name.to_owned());
+				}
+			}
+		}
+		let is_lost = self.sender.as_mut().map_or(false, |sender| !sender.publish(width, height));
+		if is_lost {
+			warn!("Lost the {} sender, turn sharing off and on to try again", name);
+			self.sender = None;
+			self.failed = Some(name.to_owned());
+		}
+	}
+}
@@ -0,0 +1,29 @@
+// Spout sender over the window's framebuffer, for src/app/share/mod.rs. Spout copies the frame to a DirectX texture
+// shared with the receivers on the GPU. Built with the share feature, against SpoutLibrary from the Spout SDK.
+#include "SpoutLibrary.h"
+
+extern "C" {
+
+void* rust_oids_share_new(const char* name) {
+	SPOUTLIBRARY* spout = GetSpout();
+	if (!spout) {
+		return nullptr;
+	}
+	spout->SetSenderName(name);
+	return spout;
+}
+
+// the sender is created, or resized, on the first frame of each size
+int rust_oids_share_publish(void* sender, unsigned int width, unsigned int height) {
+	SPOUTLIBRARY* spout = static_cast<SPOUTLIBRARY*>(sender);
+	// framebuffer 0 is the window's, the GL rows go bottom up
+	return spout->SendFbo(0, width, height, true) ? 1 : 0;
+}
+
+void rust_oids_share_free(void* sender) {
+	SPOUTLIBRARY* spout = static_cast<SPOUTLIBRARY*>(sender);
+	spout->ReleaseSender();
+	spout->Release();
+}
+
+}
@@ -0,0 +1,42 @@
+// Syphon server over the window's framebuffer, for src/app/share/mod.rs. The frame is blitted into the server's
+// texture on the GPU and published to the clients. Built with the share feature, against Syphon.framework.
+#import <Syphon/Syphon.h>
+#import <OpenGL/OpenGL.h>
+#import <OpenGL/gl3.h>
+
+// the server shares the GL context current when it is made, that of the window
+void* rust_oids_share_new(const char* name) {
+	@autoreleasepool {
+		CGLContextObj context = CGLGetCurrentContext();
+		if (!context) {
+			return NULL;
+		}
+		NSString* server_name = [NSString stringWithUTF8String:name];
+		SyphonServer* server = [[SyphonServer alloc] initWithName:server_name context:context options:nil];
+		return (__bridge_retained void*)server;
+	}
+}
+
+int rust_oids_share_publish(void* sender, unsigned int width, unsigned int height) {
+	@autoreleasepool {
+		SyphonServer* server = (__bridge SyphonServer*)sender;
+		if (![server bindToDrawFrameOfSize:NSMakeSize(width, height)]) {
+			return 0;
+		}
+		// the server's framebuffer is bound for drawing, the window's is read
+		GLint read_framebuffer = 0;
+		glGetIntegerv(GL_READ_FRAMEBUFFER_BINDING, &read_framebuffer);
+		glBindFramebuffer(GL_READ_FRAMEBUFFER, 0);
+		glBlitFramebuffer(0, 0, width, height, 0, 0, width, height, GL_COLOR_BUFFER_BIT, GL_NEAREST);
+		glBindFramebuffer(GL_READ_FRAMEBUFFER, read_framebuffer);
+		[server unbindAndPublish];
+		return 1;
+	}
+}
+
+void rust_oids_share_free(void* sender) {
+	@autoreleasepool {
+		SyphonServer* server = (__bridge_transfer SyphonServer*)sender;
+		[server stop];
+	}
+}
@@ -0,0 +1,182 @@
+use app::caption::Caption;
+use app::capture::Capture;
+use app::constants::*;
+use conrod::text;
+use core::clock::SecondsValue;
+use std::io::Write;
+use std::process;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+// Where the live stream goes, ffmpeg does the encoding and the talking to the server
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct StreamSettings {
+	// e.g. rtmp://a.rtmp.youtube.com/live2/<stream key>, empty disables streaming
+	pub url: String,
+	pub bitrate: String,
+	// seconds to wait before reconnecting when the connection drops
+	pub reconnect_delay: SecondsValue,
+}
+
+impl Default for StreamSettings {
+	fn default() -> Self {
+		StreamSettings {
+			url: String::new(),
+			bitrate: "2500k".to_owned(),
+			reconnect_delay: 5.,
+		}
+	}
+}
+
+struct StreamFrame {
+	w: u32,
+	h: u32,
+	pixels: Vec<[u8; 3]>,
+	caption: Option<Caption>,
+}
+
+// Raw frames are piped into ffmpeg from a worker thread, and dropped rather than queued when it falls behind
+pub struct Stream {
+	url: String,
+	frames: Option<mpsc::SyncSender<StreamFrame>>,
+	worker: Option<thread::JoinHandle<()>>,
+	dropped: usize,
+}
+
+impl Stream {
+	pub fn start(settings: StreamSettings, caption_font: Option<Arc<text::Font>>) -> Stream {
+		let (frames, queue) = mpsc::sync_channel(STREAM_QUEUE_LENGTH);
+		let url = settings.url.clone();
+		let worker = thread::spawn(move || Self::run(&settings, &queue, caption_font.as_ref().map(|f| &**f)));
+		Stream {
+			url,
+			frames: Some(frames),
+			worker: Some(worker),
+			dropped: 0,
+		}
+	}
+
+	pub fn url(&self) -> &str { &self.url }
+
+	pub fn push(&mut self, w: u32, h: u32, pixels: Vec<[u8; 3]>, caption: Option<Caption>) {
+		let frame = StreamFrame { w, h, pixels, caption };
+		if let Some(ref frames) = self.frames {
+			if let Err(mpsc::TrySendError::Full(_)) = frames.try_send(frame) {
+				self.dropped += 1;
+				warn!("Stream falling behind, dropped {} frames so far", self.dropped);
+			}
+		}
+	}
+
+	fn spawn(settings: &StreamSettings, w: u32, h: u32) -> Option<process::Child> {
+		// frames are pushed as they are rendered, at 60Hz when the frame rate keeps up
+		let spawned = process::Command::new("ffmpeg")
+			.arg("-loglevel")
+			.arg("error")
+			.arg("-f")
+			.arg("rawvideo")
+			.arg("-pix_fmt")
+			.arg("rgb24")
+			.arg("-s")
+			.arg(format!("{}x{}", w, h))
+			.arg("-r")
+			.arg(format!("{}", (1. / FRAME_TIME_TARGET).round()))
+			.arg("-i")
+			.arg("-")
+			.arg("-c:v")
+			.arg("libx264")
+			.arg("-preset")
+			.arg("veryfast")
+			.arg("-tune")
+			.arg("zerolatency")
+			.arg("-b:v")
+			.arg(&settings.bitrate)
+			.arg("-pix_fmt")
+			.arg("yuv420p")
+			.arg("-vf")
+			.arg("scale=trunc(iw/2)*2:trunc(ih/2)*2")
+			.arg("-f")
+			.arg("flv")
+			.arg(&settings.url)
+			.stdin(process::Stdio::piped())
+			.spawn();
+		match spawned {
+			Ok(child) => {
+				info!("Streaming {}x{} to {}", w, h, settings.url);
+				Some(child)
+			}
+			Err(e) => {
+				error!("Could not run ffmpeg for the stream: {}", e);
+				None
+			}
+		}
+	}
+
+	fn close(child: &mut process::Child) {
+		// closing the input lets ffmpeg flush and hang up
+		child.stdin.take();
+		child.wait().is_ok();
+	}
+
+	fn run(settings: &StreamSettings, queue: &mpsc::Receiver<StreamFrame>, caption_font: Option<&text::Font>) {
+		let mut encoder: Option<(process::Child, (u32, u32))> = None;
+		let mut retry_at = Instant::now();
+		let reconnect_delay = Duration::from_millis((settings.reconnect_delay.max(0.) * 1000.) as u64);
+		for StreamFrame { w, h, pixels, caption } in queue.iter() {
+			if encoder.as_ref().map(|&(_, size)| size != (w, h)).unwrap_or(false) {
+				if let Some((mut child, _)) = encoder.take() {
+					Self::close(&mut child);
+				}
+			}
+			if encoder.is_none() {
+				if Instant::now() < retry_at {
+					continue;
+				}
+				encoder = Self::spawn(settings, w, h).map(|child| (child, (w, h)));
+				if encoder.is_none() {
+					retry_at = Instant::now() + reconnect_delay;
+					continue;
+				}
+			}
+			let mut img = Capture::to_image(w, h, pixels);
+			if let (Some(caption), Some(font)) = (caption, caption_font) {
+				caption.burn_in(&mut img, font);
+			}
+			let written = match encoder {
+				Some((ref mut child, _)) => match child.stdin {
+					Some(ref mut stdin) => stdin.write_all(&img.into_raw()),
+					None => Ok(()),
+				},
+				None => Ok(()),
+			};
+			if let Err(e) = written {
+				warn!(
+					"Stream to {} dropped ({}), reconnecting in {}s",
+					settings.url, e, settings.reconnect_delay
+				);
+				if let Some((mut child, _)) = encoder.take() {
+					child.kill().is_ok();
+					child.wait().is_ok();
+				}
+				retry_at = Instant::now() + reconnect_delay;
+			}
+		}
+		if let Some((mut child, _)) = encoder.take() {
+			Self::close(&mut child);
+		}
+		info!("Stopped streaming to {}", settings.url);
+	}
+}
+
+impl Drop for Stream {
+	fn drop(&mut self) {
+		self.frames = None;
+		if let Some(worker) = self.worker.take() {
+			worker.join().is_ok();
+		}
+	}
+}
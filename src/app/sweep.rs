@@ -0,0 +1,192 @@
+use super::console;
+use super::harness::AppHarness;
+use super::l10n;
+use backend::harness::Stats;
+use app::constants::*;
+use serde_json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path;
+
+// The values a parameter takes, listed or evenly spaced from one end to the other
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Values {
+	List(Vec<f32>),
+	Range { from: f32, to: f32, steps: usize },
+}
+
+impl Values {
+	fn expand(&self) -> Vec<f32> {
+		match *self {
+			Values::List(ref values) => values.clone(),
+			Values::Range { from, to, steps } if steps > 1 => {
+				(0..steps).map(|i| from + (to - from) * i as f32 / (steps - 1) as f32).collect()
+			}
+			Values::Range { from, .. } => vec![from],
+		}
+	}
+}
+
+// An experiment: every combination of the parameters, run once per seed
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Sweep {
+	pub ticks: usize,
+	// the world is seeded with each, so that a seed replays the same run, and grows the terrain from it when a
+	// layout is given
+	pub seeds: Vec<u32>,
+	pub terrain: Option<String>,
+	pub scenario: Option<path::PathBuf>,
+	// copied to the scratch folder, the defaults if none
+	pub config: Option<path::PathBuf>,
+	// the console command to each value, as in "mating mutation" or "feeders rate"
+	pub parameters: BTreeMap<String, Values>,
+	pub output: path::PathBuf,
+}
+
+impl Default for Sweep {
+	fn default() -> Self {
+		Sweep {
+			ticks: SWEEP_TICKS,
+			seeds: vec![1],
+			terrain: None,
+			scenario: None,
+			config: None,
+			parameters: BTreeMap::new(),
+			output: path::PathBuf::from(SWEEP_OUTPUT_FILE),
+		}
+	}
+}
+
+// the means over the seeds of one combination, and how many of them kept any minion alive
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Cell {
+	pub values: Vec<f32>,
+	pub runs: Vec<Stats>,
+}
+
+impl Cell {
+	fn mean<F>(&self, f: F) -> f32
+	where F: Fn(&Stats) -> f32 {
+		self.runs.iter().map(f).sum::<f32>() / self.runs.len().max(1) as f32
+	}
+
+	pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+		for value in &self.values {
+			out.write_fmt(format_args!("{},", value))?;
+		}
+		let minions = self.runs.iter().map(|stats| stats.minions);
+		out.write_fmt(format_args!(
+			"{},{},{:.2},{},{},{:.2},{:.2},{:.2},{:.2}\n",
+			self.runs.len(),
+			self.runs.iter().filter(|stats| stats.minions > 0).count(),
+			self.mean(|stats| stats.minions as f32),
+			minions.clone().min().unwrap_or_default(),
+			minions.max().unwrap_or_default(),
+			self.mean(|stats| stats.spores as f32),
+			self.mean(|stats| stats.resources as f32),
+			self.mean(|stats| stats.extinctions as f32),
+			self.mean(|stats| stats.minion_energy),
+		))
+	}
+}
+
+impl Sweep {
+	pub fn load(file_path: &path::Path) -> io::Result<Sweep> {
+		let in_file = fs::File::open(file_path)?;
+		let sweep = serde_json::from_reader(in_file)?;
+		Ok(sweep)
+	}
+
+	// the grid in the order of the parameters' names, the last one changing fastest
+	pub fn combinations(&self) -> Vec<Vec<f32>> {
+		self.parameters.values().fold(vec![Vec::new()], |combinations, values| {
+			let values = values.expand();
+			combinations
+				.iter()
+				.flat_map(|head| {
+					values.iter().map(move |value| {
+						let mut combination = head.clone();
+						combination.push(*value);
+						combination
+					})
+				}).collect()
+		})
+	}
+
+	// before any run, so that a typo doesn't show after hours; in English, as the rest of the command line
+	pub fn check(&self) -> Result<(), String> {
+		let strings = l10n::Strings::default();
+		for parameter in self.parameters.keys() {
+			console::parse(&strings, &format!("{} 0", parameter)).map_err(|e| format!("{}: {}", parameter, e))?;
+		}
+		Ok(())
+	}
+
+	// the scratch folder the runs read the config from
+	pub fn prepare(&self, config_home: &path::Path) -> Result<(), String> {
+		fs::create_dir_all(config_home).map_err(|e| format!("Failed to create {:?}: {}", config_home, e))?;
+		if let Some(ref config) = self.config {
+			fs::copy(config, config_home.join(CONFIG_FILE)).map_err(|e| format!("Could not copy {:?}: {}", config, e))?;
+		}
+		Ok(())
+	}
+
+	fn run_once(&self, config_home: &path::Path, values: &[f32], seed: u32) -> Result<Stats, String> {
+		let mut harness = match self.scenario {
+			Some(ref scenario) => AppHarness::with_scenario(config_home, scenario)?,
+			None => AppHarness::new(config_home),
+		};
+		harness.reseed(seed);
+		if let Some(ref layout) = self.terrain {
+			harness.console(&format!("terrain {} {}", layout, seed))?;
+		}
+		for (parameter, value) in self.parameters.keys().zip(values) {
+			harness.console(&format!("{} {}", parameter, value))?;
+		}
+		Ok(harness.step(self.ticks))
+	}
+
+	// one combination, once per seed
+	pub fn run_cell(&self, config_home: &path::Path, values: &[f32]) -> Result<Cell, String> {
+		let runs = self
+			.seeds
+			.iter()
+			.map(|seed| self.run_once(config_home, values, *seed))
+			.collect::<Result<Vec<_>, _>>()?;
+		Ok(Cell { values: values.to_owned(), runs })
+	}
+
+	// the header, then one line for each cell as it comes
+	pub fn write_results<I>(&self, cells: I) -> Result<usize, String>
+	where I: Iterator<Item = Result<Cell, String>> {
+		let failed = |e: io::Error| format!("Could not write {:?}: {}", self.output, e);
+		let mut out = io::BufWriter::new(fs::File::create(&self.output).map_err(failed)?);
+		let mut header: Vec<String> = self.parameters.keys().map(|parameter| parameter.replace(' ', "_")).collect();
+		header.push(
+			"runs,survived,minions,minions_min,minions_max,spores,resources,extinctions,minion_energy".to_owned(),
+		);
+		out.write_fmt(format_args!("{}\n", header.join(","))).map_err(failed)?;
+		let mut count = 0;
+		for cell in cells {
+			cell?.write(&mut out).and_then(|_| out.flush()).map_err(failed)?;
+			count += 1;
+		}
+		Ok(count)
+	}
+
+	// each combination headless in a scratch folder, one line of the output for each
+	pub fn run(&self, config_home: &path::Path) -> Result<usize, String> {
+		self.check()?;
+		self.prepare(config_home)?;
+		let combinations = self.combinations();
+		let total = combinations.len();
+		self.write_results(combinations.iter().enumerate().map(|(i, values)| {
+			info!("Sweep {} of {}: {:?}", i + 1, total, values);
+			self.run_cell(config_home, values)
+		}))
+	}
+}
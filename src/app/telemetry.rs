@@ -0,0 +1,63 @@
+use app::constants::*;
+use backend::world;
+use backend::world::agent;
+use chrono::DateTime;
+use chrono::Utc;
+use core::clock::*;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path;
+
+// Long format (seconds, key, value) so that probes can come and go without changing the header
+#[derive(Default)]
+pub struct Telemetry {
+	out: Option<io::BufWriter<fs::File>>,
+	next_sample: SecondsValue,
+}
+
+impl Telemetry {
+	pub fn open(&mut self, containing_dir: &path::Path) -> io::Result<path::PathBuf> {
+		let now: DateTime<Utc> = Utc::now();
+		let file_name = containing_dir.join(now.format(TELEMETRY_FILE_PATTERN_CSV).to_string());
+		fs::create_dir_all(containing_dir).is_ok();
+		let mut out = io::BufWriter::new(fs::File::create(&file_name)?);
+		out.write_fmt(format_args!("seconds,key,value\n"))?;
+		self.out = Some(out);
+		self.next_sample = 0.;
+		Ok(file_name)
+	}
+
+	pub fn sample(&mut self, world: &world::World) {
+		let seconds = world.seconds().get();
+		if self.out.is_none() || seconds < self.next_sample {
+			return;
+		}
+		self.next_sample = seconds + TELEMETRY_INTERVAL;
+		if Self::write(self.out.as_mut().unwrap(), seconds, world).is_err() {
+			error!("Failed to write telemetry, disabling it");
+			self.out = None;
+		}
+	}
+
+	fn write<W: Write>(out: &mut W, seconds: SecondsValue, world: &world::World) -> io::Result<()> {
+		let minions = world.agents(agent::AgentType::Minion);
+		let infected = minions.values().filter(|a| a.state.is_infected()).count();
+		let energy = minions.values().map(|a| a.state.energy()).sum::<f32>() / minions.len().max(1) as f32;
+		out.write_fmt(format_args!("{:.3},population,{}\n", seconds, minions.len()))?;
+		out.write_fmt(format_args!("{:.3},energy,{}\n", seconds, energy))?;
+		out.write_fmt(format_args!("{:.3},infected,{}\n", seconds, infected))?;
+		out.write_fmt(format_args!("{:.3},extinctions,{}\n", seconds, world.extinctions()))?;
+		// per second over the last period, so the same value shows a few times in a row
+		for &(name, rate) in world.energy().rates().entries().iter() {
+			out.write_fmt(format_args!("{:.3},flow.{},{}\n", seconds, name, rate))?;
+		}
+		for (i, probe) in world.probes().iter().enumerate() {
+			let stats = &probe.stats;
+			out.write_fmt(format_args!("{:.3},probe.{}.count,{}\n", seconds, i + 1, stats.agent_count))?;
+			out.write_fmt(format_args!("{:.3},probe.{}.energy,{}\n", seconds, i + 1, stats.mean_energy))?;
+			out.write_fmt(format_args!("{:.3},probe.{}.throughput,{}\n", seconds, i + 1, stats.throughput))?;
+		}
+		out.flush()
+	}
+}
@@ -0,0 +1,111 @@
+use app::constants::*;
+use app::l10n::Strings;
+use core::clock::SecondsValue;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::mem;
+use std::process;
+
+// Conditions worth interrupting the user for, as opposed to the sound alerts raised by the simulation
+#[derive(Clone, Debug)]
+pub enum Warning {
+	LowPopulation(usize),
+	LowFps(f32),
+	LowDiskSpace(u64),
+	AutosaveFailed,
+	Runaway(usize),
+}
+
+impl Warning {
+	pub fn message(&self, strings: &Strings) -> String {
+		match *self {
+			Warning::LowPopulation(population) => strings.fill("warning.low_population", &[&population.to_string()]),
+			Warning::LowFps(fps) => strings.fill("warning.low_fps", &[&format!("{:.0}", fps)]),
+			Warning::LowDiskSpace(megabytes) => strings.fill("warning.low_disk_space", &[&megabytes.to_string()]),
+			Warning::AutosaveFailed => strings.get("warning.autosave_failed").to_owned(),
+			Warning::Runaway(id) => strings.fill("warning.runaway", &[&id.to_string()]),
+		}
+	}
+}
+
+// Thresholds firing the warnings, zero disables a condition
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WarningRules {
+	pub min_population: usize,
+	pub min_fps: f32,
+	// free space in the capture folder, in megabytes
+	pub min_free_disk: u64,
+	pub autosave: bool,
+	// program and arguments run with the message appended, e.g. ["notify-send", "rust-oids"] or a webhook script
+	pub command: Vec<String>,
+}
+
+impl Default for WarningRules {
+	fn default() -> Self {
+		WarningRules {
+			min_population: 5,
+			min_fps: 0.,
+			min_free_disk: 1024,
+			autosave: true,
+			command: Vec::new(),
+		}
+	}
+}
+
+struct Toast {
+	text: String,
+	expires: SecondsValue,
+}
+
+#[derive(Default)]
+pub struct Warnings {
+	// conditions currently holding, so that each fires once until it clears
+	active: HashSet<mem::Discriminant<Warning>>,
+	toasts: VecDeque<Toast>,
+}
+
+impl Warnings {
+	// the message, if the warning fired
+	pub fn check(
+		&mut self,
+		rules: &WarningRules,
+		strings: &Strings,
+		warning: Warning,
+		holds: bool,
+		now: SecondsValue,
+	) -> Option<String> {
+		let key = mem::discriminant(&warning);
+		if !holds {
+			self.active.remove(&key);
+			None
+		} else if self.active.insert(key) {
+			Some(self.raise(rules, strings, &warning, now))
+		} else {
+			None
+		}
+	}
+
+	pub fn raise(&mut self, rules: &WarningRules, strings: &Strings, warning: &Warning, now: SecondsValue) -> String {
+		let text = warning.message(strings);
+		warn!("{}", text);
+		if let Some((program, args)) = rules.command.split_first() {
+			if let Err(e) = process::Command::new(program).args(args).arg(&text).spawn() {
+				error!("Failed to run warning command {}: {}", program, e);
+			}
+		}
+		if self.toasts.len() >= MAX_TOASTS {
+			self.toasts.pop_front();
+		}
+		self.toasts.push_back(Toast {
+			text: text.clone(),
+			expires: now + TOAST_DURATION,
+		});
+		text
+	}
+
+	pub fn toasts(&mut self, now: SecondsValue) -> Vec<String> {
+		self.toasts.retain(|toast| toast.expires > now);
+		self.toasts.iter().map(|toast| toast.text.clone()).collect()
+	}
+}
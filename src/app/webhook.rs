@@ -0,0 +1,80 @@
+use core::clock::SecondsValue;
+use serde_json;
+use std::path;
+use std::process;
+use std::thread;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+	// accepts an attached image along with the message
+	Discord,
+	// text only
+	Slack,
+}
+
+// Where run summaries and warnings are posted, so that long runs can be watched from afar
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Webhook {
+	// empty disables the webhook
+	pub url: String,
+	pub format: WebhookFormat,
+	// seconds between summaries, zero sends warnings only
+	pub summary_interval: SecondsValue,
+	// attach a picture of the world to the summaries
+	pub thumbnail: bool,
+}
+
+impl Default for Webhook {
+	fn default() -> Self {
+		Webhook {
+			url: String::new(),
+			format: WebhookFormat::Discord,
+			summary_interval: 3600.,
+			thumbnail: true,
+		}
+	}
+}
+
+impl Webhook {
+	pub fn is_enabled(&self) -> bool { !self.url.is_empty() }
+
+	fn payload(&self, text: &str) -> String {
+		let key = match self.format {
+			WebhookFormat::Discord => "content",
+			WebhookFormat::Slack => "text",
+		};
+		let mut payload = serde_json::Map::new();
+		payload.insert(key.to_owned(), serde_json::Value::String(text.to_owned()));
+		serde_json::Value::Object(payload).to_string()
+	}
+
+	// sent by curl in the background, so that a slow server can't stall the simulation
+	pub fn post(&self, text: &str, thumbnail: Option<&path::Path>) {
+		if !self.is_enabled() {
+			return;
+		}
+		let mut command = process::Command::new("curl");
+		command.arg("-s").arg("-f");
+		let payload = self.payload(text);
+		match (self.format, thumbnail) {
+			(WebhookFormat::Discord, Some(thumbnail)) => {
+				command
+					.arg("-F")
+					.arg(format!("payload_json={}", payload))
+					.arg("-F")
+					.arg(format!("file=@{}", thumbnail.to_string_lossy()));
+			}
+			_ => {
+				command.arg("-H").arg("Content-Type: application/json").arg("-d").arg(payload);
+			}
+		}
+		command.arg(&self.url);
+		thread::spawn(move || match command.status() {
+			Ok(ref status) if status.success() => {}
+			Ok(status) => error!("Webhook post failed: {}", status),
+			Err(e) => error!("Could not run curl for the webhook: {}", e),
+		});
+	}
+}
@@ -90,6 +90,7 @@ impl input::EventMapper<winit::WindowEvent> for WinitEventMapper {
 				Z -> Z,
 				Equals -> Plus,
 				Subtract -> Minus,
+				Grave -> Backtick,
 				Space -> Space,
 				Escape -> Esc
 			]
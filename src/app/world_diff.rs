@@ -0,0 +1,212 @@
+use app::constants::*;
+use backend::obj::Transformable;
+use backend::world;
+use backend::world::agent::AgentType;
+use backend::world::gen;
+use backend::world::persist::Serializer;
+use core::resource::filesystem::ResourceLoaderBuilder;
+use serde_json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path;
+
+// What a save holds, counted the same way for both sides of a diff
+#[derive(Serialize, Clone, Debug)]
+pub struct Census {
+	pub file: String,
+	pub seconds: f64,
+	// by agent type
+	pub population: BTreeMap<String, usize>,
+	// minions by their first genes, those that shape the body, as the species colors group them
+	pub species: BTreeMap<String, usize>,
+	// the mean of each gene over the minions
+	pub genes: Vec<f32>,
+	// the mean fraction of different bits between two minions
+	pub diversity: f32,
+	// minions in each cell of a grid over the world, top row first
+	pub regions: Vec<usize>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Shift<T> {
+	pub name: String,
+	pub before: T,
+	pub after: T,
+}
+
+// How a world changed between two saves, e.g. overnight
+#[derive(Serialize, Clone, Debug)]
+pub struct WorldDiff {
+	pub before: Census,
+	pub after: Census,
+	pub population: Vec<Shift<usize>>,
+	// the species that changed the most first
+	pub species: Vec<Shift<usize>>,
+	// the genes whose mean moved the most first
+	pub genes: Vec<Shift<f32>>,
+	pub regions: Vec<isize>,
+}
+
+fn species_of(dna: &[u8]) -> String { dna.iter().take(SPECIES_GENES).map(|gene| format!("{:02x}", gene)).collect() }
+
+// the saves carry their own gene pools, there is nothing to read from the resources
+fn load(file: &path::Path) -> Result<world::World, String> {
+	let loader = ResourceLoaderBuilder::new().build();
+	let mut world = world::World::new(&loader, DEFAULT_MINION_GENE_POOL_FILE);
+	Serializer::load(file, &mut world).map_err(|e| format!("Failed to load {:?}: {}", file, e))?;
+	Ok(world)
+}
+
+impl Census {
+	pub fn of(world: &world::World, file: &path::Path) -> Census {
+		let population = AgentType::all()
+			.iter()
+			.map(|agent_type| (agent_type.to_string(), world.agents(*agent_type).len()))
+			.filter(|&(_, count)| count > 0)
+			.collect();
+		let mut minions: Vec<_> = world.agents(AgentType::Minion).values().collect();
+		minions.sort_by_key(|minion| minion.id());
+
+		let mut species = BTreeMap::new();
+		let mut genes = Vec::new();
+		let mut regions = vec![0; WORLD_DIFF_GRID * WORLD_DIFF_GRID];
+		let extent = &world.extent;
+		let cell = |v: f32, min: f32, max: f32| {
+			let t = (v - min) / (max - min).max(1e-3);
+			// what lies past the edges of an unbounded world goes to the cells on the edge
+			((t * WORLD_DIFF_GRID as f32).max(0.) as usize).min(WORLD_DIFF_GRID - 1)
+		};
+		for minion in &minions {
+			*species.entry(species_of(minion.dna())).or_insert(0) += 1;
+			if genes.len() < minion.dna().len() {
+				genes.resize(minion.dna().len(), 0.);
+			}
+			for (sum, gene) in genes.iter_mut().zip(minion.dna().iter()) {
+				*sum += f32::from(*gene);
+			}
+			let position = minion.transform().position;
+			let column = cell(position.x, extent.min.x, extent.max.x);
+			let row = WORLD_DIFF_GRID - 1 - cell(position.y, extent.min.y, extent.max.y);
+			regions[row * WORLD_DIFF_GRID + column] += 1;
+		}
+		for sum in &mut genes {
+			*sum /= minions.len().max(1) as f32;
+		}
+
+		// a sample of the pairs is enough to tell, large worlds have too many
+		let sample: Vec<_> = minions.iter().take(WORLD_DIFF_SAMPLE).collect();
+		let mut distances = Vec::new();
+		for (i, a) in sample.iter().enumerate() {
+			for b in &sample[i + 1..] {
+				distances.push(gen::distance(a.dna(), b.dna()));
+			}
+		}
+		let diversity = distances.iter().sum::<f32>() / distances.len().max(1) as f32;
+
+		Census {
+			file: file.to_string_lossy().into_owned(),
+			seconds: world.seconds().get(),
+			population,
+			species,
+			genes,
+			diversity,
+			regions,
+		}
+	}
+
+	pub fn load(file: &path::Path) -> Result<Census, String> { load(file).map(|world| Census::of(&world, file)) }
+}
+
+fn shifts<T: Copy + Default>(before: &BTreeMap<String, T>, after: &BTreeMap<String, T>) -> Vec<Shift<T>> {
+	let mut names: Vec<&String> = before.keys().chain(after.keys()).collect();
+	names.sort();
+	names.dedup();
+	names
+		.into_iter()
+		.map(|name| Shift {
+			name: name.clone(),
+			before: before.get(name).cloned().unwrap_or_default(),
+			after: after.get(name).cloned().unwrap_or_default(),
+		}).collect()
+}
+
+impl WorldDiff {
+	pub fn new(before: Census, after: Census) -> WorldDiff {
+		let population = shifts(&before.population, &after.population);
+		let mut species = shifts(&before.species, &after.species);
+		species.sort_by_key(|shift| -(shift.after as isize - shift.before as isize).abs());
+		species.truncate(WORLD_DIFF_TOP);
+		let mut genes: Vec<_> = (0..before.genes.len().max(after.genes.len()))
+			.map(|gene| Shift {
+				name: gene.to_string(),
+				before: before.genes.get(gene).cloned().unwrap_or_default(),
+				after: after.genes.get(gene).cloned().unwrap_or_default(),
+			}).collect();
+		genes.sort_by(|a, b| {
+			let moved = |shift: &Shift<f32>| (shift.after - shift.before).abs();
+			moved(b).partial_cmp(&moved(a)).unwrap_or(::std::cmp::Ordering::Equal)
+		});
+		genes.truncate(WORLD_DIFF_TOP);
+		let regions = before
+			.regions
+			.iter()
+			.zip(after.regions.iter())
+			.map(|(before, after)| *after as isize - *before as isize)
+			.collect();
+		WorldDiff {
+			before,
+			after,
+			population,
+			species,
+			genes,
+			regions,
+		}
+	}
+
+	pub fn load(before: &path::Path, after: &path::Path) -> Result<WorldDiff, String> {
+		Ok(WorldDiff::new(Census::load(before)?, Census::load(after)?))
+	}
+
+	pub fn to_json(&self) -> Result<String, String> { serde_json::to_string_pretty(self).map_err(|e| e.to_string()) }
+
+	pub fn save(&self, file: &path::Path) -> Result<(), String> {
+		let failed = |e: String| format!("Failed to write {:?}: {}", file, e);
+		let out_file = fs::File::create(file).map_err(|e| failed(e.to_string()))?;
+		serde_json::to_writer_pretty(out_file, self).map_err(|e| failed(e.to_string()))
+	}
+
+	pub fn to_text(&self) -> String {
+		let mut lines = vec![format!(
+			"{} at {:.0}s to {} at {:.0}s ({:+.0}s)",
+			self.before.file,
+			self.before.seconds,
+			self.after.file,
+			self.after.seconds,
+			self.after.seconds - self.before.seconds
+		)];
+		for shift in &self.population {
+			let delta = shift.after as isize - shift.before as isize;
+			lines.push(format!("{}: {} to {} ({:+})", shift.name, shift.before, shift.after, delta));
+		}
+		lines.push(format!("Diversity: {:.3} to {:.3}", self.before.diversity, self.after.diversity));
+		lines.push(format!(
+			"Species: {} to {}, those that changed most:",
+			self.before.species.len(),
+			self.after.species.len()
+		));
+		for shift in &self.species {
+			let delta = shift.after as isize - shift.before as isize;
+			lines.push(format!("  {}: {} to {} ({:+})", shift.name, shift.before, shift.after, delta));
+		}
+		lines.push("Genes whose mean moved most:".to_owned());
+		for shift in &self.genes {
+			lines.push(format!("  {}: {:.1} to {:.1}", shift.name, shift.before, shift.after));
+		}
+		lines.push("Minions by region, top row first:".to_owned());
+		for row in self.regions.chunks(WORLD_DIFF_GRID) {
+			let cells: Vec<_> = row.iter().map(|delta| format!("{:+5}", delta)).collect();
+			lines.push(format!("  {}", cells.join(" ")));
+		}
+		lines.join("\n")
+	}
+}
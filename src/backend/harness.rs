@@ -0,0 +1,125 @@
+//! A headless world stepped by hand, so that tests can wait for something to happen and check that it did.
+
+use backend::simulation::Simulation;
+use backend::systems::PhysicsSettings;
+use backend::world;
+use backend::world::agent::AgentType;
+use constants::*;
+use core::clock::{seconds, SecondsValue};
+use core::resource::ResourceLoader;
+use serde_json;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path;
+
+/// What the assertions usually look at, after each step.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Stats {
+	pub ticks: usize,
+	pub seconds: SecondsValue,
+	pub minions: usize,
+	pub spores: usize,
+	pub resources: usize,
+	pub extinctions: usize,
+	/// Of all the minions together.
+	pub minion_energy: f32,
+}
+
+impl Stats {
+	pub fn of(world: &world::World, ticks: usize) -> Self {
+		let minions = world.agents(AgentType::Minion);
+		Stats {
+			ticks,
+			seconds: world.seconds().get(),
+			minions: minions.len(),
+			spores: world.agents(AgentType::Spore).len(),
+			resources: world.agents(AgentType::Resource).len(),
+			extinctions: world.extinctions(),
+			minion_energy: minions.values().map(|minion| minion.state.energy()).sum(),
+		}
+	}
+}
+
+/// A world without a window, stepped at the frame rate of the headless mode with the default physics.
+pub struct SimHarness {
+	simulation: Simulation,
+}
+
+impl SimHarness {
+	/// The minions are bred from the gene pool found by `res`, or from the built-in one, and everything random in the
+	/// world follows from `seed`, so that two harnesses with the same one step the same way.
+	pub fn new<R>(res: &R, minion_gene_pool: &str, seed: u32) -> Self
+	where R: ResourceLoader<u8> {
+		let mut world = world::World::new(res, minion_gene_pool);
+		world.reseed(seed);
+		SimHarness { simulation: Simulation::new(world, PhysicsSettings::default()) }
+	}
+
+	/// The walls, zones, portals, feeders, probes and collision rules of a scenario saved from the editor.
+	pub fn with_scenario<R>(res: &R, minion_gene_pool: &str, seed: u32, scenario: &path::Path) -> io::Result<Self>
+	where R: ResourceLoader<u8> {
+		let mut harness = Self::new(res, minion_gene_pool, seed);
+		let mut physics = PhysicsSettings::default();
+		world::persist::Serializer::load_scenario(scenario, harness.simulation.world_mut(), &mut physics.collisions)?;
+		harness.simulation.set_physics(physics);
+		Ok(harness)
+	}
+
+	pub fn step(&mut self, ticks: usize) -> Stats {
+		for _ in 0..ticks {
+			self.simulation.step(seconds(FRAME_TIME_TARGET));
+		}
+		self.stats()
+	}
+
+	/// The ticks it took for the condition to hold, none if it didn't within the limit.
+	pub fn step_until<F>(&mut self, max_ticks: usize, mut condition: F) -> Option<usize>
+	where F: FnMut(&world::World) -> bool {
+		for tick in 0..max_ticks {
+			if condition(self.simulation.world()) {
+				return Some(tick);
+			}
+			self.step(1);
+		}
+		None
+	}
+
+	pub fn stats(&self) -> Stats { Stats::of(self.simulation.world(), self.simulation.ticks()) }
+
+	pub fn digest(&self) -> u64 { self.simulation.world().digest() }
+
+	/// Runs the given ticks and compares the digest with the one stored under the name in the golden file. With
+	/// `GOLDEN_BLESS_VAR` set, after an intended change of behavior or for a new name, the digest is recorded instead;
+	/// without it a name missing from the file is an error.
+	pub fn check_golden(&mut self, golden: &path::Path, name: &str, ticks: usize) -> Result<(), String> {
+		self.step(ticks);
+		let found = format!("{:016x}", self.digest());
+		let mut digests: BTreeMap<String, String> = match fs::File::open(golden) {
+			Ok(in_file) => serde_json::from_reader(in_file).map_err(|e| format!("Invalid {:?}: {}", golden, e))?,
+			Err(_) => BTreeMap::new(),
+		};
+		let bless = env::var_os(GOLDEN_BLESS_VAR).is_some();
+		match digests.get(name) {
+			Some(expected) if *expected == found => return Ok(()),
+			Some(expected) if !bless => {
+				return Err(format!("{}: expected {}, found {} after {} ticks", name, expected, found, ticks))
+			}
+			None if !bless => {
+				return Err(format!("{}: not in {:?}, set {} to record it", name, golden, GOLDEN_BLESS_VAR))
+			}
+			_ => {}
+		}
+		digests.insert(name.to_owned(), found);
+		let out_file = fs::File::create(golden).map_err(|e| format!("Could not write {:?}: {}", golden, e))?;
+		serde_json::to_writer_pretty(out_file, &digests).map_err(|e| format!("Could not write {:?}: {}", golden, e))
+	}
+
+	pub fn world(&self) -> &world::World { self.simulation.world() }
+
+	/// Changes made here reach the systems on the next step.
+	pub fn world_mut(&mut self) -> &mut world::World { self.simulation.world_mut() }
+
+	pub fn simulation_mut(&mut self) -> &mut Simulation { &mut self.simulation }
+}
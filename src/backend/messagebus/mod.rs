@@ -1,4 +1,4 @@
-use app::Event;
+use events::Event;
 use backend::world::alert::Alert;
 use backend::world::particle::Emitter;
 use std::sync::mpsc::{Sender, Receiver};
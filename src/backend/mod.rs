@@ -1,4 +1,9 @@
+//! The simulation: the world and its agents, the systems that move them and the bus they talk over.
+
 pub mod obj;
 pub mod world;
 pub mod systems;
 pub mod messagebus;
+pub mod raster;
+pub mod simulation;
+pub mod harness;
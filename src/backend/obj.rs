@@ -2,7 +2,7 @@ use std::f32::consts::*;
 use core::geometry::*;
 use core::geometry::Transform;
 use core::color;
-use app::constants::*;
+use constants::*;
 
 pub type Rgba = color::Rgba<f32>;
 
@@ -0,0 +1,174 @@
+//! A software renderer drawing a world into an RGB buffer: walls, zones and the bodies as discs, with no lights,
+//! trails or effects. Slow next to the window, but it needs no GPU, for notebooks and headless runs.
+
+use backend::obj::Drawable;
+use backend::obj::Transformable;
+use backend::world::agent::Agent;
+use backend::world::World;
+use constants::*;
+use core::geometry::{Position, Rect};
+
+/// Pixels top row first, three bytes each.
+#[derive(Clone, Debug)]
+pub struct Frame {
+	pub width: usize,
+	pub height: usize,
+	pub pixels: Vec<u8>,
+}
+
+impl Frame {
+	fn new(width: usize, height: usize) -> Self {
+		let mut pixels = Vec::with_capacity(width * height * 3);
+		for _ in 0..width * height {
+			pixels.extend_from_slice(&RASTER_BACKGROUND);
+		}
+		Frame { width, height, pixels }
+	}
+
+	// the colors are HDR, they are brought back to a byte with an exposure curve
+	fn blend(&mut self, x: usize, y: usize, rgb: [f32; 3], alpha: f32) {
+		let offset = (y * self.width + x) * 3;
+		for (channel, value) in self.pixels[offset..offset + 3].iter_mut().zip(rgb.iter()) {
+			let exposed = (1. - (-value * RASTER_EXPOSURE).exp()) * 255.;
+			*channel = (f32::from(*channel) * (1. - alpha) + exposed * alpha) as u8;
+		}
+	}
+}
+
+// from world coordinates to pixels, y down
+struct View {
+	origin: Position,
+	scale: f32,
+}
+
+impl View {
+	// the extent in the middle of the frame, as big as it fits
+	fn fit(extent: &Rect, width: usize, height: usize) -> Self {
+		let span = extent.top_right() - extent.bottom_left();
+		let scale = (width as f32 / span.x).min(height as f32 / span.y);
+		let center = (extent.bottom_left() + extent.top_right()) * 0.5;
+		View {
+			origin: center + Position::new(-(width as f32) * 0.5, height as f32 * 0.5) / scale,
+			scale,
+		}
+	}
+
+	fn to_pixels(&self, p: Position) -> (f32, f32) {
+		((p.x - self.origin.x) * self.scale, (self.origin.y - p.y) * self.scale)
+	}
+}
+
+fn fill_rect(frame: &mut Frame, view: &View, extent: &Rect, rgb: [f32; 3], alpha: f32) {
+	let (left, top) = view.to_pixels(extent.top_left());
+	let (right, bottom) = view.to_pixels(extent.bottom_right());
+	let clip = |v: f32, max: usize| v.max(0.).min(max as f32) as usize;
+	for y in clip(top, frame.height)..clip(bottom, frame.height) {
+		for x in clip(left, frame.width)..clip(right, frame.width) {
+			frame.blend(x, y, rgb, alpha);
+		}
+	}
+}
+
+fn fill_disc(frame: &mut Frame, view: &View, center: Position, radius: f32, rgb: [f32; 3], alpha: f32) {
+	let (cx, cy) = view.to_pixels(center);
+	// anything smaller than a pixel still shows
+	let r = (radius * view.scale).max(0.5);
+	let clip = |v: f32, max: usize| v.max(0.).min(max as f32) as usize;
+	for y in clip(cy - r, frame.height)..clip(cy + r + 1., frame.height) {
+		for x in clip(cx - r, frame.width)..clip(cx + r + 1., frame.width) {
+			let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+			if dx * dx + dy * dy <= r * r {
+				frame.blend(x, y, rgb, alpha);
+			}
+		}
+	}
+}
+
+// even-odd, so that the stars and the concave shapes are filled right
+fn fill_polygon(frame: &mut Frame, view: &View, polygon: &[Position], rgb: [f32; 3], alpha: f32) {
+	let points: Vec<(f32, f32)> = polygon.iter().map(|p| view.to_pixels(*p)).collect();
+	if points.len() < 3 {
+		return;
+	}
+	let clip = |v: f32, max: usize| v.max(0.).min(max as f32) as usize;
+	let top = points.iter().fold(f32::MAX, |top, p| top.min(p.1));
+	let bottom = points.iter().fold(f32::MIN, |bottom, p| bottom.max(p.1));
+	let mut crossings = Vec::with_capacity(points.len());
+	for y in clip(top, frame.height)..clip(bottom + 1., frame.height) {
+		let yc = y as f32 + 0.5;
+		crossings.clear();
+		for (i, a) in points.iter().enumerate() {
+			let b = points[(i + 1) % points.len()];
+			if (a.1 <= yc) != (b.1 <= yc) {
+				crossings.push(a.0 + (yc - a.1) / (b.1 - a.1) * (b.0 - a.0));
+			}
+		}
+		crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(::std::cmp::Ordering::Equal));
+		for span in crossings.chunks(2).filter(|span| span.len() == 2) {
+			for x in clip(span[0] - 0.5, frame.width)..clip(span[1] - 0.5, frame.width) {
+				frame.blend(x, y, rgb, alpha);
+			}
+		}
+	}
+}
+
+/// `span` world units across around `center`, with the aspect of the frame.
+pub fn around(center: Position, span: f32, width: usize, height: usize) -> Rect {
+	let half = Position::new(span, span * height as f32 / (width as f32).max(1.)) * 0.5;
+	Rect::from_corners(center - half, center + half)
+}
+
+/// The whole world `width` pixels across, as pictured in the saves.
+pub fn thumbnail(world: &World, width: usize) -> Frame {
+	let span = world.extent.top_right() - world.extent.bottom_left();
+	let height = (width as f32 * span.y / span.x.max(1.)).round() as usize;
+	render(world, &world.extent, width, height.max(1).min(width * 2))
+}
+
+/// The part of the world in `extent`, fitted to the frame keeping its aspect: the extent grows the short way to
+/// fill it.
+pub fn render(world: &World, extent: &Rect, width: usize, height: usize) -> Frame {
+	let mut frame = Frame::new(width, height);
+	if width == 0 || height == 0 {
+		return frame;
+	}
+	let view = View::fit(extent, width, height);
+	for obstacle in world.obstacles() {
+		let (rgb, alpha) = if obstacle.is_blocking() { (RASTER_WALL, 1.) } else { (RASTER_ZONE, RASTER_ZONE_ALPHA) };
+		fill_rect(&mut frame, &view, &obstacle.extent, rgb, alpha);
+	}
+	for swarm in world.swarms().values() {
+		for agent in swarm.agents().values() {
+			let dim = if agent.state.is_dormant() { DORMANT_DIM } else { 1. };
+			for segment in agent.segments() {
+				let [r, g, b, _] = segment.color();
+				let radius = segment.growing_radius();
+				fill_disc(&mut frame, &view, segment.transform().position, radius, [r * dim, g * dim, b * dim], 1.);
+			}
+		}
+	}
+	frame
+}
+
+/// One agent alone, fitted to the frame, as its segments are shaped rather than as discs: for previewing a
+/// genome without spawning it.
+pub fn body(agent: &Agent, width: usize, height: usize) -> Frame {
+	let mut frame = Frame::new(width, height);
+	if width == 0 || height == 0 || agent.segments().is_empty() {
+		return frame;
+	}
+	let outlines: Vec<_> = agent.segments().iter().map(|segment| segment.outline()).collect();
+	let first = agent.segments()[0].transform().position;
+	let (min, max) = outlines.iter().flat_map(|outline| outline.iter()).fold((first, first), |(min, max), p| {
+		(Position::new(min.x.min(p.x), min.y.min(p.y)), Position::new(max.x.max(p.x), max.y.max(p.y)))
+	});
+	// a little room around it, and never nothing across
+	let margin = Position::new(1., 1.) * ((max - min).x.max((max - min).y) * 0.05 + 0.1);
+	let view = View::fit(&Rect::from_corners(min - margin, max + margin), width, height);
+	for (segment, outline) in agent.segments().iter().zip(outlines.iter()) {
+		let [r, g, b, _] = segment.livery.albedo;
+		let rgb = [r * RASTER_BODY_BRIGHTNESS, g * RASTER_BODY_BRIGHTNESS, b * RASTER_BODY_BRIGHTNESS];
+		fill_polygon(&mut frame, &view, outline, rgb, RASTER_BODY_ALPHA);
+	}
+	frame
+}
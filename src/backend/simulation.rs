@@ -0,0 +1,142 @@
+//! A headless simulation: a world and the systems that move it, with nothing to draw it.
+
+use backend::messagebus::{Inbox, Message, Outbox, PubSub, Whiteboard};
+use backend::obj::Id;
+use backend::systems::{PhysicsDiscrepancy, PhysicsSettings, SystemMode, Systems};
+use backend::world;
+use backend::world::agent::Agent;
+use backend::world::gen::Dna;
+use core::clock::Seconds;
+use core::geometry::{Position, Transform};
+use std::io;
+use std::path;
+
+/// Steps a world, the same way for the app, the harness, the C interface and the Python bindings.
+///
+/// Agents added to the world between two steps join the systems at the end of the next one.
+/// Unbounded worlds are streamed to disk around the focus once given a folder for the chunks with
+/// `set_chunks_dir`, without one they just keep growing.
+pub struct Simulation {
+	world: world::World,
+	systems: Systems,
+	bus: PubSub,
+	ticks: usize,
+	focus: Option<(Position, f32)>,
+	chunks_dir: Option<path::PathBuf>,
+}
+
+impl Simulation {
+	/// Takes over a world made with `World::new` or loaded with `persist::Serializer::load`.
+	pub fn new(world: world::World, physics: PhysicsSettings) -> Self {
+		Self::with_mode(world, physics, SystemMode::Batch)
+	}
+
+	/// As `new`, with the systems that only matter to a viewer too in `SystemMode::Interactive`.
+	pub fn with_mode(world: world::World, physics: PhysicsSettings, mode: SystemMode) -> Self {
+		let mut simulation = Simulation {
+			world,
+			systems: Systems::default(),
+			bus: PubSub::new(),
+			ticks: 0,
+			focus: None,
+			chunks_dir: None,
+		};
+		simulation.systems.set_mode(mode);
+		simulation.systems.set_physics(physics);
+		simulation.systems.attach(&mut simulation.bus);
+		simulation.systems.init(&simulation.world);
+		simulation.register_all();
+		simulation
+	}
+
+	/// Advances the world by `dt` simulated seconds, returning the agents that died since the last step.
+	pub fn step(&mut self, dt: Seconds) -> Box<[Agent]> {
+		self.world.cleanup_before();
+		let dead = self.world.sweep();
+		self.systems.unregister(&dead);
+		if self.world.topology() == world::Topology::Unbounded {
+			self.stream_chunks();
+		}
+		self.systems.set_ai_focus(self.focus);
+		self.systems.for_each_par_write(&self.world, &|s, world| s.step(&world, dt));
+		self.systems.for_each_read(&mut self.world, &self.bus, &|s, mut world, outbox| s.apply(&mut world, outbox));
+		self.register_all();
+		self.world.tick(dt);
+		self.ticks += 1;
+		dead
+	}
+
+	/// The steps taken so far.
+	pub fn ticks(&self) -> usize { self.ticks }
+
+	pub fn world(&self) -> &world::World { &self.world }
+
+	pub fn world_mut(&mut self) -> &mut world::World { &mut self.world }
+
+	/// Thaws the frozen chunks of an unbounded world that stops being one, keeping the topology if they can't be.
+	pub fn set_topology(&mut self, topology: world::Topology) -> io::Result<()> {
+		if self.world.topology() == world::Topology::Unbounded && topology != world::Topology::Unbounded {
+			if let Some(ref chunks_dir) = self.chunks_dir {
+				self.world.thaw_all_chunks(chunks_dir)?;
+			}
+		}
+		self.world.set_topology(topology);
+		Ok(())
+	}
+
+	/// Takes effect at once, the bodies are moved to the new partition.
+	pub fn set_physics(&mut self, physics: PhysicsSettings) { self.systems.repartition(physics, &self.world); }
+
+	/// Cross-checks the physics bodies against the agents of the world, with `repair` fixing what doesn't match.
+	pub fn check_physics(&mut self, repair: bool) -> Vec<PhysicsDiscrepancy> {
+		self.systems.check_physics(&self.world, repair)
+	}
+
+	/// Where a viewer looks and how far it sees: the AI thinks more often there in `SystemMode::Interactive`, and the
+	/// chunks of an unbounded world are streamed around it, or around the origin without one.
+	pub fn set_focus(&mut self, focus: Option<(Position, f32)>) { self.focus = focus; }
+
+	/// Where the chunks of an unbounded world go when they freeze, none to keep them all simulated.
+	pub fn set_chunks_dir(&mut self, chunks_dir: Option<path::PathBuf>) { self.chunks_dir = chunks_dir; }
+
+	/// The systems let go of every agent, for a world cleared and loaded again: its agents join them at the end of
+	/// the next step.
+	pub fn clear_systems(&mut self) { self.systems.clear(); }
+
+	/// The alerts and events the systems post from now on, for the ones `accept` lets through.
+	pub fn subscribe(&mut self, accept: Box<Fn(&Message) -> bool>) -> Inbox { self.bus.subscribe(accept) }
+
+	/// Hands an event or an alert to the systems listening for it, as the app does with the player's input.
+	pub fn post<M>(&self, message: M)
+	where M: Into<Message> {
+		self.bus.post(message.into())
+	}
+
+	/// A minion born of `dna`, it joins the systems at the end of the next step.
+	pub fn hatch(&mut self, transform: Transform, dna: &Dna) -> Id { self.world.hatch_spore(&self.bus, transform, dna) }
+
+	// the agents frozen to disk leave the systems like the dead ones, the thawed ones join them after the step
+	fn stream_chunks(&mut self) {
+		let chunks_dir = match self.chunks_dir {
+			Some(ref chunks_dir) => chunks_dir,
+			None => return,
+		};
+		let focus = self.focus.map_or_else(|| Position::new(0., 0.), |(position, _)| position);
+		match self.world.stream_chunks(focus, chunks_dir) {
+			Ok(frozen) => self.systems.unregister(&frozen),
+			Err(e) => error!("Failed to stream chunks: {}", e),
+		}
+	}
+
+	fn register_all(&mut self) {
+		// registered() drains the list, so this can be called only once per step
+		let found: Vec<Agent> = self
+			.world
+			.registered()
+			.into_iter()
+			.filter_map(|id| self.world.agent(*id))
+			.cloned()
+			.collect();
+		self.systems.register(&found[..]);
+	}
+}
@@ -1,5 +1,5 @@
 use super::*;
-use app::constants::*;
+use constants::*;
 use backend::obj;
 use backend::obj::Transformable;
 use backend::obj::Identified;
@@ -10,8 +10,12 @@ use backend::world::agent::Personality;
 use backend::world::segment;
 use backend::world::segment::Intent;
 use cgmath::*;
+use core::geometry;
 use core::geometry::Position;
+use core::geometry::Rect;
 use itertools::Itertools;
+use rayon::prelude::*;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::f32::consts;
 
@@ -20,6 +24,11 @@ type IdPositionMap = HashMap<obj::Id, Position>;
 pub struct AiSystem {
 	beacons: Box<[Position]>,
 	targets: IdPositionMap,
+	tick: usize,
+	// where the user is looking and how far, brains further away are evaluated less often
+	focus: Option<(Position, f32)>,
+	// targets can be sensed across the edges of a toroidal world
+	wrap: Option<Rect>,
 }
 
 impl System for AiSystem {
@@ -29,6 +38,8 @@ impl System for AiSystem {
 	}
 
 	fn import(&mut self, world: &world::World) {
+		self.tick = self.tick.wrapping_add(1);
+		self.wrap = world.wrap();
 		self.beacons = world
 			.feeders()
 			.iter()
@@ -38,6 +49,7 @@ impl System for AiSystem {
 		self.targets = world
 			.agents(agent::AgentType::Resource)
 			.iter()
+			.chain(world.agents(agent::AgentType::Plant).iter())
 			.filter(|&(_, ref v)| v.state.is_active())
 			.map(|(_, v)| (v.id(), v.transform().position))
 			.collect::<HashMap<_, _>>();
@@ -47,6 +59,9 @@ impl System for AiSystem {
 		Self::update_minions(
 			&self.targets,
 			&self.beacons,
+			self.wrap,
+			self.tick,
+			self.focus,
 			&mut world.agents_mut(agent::AgentType::Minion),
 		);
 	}
@@ -57,111 +72,265 @@ impl Default for AiSystem {
 		AiSystem {
 			beacons: Box::new([]),
 			targets: HashMap::new(),
+			tick: 0,
+			focus: None,
+			wrap: None,
 		}
 	}
 }
 
 impl AiSystem {
-	fn update_minions(targets: &IdPositionMap, beacons: &[Position], minions: &mut agent::AgentMap) {
-		fn nearest_beacon<'a>(beacons: &'a [Position], p: &'a Position) -> &'a Position {
-			beacons
-				.iter()
-				.fold1(|n, b| {
-					if (p - n).magnitude2() < (p - b).magnitude2() {
-						n
-					} else {
-						b
-					}
-				}).unwrap_or(p)
+	pub fn set_focus(&mut self, focus: Option<(Position, f32)>) { self.focus = focus; }
+
+	// every how many ticks the brain of a minion is evaluated, so that large populations stay within budget
+	fn stride(population: usize, far: bool) -> usize {
+		let budget_stride = ((population + AI_EVALUATION_BUDGET - 1) / AI_EVALUATION_BUDGET).max(1);
+		if far {
+			budget_stride.max(AI_LOD_FAR_STRIDE)
+		} else {
+			budget_stride
 		}
+	}
 
-		for (_, agent) in minions.iter_mut() {
-			let brain = agent.brain().clone();
-			let core = agent.first_segment(segment::Flags::CORE);
-			let head = agent.first_segment(segment::Flags::SENSOR);
-			if let Some(sensor) = head {
-				let p0 = sensor.transform.position;
-				let radar_range = sensor.growing_radius() * 10.;
-				let current_target = *agent.state.target();
-				let current_target_position = agent.state.target_position();
-				// if our original target is dead then we need to find another one
-				let new_target: Option<(obj::Id, Position)> = match current_target {
-					None => targets
-						.iter()
-						.find(|&(_, &p)| (p - p0).magnitude() < radar_range)
-						.map(|(&id, &position)| (id, position)),
-					Some(id) => targets.get(&id).map(|&position| (id, position)),
-				};
-				// and failing that again, we target
-				match new_target {
-					None => agent
-						.state
-						.retarget(None, *nearest_beacon(beacons, &current_target_position)),
-					Some((id, position)) => agent.state.retarget(Some(id), position),
-				};
-				// find where our target is in the world
-				let target_position = agent.state.target_position();
-				// and transform the world position into the head's frame
-				let t0 = target_position - sensor.transform.position;
-				let t = t0.normalize_to(t0.magnitude().min(radar_range));
-				// direction in which the head is pointing, normalized
-				let s = Matrix2::from_angle(Rad(sensor.transform.angle)) * (-Position::unit_y());
-				// some proprioception, feeding back the angle betweent the neck and the first
-				// torso
-				let neck_angle = consts::PI + sensor.transform.angle
-					- core.map(|t| t.transform.angle).unwrap_or(sensor.transform.angle);
-				// we pass the relative position of the target decomposed in our frame of
-				// reference to the neural network expecting four components we can use as
-				// thresholds
-				let r = agent.brain().response(&[neck_angle, t.dot(s), t.perp_dot(s), 0.]);
-
-				let segments = &mut agent.segments_mut();
-				let mut touch_accumulator = 0.0f32;
-				for segment in segments.iter_mut() {
-					let flags = &segment.flags;
-					if flags.contains(segment::Flags::ACTUATOR) {
-						let power = segment.state.charge() * segment.growing_radius().powi(2) * POWER_BOOST;
-						let f = Matrix2::from_angle(Rad(segment.transform.angle)) * Position::unit_y() * power;
-						let intent = if let Some(refs) = segment.state.last_touched {
-							match refs.id().type_of() {
-								agent::AgentType::Resource => Intent::Idle,
-								_ => {
-									let fear: f32 = brain.fear();
-									touch_accumulator += COLLISION_BASE_COST / segment.state.maturity();
-									Intent::RunAway(f * fear)
-								}
+	fn update_minions(
+		targets: &IdPositionMap,
+		beacons: &[Position],
+		wrap: Option<Rect>,
+		tick: usize,
+		focus: Option<(Position, f32)>,
+		minions: &mut agent::AgentMap,
+	)
+	{
+		let population = minions.len();
+		// every minion only reads the shared snapshot and writes to itself, the stagger goes by id and nothing is
+		// drawn at random, so the outcome doesn't depend on how the map is split across threads
+		minions
+			.par_iter_mut()
+			.for_each(|(_, agent)| Self::update_minion(targets, beacons, wrap, tick, focus, population, agent));
+	}
+
+	fn update_minion(
+		targets: &IdPositionMap,
+		beacons: &[Position],
+		wrap: Option<Rect>,
+		tick: usize,
+		focus: Option<(Position, f32)>,
+		population: usize,
+		agent: &mut agent::Agent,
+	)
+	{
+		// frozen specimens are held in place and their brain is switched off, dormant ones just sleep
+		if agent.state.is_frozen() || agent.state.is_dormant() {
+			for segment in agent.segments_mut().iter_mut() {
+				segment.state.intent = Intent::Idle;
+			}
+			return;
+		}
+		if let Some(steering) = agent.state.puppet() {
+			Self::update_puppet(agent, steering);
+			return;
+		}
+		let brain = agent.brain().clone();
+		let core = agent.first_segment(segment::Flags::CORE);
+		let head = agent.first_segment(segment::Flags::SENSOR);
+		if let Some(sensor) = head {
+			let p0 = sensor.transform.position;
+			let far = focus.map(|(focus, radius)| (p0 - focus).magnitude() > radius).unwrap_or(false);
+			let stride = Self::stride(population, far);
+			// staggered by id, so that a share of the minions thinks on every tick
+			let cached = agent
+				.state
+				.brain_output()
+				.filter(|_| (agent.id() >> 8).wrapping_add(tick) % stride != 0);
+			let r = match cached {
+				Some(r) => r,
+				None => {
+					let r = Self::think(agent, &sensor, core.as_ref(), targets, beacons, wrap.as_ref());
+					agent.state.set_brain_output(r);
+					r
+				}
+			};
+
+			let segments = &mut agent.segments_mut();
+			let mut touch_accumulator = 0.0f32;
+			for segment in segments.iter_mut() {
+				let flags = &segment.flags;
+				if flags.contains(segment::Flags::ACTUATOR) {
+					let power = segment.state.charge() * segment.growing_radius().powi(2) * POWER_BOOST;
+					let f = Matrix2::from_angle(Rad(segment.transform.angle)) * Position::unit_y() * power;
+					let intent = if let Some(refs) = segment.state.last_touched {
+						match refs.id().type_of() {
+							agent::AgentType::Resource | agent::AgentType::Plant => Intent::Idle,
+							_ => {
+								let fear: f32 = brain.fear();
+								touch_accumulator += COLLISION_BASE_COST / segment.state.maturity();
+								Intent::RunAway(f * fear)
 							}
-						} else if (flags.contains(segment::Flags::RUDDER | segment::Flags::LEFT)
-							&& r[0] > brain.hunger())
-							|| (flags.contains(segment::Flags::RUDDER | segment::Flags::RIGHT) && r[1] > brain.hunger())
-						{
-							Intent::Move(-f)
-						} else if flags.contains(segment::Flags::THRUSTER) && r[2] > brain.haste() {
-							Intent::Move(f)
-						} else if flags.contains(segment::Flags::BRAKE) && r[3] > brain.prudence() {
-							Intent::Brake(-f)
-						} else {
-							Intent::Idle
-						};
-						match intent {
-							Intent::Idle => segment.state.set_target_charge(brain.rest()),
-							Intent::Move(_) => segment.state.set_target_charge(brain.thrust()),
-							Intent::Brake(_) => segment.state.set_target_charge(brain.thrust()),
-							Intent::RunAway(_) => segment.state.set_output_charge(brain.thrust()),
-							_ => {}
 						}
-						segment.state.intent = intent;
+					} else if (flags.contains(segment::Flags::RUDDER | segment::Flags::LEFT)
+						&& r[0] > brain.hunger())
+						|| (flags.contains(segment::Flags::RUDDER | segment::Flags::RIGHT) && r[1] > brain.hunger())
+					{
+						Intent::Move(-f)
+					} else if flags.contains(segment::Flags::THRUSTER) && r[2] > brain.haste() {
+						Intent::Move(f)
+					} else if flags.contains(segment::Flags::BRAKE) && r[3] > brain.prudence() {
+						Intent::Brake(-f)
+					} else {
+						Intent::Idle
+					};
+					match intent {
+						Intent::Idle => segment.state.set_target_charge(brain.rest()),
+						Intent::Move(_) => segment.state.set_target_charge(brain.thrust()),
+						Intent::Brake(_) => segment.state.set_target_charge(brain.thrust()),
+						Intent::RunAway(_) => segment.state.set_output_charge(brain.thrust()),
+						_ => {}
 					}
+					segment.state.intent = intent;
 				}
-				// touching costs energy, main body charges up
-				if touch_accumulator > 0. {
-					if let Some(ref mut segment) = segments.get_mut(0) {
-						let thrust: f32 = brain.thrust();
-						segment.state.set_output_charge(1.0f32.max(thrust * touch_accumulator));
-						segment.state.set_target_charge(brain.rest());
-					}
+			}
+			// touching costs energy, main body charges up
+			if touch_accumulator > 0. {
+				if let Some(ref mut segment) = segments.get_mut(0) {
+					let thrust: f32 = brain.thrust();
+					segment.state.set_output_charge(1.0f32.max(thrust * touch_accumulator));
+					segment.state.set_target_charge(brain.rest());
 				}
 			}
 		}
 	}
+
+	// picks a target and asks the brain what to do about it
+	fn think(
+		agent: &mut agent::Agent,
+		sensor: &segment::Segment,
+		core: Option<&segment::Segment>,
+		targets: &IdPositionMap,
+		beacons: &[Position],
+		wrap: Option<&Rect>,
+	) -> [f32; 4]
+	{
+		fn nearest_beacon<'a>(beacons: &'a [Position], p: &'a Position, wrap: Option<&Rect>) -> &'a Position {
+			let distance2 = |b: &Position| geometry::delta(wrap, *p, *b).magnitude2();
+			beacons
+				.iter()
+				.fold1(|n, b| if distance2(n) < distance2(b) { n } else { b })
+				.unwrap_or(p)
+		}
+
+		let p0 = sensor.transform.position;
+		let radar_range = sensor.growing_radius() * 10.;
+		let current_target = *agent.state.target();
+		let current_target_position = agent.state.target_position();
+		// if our original target is dead then we need to find another one
+		let new_target: Option<(obj::Id, Position)> = match current_target {
+			// the nearest one, the lowest id among equals, and never the first one the hash order comes up with
+			None => targets
+				.iter()
+				.map(|(&id, &position)| (id, position, geometry::delta(wrap, p0, position).magnitude2()))
+				.filter(|&(_, _, d2)| d2 < radar_range * radar_range)
+				.min_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(Ordering::Equal).then(a.0.cmp(&b.0)))
+				.map(|(id, position, _)| (id, position)),
+			Some(id) => targets.get(&id).map(|&position| (id, position)),
+		};
+		// and failing that again, we target
+		match new_target {
+			None => agent
+				.state
+				.retarget(None, *nearest_beacon(beacons, &current_target_position, wrap)),
+			Some((id, position)) => agent.state.retarget(Some(id), position),
+		};
+		// find where our target is in the world
+		let target_position = agent.state.target_position();
+		// and transform the world position into the head's frame
+		let t0 = geometry::delta(wrap, sensor.transform.position, target_position);
+		let t = t0.normalize_to(t0.magnitude().min(radar_range));
+		// direction in which the head is pointing, normalized
+		let s = Matrix2::from_angle(Rad(sensor.transform.angle)) * (-Position::unit_y());
+		// some proprioception, feeding back the angle betweent the neck and the first
+		// torso
+		let neck_angle = consts::PI + sensor.transform.angle
+			- core.map(|t| t.transform.angle).unwrap_or(sensor.transform.angle);
+		// we pass the relative position of the target decomposed in our frame of
+		// reference to the neural network expecting four components we can use as
+		// thresholds
+		agent.brain().response(&[neck_angle, t.dot(s), t.perp_dot(s), 0.])
+	}
+
+	// a possessed agent's actuators follow the user's steering instead of its brain
+	fn update_puppet(agent: &mut agent::Agent, steering: Position) {
+		let brain = agent.brain().clone();
+		for segment in agent.segments_mut().iter_mut() {
+			let flags = segment.flags;
+			if flags.contains(segment::Flags::ACTUATOR) {
+				let power = segment.state.charge() * segment.growing_radius().powi(2) * POWER_BOOST;
+				let f = Matrix2::from_angle(Rad(segment.transform.angle)) * Position::unit_y() * power;
+				let intent = if flags.contains(segment::Flags::RUDDER | segment::Flags::LEFT) && steering.x < 0.
+					|| flags.contains(segment::Flags::RUDDER | segment::Flags::RIGHT) && steering.x > 0.
+				{
+					Intent::Move(-f * steering.x.abs())
+				} else if flags.contains(segment::Flags::THRUSTER) && steering.y > 0. {
+					Intent::Move(f * steering.y)
+				} else if flags.contains(segment::Flags::BRAKE) && steering.y < 0. {
+					Intent::Brake(f * steering.y)
+				} else {
+					Intent::Idle
+				};
+				match intent {
+					Intent::Idle => segment.state.set_target_charge(brain.rest()),
+					_ => segment.state.set_target_charge(brain.thrust()),
+				}
+				segment.state.intent = intent;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::resource::filesystem::ResourceLoaderBuilder;
+	use std::path::Path;
+
+	// the brain outputs and the charges the actuators aim for, by id, after a few ticks of the ai alone with the
+	// minions taken in the given order, as different splits across threads would
+	fn think_in_order(reverse: bool) -> Vec<(obj::Id, Option<[f32; 4]>, Vec<f32>)> {
+		let resources = ResourceLoaderBuilder::new().add(Path::new("resources")).build();
+		let mut world = world::World::new(&resources, DEFAULT_MINION_GENE_POOL_FILE);
+		for i in 0..40 {
+			let angle = i as f32 * 0.7;
+			let p = Position::new(angle.cos(), angle.sin()) * (10. + i as f32);
+			world.new_minion(p, geometry::Motion::default());
+			world.new_resource(geometry::Transform::from_position(p * 0.5), geometry::Motion::default());
+		}
+		let mut ai = AiSystem::default();
+		ai.set_focus(Some((Position::new(0., 0.), 20.)));
+		for _ in 0..8 {
+			ai.import(&world);
+			let minions = &mut world.agents_mut(agent::AgentType::Minion);
+			let population = minions.len();
+			let mut ids: Vec<_> = minions.keys().cloned().sorted();
+			if reverse {
+				ids.reverse();
+			}
+			for id in ids {
+				let minion = minions.get_mut(&id).unwrap();
+				AiSystem::update_minion(&ai.targets, &ai.beacons, ai.wrap, ai.tick, ai.focus, population, minion);
+			}
+		}
+		world
+			.agents(agent::AgentType::Minion)
+			.values()
+			.map(|minion| {
+				let charges = minion.segments().iter().map(|segment| segment.state.target_charge()).collect();
+				(minion.id(), minion.state.brain_output(), charges)
+			}).sorted_by(|a, b| a.0.cmp(&b.0))
+	}
+
+	#[test]
+	fn brains_think_the_same_in_any_order() {
+		let forward = think_in_order(false);
+		assert!(forward.iter().all(|&(_, output, _)| output.is_some()));
+		assert_eq!(forward, think_in_order(true));
+	}
 }
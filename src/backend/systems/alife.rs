@@ -1,5 +1,5 @@
 use super::*;
-use app::constants::*;
+use constants::*;
 use backend::messagebus::Outbox;
 use backend::obj;
 use backend::obj::Identified;
@@ -7,25 +7,43 @@ use backend::obj::Transformable;
 use backend::world;
 use backend::world::agent;
 use backend::world::alert;
+use backend::world::disease::Disease;
+use backend::world::energy::EnergyFlow;
+use backend::world::fitness::Fitness;
+use backend::world::mating::Mating;
+use backend::world::mating::Reproduction;
 use backend::world::gen;
 use backend::world::particle;
 use backend::world::segment;
 use backend::world::AgentState;
+use cgmath::InnerSpace;
 use core::clock::SimulationTimer;
 use core::geometry;
-use rand;
+use core::spatial;
+use rand::Rng;
+use rand::XorShiftRng;
 use serialize::base64::{self, ToBase64};
 use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 
 type StateMap = HashMap<obj::Id, agent::State>;
-type GeneMap = HashMap<obj::Id, gen::Dna>;
+// the spores touched, to the father and his dna
+type FatherMap = HashMap<obj::Id, (obj::Id, gen::Dna)>;
 
 pub struct AlifeSystem {
 	dt: Seconds,
 	simulation_timer: SimulationTimer,
 	source: Box<[world::Feeder]>,
 	eaten: StateMap,
-	touched: GeneMap,
+	touched: FatherMap,
+	fitness: Fitness,
+	reproduction: Reproduction,
+	food: spatial::Grid<obj::Id>,
+	disease: Disease,
+	exposed: HashSet<obj::Id>,
+	// where the light comes from, and how bright it is there
+	lights: Box<[(geometry::Position, f32)]>,
 }
 
 impl System for AlifeSystem {
@@ -33,18 +51,43 @@ impl System for AlifeSystem {
 		self.source = Box::new([]);
 		self.eaten.clear();
 		self.touched.clear();
+		self.exposed.clear();
+		self.lights = Box::new([]);
 	}
 
 	fn import(&mut self, world: &world::World) {
 		self.source = world.feeders().to_vec().into_boxed_slice();
+		self.fitness = world.fitness().clone();
+		self.reproduction = world.mating().mode;
+		self.disease = world.disease().clone();
+		self.food.clear();
+		self.food.set_wrap(world.wrap());
+		// plants are eaten just like resources
+		for agent_type in &[agent::AgentType::Resource, agent::AgentType::Plant] {
+			for resource in world.agents(*agent_type).values().filter(|r| r.state.is_active()) {
+				self.food.insert(resource.transform().position, resource.id());
+			}
+		}
 		self.eaten = Self::find_eaten_resources(
 			&world.agents(agent::AgentType::Minion),
 			&world.agents(agent::AgentType::Resource),
 		);
+		self.eaten.extend(Self::find_eaten_resources(
+			&world.agents(agent::AgentType::Minion),
+			&world.agents(agent::AgentType::Plant),
+		));
+		let mut lights: Vec<_> = world.feeders().iter().map(|e| (e.transform().position, e.intensity())).collect();
+		if let Some(segment) = world.get_player_segment() {
+			lights.push((segment.transform().position, segment.state.charge().min(1.)));
+		}
+		self.lights = lights.into_boxed_slice();
 		self.touched = Self::find_touched_spores(
+			world.mating(),
+			world.wrap(),
 			&world.agents(agent::AgentType::Minion),
 			&world.agents(agent::AgentType::Spore),
 		);
+		self.exposed = Self::find_exposed_minions(&world.agents(agent::AgentType::Minion));
 	}
 
 	fn update(&mut self, _: &AgentState, dt: Seconds) {
@@ -53,36 +96,65 @@ impl System for AlifeSystem {
 	}
 
 	fn export(&self, world: &mut world::World, outbox: &Outbox) {
+		let mut flow = EnergyFlow::default();
+		let mut rng = world.rng_for(ALIFE_RNG_STREAM);
 		Self::update_resources(
 			self.dt,
 			&self.simulation_timer,
 			&mut world.agents_mut(agent::AgentType::Resource),
 			&self.eaten,
+			&mut flow,
+		);
+
+		let seedlings = Self::update_plants(
+			&mut rng,
+			self.dt,
+			&self.lights,
+			&mut world.agents_mut(agent::AgentType::Plant),
+			&self.eaten,
+			&mut flow,
 		);
 
 		let MinionEndState(spores, corpses) = Self::update_minions(
+			&mut rng,
 			outbox,
 			self.dt,
-			world.extent,
+			world.bounds(),
+			&self.fitness,
+			&self.food,
+			&self.disease,
+			&self.exposed,
 			&mut world.agents_mut(agent::AgentType::Minion),
 			&self.eaten,
+			&mut flow,
 		);
+		world.energy_mut().record(&flow);
 
 		let SporeEndState(hatch, fertilised) = Self::update_spores(
+			&mut rng,
 			self.dt,
+			self.reproduction,
 			&self.simulation_timer,
 			&mut world.agents_mut(agent::AgentType::Spore),
 			&self.touched,
 		);
 
-		for (transform, dna) in &*spores {
+		let now = world.seconds().get();
+		for (mother, transform, dna) in &*spores {
 			outbox.post(alert::Alert::NewSpore.into());
-			world.new_spore(outbox, transform.clone(), dna);
+			let spore = world.new_spore(outbox, transform.clone(), dna);
+			let spore_dna = world.agent(spore).map_or_else(|| dna.clone(), |spore| spore.dna().clone());
+			world.lineage_mut().laid(spore, *mother, dna, &spore_dna, now);
 		}
 
-		for (transform, dna) in &*hatch {
+		for (spore, father, father_dna) in &*fertilised {
+			world.lineage_mut().fertilised(*spore, *father, father_dna, now);
+		}
+
+		for (spore, transform, dna) in &*hatch {
 			outbox.post(alert::Alert::NewMinion.into());
-			world.hatch_spore(outbox, transform.clone(), dna);
+			let minion = world.hatch_spore(outbox, transform.clone(), dna);
+			world.lineage_mut().hatched(*spore, minion, dna, now);
 		}
 
 		for (transforms, dna) in &*corpses {
@@ -92,9 +164,16 @@ impl System for AlifeSystem {
 			}
 		}
 
-		for _ in 0..fertilised {
+		for _ in 0..fertilised.len() {
 			outbox.post(alert::Alert::DieMinion.into());
 		}
+
+		for (transform, dna) in &*seedlings {
+			if world.agents(agent::AgentType::Plant).len() >= PLANT_MAX_POPULATION {
+				break;
+			}
+			world.new_plant(transform.clone(), Some(dna));
+		}
 	}
 }
 
@@ -105,17 +184,25 @@ impl Default for AlifeSystem {
 			simulation_timer: SimulationTimer::new(),
 			source: Box::new([]),
 			eaten: StateMap::new(),
-			touched: GeneMap::new(),
+			touched: FatherMap::new(),
+			fitness: Fitness::default(),
+			reproduction: Reproduction::Mixed,
+			food: spatial::Grid::new(DORMANT_WAKE_RANGE),
+			disease: Disease::default(),
+			exposed: HashSet::new(),
+			lights: Box::new([]),
 		}
 	}
 }
 
+// the spores laid with their mother, and the bodies of the dead
 struct MinionEndState(
-	Box<[(geometry::Transform, gen::Dna)]>,
+	Box<[(obj::Id, geometry::Transform, gen::Dna)]>,
 	Box<[(Box<[geometry::Transform]>, gen::Dna)]>,
 );
 
-struct SporeEndState(Box<[(geometry::Transform, gen::Dna)]>, usize);
+// the spores hatching, and those fertilised with their father
+struct SporeEndState(Box<[(obj::Id, geometry::Transform, gen::Dna)]>, Box<[(obj::Id, obj::Id, gen::Dna)]>);
 
 impl AlifeSystem {
 	fn find_eaten_resources(minions: &agent::AgentMap, resources: &agent::AgentMap) -> StateMap {
@@ -136,7 +223,26 @@ impl AlifeSystem {
 		eaten
 	}
 
-	fn find_touched_spores(minions: &agent::AgentMap, spores: &agent::AgentMap) -> GeneMap {
+	// spores that have been fertilised, with the dna of the father
+	fn find_touched_spores(
+		mating: &Mating,
+		wrap: Option<geometry::Rect>,
+		minions: &agent::AgentMap,
+		spores: &agent::AgentMap,
+	) -> FatherMap
+	{
+		if mating.mode == Reproduction::Budding {
+			return FatherMap::new();
+		}
+		let compatible = |spore: &agent::Agent, agent: &agent::Agent| {
+			agent.gender() != spore.gender() && mating.is_compatible(gen::distance(spore.dna(), agent.dna()))
+		};
+		let mut grid = spatial::Grid::new(mating.range).with_wrap(wrap);
+		if mating.assortative {
+			for agent in minions.values().filter(|a| a.state.is_active()) {
+				grid.insert(agent.transform().position, agent.id());
+			}
+		}
 		let mut touched = HashMap::new();
 		for spore in spores
 			.values()
@@ -144,27 +250,74 @@ impl AlifeSystem {
 		{
 			for segment in spore.segments.iter() {
 				if let Some(key) = segment.state.last_touched {
-					if let Some(ref agent) = minions.get(&key.id()) {
-						if agent.gender() != spore.gender() {
-							touched.insert(key.id(), agent.dna().clone());
+					if let Some(agent) = minions.get(&key.id()) {
+						if compatible(spore, agent) {
+							touched.insert(spore.id(), (agent.id(), agent.dna().clone()));
 						}
 					}
 				}
 			}
+			// choosy partners would rarely bump into each other, so look around for the nearest match
+			if mating.assortative && !touched.contains_key(&spore.id()) {
+				let p0 = spore.transform().position;
+				let nearest = grid
+					.within(p0, mating.range)
+					.into_iter()
+					.filter_map(|(p, id)| minions.get(&id).map(|agent| (p, agent)))
+					.filter(|&(_, agent)| compatible(spore, agent))
+					.min_by(|&(a, _), &(b, _)| {
+						(a - p0).magnitude2().partial_cmp(&(b - p0).magnitude2()).unwrap_or(Ordering::Equal)
+					});
+				if let Some((_, agent)) = nearest {
+					touched.insert(spore.id(), (agent.id(), agent.dna().clone()));
+				}
+			}
 		}
 		touched
 	}
 
+	// healthy minions in contact with an infected one, whoever touched whom
+	fn find_exposed_minions(minions: &agent::AgentMap) -> HashSet<obj::Id> {
+		let mut exposed = HashSet::new();
+		for agent in minions.values().filter(|&a| a.state.is_active()) {
+			for key in agent.segments.iter().filter_map(|s| s.state.last_touched) {
+				if let Some(other) = minions.get(&key.id()) {
+					if agent.state.is_infected() && !other.state.is_infected() {
+						exposed.insert(other.id());
+					} else if other.state.is_infected() && !agent.state.is_infected() {
+						exposed.insert(agent.id());
+					}
+				}
+			}
+		}
+		exposed
+	}
+
 	fn update_minions(
+		rng: &mut XorShiftRng,
 		outbox: &Outbox,
 		dt: Seconds,
-		extent: geometry::Rect,
+		bounds: Option<geometry::Rect>,
+		fitness: &Fitness,
+		food: &spatial::Grid<obj::Id>,
+		disease: &Disease,
+		exposed: &HashSet<obj::Id>,
 		minions: &mut agent::AgentMap,
 		eaten: &StateMap,
+		flow: &mut EnergyFlow,
 	) -> MinionEndState
 	{
+		// what actually changed hands, absorbing stops at the limit
+		fn moved<F>(state: &mut agent::State, f: F) -> f32
+		where F: FnOnce(&mut agent::State) {
+			let before = state.energy();
+			f(state);
+			(state.energy() - before).abs()
+		}
+
 		let mut spawns = Vec::new();
 		let mut corpses = Vec::new();
+		let mut killed = HashSet::new();
 		for agent in minions.values_mut() {
 			if agent.state.is_active() {
 				agent.state.reset_growth();
@@ -173,11 +326,25 @@ impl AlifeSystem {
 				let maturity = segment.state.maturity();
 				let livery_color = segment.livery.albedo;
 				let transform = segment.transform().clone();
-				if maturity < 1. {
-					// just grow a bit
-					let r = GROWTH_COST_RATIO;
-					if agent.state.consume_ratio(1. - r, r) {
-						let growth = 1. + r;
+				// starving minions doze off until food shows up nearby
+				let food_nearby = !food.within(transform.position, DORMANT_WAKE_RANGE).is_empty();
+				if agent.state.is_dormant() {
+					agent.state.set_dormant(!food_nearby);
+				} else if agent.state.energy_ratio() < DORMANCY_THRESHOLD && !food_nearby {
+					agent.state.set_dormant(true);
+				}
+				let stage = agent.life_stage();
+				let metabolism = agent.metabolism();
+				let before = agent.state.energy();
+				if stage == agent::LifeStage::Juvenile {
+					// juveniles grow with age, as long as they can afford it
+					let age = agent.age().get() as f32;
+					let target = MATURITY_MINION_DEFAULT + age * (1. - MATURITY_MINION_DEFAULT) / MINION_GROWTH_TIME;
+					if maturity < target
+						&& agent.state.consume_ratio(GROWTH_COST_THRESHOLD, GROWTH_STEP * GROWTH_COST_RATIO)
+					{
+						flow.growth += before - agent.state.energy();
+						let growth = (1. + GROWTH_STEP).min(1. / maturity);
 						agent.state.grow_by(growth);
 						outbox.post(alert::Alert::GrowMinion.into());
 						outbox.post(particle::Emitter::for_new_spore(transform, livery_color, id).into());
@@ -188,26 +355,46 @@ impl AlifeSystem {
 							segment.transform.position = zero + (segment.transform.position - zero) * growth;
 						}
 					}
-				} else if agent.state.consume_ratio(SPAWN_COST_THRESHOLD, SPAWN_COST_RATIO) {
-					spawns.push((agent.last_segment().transform().clone(), agent.dna().clone()));
+				} else if stage == agent::LifeStage::Adult
+					&& agent.state.consume_ratio(SPAWN_COST_THRESHOLD, SPAWN_COST_RATIO)
+				{
+					flow.spawning += before - agent.state.energy();
+					spawns.push((id, agent.last_segment().transform().clone(), agent.dna().clone()));
+				}
+
+				let speed = segment.motion.velocity.magnitude();
+				flow.rewarded += moved(&mut agent.state, |state| {
+					state.absorb(dt * (fitness.survival + fitness.distance * speed))
+				});
+
+				if agent.state.is_infected() {
+					flow.disease += moved(&mut agent.state, |state| {
+						state.consume(dt * disease.drain);
+					});
+					agent.state.recover(dt.get() as f32);
+				} else if exposed.contains(&id)
+					&& rng.gen::<f32>() < disease.chance(agent.resistance(), dt.get() as f32)
+				{
+					agent.state.infect(disease.duration);
 				}
 
 				for segment in agent.segments.iter_mut() {
 					let p = segment.transform().position;
-					if p.x < extent.min.x || p.x > extent.max.x || p.y < extent.min.y || p.y > extent.max.y {
+					if bounds.map_or(false, |bounds| !bounds.contains(p)) {
 						agent.state.die();
 					}
 					if segment.flags.contains(segment::Flags::MOUTH) {
 						if let Some(id) = segment.state.last_touched {
 							if let Some(eaten_state) = eaten.get(&id.id()) {
 								let energy = eaten_state.energy();
-								agent.state.absorb(energy);
+								flow.eaten += moved(&mut agent.state, |state| state.absorb(energy * fitness.food));
 							}
 						}
 					}
-					agent
-						.state
-						.consume(dt * segment.state.charge() * segment.growing_radius());
+					let cost = dt * metabolism * segment.state.charge() * segment.growing_radius();
+					flow.metabolism += moved(&mut agent.state, |state| {
+						state.consume(cost);
+					});
 					segment.state.update(dt);
 				}
 
@@ -221,20 +408,45 @@ impl AlifeSystem {
 					agent.state.die();
 				}
 
+				if !agent.state.is_alive() {
+					flow.died += agent.state.energy();
+					killed.insert(id);
+				}
+
 				if let Some(segment) = agent.first_segment(segment::Flags::TRACKER) {
 					agent.state.track_position(segment.transform.position);
 				}
 			}
 		}
+		if fitness.kills != 0. && !killed.is_empty() {
+			// credit whoever had their mouth on a minion when it died
+			for agent in minions.values_mut().filter(|a| a.state.is_alive()) {
+				let kills = agent
+					.segments
+					.iter()
+					.filter(|s| s.flags.contains(segment::Flags::MOUTH))
+					.filter_map(|s| s.state.last_touched)
+					.filter(|key| killed.contains(&key.id()))
+					.count();
+				flow.preyed += moved(&mut agent.state, |state| state.absorb(kills as f32 * fitness.kills));
+			}
+		}
 		MinionEndState(spawns.into_boxed_slice(), corpses.into_boxed_slice())
 	}
 
-	fn update_resources(dt: Seconds, timer: &SimulationTimer, resources: &mut agent::AgentMap, eaten: &StateMap) {
+	fn update_resources(
+		dt: Seconds,
+		timer: &SimulationTimer,
+		resources: &mut agent::AgentMap,
+		eaten: &StateMap,
+		flow: &mut EnergyFlow,
+	)
+	{
 		for resource in resources.values_mut() {
-			if eaten.get(&resource.id()).is_some()
-				|| resource.state.energy() <= 0.
-				|| resource.state.lifecycle().is_expired(timer)
-			{
+			if eaten.get(&resource.id()).is_some() || resource.state.energy() <= 0. {
+				resource.state.die();
+			} else if resource.state.lifecycle().is_expired(timer) {
+				flow.wasted += resource.state.energy();
 				resource.state.die();
 			} else if resource.state.is_active() {
 				for segment in resource.segments.iter_mut() {
@@ -244,51 +456,102 @@ impl AlifeSystem {
 		}
 	}
 
-	fn crossover(dna: &gen::Dna, foreign_dna: &Option<gen::Dna>) -> gen::Dna {
+	// how much light reaches a point, each source falling off with the distance
+	fn light_at(lights: &[(geometry::Position, f32)], p: geometry::Position) -> f32 {
+		lights
+			.iter()
+			.map(|&(light, intensity)| intensity / (1. + (light - p).magnitude2() / PLANT_LIGHT_RANGE.powi(2)))
+			.sum()
+	}
+
+	// plants live off the light and drop seeds around them when they have plenty;
+	// for the energy they count as resources, sown by the light and wasted when they wither
+	fn update_plants(
+		rng: &mut XorShiftRng,
+		dt: Seconds,
+		lights: &[(geometry::Position, f32)],
+		plants: &mut agent::AgentMap,
+		eaten: &StateMap,
+		flow: &mut EnergyFlow,
+	) -> Box<[(geometry::Transform, gen::Dna)]>
+	{
+		let mut seedlings = Vec::new();
+		let t = dt.get() as f32;
+		for plant in plants.values_mut() {
+			if eaten.get(&plant.id()).is_some() {
+				plant.state.die();
+			} else if plant.state.is_active() {
+				let leaves = plant.segments.len() - 1;
+				let light = Self::light_at(lights, plant.transform().position);
+				let before = plant.state.energy();
+				plant.state.absorb(t * light * PLANT_PHOTOSYNTHESIS * leaves as f32);
+				flow.sown += plant.state.energy() - before;
+				let cost = t * PLANT_METABOLISM * plant.segments.len() as f32;
+				if !plant.state.consume(cost) || plant.state.energy() < 1. {
+					flow.wasted += plant.state.energy();
+					plant.state.die();
+					continue;
+				}
+				flow.wasted += cost;
+				let before = plant.state.energy();
+				if plant.state.consume_ratio(PLANT_SEED_THRESHOLD, PLANT_SEED_COST_RATIO) {
+					flow.wasted += before - plant.state.energy();
+					let angle = rng.gen::<f32>() * 2. * ::std::f32::consts::PI;
+					let distance = PLANT_SEED_RANGE * (0.5 + 0.5 * rng.gen::<f32>());
+					let position =
+						plant.transform().position + geometry::Position::new(angle.cos(), angle.sin()) * distance;
+					seedlings.push((geometry::Transform::new(position, angle), plant.dna().clone()));
+				}
+				for segment in plant.segments.iter_mut() {
+					segment.state.update(dt)
+				}
+			}
+		}
+		seedlings.into_boxed_slice()
+	}
+
+	fn crossover(rng: &mut XorShiftRng, dna: &gen::Dna, foreign_dna: &Option<gen::Dna>) -> gen::Dna {
 		match *foreign_dna {
 			Some(ref foreign) => gen::Genome::copy_from(&foreign)
-				.crossover(&mut rand::thread_rng(), dna)
+				.crossover(rng, dna)
 				.dna_cloned(),
 			None => dna.clone(),
 		}
 	}
 
 	fn update_spores(
+		rng: &mut XorShiftRng,
 		dt: Seconds,
+		reproduction: Reproduction,
 		timer: &SimulationTimer,
 		spores: &mut agent::AgentMap,
-		touched: &GeneMap,
+		touched: &FatherMap,
 	) -> SporeEndState
 	{
 		let mut spawns = Vec::new();
-		let mut fertilise_count = 0usize;
+		let mut fertilised = Vec::new();
 		for (spore_id, spore) in spores.iter_mut() {
 			if spore.state.lifecycle().is_expired(timer) {
 				spore.state.die();
-				spawns.push((
-					spore.transform().clone(),
-					Self::crossover(spore.dna(), spore.state.foreign_dna()),
-				))
+				// with two parents required, an unfertilised spore is just wasted
+				if reproduction != Reproduction::Mating || spore.state.is_fertilised() {
+					spawns.push((
+						*spore_id,
+						spore.transform().clone(),
+						Self::crossover(rng, spore.dna(), spore.state.foreign_dna()),
+					))
+				}
 			} else if spore.state.is_active() {
-				for segment in spore.segments.iter_mut() {
-					if let Some(key) = segment.state.last_touched {
-						if let Some(touched_dna) = touched.get(&key.id()) {
-							debug!(
-								"fertilised: {} by {} as {}",
-								spore_id,
-								key.id(),
-								touched_dna.to_base64(base64::STANDARD)
-							);
-							fertilise_count += 1;
-							spore.state.fertilise(touched_dna);
-						}
-					}
+				if let Some(&(father, ref touched_dna)) = touched.get(spore_id) {
+					debug!("fertilised: {} as {}", spore_id, touched_dna.to_base64(base64::STANDARD));
+					fertilised.push((*spore_id, father, touched_dna.clone()));
+					spore.state.fertilise(touched_dna);
 				}
 				for segment in spore.segments.iter_mut() {
 					segment.state.update(dt)
 				}
 			}
 		}
-		SporeEndState(spawns.into_boxed_slice(), fertilise_count)
+		SporeEndState(spawns.into_boxed_slice(), fertilised.into_boxed_slice())
 	}
 }
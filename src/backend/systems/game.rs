@@ -1,17 +1,20 @@
 use super::*;
-use app::constants::*;
-use app::Event;
+use constants::*;
+use events::Event;
 use backend::messagebus::{Inbox, Message, PubSub, ReceiveDrain, Whiteboard};
+use backend::obj;
 use backend::obj::Transformable;
 use backend::world;
 use backend::world::agent;
+use backend::world::objective::{GameMode, Objective, Score};
 use cgmath::InnerSpace;
 use core::clock::*;
 use core::geometry::Transform;
 use core::geometry::*;
 use core::math::{exponential_filter, ExponentialFilter};
-use rand;
 use rand::Rng;
+use rand::XorShiftRng;
+use std::collections::HashMap;
 use std::f32::consts;
 
 #[derive(Default)]
@@ -29,6 +32,16 @@ pub struct GameSystem {
 	playerstate: PlayerState,
 	feeders: Vec<Feeder>,
 	inbox: Option<Inbox>,
+	mode: GameMode,
+	score: Score,
+	minions: usize,
+	// where each minion was first seen, and the farthest any got from there
+	origins: HashMap<obj::Id, Position>,
+	farthest: f32,
+	arena: f32,
+	// the objectives met in the last update
+	met: Vec<usize>,
+	rng: XorShiftRng,
 }
 
 struct Feeder {
@@ -74,6 +87,8 @@ impl System for GameSystem {
 	fn clear(&mut self) {
 		self.playerstate = PlayerState::default();
 		self.feeders = Vec::new();
+		self.origins.clear();
+		self.met.clear();
 	}
 
 	fn import(&mut self, world: &world::World) {
@@ -89,7 +104,8 @@ impl System for GameSystem {
 		}
 
 		let source = world.feeders();
-		// Add missing emitters - deletion not supported
+		// Add missing emitters, drop the deleted ones
+		self.feeders.truncate(source.len());
 		for s in &source[self.feeders.len()..] {
 			//			let s = &source[i];
 			self.feeders
@@ -97,11 +113,22 @@ impl System for GameSystem {
 		}
 		for (i, d) in self.feeders.iter_mut().enumerate() {
 			d.position = source[i].transform().position;
+			if d.hourglass.capacity() != source[i].rate() {
+				d.hourglass.set_capacity(source[i].rate());
+			}
+		}
+
+		self.rng = world.rng_for(GAME_RNG_STREAM);
+		self.mode = world.game_mode().clone();
+		self.score = world.score().clone();
+		self.score.fit(&self.mode);
+		if self.mode.enabled {
+			self.census(world);
 		}
 	}
 
 	fn update(&mut self, _: &world::AgentState, dt: Seconds) {
-		let rng = &mut rand::thread_rng();
+		let rng = &mut self.rng;
 		self.dt = dt;
 
 		self.timer.tick(dt);
@@ -134,6 +161,7 @@ impl System for GameSystem {
 				.min(self.playerstate.bullet_charge + dt.get() * BULLET_FIRE_RATE * self.playerstate.firing_rate)
 		};
 		self.playerstate.trigger_held = false;
+		self.update_score(dt.get() as f32);
 	}
 
 	fn export(&self, world: &mut world::World, outbox: &Outbox) {
@@ -180,6 +208,13 @@ impl System for GameSystem {
 				segment.state.update(self.dt);
 			}
 		}
+
+		if self.mode.enabled {
+			world.set_score(self.score.clone());
+		}
+		for index in &self.met {
+			outbox.post(Event::ObjectiveMet(*index).into());
+		}
 	}
 }
 
@@ -191,6 +226,14 @@ impl Default for GameSystem {
 			playerstate: PlayerState::default(),
 			feeders: Vec::new(),
 			inbox: None,
+			mode: GameMode::default(),
+			score: Score::default(),
+			minions: 0,
+			origins: HashMap::new(),
+			farthest: 0.,
+			arena: 0.,
+			met: Vec::new(),
+			rng: XorShiftRng::new_unseeded(),
 		}
 	}
 }
@@ -201,4 +244,52 @@ impl GameSystem {
 		self.playerstate.firing_rate = firing_rate;
 		self.playerstate.trigger_held = true;
 	}
+
+	fn census(&mut self, world: &world::World) {
+		let minions = world.agents(agent::AgentType::Minion);
+		self.minions = minions.len();
+		self.origins.retain(|id, _| minions.contains_key(id));
+		let extent = &world.extent;
+		self.arena = (extent.max.x - extent.min.x).min(extent.max.y - extent.min.y);
+		self.farthest = 0.;
+		for (id, minion) in minions.iter() {
+			let position = minion.transform().position;
+			let origin = *self.origins.entry(*id).or_insert(position);
+			self.farthest = self.farthest.max((position - origin).magnitude());
+		}
+	}
+
+	// points for every second the population holds, and a bonus for each objective met
+	fn update_score(&mut self, dt: f32) {
+		self.met.clear();
+		if !self.mode.enabled {
+			return;
+		}
+		for (i, objective) in self.mode.objectives.iter().enumerate() {
+			if self.score.met[i] {
+				continue;
+			}
+			let done = match *objective {
+				Objective::Population { minions, minutes } => {
+					if self.minions >= minions {
+						self.score.held[i] += dt;
+						self.score.points += dt * SCORE_POINTS_PER_SECOND;
+					} else {
+						self.score.held[i] = 0.;
+					}
+					self.score.held[i] >= minutes * 60.
+				}
+				Objective::Crossing => {
+					let crossed = self.farthest / self.arena.max(1.);
+					self.score.held[i] = self.score.held[i].max(crossed);
+					crossed >= OBJECTIVE_CROSSING_RATIO
+				}
+			};
+			if done {
+				self.score.met[i] = true;
+				self.score.points += SCORE_OBJECTIVE_BONUS;
+				self.met.push(i);
+			}
+		}
+	}
 }
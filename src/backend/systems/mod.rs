@@ -4,13 +4,23 @@ pub mod ai;
 pub mod alife;
 pub mod game;
 pub mod particle;
+pub mod probe;
+pub mod registry;
 
 pub use self::physics::PhysicsSystem;
+pub use self::physics::Category as CollisionCategory;
+pub use self::physics::CollisionRules;
+pub use self::physics::Engine as PhysicsEngine;
+pub use self::physics::Discrepancy as PhysicsDiscrepancy;
+pub use self::physics::PhysicsSettings;
 pub use self::animation::AnimationSystem;
 pub use self::game::GameSystem;
 pub use self::ai::AiSystem;
 pub use self::alife::AlifeSystem;
 pub use self::particle::ParticleSystem;
+pub use self::probe::ProbeSystem;
+pub use self::registry::SystemMode;
+pub use self::registry::Systems;
 
 use backend::world;
 use backend::messagebus::{PubSub, Outbox};
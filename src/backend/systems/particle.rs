@@ -1,5 +1,5 @@
 use super::*;
-use app::constants::*;
+use constants::*;
 use backend::messagebus::{Inbox, Message, ReceiveDrain, Whiteboard};
 use backend::obj;
 use backend::world;
@@ -12,8 +12,8 @@ use core::geometry::{Acceleration, Motion, Position, Transform, Velocity};
 use num;
 use num::NumCast;
 use num::Zero;
-use rand;
 use rand::Rng;
+use rand::XorShiftRng;
 use rayon::prelude::*;
 use std::collections::HashMap;
 use std::collections::VecDeque;
@@ -106,7 +106,13 @@ struct Particle {
 }
 
 trait Emitter {
-	fn emit(&mut self, dt: Seconds, id_counter: &mut usize, destination: &mut HashMap<obj::Id, ParticleBatch>) -> bool;
+	fn emit(
+		&mut self,
+		rng: &mut XorShiftRng,
+		dt: Seconds,
+		id_counter: &mut usize,
+		destination: &mut HashMap<obj::Id, ParticleBatch>,
+	) -> bool;
 	fn attached_to(&self) -> EmitterAttachment { EmitterAttachment::None }
 	fn update_transform(&mut self, _transform: Transform, _motion: Motion) {}
 }
@@ -272,8 +278,14 @@ impl SimpleEmitter {
 }
 
 impl Emitter for SimpleEmitter {
-	fn emit(&mut self, dt: Seconds, id_counter: &mut usize, destination: &mut HashMap<obj::Id, ParticleBatch>) -> bool {
-		let mut rng = rand::thread_rng();
+	fn emit(
+		&mut self,
+		rng: &mut XorShiftRng,
+		dt: Seconds,
+		id_counter: &mut usize,
+		destination: &mut HashMap<obj::Id, ParticleBatch>,
+	) -> bool
+	{
 		let jitter_value = self.jitter;
 		let mut jitter = move |w| (rng.next_f32() * 2. * w - w) * jitter_value + 1.;
 		if self.active {
@@ -349,6 +361,7 @@ pub struct ParticleSystem {
 	dt: Seconds,
 	simulation_timer: SimulationTimer,
 	simulation_clock: TimerStopwatch,
+	rng: XorShiftRng,
 }
 
 impl System for ParticleSystem {
@@ -362,6 +375,7 @@ impl System for ParticleSystem {
 	}
 
 	fn import(&mut self, world: &world::World) {
+		self.rng = world.rng_for(PARTICLE_RNG_STREAM);
 		let mut emitters = Vec::new();
 		if let Some(ref inbox) = self.inbox {
 			for message in inbox.drain() {
@@ -519,6 +533,7 @@ impl Default for ParticleSystem {
 			dt: seconds(0.),
 			simulation_clock: TimerStopwatch::new(&simulation_timer),
 			simulation_timer,
+			rng: XorShiftRng::new_unseeded(),
 		}
 	}
 }
@@ -534,7 +549,7 @@ impl ParticleSystem {
 		let mut expired: Vec<usize> = Vec::new();
 
 		for (id, emitter) in &mut self.emitters {
-			let alive = emitter.emit(dt, &mut self.id_counter, &mut self.particles);
+			let alive = emitter.emit(&mut self.rng, dt, &mut self.id_counter, &mut self.particles);
 			if !alive {
 				expired.push(*id);
 			}
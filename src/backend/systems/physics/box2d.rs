@@ -1,20 +1,15 @@
 use super::*;
-use app::constants::*;
-use app::Event;
-use backend::messagebus::{Inbox, Message, PubSub, ReceiveDrain, Whiteboard};
+use constants::*;
 use backend::obj;
 use backend::obj::*;
 use backend::world;
 use backend::world::agent;
+use backend::world::obstacle::Obstacle;
 use backend::world::segment;
-use backend::world::segment::Intent;
-use backend::world::segment::PilotRotation;
-use cgmath::InnerSpace;
 use core::geometry::Transform;
 use core::geometry::*;
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::rc::Rc;
 use wrapped2d::b2;
 use wrapped2d::dynamics::world::callbacks::ContactAccess;
@@ -29,24 +24,35 @@ impl UserDataTypes for AgentData {
 }
 
 type ContactSet = Rc<RefCell<HashMap<agent::Key, agent::Key>>>;
+// segments that started touching an obstacle, and its index
+type ObstacleContacts = Rc<RefCell<Vec<(agent::Key, usize)>>>;
+
+// a segment held by a sticky obstacle until the time runs out
+struct Stuck {
+	key: agent::Key,
+	obstacle: usize,
+	joint: b2::JointHandle,
+	remaining: f32,
+}
 
-pub struct PhysicsSystem {
+pub struct Box2dBackend {
 	world: b2::World<AgentData>,
-	initial_extent: Rect,
-	inbox: Option<Inbox>,
+	rules: CollisionRules,
 	handles: HashMap<agent::Key, b2::BodyHandle>,
 	touched: ContactSet,
-	picked: HashSet<Id>,
+	walls: Option<b2::BodyHandle>,
+	obstacles: Vec<Obstacle>,
+	obstacle_handles: HashMap<usize, b2::BodyHandle>,
+	obstacle_contacts: ObstacleContacts,
+	stuck: Vec<Stuck>,
+	// segments let go of lately, still touching the obstacle they were stuck to, and the seconds until they can stick
+	released: HashMap<agent::Key, f32>,
 }
 
-#[allow(unused)]
-enum BodyUpdate {
-	Transform(b2::Vec2, f32),
-	Torque(f32),
-	AngularImpulse(f32),
-	Force(b2::Vec2, b2::Vec2),
-	LinearImpulse(b2::Vec2, b2::Vec2),
-}
+// box2d worlds are not thread safe: the world keeps raw pointers, and its contact listener shares cells with the
+// backend. The backend owns the world and every clone of the cells, so they all move together when the physics
+// system moves to another thread, and its regions are stepped one after the other, never from two threads at once.
+unsafe impl Send for Box2dBackend {}
 
 struct JointRef<'a> {
 	refs: agent::Key,
@@ -58,35 +64,50 @@ struct JointRef<'a> {
 	attachment: Option<segment::Attachment>,
 }
 
-impl System for PhysicsSystem {
-	fn attach(&mut self, bus: &mut PubSub) {
-		self.inbox = Some(bus.subscribe(Box::new(|m| match *m {
-			Message::Event(Event::PickMinion(_)) => true,
-			_ => false,
-		})));
-	}
-
-	fn init(&mut self, world: &world::World) {
-		self.initial_extent = world.extent;
-		self.init_extent();
+impl PhysicsBackend for Box2dBackend {
+	fn clear(&mut self, extent: Rect, rules: &CollisionRules) {
+		self.rules = rules.clone();
+		self.touched.borrow_mut().clear();
+		self.obstacle_contacts.borrow_mut().clear();
+		self.handles.clear();
+		self.obstacles.clear();
+		self.obstacle_handles.clear();
+		self.stuck.clear();
+		self.released.clear();
+		self.world = Self::new_world(self.touched.clone(), self.obstacle_contacts.clone());
+		self.walls = None;
+		self.set_walls(Some(extent));
 	}
 
-	fn clear(&mut self) {
-		for i in &self.inbox {
-			i.drain();
+	fn set_walls(&mut self, extent: Option<Rect>) {
+		if let Some(handle) = self.walls.take() {
+			self.world.destroy_body(handle);
+		}
+		if let Some(extent) = extent {
+			let mut f_def = b2::FixtureDef::new();
+			let mut b_def = b2::BodyDef::new();
+			b_def.body_type = b2::BodyType::Static;
+			let refs = agent::Key::with_id(0xFFFF_FFFFusize);
+			let handle = self.world.create_body_with(&b_def, refs);
+
+			let mut rect = b2::ChainShape::new();
+			rect.create_loop(&[
+				Self::p2v(extent.bottom_left()),
+				Self::p2v(extent.bottom_right()),
+				Self::p2v(extent.top_right()),
+				Self::p2v(extent.top_left()),
+			]);
+
+			self.world.body_mut(handle).create_fixture_with(&rect, &mut f_def, refs);
+			self.walls = Some(handle);
 		}
-		self.touched.borrow_mut().clear();
-		self.handles.clear();
-		self.picked.clear();
-		self.world = Self::new_world(self.touched.clone());
-		self.init_extent();
 	}
 
 	fn register(&mut self, agent: &world::agent::Agent) {
 		// build fixtures
-		let joint_refs = PhysicsSystem::build_fixtures(&mut self.world, &agent);
+		let joint_refs = Self::build_fixtures(&mut self.world, &self.rules, agent);
 		// and then assemble them with joints
-		PhysicsSystem::build_joints(&mut self.world, &joint_refs);
+		Self::build_joints(&mut self.world, &joint_refs);
 		// record them
 		for JointRef { refs, handle, .. } in joint_refs {
 			self.handles.insert(refs, handle);
@@ -95,95 +116,65 @@ impl System for PhysicsSystem {
 
 	fn unregister(&mut self, agent: &world::agent::Agent) {
 		let object_id = agent.id();
-		let segments = agent.segments();
-		for segment in segments {
-			let refs = agent::Key::with_segment(object_id, segment.index);
-			if let Some(handle) = self.handles.remove(&refs) {
-				self.world.destroy_body(handle);
-			}
+		for segment in agent.segments() {
+			self.remove(agent::Key::with_segment(object_id, segment.index));
 		}
 	}
 
-	fn import(&mut self, world: &world::World) {
-		let messages = match self.inbox {
-			Some(ref drain) => drain.drain(),
-			None => Vec::new(),
-		};
-		self.picked.clear();
-		for message in messages {
-			if let Message::Event(Event::PickMinion(position)) = message {
-				let picked = self.pick(position);
-				if let Some(picked_id) = picked {
-					self.picked.insert(picked_id);
-				}
-			}
+	fn keys(&self) -> Vec<agent::Key> { self.handles.keys().cloned().collect() }
+
+	fn remove(&mut self, key: agent::Key) {
+		self.release(|stuck| stuck.key == key);
+		self.released.remove(&key);
+		if let Some(handle) = self.handles.remove(&key) {
+			self.world.destroy_body(handle);
 		}
-		for agent in world.agents(agent::AgentType::Minion).values() {
-			if agent.state.growth() > 0. {
-				self.refresh_registration(agent)
+	}
+
+	// only the obstacles that changed are built again, the segments stuck to the others stay stuck
+	fn set_obstacles(&mut self, obstacles: &[Obstacle]) {
+		let kept = kept_obstacles(&self.obstacles, obstacles, self.obstacle_handles.keys());
+		self.release(|stuck| !kept.contains(&stuck.obstacle) || obstacles[stuck.obstacle].sticky <= 0.);
+		let changed: Vec<_> = self.obstacle_handles.keys().filter(|index| !kept.contains(index)).cloned().collect();
+		for index in changed {
+			if let Some(handle) = self.obstacle_handles.remove(&index) {
+				self.world.destroy_body(handle);
 			}
 		}
+		self.obstacles = obstacles.to_vec();
+		for (index, obstacle) in obstacles
+			.iter()
+			.enumerate()
+			.filter(|&(index, o)| o.is_blocking() && !kept.contains(&index))
+		{
+			let extent = &obstacle.extent;
+			let refs = Obstacle::key(index);
+			let mut b_def = b2::BodyDef::new();
+			b_def.body_type = b2::BodyType::Static;
+			b_def.position = Self::p2v((extent.min + extent.max) * 0.5);
+			let handle = self.world.create_body_with(&b_def, refs);
+			let mut f_def = b2::FixtureDef::new();
+			f_def.friction = obstacle.friction;
+			f_def.restitution = obstacle.restitution;
+			let mut shape = b2::PolygonShape::new();
+			shape.set_as_box((extent.max.x - extent.min.x) * 0.5, (extent.max.y - extent.min.y) * 0.5);
+			self.world.body_mut(handle).create_fixture_with(&shape, &mut f_def, refs);
+			self.obstacle_handles.insert(index, handle);
+		}
 	}
 
-	fn update(&mut self, state: &world::AgentState, dt_sec: Seconds) {
-		use self::BodyUpdate::*;
+	fn apply(&mut self, state: &world::AgentState, dt: f32) {
 		let mut dynamic_updates = Vec::new();
-		let dt: f32 = dt_sec.into();
-		#[inline]
-		fn to_vec2(v: Position) -> b2::Vec2 { PhysicsSystem::p2v(v) };
-		#[inline]
-		fn from_vec2(v: b2::Vec2) -> Position { PhysicsSystem::v2p(v) };
 		for (h, b) in self.world.bodies() {
 			let body = b.borrow();
-			let center = *(*body).world_center();
-			let key = (*body).user_data();
-			if let Some(segment) = state.agent(key.agent_id).and_then(|c| c.segment(key.segment_index)) {
-				match segment.state.intent {
-					Intent::Move(force) => dynamic_updates.push((h, Force(center, to_vec2(force)))),
-					Intent::Brake(force) => {
-						let linear_velocity = from_vec2(*(*body).linear_velocity());
-						let comp = force.dot(linear_velocity);
-						if comp < 0. {
-							dynamic_updates.push((h, Force(center, to_vec2(force))));
-						}
-					}
-					Intent::PilotTo(force, ref target_angle) => {
-						if let Some(force) = force {
-							let linear_velocity = from_vec2(*(*body).linear_velocity());
-							let speed2 = linear_velocity.magnitude2();
-							let drag_factor = (1. - speed2 * DRAG_COEFFICIENT).min(1.).max(0.);
-							dynamic_updates.push((h, Force(center, to_vec2(force * drag_factor))));
-						}
-						match *target_angle {
-							PilotRotation::LookAt(target) => {
-								let look_at_vector = target - from_vec2(center);
-								let target_angle = f32::atan2(-look_at_vector.x, look_at_vector.y);
-								dynamic_updates.push((h, Transform(*(*body).position(), target_angle)));
-							}
-							PilotRotation::Orientation(direction) => {
-								let target_angle = f32::atan2(-direction.x, direction.y);
-								dynamic_updates.push((h, Transform(*(*body).position(), target_angle)));
-							}
-							PilotRotation::Turn(angle) => {
-								dynamic_updates.push((h, Torque(angle)));
-							}
-							PilotRotation::FromVelocity => {
-								let linear_velocity = from_vec2(*(*body).linear_velocity());
-								let target_angle = f32::atan2(-linear_velocity.x, linear_velocity.y);
-								dynamic_updates.push((h, Transform(*(*body).position(), target_angle)));
-							}
-							PilotRotation::None => {}
-							//TODO: try physics!
-							//let angle = (*body).angle();
-							//let norm_diff = math::normalize_rad(target_angle - angle);
-							//body_updates.push((h, Torque(norm_diff * COMPASS_SPRING_POWER)))
-							//torques.push((h, norm_diff * COMPASS_SPRING_POWER));
-						}
-					}
-					Intent::RunAway(impulse) => dynamic_updates.push((h, LinearImpulse(center, to_vec2(impulse * dt)))),
-					_ => {}
-				}
-			}
+			let body_state = BodyState {
+				position: Self::v2p(*(*body).position()),
+				center: Self::v2p(*(*body).world_center()),
+				velocity: Self::v2p(*(*body).linear_velocity()),
+				mass: (*body).mass(),
+				is_frozen: (*body).body_type() == b2::BodyType::Static,
+			};
+			body_updates(state, (*body).user_data(), &body_state, &self.obstacles, dt, h, &mut dynamic_updates);
 		}
 
 		for (h, update) in dynamic_updates {
@@ -191,58 +182,81 @@ impl System for PhysicsSystem {
 			match update {
 				BodyUpdate::Torque(torque) => b.apply_torque(torque, true),
 				BodyUpdate::AngularImpulse(impulse) => b.apply_angular_impulse(impulse, true),
-				BodyUpdate::Force(application_point, force) => b.apply_force(&force, &application_point, true),
+				BodyUpdate::Force(application_point, force) => {
+					b.apply_force(&Self::p2v(force), &Self::p2v(application_point), true)
+				}
 				BodyUpdate::LinearImpulse(application_point, impulse) => {
-					b.apply_linear_impulse(&impulse, &application_point, true)
+					b.apply_linear_impulse(&Self::p2v(impulse), &Self::p2v(application_point), true)
 				}
-				BodyUpdate::Transform(translation, rotation) => b.set_transform(&translation, rotation),
+				BodyUpdate::Transform(translation, rotation) => b.set_transform(&Self::p2v(translation), rotation),
+				BodyUpdate::Frozen(is_frozen) => b.set_body_type(if is_frozen {
+					b2::BodyType::Static
+				} else {
+					b2::BodyType::Dynamic
+				}),
 			}
 		}
+	}
+
+	fn step(&mut self, dt: f32) {
 		self.world.step(dt, 8, 3);
+		self.update_stuck(dt);
 	}
 
-	fn export(&self, world: &mut world::World, outbox: &Outbox) {
-		for (_, b) in self.world.bodies() {
-			let body = b.borrow();
-			let position = (*body).position();
-			let angle = (*body).angle();
-			let velocity = (*body).linear_velocity();
-			let spin = (*body).angular_velocity();
-			let key = (*body).user_data();
-
-			if let Some(agent) = world.agent_mut(key.agent_id) {
-				if let Some(segment) = agent.segment_mut(key.segment_index) {
-					segment.transform_to(Transform::from_components(position.x, position.y, angle));
-					segment.motion_to(Motion::from_components(velocity.x, velocity.y, spin));
-					segment.state.last_touched = self.touched.borrow().get(key).cloned();
+	fn is_thread_safe(&self) -> bool { false }
+
+	fn export(&self, world: &mut world::World) -> Vec<Id> {
+		let mut runaways = Vec::new();
+		{
+			let touched = self.touched.borrow();
+			for (_, b) in self.world.bodies() {
+				let body = b.borrow();
+				let position = (*body).position();
+				let angle = (*body).angle();
+				let velocity = (*body).linear_velocity();
+				let spin = (*body).angular_velocity();
+				let key = (*body).user_data();
+
+				if is_runaway(Self::v2p(*position), angle, Self::v2p(*velocity), spin) {
+					runaways.push(key.agent_id);
+					continue;
+				}
+				if let Some(agent) = world.agent_mut(key.agent_id) {
+					if let Some(segment) = agent.segment_mut(key.segment_index) {
+						segment.transform_to(Transform::from_components(position.x, position.y, angle));
+						segment.motion_to(Motion::from_components(velocity.x, velocity.y, spin));
+						segment.state.last_touched = touched.get(key).cloned();
+					}
 				}
 			}
 		}
-		for (_, agent) in world.agents_mut(agent::AgentType::Minion).iter_mut() {
-			agent.state.reset_growth()
-		}
-		for id in &self.picked {
-			outbox.post(Event::SelectMinion(*id).into());
-		}
 		self.touched.borrow_mut().clear();
+		runaways
 	}
+
+	fn pick(&self, pos: Position) -> Option<Id> { Self::pick_in(&self.world, pos) }
 }
 
-impl Default for PhysicsSystem {
+impl Default for Box2dBackend {
 	fn default() -> Self {
 		let touched = Rc::new(RefCell::new(HashMap::new()));
-		PhysicsSystem {
-			inbox: None,
-			initial_extent: Rect::default(),
-			world: Self::new_world(touched.clone()),
+		let obstacle_contacts = Rc::new(RefCell::new(Vec::new()));
+		Box2dBackend {
+			world: Self::new_world(touched.clone(), obstacle_contacts.clone()),
+			rules: CollisionRules::default(),
 			handles: HashMap::with_capacity(5000),
-			picked: HashSet::with_capacity(100),
 			touched,
+			walls: None,
+			obstacles: Vec::new(),
+			obstacle_handles: HashMap::new(),
+			obstacle_contacts,
+			stuck: Vec::new(),
+			released: HashMap::new(),
 		}
 	}
 }
 
-impl PhysicsSystem {
+impl Box2dBackend {
 	fn p2v(p: Position) -> b2::Vec2 { b2::Vec2 { x: p.x, y: p.y } }
 
 	fn pr2v(p: Position, radius: f32) -> b2::Vec2 {
@@ -254,28 +268,58 @@ impl PhysicsSystem {
 
 	fn v2p(p: b2::Vec2) -> Position { Position::new(p.x, p.y) }
 
-	fn init_extent(&mut self) {
-		let extent = self.initial_extent;
-		let mut f_def = b2::FixtureDef::new();
-		let mut b_def = b2::BodyDef::new();
-		b_def.body_type = b2::BodyType::Static;
-		let refs = agent::Key::with_id(0xFFFF_FFFFusize);
-		let handle = self.world.create_body_with(&b_def, refs);
-
-		let mut rect = b2::ChainShape::new();
-		rect.create_loop(&[
-			Self::p2v(extent.bottom_left()),
-			Self::p2v(extent.bottom_right()),
-			Self::p2v(extent.top_right()),
-			Self::p2v(extent.top_left()),
-		]);
-
-		self.world.body_mut(handle).create_fixture_with(&rect, &mut f_def, refs);
+	fn release<F>(&mut self, f: F)
+	where F: Fn(&Stuck) -> bool {
+		let world = &mut self.world;
+		self.stuck.retain(|stuck| {
+			if f(stuck) {
+				world.destroy_joint(stuck.joint);
+				false
+			} else {
+				true
+			}
+		});
 	}
 
-	fn refresh_registration(&mut self, agent: &world::agent::Agent) {
-		self.unregister(agent);
-		self.register(agent);
+	// welds the segments that started touching a sticky obstacle to it where they are, and lets go of the expired
+	// ones: these are still touching it, so they get a while to move away before a new contact can weld them again
+	fn update_stuck(&mut self, dt: f32) {
+		for stuck in &mut self.stuck {
+			stuck.remaining -= dt;
+		}
+		for cooldown in self.released.values_mut() {
+			*cooldown -= dt;
+		}
+		self.released.retain(|_, cooldown| *cooldown > 0.);
+		let expired: Vec<_> = self.stuck.iter().filter(|stuck| stuck.remaining <= 0.).map(|stuck| stuck.key).collect();
+		self.release(|stuck| stuck.remaining <= 0.);
+		self.released.extend(expired.into_iter().map(|key| (key, OBSTACLE_STICKY_COOLDOWN)));
+		let contacts: Vec<_> = self.obstacle_contacts.borrow_mut().drain(..).collect();
+		for (key, index) in contacts {
+			let sticky = self.obstacles.get(index).map(|o| o.sticky).unwrap_or(0.);
+			if sticky <= 0. || self.released.contains_key(&key) || self.stuck.iter().any(|stuck| stuck.key == key) {
+				continue;
+			}
+			if let (Some(&body), Some(&anchor)) = (self.handles.get(&key), self.obstacle_handles.get(&index)) {
+				let (position, angle) = {
+					let body = self.world.body(body);
+					(Self::v2p(*body.position()), body.angle())
+				};
+				let anchor_position = Self::v2p(*self.world.body(anchor).position());
+				let mut joint = b2::WeldJointDef::new(anchor, body);
+				joint.collide_connected = true;
+				joint.local_anchor_a = Self::p2v(position - anchor_position);
+				joint.local_anchor_b = b2::Vec2 { x: 0., y: 0. };
+				joint.reference_angle = angle;
+				let joint = self.world.create_joint_with(&joint, ());
+				self.stuck.push(Stuck {
+					key,
+					obstacle: index,
+					joint,
+					remaining: sticky,
+				});
+			}
+		}
 	}
 
 	#[allow(too_many_arguments)]
@@ -395,7 +439,12 @@ impl PhysicsSystem {
 		};
 	}
 
-	fn build_fixtures<'a>(world: &mut b2::World<AgentData>, agent: &'a world::agent::Agent) -> Vec<JointRef<'a>> {
+	fn build_fixtures<'a>(
+		world: &mut b2::World<AgentData>,
+		rules: &CollisionRules,
+		agent: &'a world::agent::Agent,
+	) -> Vec<JointRef<'a>>
+	{
 		let object_id = agent.id();
 		let segments = agent.segments();
 		segments
@@ -408,6 +457,11 @@ impl PhysicsSystem {
 				f_def.density = material.density;
 				f_def.restitution = material.restitution;
 				f_def.friction = material.friction;
+				let category = Category::of(object_id, segment.flags);
+				let (category_bits, mask_bits) = rules.filter(category);
+				f_def.filter.category_bits = category_bits;
+				f_def.filter.mask_bits = mask_bits;
+				f_def.is_sensor = rules.is_ghost(category);
 
 				let transform = segment.transform();
 				let mut b_def = b2::BodyDef::new();
@@ -490,13 +544,16 @@ impl PhysicsSystem {
 		}
 	}
 
-	fn new_world(touched: ContactSet) -> b2::World<AgentData> {
+	fn new_world(touched: ContactSet, obstacle_contacts: ObstacleContacts) -> b2::World<AgentData> {
 		let mut world = b2::World::new(&b2::Vec2 { x: 0.0, y: -0.0 });
-		world.set_contact_listener(Box::new(ContactListener { touched }));
+		world.set_contact_listener(Box::new(ContactListener {
+			touched,
+			obstacle_contacts,
+		}));
 		world
 	}
 
-	pub fn pick(&self, pos: Position) -> Option<Id> {
+	fn pick_in(world: &b2::World<AgentData>, pos: Position) -> Option<Id> {
 		let point = Self::p2v(pos);
 		let eps = PICK_EPS;
 		let aabb = b2::AABB {
@@ -512,7 +569,7 @@ impl PhysicsSystem {
 		let mut result = None;
 		{
 			let mut callback = |body_h: b2::BodyHandle, fixture_h: b2::FixtureHandle| {
-				let body = self.world.body(body_h);
+				let body = world.body(body_h);
 				let fixture = body.fixture(fixture_h);
 				if fixture.test_point(&point) {
 					result = Some(body.user_data().id());
@@ -521,7 +578,7 @@ impl PhysicsSystem {
 					true
 				}
 			};
-			self.world.query_aabb(&mut callback, &aabb);
+			world.query_aabb(&mut callback, &aabb);
 		}
 		result
 	}
@@ -529,10 +586,11 @@ impl PhysicsSystem {
 
 struct ContactListener {
 	touched: ContactSet,
+	obstacle_contacts: ObstacleContacts,
 }
 
-impl b2::ContactListener<AgentData> for ContactListener {
-	fn post_solve(&mut self, ca: ContactAccess<AgentData>, _: &b2::ContactImpulse) {
+impl ContactListener {
+	fn touch(&mut self, ca: &ContactAccess<AgentData>) {
 		let body_a = ca.fixture_a.user_data();
 		let body_b = ca.fixture_b.user_data();
 		if body_a.agent_id != body_b.agent_id {
@@ -541,3 +599,22 @@ impl b2::ContactListener<AgentData> for ContactListener {
 		}
 	}
 }
+
+impl b2::ContactListener<AgentData> for ContactListener {
+	// sensors are never solved, so ghosts only report the beginning of a touch
+	fn begin_contact(&mut self, ca: ContactAccess<AgentData>) {
+		if ca.fixture_a.is_sensor() || ca.fixture_b.is_sensor() {
+			self.touch(&ca);
+		}
+		// joints can't be made while the world steps, they are made after
+		let key_a = *ca.fixture_a.user_data();
+		let key_b = *ca.fixture_b.user_data();
+		match (Obstacle::index_of(&key_a), Obstacle::index_of(&key_b)) {
+			(Some(index), None) => self.obstacle_contacts.borrow_mut().push((key_b.no_bone(), index)),
+			(None, Some(index)) => self.obstacle_contacts.borrow_mut().push((key_a.no_bone(), index)),
+			_ => {}
+		}
+	}
+
+	fn post_solve(&mut self, ca: ContactAccess<AgentData>, _: &b2::ContactImpulse) { self.touch(&ca); }
+}
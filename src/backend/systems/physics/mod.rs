@@ -0,0 +1,773 @@
+#[cfg(feature = "box2d")]
+mod box2d;
+#[cfg(feature = "rapier")]
+mod rapier;
+
+#[cfg(not(any(feature = "box2d", feature = "rapier")))]
+compile_error!("no physics engine, build with the box2d or the rapier feature");
+
+use super::*;
+use constants::*;
+use events::Event;
+use backend::messagebus::{Inbox, Message, PubSub, ReceiveDrain, Whiteboard};
+use backend::obj::*;
+use backend::world;
+use backend::world::agent;
+use backend::world::agent::TypedAgent;
+use backend::world::obstacle::Obstacle;
+use backend::world::segment;
+use backend::world::segment::Intent;
+use backend::world::segment::PilotRotation;
+use cgmath::InnerSpace;
+use core::geometry::Transform;
+use core::geometry::*;
+use num;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+	// the C++ library, through wrapped2d
+	Box2d,
+	// pure Rust, no native dependencies
+	Rapier,
+}
+
+impl Engine {
+	pub fn parse(name: &str) -> Option<Engine> {
+		match name {
+			"box2d" => Some(Engine::Box2d),
+			"rapier" => Some(Engine::Rapier),
+			_ => None,
+		}
+	}
+
+	// whether this build was compiled with the engine
+	pub fn is_available(self) -> bool {
+		match self {
+			Engine::Box2d => cfg!(feature = "box2d"),
+			Engine::Rapier => cfg!(feature = "rapier"),
+		}
+	}
+}
+
+impl Default for Engine {
+	fn default() -> Self {
+		if cfg!(feature = "box2d") {
+			Engine::Box2d
+		} else {
+			Engine::Rapier
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+	// minions, players and enemies
+	Agents,
+	Resources,
+	Projectiles,
+	// the sensor segments of the agents
+	Sensors,
+	// spores and props
+	Debris,
+}
+
+const CATEGORIES: &[Category] = &[
+	Category::Agents,
+	Category::Resources,
+	Category::Projectiles,
+	Category::Sensors,
+	Category::Debris,
+];
+
+// the walls keep the first bit, and collide with everything
+const WALL_BITS: u16 = 0x1;
+
+impl Category {
+	pub fn parse(name: &str) -> Option<Category> {
+		match name {
+			"agents" => Some(Category::Agents),
+			"resources" => Some(Category::Resources),
+			"projectiles" => Some(Category::Projectiles),
+			"sensors" => Some(Category::Sensors),
+			"debris" => Some(Category::Debris),
+			_ => None,
+		}
+	}
+
+	pub fn of(id: Id, flags: segment::Flags) -> Category {
+		match id.type_of() {
+			agent::AgentType::Resource | agent::AgentType::Plant => Category::Resources,
+			agent::AgentType::FriendlyBullet | agent::AgentType::EnemyBullet => Category::Projectiles,
+			agent::AgentType::Spore | agent::AgentType::Prop => Category::Debris,
+			_ if flags.contains(segment::Flags::SENSOR) => Category::Sensors,
+			_ => Category::Agents,
+		}
+	}
+
+	fn bits(self) -> u16 {
+		match self {
+			Category::Agents => 0x2,
+			Category::Resources => 0x4,
+			Category::Projectiles => 0x8,
+			Category::Sensors => 0x10,
+			Category::Debris => 0x20,
+		}
+	}
+}
+
+// Which categories of bodies collide, two pass through each other if either one ignores the other
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CollisionRules {
+	pub ignore: HashMap<Category, Vec<Category>>,
+	// touch without pushing, touches are still noticed when they begin
+	pub ghosts: Vec<Category>,
+}
+
+impl Default for CollisionRules {
+	fn default() -> Self {
+		let mut ignore = HashMap::new();
+		ignore.insert(Category::Debris, vec![Category::Debris]);
+		CollisionRules {
+			ignore,
+			ghosts: Vec::new(),
+		}
+	}
+}
+
+impl CollisionRules {
+	// the category and mask bits of a fixture
+	pub fn filter(&self, category: Category) -> (u16, u16) {
+		let ignored = self.ignore.get(&category).map(|v| v.as_slice()).unwrap_or(&[]);
+		let mask = CATEGORIES
+			.iter()
+			.filter(|c| !ignored.contains(c))
+			.fold(WALL_BITS, |mask, c| mask | c.bits());
+		(category.bits(), mask)
+	}
+
+	pub fn is_ghost(&self, category: Category) -> bool { self.ghosts.contains(&category) }
+
+	pub fn collides(&self, a: Category, b: Category) -> bool {
+		let (bits_a, mask_a) = self.filter(a);
+		let (bits_b, mask_b) = self.filter(b);
+		mask_a & bits_b != 0 && mask_b & bits_a != 0
+	}
+
+	// each pair once
+	pub fn ignored_pairs(&self) -> Vec<(Category, Category)> {
+		let mut pairs = Vec::new();
+		for (i, &a) in CATEGORIES.iter().enumerate() {
+			for &b in &CATEGORIES[i..] {
+				if !self.collides(a, b) {
+					pairs.push((a, b));
+				}
+			}
+		}
+		pairs
+	}
+
+	pub fn set_collides(&mut self, a: Category, b: Category, collides: bool) {
+		for &(from, to) in &[(a, b), (b, a)] {
+			let ignored = self.ignore.entry(from).or_insert_with(Vec::new);
+			ignored.retain(|&c| c != to);
+			if !collides {
+				ignored.push(to);
+			}
+		}
+		self.ignore.retain(|_, ignored| !ignored.is_empty());
+	}
+
+	pub fn set_ghost(&mut self, category: Category, is_ghost: bool) {
+		self.ghosts.retain(|&c| c != category);
+		if is_ghost {
+			self.ghosts.push(category);
+		}
+	}
+}
+
+// The world can be split in vertical bands, each simulated by its own physics world, on its own thread when the
+// engine allows.
+// Bodies in different bands don't collide, so more regions scale better but miss contacts on the boundaries.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PhysicsSettings {
+	pub engine: Engine,
+	pub regions: usize,
+	// how far past a boundary an agent moves before it is handed over, so that it doesn't flip back and forth
+	pub margin: f32,
+	pub collisions: CollisionRules,
+	// the agents quarantined by the watchdog are removed instead of left frozen
+	pub despawn_runaways: bool,
+}
+
+impl Default for PhysicsSettings {
+	fn default() -> Self {
+		PhysicsSettings {
+			engine: Engine::default(),
+			regions: 1,
+			margin: PHYSICS_REGION_MARGIN,
+			collisions: CollisionRules::default(),
+			despawn_runaways: false,
+		}
+	}
+}
+
+// One simulated world, the physics system keeps one for each region
+pub trait PhysicsBackend: Send {
+	// leaves only the walls around the extent, the bodies registered from now on follow the rules
+	fn clear(&mut self, extent: Rect, rules: &CollisionRules);
+	fn register(&mut self, agent: &agent::Agent);
+	fn unregister(&mut self, agent: &agent::Agent);
+	// of every body, to tell those the world forgot about
+	fn keys(&self) -> Vec<agent::Key>;
+	// a body alone, when its agent is gone
+	fn remove(&mut self, key: agent::Key);
+	// the loop around the arena, none for a world that wraps around
+	fn set_walls(&mut self, extent: Option<Rect>);
+	// replaces all the obstacles
+	fn set_obstacles(&mut self, obstacles: &[Obstacle]);
+	// turns the intents of the segments into forces on their bodies
+	fn apply(&mut self, state: &world::AgentState, dt: f32);
+	fn step(&mut self, dt: f32);
+	// whether regions of this engine can be stepped side by side, each on its own thread
+	fn is_thread_safe(&self) -> bool { true }
+	// positions, velocities and what touched what, back into the world, but for the runaway bodies: their agents
+	// are returned instead
+	fn export(&self, world: &mut world::World) -> Vec<Id>;
+	fn pick(&self, position: Position) -> Option<Id>;
+}
+
+#[allow(unused)]
+pub enum BodyUpdate {
+	Transform(Position, f32),
+	Torque(f32),
+	AngularImpulse(f32),
+	// application point and force, in world coordinates
+	Force(Position, Position),
+	LinearImpulse(Position, Position),
+	Frozen(bool),
+}
+
+// a body gone to NaN or spun off by the solver, not worth writing back into the world
+pub fn is_runaway(position: Position, angle: f32, velocity: Position, spin: f32) -> bool {
+	let finite = [position.x, position.y, angle, velocity.x, velocity.y, spin].iter().all(|v| v.is_finite());
+	let speed = PHYSICS_RUNAWAY_SPEED;
+	!finite || velocity.magnitude2() > speed * speed || spin.abs() > PHYSICS_RUNAWAY_SPIN
+}
+
+// the obstacles with a body built for them that are still there as they were, whose bodies can stay
+pub fn kept_obstacles<'a, I>(old: &[Obstacle], new: &[Obstacle], built: I) -> HashSet<usize>
+where I: Iterator<Item = &'a usize> {
+	built
+		.filter(|&&index| match (old.get(index), new.get(index)) {
+			(Some(old), Some(new)) => old.has_same_body(new),
+			_ => false,
+		}).cloned()
+		.collect()
+}
+
+// the maps keep the agents in no particular order: bodies built in the order of their ids come out of the solver the
+// same in every run
+fn in_id_order<'a, I>(agents: I) -> Vec<&'a agent::Agent>
+where I: Iterator<Item = &'a agent::Agent> {
+	let mut agents: Vec<_> = agents.collect();
+	agents.sort_by_key(|agent| agent.id());
+	agents
+}
+
+pub struct BodyState {
+	pub position: Position,
+	pub center: Position,
+	pub velocity: Position,
+	pub mass: f32,
+	pub is_frozen: bool,
+}
+
+// what the agent wants the body of one of its segments to do, worked out the same way by all the engines
+pub fn body_updates<H: Copy>(
+	state: &world::AgentState,
+	key: &agent::Key,
+	body: &BodyState,
+	obstacles: &[Obstacle],
+	dt: f32,
+	handle: H,
+	updates: &mut Vec<(H, BodyUpdate)>,
+)
+{
+	let agent = state.agent(key.agent_id);
+	// frozen agents become static bodies until they are thawed
+	if let Some(is_frozen) = agent.map(|a| a.state.is_frozen()) {
+		if is_frozen != body.is_frozen {
+			updates.push((handle, BodyUpdate::Frozen(is_frozen)));
+		}
+	}
+	if agent.is_none() {
+		return;
+	}
+	for zone in obstacles.iter().filter(|o| !o.solid && o.contains(body.center)) {
+		if zone.drag > 0. {
+			updates.push((handle, BodyUpdate::Force(body.center, body.velocity * (-zone.drag * body.mass))));
+		}
+		if let Some(flow) = zone.flow {
+			updates.push((handle, BodyUpdate::Force(body.center, flow.force(body.velocity, body.mass))));
+		}
+		// cancels the part of the velocity going the wrong way within the step
+		if let Some(direction) = zone.gate.map(|gate| gate.unit()) {
+			let backwards = body.velocity.dot(direction);
+			if backwards < 0. && dt > 0. {
+				updates.push((handle, BodyUpdate::Force(body.center, direction * (-backwards * body.mass / dt))));
+			}
+		}
+	}
+	if let Some(segment) = agent.and_then(|c| c.segment(key.segment_index)) {
+		let center = body.center;
+		let linear_velocity = body.velocity;
+		match segment.state.intent {
+			Intent::Move(force) => updates.push((handle, BodyUpdate::Force(center, force))),
+			Intent::Brake(force) => {
+				let comp = force.dot(linear_velocity);
+				if comp < 0. {
+					updates.push((handle, BodyUpdate::Force(center, force)));
+				}
+			}
+			Intent::PilotTo(force, ref target_angle) => {
+				if let Some(force) = force {
+					let speed2 = linear_velocity.magnitude2();
+					let drag_factor = (1. - speed2 * DRAG_COEFFICIENT).min(1.).max(0.);
+					updates.push((handle, BodyUpdate::Force(center, force * drag_factor)));
+				}
+				match *target_angle {
+					PilotRotation::LookAt(target) => {
+						let look_at_vector = target - center;
+						let target_angle = f32::atan2(-look_at_vector.x, look_at_vector.y);
+						updates.push((handle, BodyUpdate::Transform(body.position, target_angle)));
+					}
+					PilotRotation::Orientation(direction) => {
+						let target_angle = f32::atan2(-direction.x, direction.y);
+						updates.push((handle, BodyUpdate::Transform(body.position, target_angle)));
+					}
+					PilotRotation::Turn(angle) => {
+						updates.push((handle, BodyUpdate::Torque(angle)));
+					}
+					PilotRotation::FromVelocity => {
+						let target_angle = f32::atan2(-linear_velocity.x, linear_velocity.y);
+						updates.push((handle, BodyUpdate::Transform(body.position, target_angle)));
+					}
+					PilotRotation::None => {}
+					//TODO: try physics!
+					//let angle = (*body).angle();
+					//let norm_diff = math::normalize_rad(target_angle - angle);
+					//body_updates.push((h, Torque(norm_diff * COMPASS_SPRING_POWER)))
+					//torques.push((h, norm_diff * COMPASS_SPRING_POWER));
+				}
+			}
+			Intent::RunAway(impulse) => updates.push((handle, BodyUpdate::LinearImpulse(center, impulse * dt))),
+			_ => {}
+		}
+	}
+}
+
+// Where the bodies and the world disagree, as found by the integrity check
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Discrepancy {
+	// a body of an agent that is not in the world any more
+	Orphan(agent::Key),
+	// a body of a segment the agent does not have
+	StaleSegment(agent::Key),
+	// a body in another region than the one its agent lives in
+	Misplaced(agent::Key, usize),
+	// an agent of the world without bodies
+	Unregistered(Id),
+	// an agent with bodies for some of its segments only
+	Incomplete(Id),
+	// a home of an agent that is not in the world any more
+	Homeless(Id),
+}
+
+impl fmt::Display for Discrepancy {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			// the key is packed, its fields are copied out rather than borrowed
+			Discrepancy::Orphan(key) => {
+				write!(f, "body of {}:{} without its agent", { key.agent_id }, { key.segment_index })
+			}
+			Discrepancy::StaleSegment(key) => {
+				write!(f, "body of {}:{} without its segment", { key.agent_id }, { key.segment_index })
+			}
+			Discrepancy::Misplaced(key, region) => write!(f, "body of {} in region {}", { key.agent_id }, region),
+			Discrepancy::Unregistered(id) => write!(f, "{} without bodies", id),
+			Discrepancy::Incomplete(id) => write!(f, "{} without bodies for some of its segments", id),
+			Discrepancy::Homeless(id) => write!(f, "region recorded for {} without the agent", id),
+		}
+	}
+}
+
+pub struct PhysicsSystem {
+	regions: Vec<Box<PhysicsBackend>>,
+	settings: PhysicsSettings,
+	initial_extent: Rect,
+	inbox: Option<Inbox>,
+	// which region each agent's bodies live in
+	homes: HashMap<Id, usize>,
+	// of the obstacles in the regions, none if they need building
+	obstacles_revision: Option<usize>,
+	// as built in the regions
+	walls: Option<Rect>,
+	picked: HashSet<Id>,
+	// where the agents that went through a portal came in and out, with their color
+	teleported: Vec<(Transform, Transform, [f32; 4])>,
+}
+
+impl System for PhysicsSystem {
+	fn attach(&mut self, bus: &mut PubSub) {
+		self.inbox = Some(bus.subscribe(Box::new(|m| match *m {
+			Message::Event(Event::PickMinion(_)) | Message::Event(Event::Quarantine(_)) => true,
+			_ => false,
+		})));
+	}
+
+	fn init(&mut self, world: &world::World) {
+		self.initial_extent = world.extent;
+		self.clear();
+	}
+
+	fn clear(&mut self) {
+		for i in &self.inbox {
+			i.drain();
+		}
+		self.homes.clear();
+		self.picked.clear();
+		self.teleported.clear();
+		self.obstacles_revision = None;
+		self.walls = Some(self.initial_extent);
+		let regions = num::clamp(self.settings.regions, 1, PHYSICS_MAX_REGIONS);
+		let engine = self.settings.engine;
+		let extent = self.initial_extent;
+		let rules = &self.settings.collisions;
+		self.regions = (0..regions)
+			.map(|_| {
+				let mut region = Self::new_backend(engine);
+				region.clear(extent, rules);
+				region
+			}).collect();
+	}
+
+	fn register(&mut self, agent: &world::agent::Agent) {
+		let region = self.band(agent.transform().position.x);
+		self.register_in(region, agent);
+	}
+
+	fn unregister(&mut self, agent: &world::agent::Agent) {
+		if let Some(region) = self.homes.remove(&agent.id()) {
+			self.regions[region].unregister(agent);
+		}
+	}
+
+	fn import(&mut self, world: &world::World) {
+		let messages = match self.inbox {
+			Some(ref drain) => drain.drain(),
+			None => Vec::new(),
+		};
+		self.picked.clear();
+		for message in messages {
+			match message {
+				Message::Event(Event::PickMinion(position)) => {
+					let picked = self.pick(position);
+					if let Some(picked_id) = picked {
+						self.picked.insert(picked_id);
+					}
+				}
+				// built again from where the world last had it, frozen
+				Message::Event(Event::Quarantine(id)) => {
+					if let Some(agent) = world.agent(id) {
+						self.refresh_registration(agent);
+					}
+				}
+				_ => {}
+			}
+		}
+		if self.walls != world.bounds() {
+			for region in &mut self.regions {
+				region.set_walls(world.bounds());
+			}
+			self.walls = world.bounds();
+		}
+		if self.obstacles_revision != Some(world.obstacles_revision()) {
+			for region in &mut self.regions {
+				region.set_obstacles(world.obstacles());
+			}
+			self.obstacles_revision = Some(world.obstacles_revision());
+		}
+		for agent in in_id_order(world.agents(agent::AgentType::Minion).values()) {
+			if agent.state.growth() > 0. {
+				self.refresh_registration(agent)
+			}
+		}
+		let teleported = self.teleport(world);
+		if self.regions.len() > 1 {
+			for agent in in_id_order(world.swarms().values().flat_map(|swarm| swarm.agents().values())) {
+				// the world still has them where they came in
+				if teleported.contains(&agent.id()) {
+					continue;
+				}
+				if let Some(&home) = self.homes.get(&agent.id()) {
+					let x = agent.transform().position.x;
+					let margin = self.settings.margin;
+					if home < self.band(x - margin) || home > self.band(x + margin) {
+						// recreated in the next region from its exported state, velocities included
+						self.unregister(agent);
+						let region = self.band(x);
+						self.register_in(region, agent);
+					}
+				}
+			}
+		}
+	}
+
+	fn update(&mut self, state: &world::AgentState, dt_sec: Seconds) {
+		let dt: f32 = dt_sec.into();
+		for region in &mut self.regions {
+			region.apply(state, dt);
+		}
+		if self.regions.iter().all(|region| region.is_thread_safe()) {
+			// the regions don't share anything, so they can be stepped side by side
+			self.regions.par_iter_mut().for_each(|region| region.step(dt));
+		} else {
+			for region in &mut self.regions {
+				region.step(dt);
+			}
+		}
+	}
+
+	fn export(&self, world: &mut world::World, outbox: &Outbox) {
+		let mut runaways = Vec::new();
+		for region in &self.regions {
+			runaways.extend(region.export(world));
+		}
+		runaways.extend(self.strays(world));
+		runaways.sort();
+		runaways.dedup();
+		// one bad body would drag the others along through the joints and the contacts
+		for id in runaways {
+			if let Some(agent) = world.agent_mut(id) {
+				agent.state.freeze(true);
+				for segment in agent.segments_mut() {
+					segment.motion_to(Motion::default());
+				}
+			}
+			outbox.post(Event::Quarantine(id).into());
+		}
+		for (_, agent) in world.agents_mut(agent::AgentType::Minion).iter_mut() {
+			agent.state.reset_growth()
+		}
+		for id in &self.picked {
+			outbox.post(Event::SelectMinion(*id).into());
+		}
+		for &(ref entrance, ref exit, color) in &self.teleported {
+			outbox.post(Message::NewEmitter(world::particle::Emitter::for_teleport(entrance.clone(), color)));
+			outbox.post(Message::NewEmitter(world::particle::Emitter::for_teleport(exit.clone(), color)));
+		}
+	}
+}
+
+impl Default for PhysicsSystem {
+	fn default() -> Self {
+		PhysicsSystem {
+			inbox: None,
+			initial_extent: Rect::default(),
+			regions: Vec::new(),
+			settings: PhysicsSettings::default(),
+			homes: HashMap::with_capacity(5000),
+			obstacles_revision: None,
+			walls: None,
+			picked: HashSet::with_capacity(100),
+			teleported: Vec::new(),
+		}
+	}
+}
+
+impl PhysicsSystem {
+	pub fn settings(&self) -> &PhysicsSettings { &self.settings }
+
+	// applies from the next clear, or right away with repartition
+	pub fn set_settings(&mut self, settings: PhysicsSettings) { self.settings = settings; }
+
+	// builds the regions again and hands every agent in the world over to them
+	pub fn repartition(&mut self, world: &world::World) {
+		self.clear();
+		for agent in in_id_order(world.swarms().values().flat_map(|swarm| swarm.agents().values())) {
+			self.register(agent);
+		}
+	}
+
+	// cross-checks the bodies of every region against the agents of the world, and with repair removes the bodies
+	// left behind and builds again those missing. Minions still growing are left alone, their bodies are built
+	// again on the next step anyway
+	pub fn check_integrity(&mut self, world: &world::World, repair: bool) -> Vec<Discrepancy> {
+		let mut found = Vec::new();
+		let mut registered: HashMap<Id, usize> = HashMap::new();
+		for (index, region) in self.regions.iter().enumerate() {
+			for key in region.keys() {
+				let agent = match world.agent(key.agent_id) {
+					Some(agent) => agent,
+					None => {
+						found.push((index, Discrepancy::Orphan(key)));
+						continue;
+					}
+				};
+				if agent.state.growth() > 0. {
+					continue;
+				}
+				if agent.segments().iter().all(|segment| segment.index != key.segment_index) {
+					found.push((index, Discrepancy::StaleSegment(key)));
+				} else if self.homes.get(&{ key.agent_id }).map_or(false, |&home| home != index) {
+					found.push((index, Discrepancy::Misplaced(key, index)));
+				} else {
+					*registered.entry(key.agent_id).or_insert(0) += 1;
+				}
+			}
+		}
+		for id in self.homes.keys() {
+			if world.agent(*id).is_none() {
+				found.push((0, Discrepancy::Homeless(*id)));
+			}
+		}
+		for agent in world.swarms().values().flat_map(|swarm| swarm.agents().values()) {
+			if agent.state.growth() > 0. {
+				continue;
+			}
+			match registered.get(&agent.id()) {
+				None => found.push((0, Discrepancy::Unregistered(agent.id()))),
+				Some(&bodies) if bodies < agent.segments().len() => {
+					found.push((0, Discrepancy::Incomplete(agent.id())))
+				}
+				Some(_) => {}
+			}
+		}
+		if repair {
+			for &(index, discrepancy) in &found {
+				match discrepancy {
+					Discrepancy::Orphan(key) | Discrepancy::StaleSegment(key) | Discrepancy::Misplaced(key, _) => {
+						self.regions[index].remove(key)
+					}
+					Discrepancy::Homeless(id) => {
+						self.homes.remove(&id);
+					}
+					Discrepancy::Unregistered(id) | Discrepancy::Incomplete(id) => {
+						if let Some(agent) = world.agent(id) {
+							self.refresh_registration(agent);
+						}
+					}
+				}
+			}
+		}
+		found.into_iter().map(|(_, discrepancy)| discrepancy).collect()
+	}
+
+	fn new_backend(engine: Engine) -> Box<PhysicsBackend> {
+		match engine {
+			#[cfg(feature = "box2d")]
+			Engine::Box2d => Box::new(box2d::Box2dBackend::default()),
+			#[cfg(feature = "rapier")]
+			Engine::Rapier => Box::new(rapier::RapierBackend::default()),
+			#[allow(unreachable_patterns)]
+			_ => {
+				warn!("This build has no {:?} physics, using {:?}", engine, Engine::default());
+				Self::new_backend(Engine::default())
+			}
+		}
+	}
+
+	// the region of the band across the initial extent that contains x, clamped at the sides
+	fn band(&self, x: f32) -> usize {
+		let extent = self.initial_extent;
+		let width = extent.max.x - extent.min.x;
+		let last = self.regions.len().max(1) - 1;
+		if last == 0 || width <= 0. {
+			return 0;
+		}
+		let band = ((x - extent.min.x) / width * (last + 1) as f32).floor();
+		num::clamp(band, 0., last as f32) as usize
+	}
+
+	fn register_in(&mut self, region: usize, agent: &world::agent::Agent) {
+		self.regions[region].register(agent);
+		self.homes.insert(agent.id(), region);
+	}
+
+	// recreates the bodies of the agents in a portal at the other end, and of those off the edge of a toroidal
+	// world on the opposite side, velocities included
+	fn teleport(&mut self, world: &world::World) -> HashSet<Id> {
+		self.teleported.clear();
+		let mut teleported = HashSet::new();
+		let wrap = world.wrap();
+		if world.portals().is_empty() && wrap.is_none() {
+			return teleported;
+		}
+		for agent in in_id_order(world.swarms().values().flat_map(|swarm| swarm.agents().values())) {
+			if !self.homes.contains_key(&agent.id()) {
+				continue;
+			}
+			let entrance = agent.transform().clone();
+			let position = entrance.position;
+			let (exit, through_portal) = match wrap.filter(|extent| !extent.contains(position)) {
+				Some(extent) => (extent.wrap(position), false),
+				None => match world.portals().iter().filter_map(|portal| portal.exit(position)).next() {
+					Some(exit) => (exit, true),
+					None => continue,
+				},
+			};
+			let mut moved = agent.clone();
+			for segment in moved.segments_mut() {
+				segment.transform.position += exit - position;
+			}
+			self.unregister(agent);
+			self.register(&moved);
+			// going across the seam should go unnoticed
+			if through_portal {
+				let color = agent.segment(0).map(|segment| segment.livery.albedo).unwrap_or(COLOR_WHITE);
+				self.teleported.push((entrance, moved.transform().clone(), color));
+			}
+			teleported.insert(agent.id());
+		}
+		teleported
+	}
+
+	// the agents far out of the walls, or of the extent of a world that wraps around
+	fn strays(&self, world: &world::World) -> Vec<Id> {
+		let extent = match world.bounds().or_else(|| world.wrap()) {
+			Some(extent) => extent,
+			None => return Vec::new(),
+		};
+		let margin = PHYSICS_RUNAWAY_MARGIN;
+		let is_far = |p: Position| {
+			p.x < extent.min.x - margin || p.x > extent.max.x + margin || p.y < extent.min.y - margin
+				|| p.y > extent.max.y + margin
+		};
+		world
+			.swarms()
+			.values()
+			.flat_map(|swarm| swarm.agents().values())
+			.filter(|agent| !agent.state.is_frozen() && is_far(agent.transform().position))
+			.map(|agent| agent.id())
+			.collect()
+	}
+
+	fn refresh_registration(&mut self, agent: &world::agent::Agent) {
+		let region = self.homes.get(&agent.id()).cloned();
+		self.unregister(agent);
+		match region {
+			Some(region) => self.register_in(region, agent),
+			None => self.register(agent),
+		}
+	}
+
+	pub fn pick(&self, pos: Position) -> Option<Id> { self.regions.iter().filter_map(|region| region.pick(pos)).next() }
+}
@@ -0,0 +1,484 @@
+use super::*;
+use backend::obj;
+use backend::obj::Identified;
+use backend::obj::Transformable;
+use backend::world;
+use backend::world::agent;
+use backend::world::obstacle::Obstacle;
+use backend::world::segment;
+use core::geometry::Motion;
+use core::geometry::Position;
+use core::geometry::Rect;
+use core::geometry::Transform;
+use rapier2d::dynamics::{
+	BallJoint, CCDSolver, FixedJoint, IntegrationParameters, IslandManager, JointHandle, JointSet, RigidBodyBuilder,
+	RigidBodyHandle, RigidBodySet, RigidBodyType,
+};
+use rapier2d::geometry::{BroadPhase, ColliderBuilder, ColliderHandle, ColliderSet, InteractionGroups, NarrowPhase};
+use rapier2d::na;
+use rapier2d::pipeline::{PhysicsPipeline, QueryPipeline};
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+pub struct RapierBackend {
+	pipeline: PhysicsPipeline,
+	params: IntegrationParameters,
+	islands: IslandManager,
+	broad_phase: BroadPhase,
+	narrow_phase: NarrowPhase,
+	ccd_solver: CCDSolver,
+	query: QueryPipeline,
+	bodies: RigidBodySet,
+	colliders: ColliderSet,
+	joints: JointSet,
+	rules: CollisionRules,
+	handles: HashMap<agent::Key, RigidBodyHandle>,
+	// the owner of each collider, bones included
+	keys: HashMap<ColliderHandle, agent::Key>,
+	walls: Option<RigidBodyHandle>,
+	obstacles: Vec<Obstacle>,
+	obstacle_handles: HashMap<usize, RigidBodyHandle>,
+	// segments touching an obstacle in the last step, only new contacts stick
+	touching: HashSet<(agent::Key, usize)>,
+	stuck: Vec<Stuck>,
+}
+
+// a segment held by a sticky obstacle until the time runs out
+struct Stuck {
+	key: agent::Key,
+	obstacle: usize,
+	joint: JointHandle,
+	remaining: f32,
+}
+
+struct JointRef<'a> {
+	handle: RigidBodyHandle,
+	growing_radius: f32,
+	rest_angle: f32,
+	mesh: &'a obj::Mesh,
+	flags: segment::Flags,
+	attachment: Option<segment::Attachment>,
+}
+
+impl PhysicsBackend for RapierBackend {
+	fn clear(&mut self, extent: Rect, rules: &CollisionRules) {
+		*self = Self::default();
+		self.rules = rules.clone();
+		self.set_walls(Some(extent));
+	}
+
+	fn set_walls(&mut self, extent: Option<Rect>) {
+		if let Some(handle) = self.walls.take() {
+			self.remove_body(handle);
+		}
+		if let Some(extent) = extent {
+			let handle = self.bodies.insert(RigidBodyBuilder::new_static().build());
+			let corners = [extent.bottom_left(), extent.bottom_right(), extent.top_right(), extent.top_left()];
+			let mut vertices: Vec<_> = corners.iter().map(|&p| Self::p2p(p, 1.)).collect();
+			// closes the loop
+			vertices.push(Self::p2p(corners[0], 1.));
+			let walls = ColliderBuilder::polyline(vertices, None).build();
+			let collider = self.colliders.insert_with_parent(walls, handle, &mut self.bodies);
+			self.keys.insert(collider, agent::Key::with_id(0xFFFF_FFFFusize));
+			self.walls = Some(handle);
+		}
+	}
+
+	fn register(&mut self, agent: &world::agent::Agent) {
+		let object_id = agent.id();
+		let mut joint_refs = Vec::new();
+		for (segment_index, segment) in agent.segments().iter().enumerate() {
+			let material = segment.material();
+			let transform = segment.transform();
+			let body = RigidBodyBuilder::new_dynamic()
+				.translation(Self::p2v(transform.position))
+				.rotation(transform.angle)
+				.linvel(Self::p2v(segment.motion.velocity))
+				.angvel(segment.motion.spin)
+				.linear_damping(material.linear_damping)
+				.angular_damping(material.angular_damping)
+				.build();
+			let handle = self.bodies.insert(body);
+			let refs = agent::Key::with_segment(object_id, segment_index as u8);
+			self.handles.insert(refs, handle);
+			let category = Category::of(object_id, segment.flags);
+			let (category_bits, mask_bits) = self.rules.filter(category);
+			for (bone_refs, shape) in Self::shapes(object_id, segment_index, segment.state.maturity(), segment.mesh()) {
+				let collider = shape
+					.density(material.density)
+					.restitution(material.restitution)
+					.friction(material.friction)
+					.collision_groups(InteractionGroups::new(u32::from(category_bits), u32::from(mask_bits)))
+					.sensor(self.rules.is_ghost(category))
+					.build();
+				let collider = self.colliders.insert_with_parent(collider, handle, &mut self.bodies);
+				self.keys.insert(collider, bone_refs);
+			}
+			joint_refs.push(JointRef {
+				handle,
+				growing_radius: segment.growing_radius(),
+				rest_angle: segment.rest_angle,
+				mesh: segment.mesh(),
+				flags: segment.flags,
+				attachment: segment.attached_to,
+			});
+		}
+		self.build_joints(&joint_refs);
+	}
+
+	fn unregister(&mut self, agent: &world::agent::Agent) {
+		let object_id = agent.id();
+		for segment in agent.segments() {
+			self.remove(agent::Key::with_segment(object_id, segment.index));
+		}
+	}
+
+	fn keys(&self) -> Vec<agent::Key> { self.handles.keys().cloned().collect() }
+
+	fn remove(&mut self, key: agent::Key) {
+		self.release(|stuck| stuck.key == key);
+		if let Some(handle) = self.handles.remove(&key) {
+			self.remove_body(handle);
+		}
+	}
+
+	// only the obstacles that changed are built again, the segments stuck to the others stay stuck
+	fn set_obstacles(&mut self, obstacles: &[Obstacle]) {
+		let kept = kept_obstacles(&self.obstacles, obstacles, self.obstacle_handles.keys());
+		self.release(|stuck| !kept.contains(&stuck.obstacle) || obstacles[stuck.obstacle].sticky <= 0.);
+		let changed: Vec<_> = self.obstacle_handles.keys().filter(|index| !kept.contains(index)).cloned().collect();
+		for index in changed {
+			if let Some(handle) = self.obstacle_handles.remove(&index) {
+				self.remove_body(handle);
+			}
+		}
+		self.touching.retain(|&(_, index)| kept.contains(&index));
+		self.obstacles = obstacles.to_vec();
+		for (index, obstacle) in obstacles
+			.iter()
+			.enumerate()
+			.filter(|&(index, o)| o.is_blocking() && !kept.contains(&index))
+		{
+			let extent = &obstacle.extent;
+			let body = RigidBodyBuilder::new_static().translation(Self::p2v((extent.min + extent.max) * 0.5)).build();
+			let handle = self.bodies.insert(body);
+			let collider =
+				ColliderBuilder::cuboid((extent.max.x - extent.min.x) * 0.5, (extent.max.y - extent.min.y) * 0.5)
+					.friction(obstacle.friction)
+					.restitution(obstacle.restitution)
+					.build();
+			let collider = self.colliders.insert_with_parent(collider, handle, &mut self.bodies);
+			self.keys.insert(collider, Obstacle::key(index));
+			self.obstacle_handles.insert(index, handle);
+		}
+	}
+
+	fn apply(&mut self, state: &world::AgentState, dt: f32) {
+		let mut dynamic_updates = Vec::new();
+		for (key, &handle) in &self.handles {
+			if let Some(body) = self.bodies.get(handle) {
+				let center = body.position() * body.mass_properties().local_com;
+				let body_state = BodyState {
+					position: Self::v2p(body.translation()),
+					center: Position::new(center.x, center.y),
+					velocity: Self::v2p(body.linvel()),
+					mass: body.mass(),
+					is_frozen: body.body_type() == RigidBodyType::Static,
+				};
+				body_updates(state, key, &body_state, &self.obstacles, dt, handle, &mut dynamic_updates);
+			}
+		}
+
+		for (handle, update) in dynamic_updates {
+			if let Some(b) = self.bodies.get_mut(handle) {
+				match update {
+					BodyUpdate::Torque(torque) => b.apply_torque(torque, true),
+					BodyUpdate::AngularImpulse(impulse) => b.apply_torque_impulse(impulse, true),
+					BodyUpdate::Force(application_point, force) => {
+						b.apply_force_at_point(Self::p2v(force), Self::p2p(application_point, 1.), true)
+					}
+					BodyUpdate::LinearImpulse(application_point, impulse) => {
+						b.apply_impulse_at_point(Self::p2v(impulse), Self::p2p(application_point, 1.), true)
+					}
+					BodyUpdate::Transform(translation, rotation) => {
+						b.set_position(na::Isometry2::new(Self::p2v(translation), rotation), true)
+					}
+					BodyUpdate::Frozen(is_frozen) => b.set_body_type(if is_frozen {
+						RigidBodyType::Static
+					} else {
+						RigidBodyType::Dynamic
+					}),
+				}
+			}
+		}
+	}
+
+	fn step(&mut self, dt: f32) {
+		self.params.dt = dt;
+		self.pipeline.step(
+			&na::Vector2::zeros(),
+			&self.params,
+			&mut self.islands,
+			&mut self.broad_phase,
+			&mut self.narrow_phase,
+			&mut self.bodies,
+			&mut self.colliders,
+			&mut self.joints,
+			&mut self.ccd_solver,
+			&(),
+			&(),
+		);
+		self.query.update(&self.islands, &self.bodies, &self.colliders);
+		self.update_stuck(dt);
+	}
+
+	fn export(&self, world: &mut world::World) -> Vec<Id> {
+		let mut touched = HashMap::new();
+		let mut runaways = Vec::new();
+		let contacts = self
+			.narrow_phase
+			.contact_pairs()
+			.filter(|pair| pair.has_any_active_contact)
+			.map(|pair| (pair.collider1, pair.collider2));
+		// ghosts are sensors, they intersect instead of touching
+		let intersections = self
+			.narrow_phase
+			.intersection_pairs()
+			.filter(|&(_, _, intersecting)| intersecting)
+			.map(|(a, b, _)| (a, b));
+		for (collider_a, collider_b) in contacts.chain(intersections) {
+			if let (Some(a), Some(b)) = (self.keys.get(&collider_a), self.keys.get(&collider_b)) {
+				if a.agent_id != b.agent_id {
+					touched.insert(a.no_bone(), b.no_bone());
+					touched.insert(b.no_bone(), a.no_bone());
+				}
+			}
+		}
+		for (key, &handle) in &self.handles {
+			if let Some(body) = self.bodies.get(handle) {
+				let position = body.translation();
+				let angle = body.rotation().angle();
+				let velocity = body.linvel();
+				let spin = body.angvel();
+
+				if is_runaway(Position::new(position.x, position.y), angle, Self::v2p(velocity), spin) {
+					runaways.push(key.agent_id);
+					continue;
+				}
+				if let Some(agent) = world.agent_mut(key.agent_id) {
+					if let Some(segment) = agent.segment_mut(key.segment_index) {
+						segment.transform_to(Transform::from_components(position.x, position.y, angle));
+						segment.motion_to(Motion::from_components(velocity.x, velocity.y, spin));
+						segment.state.last_touched = touched.get(key).cloned();
+					}
+				}
+			}
+		}
+		runaways
+	}
+
+	fn pick(&self, pos: Position) -> Option<Id> {
+		let mut result = None;
+		let keys = &self.keys;
+		self.query.intersections_with_point(
+			&self.colliders,
+			&Self::p2p(pos, 1.),
+			InteractionGroups::all(),
+			None,
+			|collider| {
+				result = keys.get(&collider).map(|key| key.id());
+				false
+			},
+		);
+		result
+	}
+}
+
+impl Default for RapierBackend {
+	fn default() -> Self {
+		RapierBackend {
+			pipeline: PhysicsPipeline::new(),
+			params: IntegrationParameters::default(),
+			islands: IslandManager::new(),
+			broad_phase: BroadPhase::new(),
+			narrow_phase: NarrowPhase::new(),
+			ccd_solver: CCDSolver::new(),
+			query: QueryPipeline::new(),
+			bodies: RigidBodySet::new(),
+			colliders: ColliderSet::new(),
+			joints: JointSet::new(),
+			rules: CollisionRules::default(),
+			handles: HashMap::with_capacity(5000),
+			keys: HashMap::with_capacity(5000),
+			walls: None,
+			obstacles: Vec::new(),
+			obstacle_handles: HashMap::new(),
+			touching: HashSet::new(),
+			stuck: Vec::new(),
+		}
+	}
+}
+
+impl RapierBackend {
+	fn p2v(p: Position) -> na::Vector2<f32> { na::Vector2::new(p.x, p.y) }
+
+	fn p2p(p: Position, radius: f32) -> na::Point2<f32> { na::Point2::new(p.x * radius, p.y * radius) }
+
+	fn v2p(v: &na::Vector2<f32>) -> Position { Position::new(v.x, v.y) }
+
+	// with its colliders and joints
+	fn remove_body(&mut self, handle: RigidBodyHandle) {
+		if let Some(body) = self.bodies.get(handle) {
+			for collider in body.colliders() {
+				self.keys.remove(collider);
+			}
+		}
+		self.bodies.remove(handle, &mut self.islands, &mut self.colliders, &mut self.joints);
+	}
+
+	fn release<F>(&mut self, f: F)
+	where F: Fn(&Stuck) -> bool {
+		let (joints, islands, bodies) = (&mut self.joints, &mut self.islands, &mut self.bodies);
+		self.stuck.retain(|stuck| {
+			if f(stuck) {
+				joints.remove(stuck.joint, islands, bodies, true);
+				false
+			} else {
+				true
+			}
+		});
+	}
+
+	// fixes the segments that started touching a sticky obstacle to it where they are, and lets go of the expired ones
+	fn update_stuck(&mut self, dt: f32) {
+		for stuck in &mut self.stuck {
+			stuck.remaining -= dt;
+		}
+		self.release(|stuck| stuck.remaining <= 0.);
+		let keys = &self.keys;
+		let touching: HashSet<_> = self
+			.narrow_phase
+			.contact_pairs()
+			.filter(|pair| pair.has_any_active_contact)
+			.filter_map(|pair| match (keys.get(&pair.collider1), keys.get(&pair.collider2)) {
+				(Some(a), Some(b)) => match (Obstacle::index_of(a), Obstacle::index_of(b)) {
+					(Some(index), None) => Some((b.no_bone(), index)),
+					(None, Some(index)) => Some((a.no_bone(), index)),
+					_ => None,
+				},
+				_ => None,
+			}).collect();
+		let started: Vec<_> = touching.difference(&self.touching).cloned().collect();
+		self.touching = touching;
+		for (key, index) in started {
+			let sticky = self.obstacles.get(index).map(|o| o.sticky).unwrap_or(0.);
+			if sticky <= 0. || self.stuck.iter().any(|stuck| stuck.key == key) {
+				continue;
+			}
+			if let (Some(&body), Some(&anchor)) = (self.handles.get(&key), self.obstacle_handles.get(&index)) {
+				let (position, angle) = match self.bodies.get(body) {
+					Some(body) => (Self::v2p(body.translation()), body.rotation().angle()),
+					None => continue,
+				};
+				let anchor_position = match self.bodies.get(anchor) {
+					Some(anchor) => Self::v2p(anchor.translation()),
+					None => continue,
+				};
+				let joint = FixedJoint::new(
+					na::Isometry2::new(Self::p2v(position - anchor_position), angle),
+					na::Isometry2::identity(),
+				);
+				let joint = self.joints.insert(anchor, body, joint);
+				self.stuck.push(Stuck {
+					key,
+					obstacle: index,
+					joint,
+					remaining: sticky,
+				});
+			}
+		}
+	}
+
+	// the same shapes box2d gets, a convex piece for each bone of a star
+	fn shapes(
+		object_id: obj::Id,
+		segment_index: usize,
+		maturity: f32,
+		mesh: &obj::Mesh,
+	) -> Vec<(agent::Key, ColliderBuilder)>
+	{
+		let refs = agent::Key::with_segment(object_id, segment_index as u8);
+		// degenerate hulls become tiny circles, as with box2d
+		let hull = |vertices: &[na::Point2<f32>], grown_radius: f32| {
+			ColliderBuilder::convex_hull(vertices).unwrap_or_else(|| ColliderBuilder::ball(grown_radius))
+		};
+		match mesh.shape {
+			obj::Shape::Ball { radius } => vec![(refs, ColliderBuilder::ball(radius * maturity))],
+			obj::Shape::Box { radius, ratio } => {
+				vec![(refs, ColliderBuilder::cuboid(radius * ratio, radius * maturity))]
+			}
+			obj::Shape::Poly { radius, n, .. } => {
+				let grown_radius = radius * maturity;
+				let p = &mesh.vertices;
+				let offset = if n < 0 { 1 } else { 0 };
+				let vertices: Vec<_> =
+					(0..n.abs()).map(|i| Self::p2p(p[2 * i as usize + offset], grown_radius)).collect();
+				vec![(refs, hull(&vertices, grown_radius))]
+			}
+			obj::Shape::Star { radius, n, .. } => {
+				let grown_radius = radius * maturity;
+				if grown_radius < 0.05 * f32::from(n) {
+					vec![(refs, ColliderBuilder::ball(grown_radius))]
+				} else {
+					let p = &mesh.vertices;
+					(0..n)
+						.map(|i| {
+							let i1 = (i * 2 + 1) as usize;
+							let i2 = (i * 2) as usize;
+							let i3 = ((i * 2 + (n * 2) - 1) % (n * 2)) as usize;
+							let quad_vertices = [
+								na::Point2::origin(),
+								Self::p2p(p[i1], grown_radius),
+								Self::p2p(p[i2], grown_radius),
+								Self::p2p(p[i3], grown_radius),
+							];
+							let refs = agent::Key::with_bone(object_id, segment_index as u8, i as u8);
+							(refs, hull(&quad_vertices, grown_radius))
+						}).collect()
+				}
+			}
+			obj::Shape::Triangle { radius, .. } => {
+				let grown_radius = radius * maturity;
+				let p = &mesh.vertices;
+				let tri_vertices = [
+					Self::p2p(p[0], grown_radius),
+					Self::p2p(p[1], grown_radius),
+					Self::p2p(p[2], grown_radius),
+				];
+				vec![(refs, hull(&tri_vertices, grown_radius))]
+			}
+		}
+	}
+
+	// hinges for the joints and welds for the rest, as with box2d but without the angle limits and the springs
+	fn build_joints(&mut self, joint_refs: &[JointRef]) {
+		for joint_ref in joint_refs {
+			if let Some(attachment) = joint_ref.attachment {
+				let upstream = &joint_refs[attachment.index as usize];
+				let angle_delta = joint_ref.rest_angle - upstream.rest_angle;
+				let a = upstream.mesh.vertices[attachment.attachment_point as usize] * upstream.growing_radius;
+				let b = joint_ref.mesh.vertices[0] * joint_ref.growing_radius;
+				if joint_ref.flags.contains(segment::Flags::JOINT) {
+					let joint = BallJoint::new(Self::p2p(a, 1.), Self::p2p(b, 1.));
+					self.joints.insert(upstream.handle, joint_ref.handle, joint);
+				} else {
+					let joint = FixedJoint::new(
+						na::Isometry2::new(Self::p2v(a), angle_delta),
+						na::Isometry2::new(Self::p2v(b), 0.),
+					);
+					self.joints.insert(upstream.handle, joint_ref.handle, joint);
+				}
+			}
+		}
+	}
+}
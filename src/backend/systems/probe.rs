@@ -0,0 +1,68 @@
+use super::*;
+use backend::messagebus::Outbox;
+use backend::obj::Identified;
+use backend::obj::Transformable;
+use backend::world;
+use backend::world::agent;
+use backend::world::probe;
+use backend::world::segment;
+use backend::world::AgentState;
+use std::collections::HashMap;
+
+pub struct ProbeSystem {
+	dt: Seconds,
+	samples: Vec<probe::Sample>,
+}
+
+impl System for ProbeSystem {
+	fn clear(&mut self) { self.samples.clear(); }
+
+	fn import(&mut self, world: &world::World) {
+		let minions = world.agents(agent::AgentType::Minion);
+		let resources = world.agents(agent::AgentType::Resource);
+		// a resource can be touched by more than one mouth, count it once
+		let eaten: HashMap<_, _> = minions
+			.values()
+			.filter(|a| a.state.is_active())
+			.flat_map(|a| a.segments.iter())
+			.filter(|s| s.flags.contains(segment::Flags::MOUTH))
+			.filter_map(|s| s.state.last_touched)
+			.filter_map(|key| resources.get(&key.id()))
+			.map(|resource| (resource.id(), resource.transform().position))
+			.collect();
+
+		self.samples = world
+			.probes()
+			.iter()
+			.map(|probe| {
+				let mut sample = probe::Sample::default();
+				for agent in minions.values().filter(|a| a.state.is_active()) {
+					if probe.contains(agent.transform().position) {
+						sample.agent_count += 1;
+						sample.total_energy += agent.state.energy();
+					}
+				}
+				sample.resources_eaten = eaten.values().filter(|&&position| probe.contains(position)).count();
+				sample
+			}).collect();
+	}
+
+	fn update(&mut self, _: &AgentState, dt: Seconds) { self.dt = dt; }
+
+	fn export(&self, world: &mut world::World, _outbox: &Outbox) {
+		let seconds = world.seconds().get();
+		for (probe, sample) in world.probes_mut().iter_mut().zip(self.samples.iter()) {
+			probe.stats.update(sample, self.dt);
+			probe.record(seconds);
+		}
+	}
+}
+
+impl Default for ProbeSystem {
+	fn default() -> Self {
+		ProbeSystem {
+			dt: Seconds::new(1. / 60.),
+			samples: Vec::new(),
+		}
+	}
+}
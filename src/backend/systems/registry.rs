@@ -0,0 +1,165 @@
+use backend::messagebus::{Outbox, PubSub};
+use backend::systems;
+use backend::world;
+use core::clock::Seconds;
+use core::geometry::Position;
+use rayon::prelude::*;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+#[derive(Default)]
+struct SendSystem<T>
+where T: systems::System {
+	ptr: Arc<RwLock<T>>,
+}
+
+impl<T> SendSystem<T>
+where T: systems::System
+{
+	fn boxed(ptr: Arc<RwLock<T>>) -> Box<Self> { Box::new(SendSystem { ptr }) }
+}
+
+impl<T> systems::System for SendSystem<T>
+where T: systems::System
+{
+	fn attach(&mut self, bus: &mut PubSub) { self.ptr.write().unwrap().attach(bus) }
+	fn init(&mut self, world: &world::World) { self.ptr.write().unwrap().init(world) }
+	fn clear(&mut self) { self.ptr.write().unwrap().clear() }
+	fn register(&mut self, agent: &world::agent::Agent) { self.ptr.write().unwrap().register(agent) }
+	fn unregister(&mut self, agent: &world::agent::Agent) { self.ptr.write().unwrap().unregister(agent) }
+
+	fn step(&mut self, world: &world::World, dt: Seconds) { self.ptr.write().unwrap().step(world, dt) }
+	fn apply(&self, world: &mut world::World, outbox: &Outbox) { self.ptr.read().unwrap().apply(world, outbox) }
+}
+
+// unsafe?
+unsafe impl<T> Send for SendSystem<T> where T: systems::System {}
+
+// batch mode leaves out the systems that only matter to a viewer
+#[derive(Copy, Clone)]
+pub enum SystemMode {
+	Interactive,
+	Batch,
+}
+
+impl Default for SystemMode {
+	fn default() -> Self { SystemMode::Interactive }
+}
+
+// all the systems of the simulation, stepped in parallel and applied to the world in turn
+#[derive(Default)]
+pub struct Systems {
+	mode: SystemMode,
+	physics: Arc<RwLock<systems::PhysicsSystem>>,
+	animation: Arc<RwLock<systems::AnimationSystem>>,
+	game: Arc<RwLock<systems::GameSystem>>,
+	ai: Arc<RwLock<systems::AiSystem>>,
+	alife: Arc<RwLock<systems::AlifeSystem>>,
+	particle: Arc<RwLock<systems::ParticleSystem>>,
+	probe: Arc<RwLock<systems::ProbeSystem>>,
+}
+
+impl Systems {
+	pub fn set_mode(&mut self, mode: SystemMode) { self.mode = mode; }
+
+	// nobody is watching in batch mode
+	pub fn set_ai_focus(&mut self, focus: Option<(Position, f32)>) {
+		let focus = match self.mode {
+			SystemMode::Interactive => focus,
+			SystemMode::Batch => None,
+		};
+		self.ai.write().unwrap().set_focus(focus);
+	}
+
+	pub fn set_physics(&mut self, settings: systems::PhysicsSettings) {
+		self.physics.write().unwrap().set_settings(settings);
+	}
+
+	pub fn repartition(&mut self, settings: systems::PhysicsSettings, world: &world::World) {
+		let mut physics = self.physics.write().unwrap();
+		physics.set_settings(settings);
+		physics.repartition(world);
+	}
+
+	pub fn check_physics(&mut self, world: &world::World, repair: bool) -> Vec<systems::PhysicsDiscrepancy> {
+		self.physics.write().unwrap().check_integrity(world, repair)
+	}
+
+	fn systems(&mut self) -> Vec<Box<(systems::System + Send)>> {
+		match self.mode {
+			SystemMode::Interactive => vec![
+				SendSystem::boxed(self.physics.clone()),
+				SendSystem::boxed(self.animation.clone()),
+				SendSystem::boxed(self.particle.clone()),
+				SendSystem::boxed(self.game.clone()),
+				SendSystem::boxed(self.ai.clone()),
+				SendSystem::boxed(self.alife.clone()),
+				SendSystem::boxed(self.probe.clone()),
+			],
+			SystemMode::Batch => vec![
+				SendSystem::boxed(self.physics.clone()),
+				SendSystem::boxed(self.game.clone()),
+				SendSystem::boxed(self.ai.clone()),
+				SendSystem::boxed(self.alife.clone()),
+				SendSystem::boxed(self.probe.clone()),
+			],
+		}
+	}
+
+	pub fn unregister(&mut self, agents: &[world::agent::Agent]) {
+		if !agents.is_empty() {
+			self.systems().par_iter_mut().for_each(|system| {
+				for agent in agents {
+					system.unregister(agent)
+				}
+			})
+		}
+	}
+
+	pub fn register(&mut self, agents: &[world::agent::Agent]) {
+		if !agents.is_empty() {
+			self.systems().par_iter_mut().for_each(|system| {
+				for agent in agents {
+					system.register(&agent)
+				}
+			})
+		}
+	}
+
+	pub fn init(&mut self, world: &world::World) {
+		for system in &mut self.systems() {
+			system.init(world);
+		}
+	}
+
+	pub fn clear(&mut self) {
+		for system in &mut self.systems() {
+			system.clear();
+		}
+	}
+
+	pub fn attach(&mut self, bus: &mut PubSub) {
+		for system in &mut self.systems() {
+			system.attach(bus);
+		}
+	}
+
+	pub fn for_each_read(
+		&mut self,
+		world: &mut world::World,
+		outbox: &Outbox,
+		apply: &(Fn(&mut systems::System, &mut world::World, &Outbox) + Sync),
+	)
+	{
+		self.systems().iter_mut().for_each(|r| apply(&mut (**r), world, outbox))
+	}
+
+	pub fn for_each_par_write(
+		&mut self,
+		world: &world::World,
+		apply: &(Fn(&mut systems::System, &world::World) + Sync),
+	)
+	{
+		self.systems().par_iter_mut().for_each(|r| apply(&mut (**r), world))
+	}
+}
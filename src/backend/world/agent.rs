@@ -1,4 +1,4 @@
-use app::constants::*;
+use constants::*;
 use backend::obj;
 use backend::obj::*;
 use backend::world::gen::Dna;
@@ -13,6 +13,7 @@ use num::FromPrimitive;
 use std::collections::HashMap;
 use std::f32;
 use std::fmt;
+use std::mem;
 
 #[repr(packed)]
 #[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
@@ -82,6 +83,8 @@ enum_from_primitive! {
 		EnemyBullet,
 		Resource,
 		Prop,
+		// rooted, living off the light
+		Plant,
 	}
 }
 
@@ -97,6 +100,7 @@ impl fmt::Display for AgentType {
 			AgentType::EnemyBullet => "EnemyBullet",
 			AgentType::Resource => "Resource",
 			AgentType::Prop => "Prop",
+			AgentType::Plant => "Plant",
 		};
 		f.write_str(text)
 	}
@@ -112,6 +116,7 @@ const AGENT_TYPES: &[AgentType] = &[
 	AgentType::EnemyBullet,
 	AgentType::Resource,
 	AgentType::Prop,
+	AgentType::Plant,
 ];
 
 impl AgentType {
@@ -218,9 +223,18 @@ bitflags! {
 		const DEAD       = 0x1;
 		const ACTIVE     = 0x2;
 		const SELECTED   = 0x1000;
+		const FROZEN     = 0x2000;
+		const DORMANT    = 0x4000;
 	}
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LifeStage {
+	Juvenile,
+	Adult,
+	Senescent,
+}
+
 #[derive(Clone, Debug)]
 pub struct Limits {
 	max_energy: f32,
@@ -260,6 +274,12 @@ pub struct State {
 	limits: Limits,
 	foreign_dna: Option<Dna>,
 	trajectory: util::History<Position>,
+	// steering from the user while possessed, x turns and y thrusts or brakes
+	puppet: Option<Position>,
+	// seconds left before recovering from the disease, zero when healthy
+	infection: f32,
+	// the last response of the brain, reused on the ticks it is not evaluated
+	brain_output: Option<[f32; 4]>,
 }
 
 impl State {
@@ -322,6 +342,34 @@ impl State {
 
 	pub fn selected(&self) -> bool { self.flags.contains(Flags::SELECTED) }
 
+	pub fn freeze(&mut self, frozen: bool) { self.flags.set(Flags::FROZEN, frozen) }
+
+	pub fn is_frozen(&self) -> bool { self.flags.contains(Flags::FROZEN) }
+
+	pub fn set_dormant(&mut self, dormant: bool) { self.flags.set(Flags::DORMANT, dormant) }
+
+	pub fn is_dormant(&self) -> bool { self.flags.contains(Flags::DORMANT) }
+
+	pub fn infect(&mut self, duration: f32) { self.infection = duration }
+
+	pub fn is_infected(&self) -> bool { self.infection > 0. }
+
+	pub fn infection(&self) -> f32 { self.infection }
+
+	pub fn recover(&mut self, dt: f32) { self.infection = (self.infection - dt).max(0.) }
+
+	pub fn possess(&mut self, possessed: bool) { self.puppet = if possessed { Some(origin()) } else { None } }
+
+	pub fn is_possessed(&self) -> bool { self.puppet.is_some() }
+
+	pub fn puppet(&self) -> Option<Position> { self.puppet }
+
+	pub fn steer_puppet(&mut self, steering: Position) {
+		if self.puppet.is_some() {
+			self.puppet = Some(steering)
+		}
+	}
+
 	pub fn die(&mut self) {
 		self.flags |= Flags::DEAD;
 		self.flags -= Flags::ACTIVE;
@@ -337,6 +385,10 @@ impl State {
 
 	pub fn target(&self) -> &Option<Id> { &self.target }
 
+	pub fn brain_output(&self) -> Option<[f32; 4]> { self.brain_output }
+
+	pub fn set_brain_output(&mut self, output: [f32; 4]) { self.brain_output = Some(output); }
+
 	pub fn retarget(&mut self, target: Option<Id>, position: Position) {
 		self.target = target;
 		self.target_position = position;
@@ -357,6 +409,7 @@ pub struct Agent {
 	brain: Brain,
 	dna: Dna,
 	gender: u8,
+	resistance: f32,
 	pub state: State,
 	pub segments: Box<[Segment]>,
 }
@@ -379,11 +432,46 @@ impl Agent {
 	#[inline]
 	pub fn dna(&self) -> &Dna { &self.dna }
 
+	// roughly what it takes in memory, in bytes, the brain aside
+	pub fn footprint(&self) -> usize {
+		mem::size_of::<Agent>()
+			+ self.segments.len() * mem::size_of::<Segment>()
+			+ self.dna.len()
+			+ self.state.foreign_dna.as_ref().map_or(0, |dna| dna.len())
+			+ self.state.trajectory.capacity() * mem::size_of::<Position>()
+	}
+
+	pub fn age(&self) -> Seconds { self.segments[0].state.age_seconds() }
+
+	pub fn life_stage(&self) -> LifeStage {
+		if self.segments[0].state.maturity() < 1. {
+			LifeStage::Juvenile
+		} else if self.age().get() < SecondsValue::from(MINION_SENESCENCE_AGE) {
+			LifeStage::Adult
+		} else {
+			LifeStage::Senescent
+		}
+	}
+
+	// how much faster than an adult an old agent burns its energy, and much slower if dormant
+	pub fn metabolism(&self) -> f32 {
+		let age = self.age().get() as f32;
+		let senescence = 1. + (age - MINION_SENESCENCE_AGE).max(0.) / MINION_SENESCENCE_SPAN;
+		if self.state.is_dormant() {
+			senescence * DORMANT_METABOLISM
+		} else {
+			senescence
+		}
+	}
+
 	pub fn id(&self) -> Id { self.id }
 
 	#[inline]
 	pub fn gender(&self) -> u8 { self.gender }
 
+	#[inline]
+	pub fn resistance(&self) -> f32 { self.resistance }
+
 	#[inline]
 	pub fn segments(&self) -> &[Segment] { &self.segments }
 
@@ -411,7 +499,9 @@ impl Agent {
 			.reset_charge(PLAYER_CHARGE_INITIAL_VALUE, PLAYER_CHARGE_REST_VALUE)
 	}
 
-	pub fn new(id: Id, gender: u8, brain: &Brain, dna: &Dna, segments: Box<[Segment]>, timer: &Timer) -> Self {
+	pub fn new(
+		id: Id, gender: u8, resistance: f32, brain: &Brain, dna: &Dna, segments: Box<[Segment]>, timer: &Timer,
+	) -> Self {
 		const SCALE: f32 = 100.;
 		let max_energy = SCALE * segments
 			.iter()
@@ -430,9 +520,13 @@ impl Agent {
 				limits: Limits { max_energy },
 				foreign_dna: None,
 				trajectory: util::History::new(600),
+				puppet: None,
+				infection: 0.,
+				brain_output: None,
 			},
 			brain: brain.clone(),
 			gender,
+			resistance,
 			dna: dna.clone(),
 			segments,
 		}
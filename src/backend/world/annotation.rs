@@ -0,0 +1,19 @@
+use core::geometry::Position;
+
+#[derive(Clone, Debug)]
+pub struct Annotation {
+	pub position: Position,
+	// the arrow points from the label to the target, if any
+	pub target: Option<Position>,
+	pub text: String,
+}
+
+impl Annotation {
+	pub fn new(position: Position, target: Option<Position>, text: &str) -> Self {
+		Annotation {
+			position,
+			target,
+			text: text.to_owned(),
+		}
+	}
+}
@@ -0,0 +1,281 @@
+use super::*;
+use super::far_field::{Aggregate, Rates};
+use rand::Rng;
+use rand::SeedableRng;
+use rand::XorShiftRng;
+
+pub type ChunkKey = (i32, i32);
+
+// which chunks of an unbounded world have been populated, and which are on disk, with what became of them since
+#[derive(Clone, Debug, Default)]
+pub struct Chunks {
+	pub generated: HashSet<ChunkKey>,
+	pub frozen: HashSet<ChunkKey>,
+	pub far: HashMap<ChunkKey, Aggregate>,
+	far_stepped: SecondsValue,
+}
+
+impl Chunks {
+	// the frozen chunks live on in numbers, once in a while rather than every frame, and in steps of the same length:
+	// after a long frame they catch up a few steps at most
+	pub fn step_far_field(&mut self, rates: &Rates, now: SecondsValue) {
+		if now < self.far_stepped {
+			self.far_stepped = now;
+		}
+		let steps = ((now - self.far_stepped) / FAR_FIELD_STEP) as usize;
+		if steps == 0 {
+			return;
+		}
+		for _ in 0..steps.min(FAR_FIELD_MAX_STEPS) {
+			for aggregate in self.far.values_mut() {
+				aggregate.step(rates, FAR_FIELD_STEP as f32);
+			}
+		}
+		self.far_stepped = if steps > FAR_FIELD_MAX_STEPS {
+			now
+		} else {
+			self.far_stepped + steps as SecondsValue * FAR_FIELD_STEP
+		};
+	}
+
+	// the time the far field was last stepped isn't saved, a world loaded or cleared carries on from now
+	pub fn restart_far_field(&mut self, now: SecondsValue) { self.far_stepped = now; }
+
+	pub fn far_field(&self) -> Aggregate {
+		let mut total = Aggregate::default();
+		for aggregate in self.far.values() {
+			total.merge(aggregate);
+		}
+		total
+	}
+}
+
+pub fn key_of(p: Position) -> ChunkKey { ((p.x / CHUNK_SIZE).floor() as i32, (p.y / CHUNK_SIZE).floor() as i32) }
+
+// in chunks, along the furthest axis
+fn distance(a: ChunkKey, b: ChunkKey) -> i32 { (a.0 - b.0).abs().max((a.1 - b.1).abs()) }
+
+fn file_name(key: ChunkKey) -> String { format!("chunk_{}_{}.json", key.0, key.1) }
+
+impl World {
+	// the chunks around the focus and under the probes, which are simulated one agent at a time
+	fn active_chunks(&self, focus: Position) -> HashSet<ChunkKey> {
+		let center = key_of(focus);
+		let mut active = HashSet::new();
+		for x in center.0 - CHUNK_ACTIVE_RADIUS..=center.0 + CHUNK_ACTIVE_RADIUS {
+			for y in center.1 - CHUNK_ACTIVE_RADIUS..=center.1 + CHUNK_ACTIVE_RADIUS {
+				active.insert((x, y));
+			}
+		}
+		for probe in &self.probes {
+			let (min, max) = (key_of(probe.extent.min), key_of(probe.extent.max));
+			for x in min.0..=max.0 {
+				for y in min.1..=max.1 {
+					active.insert((x, y));
+				}
+			}
+		}
+		active
+	}
+
+	// keeps the active chunks simulated: thaws those coming back from disk, populates the new ones
+	// and freezes the agents that got too far, returning them for the systems to let go
+	pub fn stream_chunks(&mut self, focus: Position, containing_dir: &path::Path) -> io::Result<Box<[Agent]>> {
+		let active = self.active_chunks(focus);
+		for &key in &active {
+			if self.chunks.frozen.remove(&key) {
+				self.thaw_chunk(key, containing_dir)?;
+			} else if self.chunks.generated.insert(key) {
+				self.generate_chunk(key);
+			}
+		}
+
+		// a chunk or so of slack, so that agents on the border don't go back and forth to disk
+		let mut leaving: HashMap<ChunkKey, Vec<obj::Id>> = HashMap::new();
+		for swarm in self.swarms.values().filter(|swarm| swarm.agent_type() != AgentType::Player) {
+			for (&id, agent) in swarm.agents().iter().filter(|&(_, agent)| agent.state.is_alive()) {
+				let key = key_of(agent.transform().position);
+				if active.iter().all(|&near| distance(key, near) > CHUNK_FREEZE_SLACK) {
+					leaving.entry(key).or_insert_with(Vec::new).push(id);
+				}
+			}
+		}
+		let mut frozen = Vec::new();
+		if !leaving.is_empty() {
+			fs::create_dir_all(containing_dir)?;
+		}
+		for (key, ids) in leaving {
+			let agents: Vec<Agent> = ids.iter()
+				.filter_map(|&id| self.swarms.get_mut(&id.type_of()).and_then(|swarm| swarm.agents_mut().remove(&id)))
+				.collect();
+			let file_path = containing_dir.join(file_name(key));
+			// whatever is there from an earlier world doesn't belong to this one
+			if self.chunks.frozen.insert(key) && file_path.exists() {
+				fs::remove_file(&file_path)?;
+			}
+			persist::Serializer::save_chunk(&file_path, &agents)?;
+			self.chunks.far.entry(key).or_insert_with(Aggregate::default).merge(&aggregate_of(&agents));
+			frozen.extend(agents);
+		}
+		Ok(frozen.into_boxed_slice())
+	}
+
+	// brings every frozen chunk back, for a world that stops being unbounded and is all simulated from then on: those
+	// that fail to load stay frozen
+	pub fn thaw_all_chunks(&mut self, containing_dir: &path::Path) -> io::Result<()> {
+		let frozen: Vec<_> = self.chunks.frozen.iter().cloned().collect();
+		for key in frozen {
+			self.thaw_chunk(key, containing_dir)?;
+			self.chunks.frozen.remove(&key);
+		}
+		Ok(())
+	}
+
+	fn thaw_chunk(&mut self, key: ChunkKey, containing_dir: &path::Path) -> io::Result<()> {
+		let file_path = containing_dir.join(file_name(key));
+		let mut ids = persist::Serializer::load_chunk(&file_path, self)?;
+		if let Some(aggregate) = self.chunks.far.remove(&key) {
+			ids = self.reinstate(key, ids, &aggregate);
+		}
+		for id in ids {
+			self.register(id);
+		}
+		fs::remove_file(file_path)
+	}
+
+	// brings the agents of a chunk in line with its aggregate before they are let in: the extra die unseen,
+	// the missing ones are bred from the survivors, and the minions get the energy the chunk drifted to
+	fn reinstate(&mut self, key: ChunkKey, ids: Vec<obj::Id>, aggregate: &Aggregate) -> Vec<obj::Id> {
+		let seed = [key.0 as u32 ^ CHUNK_SEED, key.1 as u32, CHUNK_SEED, self.seconds().get() as u32];
+		let mut rng: XorShiftRng = SeedableRng::from_seed(seed);
+		let (mut minions, mut resources, mut others) = (Vec::new(), Vec::new(), Vec::new());
+		for id in ids {
+			match id.type_of() {
+				AgentType::Minion => minions.push(id),
+				AgentType::Resource => resources.push(id),
+				_ => others.push(id),
+			}
+		}
+		rng.shuffle(&mut minions);
+		rng.shuffle(&mut resources);
+		let minion_count = aggregate.minions.round() as usize;
+		let resource_count = aggregate.resources.round() as usize;
+		for (ids, count) in vec![(&mut minions, minion_count), (&mut resources, resource_count)] {
+			let kept = count.min(ids.len());
+			for id in ids.split_off(kept) {
+				self.swarm_mut(&id.type_of()).agents_mut().remove(&id);
+			}
+		}
+
+		let origin = Position::new(key.0 as f32, key.1 as f32) * CHUNK_SIZE;
+		let parents: Vec<gen::Dna> = minions.iter().filter_map(|&id| self.agent(id).map(|a| a.dna().clone())).collect();
+		while !parents.is_empty() && minions.len() < minion_count {
+			let dna = &parents[rng.gen_range(0, parents.len())];
+			let mut genome = gen::Genome::copy_from(dna).mutate_by(&mut rng, self.mating.mutation);
+			let p = origin + Position::new(rng.next_f32(), rng.next_f32()) * CHUNK_SIZE;
+			minions.push(self.new_minion_with(p, Motion::default(), &mut genome));
+		}
+		while resources.len() < resource_count {
+			let p = origin + Position::new(rng.next_f32(), rng.next_f32()) * CHUNK_SIZE;
+			resources.push(self.new_resource(Transform::from_position(p), Motion::default()));
+		}
+
+		let energies: Vec<f32> = minions.iter().filter_map(|&id| self.agent(id).map(|a| a.state.energy())).collect();
+		let mean = energies.iter().sum::<f32>() / energies.len().max(1) as f32;
+		if mean > 0. {
+			let scale = aggregate.energy / mean;
+			for &id in &minions {
+				if let Some(minion) = self.agent_mut(id) {
+					let energy = minion.state.energy();
+					if scale > 1. {
+						minion.state.absorb(energy * (scale - 1.));
+					} else {
+						minion.state.consume(energy * (1. - scale));
+					}
+				}
+			}
+		}
+		minions.into_iter().chain(resources).chain(others).collect()
+	}
+
+	// the same chunk gets the same resources whenever the world is generated again
+	fn generate_chunk(&mut self, key: ChunkKey) {
+		let seed = [key.0 as u32 ^ CHUNK_SEED, key.1 as u32, CHUNK_SEED, 1];
+		let mut rng: XorShiftRng = SeedableRng::from_seed(seed);
+		let origin = Position::new(key.0 as f32, key.1 as f32) * CHUNK_SIZE;
+		for _ in 0..CHUNK_RESOURCES {
+			let p = origin + Position::new(rng.next_f32(), rng.next_f32()) * CHUNK_SIZE;
+			self.new_resource(Transform::from_position(p), Motion::default());
+		}
+	}
+}
+
+// the living minions and resources among the agents leaving for disk
+fn aggregate_of(agents: &[Agent]) -> Aggregate {
+	let minions: Vec<&Agent> = agents.iter().filter(|a| a.id().type_of() == AgentType::Minion).collect();
+	let energy = minions.iter().map(|a| a.state.energy()).sum::<f32>();
+	Aggregate {
+		minions: minions.len() as f32,
+		resources: agents.iter().filter(|a| a.id().type_of() == AgentType::Resource).count() as f32,
+		energy: energy / minions.len().max(1) as f32,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const RATES: Rates = Rates {
+		births: 0.,
+		deaths: 0.,
+		energy: 0.,
+	};
+
+	fn chunks(now: SecondsValue) -> Chunks {
+		let mut chunks = Chunks::default();
+		chunks.far.insert((0, 0), Aggregate::default());
+		chunks.restart_far_field(now);
+		chunks
+	}
+
+	// resources regrow by the same amount each step, which shows how many steps were taken
+	fn steps(chunks: &Chunks) -> usize {
+		(chunks.far[&(0, 0)].resources / (FAR_FIELD_REGROWTH * FAR_FIELD_STEP as f32)).round() as usize
+	}
+
+	#[test]
+	fn the_far_field_waits_for_a_whole_step() {
+		let mut chunks = chunks(0.);
+		chunks.step_far_field(&RATES, FAR_FIELD_STEP * 0.5);
+		assert_eq!(steps(&chunks), 0);
+		chunks.step_far_field(&RATES, FAR_FIELD_STEP);
+		assert_eq!(steps(&chunks), 1);
+	}
+
+	#[test]
+	fn the_far_field_catches_up_in_steps() {
+		let mut chunks = chunks(0.);
+		chunks.step_far_field(&RATES, FAR_FIELD_STEP * 3.5);
+		assert_eq!(steps(&chunks), 3);
+		// the half step left over counts towards the next one
+		chunks.step_far_field(&RATES, FAR_FIELD_STEP * 4.);
+		assert_eq!(steps(&chunks), 4);
+	}
+
+	#[test]
+	fn the_far_field_catches_up_a_few_steps_at_most() {
+		let mut chunks = chunks(0.);
+		chunks.step_far_field(&RATES, 3600.);
+		assert_eq!(steps(&chunks), FAR_FIELD_MAX_STEPS);
+		chunks.step_far_field(&RATES, 3600. + FAR_FIELD_STEP * 0.5);
+		assert_eq!(steps(&chunks), FAR_FIELD_MAX_STEPS);
+	}
+
+	#[test]
+	fn a_restarted_far_field_carries_on_from_then() {
+		let mut chunks = chunks(0.);
+		chunks.restart_far_field(3600.);
+		chunks.step_far_field(&RATES, 3600. + FAR_FIELD_STEP);
+		assert_eq!(steps(&chunks), 1);
+	}
+}
@@ -0,0 +1,106 @@
+use super::*;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// FNV-1a over the fields written one by one in little endian, so that the digest stays the same across builds,
+// platforms and compiler versions, unlike the std hashers
+struct Fnv1a(u64);
+
+impl Fnv1a {
+	fn new() -> Self { Fnv1a(FNV_OFFSET_BASIS) }
+
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 ^= u64::from(*byte);
+			self.0 = self.0.wrapping_mul(FNV_PRIME);
+		}
+	}
+
+	fn write_u32(&mut self, value: u32) {
+		self.write(&[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8])
+	}
+
+	fn write_u64(&mut self, value: u64) {
+		self.write_u32(value as u32);
+		self.write_u32((value >> 32) as u32);
+	}
+
+	fn write_bool(&mut self, value: bool) { self.write(&[value as u8]) }
+
+	fn write_f32(&mut self, value: f32) { self.write_u32(value.to_bits()) }
+
+	fn write_position(&mut self, p: Position) {
+		self.write_f32(p.x);
+		self.write_f32(p.y);
+	}
+
+	// the length first, so that two byte strings side by side can't be mistaken for two others
+	fn write_bytes(&mut self, bytes: &[u8]) {
+		self.write_u64(bytes.len() as u64);
+		self.write(bytes);
+	}
+
+	fn finish(&self) -> u64 { self.0 }
+}
+
+impl World {
+	// the same for the same state in any run of any build: agents are visited by type and id, never in the order of
+	// the maps, and particles and other visuals are left out
+	pub fn digest(&self) -> u64 {
+		let mut state = Fnv1a::new();
+		state.write_u64(self.clock.seconds().get().to_bits());
+		for agent_type in AgentType::all() {
+			let agents = self.agents(*agent_type);
+			let mut ids: Vec<_> = agents.keys().cloned().collect();
+			ids.sort();
+			state.write_u64(ids.len() as u64);
+			for id in ids {
+				let agent = &agents[&id];
+				state.write_u64(id as u64);
+				state.write_bytes(agent.dna());
+				state.write_u32(agent.state.flags().bits());
+				state.write_f32(agent.state.energy());
+				state.write_u64(agent.segments().len() as u64);
+				for segment in agent.segments() {
+					state.write_position(segment.transform.position);
+					state.write_f32(segment.transform.angle);
+					state.write_position(segment.motion.velocity);
+					state.write_f32(segment.motion.spin);
+				}
+			}
+		}
+		state.write_u64(self.obstacles.len() as u64);
+		for obstacle in &self.obstacles {
+			state.write_position(obstacle.extent.min);
+			state.write_position(obstacle.extent.max);
+			state.write_bool(obstacle.is_blocking());
+		}
+		state.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Fnv1a;
+
+	fn fnv1a(bytes: &[u8]) -> u64 {
+		let mut state = Fnv1a::new();
+		state.write(bytes);
+		state.finish()
+	}
+
+	#[test]
+	fn matches_the_reference_vectors() {
+		assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+		assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+		assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+	}
+
+	#[test]
+	fn encodes_integers_in_little_endian() {
+		let mut state = Fnv1a::new();
+		state.write_u64(0x0102_0304_0506_0708);
+		assert_eq!(state.finish(), fnv1a(&[8, 7, 6, 5, 4, 3, 2, 1]));
+	}
+}
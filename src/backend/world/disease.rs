@@ -0,0 +1,26 @@
+// A parasite passed on between touching minions, draining their energy until it runs its course
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Disease {
+	// chance per second of catching it while touching an infected minion, before resistance
+	pub transmission: f32,
+	// energy drained per second while infected
+	pub drain: f32,
+	// seconds before an infected minion recovers
+	pub duration: f32,
+}
+
+impl Default for Disease {
+	fn default() -> Self {
+		Disease {
+			transmission: 2.,
+			drain: 5.,
+			duration: 30.,
+		}
+	}
+}
+
+impl Disease {
+	// resistance in [0, 1] is inherited, fully resistant minions never catch it
+	pub fn chance(&self, resistance: f32, dt: f32) -> f32 { self.transmission * dt * (1. - resistance) }
+}
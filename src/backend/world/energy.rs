@@ -0,0 +1,121 @@
+use constants::*;
+use core::clock::SecondsValue;
+
+// Energy moved between the parts of the ecosystem, by where it came from and where it went
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EnergyFlow {
+	// into resources, from the feeders, the terrain and new chunks
+	pub sown: f32,
+	// into resources, from the bodies of dead minions
+	pub recycled: f32,
+	// from resources into minions
+	pub eaten: f32,
+	// into minions hatched from spores
+	pub born: f32,
+	// into minions, the fitness for surviving and moving
+	pub rewarded: f32,
+	// into minions, the fitness for the minions they killed
+	pub preyed: f32,
+	// out of minions
+	pub metabolism: f32,
+	pub growth: f32,
+	pub spawning: f32,
+	pub disease: f32,
+	// left in minions when they die
+	pub died: f32,
+	// left in resources that expire uneaten
+	pub wasted: f32,
+}
+
+impl EnergyFlow {
+	pub fn add(&mut self, other: &EnergyFlow) {
+		self.sown += other.sown;
+		self.recycled += other.recycled;
+		self.eaten += other.eaten;
+		self.born += other.born;
+		self.rewarded += other.rewarded;
+		self.preyed += other.preyed;
+		self.metabolism += other.metabolism;
+		self.growth += other.growth;
+		self.spawning += other.spawning;
+		self.disease += other.disease;
+		self.died += other.died;
+		self.wasted += other.wasted;
+	}
+
+	pub fn scale(&self, factor: f32) -> EnergyFlow {
+		EnergyFlow {
+			sown: self.sown * factor,
+			recycled: self.recycled * factor,
+			eaten: self.eaten * factor,
+			born: self.born * factor,
+			rewarded: self.rewarded * factor,
+			preyed: self.preyed * factor,
+			metabolism: self.metabolism * factor,
+			growth: self.growth * factor,
+			spawning: self.spawning * factor,
+			disease: self.disease * factor,
+			died: self.died * factor,
+			wasted: self.wasted * factor,
+		}
+	}
+
+	// in the order they happen, from the feeders to the decay
+	pub fn entries(&self) -> [(&'static str, f32); 12] {
+		[
+			("sown", self.sown),
+			("recycled", self.recycled),
+			("eaten", self.eaten),
+			("born", self.born),
+			("rewarded", self.rewarded),
+			("preyed", self.preyed),
+			("metabolism", self.metabolism),
+			("growth", self.growth),
+			("spawning", self.spawning),
+			("disease", self.disease),
+			("died", self.died),
+			("wasted", self.wasted),
+		]
+	}
+
+	// what the resources gained, less what was eaten or wasted
+	pub fn resource_balance(&self) -> f32 { self.sown + self.recycled - self.eaten - self.wasted }
+
+	// what the minions gained, less what they spent or took to the grave
+	pub fn minion_balance(&self) -> f32 {
+		self.eaten + self.born + self.rewarded + self.preyed
+			- self.metabolism
+			- self.growth
+			- self.spawning
+			- self.disease
+			- self.died
+	}
+}
+
+// The flows summed as they happen, and per second over the last period once it is over
+#[derive(Clone, Debug, Default)]
+pub struct EnergyLedger {
+	current: EnergyFlow,
+	started: SecondsValue,
+	rates: EnergyFlow,
+}
+
+impl EnergyLedger {
+	pub fn record(&mut self, flow: &EnergyFlow) { self.current.add(flow) }
+
+	pub fn current_mut(&mut self) -> &mut EnergyFlow { &mut self.current }
+
+	pub fn tick(&mut self, now: SecondsValue) {
+		let elapsed = now - self.started;
+		if elapsed < 0. {
+			// the clock went back with a world loaded
+			self.started = now;
+		} else if elapsed >= ENERGY_FLOW_PERIOD {
+			self.rates = self.current.scale((1. / elapsed) as f32);
+			self.current = EnergyFlow::default();
+			self.started = now;
+		}
+	}
+
+	pub fn rates(&self) -> &EnergyFlow { &self.rates }
+}
@@ -0,0 +1,166 @@
+use constants::*;
+use core::clock::SecondsValue;
+
+// Per minion and per second, as measured on the minions simulated one by one
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rates {
+	pub births: f32,
+	pub deaths: f32,
+	// the mean energy of the simulated minions, what the far ones drift to
+	pub energy: f32,
+}
+
+// The births and deaths of the simulated minions over the current period, and the rates over the last one
+#[derive(Clone, Debug, Default)]
+pub struct Demography {
+	births: usize,
+	deaths: usize,
+	started: SecondsValue,
+	rates: Rates,
+}
+
+impl Demography {
+	pub fn born(&mut self) { self.births += 1 }
+
+	pub fn died(&mut self, count: usize) { self.deaths += count }
+
+	pub fn is_due(&self, now: SecondsValue) -> bool { now < self.started || now - self.started >= ENERGY_FLOW_PERIOD }
+
+	// the rates of an empty world are kept from when it had minions
+	pub fn close(&mut self, now: SecondsValue, population: usize, mean_energy: f32) {
+		let elapsed = now - self.started;
+		if elapsed > 0. && population > 0 {
+			let per_minion = 1. / (elapsed as f32 * population as f32);
+			self.rates = Rates {
+				births: self.births as f32 * per_minion,
+				deaths: self.deaths as f32 * per_minion,
+				energy: mean_energy,
+			};
+		}
+		self.births = 0;
+		self.deaths = 0;
+		self.started = now;
+	}
+
+	pub fn rates(&self) -> &Rates { &self.rates }
+}
+
+// What became of a frozen chunk of an unbounded world, in numbers rather than agents
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Aggregate {
+	pub minions: f32,
+	pub resources: f32,
+	pub energy: f32,
+}
+
+impl Aggregate {
+	// more agents frozen in the same chunk
+	pub fn merge(&mut self, other: &Aggregate) {
+		let minions = self.minions + other.minions;
+		if minions > 0. {
+			self.energy = (self.energy * self.minions + other.energy * other.minions) / minions;
+		}
+		self.minions = minions;
+		self.resources += other.resources;
+	}
+
+	// the minions are born and die at the measured rates, with fewer births as the resources run short, and the
+	// resources grow back towards what a new chunk has
+	pub fn step(&mut self, rates: &Rates, dt: f32) {
+		let food = (self.resources / CHUNK_RESOURCES as f32).min(1.);
+		let births = self.minions * rates.births * food * dt;
+		let deaths = self.minions * rates.deaths * dt;
+		let meals = (self.minions * FAR_FIELD_MEALS * dt).min(self.resources);
+		self.minions = (self.minions + births - deaths).max(0.).min(FAR_FIELD_MAX_MINIONS);
+		self.resources = (self.resources - meals + FAR_FIELD_REGROWTH * dt).max(0.).min(CHUNK_RESOURCES as f32);
+		self.energy += (rates.energy - self.energy) * (dt / FAR_FIELD_ENERGY_TIME).min(1.);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn aggregate(minions: f32, resources: f32, energy: f32) -> Aggregate {
+		Aggregate {
+			minions,
+			resources,
+			energy,
+		}
+	}
+
+	#[test]
+	fn merging_weighs_the_energy_by_the_minions() {
+		let mut a = aggregate(1., 2., 10.);
+		a.merge(&aggregate(3., 4., 30.));
+		assert_eq!(a, aggregate(4., 6., 25.));
+	}
+
+	#[test]
+	fn merging_no_minions_keeps_the_energy() {
+		let mut a = aggregate(0., 2., 10.);
+		a.merge(&aggregate(0., 4., 30.));
+		assert_eq!(a, aggregate(0., 6., 10.));
+	}
+
+	#[test]
+	fn minions_are_born_and_die_at_the_rates() {
+		let rates = Rates {
+			births: 0.5,
+			deaths: 0.25,
+			energy: 0.,
+		};
+		let mut a = aggregate(10., CHUNK_RESOURCES as f32, 0.);
+		a.step(&rates, 1.);
+		assert_eq!(a.minions, 10. + 5. - 2.5);
+	}
+
+	#[test]
+	fn fewer_minions_are_born_as_the_resources_run_short() {
+		let rates = Rates {
+			births: 1.,
+			deaths: 0.,
+			energy: 0.,
+		};
+		let mut a = aggregate(10., CHUNK_RESOURCES as f32 * 0.5, 0.);
+		a.step(&rates, 0.1);
+		assert_eq!(a.minions, 10. + 0.5);
+	}
+
+	#[test]
+	fn the_minions_and_resources_stay_in_bounds() {
+		let rates = Rates {
+			births: 100.,
+			deaths: 0.,
+			energy: 0.,
+		};
+		let mut a = aggregate(FAR_FIELD_MAX_MINIONS, CHUNK_RESOURCES as f32, 0.);
+		a.step(&rates, 1.);
+		assert_eq!(a.minions, FAR_FIELD_MAX_MINIONS);
+		assert!(a.resources >= 0. && a.resources <= CHUNK_RESOURCES as f32);
+
+		let dying = Rates {
+			births: 0.,
+			deaths: 100.,
+			energy: 0.,
+		};
+		let mut b = aggregate(10., 0., 0.);
+		b.step(&dying, 1.);
+		assert_eq!(b.minions, 0.);
+		assert_eq!(b.resources, FAR_FIELD_REGROWTH);
+	}
+
+	#[test]
+	fn the_energy_drifts_to_that_of_the_minions_in_view() {
+		let rates = Rates {
+			births: 0.,
+			deaths: 0.,
+			energy: 100.,
+		};
+		let mut a = aggregate(1., CHUNK_RESOURCES as f32, 0.);
+		a.step(&rates, FAR_FIELD_ENERGY_TIME * 0.5);
+		assert_eq!(a.energy, 50.);
+		a.step(&rates, FAR_FIELD_ENERGY_TIME * 10.);
+		assert_eq!(a.energy, 100.);
+	}
+}
@@ -0,0 +1,50 @@
+// The energy rewarded to a minion is a weighted sum of these terms, and energy is what buys offspring
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Fitness {
+	// multiplier of the energy absorbed from food
+	pub food: f32,
+	// energy per second alive
+	pub survival: f32,
+	// energy per unit of distance traveled
+	pub distance: f32,
+	// energy per minion that dies while bitten
+	pub kills: f32,
+}
+
+impl Default for Fitness {
+	fn default() -> Self {
+		Fitness {
+			food: 1.,
+			survival: 0.,
+			distance: 0.,
+			kills: 0.,
+		}
+	}
+}
+
+pub const TERMS: &[&str] = &["food", "survival", "distance", "kills"];
+
+impl Fitness {
+	pub fn weight(&self, term: &str) -> Option<f32> {
+		match term {
+			"food" => Some(self.food),
+			"survival" => Some(self.survival),
+			"distance" => Some(self.distance),
+			"kills" => Some(self.kills),
+			_ => None,
+		}
+	}
+
+	pub fn set_weight(&mut self, term: &str, weight: f32) -> bool {
+		let target = match term {
+			"food" => &mut self.food,
+			"survival" => &mut self.survival,
+			"distance" => &mut self.distance,
+			"kills" => &mut self.kills,
+			_ => return false,
+		};
+		*target = weight;
+		true
+	}
+}
@@ -6,6 +6,7 @@ use std::cmp;
 use rand;
 use rand::Rng;
 use backend::obj::*;
+use constants::*;
 use std::slice::Iter;
 use serialize::base64::{self, ToBase64, FromBase64};
 
@@ -21,6 +22,22 @@ fn split_bit(p: usize) -> (usize, u8) {
 	(p >> 3, (p & 0x7) as u8)
 }
 
+// fraction of differing bits, the tail of the longer dna counts as all different
+pub fn distance(a: &[u8], b: &[u8]) -> f32 {
+	let total = bit_count(cmp::max(a.len(), b.len()));
+	if total == 0 {
+		return 0.;
+	}
+	let common: u32 = a.iter().zip(b.iter()).map(|(x, y)| (x ^ y).count_ones()).sum();
+	let tail = bit_count(cmp::max(a.len(), b.len()) - cmp::min(a.len(), b.len()));
+	(common as usize + tail) as f32 / total as f32
+}
+
+// the genes at the start of the genome shape the body, so relatives mostly share them
+pub fn species_key(dna: &[u8]) -> u32 {
+	dna.iter().take(SPECIES_GENES).fold(2_166_136_261u32, |h, b| (h ^ u32::from(*b)).wrapping_mul(16_777_619))
+}
+
 pub struct GenePool {
 	gene_pool: Box<[Dna]>,
 	round_robin: usize,
@@ -79,14 +96,14 @@ impl GenePool {
 		}
 	}
 
-	pub fn randomize(&mut self) {
-		let mut rnd = Randomizer::new();
+	pub fn randomize<R: rand::Rng>(&mut self, rng: &mut R) {
+		let mut rnd = Randomizer { rng };
 		self.gene_pool[self.round_robin] = rnd.seed().dna_cloned();
 	}
 
-	pub fn next(&mut self) -> Genome {
+	pub fn next<R: rand::Rng>(&mut self, rng: &mut R) -> Genome {
 		let gen = Genome::copy_from(&self.gene_pool[self.round_robin].clone());
-		let mutated = gen.mutate(&mut rand::thread_rng());
+		let mutated = gen.mutate(rng);
 		self.gene_pool[self.round_robin] = mutated.dna_cloned();
 		self.round_robin = (self.round_robin + 1) % self.gene_pool.len();
 		gen
@@ -224,7 +241,7 @@ impl<R> Seeder for Randomizer<R>
 		R: rand::Rng,
 {
 	fn seed(&mut self) -> Genome {
-		let mut dna = vec![0u8; 72];
+		let mut dna = vec![0u8; GENOME_LIMB_GENES_AT + GENOME_LIMB_GENES_BYTES];
 		self.rng.fill_bytes(dna.as_mut_slice());
 		Genome::new(dna)
 	}
@@ -313,9 +330,13 @@ impl Genome {
 		Genome::new(new_genes)
 	}
 
-	pub fn mutate<R: rand::Rng>(&self, rng: &mut R) -> Self {
+	pub fn mutate<R: rand::Rng>(&self, rng: &mut R) -> Self { self.mutate_by(rng, 1.) }
+
+	// `rate` scales the number of flipped bits
+	pub fn mutate_by<R: rand::Rng>(&self, rng: &mut R, rate: f32) -> Self {
 		let mut new_genes = self.dna.to_vec();
-		let n_mutations = rng.gen::<usize>() % (new_genes.len() / 8 + 1);
+		let usual = rng.gen::<usize>() % (new_genes.len() / 8 + 1);
+		let n_mutations = (usual as f32 * rate.max(0.)).round() as usize;
 		for _ in 0..n_mutations {
 			let (byte, bit) = split_bit(rng.gen::<usize>() % self.bit_count);
 			new_genes[byte] ^= 1 << bit;
@@ -323,6 +344,17 @@ impl Genome {
 		Genome::new(new_genes)
 	}
 
+	// moves on to the genes starting at the byte, if the genome is long enough to have that many bytes of them
+	pub fn seek(&mut self, byte: usize, len: usize) -> bool {
+		if self.dna.len() < byte + len {
+			return false;
+		}
+		self.ptr = bit_count(byte);
+		true
+	}
+
+	pub fn dna(&self) -> &[u8] { &self.dna }
+
 	pub fn dna_cloned(&self) -> Box<[u8]> {
 		self.dna.clone()
 	}
@@ -356,4 +388,21 @@ impl Generator for Genome {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+	use super::*;
+
+	#[test]
+	fn seeks_only_within_the_genome() {
+		let mut genome = Genome::new(vec![0, 0xff]);
+		assert!(!genome.seek(1, 2));
+		assert_eq!(genome.next_integer(0, 127), 0);
+		assert!(genome.seek(1, 1));
+		assert_eq!(genome.next_integer(0, 127), 127);
+	}
+
+	#[test]
+	fn random_genomes_have_limb_genes() {
+		let mut genome = Randomizer::new().seed();
+		assert!(genome.seek(GENOME_LIMB_GENES_AT, GENOME_LIMB_GENES_BYTES));
+	}
+}
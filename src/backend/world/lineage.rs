@@ -0,0 +1,179 @@
+use backend::obj::Id;
+use backend::world::gen::Dna;
+use constants::*;
+use core::clock::SecondsValue;
+use std::cmp;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::mem;
+
+// Who an agent came from and who came from it, kept after it dies
+#[derive(Clone, Debug)]
+pub struct Record {
+	pub mother: Option<Id>,
+	// none when budding, or not fertilised yet
+	pub father: Option<Id>,
+	// the minions of the first population are the generation zero
+	pub generation: usize,
+	pub born: SecondsValue,
+	pub dna: Dna,
+	pub children: Vec<Id>,
+}
+
+// The family tree of the minions since the world was started or loaded, spores included until they hatch. The
+// oldest records are forgotten first once there are too many
+#[derive(Clone, Debug, Default)]
+pub struct Lineage {
+	records: HashMap<Id, Record>,
+	// oldest first, hatched spores stay in it until their turn to be forgotten comes
+	order: VecDeque<Id>,
+}
+
+impl Lineage {
+	pub fn get(&self, id: Id) -> Option<&Record> { self.records.get(&id) }
+
+	fn insert(&mut self, id: Id, record: Record) {
+		if self.records.insert(id, record).is_none() {
+			self.order.push_back(id);
+		}
+		while self.records.len() > LINEAGE_RECORDS_MAX {
+			match self.order.pop_front() {
+				Some(oldest) => {
+					self.records.remove(&oldest);
+				}
+				None => break,
+			}
+		}
+	}
+
+	pub fn len(&self) -> usize { self.records.len() }
+
+	pub fn is_empty(&self) -> bool { self.records.is_empty() }
+
+	// roughly what the records take in memory, in bytes
+	pub fn footprint(&self) -> usize {
+		let records: usize = self
+			.records
+			.values()
+			.map(|record| record.dna.len() + record.children.len() * mem::size_of::<Id>())
+			.sum();
+		records + self.records.len() * mem::size_of::<(Id, Record)>() + self.order.len() * mem::size_of::<Id>()
+	}
+
+	// forgets the oldest records down to the given number
+	pub fn trim(&mut self, keep: usize) {
+		while self.records.len() > keep {
+			match self.order.pop_front() {
+				Some(oldest) => {
+					self.records.remove(&oldest);
+				}
+				None => break,
+			}
+		}
+	}
+
+	// a parent nobody saw born, one of the first population
+	fn found(&mut self, id: Id, dna: &Dna, now: SecondsValue) {
+		if !self.records.contains_key(&id) {
+			self.insert(
+				id,
+				Record {
+					mother: None,
+					father: None,
+					generation: 0,
+					born: now,
+					dna: dna.clone(),
+					children: Vec::new(),
+				},
+			);
+		}
+	}
+
+	pub fn laid(&mut self, spore: Id, mother: Id, mother_dna: &Dna, spore_dna: &Dna, now: SecondsValue) {
+		self.found(mother, mother_dna, now);
+		let generation = self.generation(mother) + 1;
+		self.insert(
+			spore,
+			Record {
+				mother: Some(mother),
+				father: None,
+				generation,
+				born: now,
+				dna: spore_dna.clone(),
+				children: Vec::new(),
+			},
+		);
+	}
+
+	pub fn fertilised(&mut self, spore: Id, father: Id, father_dna: &Dna, now: SecondsValue) {
+		self.found(father, father_dna, now);
+		if let Some(record) = self.records.get_mut(&spore) {
+			record.father = Some(father);
+		}
+	}
+
+	// the spore's parents become the minion's, and it becomes their child
+	pub fn hatched(&mut self, spore: Id, minion: Id, dna: &Dna, now: SecondsValue) {
+		let record = match self.records.remove(&spore) {
+			Some(record) => record,
+			None => return,
+		};
+		for parent in record.mother.iter().chain(record.father.iter()) {
+			if let Some(parent) = self.records.get_mut(parent) {
+				parent.children.push(minion);
+			}
+		}
+		self.insert(
+			minion,
+			Record {
+				born: now,
+				dna: dna.clone(),
+				..record
+			},
+		);
+	}
+
+	// a minion spawned from the genomes of two others, with no spore in between
+	pub fn bred(&mut self, child: Id, mother: (Id, &Dna), father: (Id, &Dna), dna: &Dna, now: SecondsValue) {
+		self.found(mother.0, mother.1, now);
+		self.found(father.0, father.1, now);
+		let generation = cmp::max(self.generation(mother.0), self.generation(father.0)) + 1;
+		for parent in &[mother.0, father.0] {
+			if let Some(parent) = self.records.get_mut(parent) {
+				parent.children.push(child);
+			}
+		}
+		self.insert(
+			child,
+			Record {
+				mother: Some(mother.0),
+				father: Some(father.0),
+				generation,
+				born: now,
+				dna: dna.clone(),
+				children: Vec::new(),
+			},
+		);
+	}
+
+	fn generation(&self, id: Id) -> usize { self.records.get(&id).map_or(0, |record| record.generation) }
+
+	// the other children of either parent
+	pub fn siblings(&self, id: Id) -> Vec<Id> {
+		let record = match self.records.get(&id) {
+			Some(record) => record,
+			None => return Vec::new(),
+		};
+		let mut siblings = record
+			.mother
+			.iter()
+			.chain(record.father.iter())
+			.filter_map(|parent| self.records.get(parent))
+			.flat_map(|parent| parent.children.iter().cloned())
+			.filter(|sibling| *sibling != id)
+			.collect::<Vec<_>>();
+		siblings.sort();
+		siblings.dedup();
+		siblings
+	}
+}
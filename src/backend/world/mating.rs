@@ -0,0 +1,62 @@
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Reproduction {
+	// spores hatch as clones unless a partner fertilises them
+	Mixed,
+	// single parent, spores are never fertilised
+	Budding,
+	// two parents, unfertilised spores die without hatching
+	Mating,
+}
+
+// Who can fertilise whose spores
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Mating {
+	pub mode: Reproduction,
+	// only genetically similar partners can mate
+	pub assortative: bool,
+	// the largest fraction of differing genes between compatible partners
+	pub max_distance: f32,
+	// how far a spore can find a mate without touching it, when assortative
+	pub range: f32,
+	// how many bits of a spore's genome flip, relative to the usual, zero hatches exact clones
+	pub mutation: f32,
+}
+
+impl Default for Mating {
+	fn default() -> Self {
+		Mating {
+			mode: Reproduction::Mixed,
+			assortative: false,
+			max_distance: 0.25,
+			range: 5.,
+			mutation: 1.,
+		}
+	}
+}
+
+impl Reproduction {
+	pub fn parse(name: &str) -> Option<Reproduction> {
+		match name {
+			"mixed" => Some(Reproduction::Mixed),
+			"budding" => Some(Reproduction::Budding),
+			"mating" => Some(Reproduction::Mating),
+			_ => None,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Reproduction::Mixed => "mixed",
+			Reproduction::Budding => "budding",
+			Reproduction::Mating => "mating",
+		}
+	}
+}
+
+impl Mating {
+	pub fn is_compatible(&self, genetic_distance: f32) -> bool {
+		!self.assortative || genetic_distance <= self.max_distance
+	}
+}
@@ -1,17 +1,34 @@
 pub mod agent;
 pub mod alert;
+pub mod annotation;
+pub mod chunk;
+pub mod digest;
+pub mod disease;
+pub mod energy;
+pub mod far_field;
+pub mod fitness;
 pub mod gen;
+pub mod lineage;
+pub mod mating;
+pub mod moment;
+pub mod objective;
+pub mod obstacle;
 pub mod particle;
 pub mod persist;
+pub mod portal;
 pub mod phen;
+pub mod probe;
 pub mod segment;
 pub mod swarm;
+pub mod terrain;
 
 use backend::obj;
 use backend::obj::*;
 use chrono::DateTime;
 use chrono::Utc;
 use rand;
+use rand::SeedableRng;
+use rand::XorShiftRng;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::f32::consts;
@@ -23,9 +40,20 @@ use std::path;
 use self::agent::Agent;
 use self::agent::AgentType;
 use self::agent::TypedAgent;
+use self::annotation::Annotation;
+use self::disease::Disease;
+use self::energy::EnergyLedger;
+use self::far_field::Demography;
+use self::fitness::Fitness;
+use self::lineage::Lineage;
+use self::mating::Mating;
+use self::objective::{GameMode, Score};
+use self::obstacle::Obstacle;
 use self::particle::Particle;
+use self::portal::Portal;
+use self::probe::Probe;
 use self::swarm::*;
-use app::constants::*;
+use constants::*;
 use backend::messagebus::{Message, Outbox};
 use core::clock::*;
 use core::color::Rgba;
@@ -40,6 +68,33 @@ pub trait AgentState {
 	fn agent(&self, id: obj::Id) -> Option<&Agent>;
 }
 
+// what lies beyond the extent: walls, the opposite edge, or more chunks streamed in as needed
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Topology {
+	Bounded,
+	Toroidal,
+	Unbounded,
+}
+
+impl Default for Topology {
+	fn default() -> Self { Topology::Bounded }
+}
+
+impl Topology {
+	pub fn parse(name: &str) -> Option<Topology> {
+		match name {
+			"bounded" => Some(Topology::Bounded),
+			"toroidal" => Some(Topology::Toroidal),
+			"unbounded" => Some(Topology::Unbounded),
+			_ => None,
+		}
+	}
+}
+
+// the generator doesn't take an all zero seed
+fn seeded_rng(seed: u32, low: u32, high: u32) -> XorShiftRng { SeedableRng::from_seed([seed, low, high, 0x9e37_79b9]) }
+
 pub struct World {
 	pub extent: Rect,
 	phase: Rgba,
@@ -52,6 +107,25 @@ pub struct World {
 	resource_gene_pool: gen::GenePool,
 	clock: SimulationTimer,
 	particles: Vec<Particle>,
+	annotations: Vec<Annotation>,
+	probes: Vec<Probe>,
+	obstacles: Vec<Obstacle>,
+	portals: Vec<Portal>,
+	topology: Topology,
+	pub chunks: chunk::Chunks,
+	// bumped on every change, so that the physics knows when to rebuild them
+	obstacles_revision: usize,
+	fitness: Fitness,
+	mating: Mating,
+	disease: Disease,
+	game_mode: GameMode,
+	score: Score,
+	energy: EnergyLedger,
+	demography: Demography,
+	lineage: Lineage,
+	// everything random in the world and its systems follows from it, so that the same seed replays the same run
+	seed: u32,
+	rng: XorShiftRng,
 }
 
 impl AgentState for World {
@@ -74,6 +148,7 @@ impl Feeder {
 		}
 	}
 	pub fn rate(&self) -> Seconds { self.rate }
+	pub fn set_rate(&mut self, rate: Seconds) { self.rate = rate }
 	pub fn intensity(&self) -> f32 { self.intensity }
 	pub fn set_intensity(&mut self, intensity: f32) { self.intensity = intensity }
 }
@@ -102,7 +177,7 @@ impl World {
 				let (s, c) = (consts::PI * 2. * (i as f32 / num_emitters as f32)).sin_cos();
 				Feeder::new(c * EMITTER_DISTANCE, s * EMITTER_DISTANCE, emitter_rate)
 			}).collect::<Vec<_>>();
-		World {
+		let mut world = World {
 			extent: Rect::new(-WORLD_RADIUS, -WORLD_RADIUS, WORLD_RADIUS, WORLD_RADIUS),
 			phase: COLOR_TRANSPARENT,
 			swarms,
@@ -117,7 +192,41 @@ impl World {
 			regenerations: 0usize,
 			clock,
 			particles: Vec::with_capacity(10000),
-		}
+			annotations: Vec::new(),
+			probes: Vec::new(),
+			obstacles: Vec::new(),
+			portals: Vec::new(),
+			topology: Topology::default(),
+			chunks: chunk::Chunks::default(),
+			obstacles_revision: 0,
+			fitness: Fitness::default(),
+			mating: Mating::default(),
+			disease: Disease::default(),
+			game_mode: GameMode::default(),
+			score: Score::default(),
+			energy: EnergyLedger::default(),
+			demography: Demography::default(),
+			lineage: Lineage::default(),
+			seed: 0,
+			rng: seeded_rng(0, 0, 0),
+		};
+		world.reseed(rand::random());
+		world
+	}
+
+	pub fn seed(&self) -> u32 { self.seed }
+
+	/// The gene pools mutate, and the systems draw, the same way from here on for the same seed.
+	pub fn reseed(&mut self, seed: u32) {
+		self.seed = seed;
+		self.rng = seeded_rng(seed, 0, 0);
+	}
+
+	/// A generator for a system in the current step, told apart from the others by `stream`: it follows from the seed
+	/// and the simulated time only, so the systems draw the same whatever order they run in.
+	pub fn rng_for(&self, stream: u32) -> XorShiftRng {
+		let bits = self.seconds().get().to_bits();
+		seeded_rng(self.seed ^ stream, bits as u32, (bits >> 32) as u32)
 	}
 
 	pub fn clear(&mut self) {
@@ -127,9 +236,51 @@ impl World {
 		self.registered.clear();
 		self.registered_player_id = None;
 		self.particles.clear();
+		self.annotations.clear();
+		self.probes.clear();
+		self.obstacles_mut().clear();
+		self.portals.clear();
+		self.chunks = chunk::Chunks::default();
+		self.chunks.restart_far_field(self.clock.seconds().get());
+		self.score = Score::default();
 	}
 
-	pub fn tick(&mut self, dt: Seconds) { self.clock.tick(dt); }
+	pub fn tick(&mut self, dt: Seconds) {
+		self.clock.tick(dt);
+		self.operate_doors(dt);
+		self.energy.tick(self.clock.seconds().get());
+		self.measure_demography();
+	}
+
+	// the rates the frozen chunks of an unbounded world live on by are those of the minions in view
+	fn measure_demography(&mut self) {
+		let now = self.clock.seconds().get();
+		if self.demography.is_due(now) {
+			let energies: Vec<f32> = self
+				.agents(AgentType::Minion)
+				.values()
+				.filter(|minion| minion.state.is_alive())
+				.map(|minion| minion.state.energy())
+				.collect();
+			let mean = energies.iter().sum::<f32>() / energies.len().max(1) as f32;
+			self.demography.close(now, energies.len(), mean);
+		}
+		if self.topology == Topology::Unbounded {
+			self.chunks.step_far_field(self.demography.rates(), now);
+		}
+	}
+
+	// the physics only rebuilds the obstacles when a door actually opened or closed
+	fn operate_doors(&mut self, dt: Seconds) {
+		let population = self.agents(AgentType::Minion).len();
+		let mut changed = false;
+		for door in self.obstacles.iter_mut().filter_map(|o| o.door.as_mut()) {
+			changed |= door.update(dt.get() as f32, population);
+		}
+		if changed {
+			self.obstacles_revision = self.obstacles_revision.wrapping_add(1);
+		}
+	}
 
 	pub fn seconds(&self) -> Seconds { self.clock.seconds() }
 
@@ -142,7 +293,7 @@ impl World {
 	}
 
 	pub fn new_resource(&mut self, transform: Transform, motion: Motion) -> obj::Id {
-		let mut gen = &mut self.resource_gene_pool.next();
+		let mut gen = &mut self.resource_gene_pool.next(&mut self.rng);
 		let clock = self.clock.clone();
 		let id = self.swarm_mut(&AgentType::Resource).spawn(
 			&mut gen,
@@ -154,6 +305,7 @@ impl World {
 			},
 			&clock,
 		);
+		self.energy.current_mut().sown += self.agent(id).map_or(0., |resource| resource.state.energy());
 		self.register(id)
 	}
 
@@ -168,6 +320,7 @@ impl World {
 			},
 			&clock,
 		);
+		self.energy.current_mut().recycled += self.agent(id).map_or(0., |resource| resource.state.energy());
 		let livery_color = self.agent(id).unwrap().segment(0).unwrap().livery.albedo;
 		outbox.post(Message::NewEmitter(particle::Emitter::for_dead_minion(
 			transform,
@@ -176,10 +329,34 @@ impl World {
 		self.register(id)
 	}
 
+	// plants take root where they are spawned, the physics keeps frozen bodies still
+	pub fn new_plant(&mut self, transform: Transform, dna: Option<&gen::Dna>) -> obj::Id {
+		let mut gen = match dna {
+			Some(dna) => gen::Genome::copy_from(dna).mutate_by(&mut self.rng, self.mating.mutation),
+			None => self.resource_gene_pool.next(&mut self.rng),
+		};
+		let clock = self.clock.clone();
+		let id = self.swarm_mut(&AgentType::Plant).spawn(
+			&mut gen,
+			agent::InitialState {
+				transform,
+				charge: DEFAULT_PLANT_CHARGE,
+				..Default::default()
+			},
+			&clock,
+		);
+		if let Some(plant) = self.agent_mut(id) {
+			plant.state.freeze(true);
+		}
+		self.energy.current_mut().sown += self.agent(id).map_or(0., |plant| plant.state.energy());
+		self.register(id)
+	}
+
 	pub fn new_spore(&mut self, outbox: &Outbox, transform: Transform, dna: &gen::Dna) -> obj::Id {
 		let clock = self.clock.clone();
+		let mut gen = gen::Genome::copy_from(dna).mutate_by(&mut self.rng, self.mating.mutation);
 		let id = self.swarm_mut(&AgentType::Spore).spawn(
-			&mut gen::Genome::copy_from(dna).mutate(&mut rand::thread_rng()),
+			&mut gen,
 			agent::InitialState {
 				transform: transform.clone(),
 				charge: DEFAULT_SPORE_CHARGE,
@@ -196,6 +373,19 @@ impl World {
 		self.register(id)
 	}
 
+	// the minion a genome grows into when fully mature, left out of the world
+	pub fn develop_minion(&self, dna: &gen::Dna) -> Agent {
+		phen::phenotype_of(AgentType::Minion).develop(
+			&mut gen::Genome::copy_from(dna),
+			0,
+			agent::InitialState {
+				maturity: Some(1.),
+				..Default::default()
+			},
+			&self.clock,
+		)
+	}
+
 	pub fn hatch_spore(&mut self, outbox: &Outbox, transform: Transform, dna: &gen::Dna) -> obj::Id {
 		let clock = self.clock.clone();
 		let id = self.swarm_mut(&AgentType::Minion).spawn(
@@ -207,6 +397,8 @@ impl World {
 			},
 			&clock,
 		);
+		self.energy.current_mut().born += self.agent(id).map_or(0., |minion| minion.state.energy());
+		self.demography.born();
 		let livery_color = self.agent(id).unwrap().segment(0).unwrap().livery.albedo;
 		outbox.post(Message::NewEmitter(particle::Emitter::for_new_minion(
 			transform,
@@ -216,10 +408,15 @@ impl World {
 	}
 
 	pub fn randomize_minion(&mut self, pos: Position, motion: Motion) -> obj::Id {
-		self.minion_gene_pool.randomize();
+		self.minion_gene_pool.randomize(&mut self.rng);
 		self.new_minion(pos, motion)
 	}
 
+	// the next minions and spores are bred from these, as in a saved gene pool
+	pub fn replace_minion_gene_pool(&mut self, base64: &[String]) {
+		self.minion_gene_pool.populate_from_base64(base64, 0);
+	}
+
 	pub fn init_minions(&mut self) {
 		self.regenerations += 1;
 		let n = self.minion_gene_pool.len();
@@ -229,7 +426,7 @@ impl World {
 		let angle_delta = consts::PI * 2. / INITIAL_SPAWN_RADIUS_SLICES as f32;
 		for _ in 0..n {
 			let pos = Position::new(r * angle.cos(), r * angle.sin());
-			let mut gen = self.minion_gene_pool.next();
+			let mut gen = self.minion_gene_pool.next(&mut self.rng);
 			let id = self.swarm_mut(&AgentType::Minion).spawn(
 				&mut gen,
 				agent::InitialState {
@@ -312,11 +509,16 @@ impl World {
 	}
 
 	pub fn new_minion(&mut self, pos: Position, motion: Motion) -> obj::Id {
+		let mut gen = self.minion_gene_pool.next(&mut self.rng);
+		self.new_minion_with(pos, motion, &mut gen)
+	}
+
+	// a genome of one's own rather than the next of the gene pool
+	pub fn new_minion_with(&mut self, pos: Position, motion: Motion, gen: &mut gen::Genome) -> obj::Id {
 		let angle = consts::PI / 2. + f32::atan2(pos.y, pos.x);
-		let mut gen = self.minion_gene_pool.next();
 		let clock = self.clock.clone();
 		let id = self.swarm_mut(&AgentType::Minion).spawn(
-			&mut gen,
+			gen,
 			agent::InitialState {
 				transform: Transform::new(pos, angle),
 				motion,
@@ -328,12 +530,37 @@ impl World {
 		self.register(id)
 	}
 
+	// infects up to count random healthy minions, returns how many caught it
+	pub fn outbreak(&mut self, count: usize) -> usize {
+		let duration = self.disease.duration;
+		// in the order of their ids, the minions are kept in no particular one
+		let mut healthy: Vec<Id> = self
+			.agents(AgentType::Minion)
+			.values()
+			.filter(|a| a.state.is_active() && !a.state.is_infected())
+			.map(|a| a.id())
+			.collect();
+		healthy.sort();
+		let victims = rand::sample(&mut self.rng, healthy, count);
+		for id in &victims {
+			if let Some(agent) = self.agent_mut(*id) {
+				agent.state.infect(duration);
+			}
+		}
+		victims.len()
+	}
+
 	pub fn register(&mut self, id: obj::Id) -> obj::Id {
 		self.registered.insert(id);
 		id
 	}
 
-	pub fn registered(&mut self) -> Box<[Id]> { self.registered.drain().collect::<Vec<_>>().into_boxed_slice() }
+	// in the order of their ids, so that the systems take them in the same order in every run
+	pub fn registered(&mut self) -> Box<[Id]> {
+		let mut registered = self.registered.drain().collect::<Vec<_>>();
+		registered.sort();
+		registered.into_boxed_slice()
+	}
 
 	#[allow(dead_code)]
 	pub fn agent(&self, id: obj::Id) -> Option<&Agent> { self.swarms.get(&id.type_of()).and_then(|m| m.get(id)) }
@@ -361,7 +588,7 @@ impl World {
 
 	pub fn feeders(&self) -> &[Feeder] { self.feeders.as_slice() }
 
-	pub fn feeders_mut(&mut self) -> &mut [Feeder] { self.feeders.as_mut_slice() }
+	pub fn feeders_mut(&mut self) -> &mut Vec<Feeder> { &mut self.feeders }
 
 	pub fn swarms(&self) -> &SwarmMap { &self.swarms }
 
@@ -375,6 +602,69 @@ impl World {
 
 	pub fn add_particle(&mut self, particle: Particle) { self.particles.push(particle); }
 
+	pub fn annotations(&self) -> &[Annotation] { &self.annotations }
+
+	pub fn annotations_mut(&mut self) -> &mut Vec<Annotation> { &mut self.annotations }
+
+	pub fn probes(&self) -> &[Probe] { &self.probes }
+
+	pub fn fitness(&self) -> &Fitness { &self.fitness }
+
+	pub fn set_fitness(&mut self, fitness: Fitness) { self.fitness = fitness }
+
+	pub fn mating(&self) -> &Mating { &self.mating }
+
+	pub fn set_mating(&mut self, mating: Mating) { self.mating = mating }
+
+	pub fn disease(&self) -> &Disease { &self.disease }
+
+	pub fn energy(&self) -> &EnergyLedger { &self.energy }
+
+	pub fn energy_mut(&mut self) -> &mut EnergyLedger { &mut self.energy }
+
+	pub fn lineage(&self) -> &Lineage { &self.lineage }
+
+	pub fn lineage_mut(&mut self) -> &mut Lineage { &mut self.lineage }
+
+	pub fn set_disease(&mut self, disease: Disease) { self.disease = disease }
+
+	pub fn game_mode(&self) -> &GameMode { &self.game_mode }
+
+	pub fn set_game_mode(&mut self, game_mode: GameMode) { self.game_mode = game_mode }
+
+	pub fn score(&self) -> &Score { &self.score }
+
+	pub fn set_score(&mut self, score: Score) { self.score = score }
+
+	pub fn probes_mut(&mut self) -> &mut Vec<Probe> { &mut self.probes }
+
+	pub fn obstacles(&self) -> &[Obstacle] { &self.obstacles }
+
+	pub fn obstacles_mut(&mut self) -> &mut Vec<Obstacle> {
+		self.obstacles_revision = self.obstacles_revision.wrapping_add(1);
+		&mut self.obstacles
+	}
+
+	pub fn obstacles_revision(&self) -> usize { self.obstacles_revision }
+
+	pub fn portals(&self) -> &[Portal] { &self.portals }
+
+	pub fn portals_mut(&mut self) -> &mut Vec<Portal> { &mut self.portals }
+
+	pub fn topology(&self) -> Topology { self.topology }
+
+	pub fn set_topology(&mut self, topology: Topology) { self.topology = topology }
+
+	pub fn is_toroidal(&self) -> bool { self.topology == Topology::Toroidal }
+
+	// the walls around the world, if it has any
+	pub fn bounds(&self) -> Option<Rect> {
+		if self.topology == Topology::Bounded { Some(self.extent) } else { None }
+	}
+
+	// the extent positions wrap around, if they do
+	pub fn wrap(&self) -> Option<Rect> { if self.is_toroidal() { Some(self.extent) } else { None } }
+
 	pub fn cleanup_before(&mut self) { self.clear_particles(); }
 
 	pub fn sweep(&mut self) -> Box<[Agent]> {
@@ -382,6 +672,9 @@ impl World {
 		for swarm in self.swarms.values_mut() {
 			swarm.free_resources(&mut v);
 		}
+		// as the registered ones, so that the physics lets go of their bodies in the same order in every run
+		v.sort_by_key(|agent| agent.id());
+		self.demography.died(v.iter().filter(|agent| agent.id().type_of() == AgentType::Minion).count());
 		v.into_boxed_slice()
 	}
 
@@ -393,6 +686,34 @@ impl World {
 		Ok(file_name)
 	}
 
+	pub fn dump_probes(&self, containing_dir: &path::Path) -> io::Result<path::PathBuf> {
+		let now: DateTime<Utc> = Utc::now();
+		let file_name = containing_dir.join(now.format(PROBES_FILE_PATTERN_CSV).to_string());
+		fs::create_dir_all(containing_dir).is_ok();
+		let mut f = io::BufWriter::new(fs::File::create(&file_name)?);
+		f.write_fmt(format_args!("seconds,probe,left,bottom,right,top,count,energy,eaten,throughput\n"))?;
+		for (i, probe) in self.probes.iter().enumerate() {
+			let rect = &probe.extent;
+			for r in probe.history() {
+				f.write_fmt(format_args!(
+					"{:.3},{},{},{},{},{},{},{},{},{}\n",
+					r.seconds,
+					i + 1,
+					rect.min.x,
+					rect.min.y,
+					rect.max.x,
+					rect.max.y,
+					r.agent_count,
+					r.mean_energy,
+					r.resources_eaten,
+					r.throughput
+				))?;
+			}
+		}
+		f.flush()?;
+		Ok(file_name)
+	}
+
 	pub fn dump(&self, containing_dir: &path::Path) -> io::Result<path::PathBuf> {
 		let now: DateTime<Utc> = Utc::now();
 		let file_name = containing_dir.join(now.format(DUMP_FILE_PATTERN_CSV).to_string());
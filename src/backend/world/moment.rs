@@ -0,0 +1,257 @@
+use backend::world::agent::{Agent, AgentType, TypedAgent};
+use backend::world::gen;
+use backend::world::World;
+use constants::*;
+use core::clock::SecondsValue;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::fmt;
+
+// Something unusual that happened in the world, worth a look later
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Moment {
+	// from how many minions to how many, since the sample before
+	Population(usize, usize),
+	// a body plan never seen before, by its species key, once it has a few members
+	NewSpecies(u32),
+	// the oldest minion yet died, by id and age in seconds
+	RecordAge(usize, f32),
+}
+
+impl Moment {
+	// short and safe for a file name, the seconds keep it apart from the others of the same kind
+	pub fn name(&self, seconds: SecondsValue) -> String {
+		let kind = match *self {
+			Moment::Population(before, after) if after > before => "boom",
+			Moment::Population(_, _) => "crash",
+			Moment::NewSpecies(_) => "species",
+			Moment::RecordAge(_, _) => "record age",
+		};
+		format!("{} at {:.0}s", kind, seconds)
+	}
+}
+
+impl fmt::Display for Moment {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Moment::Population(before, after) => write!(f, "population went from {} to {} minions", before, after),
+			Moment::NewSpecies(key) => write!(f, "new species {:08x}", key),
+			Moment::RecordAge(id, age) => write!(f, "minion {} died at the record age of {:.0}s", id, age),
+		}
+	}
+}
+
+// Watches the population, the species and the deaths for what stands out from the recent run
+#[derive(Default)]
+pub struct Detector {
+	populations: VecDeque<f64>,
+	next_sample: SecondsValue,
+	samples: usize,
+	species: HashSet<u32>,
+	oldest: f32,
+	found: Vec<Moment>,
+}
+
+impl Detector {
+	pub fn died(&mut self, agents: &[Agent]) {
+		for minion in agents.iter().filter(|agent| agent.id().type_of() == AgentType::Minion) {
+			let age = minion.age().get() as f32;
+			if age > self.oldest {
+				// the first deaths of a run are all records
+				if self.samples >= MOMENT_WINDOW && age >= MOMENT_MIN_RECORD_AGE {
+					self.found.push(Moment::RecordAge(minion.id(), age));
+				}
+				self.oldest = age;
+			}
+		}
+	}
+
+	// what was found since the last call: the deaths as they come, the rest at every sample
+	pub fn detect(&mut self, world: &World, now: SecondsValue) -> Vec<Moment> {
+		// the clock went back with a load or a restart
+		if self.next_sample > now + MOMENT_SAMPLE_INTERVAL {
+			*self = Detector::default();
+		}
+		if now >= self.next_sample {
+			self.next_sample = now + MOMENT_SAMPLE_INTERVAL;
+			self.sample(world);
+		}
+		self.found.drain(..).collect()
+	}
+
+	fn sample(&mut self, world: &World) {
+		let minions = world.agents(AgentType::Minion);
+		let population = minions.len() as f64;
+		if self.populations.len() >= MOMENT_WINDOW {
+			let n = self.populations.len() as f64;
+			let mean = self.populations.iter().sum::<f64>() / n;
+			let variance = self.populations.iter().map(|p| (p - mean) * (p - mean)).sum::<f64>() / n;
+			let last = self.populations.back().cloned().unwrap_or(population);
+			// a flat population has no spread, a few minions either way don't count then
+			let is_unusual = (population - mean).abs() > MOMENT_POPULATION_SIGMAS * variance.sqrt().max(1.);
+			if is_unusual && (population - last).abs() >= MOMENT_POPULATION_MIN_CHANGE as f64 {
+				self.found.push(Moment::Population(last as usize, population as usize));
+			}
+			self.populations.pop_front();
+		}
+		self.populations.push_back(population);
+
+		let mut members: HashMap<u32, usize> = HashMap::new();
+		for minion in minions.values() {
+			*members.entry(gen::species_key(minion.dna())).or_insert(0) += 1;
+		}
+		// those around from the start are not news
+		let is_news = self.samples > 0;
+		for (key, count) in members {
+			if count >= MOMENT_SPECIES_MEMBERS && self.species.insert(key) && is_news {
+				self.found.push(Moment::NewSpecies(key));
+			}
+		}
+		self.samples += 1;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::clock::seconds;
+	use core::geometry::{Motion, Position};
+	use core::resource::filesystem::ResourceLoaderBuilder;
+	use std::path::Path;
+
+	fn world() -> World {
+		let resources = ResourceLoaderBuilder::new().add(Path::new("resources")).build();
+		World::new(&resources, DEFAULT_MINION_GENE_POOL_FILE)
+	}
+
+	fn add_minions(world: &mut World, count: usize) {
+		for i in 0..count {
+			world.new_minion(Position::new(10. * i as f32, 0.), Motion::default());
+		}
+	}
+
+	fn add_copies(world: &mut World, dna: &[u8], count: usize) {
+		for i in 0..count {
+			let mut genome = gen::Genome::copy_from(dna);
+			world.new_minion_with(Position::new(10. * i as f32, 20.), Motion::default(), &mut genome);
+		}
+	}
+
+	// a minion taken out of the world, as the systems hand over the dead
+	fn dead_minion(world: &mut World, age: f32) -> Agent {
+		let id = world.new_minion(Position::new(0., 0.), Motion::default());
+		let mut minion = world.swarm_mut(&AgentType::Minion).agents_mut().remove(&id).unwrap();
+		minion.segments[0].state.update(seconds(age));
+		minion
+	}
+
+	// the minions added may well make up new species too
+	fn population_moments(detector: &Detector) -> Vec<Moment> {
+		detector
+			.found
+			.iter()
+			.filter_map(|moment| match *moment {
+				Moment::Population(_, _) => Some(*moment),
+				_ => None,
+			}).collect()
+	}
+
+	fn detector_after_a_window(world: &World) -> Detector {
+		let mut detector = Detector::default();
+		for _ in 0..MOMENT_WINDOW {
+			detector.sample(world);
+		}
+		detector
+	}
+
+	#[test]
+	fn a_jump_from_a_flat_population_is_found() {
+		let mut world = world();
+		add_minions(&mut world, 10);
+		let mut detector = detector_after_a_window(&world);
+		assert!(detector.found.is_empty());
+		add_minions(&mut world, 10);
+		detector.sample(&world);
+		assert_eq!(population_moments(&detector), vec![Moment::Population(10, 20)]);
+	}
+
+	#[test]
+	fn a_few_minions_either_way_are_not_a_moment() {
+		let mut world = world();
+		add_minions(&mut world, 10);
+		let mut detector = detector_after_a_window(&world);
+		add_minions(&mut world, MOMENT_POPULATION_MIN_CHANGE - 1);
+		detector.sample(&world);
+		assert!(population_moments(&detector).is_empty());
+	}
+
+	#[test]
+	fn the_population_is_judged_after_a_whole_window() {
+		let mut world = world();
+		let mut detector = Detector::default();
+		detector.sample(&world);
+		add_minions(&mut world, 20);
+		detector.sample(&world);
+		assert!(population_moments(&detector).is_empty());
+	}
+
+	#[test]
+	fn a_new_species_is_found_once_it_has_a_few_members() {
+		let mut world = world();
+		let dna = dead_minion(&mut world, 0.).dna().clone();
+		let mut detector = Detector::default();
+		detector.sample(&world);
+		add_copies(&mut world, &dna, MOMENT_SPECIES_MEMBERS - 1);
+		detector.sample(&world);
+		assert!(detector.found.is_empty());
+		add_copies(&mut world, &dna, 1);
+		detector.sample(&world);
+		assert_eq!(detector.found, vec![Moment::NewSpecies(gen::species_key(&dna))]);
+		detector.sample(&world);
+		assert_eq!(detector.found.len(), 1);
+	}
+
+	#[test]
+	fn the_species_around_from_the_start_are_not_news() {
+		let mut world = world();
+		let dna = dead_minion(&mut world, 0.).dna().clone();
+		add_copies(&mut world, &dna, MOMENT_SPECIES_MEMBERS);
+		let mut detector = Detector::default();
+		detector.sample(&world);
+		detector.sample(&world);
+		assert!(detector.found.is_empty());
+	}
+
+	#[test]
+	fn the_oldest_death_yet_is_a_record() {
+		let mut world = world();
+		let mut detector = detector_after_a_window(&world);
+		let minion = dead_minion(&mut world, MOMENT_MIN_RECORD_AGE * 2.);
+		let id = minion.id();
+		detector.died(&[minion]);
+		assert_eq!(detector.found, vec![Moment::RecordAge(id, MOMENT_MIN_RECORD_AGE * 2.)]);
+		detector.died(&[dead_minion(&mut world, MOMENT_MIN_RECORD_AGE * 1.5)]);
+		assert_eq!(detector.found.len(), 1);
+	}
+
+	#[test]
+	fn the_deaths_of_the_first_window_set_the_record_silently() {
+		let mut world = world();
+		let mut detector = Detector::default();
+		detector.died(&[dead_minion(&mut world, MOMENT_MIN_RECORD_AGE * 2.)]);
+		for _ in 0..MOMENT_WINDOW {
+			detector.sample(&world);
+		}
+		detector.died(&[dead_minion(&mut world, MOMENT_MIN_RECORD_AGE * 1.5)]);
+		assert!(detector.found.is_empty());
+	}
+
+	#[test]
+	fn young_deaths_are_no_record() {
+		let mut world = world();
+		let mut detector = detector_after_a_window(&world);
+		detector.died(&[dead_minion(&mut world, MOMENT_MIN_RECORD_AGE * 0.5)]);
+		assert!(detector.found.is_empty());
+	}
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+
+// A goal of the game mode, met once and for all
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Objective {
+	// at least so many minions alive, for so many minutes in a row
+	Population { minions: usize, minutes: f32 },
+	// a minion travelling across the arena from where it was born
+	Crossing,
+}
+
+impl fmt::Display for Objective {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Objective::Population { minions, minutes } => {
+				write!(f, "keep {} minions alive for {:.1} minutes", minions, minutes)
+			}
+			Objective::Crossing => write!(f, "evolve a minion that crosses the arena"),
+		}
+	}
+}
+
+// The challenge laid over the sandbox, off unless asked for
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct GameMode {
+	pub enabled: bool,
+	pub objectives: Vec<Objective>,
+}
+
+// How far the player got, by the objectives of the game mode in the same order
+#[derive(Clone, Debug, Default)]
+pub struct Score {
+	pub points: f32,
+	// how far each got: the seconds the population held, the share of the arena crossed
+	pub held: Vec<f32>,
+	pub met: Vec<bool>,
+}
+
+impl Score {
+	pub fn fit(&mut self, mode: &GameMode) {
+		self.held.resize(mode.objectives.len(), 0.);
+		self.met.resize(mode.objectives.len(), false);
+	}
+
+	pub fn met_count(&self) -> usize { self.met.iter().filter(|met| **met).count() }
+}
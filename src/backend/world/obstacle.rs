@@ -0,0 +1,208 @@
+use constants::*;
+use backend::world::agent;
+use cgmath::InnerSpace;
+use core::geometry::Position;
+use core::geometry::Rect;
+
+// the bodies of the obstacles carry a type no agent has, with the index of the obstacle above it
+const OBSTACLE_TYPE: usize = 0xfe;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Property {
+	Friction,
+	Restitution,
+	Sticky,
+	Drag,
+}
+
+impl Property {
+	pub fn parse(name: &str) -> Option<Property> {
+		match name {
+			"friction" => Some(Property::Friction),
+			"restitution" => Some(Property::Restitution),
+			"sticky" => Some(Property::Sticky),
+			"drag" => Some(Property::Drag),
+			_ => None,
+		}
+	}
+}
+
+// what opens and closes a door, the console can always do it too
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Trigger {
+	Manual,
+	// seconds open and closed in turn
+	Timer { open: f32, closed: f32 },
+	// open while there are at least this many minions
+	Population(usize),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Door {
+	pub trigger: Trigger,
+	pub is_open: bool,
+	// since the timer last switched
+	#[serde(default)]
+	elapsed: f32,
+}
+
+impl Door {
+	pub fn new(trigger: Trigger) -> Self {
+		Door {
+			trigger,
+			is_open: false,
+			elapsed: 0.,
+		}
+	}
+
+	pub fn set_open(&mut self, is_open: bool) {
+		self.is_open = is_open;
+		self.elapsed = 0.;
+	}
+
+	// true if it opened or closed
+	pub fn update(&mut self, dt: f32, population: usize) -> bool {
+		let was_open = self.is_open;
+		match self.trigger {
+			Trigger::Manual => {}
+			Trigger::Timer { open, closed } => {
+				self.elapsed += dt;
+				let duration = if self.is_open { open } else { closed };
+				if self.elapsed >= duration {
+					self.set_open(!was_open);
+				}
+			}
+			Trigger::Population(threshold) => self.is_open = population >= threshold,
+		}
+		self.is_open != was_open
+	}
+}
+
+// the way a gate lets bodies through, or a strip carries them
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+	Left,
+	Right,
+	Up,
+	Down,
+}
+
+impl Direction {
+	pub fn parse(name: &str) -> Option<Direction> {
+		match name {
+			"left" => Some(Direction::Left),
+			"right" => Some(Direction::Right),
+			"up" => Some(Direction::Up),
+			"down" => Some(Direction::Down),
+			_ => None,
+		}
+	}
+
+	pub fn unit(self) -> Position {
+		match self {
+			Direction::Left => Position::new(-1., 0.),
+			Direction::Right => Position::new(1., 0.),
+			Direction::Up => Position::new(0., 1.),
+			Direction::Down => Position::new(0., -1.),
+		}
+	}
+}
+
+// a conveyor belt or a river, carrying the bodies on it along
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Flow {
+	pub direction: Direction,
+	pub speed: f32,
+}
+
+impl Flow {
+	// the force that brings a body up to the speed of the strip, it never holds back the faster ones
+	pub fn force(&self, velocity: Position, mass: f32) -> Position {
+		let direction = self.direction.unit();
+		let lag = self.speed - velocity.dot(direction);
+		if lag > 0. {
+			direction * (lag * mass * OBSTACLE_FLOW_GRIP)
+		} else {
+			Position::new(0., 0.)
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Obstacle {
+	pub extent: Rect,
+	// walls block, zones only slow down what crosses them
+	pub solid: bool,
+	pub friction: f32,
+	pub restitution: f32,
+	// seconds a body touching the wall stays stuck to it, zero for none
+	pub sticky: f32,
+	// the share of their velocity bodies in the zone lose per second
+	pub drag: f32,
+	// a wall that can open
+	pub door: Option<Door>,
+	// a zone bodies only cross one way
+	pub gate: Option<Direction>,
+	// a zone that carries bodies along
+	pub flow: Option<Flow>,
+}
+
+impl Obstacle {
+	pub fn wall(a: Position, b: Position) -> Self {
+		Obstacle {
+			extent: Rect::from_corners(a, b),
+			solid: true,
+			friction: OBSTACLE_FRICTION,
+			restitution: OBSTACLE_RESTITUTION,
+			sticky: 0.,
+			drag: 0.,
+			door: None,
+			gate: None,
+			flow: None,
+		}
+	}
+
+	pub fn zone(a: Position, b: Position) -> Self {
+		Obstacle {
+			solid: false,
+			drag: OBSTACLE_ZONE_DRAG,
+			..Self::wall(a, b)
+		}
+	}
+
+	pub fn contains(&self, p: Position) -> bool { self.extent.contains(p) }
+
+	// open doors let everything through
+	pub fn is_blocking(&self) -> bool { self.solid && !self.door.as_ref().map_or(false, |door| door.is_open) }
+
+	// the physics keeps the body built for the other, and the segments stuck to it, when none of this changed
+	pub fn has_same_body(&self, other: &Obstacle) -> bool {
+		self.is_blocking() == other.is_blocking()
+			&& self.extent == other.extent
+			&& self.friction == other.friction
+			&& self.restitution == other.restitution
+	}
+
+	pub fn set(&mut self, property: Property, value: f32) {
+		let value = value.max(0.);
+		match property {
+			Property::Friction => self.friction = value,
+			Property::Restitution => self.restitution = value,
+			Property::Sticky => self.sticky = value,
+			Property::Drag => self.drag = value,
+		}
+	}
+
+	pub fn key(index: usize) -> agent::Key { agent::Key::with_id(index << 8 | OBSTACLE_TYPE) }
+
+	pub fn index_of(key: &agent::Key) -> Option<usize> {
+		let id = key.agent_id;
+		if id & 0xff == OBSTACLE_TYPE {
+			Some(id >> 8)
+		} else {
+			None
+		}
+	}
+}
@@ -1,5 +1,5 @@
 use backend::obj;
-use app::constants::*;
+use constants::*;
 use core::clock::Seconds;
 use core::color::Rgba;
 use core::color::Fade;
@@ -123,6 +123,13 @@ impl Emitter {
 			..Emitter::default()
 		}
 	}
+	pub fn for_teleport(transform: Transform, color: Rgba<f32>) -> Emitter {
+		Emitter {
+			transform,
+			style: EmitterStyle::color_sparkle(color, 100.),
+			..Emitter::default()
+		}
+	}
 	pub fn for_dead_minion(transform: Transform, color: Rgba<f32>) -> Emitter {
 		Emitter {
 			transform,
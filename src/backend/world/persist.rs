@@ -3,12 +3,20 @@ use std::fs;
 use std::path;
 use backend::world;
 use backend::world::agent;
+use backend::world::agent::TypedAgent;
 use backend::world::gen;
 use num_traits::FromPrimitive;
+use backend::obj::Transformable;
+use backend::raster;
+use backend::systems::CollisionRules;
+use constants::*;
 use core::geometry;
 use core::clock;
+use serde::Serialize;
 use serde_json;
+use toml;
 use serialize::base64::{self, ToBase64, FromBase64};
+use std::collections::HashSet;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Segment {
@@ -32,6 +40,8 @@ struct Agent {
 	maturity: f32,
 	phase: f32,
 	energy: f32,
+	#[serde(default)]
+	infection: f32,
 	segments: Vec<Segment>,
 }
 
@@ -42,6 +52,49 @@ pub struct Swarm {
 	agents: Vec<Agent>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Annotation {
+	x: f32,
+	y: f32,
+	target: Option<(f32, f32)>,
+	text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Probe {
+	left: f32,
+	bottom: f32,
+	right: f32,
+	top: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Obstacle {
+	left: f32,
+	bottom: f32,
+	right: f32,
+	top: f32,
+	solid: bool,
+	friction: f32,
+	restitution: f32,
+	#[serde(default)]
+	sticky: f32,
+	#[serde(default)]
+	drag: f32,
+	#[serde(default)]
+	door: Option<world::obstacle::Door>,
+	#[serde(default)]
+	gate: Option<world::obstacle::Direction>,
+	#[serde(default)]
+	flow: Option<world::obstacle::Flow>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Portal {
+	ends: [(f32, f32); 2],
+	radius: f32,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct World {
 	left: f32,
@@ -54,6 +107,365 @@ pub struct World {
 	minion_gene_pool_index: usize,
 	resource_gene_pool: Vec<String>,
 	resource_gene_pool_index: usize,
+	#[serde(default)]
+	annotations: Vec<Annotation>,
+	#[serde(default)]
+	probes: Vec<Probe>,
+	#[serde(default)]
+	obstacles: Vec<Obstacle>,
+	#[serde(default)]
+	portals: Vec<Portal>,
+	#[serde(default)]
+	topology: world::Topology,
+	#[serde(default)]
+	generated_chunks: Vec<world::chunk::ChunkKey>,
+	#[serde(default)]
+	frozen_chunks: Vec<world::chunk::ChunkKey>,
+	#[serde(default)]
+	far_field: Vec<(world::chunk::ChunkKey, world::far_field::Aggregate)>,
+	#[serde(default)]
+	thumbnail: Option<Thumbnail>,
+}
+
+// drawn by the software renderer when saving, three bytes a pixel in base64, top row first
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Thumbnail {
+	width: usize,
+	height: usize,
+	pixels: String,
+}
+
+// only the picture of a save, the rest is skipped
+#[derive(Deserialize)]
+struct Pictured {
+	#[serde(default)]
+	thumbnail: Option<Thumbnail>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Feeder {
+	x: f32,
+	y: f32,
+	rate: f64,
+}
+
+// the layout of a world without anything living in it, as built in the editor
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Scenario {
+	obstacles: Vec<Obstacle>,
+	portals: Vec<Portal>,
+	feeders: Vec<Feeder>,
+	probes: Vec<Probe>,
+	// none in the scenarios written before they had them, which keep the rules in use
+	collisions: Option<CollisionRules>,
+}
+
+// scenarios can be written by hand in TOML as well, told apart by the extension
+pub fn is_toml(file_path: &path::Path) -> bool { file_path.extension().map_or(false, |ext| ext == "toml") }
+
+fn invalid<E>(e: E) -> io::Error
+where E: Into<Box<::std::error::Error + Send + Sync>> {
+	io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+// TOML has no null, the settings left out are those that were none, and numbers that were f32 are written as such
+// rather than widened, 0.2 and not 0.20000000298023224
+fn for_toml(value: serde_json::Value) -> serde_json::Value {
+	match value {
+		serde_json::Value::Object(map) => serde_json::Value::Object(
+			map.into_iter().filter(|&(_, ref v)| !v.is_null()).map(|(k, v)| (k, for_toml(v))).collect(),
+		),
+		serde_json::Value::Array(items) => serde_json::Value::Array(items.into_iter().map(for_toml).collect()),
+		serde_json::Value::Number(ref n) if n.is_f64() => {
+			let wide = n.as_f64().unwrap_or(0.);
+			let narrow = wide as f32;
+			match narrow.to_string().parse::<f64>() {
+				Ok(short) if f64::from(narrow) == wide => serde_json::Value::from(short),
+				_ => serde_json::Value::from(wide),
+			}
+		}
+		value => value,
+	}
+}
+
+// read and written through the same values as JSON, so that the variants with data, like door triggers, are tables
+// keyed by their name in both
+pub fn toml_to_json(text: &str) -> Result<serde_json::Value, toml::de::Error> {
+	let value: toml::Value = toml::from_str(text)?;
+	Ok(serde_json::to_value(value).unwrap_or(serde_json::Value::Null))
+}
+
+fn read_scenario(file_path: &path::Path) -> io::Result<Scenario> {
+	let text = fs::read_to_string(file_path)?;
+	if is_toml(file_path) {
+		let value = toml_to_json(&text).map_err(invalid)?;
+		Ok(serde_json::from_value(value)?)
+	} else {
+		Ok(serde_json::from_str(&text)?)
+	}
+}
+
+fn write_scenario(file_path: &path::Path, scenario: &Scenario) -> io::Result<()> {
+	if is_toml(file_path) {
+		// a value puts the plain keys of each table before its subtables, as TOML needs
+		let value = for_toml(serde_json::to_value(scenario)?);
+		let text = toml::Value::try_from(value).and_then(|value| toml::to_string(&value)).map_err(invalid)?;
+		fs::write(file_path, text)
+	} else {
+		let out_file = fs::File::create(file_path)?;
+		serde_json::to_writer_pretty(out_file, scenario)?;
+		Ok(())
+	}
+}
+
+// the agents of a frozen chunk of an unbounded world, with their type
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Chunk {
+	agents: Vec<(usize, Agent)>,
+}
+
+fn serialize_agent(src: &world::agent::Agent) -> Agent {
+	let body = &src.segments[0];
+
+	Agent {
+		id: src.id(),
+		x: body.transform.position.x,
+		y: body.transform.position.y,
+		angle: body.transform.angle,
+		vx: body.motion.velocity.x,
+		vy: body.motion.velocity.y,
+		spin: body.motion.spin,
+		dna: src.dna().to_base64(base64::STANDARD),
+		age_seconds: body.state.age_seconds().into(),
+		age_frames: body.state.age_frames(),
+		maturity: body.state.maturity(),
+		flags: src.state.flags().bits(),
+		phase: src.state.phase(),
+		energy: src.state.energy(),
+		infection: src.state.infection(),
+		segments: src.segments().iter().map(|s| serialize_segment(s)).collect(),
+	}
+}
+
+fn serialize_segment(src: &world::segment::Segment) -> Segment {
+	Segment {
+		charge: src.state.charge(),
+		target_charge: src.state.target_charge(),
+	}
+}
+
+fn restore_agent(src_agent: &Agent, swarm: &mut world::swarm::Swarm, timer: &clock::SimulationTimer) -> Option<usize> {
+	let dna = src_agent.dna.from_base64().ok()?;
+	let id = swarm.rebuild(src_agent.id, &mut gen::Genome::new(dna), agent::InitialState {
+		transform: geometry::Transform::from_components(src_agent.x, src_agent.y, src_agent.angle),
+		motion: geometry::Motion::from_components(src_agent.vx, src_agent.vy, src_agent.spin),
+		age_seconds: clock::seconds(src_agent.age_seconds),
+		age_frames: src_agent.age_frames,
+		maturity: Some(src_agent.maturity),
+		..Default::default()
+	}, timer);
+	let agent = swarm.get_mut(id)?;
+	agent.state.restore(src_agent.flags, src_agent.phase, src_agent.energy);
+	agent.state.infect(src_agent.infection);
+
+	for (src_segment, dest_segment) in src_agent.segments.iter().zip(agent.segments_mut().iter_mut()) {
+		dest_segment.state.restore(src_segment.charge, src_segment.target_charge);
+	};
+	Some(id)
+}
+
+fn serialize_probe(src: &world::probe::Probe) -> Probe {
+	Probe {
+		left: src.extent.min.x,
+		bottom: src.extent.min.y,
+		right: src.extent.max.x,
+		top: src.extent.max.y,
+	}
+}
+
+fn serialize_obstacle(src: &world::obstacle::Obstacle) -> Obstacle {
+	Obstacle {
+		left: src.extent.min.x,
+		bottom: src.extent.min.y,
+		right: src.extent.max.x,
+		top: src.extent.max.y,
+		solid: src.solid,
+		friction: src.friction,
+		restitution: src.restitution,
+		sticky: src.sticky,
+		drag: src.drag,
+		door: src.door.clone(),
+		gate: src.gate,
+		flow: src.flow,
+	}
+}
+
+fn serialize_portal(src: &world::portal::Portal) -> Portal {
+	Portal {
+		ends: [(src.ends[0].x, src.ends[0].y), (src.ends[1].x, src.ends[1].y)],
+		radius: src.radius,
+	}
+}
+
+fn restore_probe(p: &Probe) -> world::probe::Probe {
+	world::probe::Probe::new(geometry::Position::new(p.left, p.bottom), geometry::Position::new(p.right, p.top))
+}
+
+fn restore_obstacle(o: &Obstacle) -> world::obstacle::Obstacle {
+	world::obstacle::Obstacle {
+		extent: geometry::Rect::new(o.left, o.bottom, o.right, o.top),
+		solid: o.solid,
+		friction: o.friction,
+		restitution: o.restitution,
+		sticky: o.sticky,
+		drag: o.drag,
+		door: o.door.clone(),
+		gate: o.gate,
+		flow: o.flow,
+	}
+}
+
+fn restore_portal(p: &Portal) -> world::portal::Portal {
+	world::portal::Portal {
+		ends: [geometry::Position::new(p.ends[0].0, p.ends[0].1), geometry::Position::new(p.ends[1].0, p.ends[1].1)],
+		radius: p.radius,
+	}
+}
+
+fn serialize_feeder(src: &world::Feeder) -> Feeder {
+	let position = src.transform().position;
+	Feeder {
+		x: position.x,
+		y: position.y,
+		rate: src.rate().get(),
+	}
+}
+
+fn restore_feeder(f: &Feeder) -> world::Feeder { world::Feeder::new(f.x, f.y, clock::seconds(f.rate)) }
+
+// compared as they would be written, so that rewriting the same settings or layout changes nothing
+pub fn differs<T>(a: &T, b: &T) -> bool
+where T: Serialize {
+	serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+}
+
+fn is_base64(text: &str) -> bool { text.from_base64().map_or(false, |dna| !dna.is_empty()) }
+
+fn repair_gene_pool(name: &str, pool: &mut Vec<String>, index: &mut usize, repairs: &mut Vec<String>) {
+	let before = pool.len();
+	pool.retain(|dna| is_base64(dna));
+	if pool.len() < before {
+		repairs.push(format!("{} unreadable genomes dropped from the {} gene pool", before - pool.len(), name));
+	}
+	if *index >= pool.len() {
+		repairs.push(format!("{} gene pool index {} reset, past the end of the pool", name, index));
+		*index = 0;
+	}
+}
+
+// puts right what the restore would choke on later, the agents past saving are dropped
+fn repair_agent(agent: &mut Agent, repairs: &mut Vec<String>) -> bool {
+	let id = agent.id;
+	if !(agent.x.is_finite() && agent.y.is_finite() && agent.angle.is_finite()) {
+		repairs.push(format!("{} dropped, nowhere to be put at {}, {}", id, agent.x, agent.y));
+		return false;
+	}
+	if !agent.energy.is_finite() {
+		repairs.push(format!("{} dropped, its energy is {}", id, agent.energy));
+		return false;
+	}
+	if !is_base64(&agent.dna) {
+		repairs.push(format!("{} dropped, its genome is unreadable", id));
+		return false;
+	}
+	if !(agent.vx.is_finite() && agent.vy.is_finite() && agent.spin.is_finite()) {
+		repairs.push(format!("{} stopped, it was moving at {}, {}", id, agent.vx, agent.vy));
+		agent.vx = 0.;
+		agent.vy = 0.;
+		agent.spin = 0.;
+	}
+	if !(agent.maturity.is_finite() && agent.phase.is_finite() && agent.infection.is_finite()) {
+		repairs.push(format!("{} grown up, healed and put in phase", id));
+		agent.maturity = 1.;
+		agent.phase = 0.;
+		agent.infection = 0.;
+	}
+	if agent.segments.iter().any(|segment| !(segment.charge.is_finite() && segment.target_charge.is_finite())) {
+		repairs.push(format!("{} discharged, some of its segments had no charge to tell", id));
+		for segment in &mut agent.segments {
+			segment.charge = 0.;
+			segment.target_charge = 0.;
+		}
+	}
+	true
+}
+
+fn repair_swarm(swarm: &mut Swarm, agent_type: agent::AgentType, repairs: &mut Vec<String>) {
+	let mut ids = HashSet::new();
+	// the same genome at the same spot is the same agent twice, their bodies would blow each other apart
+	let mut spots = HashSet::new();
+	let agents = swarm.agents.drain(..).collect::<Vec<_>>();
+	for mut agent in agents {
+		if agent.id.type_of() != agent_type {
+			repairs.push(format!("{} dropped, its id is not of the {} swarm", agent.id, agent_type));
+		} else if !ids.insert(agent.id) {
+			repairs.push(format!("{} dropped, the id is taken", agent.id));
+		} else if repair_agent(&mut agent, repairs) {
+			let cell = |v: f32| (v / SAVE_DUPLICATE_DISTANCE).round() as i64;
+			if spots.insert((agent.dna.clone(), cell(agent.x), cell(agent.y))) {
+				swarm.agents.push(agent);
+			} else {
+				repairs.push(format!("{} dropped, a duplicate on top of another", agent.id));
+			}
+		}
+	}
+	// new agents would be given the ids of those saved otherwise
+	let last = swarm.agents.iter().map(|agent| agent.id >> 8).max().unwrap_or(0);
+	if last > swarm.seq {
+		repairs.push(format!("next id of the {} swarm moved from {} past {}", agent_type, swarm.seq, last));
+		swarm.seq = last;
+	}
+}
+
+// what was repaired, or why the save can't be used at all, one line each
+fn repair(src: &mut World) -> Result<Vec<String>, Vec<String>> {
+	let mut repairs = Vec::new();
+	let extent = [src.left, src.bottom, src.right, src.top];
+	if extent.iter().any(|v| !v.is_finite()) || src.left >= src.right || src.bottom >= src.top {
+		return Err(vec![format!("the extent {:?} holds nothing", extent)]);
+	}
+	let before = src.swarms.iter().map(|swarm| swarm.agents.len()).sum::<usize>();
+	src.swarms.retain(|swarm| {
+		let known = agent::AgentType::from_usize(swarm.agent_type).is_some();
+		if !known {
+			repairs.push(format!("{} agents of the unknown type {} dropped", swarm.agents.len(), swarm.agent_type));
+		}
+		known
+	});
+	for swarm in &mut src.swarms {
+		if let Some(agent_type) = agent::AgentType::from_usize(swarm.agent_type) {
+			repair_swarm(swarm, agent_type, &mut repairs);
+		}
+	}
+	repair_gene_pool("minion", &mut src.minion_gene_pool, &mut src.minion_gene_pool_index, &mut repairs);
+	repair_gene_pool("resource", &mut src.resource_gene_pool, &mut src.resource_gene_pool_index, &mut repairs);
+
+	let mut refused = Vec::new();
+	if src.minion_gene_pool.is_empty() || src.resource_gene_pool.is_empty() {
+		refused.push("no genome left in a gene pool to populate the world with".to_owned());
+	}
+	let after = src.swarms.iter().map(|swarm| swarm.agents.len()).sum::<usize>();
+	let dropped = before - after;
+	if dropped as f32 > before as f32 * SAVE_REPAIR_MAX_DROPPED {
+		refused.push(format!("{} of {} agents would be dropped, too many to call it the same world", dropped, before));
+	}
+	if refused.is_empty() {
+		Ok(repairs)
+	} else {
+		refused.extend(repairs);
+		Err(refused)
+	}
 }
 
 pub struct Serializer;
@@ -68,32 +480,12 @@ impl Serializer {
 			}
 		}
 
-		fn serialize_agent(src: &world::agent::Agent) -> Agent {
-			let body = &src.segments[0];
-
-			Agent {
-				id: src.id(),
-				x: body.transform.position.x,
-				y: body.transform.position.y,
-				angle: body.transform.angle,
-				vx: body.motion.velocity.x,
-				vy: body.motion.velocity.y,
-				spin: body.motion.spin,
-				dna: src.dna().to_base64(base64::STANDARD),
-				age_seconds: body.state.age_seconds().into(),
-				age_frames: body.state.age_frames(),
-				maturity: body.state.maturity(),
-				flags: src.state.flags().bits(),
-				phase: src.state.phase(),
-				energy: src.state.energy(),
-				segments: src.segments().iter().map(|s| serialize_segment(s)).collect(),
-			}
-		}
-
-		fn serialize_segment(src: &world::segment::Segment) -> Segment {
-			Segment {
-				charge: src.state.charge(),
-				target_charge: src.state.target_charge(),
+		fn serialize_annotation(src: &world::annotation::Annotation) -> Annotation {
+			Annotation {
+				x: src.position.x,
+				y: src.position.y,
+				target: src.target.map(|t| (t.x, t.y)),
+				text: src.text.clone(),
 			}
 		}
 
@@ -120,6 +512,15 @@ impl Serializer {
 			minion_gene_pool_index: world.minion_gene_pool.gene_pool_index(),
 			resource_gene_pool,
 			resource_gene_pool_index: world.resource_gene_pool.gene_pool_index(),
+			annotations: world.annotations().iter().map(serialize_annotation).collect(),
+			probes: world.probes().iter().map(serialize_probe).collect(),
+			obstacles: world.obstacles().iter().map(serialize_obstacle).collect(),
+			portals: world.portals().iter().map(serialize_portal).collect(),
+			topology: world.topology(),
+			generated_chunks: world.chunks.generated.iter().cloned().collect(),
+			frozen_chunks: world.chunks.frozen.iter().cloned().collect(),
+			far_field: world.chunks.far.iter().map(|(&key, &aggregate)| (key, aggregate)).collect(),
+			thumbnail: None,
 		}
 	}
 
@@ -130,34 +531,32 @@ impl Serializer {
 		world.extent.max.x = src.right;
 		world.extent.max.y = src.top;
 		world.regenerations = src.regenerations;
+		world.set_topology(src.topology);
+		world.chunks.generated = src.generated_chunks.iter().cloned().collect();
+		world.chunks.frozen = src.frozen_chunks.iter().cloned().collect();
+		world.chunks.far = src.far_field.iter().cloned().collect();
+		world.chunks.restart_far_field(world.seconds().get());
 
 		world.minion_gene_pool.populate_from_base64(&src.minion_gene_pool, src.minion_gene_pool_index);
 		world.resource_gene_pool.populate_from_base64(&src.resource_gene_pool, src.resource_gene_pool_index);
 
+		*world.annotations_mut() = src.annotations.iter()
+			.map(|a| world::annotation::Annotation::new(
+				geometry::Position::new(a.x, a.y),
+				a.target.map(|(x, y)| geometry::Position::new(x, y)),
+				&a.text))
+			.collect();
+		*world.probes_mut() = src.probes.iter().map(restore_probe).collect();
+		*world.obstacles_mut() = src.obstacles.iter().map(restore_obstacle).collect();
+		*world.portals_mut() = src.portals.iter().map(restore_portal).collect();
+
 		let mut registered = Vec::new();
 		for src_swarm in &src.swarms {
 			if let Some(agent_type) = agent::AgentType::from_usize(src_swarm.agent_type) {
 				let swarm = world.swarm_mut(&agent_type);
 				swarm.reset(src_swarm.seq);
 				for src_agent in &src_swarm.agents {
-					if let Ok(dna) = src_agent.dna.from_base64() {
-						let id = swarm.rebuild(src_agent.id, &mut gen::Genome::new(dna), agent::InitialState {
-							transform: geometry::Transform::from_components(src_agent.x, src_agent.y, src_agent.angle),
-							motion: geometry::Motion::from_components(src_agent.vx, src_agent.vy, src_agent.spin),
-							age_seconds: clock::seconds(src_agent.age_seconds),
-							age_frames: src_agent.age_frames,
-							maturity: Some(src_agent.maturity),
-							..Default::default()
-						}, &timer);
-						if let Some(agent) = swarm.get_mut(id) {
-							agent.state.restore(src_agent.flags, src_agent.phase, src_agent.energy);
-
-							for (src_segment, dest_segment) in src_agent.segments.iter().zip(agent.segments_mut().iter_mut()) {
-								dest_segment.state.restore(src_segment.charge, src_segment.target_charge);
-							};
-							registered.push(id);
-						}
-					}
+					registered.extend(restore_agent(src_agent, swarm, &timer));
 				}
 			}
 		}
@@ -182,15 +581,343 @@ impl Serializer {
 
 	pub fn save(file_path: &path::Path, world: &world::World) -> io::Result<()> {
 		let out_file = fs::File::create(file_path)?;
-		let s_world = Self::save_snapshot(world);
+		let mut s_world = Self::save_snapshot(world);
+		let frame = raster::thumbnail(world, SAVE_THUMBNAIL_WIDTH);
+		s_world.thumbnail = Some(Thumbnail {
+			width: frame.width,
+			height: frame.height,
+			pixels: frame.pixels.to_base64(base64::STANDARD),
+		});
 		serde_json::to_writer_pretty(out_file, &s_world)?;
 		Ok(())
 	}
 
+	// adds to what's already frozen there
+	pub fn save_chunk(file_path: &path::Path, agents: &[world::agent::Agent]) -> io::Result<()> {
+		let mut chunk: Chunk = match fs::File::open(file_path) {
+			Ok(in_file) => serde_json::from_reader(in_file)?,
+			Err(_) => Chunk::default(),
+		};
+		chunk.agents.extend(agents.iter().map(|agent| (agent.id().type_of() as usize, serialize_agent(agent))));
+		let out_file = fs::File::create(file_path)?;
+		serde_json::to_writer(out_file, &chunk)?;
+		Ok(())
+	}
+
+	// the ids of the agents back in the world
+	pub fn load_chunk(file_path: &path::Path, world: &mut world::World) -> io::Result<Vec<usize>> {
+		let in_file = fs::File::open(file_path)?;
+		let chunk: Chunk = serde_json::from_reader(in_file)?;
+		let timer = world.clock.clone();
+		let mut restored = Vec::new();
+		for (agent_type, src_agent) in &chunk.agents {
+			if let Some(agent_type) = agent::AgentType::from_usize(*agent_type) {
+				restored.extend(restore_agent(src_agent, world.swarm_mut(&agent_type), &timer));
+			}
+		}
+		Ok(restored)
+	}
+
+	pub fn save_scenario(file_path: &path::Path, world: &world::World, collisions: &CollisionRules) -> io::Result<()> {
+		let scenario = Scenario {
+			obstacles: world.obstacles().iter().map(serialize_obstacle).collect(),
+			portals: world.portals().iter().map(serialize_portal).collect(),
+			feeders: world.feeders().iter().map(serialize_feeder).collect(),
+			probes: world.probes().iter().map(serialize_probe).collect(),
+			collisions: Some(collisions.clone()),
+		};
+		write_scenario(file_path, &scenario)
+	}
+
+	// only replaces what differs from the current layout and collision rules, and tells what that was
+	pub fn reload_scenario(
+		file_path: &path::Path,
+		world: &mut world::World,
+		collisions: &mut CollisionRules,
+	) -> io::Result<Vec<&'static str>>
+	{
+		let scenario = read_scenario(file_path)?;
+		let obstacles: Vec<_> = world.obstacles().iter().map(serialize_obstacle).collect();
+		let portals: Vec<_> = world.portals().iter().map(serialize_portal).collect();
+		let feeders: Vec<_> = world.feeders().iter().map(serialize_feeder).collect();
+		let probes: Vec<_> = world.probes().iter().map(serialize_probe).collect();
+		let mut changed = Vec::new();
+		if differs(&scenario.obstacles, &obstacles) {
+			*world.obstacles_mut() = scenario.obstacles.iter().map(restore_obstacle).collect();
+			changed.push("obstacles");
+		}
+		if differs(&scenario.portals, &portals) {
+			*world.portals_mut() = scenario.portals.iter().map(restore_portal).collect();
+			changed.push("portals");
+		}
+		// the feeders drift, so the same ones keep going from where they are with the new rates
+		if scenario.feeders.len() != feeders.len() {
+			*world.feeders_mut() = scenario.feeders.iter().map(restore_feeder).collect();
+			changed.push("feeders");
+		} else if scenario.feeders.iter().zip(feeders.iter()).any(|(a, b)| a.rate != b.rate) {
+			for (feeder, src) in world.feeders_mut().iter_mut().zip(scenario.feeders.iter()) {
+				feeder.set_rate(clock::seconds(src.rate));
+			}
+			changed.push("feeder rates");
+		}
+		// the probes keep their history when left alone
+		if differs(&scenario.probes, &probes) {
+			*world.probes_mut() = scenario.probes.iter().map(restore_probe).collect();
+			changed.push("probes");
+		}
+		if let Some(rules) = scenario.collisions {
+			if differs(&rules, collisions) {
+				*collisions = rules;
+				changed.push("collisions");
+			}
+		}
+		Ok(changed)
+	}
+
+	// replaces the layout, the agents stay where they are, and the collision rules with those of the scenario if it
+	// has any
+	pub fn load_scenario(
+		file_path: &path::Path,
+		world: &mut world::World,
+		collisions: &mut CollisionRules,
+	) -> io::Result<()>
+	{
+		let scenario = read_scenario(file_path)?;
+		*world.obstacles_mut() = scenario.obstacles.iter().map(restore_obstacle).collect();
+		*world.portals_mut() = scenario.portals.iter().map(restore_portal).collect();
+		*world.feeders_mut() = scenario.feeders.iter().map(restore_feeder).collect();
+		*world.probes_mut() = scenario.probes.iter().map(restore_probe).collect();
+		if let Some(rules) = scenario.collisions {
+			*collisions = rules;
+		}
+		Ok(())
+	}
+
+	// none for the saves written before they had one
+	pub fn load_thumbnail(file_path: &path::Path) -> io::Result<Option<raster::Frame>> {
+		let in_file = io::BufReader::new(fs::File::open(file_path)?);
+		let pictured: Pictured = serde_json::from_reader(in_file)?;
+		match pictured.thumbnail {
+			Some(thumbnail) => {
+				let pixels = thumbnail.pixels
+					.from_base64()
+					.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+				if pixels.len() != thumbnail.width * thumbnail.height * 3 {
+					return Err(io::Error::new(io::ErrorKind::InvalidData, "thumbnail size mismatch"));
+				}
+				Ok(Some(raster::Frame {
+					width: thumbnail.width,
+					height: thumbnail.height,
+					pixels,
+				}))
+			}
+			None => Ok(None),
+		}
+	}
+
+	// repairs what it can of a damaged save, and refuses one that can't be, with the reasons
 	pub fn load(file_path: &path::Path, world: &mut world::World) -> io::Result<()> {
 		let in_file = fs::File::open(file_path)?;
-		let src = serde_json::from_reader(in_file)?;
+		let mut src = serde_json::from_reader(in_file)?;
+		match repair(&mut src) {
+			Ok(repairs) => for line in &repairs {
+				warn!("{:?} repaired: {}", file_path, line);
+			},
+			Err(problems) => {
+				let report = problems.join("; ");
+				error!("{:?} refused: {}", file_path, report);
+				return Err(io::Error::new(io::ErrorKind::InvalidData, report));
+			}
+		}
 		Self::restore_snapshot(&src, world);
 		Ok(())
 	}
+
+	// what loading a save would repair, or why it would refuse it, without loading it
+	pub fn check(file_path: &path::Path) -> io::Result<Result<Vec<String>, Vec<String>>> {
+		let in_file = fs::File::open(file_path)?;
+		let mut src = serde_json::from_reader(in_file)?;
+		Ok(repair(&mut src))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use backend::systems::physics::Category;
+	use backend::world::obstacle::{Direction, Door, Flow, Obstacle as WorldObstacle, Trigger};
+	use core::geometry::Position;
+	use core::resource::filesystem::ResourceLoaderBuilder;
+
+	const MINIONS: usize = 4;
+
+	// a save of a few minions with nothing wrong with it
+	fn snapshot() -> World {
+		let resources = ResourceLoaderBuilder::new().add(path::Path::new("resources")).build();
+		let mut world = world::World::new(&resources, DEFAULT_MINION_GENE_POOL_FILE);
+		for i in 0..MINIONS {
+			world.new_minion(geometry::Position::new(20. * i as f32, 0.), geometry::Motion::default());
+		}
+		Serializer::save_snapshot(&world)
+	}
+
+	fn minions(src: &mut World) -> &mut Vec<Agent> {
+		let minion_type = agent::AgentType::Minion as usize;
+		&mut src.swarms.iter_mut().find(|swarm| swarm.agent_type == minion_type).unwrap().agents
+	}
+
+	// a layout with a bit of everything a scenario holds
+	fn layout() -> world::World {
+		let resources = ResourceLoaderBuilder::new().add(path::Path::new("resources")).build();
+		let mut world = world::World::new(&resources, DEFAULT_MINION_GENE_POOL_FILE);
+		let mut door = WorldObstacle::wall(Position::new(-5., 0.), Position::new(5., 1.));
+		door.door = Some(Door::new(Trigger::Timer { open: 2., closed: 3. }));
+		let mut strip = WorldObstacle::zone(Position::new(0., 10.), Position::new(20., 14.));
+		strip.flow = Some(Flow {
+			direction: Direction::Left,
+			speed: 4.,
+		});
+		*world.obstacles_mut() = vec![door, strip];
+		world.portals_mut().push(world::portal::Portal::new(Position::new(-30., 0.), Position::new(30., 0.)));
+		world.probes_mut().push(world::probe::Probe::new(Position::new(0., 0.), Position::new(10., 10.)));
+		world
+	}
+
+	fn copy_of(agent: &Agent) -> Agent { serde_json::from_value(serde_json::to_value(agent).unwrap()).unwrap() }
+
+	#[test]
+	fn a_sound_save_needs_no_repair() {
+		let mut src = snapshot();
+		assert_eq!(repair(&mut src), Ok(Vec::new()));
+		assert_eq!(minions(&mut src).len(), MINIONS);
+	}
+
+	#[test]
+	fn agents_with_no_position_are_dropped() {
+		let mut src = snapshot();
+		minions(&mut src)[0].x = ::std::f32::NAN;
+		let repairs = repair(&mut src).unwrap();
+		assert_eq!(repairs.len(), 1);
+		assert!(repairs[0].contains("nowhere to be put"));
+		assert!(minions(&mut src).iter().all(|agent| agent.x.is_finite()));
+		assert_eq!(minions(&mut src).len(), MINIONS - 1);
+	}
+
+	#[test]
+	fn agents_with_no_motion_are_stopped() {
+		let mut src = snapshot();
+		minions(&mut src)[0].vx = ::std::f32::INFINITY;
+		assert_eq!(repair(&mut src).unwrap().len(), 1);
+		assert_eq!(minions(&mut src).len(), MINIONS);
+		assert_eq!(minions(&mut src)[0].vx, 0.);
+	}
+
+	#[test]
+	fn a_taken_id_drops_the_second_agent() {
+		let mut src = snapshot();
+		let mut twin = copy_of(&minions(&mut src)[0]);
+		twin.x += 100.;
+		let id = twin.id;
+		minions(&mut src).push(twin);
+		let repairs = repair(&mut src).unwrap();
+		assert_eq!(repairs, vec![format!("{} dropped, the id is taken", id)]);
+		assert_eq!(minions(&mut src).len(), MINIONS);
+	}
+
+	#[test]
+	fn a_copy_on_top_of_another_is_dropped() {
+		let mut src = snapshot();
+		let mut copy = copy_of(&minions(&mut src)[0]);
+		// an id of its own, past those in use
+		copy.id = minions(&mut src).iter().map(|agent| agent.id).max().unwrap() + (1 << 8);
+		let id = copy.id;
+		minions(&mut src).push(copy);
+		let repairs = repair(&mut src).unwrap();
+		assert_eq!(repairs, vec![format!("{} dropped, a duplicate on top of another", id)]);
+		assert_eq!(minions(&mut src).len(), MINIONS);
+	}
+
+	#[test]
+	fn a_gene_pool_index_past_the_end_is_reset() {
+		let mut src = snapshot();
+		src.minion_gene_pool_index = src.minion_gene_pool.len();
+		let repairs = repair(&mut src).unwrap();
+		assert_eq!(repairs.len(), 1);
+		assert!(repairs[0].starts_with("minion gene pool index"));
+		assert_eq!(src.minion_gene_pool_index, 0);
+	}
+
+	#[test]
+	fn an_empty_gene_pool_is_refused() {
+		let mut src = snapshot();
+		for dna in &mut src.resource_gene_pool {
+			*dna = "not base64!".to_owned();
+		}
+		assert!(repair(&mut src).is_err());
+	}
+
+	#[test]
+	fn a_world_with_no_extent_is_refused() {
+		let mut src = snapshot();
+		src.right = src.left;
+		assert!(repair(&mut src).is_err());
+	}
+
+	#[test]
+	fn half_the_agents_can_be_dropped() {
+		let mut src = snapshot();
+		let dropped = (MINIONS as f32 * SAVE_REPAIR_MAX_DROPPED) as usize;
+		for agent in minions(&mut src).iter_mut().take(dropped) {
+			agent.energy = ::std::f32::NAN;
+		}
+		assert_eq!(repair(&mut src).unwrap().len(), dropped);
+	}
+
+	#[test]
+	fn more_than_half_the_agents_dropped_is_refused() {
+		let mut src = snapshot();
+		let dropped = (MINIONS as f32 * SAVE_REPAIR_MAX_DROPPED) as usize + 1;
+		for agent in minions(&mut src).iter_mut().take(dropped) {
+			agent.energy = ::std::f32::NAN;
+		}
+		let refused = repair(&mut src).unwrap_err();
+		assert!(refused[0].starts_with(&format!("{} of {} agents would be dropped", dropped, MINIONS)));
+		// the repairs follow the reasons
+		assert_eq!(refused.len(), 1 + dropped);
+	}
+
+	#[test]
+	fn scenarios_read_back_the_same_in_json_and_toml() {
+		let world = layout();
+		let mut collisions = CollisionRules::default();
+		collisions.ghosts.push(Category::Resources);
+		let dir = ::std::env::temp_dir().join("rust-oids-test-scenarios");
+		fs::create_dir_all(&dir).unwrap();
+		for name in &["layout.json", "layout.toml"] {
+			let file_path = dir.join(name);
+			Serializer::save_scenario(&file_path, &world, &collisions).unwrap();
+			let mut loaded = layout();
+			loaded.obstacles_mut().clear();
+			loaded.portals_mut().clear();
+			loaded.probes_mut().clear();
+			let mut loaded_collisions = CollisionRules::default();
+			Serializer::load_scenario(&file_path, &mut loaded, &mut loaded_collisions).unwrap();
+			let (saved, loaded) = (Serializer::save_snapshot(&world), Serializer::save_snapshot(&loaded));
+			assert!(!differs(&saved.obstacles, &loaded.obstacles));
+			assert!(!differs(&saved.portals, &loaded.portals));
+			assert!(!differs(&saved.probes, &loaded.probes));
+			assert!(!differs(&collisions, &loaded_collisions));
+			fs::remove_file(file_path).unwrap();
+		}
+	}
+
+	#[test]
+	fn scenarios_can_be_written_by_hand_in_toml() {
+		let text = "[[obstacles]]\nleft = -10.0\nbottom = 0.0\nright = 10.0\ntop = 1.0\nsolid = true\nfriction = 0.5\n\
+			restitution = 0.1\n\n[[feeders]]\nx = 0.0\ny = 20.0\nrate = 1.5\n";
+		let scenario: Scenario = serde_json::from_value(toml_to_json(text).unwrap()).unwrap();
+		assert_eq!(scenario.obstacles.len(), 1);
+		assert_eq!(scenario.feeders[0].rate, 1.5);
+		assert!(scenario.collisions.is_none());
+	}
 }
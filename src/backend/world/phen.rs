@@ -1,6 +1,6 @@
 use backend::obj::*;
 use std::f32::consts;
-use app::constants::*;
+use constants::*;
 use core::color;
 use core::color::ToRgb;
 use core::geometry::*;
@@ -27,10 +27,19 @@ pub fn phenotype_of(agent_type: agent::AgentType) -> Box<Phenotype> {
 		agent::AgentType::Minion => Box::new(Minion {}),
 		agent::AgentType::Spore => Box::new(Spore {}),
 		agent::AgentType::Player => Box::new(Player {}),
+		agent::AgentType::Plant => Box::new(Plant {}),
 		_ => Box::new(Resource {}),
 	}
 }
 
+// the species picks one of a few hues far apart, the tint gene a shade of it, the same whenever the genome develops
+fn species_albedo(dna: &[u8], tint: f32) -> color::Rgba {
+	let slot = (species_key(dna) % SPECIES_HUES) as f32;
+	let hue = (slot + 0.5 + (tint - 0.5) * SPECIES_HUE_SPREAD) / SPECIES_HUES as f32;
+	let [r, g, b] = color::equiluminant(hue, SPECIES_LUMA, SPECIES_CHROMA);
+	[r, g, b, 1.]
+}
+
 struct Resource;
 
 struct Minion;
@@ -39,6 +48,8 @@ struct Player;
 
 struct Spore;
 
+struct Plant;
+
 impl Phenotype for Resource {
 	fn develop(&self, gen: &mut Genome, id: Id, initial_state: agent::InitialState, timer: &Timer) -> agent::Agent {
 		gen.next_integer::<u8>(0, 3);
@@ -63,6 +74,40 @@ impl Phenotype for Resource {
 	}
 }
 
+impl Phenotype for Plant {
+	fn develop(&self, gen: &mut Genome, id: Id, initial_state: agent::InitialState, timer: &Timer) -> agent::Agent {
+		// the greens, from yellowish to bluish
+		let tint = gen.next_float(0.2, 0.45);
+		let albedo = color::Hsl::new(tint, 0.6, 0.4);
+		let mut builder = AgentBuilder::new(
+			id,
+			Material {
+				density: DENSITY_RESOURCE,
+				..Default::default()
+			},
+			Livery {
+				albedo: albedo.to_rgba(),
+				..Default::default()
+			},
+			gen.dna_cloned(),
+			segment::State::with_charge(initial_state.charge, 0., seconds(DEFAULT_CHARGE_DECAY_TIME)),
+		);
+		let stem = gen.star();
+		let torso = builder
+			.maturity(initial_state.maturity.unwrap_or(MATURITY_DEFAULT))
+			.start(initial_state.transform, initial_state.motion, &stem)
+			.index();
+		// leaves in pairs, more of them catch more light and cost more to keep
+		for i in 0..gen.next_integer(1, PLANT_LEAF_PAIRS_MAX) {
+			let leaf = gen.iso_triangle();
+			builder
+				.addr(torso, i as isize + 1, &leaf, Flags::empty())
+				.addl(torso, -(i as isize) - 1, &leaf, Flags::empty());
+		}
+		builder.build(timer)
+	}
+}
+
 impl Phenotype for Player {
 	fn develop(&self, gen: &mut Genome, id: Id, initial_state: agent::InitialState, timer: &Timer) -> agent::Agent {
 		let albedo = color::YPbPr::new(0.5, 0., 0.);
@@ -95,7 +140,7 @@ impl Phenotype for Minion {
 	fn develop(&self, gen: &mut Genome, id: Id, initial_state: agent::InitialState, timer: &Timer) -> agent::Agent {
 		let gender = gen.next_integer::<u8>(0, 3);
 		let tint = gen.next_float(0., 1.);
-		let albedo = color::Hsl::new(tint, 0.5, 0.5);
+		let albedo = species_albedo(gen.dna(), tint);
 		let charge = initial_state.charge;
 		let mut builder = AgentBuilder::new(
 			id,
@@ -104,7 +149,7 @@ impl Phenotype for Minion {
 				..Default::default()
 			},
 			Livery {
-				albedo: albedo.to_rgba(),
+				albedo,
 				..Default::default()
 			},
 			gen.dna_cloned(),
@@ -179,6 +224,7 @@ impl Phenotype for Minion {
 
 		let mut belly = torso;
 		let mut belly_mid = torso_shape.mid();
+		let mut trunk = vec![torso];
 		while gen.next_integer(0, 3) == 0 {
 			let belly_shape = gen.any_poly();
 
@@ -191,6 +237,7 @@ impl Phenotype for Minion {
 				)
 				.index();
 			belly_mid = belly_shape.mid();
+			trunk.push(belly);
 			if belly_shape.length() > 6 {
 				if gen.next_integer(0, 1) == 0 {
 					builder.addr(
@@ -214,7 +261,10 @@ impl Phenotype for Minion {
 			}
 		}
 		let leg_shape = gen.star();
+		// read last so that older genomes keep their body plan
+		let resistance = gen.next_float(0., 1.);
 		builder
+			.resistance(resistance)
 			.addr(
 				belly,
 				belly_mid - 1,
@@ -232,8 +282,45 @@ impl Phenotype for Minion {
 				belly_mid,
 				&tail_shape,
 				Flags::TAIL | Flags::ACTUATOR | Flags::BRAKE,
-			)
-			.build(timer)
+			);
+
+		// extra limb modules sprouting from the trunk, mirrored or repeated all around, from genes of their own that the
+		// genomes from before them don't have
+		let has_limb_genes = gen.seek(GENOME_LIMB_GENES_AT, GENOME_LIMB_GENES_BYTES);
+		let mut modules = 0;
+		while has_limb_genes && modules < MINION_MAX_LIMB_MODULES && gen.next_integer(0, 2) == 0 {
+			let parent = trunk[gen.next_integer(0, trunk.len() - 1)];
+			let symmetry = match gen.next_integer(0, 2) {
+				0 => Symmetry::Radial(gen.next_integer(3, 5)),
+				_ => Symmetry::Bilateral,
+			};
+			let shape = match gen.next_integer(0, 2) {
+				0 => gen.triangle(),
+				1 => gen.vbar(),
+				_ => gen.star(),
+			};
+			// radial limbs have no side, so they can push but not steer
+			let role = match (gen.next_integer(0, 2), symmetry) {
+				(0, Symmetry::Bilateral) => Flags::ARM | Flags::ACTUATOR | Flags::RUDDER,
+				(0, Symmetry::Radial(_)) | (1, _) => Flags::LEG | Flags::ACTUATOR | Flags::THRUSTER,
+				_ => Flags::ARM,
+			};
+			let joint = if gen.next_bool() { Flags::JOINT } else { Flags::empty() };
+			let length = gen.next_integer(1, 3);
+			let offset = gen.next_integer(1, 3);
+			builder.limbs(
+				parent,
+				offset,
+				symmetry,
+				&Limb {
+					shape,
+					length,
+					flags: role | joint,
+				},
+			);
+			modules += 1;
+		}
+		builder.build(timer)
 	}
 }
 
@@ -241,7 +328,7 @@ impl Phenotype for Spore {
 	fn develop(&self, gen: &mut Genome, id: Id, initial_state: agent::InitialState, timer: &Timer) -> agent::Agent {
 		let gender = gen.next_integer::<u8>(0, 3);
 		let tint = gen.next_float(0., 1.);
-		let albedo = color::Hsl::new(tint, 0.5, 0.5);
+		let albedo = species_albedo(gen.dna(), tint);
 		let charge = initial_state.charge;
 		let mut builder = AgentBuilder::new(
 			id,
@@ -250,7 +337,7 @@ impl Phenotype for Spore {
 				..Default::default()
 			},
 			Livery {
-				albedo: albedo.to_rgba(),
+				albedo,
 				..Default::default()
 			},
 			gen.dna_cloned(),
@@ -264,11 +351,29 @@ impl Phenotype for Spore {
 	}
 }
 
+// How copies of a limb module are laid out around the segment they grow from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symmetry {
+	// a mirrored pair, one on each side
+	Bilateral,
+	// n copies evenly spaced all around
+	Radial(usize),
+}
+
+// A chain of identical segments, each attached to the tip of the previous one
+#[derive(Clone)]
+pub struct Limb {
+	pub shape: Shape,
+	pub length: usize,
+	pub flags: segment::Flags,
+}
+
 pub struct AgentBuilder {
 	id: Id,
 	material: Material,
 	livery: Livery,
 	gender: u8,
+	resistance: f32,
 	brain: Brain,
 	dna: Dna,
 	state: segment::State,
@@ -283,6 +388,7 @@ impl AgentBuilder {
 			livery,
 			state,
 			gender: 0u8,
+			resistance: 0.,
 			brain: Brain::default(),
 			dna,
 			segments: Vec::new(),
@@ -309,6 +415,12 @@ impl AgentBuilder {
 		self
 	}
 
+	#[inline]
+	pub fn resistance(&mut self, resistance: f32) -> &mut Self {
+		self.resistance = resistance;
+		self
+	}
+
 	#[inline]
 	pub fn add(&mut self, parent_index: SegmentIndex, attachment_index_offset: isize, shape: &Shape, flags: segment::Flags)
 			   -> &mut Self {
@@ -372,6 +484,40 @@ impl AgentBuilder {
 		self
 	}
 
+	pub fn limbs(&mut self, parent_index: SegmentIndex, attachment_index_offset: isize, symmetry: Symmetry, limb: &Limb)
+				 -> &mut Self {
+		match symmetry {
+			Symmetry::Bilateral => self
+				.limb(parent_index, attachment_index_offset, Winding::CW, limb, Flags::RIGHT)
+				.limb(parent_index, -attachment_index_offset, Winding::CCW, limb, Flags::LEFT),
+			Symmetry::Radial(n) => {
+				let parent_length = self.segments[parent_index as usize].mesh.shape.length() as isize;
+				for i in 0..n as isize {
+					let offset = attachment_index_offset + i * parent_length / n as isize;
+					self.limb(parent_index, offset, Winding::CW, limb, Flags::MIDDLE);
+				}
+				self
+			}
+		}
+	}
+
+	fn limb(
+		&mut self, parent_index: SegmentIndex, attachment_index_offset: isize, winding: Winding, limb: &Limb,
+		side: segment::Flags,
+	) -> &mut Self {
+		let mut parent = parent_index;
+		let mut offset = attachment_index_offset;
+		for _ in 0..limb.length {
+			if self.segments.len() >= MINION_MAX_SEGMENTS {
+				break;
+			}
+			parent = self.addw(parent, offset, &limb.shape, winding, limb.flags | side).index();
+			// the first vertex is where a segment is attached, so the next hangs off the opposite one
+			offset = limb.shape.mid();
+		}
+		self
+	}
+
 	pub fn index(&self) -> SegmentIndex {
 		match self.segments.len() {
 			0 => 0,
@@ -453,6 +599,7 @@ impl AgentBuilder {
 		Agent::new(
 			self.id,
 			self.gender,
+			self.resistance,
 			&self.brain,
 			&self.dna,
 			self.segments.clone().into_boxed_slice(),
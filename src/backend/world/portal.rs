@@ -0,0 +1,34 @@
+use constants::*;
+use cgmath::InnerSpace;
+use core::geometry::Position;
+
+// two ends, what enters one comes out of the other
+#[derive(Clone, Debug)]
+pub struct Portal {
+	pub ends: [Position; 2],
+	pub radius: f32,
+}
+
+impl Portal {
+	pub fn new(a: Position, b: Position) -> Self {
+		Portal {
+			ends: [a, b],
+			radius: PORTAL_RADIUS,
+		}
+	}
+
+	// where a body at the position comes out, on the far side of the other end so that it keeps going
+	// the way it came in instead of bouncing back and forth
+	pub fn exit(&self, position: Position) -> Option<Position> {
+		let (entrance, exit) = if (position - self.ends[0]).magnitude2() < self.radius * self.radius {
+			(self.ends[0], self.ends[1])
+		} else if (position - self.ends[1]).magnitude2() < self.radius * self.radius {
+			(self.ends[1], self.ends[0])
+		} else {
+			return None;
+		};
+		let offset = position - entrance;
+		let side = if offset.magnitude2() > 0. { offset.normalize() } else { Position::unit_x() };
+		Some(exit - side * (self.radius * PORTAL_EXIT_DISTANCE))
+	}
+}
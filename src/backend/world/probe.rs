@@ -0,0 +1,99 @@
+use constants::*;
+use core::clock::Seconds;
+use core::clock::SecondsValue;
+use core::geometry::Position;
+use core::geometry::Rect;
+use std::collections::VecDeque;
+use std::mem;
+
+#[derive(Clone, Debug, Default)]
+pub struct Sample {
+	pub agent_count: usize,
+	pub total_energy: f32,
+	pub resources_eaten: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProbeStats {
+	pub agent_count: usize,
+	pub mean_energy: f32,
+	pub resources_eaten: usize,
+	// resources eaten per second, smoothed
+	pub throughput: f32,
+}
+
+impl ProbeStats {
+	pub fn update(&mut self, sample: &Sample, dt: Seconds) {
+		self.agent_count = sample.agent_count;
+		self.mean_energy = if sample.agent_count > 0 {
+			sample.total_energy / sample.agent_count as f32
+		} else {
+			0.
+		};
+		self.resources_eaten += sample.resources_eaten;
+		let dt: f32 = dt.into();
+		if dt > 0. {
+			let rate = sample.resources_eaten as f32 / dt;
+			self.throughput += (rate - self.throughput) * (dt / PROBE_THROUGHPUT_WINDOW).min(1.);
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Record {
+	pub seconds: SecondsValue,
+	pub agent_count: usize,
+	pub mean_energy: f32,
+	pub resources_eaten: usize,
+	pub throughput: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Probe {
+	pub extent: Rect,
+	pub stats: ProbeStats,
+	history: VecDeque<Record>,
+	next_record: SecondsValue,
+}
+
+impl Probe {
+	pub fn new(a: Position, b: Position) -> Self {
+		Probe {
+			extent: Rect::from_corners(a, b),
+			stats: ProbeStats::default(),
+			history: VecDeque::new(),
+			next_record: 0.,
+		}
+	}
+
+	pub fn contains(&self, p: Position) -> bool { self.extent.contains(p) }
+
+	pub fn history(&self) -> &VecDeque<Record> { &self.history }
+
+	pub fn footprint(&self) -> usize { mem::size_of::<Probe>() + self.history.len() * mem::size_of::<Record>() }
+
+	// forgets the oldest records down to the given number
+	pub fn trim_history(&mut self, keep: usize) {
+		while self.history.len() > keep {
+			self.history.pop_front();
+		}
+	}
+
+	// samples the current stats every PROBE_HISTORY_INTERVAL, keeping the last PROBE_HISTORY_MAX records
+	pub fn record(&mut self, seconds: SecondsValue) {
+		if seconds < self.next_record {
+			return;
+		}
+		self.next_record = seconds + PROBE_HISTORY_INTERVAL;
+		if self.history.len() >= PROBE_HISTORY_MAX {
+			self.history.pop_front();
+		}
+		self.history.push_back(Record {
+			seconds,
+			agent_count: self.stats.agent_count,
+			mean_energy: self.stats.mean_energy,
+			resources_eaten: self.stats.resources_eaten,
+			throughput: self.stats.throughput,
+		});
+	}
+}
@@ -6,6 +6,7 @@ use core::math::ExponentialFilter;
 use core::geometry::*;
 use core::geometry::Transform;
 use core::clock::Seconds;
+use cgmath;
 use num::Zero;
 
 #[derive(Clone)]
@@ -141,12 +142,23 @@ impl Segment {
 	pub fn growing_scaled_vertex(&self, index: usize) -> Position {
 		self.state.maturity * self.mesh.scaled_vertex(index)
 	}
+
+	// the vertices where they are in the world, as grown so far
+	pub fn outline(&self) -> Vec<Position> {
+		let rotation = cgmath::Matrix2::from_angle(cgmath::Rad(self.transform.angle));
+		(0..self.mesh.vertices.len())
+			.map(|i| self.transform.position + rotation * self.growing_scaled_vertex(i))
+			.collect()
+	}
+
+	// how much the charge lights the segment up
+	pub fn brightness(&self) -> f32 { 5. * ((self.state.charge.get() * 0.99) + 0.01) }
 }
 
 impl obj::Drawable for Segment {
 	fn color(&self) -> Rgba {
 		let rgba = self.livery.albedo;
-		let c = 5. * ((self.state.charge.get() * 0.99) + 0.01);
+		let c = self.brightness();
 		[
 			rgba[0] * c,
 			rgba[1] * c,
@@ -0,0 +1,117 @@
+use super::*;
+use cgmath::InnerSpace;
+use core::noise::Perlin;
+use rand::Rng;
+use rand::SeedableRng;
+use rand::XorShiftRng;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+	// mostly rock, with tunnels through it
+	Caves,
+	// mostly open water, with rocks here and there
+	Islands,
+}
+
+impl Layout {
+	pub fn parse(name: &str) -> Option<Layout> {
+		match name {
+			"caves" => Some(Layout::Caves),
+			"islands" => Some(Layout::Islands),
+			_ => None,
+		}
+	}
+
+	// the noise above which a cell is rock
+	fn threshold(self) -> f32 {
+		match self {
+			Layout::Caves => TERRAIN_CAVES_THRESHOLD,
+			Layout::Islands => TERRAIN_ISLANDS_THRESHOLD,
+		}
+	}
+}
+
+// everything needed to grow the same terrain again
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Terrain {
+	pub seed: u32,
+	pub layout: Layout,
+	// the size of the features, in world units
+	pub scale: f32,
+	// spread where the resource noise is high, and nowhere in the rock
+	pub resources: usize,
+}
+
+impl Default for Terrain {
+	fn default() -> Self {
+		Terrain {
+			seed: 0,
+			layout: Layout::Caves,
+			scale: TERRAIN_SCALE,
+			resources: TERRAIN_RESOURCES,
+		}
+	}
+}
+
+impl World {
+	// replaces the obstacles with the rock of the terrain and scatters resources in the open,
+	// returning how many walls and resources it made
+	pub fn generate_terrain(&mut self, terrain: &Terrain) -> (usize, usize) {
+		let rock = Perlin::new(terrain.seed);
+		let food = Perlin::new(terrain.seed.wrapping_add(1));
+		let scale = terrain.scale.max(TERRAIN_CELL);
+		let threshold = terrain.layout.threshold();
+		let extent = self.extent;
+		let columns = ((extent.max.x - extent.min.x) / TERRAIN_CELL).ceil() as usize;
+		let rows = ((extent.max.y - extent.min.y) / TERRAIN_CELL).ceil() as usize;
+		let cell_center = |column: usize, row: usize| {
+			extent.min + Position::new(column as f32 + 0.5, row as f32 + 0.5) * TERRAIN_CELL
+		};
+		// the players start in the middle, which is always kept open
+		let is_rock = |p: Position| {
+			p.magnitude() > TERRAIN_CLEARING && rock.fractal(p.x / scale, p.y / scale, TERRAIN_OCTAVES) > threshold
+		};
+
+		// rock cells next to each other in a row make up a single wall
+		let mut walls = Vec::new();
+		for row in 0..rows {
+			let mut start = None;
+			for column in 0..=columns {
+				let solid = column < columns && is_rock(cell_center(column, row));
+				match (start, solid) {
+					(None, true) => start = Some(column),
+					(Some(first), false) => {
+						let min = extent.min + Position::new(first as f32, row as f32) * TERRAIN_CELL;
+						let max = extent.min + Position::new(column as f32, row as f32 + 1.) * TERRAIN_CELL;
+						walls.push(Obstacle::wall(min, max));
+						start = None;
+					}
+					_ => {}
+				}
+			}
+		}
+		let wall_count = walls.len();
+		*self.obstacles_mut() = walls;
+
+		// the food noise is the chance of keeping a random spot, so resources gather in patches
+		let mut rng: XorShiftRng = SeedableRng::from_seed([terrain.seed, !terrain.seed, 0x6a09_e667, 1]);
+		let mut resource_count = 0;
+		for _ in 0..terrain.resources * TERRAIN_RESOURCE_ATTEMPTS {
+			if resource_count >= terrain.resources {
+				break;
+			}
+			let p = Position::new(
+				extent.min.x + rng.next_f32() * (extent.max.x - extent.min.x),
+				extent.min.y + rng.next_f32() * (extent.max.y - extent.min.y),
+			);
+			let density = food.fractal(p.x / scale, p.y / scale, TERRAIN_OCTAVES) * 0.5 + 0.5;
+			if rng.next_f32() < density * density && !is_rock(p) {
+				self.new_resource(Transform::from_position(p), Motion::default());
+				resource_count += 1;
+			}
+		}
+		(wall_count, resource_count)
+	}
+}
@@ -0,0 +1,391 @@
+//! The tuning of the simulation: sizes, rates and limits shared by the world, its systems and the app.
+
+use core::clock::{SecondsValue, SpeedFactor};
+use core::geometry::Position;
+use std::f32::consts;
+
+pub const DEFAULT_WINDOW_WIDTH: u32 = 1280;
+pub const DEFAULT_WINDOW_HEIGHT: u32 = 720;
+pub const VIEW_SCALE_BASE: f32 = 100.0;
+pub const VIEW_ZOOM_MAX: f32 = 8.0;
+pub const VIEW_ZOOM_MIN: f32 = 1. / 4.0;
+pub const VIEW_ZOOM_MULTIPLIER: f32 = consts::SQRT_2;
+pub const VIEW_ZOOM_DURATION: f32 = 0.25;
+pub const CAMERA_IMPULSE: f32 = 5.0;
+pub const CAMERA_INERTIA: f32 = 4.0;
+pub const CAMERA_LIMIT: f32 = 0.5;
+pub const CAMERA_DASH_SPEED: f32 = 2.0;
+pub const FRAME_SMOOTH_COUNT: usize = 120;
+pub const FRAME_TIME_TARGET: SecondsValue = 1. / 60.;
+// how often the paused app checks the gamepad while waiting for input
+pub const IDLE_WAKEUP_MILLIS: u64 = 100;
+pub const LOG_INTERVAL: SecondsValue = 5.0;
+pub const SAVE_INTERVAL: SecondsValue = 300.0;
+pub const TELEMETRY_INTERVAL: SecondsValue = 1.0;
+// seconds the energy flows are summed over before they are shown, per second
+pub const ENERGY_FLOW_PERIOD: SecondsValue = 5.0;
+// the population is sampled every so often, and a sample far from the mean of the window before stands out
+pub const MOMENT_SAMPLE_INTERVAL: SecondsValue = 2.0;
+pub const MOMENT_WINDOW: usize = 30;
+pub const MOMENT_POPULATION_SIGMAS: f64 = 3.0;
+pub const MOMENT_POPULATION_MIN_CHANGE: usize = 5;
+pub const MOMENT_SPECIES_MEMBERS: usize = 3;
+// in seconds, younger records are set all the time early on
+pub const MOMENT_MIN_RECORD_AGE: f32 = 60.;
+// of the wall clock, between two clips or bookmarks
+pub const MOMENT_COOLDOWN: SecondsValue = 30.;
+pub const MOMENT_CLIP_SECONDS: SecondsValue = 10.;
+// minions and spores remembered in the family tree, the oldest are forgotten first
+pub const LINEAGE_RECORDS_MAX: usize = 10000;
+// the buttons of the genealogy browser
+pub const GENEALOGY_RELATIVES_MAX: usize = 12;
+// the genes shown side by side in the genome diff, longer genomes are cut
+pub const GENOME_DIFF_GENES_MAX: usize = 96;
+// in pixels, of the picture of a previewed genome
+pub const PREVIEW_SIZE: usize = 160;
+// the sliders of the genome editor, a page of genes at a time
+pub const GENOME_EDITOR_ROWS: usize = 8;
+// the children of each round of the breeding workbench
+pub const BREEDING_CANDIDATES: usize = 4;
+// the cells across and down the world that the minions of two saves are counted in
+pub const WORLD_DIFF_GRID: usize = 4;
+// the minions whose genomes are compared with each other, the pairs grow quickly
+pub const WORLD_DIFF_SAMPLE: usize = 500;
+// the species and genes listed, those that changed most
+pub const WORLD_DIFF_TOP: usize = 8;
+pub const WORLD_DIFF_FILE: &str = "world_diff.json";
+// agent messages per OSC bundle, to stay well within a datagram
+pub const OSC_BUNDLE_SIZE: usize = 16;
+pub const TURN_SPEED: f32 = consts::PI * 200.;
+pub const DEBUG_DRAW_BRAKE_SCALE: f32 = 0.05;
+pub const DEBUG_DRAW_MOVE_SCALE: f32 = 0.05;
+pub const MIN_FRAME_LENGTH: SecondsValue = (1.0 / 1000.0) as SecondsValue;
+pub const MAX_FRAME_LENGTH: SecondsValue = (1.0 / 30.0) as SecondsValue;
+pub const THRUST_POWER: f32 = 5000.;
+pub const POWER_BOOST: f32 = 100.;
+pub const DRAG_COEFFICIENT: f32 = 0.000_001;
+#[allow(unused)]
+pub const COMPASS_SPRING_POWER: f32 = 1000.0;
+pub const JOINT_UPPER_ANGLE: f32 = consts::PI / 6.;
+pub const JOINT_LOWER_ANGLE: f32 = -consts::PI / 6.;
+pub const JOINT_FREQUENCY: f32 = 5.0;
+pub const JOINT_DAMPING_RATIO: f32 = 0.9;
+pub const LINEAR_DAMPING_DEFAULT: f32 = 0.8;
+pub const LINEAR_DAMPING_PLAYER: f32 = 2.0;
+pub const ANGULAR_DAMPING: f32 = 0.9;
+pub const PICK_EPS: f32 = 0.001f32;
+pub const DEFAULT_RESOURCE_CHARGE: f32 = 0.8;
+pub const DEFAULT_SPORE_CHARGE: f32 = 0.8;
+pub const DEFAULT_MINION_CHARGE: f32 = 0.3;
+pub const INITIAL_SPAWN_RADIUS_RATIO: f32 = 0.1;
+pub const INITIAL_SPAWN_RADIUS_SLICES: f32 = 19.;
+pub const INITIAL_SPAWN_RADIUS_INCREMENT: f32 = 0.5;
+pub const MATURITY_MINION_DEFAULT: f32 = 0.5;
+pub const MATURITY_DEFAULT: f32 = 1.0;
+// energy spent per unit of relative growth, as a ratio of max energy
+pub const GROWTH_COST_RATIO: f32 = 1.0;
+pub const GROWTH_COST_THRESHOLD: f32 = 0.5;
+pub const GROWTH_STEP: f32 = 0.05;
+pub const MINION_GROWTH_TIME: f32 = 30.;
+pub const MINION_MAX_LIMB_MODULES: usize = 3;
+pub const MINION_MAX_SEGMENTS: usize = 48;
+// the limb genes come after the bytes of the genomes from before them, which are too short to grow limb modules
+pub const GENOME_LIMB_GENES_AT: usize = 100;
+pub const GENOME_LIMB_GENES_BYTES: usize = 32;
+pub const MINION_SENESCENCE_AGE: f32 = 300.;
+pub const MINION_SENESCENCE_SPAN: f32 = 60.;
+pub const DORMANCY_THRESHOLD: f32 = 0.15;
+pub const DORMANT_METABOLISM: f32 = 0.2;
+pub const DORMANT_WAKE_RANGE: f32 = 10.;
+pub const DORMANT_DIM: f32 = 0.35;
+pub const INFECTED_TINT: [f32; 3] = [0.6, 1., 0.4];
+// a species is one of a few hues far apart, and the tint gene shades it within part of its slot
+pub const SPECIES_GENES: usize = 4;
+pub const SPECIES_HUES: u32 = 12;
+pub const SPECIES_HUE_SPREAD: f32 = 0.6;
+pub const SPECIES_LUMA: f32 = 0.55;
+pub const SPECIES_CHROMA: f32 = 0.3;
+pub const DEFAULT_PLANT_CHARGE: f32 = 0.6;
+pub const PLANT_LEAF_PAIRS_MAX: usize = 3;
+// the distance at which a light gives half of its intensity
+pub const PLANT_LIGHT_RANGE: f32 = 15.;
+// per second, for each leaf in full light
+pub const PLANT_PHOTOSYNTHESIS: f32 = 5.0;
+// per second, for each segment
+pub const PLANT_METABOLISM: f32 = 0.5;
+pub const PLANT_SEED_THRESHOLD: f32 = 0.9;
+pub const PLANT_SEED_COST_RATIO: f32 = 0.5;
+pub const PLANT_SEED_RANGE: f32 = 8.;
+pub const PLANT_MAX_POPULATION: usize = 200;
+pub const SPAWN_COST_THRESHOLD: f32 = 0.95;
+// the game mode, how far a minion goes of the shorter side of the arena to cross it
+pub const OBJECTIVE_CROSSING_RATIO: f32 = 0.9;
+pub const SCORE_POINTS_PER_SECOND: f32 = 1.;
+pub const SCORE_OBJECTIVE_BONUS: f32 = 1000.;
+pub const SPAWN_COST_RATIO: f32 = 0.75;
+pub const COLLISION_BASE_COST: f32 = 0.5;
+pub const WORLD_RADIUS: f32 = 80.;
+pub const DEFAULT_CHARGE_DECAY_TIME: SecondsValue = 0.5;
+pub const MINION_CHARGE_DECAY_TIME: SecondsValue = 0.25;
+pub const PLAYER_CHARGE_DECAY_TIME: SecondsValue = 0.1;
+pub const PLAYER_CHARGE_INITIAL_VALUE: f32 = 25.0;
+pub const PLAYER_CHARGE_REST_VALUE: f32 = 0.05;
+pub const EMITTER_DISTANCE: f32 = 40.;
+pub const EMITTER_PERIOD: SecondsValue = 0.2;
+#[allow(unused)]
+pub const EMITTER_SPREAD_ANGLE: f32 = consts::PI / 12.;
+pub const EMITTER_SPREAD_JITTER: f32 = 0.1;
+pub const EMITTER_INTENSITY_DECAY: f32 = 1.0;
+pub const BULLET_SPEED_SCALE: f32 = 100.;
+pub const BULLET_FIRE_RATE_SCALE: SecondsValue = 0.5;
+pub const BULLET_FULL_CHARGE: SecondsValue = 1.0;
+pub const BULLET_FIRE_RATE: SecondsValue = 45.0;
+pub const DENSITY_DEFAULT: f32 = 1.0;
+pub const DENSITY_RESOURCE: f32 = DENSITY_DEFAULT;
+pub const DENSITY_PLAYER: f32 = 1.0;
+pub const DENSITY_MINION: f32 = 0.2;
+pub const DENSITY_SPORE: f32 = 0.5;
+pub const RESTITUTION_DEFAULT: f32 = 0.6;
+pub const RESTITUTION_PLAYER: f32 = 0.1;
+pub const FRICTION_DEFAULT: f32 = 0.7;
+pub const FRICTION_PLAYER: f32 = 0.6;
+pub const B2_LINEAR_SLOP: f32 = 0.005;
+pub const DEFAULT_MINION_GENE_POOL_FILE: &str = "minion_gene_pool.csv";
+pub const DEFAULT_MINION_GENE_POOL: &[&str] = &[
+	"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+	"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+	"GzB2lQVwM00tTAm5gwajjf4wc0a5GzB2lQVwM00tTAm5gwajjf4wc0a5",
+	"GzB2lQdwM10vQEu5zwaPgDhfq2v8GzB2lQdwM10vQEu5zwaPgDhfq2v8",
+];
+
+pub const COLOR_SUNSHINE: [f32; 4] = [400.0, 90.0, 1.0, 1.0];
+pub const COLOR_TRANSPARENT: [f32; 4] = [0.; 4];
+pub const COLOR_WHITE: [f32; 4] = [1.; 4];
+pub const COLOR_BLACK: [f32; 4] = [0., 0., 0., 1.];
+
+pub const SHADOW_INTENSITY: f32 = 0.8;
+pub const SHADOW_LIGHT_RANGE: f32 = 60.;
+pub const SHADOW_MIN_DISTANCE_RATIO: f32 = 1.5;
+pub const SHADOW_LENGTH_RATIO: f32 = 6.;
+pub const SHADOW_LENGTH_MAX: f32 = 10.;
+pub const SHADOW_SECTIONS: usize = 4;
+
+pub const DEFAULT_RESOURCE_GENE_POOL: &[&str] = &["GyA21QoQ", "M00sWS0M"];
+
+pub const CONFIG_DIR_HOME: &str = ".config/rust-oids";
+pub const CONFIG_DIR_SAVED_STATE: &str = "saved_state";
+pub const CONFIG_DIR_RESOURCES: &str = "resources";
+pub const CONFIG_FILE: &str = "config.json";
+// in the platform's configuration directory, as in ~/.config/rust-oids or %APPDATA%\rust-oids
+pub const PROFILE_DIR: &str = "rust-oids";
+pub const PROFILE_FILE: &str = "profile.json";
+pub const PROFILE_RECENT_MAX: usize = 10;
+// the first timeline saves to the saved state directory itself, the others to branches/<name> in it
+pub const MAIN_BRANCH: &str = "main";
+pub const BRANCHES_DIR: &str = "branches";
+pub const BRANCH_FILE: &str = "branch.json";
+pub const CHECKPOINTS_DIR: &str = "checkpoints";
+pub const DUMP_FILE_PATTERN_CSV: &str = "%Y%m%d_%H%M%S.csv";
+pub const DUMP_FILE_PATTERN_JSON: &str = "%Y%m%d_%H%M%S.json";
+pub const PROBES_FILE_PATTERN_CSV: &str = "probes_%Y%m%d_%H%M%S.csv";
+pub const TELEMETRY_FILE_PATTERN_CSV: &str = "telemetry_%Y%m%d_%H%M%S.csv";
+
+pub const CAPTURE_FOLDER_TIMESTAMP_PATTERN: &str = "%Y%m%d_%H%M%S";
+pub const CAPTURE_FOLDER: &str = "capture";
+pub const CAPTURE_FILENAME_PREFIX: &str = "capture_";
+// the last seconds of frames, kept for a clip while not recording
+pub const CAPTURE_CLIP_DIR: &str = "clip";
+// frames waiting to be encoded, about 100MB at 1080p
+pub const CAPTURE_QUEUE_LENGTH: usize = 16;
+pub const CAPTURE_ENCODERS: usize = 4;
+pub const MEGABYTE: usize = 1024 * 1024;
+// the memory used is estimated every this many seconds, and a part over its budget is trimmed to this share of it
+pub const MEMORY_CHECK_INTERVAL: SecondsValue = 5.;
+pub const MEMORY_TRIM_SLACK: f64 = 0.8;
+pub const MEMORY_DEFAULT_BUDGET: usize = 4096;
+pub const CAPTURE_MUX_POLL_MILLIS: u64 = 100;
+// frames waiting to be piped into the stream encoder
+pub const STREAM_QUEUE_LENGTH: usize = 8;
+pub const CAPTION_FONT: &str = "fonts/FreeMono.ttf";
+pub const CAPTURE_REGION_COLOR: [f32; 4] = [1., 0.2, 0.2, 0.8];
+
+pub const AMBIENT_LIGHTS: &[[f32; 4]] = &[
+	[1.0, 1.0, 1.0, 1.0],
+	[3.1, 3.1, 3.1, 1.0],
+	[10.0, 10.0, 10.0, 1.0],
+	[31.0, 31.0, 31.0, 1.0],
+	[100.0, 100.0, 100.0, 1.0],
+	[0.001, 0.001, 0.001, 1.0],
+	[0.01, 0.01, 0.01, 1.0],
+	[0.1, 0.1, 0.1, 1.0],
+	[0.31, 0.31, 0.31, 0.5],
+];
+
+pub const SPEED_FACTORS: &[SpeedFactor] = &[1.0, 0.5, 0.2, 0.1, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0];
+// what the MIDI knobs turn between, the speed exponentially
+pub const MIDI_SPEED_MIN: SpeedFactor = 0.1;
+pub const MIDI_SPEED_MAX: SpeedFactor = 100.0;
+pub const MIDI_MUTATION_MAX: f32 = 2.0;
+pub const MIDI_LIGHT_MAX: f32 = 2.0;
+pub const UI_SCALE_MIN: f32 = 0.5;
+pub const UI_SCALE_MAX: f32 = 4.0;
+
+pub const GRID_TARGET_LINES: f32 = 10.;
+pub const GRID_COLOR: [f32; 4] = [0.2, 0.3, 0.4, 1.0];
+pub const GRID_AXIS_COLOR: [f32; 4] = [0.6, 0.3, 0.2, 1.0];
+pub const GRID_LABEL_OFFSET_X: Position = Position { x: 0., y: 24. };
+pub const GRID_LABEL_OFFSET_Y: Position = Position { x: 32., y: 0. };
+pub const MAX_WORLD_LABELS: usize = 64;
+
+// brains evaluated per tick before all of them start skipping ticks
+pub const AI_EVALUATION_BUDGET: usize = 400;
+// out of view, brains are evaluated at most every this many ticks
+pub const AI_LOD_FAR_STRIDE: usize = 4;
+// how far out of the view the full rate still applies, relative to its half diagonal
+pub const AI_LOD_VIEW_MARGIN: f32 = 1.5;
+// the adaptive quality drops a step after the frame rate stays under the target this many seconds, and brings one
+// back after it stays over the target by the headroom for longer
+pub const ADAPTIVE_TARGET_FPS: f32 = 30.;
+pub const ADAPTIVE_HEADROOM: f32 = 1.3;
+pub const ADAPTIVE_LOWER_DELAY: f64 = 2.;
+pub const ADAPTIVE_RAISE_DELAY: f64 = 10.;
+pub const ADAPTIVE_FOCUS_SCALE: f32 = 0.5;
+// how far past a region boundary an agent goes before it is handed over to the next physics world
+pub const PHYSICS_REGION_MARGIN: f32 = 4.;
+pub const PHYSICS_MAX_REGIONS: usize = 32;
+// past these a body is taken for one blown up by the solver, and its agent quarantined
+pub const PHYSICS_RUNAWAY_SPEED: f32 = 1000.;
+pub const PHYSICS_RUNAWAY_SPIN: f32 = 1000.;
+// how far out of the walls or the wrapping extent
+pub const PHYSICS_RUNAWAY_MARGIN: f32 = WORLD_RADIUS;
+// simulation steps between the integrity checks of debug builds
+pub const PHYSICS_CHECK_STEPS: usize = 600;
+// the discrepancies listed in the console, all of them are logged
+pub const PHYSICS_CHECK_SHOWN: usize = 8;
+pub const MEASURE_COLOR: [f32; 4] = [1.0, 1.0, 0.2, 1.0];
+pub const MEASURE_MIN_LENGTH: f32 = 0.01;
+pub const MEASURE_TICK_PIXELS: f32 = 8.;
+pub const ANNOTATION_COLOR: [f32; 4] = [0.2, 1.0, 0.6, 1.0];
+pub const ANNOTATION_ARROW_PIXELS: f32 = 12.;
+pub const CONSOLE_MAX_LINES: usize = 12;
+pub const MAX_TOASTS: usize = 5;
+pub const TOAST_DURATION: SecondsValue = 10.;
+pub const WARNING_FPS_WARMUP_FRAMES: usize = 300;
+// a frame that takes this many frame times or more missed at least one
+pub const DIAGNOSTICS_MISSED_FRAME: SecondsValue = 1.5;
+// the peaks shown are those of the last few seconds
+pub const DIAGNOSTICS_PEAK_WINDOW: SecondsValue = 5.;
+pub const WARNING_DISK_CHECK_INTERVAL: SecondsValue = 10.;
+pub const WEBHOOK_THUMBNAIL_FILE: &str = "webhook_thumbnail.png";
+pub const WEBHOOK_THUMBNAIL_WIDTH: u32 = 480;
+pub const REPORT_FILE_PATTERN_HTML: &str = "report_%Y%m%d_%H%M%S.html";
+pub const REPORT_MAX_SAMPLES: usize = 1000;
+pub const REPORT_SAMPLE_INTERVAL: SecondsValue = 10.;
+pub const REPORT_THUMBNAIL_INTERVAL: SecondsValue = 600.;
+pub const REPORT_THUMBNAIL_PREFIX: &str = "report_snapshot_";
+pub const REPORT_THUMBNAIL_WIDTH: u32 = 320;
+pub const REPORT_TOP_GENOMES: usize = 5;
+// next to each save and gene pool, for the quick load menu
+pub const SAVE_THUMBNAIL_WIDTH: usize = 160;
+// agents of a save this close with the same genome are taken for one saved twice
+pub const SAVE_DUPLICATE_DISTANCE: f32 = 0.01;
+// the share of the agents of a save that can be dropped while repairing it, past it the save is refused
+pub const SAVE_REPAIR_MAX_DROPPED: f32 = 0.5;
+pub const QUICK_LOAD_GENE_POOLS: usize = 5;
+pub const QUICK_LOAD_ENTRIES: usize = PROFILE_RECENT_MAX + QUICK_LOAD_GENE_POOLS;
+// telemetry logs drawn over each other, and the samples kept of each
+pub const COMPARE_CHARTS: &[&str] = &["population", "energy"];
+pub const COMPARE_RUNS_MAX: usize = 6;
+pub const COMPARE_POINTS_MAX: usize = 400;
+pub const PROBE_COLOR: [f32; 4] = [0.2, 0.6, 1.0, 1.0];
+pub const OBSTACLE_COLOR: [f32; 4] = [0.5, 0.5, 0.55, 1.0];
+pub const OBSTACLE_STICKY_COLOR: [f32; 4] = [0.6, 0.5, 0.2, 1.0];
+pub const OBSTACLE_ZONE_COLOR: [f32; 4] = [0.3, 0.5, 0.7, 0.8];
+pub const OBSTACLE_DOOR_COLOR: [f32; 4] = [0.6, 0.3, 0.3, 1.0];
+pub const OBSTACLE_GATE_COLOR: [f32; 4] = [0.3, 0.7, 0.4, 0.8];
+pub const OBSTACLE_FRICTION: f32 = 0.5;
+pub const OBSTACLE_RESTITUTION: f32 = 0.2;
+pub const OBSTACLE_ZONE_DRAG: f32 = 1.0;
+// seconds a segment let go by a sticky obstacle can't be stuck again, to get away from it
+pub const OBSTACLE_STICKY_COOLDOWN: f32 = 0.5;
+// how close to an edge of a toroidal world an agent also shows on the other side
+pub const WRAP_SEAM_MARGIN: f32 = 10.0;
+// an unbounded world is simulated in these squares around the camera, and the rest are kept on disk
+pub const CHUNK_SIZE: f32 = 50.0;
+pub const CHUNK_ACTIVE_RADIUS: i32 = 1;
+pub const CHUNK_FREEZE_SLACK: i32 = 1;
+pub const CHUNK_RESOURCES: usize = 20;
+pub const CHUNK_SEED: u32 = 0x9e37_79b9;
+pub const CHUNKS_DIR: &str = "chunks";
+// the frozen chunks are stepped in numbers, at the birth and death rates of the minions in view
+pub const FAR_FIELD_STEP: SecondsValue = 1.0;
+// the steps the far field catches up on at once, the time beyond is lost
+pub const FAR_FIELD_MAX_STEPS: usize = 10;
+// resources a minion eats and a chunk grows back, per second
+pub const FAR_FIELD_MEALS: f32 = 0.05;
+pub const FAR_FIELD_REGROWTH: f32 = 0.2;
+pub const FAR_FIELD_MAX_MINIONS: f32 = 100.0;
+// seconds for the energy of the far minions to get most of the way to that of those in view
+pub const FAR_FIELD_ENERGY_TIME: f32 = 30.0;
+pub const SCENARIOS_DIR: &str = "scenarios";
+pub const CHALLENGES_DIR: &str = "challenges";
+pub const CHALLENGE_MENU_ENTRIES: usize = 10;
+pub const FUZZ_CONFIG_HOME: &str = "rust-oids-fuzz";
+pub const FUZZ_STEPS: usize = 10000;
+pub const SWEEP_CONFIG_HOME: &str = "rust-oids-sweep";
+pub const SWEEP_TICKS: usize = 3600;
+pub const SWEEP_OUTPUT_FILE: &str = "sweep.csv";
+pub const SWEEP_PENDING_DIR: &str = "pending";
+pub const SWEEP_RUNNING_DIR: &str = "running";
+pub const SWEEP_DONE_DIR: &str = "done";
+// seconds between looks at the queue while the other machines finish
+pub const SWEEP_POLL_INTERVAL: u64 = 10;
+// set to record the digests of the golden runs again instead of comparing them
+pub const GOLDEN_BLESS_VAR: &str = "RUST_OIDS_BLESS";
+// the streams the systems draw from in each step, all from the seed of the world
+pub const ALIFE_RNG_STREAM: u32 = 0xa11f_e000;
+pub const GAME_RNG_STREAM: u32 = 0x6a3e_0000;
+pub const PARTICLE_RNG_STREAM: u32 = 0x9a27_0000;
+// how often the config and the scenario are looked at for changes, in seconds
+pub const RELOAD_CHECK_INTERVAL: f64 = 1.0;
+// how close to a feeder a click in the editor picks it
+pub const EDITOR_PICK_RADIUS: f32 = 2.0;
+pub const EDITOR_COLOR: [f32; 4] = [1.0, 0.8, 0.2, 1.0];
+// generated terrain is laid out in square cells of this size
+pub const TERRAIN_CELL: f32 = 4.0;
+pub const TERRAIN_SCALE: f32 = 60.0;
+pub const TERRAIN_OCTAVES: usize = 3;
+pub const TERRAIN_CAVES_THRESHOLD: f32 = -0.1;
+pub const TERRAIN_ISLANDS_THRESHOLD: f32 = 0.25;
+// the radius around the center kept free of rock
+pub const TERRAIN_CLEARING: f32 = 20.0;
+pub const TERRAIN_RESOURCES: usize = 200;
+// random spots tried per resource before giving up, in case the rock takes most of the world
+pub const TERRAIN_RESOURCE_ATTEMPTS: usize = 20;
+pub const PORTAL_RADIUS: f32 = 3.0;
+pub const PORTAL_MIN_RADIUS: f32 = 0.5;
+// how far past the radius of the other end bodies come out, in radii
+pub const PORTAL_EXIT_DISTANCE: f32 = 1.5;
+pub const PORTAL_COLOR: [f32; 4] = [0.7, 0.4, 0.9, 0.9];
+pub const PORTAL_EDGES: usize = 24;
+// how quickly a strip brings the bodies on it up to its speed, per second
+pub const OBSTACLE_FLOW_GRIP: f32 = 2.0;
+pub const OBSTACLE_FLOW_COLOR: [f32; 4] = [0.3, 0.6, 0.8, 0.6];
+// the distance between the chevrons drawn on a strip
+pub const OBSTACLE_FLOW_SPACING: f32 = 2.0;
+pub const PROBE_THROUGHPUT_WINDOW: f32 = 5.0;
+pub const PROBE_HISTORY_INTERVAL: SecondsValue = 1.0;
+pub const PROBE_HISTORY_MAX: usize = 36_000;
+pub const PROBE_LABEL_OFFSET: Position = Position { x: 80., y: -12. };
+pub const MEASURE_LABEL_OFFSET: Position = Position { x: 0., y: 20. };
+
+// the software renderer, close to the default background of the window
+pub const RASTER_BACKGROUND: [u8; 3] = [13, 18, 26];
+pub const RASTER_WALL: [f32; 3] = [0.5, 0.5, 0.55];
+pub const RASTER_ZONE: [f32; 3] = [0.2, 0.35, 0.5];
+pub const RASTER_ZONE_ALPHA: f32 = 0.3;
+pub const RASTER_EXPOSURE: f32 = 1.0;
+// bodies drawn alone are lit by their livery, not by their charge
+pub const RASTER_BODY_BRIGHTNESS: f32 = 1.5;
+pub const RASTER_BODY_ALPHA: f32 = 0.85;
+
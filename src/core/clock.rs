@@ -220,6 +220,11 @@ impl Hourglass {
 
 	pub fn delay(&mut self, delay_seconds: Seconds) { self.timeout += delay_seconds; }
 
+	pub fn capacity(&self) -> Seconds { self.capacity }
+
+	// from the next flip on
+	pub fn set_capacity(&mut self, seconds: Seconds) { self.capacity = seconds; }
+
 	#[allow(unused)]
 	pub fn elapsed<T>(&self, timer: &T) -> Seconds
 	where T: Timer {
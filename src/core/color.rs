@@ -1,4 +1,5 @@
 use num;
+use std::f32::consts;
 
 pub type Rgb<T = f32> = [T; 3];
 pub type Rgba<T = f32> = [T; 4];
@@ -169,3 +170,9 @@ impl ToRgb<f32> for Hsl<f32> {
 		}
 	}
 }
+
+// A hue in [0, 1) around the chroma plane at a fixed luma, so that no hue stands out brighter than another
+pub fn equiluminant(hue: f32, luma: f32, chroma: f32) -> Rgb<f32> {
+	let angle = hue * 2. * consts::PI;
+	YPbPr::new(luma, chroma * angle.cos(), chroma * angle.sin()).to_rgb()
+}
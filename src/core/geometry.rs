@@ -31,7 +31,7 @@ pub struct Motion {
 	pub spin: Spin,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Rect {
 	pub min: Position,
 	pub max: Position,
@@ -45,12 +45,65 @@ impl Rect {
 		}
 	}
 
+	pub fn from_corners(a: Position, b: Position) -> Self { Rect::new(a.x.min(b.x), a.y.min(b.y), a.x.max(b.x), a.y.max(b.y)) }
+
+	pub fn contains(&self, p: Position) -> bool {
+		p.x >= self.min.x && p.x <= self.max.x && p.y >= self.min.y && p.y <= self.max.y
+	}
+
+	// sharing more than an edge
+	pub fn overlaps(&self, other: &Rect) -> bool {
+		self.min.x < other.max.x && other.min.x < self.max.x && self.min.y < other.max.y && other.min.y < self.max.y
+	}
+
 	pub fn bottom_left(&self) -> Position { self.min }
 	pub fn top_right(&self) -> Position { self.max }
 
 	pub fn bottom_right(&self) -> Position { Position::new(self.max.x, self.min.y) }
 
 	pub fn top_left(&self) -> Position { Position::new(self.min.x, self.max.y) }
+
+	fn size(&self) -> Position { self.max - self.min }
+
+	// the same point brought back inside, on a torus the size of the rect
+	pub fn wrap(&self, p: Position) -> Position {
+		let size = self.size();
+		let d = p - self.min;
+		self.min + Position::new(((d.x % size.x) + size.x) % size.x, ((d.y % size.y) + size.y) % size.y)
+	}
+
+	// the shortest of the displacements equivalent to d on the torus
+	pub fn wrap_delta(&self, d: Position) -> Position {
+		let size = self.size();
+		Position::new(d.x - size.x * (d.x / size.x).round(), d.y - size.y * (d.y / size.y).round())
+	}
+
+	// the offsets of the copies of something within the margin of p that show inside, the original included
+	pub fn wrap_offsets(&self, p: Position, margin: f32) -> Vec<Position> {
+		let size = self.size();
+		let mut offsets = Vec::with_capacity(4);
+		for &dx in &[0., -size.x, size.x] {
+			for &dy in &[0., -size.y, size.y] {
+				let q = p + Position::new(dx, dy);
+				if q.x + margin >= self.min.x
+					&& q.x - margin <= self.max.x
+					&& q.y + margin >= self.min.y
+					&& q.y - margin <= self.max.y
+				{
+					offsets.push(Position::new(dx, dy));
+				}
+			}
+		}
+		offsets
+	}
+}
+
+// the way from a to b, around the torus if there's one
+pub fn delta(wrap: Option<&Rect>, from: Position, to: Position) -> Position {
+	match wrap {
+		Some(rect) => rect.wrap_delta(to - from),
+		None => to - from,
+	}
 }
 
 impl Default for Rect {
@@ -1,8 +1,12 @@
+//! The plumbing under the simulation: clocks, geometry, maths, noise, colors and resources.
+
 pub mod math;
 pub mod util;
 pub mod clock;
 pub mod resource;
 pub mod color;
 pub mod geometry;
+pub mod noise;
+pub mod spatial;
 pub mod view;
 
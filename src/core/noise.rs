@@ -0,0 +1,67 @@
+use rand::Rng;
+use rand::SeedableRng;
+use rand::XorShiftRng;
+use std::f32::consts;
+
+// classic gradient noise, between -1 and 1 and zero on the integer lattice
+pub struct Perlin {
+	permutation: Vec<usize>,
+}
+
+const GRADIENTS: [(f32, f32); 8] = [
+	(1., 0.),
+	(-1., 0.),
+	(0., 1.),
+	(0., -1.),
+	(consts::FRAC_1_SQRT_2, consts::FRAC_1_SQRT_2),
+	(-consts::FRAC_1_SQRT_2, consts::FRAC_1_SQRT_2),
+	(consts::FRAC_1_SQRT_2, -consts::FRAC_1_SQRT_2),
+	(-consts::FRAC_1_SQRT_2, -consts::FRAC_1_SQRT_2),
+];
+
+fn fade(t: f32) -> f32 { t * t * t * (t * (t * 6. - 15.) + 10.) }
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 { a + t * (b - a) }
+
+impl Perlin {
+	pub fn new(seed: u32) -> Self {
+		// the generator doesn't take an all zero seed
+		let mut rng: XorShiftRng = SeedableRng::from_seed([seed, !seed, 0x2545_f491, 1]);
+		let mut permutation: Vec<usize> = (0..256).collect();
+		rng.shuffle(&mut permutation);
+		let repeated = permutation.clone();
+		permutation.extend(repeated);
+		Perlin { permutation }
+	}
+
+	fn gradient(&self, x: i32, y: i32, dx: f32, dy: f32) -> f32 {
+		let p = &self.permutation;
+		let (gx, gy) = GRADIENTS[p[p[(x & 0xff) as usize] + (y & 0xff) as usize] & 0x7];
+		gx * dx + gy * dy
+	}
+
+	pub fn at(&self, x: f32, y: f32) -> f32 {
+		let (x0, y0) = (x.floor(), y.floor());
+		let (dx, dy) = (x - x0, y - y0);
+		let (ix, iy) = (x0 as i32, y0 as i32);
+		let (u, v) = (fade(dx), fade(dy));
+		let bottom = lerp(u, self.gradient(ix, iy, dx, dy), self.gradient(ix + 1, iy, dx - 1., dy));
+		let top = lerp(u, self.gradient(ix, iy + 1, dx, dy - 1.), self.gradient(ix + 1, iy + 1, dx - 1., dy - 1.));
+		lerp(v, bottom, top)
+	}
+
+	// octaves of halving size and weight add detail to the coarse shapes
+	pub fn fractal(&self, x: f32, y: f32, octaves: usize) -> f32 {
+		let mut total = 0.;
+		let mut weight = 1.;
+		let mut frequency = 1.;
+		let mut weights = 0.;
+		for _ in 0..octaves {
+			total += self.at(x * frequency, y * frequency) * weight;
+			weights += weight;
+			weight *= 0.5;
+			frequency *= 2.;
+		}
+		if weights > 0. { total / weights } else { 0. }
+	}
+}
@@ -0,0 +1,73 @@
+use core::geometry::Position;
+use core::geometry::Rect;
+use std::collections::HashMap;
+
+// Uniform grid bucketing items by position, for neighbourhood queries cheaper than a linear scan
+pub struct Grid<T> {
+	cell_size: f32,
+	cells: HashMap<(i32, i32), Vec<(Position, T)>>,
+	// the extent of a toroidal world, where neighbourhoods go across the edges
+	wrap: Option<Rect>,
+}
+
+impl<T> Grid<T>
+where T: Copy
+{
+	pub fn new(cell_size: f32) -> Self {
+		Grid {
+			cell_size: cell_size.max(1e-3),
+			cells: HashMap::new(),
+			wrap: None,
+		}
+	}
+
+	pub fn with_wrap(self, wrap: Option<Rect>) -> Self { Grid { wrap, ..self } }
+
+	pub fn set_wrap(&mut self, wrap: Option<Rect>) { self.wrap = wrap; }
+
+	fn cell_of(&self, p: Position) -> (i32, i32) {
+		((p.x / self.cell_size).floor() as i32, (p.y / self.cell_size).floor() as i32)
+	}
+
+	pub fn insert(&mut self, p: Position, item: T) {
+		let cell = self.cell_of(p);
+		self.cells.entry(cell).or_insert_with(Vec::new).push((p, item));
+	}
+
+	pub fn clear(&mut self) { self.cells.clear(); }
+
+	// across the edges of a toroidal world the positions are those of the copies nearest to the center
+	pub fn within(&self, center: Position, radius: f32) -> Vec<(Position, T)> {
+		match self.wrap {
+			None => self.within_plain(center, radius),
+			Some(rect) => rect
+				.wrap_offsets(center, radius)
+				.into_iter()
+				.flat_map(|offset| {
+					self.within_plain(center + offset, radius)
+						.into_iter()
+						.map(move |(p, item)| (p - offset, item))
+				}).collect(),
+		}
+	}
+
+	fn within_plain(&self, center: Position, radius: f32) -> Vec<(Position, T)> {
+		let (x0, y0) = self.cell_of(Position::new(center.x - radius, center.y - radius));
+		let (x1, y1) = self.cell_of(Position::new(center.x + radius, center.y + radius));
+		let radius2 = radius * radius;
+		let mut found = Vec::new();
+		for x in x0..=x1 {
+			for y in y0..=y1 {
+				if let Some(cell) = self.cells.get(&(x, y)) {
+					for &(p, item) in cell {
+						let d = p - center;
+						if d.x * d.x + d.y * d.y <= radius2 {
+							found.push((p, item));
+						}
+					}
+				}
+			}
+		}
+		found
+	}
+}
@@ -20,6 +20,8 @@ where T: Clone + Initial
 		}
 	}
 
+	pub fn capacity(&self) -> usize { self.values.len() }
+
 	pub fn clear(&mut self) {
 		self.count = 0;
 		self.ptr = 0;
@@ -1,4 +1,6 @@
 use super::geometry::*;
+use super::math;
+use super::math::Directional;
 
 pub trait ViewTransform {
 	fn to_view(&self, screen_position: Position) -> Position;
@@ -26,6 +28,26 @@ impl Viewport {
 	}
 
 	pub fn scale(&mut self, scale: f32) { self.scale = scale; }
+
+	pub fn width(&self) -> u32 { self.width }
+
+	pub fn height(&self) -> u32 { self.height }
+
+	// half width and half height of the visible area, in world units
+	pub fn half_extent(&self) -> Position { Position::new(self.scale * 0.5, self.scale * 0.5 / self.ratio) }
+
+	// world units per pixel
+	pub fn pixel_size(&self) -> f32 { self.scale / self.width as f32 }
+
+	// view coordinates to pixels, origin at the center of the window, y up
+	pub fn to_pixels(&self, view_position: Position) -> Position {
+		view_position * (self.width as f32 / self.scale)
+	}
+}
+
+// a camera following its target
+impl WorldTransform for math::Inertial<f32> {
+	fn to_world(&self, view_position: Position) -> Position { view_position + self.position() }
 }
 
 impl WorldTransform for Viewport {
@@ -0,0 +1,112 @@
+//! What the player and the app ask of the simulation; the systems answer some of them over the bus.
+
+use core::geometry::*;
+use core::clock::*;
+use backend::world::moment::Moment;
+
+#[derive(Clone, Copy, Debug)]
+pub enum VectorDirection {
+	None,
+	Orientation(Position),
+	LookAt(Position),
+	Turn(Angle),
+	FromVelocity,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Event {
+	CamUp(f32),
+	CamDown(f32),
+	CamLeft(f32),
+	CamRight(f32),
+	// a burst of speed beyond what holding the keys gives
+	CamDash(Position),
+
+	ZoomIn,
+	ZoomOut,
+	ZoomReset,
+
+	VectorThrust(Option<Position>, VectorDirection),
+	PrimaryTrigger(f32, SecondsValue),
+	PrimaryFire(f32, SecondsValue),
+
+	CamReset,
+
+	NextLight,
+	PrevLight,
+
+	NextBackground,
+	PrevBackground,
+
+	NextSpeedFactor,
+	PrevSpeedFactor,
+
+	Reload,
+	SaveGenePoolToFile,
+	SaveProbesToFile,
+	SaveWorldToFile,
+	RestartFromCheckpoint,
+	ToggleDebug,
+	ToggleShadows,
+	ToggleGrid,
+	ToggleAnnotations,
+
+	TogglePause,
+	ToggleGui,
+	ToggleCapture,
+
+	AppQuit,
+
+	NewMinion(Position),
+	RandomizeMinion(Position),
+
+	PickMinion(Position),
+	SelectMinion(usize),
+	// an agent whose bodies went to NaN or flew off, frozen by the physics
+	Quarantine(usize),
+	// an objective of the game mode, by its index
+	ObjectiveMet(usize),
+	// something unusual, clipped and bookmarked if the app is set to
+	InterestingMoment(Moment),
+	DeselectAll,
+	ToggleFreezeSelected,
+	TogglePossess,
+	ToggleConsole,
+	ToggleQuickLoad,
+	// the entry of the quick load menu
+	QuickLoad(usize),
+	ToggleChallenges,
+	// the entry of the challenge menu
+	StartChallenge(usize),
+	ToggleGenealogy,
+	// a relative picked in the genealogy browser
+	FocusRelative(usize),
+	// an agent against another, or its mother if none
+	DiffGenomes(usize, Option<usize>),
+	CloseGenomeDiff,
+	// the genome in the preview pane, a new minion at the camera
+	SpawnPreview,
+	ClosePreview,
+	ToggleGenomeEditor,
+	// the first gene of the page of the editor
+	ShowGenes(usize),
+	EditGene(usize, u8),
+	// a candidate of the breeding workbench, spawned and bred again
+	PickCandidate(usize),
+	Rebreed,
+	CloseBreeding,
+
+	BeginDrag(Position, Position),
+	Drag(Position, Position),
+	EndDrag(Position, Position, Velocity),
+
+	Measure(Position, Position),
+	EndMeasure(Position, Position),
+
+	NewAnnotation(Position, Position),
+	NewProbe(Position, Position),
+
+	ToggleEditor,
+	EditDrag(Position, Position),
+	EndEditDrag(Position, Position),
+}
\ No newline at end of file
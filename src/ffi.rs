@@ -0,0 +1,266 @@
+//! A C interface to the simulation, for cffi, ctypes and the like. `include/rust_oids.h` declares it.
+//!
+//! The pointers are trusted to be null or what the functions handed out, as C would.
+//! No panic unwinds into the caller: it comes back as `RUST_OIDS_PANIC`, after which the world may be
+//! inconsistent. `rust_oids_free` is still safe on it.
+
+use backend::obj::Motionable;
+use backend::obj::Transformable;
+use backend::raster;
+use backend::simulation::Simulation;
+use backend::systems::PhysicsSettings;
+use backend::world::agent::AgentType;
+use backend::world::persist::Serializer;
+use backend::world::World;
+use constants::DEFAULT_MINION_GENE_POOL_FILE;
+use core::clock::Seconds;
+use core::geometry::{Motion, Position, Transform};
+use core::resource::filesystem::ResourceLoaderBuilder;
+use events::Event;
+use num_traits::FromPrimitive;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::panic;
+use std::path::Path;
+use std::ptr;
+
+pub const RUST_OIDS_OK: c_int = 0;
+pub const RUST_OIDS_INVALID: c_int = -1;
+pub const RUST_OIDS_IO: c_int = -2;
+pub const RUST_OIDS_PANIC: c_int = -3;
+
+pub const RUST_OIDS_EVENT_NEW_MINION: u32 = 0;
+pub const RUST_OIDS_EVENT_RANDOM_MINION: u32 = 1;
+pub const RUST_OIDS_EVENT_NEW_RESOURCE: u32 = 2;
+pub const RUST_OIDS_EVENT_PICK_MINION: u32 = 3;
+
+/// An agent as C sees it, `agent_type` is the index of its `AgentType`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RustOidsAgent {
+	pub id: u64,
+	pub agent_type: u32,
+	pub segments: u32,
+	pub x: f32,
+	pub y: f32,
+	pub angle: f32,
+	pub vx: f32,
+	pub vy: f32,
+	pub energy: f32,
+	pub age: f64,
+}
+
+unsafe fn path_of<'a>(s: *const c_char) -> Option<&'a Path> {
+	if s.is_null() {
+		None
+	} else {
+		CStr::from_ptr(s).to_str().ok().map(Path::new)
+	}
+}
+
+fn guard<F>(f: F) -> c_int
+where F: FnOnce() -> c_int {
+	panic::catch_unwind(panic::AssertUnwindSafe(f)).unwrap_or(RUST_OIDS_PANIC)
+}
+
+/// A new world with the default gene pool from `resources`, or the one saved in `snapshot` if not null.
+/// Null if `resources` is null, or the snapshot doesn't load.
+#[no_mangle]
+pub unsafe extern "C" fn rust_oids_new(resources: *const c_char, snapshot: *const c_char) -> *mut Simulation {
+	let resources = match path_of(resources) {
+		Some(resources) => resources,
+		None => return ptr::null_mut(),
+	};
+	let snapshot = path_of(snapshot);
+	panic::catch_unwind(|| {
+		let loader = ResourceLoaderBuilder::new().add(resources).build();
+		let mut world = World::new(&loader, DEFAULT_MINION_GENE_POOL_FILE);
+		match snapshot.map(|snapshot| Serializer::load(snapshot, &mut world)) {
+			Some(Err(e)) => {
+				error!("Could not load {:?}: {}", snapshot, e);
+				ptr::null_mut()
+			}
+			_ => Box::into_raw(Box::new(Simulation::new(world, PhysicsSettings::default()))),
+		}
+	}).unwrap_or(ptr::null_mut())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_oids_free(simulation: *mut Simulation) {
+	if !simulation.is_null() {
+		drop(Box::from_raw(simulation));
+	}
+}
+
+/// `steps` steps of `dt` seconds each.
+#[no_mangle]
+pub unsafe extern "C" fn rust_oids_step(simulation: *mut Simulation, dt: f64, steps: u32) -> c_int {
+	let simulation = match simulation.as_mut() {
+		Some(simulation) => simulation,
+		None => return RUST_OIDS_INVALID,
+	};
+	guard(|| {
+		for _ in 0..steps {
+			simulation.step(Seconds::new(dt));
+		}
+		RUST_OIDS_OK
+	})
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rust_oids_ticks(simulation: *const Simulation) -> u64 {
+	simulation.as_ref().map_or(0, |simulation| simulation.ticks() as u64)
+}
+
+/// Simulated seconds since the world began.
+#[no_mangle]
+pub unsafe extern "C" fn rust_oids_seconds(simulation: *const Simulation) -> f64 {
+	simulation.as_ref().map_or(0., |simulation| simulation.world().seconds().get())
+}
+
+/// Fills `out` with up to `capacity` agents of the type, by id, and returns how many there are in all:
+/// call it again with a bigger buffer if that is more than `capacity`. Null `out` only counts them.
+#[no_mangle]
+pub unsafe extern "C" fn rust_oids_agents(
+	simulation: *const Simulation,
+	agent_type: u32,
+	out: *mut RustOidsAgent,
+	capacity: usize,
+) -> usize
+{
+	let (simulation, agent_type) = match (simulation.as_ref(), AgentType::from_u32(agent_type)) {
+		(Some(simulation), Some(agent_type)) => (simulation, agent_type),
+		_ => return 0,
+	};
+	let agents = simulation.world().agents(agent_type);
+	if !out.is_null() && capacity > 0 {
+		let out = ::std::slice::from_raw_parts_mut(out, capacity);
+		let mut ids = agents.keys().cloned().collect::<Vec<_>>();
+		ids.sort();
+		for (slot, id) in out.iter_mut().zip(ids) {
+			let agent = &agents[&id];
+			let transform = agent.transform();
+			let motion = agent.motion();
+			*slot = RustOidsAgent {
+				id: id as u64,
+				agent_type: agent_type as u32,
+				segments: agent.segments().len() as u32,
+				x: transform.position.x,
+				y: transform.position.y,
+				angle: transform.angle,
+				vx: motion.velocity.x,
+				vy: motion.velocity.y,
+				energy: agent.state.energy(),
+				age: agent.age().get(),
+			};
+		}
+	}
+	agents.len()
+}
+
+/// Spawns at, or picks the minion at, `x`, `y` in world coordinates.
+#[no_mangle]
+pub unsafe extern "C" fn rust_oids_inject(simulation: *mut Simulation, event: u32, x: f32, y: f32) -> c_int {
+	let simulation = match simulation.as_mut() {
+		Some(simulation) => simulation,
+		None => return RUST_OIDS_INVALID,
+	};
+	let position = Position::new(x, y);
+	guard(|| {
+		match event {
+			RUST_OIDS_EVENT_NEW_MINION => {
+				simulation.world_mut().new_minion(position, Motion::default());
+			}
+			RUST_OIDS_EVENT_RANDOM_MINION => {
+				simulation.world_mut().randomize_minion(position, Motion::default());
+			}
+			RUST_OIDS_EVENT_NEW_RESOURCE => {
+				simulation.world_mut().new_resource(Transform::new(position, 0.), Motion::default());
+			}
+			RUST_OIDS_EVENT_PICK_MINION => simulation.post(Event::PickMinion(position)),
+			_ => return RUST_OIDS_INVALID,
+		}
+		RUST_OIDS_OK
+	})
+}
+
+/// Writes a snapshot that `rust_oids_new` and the app can load.
+#[no_mangle]
+pub unsafe extern "C" fn rust_oids_save(simulation: *const Simulation, path: *const c_char) -> c_int {
+	match (simulation.as_ref(), path_of(path)) {
+		(Some(simulation), Some(path)) => guard(|| match Serializer::save(path, simulation.world()) {
+			Ok(()) => RUST_OIDS_OK,
+			Err(e) => {
+				error!("Could not save {:?}: {}", path, e);
+				RUST_OIDS_IO
+			}
+		}),
+		_ => RUST_OIDS_INVALID,
+	}
+}
+
+/// Draws `span` world units across around `x`, `y`, or all the world if `span` is not positive, into `out`:
+/// `width` by `height` RGB pixels, top row first, so it must hold `width * height * 3` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rust_oids_render(
+	simulation: *const Simulation,
+	x: f32,
+	y: f32,
+	span: f32,
+	width: u32,
+	height: u32,
+	out: *mut u8,
+) -> c_int
+{
+	let simulation = match simulation.as_ref() {
+		Some(simulation) if !out.is_null() => simulation,
+		_ => return RUST_OIDS_INVALID,
+	};
+	let (width, height) = (width as usize, height as usize);
+	let out = ::std::slice::from_raw_parts_mut(out, width * height * 3);
+	guard(|| {
+		let world = simulation.world();
+		let extent = if span > 0. { raster::around(Position::new(x, y), span, width, height) } else { world.extent };
+		out.copy_from_slice(&raster::render(world, &extent, width, height).pixels);
+		RUST_OIDS_OK
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const HEADER: &str = include_str!("../include/rust_oids.h");
+
+	// FriendlyBullet is RUST_OIDS_FRIENDLY_BULLET
+	fn define_of(agent_type: AgentType) -> String {
+		let mut name = String::from("RUST_OIDS_");
+		for (i, c) in agent_type.to_string().chars().enumerate() {
+			if i > 0 && c.is_uppercase() {
+				name.push('_');
+			}
+			name.push(c.to_ascii_uppercase());
+		}
+		name
+	}
+
+	fn value_of(define: &str) -> Option<u32> {
+		HEADER
+			.lines()
+			.filter_map(|line| {
+				let mut words = line.split_whitespace();
+				match (words.next(), words.next(), words.next()) {
+					(Some("#define"), Some(name), Some(value)) if name == define => value.parse().ok(),
+					_ => None,
+				}
+			}).next()
+	}
+
+	#[test]
+	fn the_header_defines_every_agent_type() {
+		for agent_type in AgentType::all() {
+			let define = define_of(*agent_type);
+			assert_eq!(value_of(&define), Some(*agent_type as u32), "{} in include/rust_oids.h", define);
+		}
+	}
+}
@@ -12,6 +12,7 @@ use std::sync::mpsc::SendError;
 use std::sync::Arc;
 use std::sync::Mutex;
 use app;
+use app::AudioTrack;
 use sample::ToFrameSliceMut;
 use frontend::ui::AlertPlayer;
 use backend::world::Alert;
@@ -90,6 +91,12 @@ pub trait SoundSystem: Sized {
 pub struct ThreadedSoundSystem {
 	sound_thread: Option<thread::JoinHandle<()>>,
 	trigger: Sender<SoundEffect>,
+	track: AudioTrack,
+}
+
+impl ThreadedSoundSystem {
+	// What is played, for the screen capture
+	pub fn track(&self) -> AudioTrack { self.track.clone() }
 }
 
 pub struct SoundSystemAlertPlayer<S> where S: SoundSystem {
@@ -140,14 +147,31 @@ impl AlertPlayer<app::Event, self::Error> for SoundSystemAlertPlayer<ThreadedSou
 			Event::PrevSpeedFactor |
 			Event::Reload |
 			Event::SaveGenePoolToFile |
+			Event::SaveProbesToFile |
 			Event::SaveWorldToFile |
 			Event::DeselectAll |
 			Event::ZoomReset |
+			Event::ToggleShadows |
+			Event::ToggleGrid |
+			Event::ToggleAnnotations |
+			Event::ToggleFreezeSelected |
+			Event::TogglePossess |
+			Event::ToggleConsole |
+			Event::ToggleQuickLoad |
+			Event::ToggleChallenges |
+			Event::ToggleGenealogy |
+			Event::CloseGenomeDiff |
+			Event::ClosePreview |
+			Event::ToggleGenomeEditor |
+			Event::Rebreed |
+			Event::CloseBreeding |
 			Event::ToggleDebug => SoundEffect::UserOption,
 
 			Event::PickMinion(_) => SoundEffect::SelectMinion,
 
 			Event::NewMinion(_) |
+			Event::SpawnPreview |
+			Event::PickCandidate(_) |
 			Event::RandomizeMinion(_) => SoundEffect::NewMinion,
 
 			Event::EndDrag(_, _, _) => SoundEffect::Release(0),
@@ -178,6 +202,8 @@ impl ThreadedAlertPlayer {
 impl SoundSystem for ThreadedSoundSystem {
 	fn new(audio_device: Option<usize>) -> Result<ThreadedSoundSystem, self::Error> {
 		let (tx, rx) = channel();
+		let track = AudioTrack::new(SAMPLE_HZ as u32, CHANNELS as u16);
+		let callback_track = track.clone();
 		let sound_thread = thread::Builder::new().name("SoundControl".to_string()).spawn(move || {
 			info!("Started sound control thread");
 			let portaudio = pa::PortAudio::new()
@@ -221,6 +247,7 @@ impl SoundSystem for ThreadedSoundSystem {
 				sample::slice::equilibrium(buffer);
 				// uhm what?
 				dsp_handle.lock().unwrap().audio_requested(buffer);
+				callback_track.record(&buffer[..]);
 
 				trace!("Callback end");
 				pa::Continue
@@ -276,6 +303,7 @@ impl SoundSystem for ThreadedSoundSystem {
 		Ok(ThreadedSoundSystem {
 			sound_thread: Some(sound_thread),
 			trigger: tx,
+			track,
 		})
 	}
 
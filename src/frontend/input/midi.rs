@@ -0,0 +1,82 @@
+use midir::{MidiInput, MidiInputConnection};
+use std::sync::mpsc;
+
+use frontend::input;
+use frontend::input::EventMapper;
+
+// A MIDI controller, its messages come from the driver thread and are polled like the gamepad's
+pub struct MidiEventLoop {
+	_connection: MidiInputConnection<()>,
+	receiver: mpsc::Receiver<Vec<u8>>,
+}
+
+impl input::EventMapper<Vec<u8>> for MidiEventLoop {
+	fn translate(&self, message: &Vec<u8>) -> Option<input::Event> {
+		// the low nibble of the status is the channel, all channels are the same here
+		match message.as_slice() {
+			&[status, note, velocity] if status & 0xf0 == 0x90 && velocity > 0 =>
+				Some(input::Event::MidiNote(note, input::State::Down)),
+			&[status, note, _] if status & 0xf0 == 0x90 || status & 0xf0 == 0x80 =>
+				Some(input::Event::MidiNote(note, input::State::Up)),
+			&[status, control, value] if status & 0xf0 == 0xb0 =>
+				Some(input::Event::MidiControl(control, f32::from(value) / 127.)),
+			_ => None,
+		}
+	}
+}
+
+impl MidiEventLoop {
+	// the first input port with `port_name` in its name, any port if empty
+	pub fn new(port_name: &str) -> Option<Self> {
+		let midi_input = match MidiInput::new("rust-oids") {
+			Ok(midi_input) => midi_input,
+			Err(err) => {
+				error!("Error initializing MIDI: {}", err);
+				return None;
+			}
+		};
+		let port = midi_input.ports().into_iter().find(|port| {
+			midi_input.port_name(port).map(|name| name.contains(port_name)).unwrap_or(false)
+		});
+		let port = match port {
+			Some(port) => port,
+			None => {
+				warn!("No MIDI input matching \"{}\"", port_name);
+				return None;
+			}
+		};
+		let name = midi_input.port_name(&port).unwrap_or_default();
+		let (sender, receiver) = mpsc::channel();
+		let connection = midi_input.connect(
+			&port,
+			"rust-oids-in",
+			move |_, message, _| {
+				let _ = sender.send(message.to_vec());
+			},
+			(),
+		);
+		match connection {
+			Ok(connection) => {
+				info!("Listening to MIDI from {}", name);
+				Some(MidiEventLoop {
+					_connection: connection,
+					receiver,
+				})
+			}
+			Err(err) => {
+				error!("Could not connect to MIDI input {}: {}", name, err);
+				None
+			}
+		}
+	}
+
+	pub fn poll_events<F>(&mut self, mut on_input_event: F)
+	where F: FnMut(input::Event) {
+		while let Ok(message) = self.receiver.try_recv() {
+			trace!("MIDI {:?}", message);
+			if let Some(event) = self.translate(&message) {
+				on_input_event(event);
+			}
+		}
+	}
+}
@@ -1,7 +1,9 @@
 //! Input state, including current mouse position and button click
 pub mod gamepad;
+pub mod midi;
 
 pub use self::gamepad::GamepadEventLoop;
+pub use self::midi::MidiEventLoop;
 
 use core::geometry;
 use core::util::History;
@@ -10,6 +12,8 @@ use core::view::ViewTransform;
 use bit_set::BitSet;
 use std::iter::Iterator;
 use std::collections::HashMap;
+use std::time::Duration;
+use std::time::Instant;
 
 #[derive(Clone)]
 enum DragState {
@@ -26,12 +30,27 @@ pub enum Dragging {
 }
 
 const MAX_AXIS: usize = 6;
+// the longest gap between the two taps of a double tap
+const DOUBLE_TAP_MILLIS: u64 = 300;
+
+// How the input kept up with the updates, as of the last one
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InputLag {
+	// from the first event after the update before to the update that read it
+	pub latency: Duration,
+	// the events the update read
+	pub events: usize,
+	// taps lost so far to another tap of the same key before the update
+	pub dropped_taps: usize,
+}
 
 #[derive(Clone)]
 pub struct GamepadState {
 	pub connected: bool,
 	pub button_pressed: BitSet,
 	pub button_pressed_last: BitSet,
+	// went down since the last update, even if already released
+	pub button_tapped: BitSet,
 	pub axis: [AxisValue; MAX_AXIS],
 }
 
@@ -39,10 +58,22 @@ pub struct InputState {
 	gamepad: HashMap<usize, GamepadState>,
 	key_pressed: BitSet,
 	key_pressed_last: BitSet,
+	// latched until the next update, so that a quick tap between two frames is not missed
+	key_tapped: BitSet,
+	key_double_tapped: BitSet,
+	last_tap: HashMap<usize, Instant>,
+	// physical mouse buttons to the ones bound to actions
+	button_map: HashMap<Key, Key>,
+	// MIDI notes to the keys they press
+	midi_map: HashMap<u8, Key>,
 	drag_state: DragState,
 	dragging: Dragging,
 	mouse_history: History<Position>,
 	mouse_position: Position,
+	pending_events: usize,
+	pending_since: Option<Instant>,
+	dropped_taps: usize,
+	lag: InputLag,
 }
 
 impl Default for GamepadState {
@@ -51,6 +82,7 @@ impl Default for GamepadState {
 			connected: false,
 			button_pressed: BitSet::new(),
 			button_pressed_last: BitSet::new(),
+			button_tapped: BitSet::new(),
 			axis: [0.0; MAX_AXIS],
 		}
 	}
@@ -64,10 +96,19 @@ impl Default for InputState {
 			gamepad: default_map,
 			key_pressed: BitSet::new(),
 			key_pressed_last: BitSet::new(),
+			key_tapped: BitSet::new(),
+			key_double_tapped: BitSet::new(),
+			last_tap: HashMap::new(),
+			button_map: HashMap::new(),
+			midi_map: HashMap::new(),
 			drag_state: DragState::Nothing,
 			dragging: Dragging::Nothing,
 			mouse_history: History::new(60),
 			mouse_position: geometry::origin(),
+			pending_events: 0,
+			pending_since: None,
+			dropped_taps: 0,
+			lag: InputLag::default(),
 		}
 	}
 }
@@ -79,7 +120,7 @@ pub enum State {
 }
 
 #[allow(dead_code)]
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Key {
 	A,
 	B,
@@ -222,6 +263,23 @@ pub enum Key {
 	GamepadRStickDown,
 }
 
+impl Key {
+	// the same modifier on the other side of the keyboard
+	fn twin(self) -> Option<Key> {
+		match self {
+			Key::LCtrl => Some(Key::RCtrl),
+			Key::RCtrl => Some(Key::LCtrl),
+			Key::LShift => Some(Key::RShift),
+			Key::RShift => Some(Key::LShift),
+			Key::LAlt => Some(Key::RAlt),
+			Key::RAlt => Some(Key::LAlt),
+			Key::LSuper => Some(Key::RSuper),
+			Key::RSuper => Some(Key::LSuper),
+			_ => None,
+		}
+	}
+}
+
 #[allow(unused)]
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum Axis {
@@ -240,13 +298,22 @@ pub enum Event {
 	Mouse(Position),
 	GamepadButton(usize, State, Key),
 	GamepadAxis(usize, AxisValue, Axis),
+	// note number
+	MidiNote(u8, State),
+	// controller number, value from 0 to 1
+	MidiControl(u8, AxisValue),
 }
 
 #[allow(dead_code)]
 impl GamepadState {
 	fn button(&mut self, state: State, b: Key) {
 		match state {
-			State::Down => self.button_pressed.insert(b as usize),
+			State::Down => {
+				if !self.button_pressed.contains(b as usize) {
+					self.button_tapped.insert(b as usize);
+				}
+				self.button_pressed.insert(b as usize)
+			}
 			State::Up => self.button_pressed.remove(b as usize),
 		};
 	}
@@ -256,12 +323,12 @@ impl GamepadState {
 	}
 
 	pub fn button_once(&self, b: Key) -> bool {
-		self.button_pressed.contains(b as usize) &&
-			!self.button_pressed_last.contains(b as usize)
+		self.button_tapped.contains(b as usize)
 	}
 
 	pub fn update_button_pressed(&mut self) {
 		self.button_pressed_last = self.button_pressed.clone();
+		self.button_tapped.clear();
 	}
 
 	fn axis(&mut self, value: AxisValue, axis: Axis) {
@@ -274,9 +341,12 @@ pub trait InputRead {
 	fn key_once(&self, b: Key) -> bool;
 	fn any_key_pressed(&self, b: &[Key]) -> bool;
 	fn any_ctrl_pressed(&self) -> bool;
+	fn any_shift_pressed(&self) -> bool;
 	fn any_alt_pressed(&self) -> bool;
 	fn any_super_pressed(&self) -> bool;
 	fn chord_pressed(&self, b: &[Key]) -> bool;
+	fn chord_once(&self, b: &[Key]) -> bool;
+	fn key_double_tap(&self, b: Key) -> bool;
 	fn gamepad_button_pressed(&self, gamepad_id: usize, b: Key) -> bool;
 	fn gamepad_axis(&self, gamepad_id: usize, axis: Axis) -> AxisValue;
 	fn gamepad_button_once(&self, gamepad_id: usize, b: Key) -> bool;
@@ -291,8 +361,7 @@ impl InputRead for InputState {
 	}
 
 	fn key_once(&self, b: Key) -> bool {
-		self.key_pressed.contains(b as usize) &&
-			!self.key_pressed_last.contains(b as usize)
+		self.key_tapped.contains(b as usize)
 	}
 
 	fn any_key_pressed(&self, b: &[Key]) -> bool {
@@ -304,6 +373,10 @@ impl InputRead for InputState {
 		self.any_key_pressed(&[Key::LCtrl, Key::RCtrl])
 	}
 
+	fn any_shift_pressed(&self) -> bool {
+		self.any_key_pressed(&[Key::LShift, Key::RShift])
+	}
+
 	fn any_alt_pressed(&self) -> bool {
 		self.any_key_pressed(&[Key::LAlt, Key::RAlt])
 	}
@@ -312,9 +385,17 @@ impl InputRead for InputState {
 		self.any_key_pressed(&[Key::LSuper, Key::RSuper])
 	}
 
+	// all held, modifiers on either side will do
 	fn chord_pressed(&self, b: &[Key]) -> bool {
-		let other: BitSet = b.into_iter().map(|k| *k as usize).collect();
-		self.key_pressed.is_superset(&other)
+		let held = |k: Key| self.key_pressed(k) || k.twin().map(|twin| self.key_pressed(twin)).unwrap_or(false);
+		b.iter().all(|k| held(*k))
+	}
+
+	// all held and one of them just went down
+	fn chord_once(&self, b: &[Key]) -> bool { self.chord_pressed(b) && b.iter().any(|k| self.key_once(*k)) }
+
+	fn key_double_tap(&self, b: Key) -> bool {
+		self.key_double_tapped.contains(b as usize)
 	}
 
 	fn gamepad_button_pressed(&self, gamepad_id: usize, b: Key) -> bool {
@@ -348,22 +429,72 @@ impl InputRead for InputState {
 #[allow(dead_code)]
 impl InputState {
 	pub fn event(&mut self, event: &Event) {
+		self.pending_events += 1;
+		if self.pending_since.is_none() {
+			self.pending_since = Some(Instant::now());
+		}
+		self.apply(event)
+	}
+
+	fn apply(&mut self, event: &Event) {
 		match *event {
-			Event::Key(state, key) => self.key(state, key),
+			Event::Key(state, key) => {
+				let key = self.button_map.get(&key).cloned().unwrap_or(key);
+				self.key(state, key)
+			}
 			Event::Mouse(position) => self.mouse_at(position),
 			Event::GamepadButton(id, state, button) => self.gamepad_button(id, state, button),
 			Event::GamepadAxis(id, axis, position) => self.gamepad_axis_update(id, axis, position),
+			Event::MidiNote(note, state) => if let Some(key) = self.midi_map.get(&note).cloned() {
+				self.apply(&Event::Key(state, key))
+			},
+			// the knobs are not keys, the app reads them as they come
+			Event::MidiControl(_, _) => {}
 		}
 	}
 
 	pub fn pre_update<V>(&mut self, view_transform: &V) where V: ViewTransform {
 		let mouse_window_pos = self.mouse_position();
 		let mouse_view_pos = view_transform.to_view(mouse_window_pos);
-		// TODO: generalise, for any button. Only RMB is supported otherwise
-		self.update_dragging(Key::MouseRight, mouse_view_pos);
+		// LMB and RMB can both drag, whichever is held first wins until released
+		let drag_key = match self.drag_state {
+			DragState::Hold(key, _) => key,
+			DragState::Nothing => if self.key_pressed(Key::MouseLeft) {
+				Key::MouseLeft
+			} else {
+				Key::MouseRight
+			},
+		};
+		self.update_dragging(drag_key, mouse_view_pos);
+	}
+
+	pub fn set_button_map(&mut self, map: &[(Key, Key)]) {
+		// buttons held through the change would never be released
+		for &(physical, _) in map {
+			self.key_pressed.remove(physical as usize);
+		}
+		self.button_map = map.iter().cloned().collect();
 	}
 
+	pub fn set_midi_map(&mut self, map: &[(u8, Key)]) {
+		for &key in self.midi_map.values() {
+			self.key_pressed.remove(key as usize);
+		}
+		self.midi_map = map.iter().cloned().collect();
+	}
+
+	// nothing held, so that nothing changes until the next event
+	pub fn is_idle(&self) -> bool { self.key_pressed.is_empty() }
+
+	pub fn lag(&self) -> InputLag { self.lag }
+
 	pub fn post_update(&mut self) {
+		self.lag = InputLag {
+			latency: self.pending_since.take().map(|since| since.elapsed()).unwrap_or_default(),
+			events: self.pending_events,
+			dropped_taps: self.dropped_taps,
+		};
+		self.pending_events = 0;
 		self.update_mouse_scroll();
 		self.update_key_pressed();
 		self.update_gamepad_button_pressed();
@@ -379,11 +510,32 @@ impl InputState {
 
 	fn key(&mut self, state: State, b: Key) {
 		match state {
-			State::Down => self.key_pressed.insert(b as usize),
+			// key repeats don't count as taps
+			State::Down => {
+				if !self.key_pressed.contains(b as usize) {
+					// released and pressed again before the update, which sees it once
+					if !self.key_tapped.insert(b as usize) {
+						self.dropped_taps += 1;
+					}
+					self.tap(b);
+				}
+				self.key_pressed.insert(b as usize)
+			}
 			State::Up => self.key_pressed.remove(b as usize),
 		};
 	}
 
+	fn tap(&mut self, b: Key) {
+		let now = Instant::now();
+		if let Some(last) = self.last_tap.insert(b as usize, now) {
+			if now.duration_since(last) < Duration::from_millis(DOUBLE_TAP_MILLIS) {
+				self.key_double_tapped.insert(b as usize);
+				// a third tap starts over
+				self.last_tap.remove(&(b as usize));
+			}
+		}
+	}
+
 	fn gamepad_mut(&mut self, gamepad_id: usize) -> &mut GamepadState {
 		self.gamepad
 			.entry(gamepad_id)
@@ -401,6 +553,8 @@ impl InputState {
 
 	fn update_key_pressed(&mut self) {
 		self.key_pressed_last = self.key_pressed.clone();
+		self.key_tapped.clear();
+		self.key_double_tapped.clear();
 	}
 
 	fn update_mouse_scroll(&mut self) {
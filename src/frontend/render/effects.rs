@@ -39,16 +39,75 @@ gfx_defines! {
 		src: gfx::TextureSampler<[f32; 4]> = "t_Source",
 		dst: gfx::RenderTarget<RenderColorFormat> = "o_Color",
 	}
+	constant ComposeFragmentArgs {
+		warp: [f32; 4] = "u_Warp",
+		outputs: f32 = "u_Outputs",
+		overlap: f32 = "u_Overlap",
+		blend_gamma: f32 = "u_BlendGamma",
+		padding: f32 = "u_Padding",
+	}
 	pipeline compose {
 		vbuf: gfx::VertexBuffer<BlitVertex> = (),
 		src1: gfx::TextureSampler<[f32; 4]> = "t_Source1",
 		src2: gfx::TextureSampler<[f32; 4]> = "t_Source2",
+		fragment_args: gfx::ConstantBuffer<ComposeFragmentArgs> = "cb_FragmentArgs",
 		dst: gfx::RenderTarget<ScreenColorFormat> = "o_Color",
 	}
 }
 
 use std::marker::PhantomData;
 
+pub const MAX_OUTPUTS: usize = 4;
+
+// How the frame is split between the outputs of a window spanning several projectors
+#[derive(Clone, Debug)]
+pub struct Projection {
+	// side by side, each showing the next part of the frame
+	pub outputs: usize,
+	// the fraction of an output shown by its neighbour too, faded across
+	pub overlap: f32,
+	// of the projectors, for the fade to add up to an even brightness
+	pub blend_gamma: f32,
+	// of each output, from flat to equirectangular
+	pub warp: [f32; MAX_OUTPUTS],
+}
+
+impl Default for Projection {
+	fn default() -> Self {
+		Projection {
+			outputs: 1,
+			overlap: 0.,
+			blend_gamma: 2.2,
+			warp: [0.; MAX_OUTPUTS],
+		}
+	}
+}
+
+// How much of the post processing is done, the low one skips the bloom for older GPUs
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Quality {
+	Low,
+	High,
+}
+
+impl Quality {
+	pub fn parse(name: &str) -> Option<Quality> {
+		match name {
+			"low" => Some(Quality::Low),
+			"high" => Some(Quality::High),
+			_ => None,
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Quality::Low => "low",
+			Quality::High => "high",
+		}
+	}
+}
+
 pub struct PostLighting<R: gfx::Resources, C: gfx::CommandBuffer<R>> {
 	vertex_buffer: gfx::handle::Buffer<R, BlitVertex>,
 	index_buffer_slice: gfx::Slice<R>,
@@ -78,6 +137,7 @@ pub struct PostLighting<R: gfx::Resources, C: gfx::CommandBuffer<R>> {
 	tone_map_vertex_args: gfx::handle::Buffer<R, ToneMapVertexArgs>,
 	tone_map_pso: gfx::pso::PipelineState<R, tone_map::Meta>,
 
+	compose_fragment_args: gfx::handle::Buffer<R, ComposeFragmentArgs>,
 	compose_pso: gfx::pso::PipelineState<R, compose::Meta>,
 
 	_buffer: PhantomData<C>,
@@ -116,6 +176,7 @@ impl<R: gfx::Resources, C: gfx::CommandBuffer<R>> PostLighting<R, C> {
 
 		let tone_map_vertex_args = factory.create_constant_buffer(1);
 		let smooth_fragment_args = factory.create_constant_buffer(1);
+		let compose_fragment_args = factory.create_constant_buffer(1);
 
 		macro_rules! load_pipeline_simple {
 			($v:expr, $f:expr, $s:ident) => { factory.create_pipeline_simple(
@@ -132,7 +193,7 @@ impl<R: gfx::Resources, C: gfx::CommandBuffer<R>> PostLighting<R, C> {
 		let blit_pso = load_pipeline_simple!("identity", "simple_blit", postprocess)?;
 		let smooth_pso = load_pipeline_simple!("identity", "exponential_smooth", smooth)?;
 		let average_pso = load_pipeline_simple!("identity", "quad_smooth", postprocess)?;
-		let compose_pso = load_pipeline_simple!("identity", "compose_2", compose)?;
+		let compose_pso = load_pipeline_simple!("identity", "compose_projected", compose)?;
 
 		let resolved = factory.create_render_target::<RenderColorFormat>(w, h)?;
 
@@ -186,6 +247,7 @@ impl<R: gfx::Resources, C: gfx::CommandBuffer<R>> PostLighting<R, C> {
 			resolve_msaa_pso,
 
 			resolved,
+			compose_fragment_args,
 			compose_pso,
 
 			mips,
@@ -217,7 +279,9 @@ impl<R: gfx::Resources, C: gfx::CommandBuffer<R>> PostLighting<R, C> {
 	pub fn apply_all(
 		&mut self, encoder: &mut gfx::Encoder<R, C>, 
 		raw_hdr_src: &gfx::handle::ShaderResourceView<R, [f32; 4]>,
-		color_target: &gfx::handle::RenderTargetView<R, ScreenColorFormat>
+		color_target: &gfx::handle::RenderTargetView<R, ScreenColorFormat>,
+		projection: &Projection,
+		quality: Quality,
 	) {
 		let ping_pong_full = &self.ping_pong_full[..];
 		let ping_pong_half = &self.ping_pong_half[..];
@@ -288,30 +352,44 @@ impl<R: gfx::Resources, C: gfx::CommandBuffer<R>> PostLighting<R, C> {
 			&self.luminance_smooth.1,
 			&self.luminance_acc.2,
 		);
-		// Bloom
-		// 1. extract high luminance
-		self.full_screen_pass(
-			encoder,
-			&self.highlight_pso,
-			&ping_pong_full[0].1,
-			&ping_pong_half[0].2,
-		);
-		// 2. horizontal 4x, 9x9 gaussian blur
-		self.full_screen_pass(
-			encoder,
-			&self.blur_h_pso,
-			&ping_pong_half[0].1,
-			&ping_pong_half[1].2,
-		);
-		// 2. vertical 4x, 9x9 gaussian blur
-		self.full_screen_pass(
-			encoder,
-			&self.blur_v_pso,
-			&ping_pong_half[1].1,
-			&ping_pong_half[0].2,
-		);
+		// Bloom, composed as black when skipped
+		if quality == Quality::Low {
+			encoder.clear(&ping_pong_half[0].2, [0.; 4]);
+		} else {
+			// 1. extract high luminance
+			self.full_screen_pass(
+				encoder,
+				&self.highlight_pso,
+				&ping_pong_full[0].1,
+				&ping_pong_half[0].2,
+			);
+			// 2. horizontal 4x, 9x9 gaussian blur
+			self.full_screen_pass(
+				encoder,
+				&self.blur_h_pso,
+				&ping_pong_half[0].1,
+				&ping_pong_half[1].2,
+			);
+			// 2. vertical 4x, 9x9 gaussian blur
+			self.full_screen_pass(
+				encoder,
+				&self.blur_v_pso,
+				&ping_pong_half[1].1,
+				&ping_pong_half[0].2,
+			);
+		}
 
-		// compose tone mapped + bloom and resolve
+		// compose tone mapped + bloom and resolve, split between the outputs
+		encoder.update_constant_buffer(
+			&self.compose_fragment_args,
+			&ComposeFragmentArgs {
+				warp: projection.warp,
+				outputs: projection.outputs.max(1).min(MAX_OUTPUTS) as f32,
+				overlap: projection.overlap.max(0.).min(0.5),
+				blend_gamma: projection.blend_gamma.max(0.1),
+				padding: 0.,
+			},
+		);
 		encoder.draw(
 			&self.index_buffer_slice,
 			&self.compose_pso,
@@ -321,6 +399,7 @@ impl<R: gfx::Resources, C: gfx::CommandBuffer<R>> PostLighting<R, C> {
 				src1: (ping_pong_full[0].1.clone(), self.nearest_sampler.clone()),
 				// bloom
 				src2: (ping_pong_half[0].1.clone(), self.linear_sampler.clone()),
+				fragment_args: self.compose_fragment_args.clone(),
 				dst: color_target.clone(),
 			},
 		);
@@ -160,6 +160,8 @@ impl<R: gfx::Resources, C: gfx::CommandBuffer<R>, D> ForwardLighting<R, C, D>
 		let stage_shaders = load_shaders!("lighting", "lighting_stage")?;
 		let particle_shaders = load_shaders!("unlit", "ripple_particle")?;
 		let ball_shaders = load_shaders!("point_ball", "lighting", "lighting_poly")?;
+		let shadow_shaders = load_shaders!("unlit", "shadow")?;
+		let background_shaders = load_shaders!("unlit", "background")?;
 
 		let solid_rasterizer = gfx::state::Rasterizer {
 			samples: Some(gfx::state::MultiSample),
@@ -229,6 +231,20 @@ impl<R: gfx::Resources, C: gfx::CommandBuffer<R>, D> ForwardLighting<R, C, D>
 			&flat_shaders,
 			gfx::Primitive::LineList,
 			debug_line_rasterizer,
+			init.clone(),
+		)?;
+		let shadow_pso = Self::new_pso(
+			factory,
+			&shadow_shaders,
+			gfx::Primitive::TriangleList,
+			solid_rasterizer,
+			init.clone(),
+		)?;
+		let background_pso = Self::new_pso(
+			factory,
+			&background_shaders,
+			gfx::Primitive::TriangleList,
+			solid_rasterizer,
 			init,
 		)?;
 		Ok(ForwardLighting {
@@ -246,6 +262,8 @@ impl<R: gfx::Resources, C: gfx::CommandBuffer<R>, D> ForwardLighting<R, C, D>
 				lit_pso,
 				lines_pso,
 				debug_lines_pso,
+				shadow_pso,
+				background_pso,
 			],
 			_buffer: PhantomData,
 		})
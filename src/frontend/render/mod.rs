@@ -3,6 +3,8 @@ pub mod formats;
 #[macro_use]
 mod forward;
 
+pub use self::effects::{Projection, Quality, MAX_OUTPUTS};
+
 use core::geometry::Position;
 use core::geometry::M44;
 use core::resource::ResourceLoader;
@@ -37,9 +39,34 @@ pub enum Style {
 	Lit,
 	Lines,
 	DebugLines,
+	Shadow,
+	Background,
 	Count,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+	Starfield = 0,
+	Grid,
+	Nebula,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackgroundLayer {
+	pub pattern: Pattern,
+	pub color: formats::Rgba,
+	// 0: fixed in world space, 1: fixed to the camera
+	pub parallax: f32,
+	// pattern cells per world unit
+	pub density: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Background {
+	pub color: formats::Rgba,
+	pub layers: &'static [BackgroundLayer],
+}
+
 impl Appearance {
 	pub fn new(color: formats::Rgba, effect: formats::Float4) -> Self { Appearance { color, effect } }
 
@@ -206,6 +233,7 @@ pub trait Draw {
 	fn draw_star(&mut self, style: Option<Style>, transform: M44, vertices: &[Position], appearance: Appearance);
 	fn draw_lines(&mut self, style: Option<Style>, transform: M44, vertices: &[Position], appearance: Appearance);
 	fn draw_ball(&mut self, style: Option<Style>, transform: M44, appearance: Appearance);
+	fn draw_ribbon(&mut self, style: Option<Style>, transform: M44, left: &[Position], right: &[Position], appearance: Appearance);
 }
 
 pub trait DrawBatch {
@@ -314,6 +342,28 @@ where T: PrimitiveSequence
 			appearance,
 		).expect("Unable to draw ball");
 	}
+
+	fn draw_ribbon(&mut self, style: Option<Style>, transform: M44, left: &[Position], right: &[Position], appearance: Appearance) {
+		// a strip of quads between two edges: u runs across (0 left, 1 right), v along (0 first, 1 last)
+		let n = left.len().min(right.len());
+		if n > 1 {
+			let dv = 1. / (n - 1) as f32;
+			let mut v: Vec<Vertex> = Vec::with_capacity(n * 2);
+			for k in 0..n {
+				v.push(Vertex::new([left[k].x, left[k].y, 0.0], [0.0, k as f32 * dv]));
+				v.push(Vertex::new([right[k].x, right[k].y, 0.0], [1.0, k as f32 * dv]));
+			}
+			let mut i: Vec<VertexIndex> = Vec::new();
+			for k in 0..n - 1 {
+				let l0 = (2 * k) as VertexIndex;
+				let (r0, l1, r1) = (l0 + 1, l0 + 2, l0 + 3);
+				i.extend_from_slice(&[l0, r0, r1, l0, r1, l1]);
+			}
+
+			self.push_primitive(style.unwrap_or(Style::Flat), v, i, transform, appearance)
+				.expect("Unable to draw ribbon");
+		}
+	}
 }
 
 impl PrimitiveBatch {
@@ -443,7 +493,7 @@ pub enum Light {
 pub trait Renderer<R: gfx::Resources, C: gfx::CommandBuffer<R>>: Draw {
 	fn setup_frame(&mut self, camera: &Camera, background_color: formats::Rgba, lights: &[Light]);
 	fn begin_frame(&mut self);
-	fn resolve_frame_buffer(&mut self);
+	fn resolve_frame_buffer(&mut self, projection: &Projection, quality: Quality);
 	fn end_frame<D: gfx::Device<Resources = R, CommandBuffer = C>>(&mut self, device: &mut D);
 	fn cleanup<D: gfx::Device<Resources = R, CommandBuffer = C>>(&mut self, device: &mut D);
 }
@@ -647,9 +697,9 @@ impl<'e, 'l, R: gfx::Resources, C: 'e + gfx::CommandBuffer<R>, F: Factory<R>, L:
 		self.encoder.clear(&self.frame_buffer, self.background_color);
 	}
 
-	fn resolve_frame_buffer(&mut self) {
+	fn resolve_frame_buffer(&mut self, projection: &Projection, quality: Quality) {
 		self.pass_effects
-			.apply_all(&mut self.encoder, &self.hdr_srv, &self.frame_buffer);
+			.apply_all(&mut self.encoder, &self.hdr_srv, &self.frame_buffer, projection, quality);
 	}
 
 	fn end_frame<D: gfx::Device<Resources = R, CommandBuffer = C>>(&mut self, device: &mut D) {
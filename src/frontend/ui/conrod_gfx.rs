@@ -626,7 +626,7 @@ fn gamma_srgb_to_linear(c: [f32; 4]) -> [f32; 4] {
 }
 
 // Creates a gfx texture with the given data
-fn create_texture<F, R>(factory: &mut F, width: u32, height: u32, data: &[u8])
+pub fn create_texture<F, R>(factory: &mut F, width: u32, height: u32, data: &[u8])
 						-> (gfx::handle::Texture<R, SurfaceFormat>, gfx::handle::ShaderResourceView<R, [f32; 4]>)
 	where R: gfx::Resources, F: gfx::Factory<R>
 {
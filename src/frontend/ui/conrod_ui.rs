@@ -1,6 +1,7 @@
 use super::conrod_gfx;
-use super::{theme, Error, Screen};
+use super::{theme, Error, Screen, TextEdit};
 use app;
+use backend::world::gen::Dna;
 use conrod::widget::button;
 use conrod::widget::text;
 use conrod::{self, event, widget, Colorable, Labelable, Positionable, Sizeable, Widget};
@@ -8,7 +9,10 @@ use core::resource::ResourceLoader;
 use frontend::render::formats;
 use gfx::handle::{RenderTargetView, ShaderResourceView};
 use gfx::{CommandBuffer, Encoder, Factory, Resources};
+use image;
+use std::collections::HashMap;
 use std::io;
+use std::path;
 use std::vec::Drain;
 
 #[derive(Clone, Debug)]
@@ -27,10 +31,89 @@ pub struct Ids {
 	hud_speed_button: widget::Id,
 	hud_canvas: widget::Id,
 	hud_labels: Vec<WidgetIdGroup>,
+
+	ruler_text: widget::Id,
+	world_labels: Vec<widget::Id>,
+	annotation_text_box: widget::Id,
+
+	inspector_canvas: widget::Id,
+	inspector_text: widget::Id,
+	inspector_freeze_button: widget::Id,
+	inspector_possess_button: widget::Id,
+	inspector_genome_button: widget::Id,
+
+	console_canvas: widget::Id,
+	console_log: widget::Id,
+	console_text_box: widget::Id,
+
+	toast_text: widget::Id,
+	diagnostics_text: widget::Id,
+
+	quick_load_canvas: widget::Id,
+	quick_load_empty: widget::Id,
+	quick_load_images: Vec<widget::Id>,
+	quick_load_buttons: Vec<widget::Id>,
+
+	challenges_canvas: widget::Id,
+	challenges_empty: widget::Id,
+	challenges_buttons: Vec<widget::Id>,
+
+	compare_canvas: widget::Id,
+	compare_titles: Vec<widget::Id>,
+	compare_frames: Vec<widget::Id>,
+	compare_paths: Vec<widget::Id>,
+	compare_legend: Vec<widget::Id>,
+
+	genealogy_canvas: widget::Id,
+	genealogy_text: widget::Id,
+	genealogy_buttons: Vec<widget::Id>,
+	genealogy_diff_buttons: Vec<widget::Id>,
+
+	genome_canvas: widget::Id,
+	genome_title: widget::Id,
+	genome_close: widget::Id,
+	genome_frames: Vec<widget::Id>,
+	genome_outlines: Vec<widget::Id>,
+	genome_genes: Vec<widget::Id>,
+	genome_changes: widget::Id,
+
+	preview_canvas: widget::Id,
+	preview_title: widget::Id,
+	preview_image: widget::Id,
+	preview_traits: widget::Id,
+	preview_spawn: widget::Id,
+	preview_close: widget::Id,
+	preview_edit: widget::Id,
+
+	editor_canvas: widget::Id,
+	editor_title: widget::Id,
+	editor_prev: widget::Id,
+	editor_next: widget::Id,
+	editor_sliders: Vec<widget::Id>,
+
+	breeding_canvas: widget::Id,
+	breeding_title: widget::Id,
+	breeding_again: widget::Id,
+	breeding_close: widget::Id,
+	breeding_images: Vec<widget::Id>,
+	breeding_buttons: Vec<widget::Id>,
 }
 
 pub type ImageMap<R> = conrod::image::Map<(ShaderResourceView<R, [f32; 4]>, (u32, u32))>;
 
+// a line for each run compared, in the same order as the legend
+const RUN_COLORS: &[conrod::color::Color] = &[
+	conrod::color::LIGHT_BLUE,
+	conrod::color::LIGHT_ORANGE,
+	conrod::color::LIGHT_GREEN,
+	conrod::color::LIGHT_PURPLE,
+	conrod::color::LIGHT_YELLOW,
+	conrod::color::LIGHT_RED,
+];
+
+// loaded once each, with their width over height, none if they could not be read
+type Thumbnails = HashMap<path::PathBuf, Option<(conrod::image::Id, f64)>>;
+
 #[derive(Clone, Debug)]
 pub struct Styles {
 	pub label: text::Style,
@@ -46,12 +129,18 @@ where
 	renderer: conrod_gfx::Renderer<'font, R>,
 	ui: Box<conrod::Ui>,
 	image_map: ImageMap<R>,
+	thumbnails: Thumbnails,
+	// the pictures of the previewed genomes, the slot of the preview pane first and those of the breeding after
+	previews: Vec<(Dna, conrod::image::Id)>,
 	win_w: u16,
 	win_h: u16,
 	hidpi_factor: f64,
+	// on top of the hidpi factor, everything is drawn this much bigger
+	scale: f64,
 	styles: Styles,
 	ids: Ids,
 	app_events: Vec<app::Event>,
+	text_edits: Vec<TextEdit>,
 	events: Vec<event::Input>,
 }
 
@@ -70,7 +159,10 @@ impl Screen {
 		root_window_id: widget::Id,
 		styles: &Styles,
 		ids: &Ids,
+		thumbnails: &Thumbnails,
+		previews: &[(Dna, conrod::image::Id)],
 		app_events: &mut Vec<app::Event>,
+		text_edits: &mut Vec<TextEdit>,
 	) -> conrod::UiCell<'e>
 	{
 		let mut widgets = ui.set_widgets();
@@ -91,6 +183,7 @@ impl Screen {
 					.set(ids.help_text, &mut widgets);
 			}
 			Screen::Main(ref frame_update) => {
+				let strings = &frame_update.strings;
 				let splits = ids
 					.hud_labels
 					.iter()
@@ -178,31 +271,31 @@ impl Screen {
 				txt_with_label(
 					&mut ids_iter,
 					&mut widgets,
-					"Sim Frames",
+					strings.get("hud.sim_frames"),
 					&format!("{}", frame_update.simulation.count),
 				);
 				txt_with_label(
 					&mut ids_iter,
 					&mut widgets,
-					"Vid Frames",
+					strings.get("hud.vid_frames"),
 					&format!("{}", frame_update.count),
 				);
 				txt_with_label(
 					&mut ids_iter,
 					&mut widgets,
-					"Elapsed",
+					strings.get("hud.elapsed"),
 					&format!("{:.3}", frame_update.elapsed),
 				);
 				txt_with_label(
 					&mut ids_iter,
 					&mut widgets,
-					"Sim dt",
+					strings.get("hud.sim_dt"),
 					&format!("{:.3}", frame_update.simulation.dt),
 				);
 				txt_with_label(
 					&mut ids_iter,
 					&mut widgets,
-					"Vid dt",
+					strings.get("hud.vid_dt"),
 					&format!("{:.3}", frame_update.dt),
 				);
 				if button_with_label(
@@ -217,22 +310,654 @@ impl Screen {
 				txt_with_label(
 					&mut ids_iter,
 					&mut widgets,
-					"Avg dt",
+					strings.get("hud.avg_dt"),
 					&format!("{:.3}", frame_update.duration_smooth),
 				);
-				txt_with_label(&mut ids_iter, &mut widgets, "FPS", &format!("{:.1}", frame_update.fps));
+				let fps = format!("{:.1}", frame_update.fps);
+				txt_with_label(&mut ids_iter, &mut widgets, strings.get("hud.fps"), &fps);
 				txt_with_label(
 					&mut ids_iter,
 					&mut widgets,
-					"Population",
+					strings.get("hud.memory"),
+					&format!("{:.0} MB", frame_update.memory.total() as f64 / app::constants::MEGABYTE as f64),
+				);
+				txt_with_label(
+					&mut ids_iter,
+					&mut widgets,
+					strings.get("hud.population"),
 					&format!("{}", frame_update.simulation.population),
 				);
 				txt_with_label(
 					&mut ids_iter,
 					&mut widgets,
-					"Extinctions",
+					strings.get("hud.extinctions"),
 					&format!("{}", frame_update.simulation.extinctions),
 				);
+				txt_with_label(
+					&mut ids_iter,
+					&mut widgets,
+					strings.get("hud.energy_flow"),
+					&format!("{:+.1}/s", frame_update.simulation.energy.minion_balance()),
+				);
+				if let Some(ref score) = frame_update.simulation.score {
+					txt_with_label(
+						&mut ids_iter,
+						&mut widgets,
+						strings.get("hud.score"),
+						&format!("{:.0} ({}/{})", score.points, score.met_count(), score.met.len()),
+					);
+				}
+
+				for (label, &label_id) in frame_update.labels.iter().zip(ids.world_labels.iter()) {
+					widget::Text::new(&label.text)
+						.x_y(f64::from(label.position.x), f64::from(label.position.y))
+						.parent(root_window_id)
+						.with_style(styles.label)
+						.set(label_id, &mut widgets);
+				}
+
+				if let Some(cursor) = frame_update.cursor {
+					widget::Text::new(&format!("x: {:.2} y: {:.2}", cursor.x, cursor.y))
+						.bottom_left_with_margin_on(root_window_id, 20.0)
+						.with_style(styles.value)
+						.set(ids.ruler_text, &mut widgets);
+				}
+
+				if let Some(ref inspector) = frame_update.inspector {
+					widget::Canvas::new()
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.4))
+						.w_h(240.0, 205.0)
+						.top_right_with_margin_on(root_window_id, 50.0)
+						.set(ids.inspector_canvas, &mut widgets);
+
+					widget::Text::new(&format!(
+						"{} {}\n{} {:.1} ({:.0}%)\n{} {:.1}s, {:?}\n{} {}\n{} {:.0}%{}{}",
+						strings.get("inspector.agent"),
+						inspector.id,
+						strings.get("inspector.energy"),
+						inspector.energy,
+						inspector.energy_ratio * 100.,
+						strings.get("inspector.age"),
+						inspector.age.get(),
+						inspector.stage,
+						strings.get("inspector.segments"),
+						inspector.segments,
+						strings.get("inspector.resistance"),
+						inspector.resistance * 100.,
+						if inspector.is_infected { ", " } else { "" },
+						if inspector.is_infected { strings.get("inspector.infected") } else { "" },
+					)).top_left_of(ids.inspector_canvas)
+						.with_style(styles.label)
+						.set(ids.inspector_text, &mut widgets);
+
+					if widget::Button::new()
+						.label(strings.get(if inspector.is_frozen { "inspector.thaw" } else { "inspector.freeze" }))
+						.w_h(100.0, 30.0)
+						.bottom_left_of(ids.inspector_canvas)
+						.with_style(styles.button)
+						.set(ids.inspector_freeze_button, &mut widgets)
+						.was_clicked()
+					{
+						app_events.push(app::Event::ToggleFreezeSelected);
+					}
+
+					let possess = if inspector.is_possessed { "inspector.release" } else { "inspector.possess" };
+					if widget::Button::new()
+						.label(strings.get(possess))
+						.w_h(100.0, 30.0)
+						.bottom_right_of(ids.inspector_canvas)
+						.with_style(styles.button)
+						.set(ids.inspector_possess_button, &mut widgets)
+						.was_clicked()
+					{
+						app_events.push(app::Event::TogglePossess);
+					}
+
+					if widget::Button::new()
+						.label(strings.get("genome.compare"))
+						.w_h(220.0, 30.0)
+						.mid_bottom_with_margin_on(ids.inspector_canvas, 35.0)
+						.with_style(styles.button)
+						.set(ids.inspector_genome_button, &mut widgets)
+						.was_clicked()
+					{
+						app_events.push(app::Event::DiffGenomes(inspector.id, None));
+					}
+				}
+
+				if let Some(ref genealogy) = frame_update.genealogy {
+					let rows = genealogy.relatives.len().min(ids.genealogy_buttons.len());
+					widget::Canvas::new()
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.4))
+						.w_h(240.0, 70.0 + 30.0 * rows as f64)
+						.top_right_with_margins_on(root_window_id, 265.0, 50.0)
+						.set(ids.genealogy_canvas, &mut widgets);
+
+					let dead = |alive: bool| if alive { "" } else { " \u{2020}" };
+					let mut title = format!(
+						"{} {}{}\n{}",
+						strings.get("inspector.agent"),
+						genealogy.id,
+						dead(genealogy.alive),
+						strings.fill("genealogy.generation", &[&genealogy.generation.to_string()])
+					);
+					if let Some((changed, total)) = genealogy.changed {
+						title.push('\n');
+						title.push_str(&strings.fill("genealogy.changed", &[&changed.to_string(), &total.to_string()]));
+					}
+					widget::Text::new(&title)
+						.top_left_of(ids.genealogy_canvas)
+						.with_style(styles.label)
+						.set(ids.genealogy_text, &mut widgets);
+
+					// the living are where the camera goes, the dead can still be browsed
+					for (i, relative) in genealogy.relatives.iter().take(rows).enumerate() {
+						let kinship = strings.get(relative.kinship.key());
+						let label = format!("{} {}{}", kinship, relative.id, dead(relative.alive));
+						if widget::Button::new()
+							.label(&label)
+							.w_h(186.0, 26.0)
+							.top_left_with_margins_on(ids.genealogy_canvas, 60.0 + 30.0 * i as f64, 0.0)
+							.with_style(styles.button)
+							.set(ids.genealogy_buttons[i], &mut widgets)
+							.was_clicked()
+						{
+							app_events.push(app::Event::FocusRelative(relative.id));
+						}
+						if widget::Button::new()
+							.label("\u{2260}")
+							.w_h(30.0, 26.0)
+							.top_left_with_margins_on(ids.genealogy_canvas, 60.0 + 30.0 * i as f64, 190.0)
+							.with_style(styles.button)
+							.set(ids.genealogy_diff_buttons[i], &mut widgets)
+							.was_clicked()
+						{
+							app_events.push(app::Event::DiffGenomes(genealogy.id, Some(relative.id)));
+						}
+					}
+				}
+
+				if let Some(ref diff) = frame_update.genome_diff {
+					let (column_w, frame_h, gene_w, gene_h) = (192.0, 140.0, 24.0, 18.0);
+					let genes = diff.len().min(app::constants::GENOME_DIFF_GENES_MAX);
+					let rows = (genes + 7) / 8;
+					let consequences = diff.consequences();
+					let genes_top = 40.0 + frame_h + 10.0;
+					let changes_top = genes_top + gene_h * rows as f64 + 10.0;
+					widget::Canvas::new()
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.6))
+						.w_h(2.0 * column_w + 40.0, changes_top + 20.0 * consequences.len().max(1) as f64 + 20.0)
+						.top_left_with_margins_on(root_window_id, 50.0, 20.0)
+						.set(ids.genome_canvas, &mut widgets);
+
+					let title = strings.fill(
+						"genome.title",
+						&[
+							&diff.left.id.to_string(),
+							&diff.right.id.to_string(),
+							&diff.changed().to_string(),
+							&diff.len().to_string(),
+						],
+					);
+					widget::Text::new(&title)
+						.top_left_of(ids.genome_canvas)
+						.with_style(styles.label)
+						.set(ids.genome_title, &mut widgets);
+					if widget::Button::new()
+						.label("x")
+						.w_h(26.0, 26.0)
+						.top_right_of(ids.genome_canvas)
+						.with_style(styles.button)
+						.set(ids.genome_close, &mut widgets)
+						.was_clicked()
+					{
+						app_events.push(app::Event::CloseGenomeDiff);
+					}
+
+					// both bodies to the same scale, so that the sizes compare too
+					let radius = diff.left.plan.radius().max(diff.right.plan.radius()).max(1e-3);
+					let scale = 0.45 * frame_h / f64::from(radius);
+					for (side, &(plan, dna)) in [(&diff.left.plan, &diff.left.dna), (&diff.right.plan, &diff.right.dna)]
+						.iter()
+						.enumerate()
+					{
+						let left = (column_w + 20.0) * side as f64;
+						widget::Rectangle::outline([column_w, frame_h])
+							.top_left_with_margins_on(ids.genome_canvas, 40.0, left)
+							.color(conrod::color::GRAY)
+							.set(ids.genome_frames[side], &mut widgets);
+						if let Some(center) = widgets.rect_of(ids.genome_frames[side]).map(|rect| rect.xy()) {
+							let [r, g, b, _] = plan.color;
+							let max_segments = app::constants::MINION_MAX_SEGMENTS;
+							for (i, outline) in plan.outlines.iter().take(max_segments).enumerate() {
+								let points = outline
+									.iter()
+									.chain(outline.first())
+									.map(|p| [center[0] + f64::from(p.x) * scale, center[1] + f64::from(p.y) * scale])
+									.collect::<Vec<_>>();
+								widget::PointPath::abs(points)
+									.color(conrod::color::rgba(r, g, b, 1.0))
+									.thickness(1.5)
+									.set(ids.genome_outlines[side * max_segments + i], &mut widgets);
+							}
+						}
+						// eight genes a row, those that differ lit up
+						for (i, gene) in dna.iter().take(genes).enumerate() {
+							let color = if diff.is_changed(i) {
+								conrod::color::LIGHT_ORANGE
+							} else {
+								conrod::color::DARK_GRAY
+							};
+							widget::Text::new(&format!("{:02x}", gene))
+								.top_left_with_margins_on(
+									ids.genome_canvas,
+									genes_top + gene_h * (i / 8) as f64,
+									left + gene_w * (i % 8) as f64,
+								).with_style(styles.label)
+								.color(color)
+								.set(ids.genome_genes[side * app::constants::GENOME_DIFF_GENES_MAX + i], &mut widgets);
+						}
+					}
+
+					let changes = if consequences.is_empty() {
+						strings.get("genome.same").to_owned()
+					} else {
+						consequences.join("\n")
+					};
+					widget::Text::new(&changes)
+						.top_left_with_margins_on(ids.genome_canvas, changes_top, 0.0)
+						.with_style(styles.label)
+						.set(ids.genome_changes, &mut widgets);
+				}
+
+				// found by genome, a picture is a frame late the first time while it is uploaded
+				let preview_image = |dna: &Dna| {
+					previews.iter().find(|&&(ref uploaded, _)| uploaded == dna).map(|&(_, image_id)| image_id)
+				};
+
+				if let Some(ref preview) = frame_update.preview {
+					let size = app::constants::PREVIEW_SIZE as f64;
+					widget::Canvas::new()
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.6))
+						.w_h(size + 60.0, size + 265.0)
+						.top_left_with_margins_on(root_window_id, 50.0, 470.0)
+						.set(ids.preview_canvas, &mut widgets);
+					widget::Text::new(&preview.source)
+						.top_left_of(ids.preview_canvas)
+						.with_style(styles.label)
+						.set(ids.preview_title, &mut widgets);
+					if widget::Button::new()
+						.label("x")
+						.w_h(26.0, 26.0)
+						.top_right_of(ids.preview_canvas)
+						.with_style(styles.button)
+						.set(ids.preview_close, &mut widgets)
+						.was_clicked()
+					{
+						app_events.push(app::Event::ClosePreview);
+					}
+					if let Some(image_id) = preview_image(&preview.dna) {
+						widget::Image::new(image_id)
+							.w_h(size, size)
+							.mid_top_with_margin_on(ids.preview_canvas, 35.0)
+							.set(ids.preview_image, &mut widgets);
+					}
+					widget::Text::new(&preview.plan.summary())
+						.w(size + 40.0)
+						.wrap_by_word()
+						.top_left_with_margins_on(ids.preview_canvas, size + 45.0, 0.0)
+						.with_style(styles.label)
+						.set(ids.preview_traits, &mut widgets);
+					if widget::Button::new()
+						.label(strings.get("preview.spawn"))
+						.w_h(size + 40.0, 30.0)
+						.mid_bottom_of(ids.preview_canvas)
+						.with_style(styles.button)
+						.set(ids.preview_spawn, &mut widgets)
+						.was_clicked()
+					{
+						app_events.push(app::Event::SpawnPreview);
+					}
+					let edit = if frame_update.genome_editor.is_some() { "editor.close" } else { "editor.open" };
+					if widget::Button::new()
+						.label(strings.get(edit))
+						.w_h(size + 40.0, 30.0)
+						.mid_bottom_with_margin_on(ids.preview_canvas, 35.0)
+						.with_style(styles.button)
+						.set(ids.preview_edit, &mut widgets)
+						.was_clicked()
+					{
+						app_events.push(app::Event::ToggleGenomeEditor);
+					}
+
+					if let Some(first) = frame_update.genome_editor {
+						let rows = app::constants::GENOME_EDITOR_ROWS;
+						let genes = preview.dna.len();
+						let first = first.min(genes.saturating_sub(1)) / rows * rows;
+						let last = (first + rows).min(genes);
+						widget::Canvas::new()
+							.pad(10.0)
+							.color(conrod::color::CHARCOAL.alpha(0.6))
+							.w_h(300.0, 40.0 + 32.0 * rows as f64 + 10.0)
+							.top_left_with_margins_on(root_window_id, 50.0, 470.0 + size + 70.0)
+							.set(ids.editor_canvas, &mut widgets);
+						let title = strings.fill(
+							"editor.genes",
+							&[&first.to_string(), &(last - 1).to_string(), &genes.to_string()],
+						);
+						widget::Text::new(&title)
+							.top_left_of(ids.editor_canvas)
+							.with_style(styles.label)
+							.set(ids.editor_title, &mut widgets);
+						if widget::Button::new()
+							.label("<")
+							.w_h(26.0, 26.0)
+							.top_right_with_margins_on(ids.editor_canvas, 0.0, 30.0)
+							.with_style(styles.button)
+							.set(ids.editor_prev, &mut widgets)
+							.was_clicked()
+						{
+							app_events.push(app::Event::ShowGenes(first.saturating_sub(rows)));
+						}
+						if widget::Button::new()
+							.label(">")
+							.w_h(26.0, 26.0)
+							.top_right_of(ids.editor_canvas)
+							.with_style(styles.button)
+							.set(ids.editor_next, &mut widgets)
+							.was_clicked() && last < genes
+						{
+							app_events.push(app::Event::ShowGenes(last));
+						}
+						// every change grows the preview again
+						for (i, gene) in (first..last).enumerate() {
+							let value = preview.dna[gene];
+							if let Some(changed) = widget::Slider::new(f32::from(value), 0., 255.)
+								.label(&format!("{}: {:02x}", gene, value))
+								.label_font_size(12)
+								.label_color(conrod::color::LIGHT_GRAY)
+								.w_h(280.0, 26.0)
+								.top_left_with_margins_on(ids.editor_canvas, 40.0 + 32.0 * i as f64, 0.0)
+								.color(conrod::color::DARK_BLUE)
+								.set(ids.editor_sliders[i], &mut widgets)
+							{
+								let changed = changed.round() as u8;
+								if changed != value {
+									app_events.push(app::Event::EditGene(gene, changed));
+								}
+							}
+						}
+					}
+				}
+
+				if let Some(ref workbench) = frame_update.breeding {
+					let (cell_w, image_size) = (130.0, 120.0);
+					let candidates = workbench.candidates.len().min(ids.breeding_buttons.len());
+					widget::Canvas::new()
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.6))
+						.w_h(cell_w * candidates.max(2) as f64 + 20.0, 40.0 + image_size + 50.0)
+						.bottom_right_with_margin_on(root_window_id, 20.0)
+						.set(ids.breeding_canvas, &mut widgets);
+					let title = strings.fill(
+						"breeding.title",
+						&[
+							&workbench.round.to_string(),
+							&workbench.parents[0].id.to_string(),
+							&workbench.parents[1].id.to_string(),
+						],
+					);
+					widget::Text::new(&title)
+						.top_left_of(ids.breeding_canvas)
+						.with_style(styles.label)
+						.set(ids.breeding_title, &mut widgets);
+					if widget::Button::new()
+						.label(strings.get("breeding.again"))
+						.w_h(100.0, 26.0)
+						.top_right_with_margins_on(ids.breeding_canvas, 0.0, 30.0)
+						.with_style(styles.button)
+						.set(ids.breeding_again, &mut widgets)
+						.was_clicked()
+					{
+						app_events.push(app::Event::Rebreed);
+					}
+					if widget::Button::new()
+						.label("x")
+						.w_h(26.0, 26.0)
+						.top_right_of(ids.breeding_canvas)
+						.with_style(styles.button)
+						.set(ids.breeding_close, &mut widgets)
+						.was_clicked()
+					{
+						app_events.push(app::Event::CloseBreeding);
+					}
+					// the one picked is spawned and bred again with the second parent
+					for (i, candidate) in workbench.candidates.iter().take(candidates).enumerate() {
+						let left = cell_w * i as f64;
+						if let Some(image_id) = preview_image(&candidate.dna) {
+							widget::Image::new(image_id)
+								.w_h(image_size, image_size)
+								.top_left_with_margins_on(ids.breeding_canvas, 35.0, left)
+								.set(ids.breeding_images[i], &mut widgets);
+						}
+						if widget::Button::new()
+							.label(&strings.fill("breeding.pick", &[&(i + 1).to_string()]))
+							.w_h(image_size, 30.0)
+							.top_left_with_margins_on(ids.breeding_canvas, 40.0 + image_size, left)
+							.with_style(styles.button)
+							.set(ids.breeding_buttons[i], &mut widgets)
+							.was_clicked()
+						{
+							app_events.push(app::Event::PickCandidate(i));
+						}
+					}
+				}
+
+				if !frame_update.toasts.is_empty() {
+					widget::Text::new(&frame_update.toasts.join("\n"))
+						.mid_top_with_margin_on(root_window_id, 20.0)
+						.center_justify()
+						.font_size(16)
+						.color(conrod::color::LIGHT_ORANGE)
+						.set(ids.toast_text, &mut widgets);
+				}
+
+				if let Some(ref diagnostics) = frame_update.diagnostics {
+					let lines = [
+						strings.fill(
+							"diagnostics.latency",
+							&[&format!("{:.1}", diagnostics.latency), &format!("{:.1}", diagnostics.peak_latency())],
+						),
+						strings.fill(
+							"diagnostics.events",
+							&[&diagnostics.events.to_string(), &diagnostics.peak_events().to_string()],
+						),
+						strings.fill("diagnostics.dropped", &[&diagnostics.dropped_taps.to_string()]),
+						strings.fill(
+							"diagnostics.missed",
+							&[&diagnostics.missed_frames.to_string(), &diagnostics.frames.to_string()],
+						),
+					];
+					widget::Text::new(&lines.join("\n"))
+						.top_right_with_margin_on(root_window_id, 20.0)
+						.right_justify()
+						.with_style(styles.label)
+						.set(ids.diagnostics_text, &mut widgets);
+				}
+
+				if let Some(ref console) = frame_update.console {
+					widget::Canvas::new()
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.6))
+						.w_h(600.0, 260.0)
+						.mid_bottom_with_margin_on(root_window_id, 20.0)
+						.set(ids.console_canvas, &mut widgets);
+
+					widget::Text::new(&console.lines.join("\n"))
+						.top_left_of(ids.console_canvas)
+						.with_style(styles.label)
+						.set(ids.console_log, &mut widgets);
+
+					for event in widget::TextBox::new(&console.input)
+						.w_h(580.0, 30.0)
+						.mid_bottom_of(ids.console_canvas)
+						.font_size(14)
+						.text_color(conrod::color::LIGHT_GRAY)
+						.color(conrod::color::BLACK.alpha(0.4))
+						.set(ids.console_text_box, &mut widgets)
+					{
+						match event {
+							widget::text_box::Event::Update(text) => text_edits.push(TextEdit::Update(text)),
+							widget::text_box::Event::Enter => text_edits.push(TextEdit::Commit),
+						}
+					}
+				}
+
+				if let Some(ref entries) = frame_update.quick_load {
+					let rows = entries.len().min(ids.quick_load_buttons.len());
+					widget::Canvas::new()
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.6))
+						.w_h(440.0, 20.0 + 60.0 * rows.max(1) as f64)
+						.middle_of(root_window_id)
+						.set(ids.quick_load_canvas, &mut widgets);
+
+					if entries.is_empty() {
+						widget::Text::new(strings.get("quick_load.empty"))
+							.middle_of(ids.quick_load_canvas)
+							.with_style(styles.label)
+							.set(ids.quick_load_empty, &mut widgets);
+					}
+
+					for (i, entry) in entries.iter().take(rows).enumerate() {
+						let top = 60.0 * i as f64;
+						let thumbnail = entry.thumbnail.as_ref().and_then(|file| thumbnails.get(file).cloned());
+						if let Some(Some((image_id, aspect))) = thumbnail {
+							widget::Image::new(image_id)
+								.w_h((50.0 * aspect).min(100.0), 50.0)
+								.top_left_with_margins_on(ids.quick_load_canvas, top, 0.0)
+								.set(ids.quick_load_images[i], &mut widgets);
+						}
+						if widget::Button::new()
+							.label(&entry.label)
+							.w_h(300.0, 50.0)
+							.top_left_with_margins_on(ids.quick_load_canvas, top, 120.0)
+							.with_style(styles.button)
+							.set(ids.quick_load_buttons[i], &mut widgets)
+							.was_clicked()
+						{
+							app_events.push(app::Event::QuickLoad(i));
+						}
+					}
+				}
+
+				if let Some(ref entries) = frame_update.challenges {
+					let rows = entries.len().min(ids.challenges_buttons.len());
+					widget::Canvas::new()
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.6))
+						.w_h(440.0, 20.0 + 40.0 * rows.max(1) as f64)
+						.middle_of(root_window_id)
+						.set(ids.challenges_canvas, &mut widgets);
+
+					if entries.is_empty() {
+						widget::Text::new(strings.get("challenges.empty"))
+							.middle_of(ids.challenges_canvas)
+							.with_style(styles.label)
+							.set(ids.challenges_empty, &mut widgets);
+					}
+
+					for (i, entry) in entries.iter().take(rows).enumerate() {
+						if widget::Button::new()
+							.label(&entry.label())
+							.w_h(420.0, 30.0)
+							.top_left_with_margins_on(ids.challenges_canvas, 40.0 * i as f64, 0.0)
+							.with_style(styles.button)
+							.set(ids.challenges_buttons[i], &mut widgets)
+							.was_clicked()
+						{
+							app_events.push(app::Event::StartChallenge(i));
+						}
+					}
+				}
+
+				if let Some(ref comparison) = frame_update.comparison {
+					let (chart_w, chart_h, row_h) = (560.0, 140.0, 170.0);
+					let charts = app::constants::COMPARE_CHARTS;
+					let runs = comparison.runs.len().min(ids.compare_legend.len());
+					widget::Canvas::new()
+						.pad(10.0)
+						.color(conrod::color::CHARCOAL.alpha(0.6))
+						.w_h(chart_w + 20.0, 20.0 + row_h * charts.len() as f64 + 20.0 * runs as f64)
+						.bottom_left_with_margin_on(root_window_id, 20.0)
+						.set(ids.compare_canvas, &mut widgets);
+					// the lines are placed in window coordinates, from the top left of the canvas inside its padding
+					let origin = widgets
+						.rect_of(ids.compare_canvas)
+						.map(|rect| [rect.left() + 10.0, rect.top() - 10.0]);
+
+					for (chart, name) in charts.iter().enumerate() {
+						// all the runs on the same axes, from zero to the highest of any
+						let extent = comparison.extent(chart);
+						let title = format!("{} {:.0}", strings.get(&format!("compare.{}", name)), extent[1]);
+						widget::Text::new(&title)
+							.top_left_with_margins_on(ids.compare_canvas, row_h * chart as f64, 0.0)
+							.with_style(styles.label)
+							.set(ids.compare_titles[chart], &mut widgets);
+						widget::Rectangle::outline([chart_w, chart_h])
+							.top_left_with_margins_on(ids.compare_canvas, row_h * chart as f64 + 25.0, 0.0)
+							.color(conrod::color::GRAY)
+							.set(ids.compare_frames[chart], &mut widgets);
+						let origin = match origin {
+							Some(origin) => origin,
+							None => continue,
+						};
+						let bottom = origin[1] - row_h * chart as f64 - 25.0 - chart_h;
+						let scale = [chart_w / extent[0].max(1.), chart_h / extent[1].max(1e-3)];
+						for (i, run) in comparison.runs.iter().take(runs).enumerate() {
+							if run.series[chart].len() < 2 {
+								continue;
+							}
+							let points = run.series[chart]
+								.iter()
+								.map(|p| [origin[0] + p[0] * scale[0], bottom + p[1] * scale[1]])
+								.collect::<Vec<_>>();
+							widget::PointPath::abs(points)
+								.color(RUN_COLORS[i % RUN_COLORS.len()])
+								.thickness(1.5)
+								.set(ids.compare_paths[chart * app::constants::COMPARE_RUNS_MAX + i], &mut widgets);
+						}
+					}
+
+					for (i, run) in comparison.runs.iter().take(runs).enumerate() {
+						let top = row_h * charts.len() as f64 + 20.0 * i as f64;
+						widget::Text::new(&run.label)
+							.top_left_with_margins_on(ids.compare_canvas, top, 0.0)
+							.with_style(styles.label)
+							.color(RUN_COLORS[i % RUN_COLORS.len()])
+							.set(ids.compare_legend[i], &mut widgets);
+					}
+				}
+
+				if let Some(ref text) = frame_update.editing {
+					for event in widget::TextBox::new(text)
+						.w_h(300.0, 40.0)
+						.mid_bottom_with_margin_on(root_window_id, 20.0)
+						.font_size(14)
+						.text_color(conrod::color::LIGHT_GRAY)
+						.color(conrod::color::CHARCOAL.alpha(0.4))
+						.set(ids.annotation_text_box, &mut widgets)
+					{
+						match event {
+							widget::text_box::Event::Update(text) => text_edits.push(TextEdit::Update(text)),
+							widget::text_box::Event::Enter => text_edits.push(TextEdit::Commit),
+						}
+					}
+				}
 			}
 		};
 		widgets
@@ -280,7 +1005,7 @@ where
 			font_size: Some(14),
 			..Default::default()
 		};
-		const MAX_HUD_LABELS: usize = 10;
+		const MAX_HUD_LABELS: usize = 13;
 		let ids = Ids {
 			help_canvas: ui.widget_id_generator().next(),
 			help_text: ui.widget_id_generator().next(),
@@ -294,6 +1019,102 @@ where
 					label_id: ui.widget_id_generator().next(),
 					value_id: ui.widget_id_generator().next(),
 				}).collect(),
+
+			ruler_text: ui.widget_id_generator().next(),
+			world_labels: (0..app::constants::MAX_WORLD_LABELS)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+			annotation_text_box: ui.widget_id_generator().next(),
+
+			inspector_canvas: ui.widget_id_generator().next(),
+			inspector_text: ui.widget_id_generator().next(),
+			inspector_freeze_button: ui.widget_id_generator().next(),
+			inspector_possess_button: ui.widget_id_generator().next(),
+			inspector_genome_button: ui.widget_id_generator().next(),
+
+			console_canvas: ui.widget_id_generator().next(),
+			console_log: ui.widget_id_generator().next(),
+			console_text_box: ui.widget_id_generator().next(),
+
+			toast_text: ui.widget_id_generator().next(),
+			diagnostics_text: ui.widget_id_generator().next(),
+
+			quick_load_canvas: ui.widget_id_generator().next(),
+			quick_load_empty: ui.widget_id_generator().next(),
+			quick_load_images: (0..app::constants::QUICK_LOAD_ENTRIES)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+			quick_load_buttons: (0..app::constants::QUICK_LOAD_ENTRIES)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+
+			challenges_canvas: ui.widget_id_generator().next(),
+			challenges_empty: ui.widget_id_generator().next(),
+			challenges_buttons: (0..app::constants::CHALLENGE_MENU_ENTRIES)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+
+			compare_canvas: ui.widget_id_generator().next(),
+			compare_titles: (0..app::constants::COMPARE_CHARTS.len())
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+			compare_frames: (0..app::constants::COMPARE_CHARTS.len())
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+			compare_paths: (0..app::constants::COMPARE_CHARTS.len() * app::constants::COMPARE_RUNS_MAX)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+			compare_legend: (0..app::constants::COMPARE_RUNS_MAX)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+
+			genealogy_canvas: ui.widget_id_generator().next(),
+			genealogy_text: ui.widget_id_generator().next(),
+			genealogy_buttons: (0..app::constants::GENEALOGY_RELATIVES_MAX)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+			genealogy_diff_buttons: (0..app::constants::GENEALOGY_RELATIVES_MAX)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+
+			genome_canvas: ui.widget_id_generator().next(),
+			genome_title: ui.widget_id_generator().next(),
+			genome_close: ui.widget_id_generator().next(),
+			genome_frames: (0..2).map(|_| ui.widget_id_generator().next()).collect(),
+			genome_outlines: (0..2 * app::constants::MINION_MAX_SEGMENTS)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+			genome_genes: (0..2 * app::constants::GENOME_DIFF_GENES_MAX)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+			genome_changes: ui.widget_id_generator().next(),
+
+			preview_canvas: ui.widget_id_generator().next(),
+			preview_title: ui.widget_id_generator().next(),
+			preview_image: ui.widget_id_generator().next(),
+			preview_traits: ui.widget_id_generator().next(),
+			preview_spawn: ui.widget_id_generator().next(),
+			preview_close: ui.widget_id_generator().next(),
+			preview_edit: ui.widget_id_generator().next(),
+
+			editor_canvas: ui.widget_id_generator().next(),
+			editor_title: ui.widget_id_generator().next(),
+			editor_prev: ui.widget_id_generator().next(),
+			editor_next: ui.widget_id_generator().next(),
+			editor_sliders: (0..app::constants::GENOME_EDITOR_ROWS)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+
+			breeding_canvas: ui.widget_id_generator().next(),
+			breeding_title: ui.widget_id_generator().next(),
+			breeding_again: ui.widget_id_generator().next(),
+			breeding_close: ui.widget_id_generator().next(),
+			breeding_images: (0..app::constants::BREEDING_CANDIDATES)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
+			breeding_buttons: (0..app::constants::BREEDING_CANDIDATES)
+				.map(|_| ui.widget_id_generator().next())
+				.collect(),
 		};
 
 		Ok(Ui {
@@ -301,9 +1122,12 @@ where
 			renderer,
 			ui: Box::new(ui),
 			image_map,
+			thumbnails: HashMap::new(),
+			previews: Vec::new(),
 			win_w: w,
 			win_h: h,
 			hidpi_factor,
+			scale: 1.,
 			styles: Styles {
 				label: style_label,
 				value: style_value,
@@ -311,6 +1135,7 @@ where
 			},
 			ids,
 			app_events: Vec::new(),
+			text_edits: Vec::new(),
 			events: Vec::new(),
 		})
 	}
@@ -319,10 +1144,29 @@ where
 		let (width, height, _, _) = frame_buffer.get_dimensions();
 		self.win_w = width;
 		self.win_h = height;
-		self.renderer = conrod_gfx::Renderer::new(self.factory, frame_buffer, self.hidpi_factor).unwrap();
+		self.renderer = conrod_gfx::Renderer::new(self.factory, frame_buffer, self.hidpi_factor * self.scale).unwrap();
 		Ok(())
 	}
 
+	pub fn set_scale(
+		&mut self,
+		frame_buffer: &RenderTargetView<R, formats::ScreenColorFormat>,
+		scale: f64,
+	) -> Result<(), Error>
+	{
+		if (scale - self.scale).abs() < ::std::f64::EPSILON {
+			return Ok(());
+		}
+		self.scale = scale;
+		// the window shrinks as seen by the widgets
+		let dpi_factor = self.hidpi_factor * scale;
+		self.ui.handle_event(event::Input::Resize(
+			(f64::from(self.win_w) / dpi_factor) as u32,
+			(f64::from(self.win_h) / dpi_factor) as u32,
+		));
+		self.resize_to(frame_buffer)
+	}
+
 	fn load_font<L>(res: &L, map: &mut conrod::text::font::Map, key: &str) -> Result<conrod::text::font::Id, Error>
 	where L: ResourceLoader<u8> {
 		let font_bytes = res.load(key)?;
@@ -332,22 +1176,106 @@ where
 		Ok(id)
 	}
 
+	// the pictures of the quick load menu, uploaded the first time they show
+	fn load_thumbnails(&mut self, screen: &Screen) {
+		let entries = match *screen {
+			Screen::Main(ref frame_update) => match frame_update.quick_load {
+				Some(ref entries) => entries,
+				None => return,
+			},
+			_ => return,
+		};
+		for file in entries.iter().filter_map(|entry| entry.thumbnail.as_ref()) {
+			if self.thumbnails.contains_key(file) {
+				continue;
+			}
+			let loaded = match image::open(file) {
+				Ok(picture) => {
+					let picture = picture.to_rgba();
+					let (w, h) = picture.dimensions();
+					let (_, texture) = conrod_gfx::create_texture(self.factory, w, h, &picture.into_raw());
+					let image_id = self.image_map.insert((texture, (w, h)));
+					Some((image_id, f64::from(w) / f64::from(h.max(1))))
+				}
+				Err(e) => {
+					warn!("Could not load thumbnail {:?}: {}", file, e);
+					None
+				}
+			};
+			self.thumbnails.insert(file.clone(), loaded);
+		}
+	}
+
+	// uploaded again only when the genome in a slot changes, replacing the picture in the image map
+	fn load_previews(&mut self, screen: &Screen) {
+		let frame_update = match *screen {
+			Screen::Main(ref frame_update) => frame_update,
+			_ => return,
+		};
+		let candidates = frame_update.breeding.iter().flat_map(|workbench| workbench.candidates.iter());
+		for (slot, preview) in frame_update.preview.iter().map(|preview| &**preview).chain(candidates).enumerate() {
+			if self.previews.get(slot).map_or(false, |&(ref dna, _)| *dna == preview.dna) {
+				continue;
+			}
+			let image = &preview.image;
+			let rgba = image.pixels.chunks(3).flat_map(|rgb| rgb.iter().cloned().chain(Some(255))).collect::<Vec<_>>();
+			let (w, h) = (image.width as u32, image.height as u32);
+			let (_, texture) = conrod_gfx::create_texture(self.factory, w, h, &rgba);
+			if slot < self.previews.len() {
+				let image_id = self.previews[slot].1;
+				self.image_map.replace(image_id, (texture, (w, h)));
+				self.previews[slot] = (preview.dna.clone(), image_id);
+			} else {
+				let image_id = self.image_map.insert((texture, (w, h)));
+				self.previews.push((preview.dna.clone(), image_id));
+			}
+		}
+	}
+
 	pub fn update_and_draw_screen<C>(&mut self, screen: &Screen, encoder: &mut Encoder<R, C>)
 	where C: CommandBuffer<R> {
+		self.load_thumbnails(screen);
+		self.load_previews(screen);
 		let dims = (f32::from(self.win_w), f32::from(self.win_h));
 		let window_id = self.ui.window;
 		let mut app_events = Vec::with_capacity(1);
-		let widgets = screen.draw_widgets(&mut self.ui, window_id, &self.styles, &self.ids, &mut app_events);
+		let mut text_edits = Vec::new();
+		let widgets = screen.draw_widgets(
+			&mut self.ui,
+			window_id,
+			&self.styles,
+			&self.ids,
+			&self.thumbnails,
+			&self.previews,
+			&mut app_events,
+			&mut text_edits,
+		);
 		let primitives = widgets.draw();
 		self.renderer.fill(encoder, dims, primitives, &self.image_map);
 		self.renderer.draw(self.factory, encoder, &self.image_map);
 		self.app_events.extend(app_events);
+		self.text_edits.extend(text_edits);
 	}
 
-	pub fn push_event(&mut self, event: event::Input) { self.events.push(event); }
+	// converted from the window, which doesn't know about the scale
+	pub fn push_event(&mut self, event: event::Input) {
+		let s = self.scale;
+		self.events.push(match event {
+			event::Input::Resize(w, h) => event::Input::Resize((f64::from(w) / s) as u32, (f64::from(h) / s) as u32),
+			event::Input::Motion(conrod::input::Motion::MouseCursor { x, y }) => {
+				event::Input::Motion(conrod::input::Motion::MouseCursor { x: x / s, y: y / s })
+			}
+			event => event,
+		});
+	}
 
 	pub fn drain_app_events(&mut self) -> Drain<app::Event> { self.app_events.drain(..) }
 
+	pub fn drain_text_edits(&mut self) -> Drain<TextEdit> { self.text_edits.drain(..) }
+
+	// keystrokes meant for a text box should not reach the app
+	pub fn is_capturing_keyboard(&self) -> bool { self.ui.global_input().current.widget_capturing_keyboard.is_some() }
+
 	pub fn handle_events(&mut self) {
 		for event in &self.events {
 			self.ui.handle_event(event.clone())
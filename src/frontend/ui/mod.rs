@@ -28,6 +28,12 @@ pub enum Error {
 	ResourceLoader,
 }
 
+#[derive(Debug, Clone)]
+pub enum TextEdit {
+	Update(String),
+	Commit,
+}
+
 #[derive(Debug, Clone)]
 pub enum Screen {
 	#[allow(unused)]
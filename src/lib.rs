@@ -0,0 +1,77 @@
+//! The rust-oids ecosystem simulation, without the window, the renderer, the audio or the gamepad.
+//!
+//! A world is made from a gene pool, optionally loaded from a saved state, and stepped by a
+//! [`Simulation`](backend/simulation/struct.Simulation.html):
+//!
+//! ```no_run
+//! extern crate rust_oids;
+//!
+//! use rust_oids::backend::simulation::Simulation;
+//! use rust_oids::backend::systems::PhysicsSettings;
+//! use rust_oids::backend::world::{agent::AgentType, World};
+//! use rust_oids::constants::DEFAULT_MINION_GENE_POOL_FILE;
+//! use rust_oids::core::clock::Seconds;
+//! use rust_oids::core::resource::filesystem::ResourceLoaderBuilder;
+//! use std::path::Path;
+//!
+//! let resources = ResourceLoaderBuilder::new().add(Path::new("resources")).build();
+//! let world = World::new(&resources, DEFAULT_MINION_GENE_POOL_FILE);
+//! let mut simulation = Simulation::new(world, PhysicsSettings::default());
+//! for _ in 0..600 {
+//! 	simulation.step(Seconds::new(1. / 60.));
+//! }
+//! println!("{} minions", simulation.world().agents(AgentType::Minion).len());
+//! ```
+//!
+//! - [`backend`](backend/index.html): the world, its agents and the systems moving them
+//! - [`core`](core/index.html): clocks, geometry, maths and resources
+//! - [`constants`](constants/index.html): the tuning of the simulation
+//! - [`events`](events/index.html): what the systems react to, posted on the bus
+//! - [`ffi`](ffi/index.html): the same for C, and whatever can load a C library
+//! - `python`: a Python extension module, with the `python` feature
+#![allow(unknown_lints)]
+#![warn(clippy::all)]
+
+pub mod backend;
+pub mod constants;
+pub mod core;
+pub mod events;
+pub mod ffi;
+#[cfg(feature = "python")]
+pub mod python;
+
+#[macro_use]
+extern crate log;
+extern crate chrono;
+extern crate csv;
+
+#[macro_use]
+extern crate bitflags;
+extern crate cgmath;
+
+#[macro_use]
+extern crate serde_derive;
+
+extern crate serde;
+extern crate serde_json;
+extern crate toml;
+
+#[cfg(feature = "box2d")]
+extern crate wrapped2d;
+#[cfg(feature = "rapier")]
+extern crate rapier2d;
+
+extern crate itertools;
+extern crate num;
+extern crate num_traits;
+extern crate rand;
+
+#[macro_use]
+extern crate enum_primitive;
+
+extern crate rayon;
+
+#[cfg(feature = "python")]
+extern crate pyo3;
+
+extern crate rustc_serialize as serialize;
@@ -2,14 +2,16 @@
 #![warn(clippy::all)]
 
 mod app;
-mod backend;
-mod core;
 mod frontend;
 
+// the simulation is the library, this is the window on it
+extern crate rust_oids;
+use rust_oids::backend;
+use rust_oids::core;
+
 #[macro_use]
 extern crate log;
 extern crate chrono;
-extern crate csv;
 extern crate log4rs;
 
 #[macro_use]
@@ -22,8 +24,7 @@ extern crate serde_derive;
 
 extern crate serde;
 extern crate serde_json;
-
-extern crate wrapped2d;
+extern crate toml;
 
 #[macro_use]
 extern crate gfx;
@@ -37,7 +38,6 @@ extern crate pitch_calc;
 extern crate portaudio;
 extern crate sample;
 
-extern crate itertools;
 extern crate num;
 extern crate num_traits;
 extern crate rand;
@@ -45,17 +45,16 @@ extern crate rand;
 #[cfg(feature = "profiler")]
 extern crate cpuprofiler;
 
-#[macro_use]
-extern crate enum_primitive;
 extern crate conrod;
 
 extern crate ctrlc;
 extern crate getopts;
 
 extern crate gilrs;
+extern crate midir;
 
 extern crate dirs;
-extern crate rayon;
+extern crate fs2;
 #[cfg(linux)]
 extern crate thread_priority;
 
@@ -0,0 +1,166 @@
+//! Python bindings, with the `python` feature: a `rust_oids` extension module with a `World` to step,
+//! its minions and their genomes, and the statistics the app logs. Genomes travel as base64, as in the gene
+//! pool files and the saved states.
+
+use backend::obj::Transformable;
+use backend::raster;
+use backend::simulation::Simulation;
+use backend::systems::PhysicsSettings;
+use backend::world::agent::{Agent, AgentType};
+use backend::world::gen;
+use backend::world::persist::Serializer;
+use backend::world::World as SimulationWorld;
+use constants::DEFAULT_MINION_GENE_POOL_FILE;
+use core::clock::Seconds;
+use core::geometry::{Position, Transform};
+use core::resource::filesystem::ResourceLoaderBuilder;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use serialize::base64::{self, FromBase64, ToBase64};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A minion as it was when it was read, it doesn't follow the world afterwards.
+#[pyclass]
+#[derive(Clone)]
+pub struct Minion {
+	#[pyo3(get)]
+	pub id: usize,
+	#[pyo3(get)]
+	pub x: f32,
+	#[pyo3(get)]
+	pub y: f32,
+	#[pyo3(get)]
+	pub energy: f32,
+	#[pyo3(get)]
+	pub age: f64,
+	#[pyo3(get)]
+	pub gender: u8,
+	#[pyo3(get)]
+	pub segments: usize,
+	#[pyo3(get)]
+	pub dna: String,
+}
+
+impl<'a> From<&'a Agent> for Minion {
+	fn from(agent: &'a Agent) -> Self {
+		let position = agent.transform().position;
+		Minion {
+			id: agent.id(),
+			x: position.x,
+			y: position.y,
+			energy: agent.state.energy(),
+			age: agent.age().get(),
+			gender: agent.gender(),
+			segments: agent.segments().len(),
+			dna: agent.dna().to_base64(base64::STANDARD),
+		}
+	}
+}
+
+fn parse_dna(dna: &str) -> PyResult<gen::Dna> {
+	dna.from_base64()
+		.map(|dna| dna.into_boxed_slice())
+		.map_err(|e| PyValueError::new_err(format!("Invalid genome: {}", e)))
+}
+
+#[pyclass(unsendable)]
+pub struct World {
+	simulation: Simulation,
+}
+
+#[pymethods]
+impl World {
+	/// A new world with the default gene pool from `resources`, or the one saved in `snapshot`.
+	#[new]
+	#[args(snapshot = "None")]
+	fn new(resources: &str, snapshot: Option<&str>) -> PyResult<Self> {
+		let loader = ResourceLoaderBuilder::new().add(Path::new(resources)).build();
+		let mut world = SimulationWorld::new(&loader, DEFAULT_MINION_GENE_POOL_FILE);
+		if let Some(snapshot) = snapshot {
+			Serializer::load(Path::new(snapshot), &mut world)
+				.map_err(|e| PyIOError::new_err(format!("Could not load {}: {}", snapshot, e)))?;
+		}
+		Ok(World {
+			simulation: Simulation::new(world, PhysicsSettings::default()),
+		})
+	}
+
+	/// `steps` steps of `dt` seconds each, 60 to a second of simulated time by default.
+	#[args(steps = 1, dt = "1. / 60.")]
+	fn step(&mut self, steps: usize, dt: f64) {
+		for _ in 0..steps {
+			self.simulation.step(Seconds::new(dt));
+		}
+	}
+
+	#[getter]
+	fn ticks(&self) -> usize { self.simulation.ticks() }
+
+	#[getter]
+	fn seconds(&self) -> f64 { self.simulation.world().seconds().get() }
+
+	/// The counts and totals the telemetry log has, as a dict ready for a data frame row.
+	fn stats(&self) -> HashMap<&'static str, f64> {
+		let world = self.simulation.world();
+		let minions = world.agents(AgentType::Minion);
+		let mut stats = HashMap::new();
+		stats.insert("ticks", self.simulation.ticks() as f64);
+		stats.insert("seconds", world.seconds().get());
+		stats.insert("minions", minions.len() as f64);
+		stats.insert("spores", world.agents(AgentType::Spore).len() as f64);
+		stats.insert("resources", world.agents(AgentType::Resource).len() as f64);
+		stats.insert("extinctions", world.extinctions() as f64);
+		stats.insert("minion_energy", minions.values().map(|minion| f64::from(minion.state.energy())).sum());
+		stats
+	}
+
+	/// All the minions, by id.
+	fn minions(&self) -> Vec<Minion> {
+		let minions = self.simulation.world().agents(AgentType::Minion);
+		let mut ids = minions.keys().cloned().collect::<Vec<_>>();
+		ids.sort();
+		ids.into_iter().map(|id| Minion::from(&minions[&id])).collect()
+	}
+
+	fn minion(&self, id: usize) -> Option<Minion> {
+		self.simulation.world().agents(AgentType::Minion).get(&id).map(Minion::from)
+	}
+
+	/// A minion born of the genome at `x`, `y`, returns its id.
+	fn hatch(&mut self, x: f32, y: f32, dna: &str) -> PyResult<usize> {
+		let dna = parse_dna(dna)?;
+		Ok(self.simulation.hatch(Transform::new(Position::new(x, y), 0.), &dna))
+	}
+
+	/// `width` by `height` RGB bytes, top row first, of `span` world units around `x`, `y` or of all the world:
+	/// `PIL.Image.frombytes("RGB", (width, height), frame)` shows it.
+	#[args(width = 512, height = 512, x = "0.", y = "0.", span = "None")]
+	fn render(&self, py: Python, width: usize, height: usize, x: f32, y: f32, span: Option<f32>) -> PyObject {
+		let world = self.simulation.world();
+		let extent = match span {
+			Some(span) => raster::around(Position::new(x, y), span, width, height),
+			None => world.extent,
+		};
+		PyBytes::new(py, &raster::render(world, &extent, width, height).pixels).into()
+	}
+
+	/// Writes a snapshot that the constructor and the app can load.
+	fn save(&self, path: &str) -> PyResult<()> {
+		Serializer::save(Path::new(path), self.simulation.world())
+			.map_err(|e| PyIOError::new_err(format!("Could not save {}: {}", path, e)))
+	}
+}
+
+/// The share of the bits two genomes differ by, from 0 to 1.
+#[pyfunction]
+fn distance(a: &str, b: &str) -> PyResult<f32> { Ok(gen::distance(&parse_dna(a)?, &parse_dna(b)?)) }
+
+#[pymodule]
+fn rust_oids(_py: Python, module: &PyModule) -> PyResult<()> {
+	module.add_class::<World>()?;
+	module.add_class::<Minion>()?;
+	module.add_function(wrap_pyfunction!(distance, module)?)?;
+	Ok(())
+}
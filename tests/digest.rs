@@ -0,0 +1,61 @@
+extern crate rust_oids;
+
+use rust_oids::backend::harness::SimHarness;
+use rust_oids::backend::world::obstacle::Obstacle;
+use rust_oids::backend::world::World;
+use rust_oids::constants::DEFAULT_MINION_GENE_POOL_FILE;
+use rust_oids::core::geometry::{Motion, Position, Transform};
+use rust_oids::core::resource::filesystem::ResourceLoaderBuilder;
+use std::path::Path;
+
+const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/digests.json");
+const SEED: u32 = 0x5eed_0001;
+// ten seconds at 60Hz, long enough for the physics, the AI and the feeders to have moved everything
+const TICKS: usize = 600;
+
+fn harness() -> SimHarness {
+	let resources = ResourceLoaderBuilder::new().add(Path::new("resources")).build();
+	SimHarness::new(&resources, DEFAULT_MINION_GENE_POOL_FILE, SEED)
+}
+
+// the first genomes of the gene pool and of the resources, before either wraps around and starts mutating
+fn populate(world: &mut World) {
+	for i in 0..4 {
+		let angle = i as f32 * 1.5;
+		world.new_minion(Position::new(20. * angle.cos(), 20. * angle.sin()), Motion::default());
+		world.new_resource(Transform::from_position(Position::new(-10. + 5. * i as f32, 30.)), Motion::default());
+	}
+	world.obstacles_mut().push(Obstacle::wall(Position::new(-40., -40.), Position::new(40., -38.)));
+}
+
+// in one test, since a blessed run rewrites the whole file
+#[test]
+fn worlds_match_their_golden_digests() {
+	let golden = Path::new(GOLDEN);
+	let mut harness = harness();
+	harness.check_golden(golden, "empty", 0).unwrap();
+	harness.check_golden(golden, "empty-600", TICKS).unwrap();
+	let mut harness = self::harness();
+	populate(harness.world_mut());
+	harness.check_golden(golden, "populated", 0).unwrap();
+	harness.check_golden(golden, "populated-600", TICKS).unwrap();
+}
+
+#[test]
+fn the_same_seed_steps_the_same_way() {
+	let mut first = harness();
+	let mut second = harness();
+	populate(first.world_mut());
+	populate(second.world_mut());
+	first.step(TICKS);
+	second.step(TICKS);
+	assert_eq!(first.digest(), second.digest());
+}
+
+#[test]
+fn the_digest_follows_the_state() {
+	let mut harness = harness();
+	let empty = harness.digest();
+	populate(harness.world_mut());
+	assert_ne!(empty, harness.digest());
+}
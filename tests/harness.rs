@@ -0,0 +1,78 @@
+extern crate rust_oids;
+
+use rust_oids::backend::harness::SimHarness;
+use rust_oids::backend::world::agent::AgentType;
+use rust_oids::backend::world::segment;
+use rust_oids::backend::world::{Topology, World};
+use rust_oids::constants::{CHUNK_SIZE, DEFAULT_MINION_GENE_POOL_FILE};
+use rust_oids::core::geometry::Position;
+use rust_oids::core::resource::filesystem::ResourceLoaderBuilder;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// half a minute of simulated time at 60Hz
+const MAX_TICKS: usize = 1800;
+const SEED: u32 = 1;
+
+fn harness() -> SimHarness {
+	let resources = ResourceLoaderBuilder::new().add(Path::new("resources")).build();
+	SimHarness::new(&resources, DEFAULT_MINION_GENE_POOL_FILE, SEED)
+}
+
+// a minion has its mouth on a resource, and is about to eat it
+fn is_feeding(world: &World) -> bool {
+	let resources = world.agents(AgentType::Resource);
+	world.agents(AgentType::Minion).values().any(|minion| {
+		minion
+			.segments
+			.iter()
+			.filter(|segment| segment.flags.contains(segment::Flags::MOUTH))
+			.filter_map(|segment| segment.state.last_touched)
+			.any(|key| resources.contains_key(&{ key.agent_id }))
+	})
+}
+
+#[test]
+fn minions_hatch_on_the_first_step() {
+	let mut harness = harness();
+	let stats = harness.step(1);
+	assert_eq!(stats.ticks, 1);
+	assert!(stats.minions > 0);
+	assert!(stats.minion_energy > 0.);
+}
+
+#[test]
+fn feeders_sow_resources() {
+	let mut harness = harness();
+	assert!(harness.step_until(MAX_TICKS, |world| !world.agents(AgentType::Resource).is_empty()).is_some());
+}
+
+#[test]
+fn minions_reach_a_resource() {
+	let mut harness = harness();
+	let ticks = harness.step_until(MAX_TICKS, is_feeding);
+	assert!(ticks.is_some(), "no minion reached a resource in {} ticks: {:?}", MAX_TICKS, harness.stats());
+}
+
+#[test]
+fn leaving_an_unbounded_world_thaws_its_frozen_chunks() {
+	let chunks_dir = env::temp_dir().join("rust-oids-test-chunks");
+	let _ = fs::remove_dir_all(&chunks_dir);
+	let mut harness = harness();
+	harness.simulation_mut().set_chunks_dir(Some(chunks_dir.clone()));
+	harness.simulation_mut().set_topology(Topology::Unbounded).unwrap();
+	let populated = harness.step(2);
+	assert!(populated.resources > 0);
+
+	// far enough for everything around the origin to freeze
+	harness.simulation_mut().set_focus(Some((Position::new(CHUNK_SIZE * 100., 0.), 0.)));
+	harness.step(1);
+	assert!(!harness.world().chunks.frozen.is_empty());
+
+	harness.simulation_mut().set_topology(Topology::Bounded).unwrap();
+	assert!(harness.world().chunks.frozen.is_empty());
+	assert!(harness.world().chunks.far.is_empty());
+	assert!(harness.stats().resources >= populated.resources);
+	let _ = fs::remove_dir_all(&chunks_dir);
+}